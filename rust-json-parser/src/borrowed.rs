@@ -0,0 +1,471 @@
+//! Zero-copy tokenization over borrowed input.
+//!
+//! [`Tokenizer`](crate::tokenizer::Tokenizer) copies its input and
+//! allocates a `String` for every string and key it produces. A
+//! [`BorrowedTokenizer`](crate::borrowed::BorrowedTokenizer) works on the
+//! caller's `&str` instead and yields
+//! [`BorrowedToken`](crate::borrowed::BorrowedToken)s whose strings borrow
+//! from it, allocating only for strings that contain escape sequences.
+//! Consumers that inspect tokens without keeping them, such as validators,
+//! filters, and key scanners, tokenize large documents with almost no
+//! allocation.
+//!
+//! Tokens are produced one at a time, so nothing is buffered either. The
+//! tokens and errors are the same as the owning tokenizer's.
+
+use std::borrow::Cow;
+
+use crate::error::JsonError;
+use crate::tokenizer::{
+    Token, decode_unicode_escape, escape_char, is_number_byte, keyword_token, number_token,
+};
+use crate::value::JsonNumber;
+
+/// A [`Token`] whose string content borrows from the input when possible.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// use rust_json_parser::borrowed::{BorrowedToken, BorrowedTokenizer};
+///
+/// let tokens = BorrowedTokenizer::new(r#"["plain", "esc\naped"]"#).tokenize()?;
+/// assert!(matches!(&tokens[1], BorrowedToken::String(Cow::Borrowed("plain"))));
+/// assert!(matches!(&tokens[3], BorrowedToken::String(Cow::Owned(s)) if s == "esc\naped"));
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorrowedToken<'a> {
+    /// `{`
+    LeftBrace,
+    /// `}`
+    RightBrace,
+    /// `[`
+    LeftBracket,
+    /// `]`
+    RightBracket,
+    /// `,`
+    Comma,
+    /// `:`
+    Colon,
+    /// A string with escape sequences resolved. Borrowed from the input
+    /// unless it contained escapes.
+    String(Cow<'a, str>),
+    /// A number, as in [`Token::Number`].
+    Number(JsonNumber),
+    /// `true` or `false`.
+    Boolean(bool),
+    /// `null`.
+    Null,
+}
+
+impl BorrowedToken<'_> {
+    /// Converts into an owned [`Token`], copying borrowed strings.
+    pub fn into_owned(self) -> Token {
+        match self {
+            BorrowedToken::LeftBrace => Token::LeftBrace,
+            BorrowedToken::RightBrace => Token::RightBrace,
+            BorrowedToken::LeftBracket => Token::LeftBracket,
+            BorrowedToken::RightBracket => Token::RightBracket,
+            BorrowedToken::Comma => Token::Comma,
+            BorrowedToken::Colon => Token::Colon,
+            BorrowedToken::String(s) => Token::String(s.into_owned()),
+            BorrowedToken::Number(n) => Token::Number(n),
+            BorrowedToken::Boolean(b) => Token::Boolean(b),
+            BorrowedToken::Null => Token::Null,
+        }
+    }
+}
+
+impl From<Token> for BorrowedToken<'_> {
+    fn from(token: Token) -> Self {
+        match token {
+            Token::LeftBrace => BorrowedToken::LeftBrace,
+            Token::RightBrace => BorrowedToken::RightBrace,
+            Token::LeftBracket => BorrowedToken::LeftBracket,
+            Token::RightBracket => BorrowedToken::RightBracket,
+            Token::Comma => BorrowedToken::Comma,
+            Token::Colon => BorrowedToken::Colon,
+            Token::String(s) => BorrowedToken::String(Cow::Owned(s)),
+            Token::Number(n) => BorrowedToken::Number(n),
+            Token::Boolean(b) => BorrowedToken::Boolean(b),
+            Token::Null => BorrowedToken::Null,
+        }
+    }
+}
+
+/// Produces [`BorrowedToken`]s on demand from a borrowed string.
+///
+/// Tokens are read with [`next_token`](Self::next_token) or by iterating.
+/// Errors carry their line and column. After the first error, iteration
+/// ends.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::borrowed::{BorrowedToken, BorrowedTokenizer};
+///
+/// let input = String::from(r#"{"id": 7, "tags": ["a", "b"]}"#);
+/// let keys: Vec<&str> = BorrowedTokenizer::new(&input)
+///     .filter_map(|token| match token {
+///         Ok(BorrowedToken::String(s)) => Some(s),
+///         _ => None,
+///     })
+///     .map(|s| match s {
+///         std::borrow::Cow::Borrowed(s) => s,
+///         std::borrow::Cow::Owned(_) => unreachable!("no escapes"),
+///     })
+///     .collect();
+/// assert_eq!(keys, ["id", "tags", "a", "b"]);
+/// ```
+pub struct BorrowedTokenizer<'a> {
+    input: &'a str,
+    position: usize,
+    // Start of the last token returned, or of the end of input.
+    token_start: usize,
+    // Reused for decoding strings that contain escape sequences.
+    scratch: String,
+    preserve_raw_numbers: bool,
+    allow_comments: bool,
+    allow_single_quotes: bool,
+    finished: bool,
+}
+
+impl<'a> BorrowedTokenizer<'a> {
+    /// Creates a tokenizer over `input`.
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            position: 0,
+            token_start: 0,
+            scratch: String::new(),
+            preserve_raw_numbers: false,
+            allow_comments: false,
+            allow_single_quotes: false,
+            finished: false,
+        }
+    }
+
+    /// Keeps the exact source text of every number token, as
+    /// [`Tokenizer::preserve_raw_numbers`](crate::tokenizer::Tokenizer::preserve_raw_numbers)
+    /// does.
+    pub fn preserve_raw_numbers(mut self, preserve: bool) -> Self {
+        self.preserve_raw_numbers = preserve;
+        self
+    }
+
+    /// Skips `//` and `/* */` comments, as
+    /// [`Tokenizer::allow_comments`](crate::tokenizer::Tokenizer::allow_comments)
+    /// does.
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.allow_comments = allow;
+        self
+    }
+
+    /// Accepts `'single-quoted'` strings, as
+    /// [`Tokenizer::allow_single_quotes`](crate::tokenizer::Tokenizer::allow_single_quotes)
+    /// does.
+    pub fn allow_single_quotes(mut self, allow: bool) -> Self {
+        self.allow_single_quotes = allow;
+        self
+    }
+
+    /// Returns the byte offset of the next unread input byte.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the byte offset where the last token returned started, or
+    /// the length of the input once the end has been reached.
+    pub fn token_start(&self) -> usize {
+        self.token_start
+    }
+
+    /// Tokenizes the rest of the input into a vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError`] for the same input the owning
+    /// [`Tokenizer`](crate::tokenizer::Tokenizer) rejects.
+    pub fn tokenize(mut self) -> Result<Vec<BorrowedToken<'a>>, JsonError> {
+        let mut tokens = Vec::with_capacity(self.input.len() / 3);
+        while let Some(token) = self.next_token()? {
+            tokens.push(token);
+        }
+        Ok(tokens)
+    }
+
+    /// Reads the next token, or `None` at the end of the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError`] if the input is not valid JSON at the lexical
+    /// level.
+    pub fn next_token(&mut self) -> Result<Option<BorrowedToken<'a>>, JsonError> {
+        if self.finished {
+            return Ok(None);
+        }
+        let result = self.scan();
+        if !matches!(result, Ok(Some(_))) {
+            self.finished = true;
+        }
+        result.map_err(|e| e.locate(self.input))
+    }
+
+    fn scan(&mut self) -> Result<Option<BorrowedToken<'a>>, JsonError> {
+        let bytes = self.input.as_bytes();
+        loop {
+            self.token_start = self.position;
+            let Some(&b) = bytes.get(self.position) else {
+                return Ok(None);
+            };
+            let structural = match b {
+                b'{' => BorrowedToken::LeftBrace,
+                b'}' => BorrowedToken::RightBrace,
+                b'[' => BorrowedToken::LeftBracket,
+                b']' => BorrowedToken::RightBracket,
+                b':' => BorrowedToken::Colon,
+                b',' => BorrowedToken::Comma,
+                b' ' | b'\n' | b'\t' | b'\r' => {
+                    self.position += 1;
+                    continue;
+                }
+                b'/' if self.allow_comments => {
+                    self.skip_comment()?;
+                    continue;
+                }
+                b'"' => return self.parse_string(b'"').map(Some),
+                b'\'' if self.allow_single_quotes => return self.parse_string(b'\'').map(Some),
+                b't' | b'f' | b'n' => return self.parse_keyword().map(Some),
+                b'0'..=b'9' | b'-' | b'.' => return self.parse_number().map(Some),
+                other => {
+                    return Err(JsonError::UnexpectedToken {
+                        expected: "valid JSON token".to_string(),
+                        found: (other as char).to_string(),
+                        position: self.position,
+                        line: 0,
+                        column: 0,
+                    });
+                }
+            };
+            self.position += 1;
+            return Ok(Some(structural));
+        }
+    }
+
+    fn skip_comment(&mut self) -> Result<(), JsonError> {
+        let bytes = self.input.as_bytes();
+        let comment_start = self.position;
+        let rest = &bytes[comment_start..];
+        match rest.get(1) {
+            Some(b'/') => {
+                let len = rest
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(rest.len(), |i| i + 1);
+                self.position += len;
+                Ok(())
+            }
+            Some(b'*') => match rest[2..].windows(2).position(|w| w == b"*/") {
+                Some(i) => {
+                    self.position += i + 4;
+                    Ok(())
+                }
+                None => Err(JsonError::UnexpectedEndOfInput {
+                    expected: "end of comment".to_string(),
+                    position: comment_start,
+                    line: 0,
+                    column: 0,
+                }),
+            },
+            Some(&other) => Err(JsonError::UnexpectedToken {
+                expected: "comment".to_string(),
+                found: format!("/{}", other as char),
+                position: comment_start,
+                line: 0,
+                column: 0,
+            }),
+            None => Err(JsonError::UnexpectedEndOfInput {
+                expected: "comment".to_string(),
+                position: comment_start,
+                line: 0,
+                column: 0,
+            }),
+        }
+    }
+
+    // `quote` is the delimiter: `"`, or `'` when single quotes are allowed.
+    fn parse_string(&mut self, quote: u8) -> Result<BorrowedToken<'a>, JsonError> {
+        let input = self.input;
+        let bytes = input.as_bytes();
+        let string_start = self.position;
+        self.position += 1;
+        self.scratch.clear();
+        let mut escaped = false;
+        let mut seg_start = self.position;
+        loop {
+            match bytes.get(self.position) {
+                Some(&b) if b == quote => {
+                    let segment = &input[seg_start..self.position];
+                    self.position += 1;
+                    let s = if escaped {
+                        self.scratch.push_str(segment);
+                        Cow::Owned(self.scratch.as_str().to_owned())
+                    } else {
+                        Cow::Borrowed(segment)
+                    };
+                    return Ok(BorrowedToken::String(s));
+                }
+                Some(b'\\') => {
+                    self.scratch.push_str(&input[seg_start..self.position]);
+                    escaped = true;
+                    self.position += 1;
+                    let ch = self.parse_escape_sequence(quote)?;
+                    self.scratch.push(ch);
+                    seg_start = self.position;
+                }
+                Some(_) => self.position += 1,
+                None => {
+                    return Err(JsonError::UnexpectedEndOfInput {
+                        expected: "closing quote".to_string(),
+                        position: string_start,
+                        line: 0,
+                        column: 0,
+                    });
+                }
+            }
+        }
+    }
+
+    fn parse_escape_sequence(&mut self, quote: u8) -> Result<char, JsonError> {
+        match self.input.as_bytes().get(self.position) {
+            Some(b'u') => {
+                let (ch, end) = decode_unicode_escape(self.input, self.position + 1)?;
+                self.position = end;
+                Ok(ch)
+            }
+            Some(b'\'') if quote == b'\'' => {
+                self.position += 1;
+                Ok('\'')
+            }
+            Some(&b) => {
+                let ch = escape_char(b, self.position)?;
+                self.position += 1;
+                Ok(ch)
+            }
+            None => Err(JsonError::UnexpectedEndOfInput {
+                expected: "escape character".to_string(),
+                position: self.position,
+                line: 0,
+                column: 0,
+            }),
+        }
+    }
+
+    fn parse_keyword(&mut self) -> Result<BorrowedToken<'a>, JsonError> {
+        let start = self.position;
+        let bytes = self.input.as_bytes();
+        while bytes.get(self.position).is_some_and(u8::is_ascii_lowercase) {
+            self.position += 1;
+        }
+        keyword_token(&self.input[start..self.position], start).map(BorrowedToken::from)
+    }
+
+    fn parse_number(&mut self) -> Result<BorrowedToken<'a>, JsonError> {
+        let start = self.position;
+        let bytes = self.input.as_bytes();
+        let mut prev = 0u8;
+        while let Some(&b) = bytes
+            .get(self.position)
+            .filter(|&&b| is_number_byte(b, prev))
+        {
+            self.position += 1;
+            prev = b;
+        }
+        number_token(
+            &self.input[start..self.position],
+            start,
+            self.preserve_raw_numbers,
+        )
+        .map(BorrowedToken::Number)
+    }
+}
+
+impl<'a> Iterator for BorrowedTokenizer<'a> {
+    type Item = Result<BorrowedToken<'a>, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_token().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::Tokenizer;
+
+    fn owned(input: &str) -> Result<Vec<Token>, JsonError> {
+        Tokenizer::new(input)
+            .allow_comments(true)
+            .allow_single_quotes(true)
+            .tokenize()
+    }
+
+    fn borrowed(input: &str) -> Result<Vec<Token>, JsonError> {
+        BorrowedTokenizer::new(input)
+            .allow_comments(true)
+            .allow_single_quotes(true)
+            .map(|token| token.map(BorrowedToken::into_owned))
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_owning_tokenizer() {
+        for input in [
+            r#"{"a": [1, -2.5e3, true, false, null], "b\"": "\u00e9\n"}"#,
+            "[1, // one\n /* two */ 'it\\'s']",
+            "",
+            "[\"unterminated",
+            "[1, @]",
+            "\"bad \\x escape\"",
+            "\"\\u12\"",
+            "\"\\u00é\"",
+            r#""\ud83d\ude00 \uD834\uDD1E""#,
+            r#""\ud83d""#,
+            r#""\ude00""#,
+            r#""\ud83d\u0041""#,
+            r#""\ud83d\uzz""#,
+            "nul",
+            "01",
+            "[1 /* open",
+            "/x",
+        ] {
+            assert_eq!(borrowed(input), owned(input), "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_borrows_unescaped_strings() {
+        let input = r#"{"key": "value", "esc": "a\tb"}"#;
+        let tokens = BorrowedTokenizer::new(input).tokenize().unwrap();
+        let strings: Vec<_> = tokens
+            .iter()
+            .filter_map(|token| match token {
+                BorrowedToken::String(s) => Some(matches!(s, Cow::Borrowed(_))),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(strings, [true, true, true, false]);
+    }
+
+    #[test]
+    fn test_positions_and_errors_end_iteration() {
+        let mut tokenizer = BorrowedTokenizer::new(" [x]");
+        assert_eq!(tokenizer.next_token(), Ok(Some(BorrowedToken::LeftBracket)));
+        assert_eq!((tokenizer.token_start(), tokenizer.position()), (1, 2));
+        let err = tokenizer.next_token().unwrap_err();
+        assert_eq!((err.position(), err.line(), err.column()), (2, 1, 3));
+        assert_eq!(tokenizer.next_token(), Ok(None));
+    }
+}
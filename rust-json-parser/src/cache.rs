@@ -0,0 +1,135 @@
+//! Reusing parsed subtrees that repeat across documents.
+//!
+//! Record feeds often embed the same large blob in every record: a
+//! `"schema"` object, a shared configuration, a fixed envelope. A
+//! [`SubtreeCache`](crate::cache::SubtreeCache) installed on a
+//! [`JsonParser`](crate::parser::JsonParser) is consulted with the exact
+//! source text of each array and object before it is built; when the cache
+//! returns a value, the parser uses it and skips building that subtree.
+//! [`HashCache`](crate::cache::HashCache) is a ready-made cache keyed by
+//! the text itself.
+
+use std::collections::HashMap;
+
+use crate::value::JsonValue;
+
+/// A store of parsed subtrees keyed by their source text.
+///
+/// The parser calls [`get`](Self::get) with the text of every array and
+/// object, from the opening bracket to the closing one inclusive, and
+/// [`insert`](Self::insert) with the text and value of each one it had
+/// to build itself. Keys are compared as exact text: `{"a":1}` and
+/// `{"a": 1}` are different subtrees.
+///
+/// The parser trusts what `get` returns. A cache must only return values
+/// that were inserted for the same text, or the parse result will not
+/// match the input.
+pub trait SubtreeCache {
+    /// Returns the value previously parsed from `raw`, if cached.
+    fn get(&mut self, raw: &str) -> Option<JsonValue>;
+
+    /// Offers a subtree the parser just built from `raw`. Implementations
+    /// may ignore it.
+    fn insert(&mut self, raw: &str, value: &JsonValue);
+}
+
+/// A [`SubtreeCache`] backed by a hash map from source text to value.
+///
+/// Subtrees shorter than [`min_len`](Self::min_len) bytes are neither
+/// looked up nor stored: building a small container is cheaper than
+/// hashing its text. The cache grows without bound; call
+/// [`clear`](Self::clear) between batches if the repeated blobs change.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::cache::HashCache;
+/// use rust_json_parser::parser::JsonParser;
+///
+/// let mut parser = JsonParser::new().subtree_cache(HashCache::new().min_len(16));
+/// for id in 1..=3 {
+///     let record = format!(r#"{{"id": {id}, "schema": {{"version": 2, "fields": ["id"]}}}}"#);
+///     let value = parser.parse(&record)?;
+///     assert_eq!(value.get("schema").and_then(|s| s.get("version")).and_then(|v| v.as_i64()), Some(2));
+/// }
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HashCache {
+    entries: HashMap<Box<str>, JsonValue>,
+    min_len: usize,
+    hits: usize,
+}
+
+impl HashCache {
+    /// Creates an empty cache that considers subtrees of any length.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the length in bytes below which subtrees are not cached.
+    pub fn min_len(mut self, len: usize) -> Self {
+        self.min_len = len;
+        self
+    }
+
+    /// Returns the number of cached subtrees.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns how many lookups found a cached value.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Removes every cached subtree and resets the hit count.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.hits = 0;
+    }
+}
+
+impl SubtreeCache for HashCache {
+    fn get(&mut self, raw: &str) -> Option<JsonValue> {
+        if raw.len() < self.min_len {
+            return None;
+        }
+        let value = self.entries.get(raw).cloned();
+        self.hits += usize::from(value.is_some());
+        value
+    }
+
+    fn insert(&mut self, raw: &str, value: &JsonValue) {
+        if raw.len() >= self.min_len {
+            self.entries.insert(raw.into(), value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    #[test]
+    fn test_min_len_and_hits() {
+        let mut cache = HashCache::new().min_len(4);
+        cache.insert("[1]", &json!([1]));
+        cache.insert("[1,2]", &json!([1, 2]));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get("[1]"), None);
+        assert_eq!(cache.get("[1,2]"), Some(json!([1, 2])));
+        assert_eq!(cache.get("[1, 2]"), None);
+        assert_eq!(cache.hits(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.hits(), 0);
+    }
+}
@@ -0,0 +1,213 @@
+//! The JSON Canonicalization Scheme (RFC 8785).
+//!
+//! Hashing or signing a JSON payload needs every party to produce the same
+//! bytes for the same data.
+//! [`JsonValue::to_canonical_string`](crate::value::JsonValue::to_canonical_string)
+//! writes the canonical form defined by RFC 8785: no whitespace, object
+//! members sorted by the UTF-16 code units of their keys, numbers
+//! formatted like ECMAScript's `Number.prototype.toString`, and only the
+//! escapes JSON requires.
+
+use std::error;
+use std::fmt;
+use std::fmt::Write;
+
+use crate::path::JsonPath;
+use crate::value::JsonValue;
+
+/// A value that has no canonical form: a NaN or infinite number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalError {
+    /// Where the number is.
+    pub path: JsonPath,
+}
+
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Non-finite number at {:?} has no canonical form",
+            self.path.to_pointer()
+        )
+    }
+}
+
+impl error::Error for CanonicalError {}
+
+pub(crate) fn write_canonical(
+    out: &mut String,
+    value: &JsonValue,
+    path: &mut JsonPath,
+) -> Result<(), CanonicalError> {
+    match value {
+        JsonValue::Null => out.push_str("null"),
+        JsonValue::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+        JsonValue::Number(n) => {
+            let f = n.as_f64();
+            if !f.is_finite() {
+                return Err(CanonicalError { path: path.clone() });
+            }
+            write_number(out, f);
+        }
+        JsonValue::String(s) => write_string(out, s),
+        JsonValue::Array(elements) => {
+            out.push('[');
+            for (i, element) in elements.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                path.push(i);
+                write_canonical(out, element, path)?;
+                path.pop();
+            }
+            out.push(']');
+        }
+        JsonValue::Object(map) => {
+            let mut members: Vec<_> = map.iter().collect();
+            members.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+            out.push('{');
+            for (i, (key, member)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                path.push(key.as_str());
+                write_canonical(out, member, path)?;
+                path.pop();
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+// ECMAScript Number::toString: the shortest digits that round-trip,
+// written positionally for decimal exponents in [-6, 21) and in
+// exponential notation otherwise.
+fn write_number(out: &mut String, f: f64) {
+    if f == 0.0 {
+        out.push('0');
+        return;
+    }
+    if f < 0.0 {
+        out.push('-');
+    }
+    // `{:e}` gives the shortest round-trip digits as `d.ddde<exp>`.
+    let scientific = format!("{:e}", f.abs());
+    let (mantissa, exponent) = scientific.split_once('e').expect("exponent");
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    // Position of the decimal point relative to the start of `digits`.
+    let n = exponent.parse::<i32>().expect("integer exponent") + 1;
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat_n('0', (n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat_n('0', (-n) as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        let _ = write!(out, "e{}{}", if n > 0 { "+" } else { "-" }, (n - 1).abs());
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn number(f: f64) -> String {
+        let mut out = String::new();
+        write_number(&mut out, f);
+        out
+    }
+
+    #[test]
+    fn test_number_formatting_matches_rfc_examples() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (4.50, "4.5"),
+            (2e-3, "0.002"),
+            (1e-6, "0.000001"),
+            (1e-7, "1e-7"),
+            (0.000000000000000000000000001, "1e-27"),
+            (333333333.3333333, "333333333.3333333"),
+            (1e30, "1e+30"),
+            (1e21, "1e+21"),
+            (295147905179352830000.0, "295147905179352830000"),
+            (9007199254740992.0, "9007199254740992"),
+            (-1.5, "-1.5"),
+            (5e-324, "5e-324"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+            (-123.456e-10, "-1.23456e-8"),
+        ];
+        for (f, expected) in cases {
+            assert_eq!(number(f), expected, "{:e}", f);
+        }
+    }
+
+    #[test]
+    fn test_keys_sort_by_utf16_code_units() {
+        let value = parse_json(
+            r#"{"\u20ac": "Euro Sign", "\r": "Carriage Return", "\ufb33": "Hebrew Letter Dalet With Dagesh",
+                "1": "One", "\ud83d\ude00": "Emoji: Grinning Face", "\u0080": "Control",
+                "\u00f6": "Latin Small Letter O With Diaeresis"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            value.to_canonical_string().unwrap(),
+            concat!(
+                r#"{"\r":"Carriage Return","1":"One","#,
+                "\"\u{80}\":\"Control\",",
+                r#""ö":"Latin Small Letter O With Diaeresis","€":"Euro Sign","#,
+                r#""😀":"Emoji: Grinning Face","#,
+                "\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}",
+            )
+        );
+    }
+
+    #[test]
+    fn test_escapes_and_errors() {
+        let value = JsonValue::from("\u{1}\u{8}\u{c}\u{7f}é/\"\\");
+        assert_eq!(
+            value.to_canonical_string().unwrap(),
+            "\"\\u0001\\b\\f\u{7f}é/\\\"\\\\\""
+        );
+        let value = JsonValue::Array(vec![JsonValue::Null, JsonValue::from(f64::INFINITY)]);
+        assert_eq!(
+            value.to_canonical_string().unwrap_err().to_string(),
+            r#"Non-finite number at "/1" has no canonical form"#
+        );
+    }
+}
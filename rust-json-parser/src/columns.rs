@@ -0,0 +1,286 @@
+//! Converting between arrays of records and objects of columns.
+//!
+//! Charting and statistics code usually wants one array per field rather
+//! than one object per record: `[{"a": 1, "b": 2}, {"a": 3, "b": 4}]`
+//! becomes `{"a": [1, 3], "b": [2, 4]}`.
+//! [`to_columns`](crate::columns::to_columns) and
+//! [`from_columns`](crate::columns::from_columns) convert in each
+//! direction, and both insist that the input is uniform: every record has
+//! the same keys, or every column has the same length.
+
+use std::error;
+use std::fmt;
+
+use crate::map::JsonMap;
+use crate::path::JsonPath;
+use crate::value::JsonValue;
+
+/// Turns an array of objects into an object of equally long arrays.
+///
+/// Columns are ordered like the first record's keys; later records may
+/// list the same keys in any order. An empty array gives an empty object.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::columns::{from_columns, to_columns};
+/// use rust_json_parser::parser::parse_json;
+/// use rust_json_parser::value::JsonValue;
+///
+/// let rows = parse_json(r#"[{"x": 1, "y": "a"}, {"y": "b", "x": 2}]"#)?;
+/// let columns = JsonValue::from(to_columns(&rows).unwrap());
+/// assert_eq!(columns.to_string(), r#"{"x":[1,2],"y":["a","b"]}"#);
+///
+/// let back = JsonValue::from(from_columns(&columns).unwrap());
+/// assert_eq!(back.to_string(), r#"[{"x":1,"y":"a"},{"x":2,"y":"b"}]"#);
+///
+/// let ragged = parse_json(r#"[{"x": 1}, {"x": 2, "z": 3}]"#)?;
+/// assert_eq!(
+///     to_columns(&ragged).unwrap_err().to_string(),
+///     r#"Record 1 has key "z", which record 0 lacks"#
+/// );
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`ColumnsError::TypeMismatch`] if `rows` is not an array of
+/// objects, and [`ColumnsError::MissingKey`] or
+/// [`ColumnsError::UnexpectedKey`] at the first record whose keys differ
+/// from the first record's.
+pub fn to_columns(rows: &JsonValue) -> Result<JsonMap, ColumnsError> {
+    let rows = rows
+        .as_array()
+        .ok_or_else(|| mismatch(JsonPath::root(), "array", rows))?;
+    let mut columns = JsonMap::new();
+    for (i, row) in rows.iter().enumerate() {
+        let members = row
+            .as_object()
+            .ok_or_else(|| mismatch(JsonPath::root().join(i), "object", row))?;
+        if i == 0 {
+            for key in members.keys() {
+                columns.insert(
+                    key.clone(),
+                    JsonValue::Array(Vec::with_capacity(rows.len())),
+                );
+            }
+        }
+        for (key, value) in members.iter() {
+            match columns.get_mut(key).and_then(JsonValue::as_array_mut) {
+                Some(column) => column.push(value.clone()),
+                None => {
+                    return Err(ColumnsError::UnexpectedKey {
+                        record: i,
+                        key: key.clone(),
+                    });
+                }
+            }
+        }
+        // Every key was found, so a shorter record must be missing one.
+        if members.len() < columns.len() {
+            let key = columns
+                .keys()
+                .find(|key| !members.contains_key(key))
+                .expect("a column absent from the record");
+            return Err(ColumnsError::MissingKey {
+                record: i,
+                key: key.clone(),
+            });
+        }
+    }
+    Ok(columns)
+}
+
+/// Turns an object of equally long arrays into an array of objects, the
+/// inverse of [`to_columns`].
+///
+/// Each record lists its keys in column order. An object with no members
+/// gives an empty array.
+///
+/// # Errors
+///
+/// Returns [`ColumnsError::TypeMismatch`] if `columns` is not an object of
+/// arrays, and [`ColumnsError::LengthMismatch`] at the first column whose
+/// length differs from the first column's.
+pub fn from_columns(columns: &JsonValue) -> Result<Vec<JsonValue>, ColumnsError> {
+    let members = columns
+        .as_object()
+        .ok_or_else(|| mismatch(JsonPath::root(), "object", columns))?;
+    let mut arrays: Vec<(&String, &Vec<JsonValue>)> = Vec::with_capacity(members.len());
+    for (key, column) in members.iter() {
+        let column = column
+            .as_array()
+            .ok_or_else(|| mismatch(JsonPath::root().join(key.as_str()), "array", column))?;
+        if let Some(&(first, first_column)) = arrays.first()
+            && column.len() != first_column.len()
+        {
+            return Err(ColumnsError::LengthMismatch {
+                column: key.clone(),
+                expected: first_column.len(),
+                found: column.len(),
+                first: first.clone(),
+            });
+        }
+        arrays.push((key, column));
+    }
+    let len = arrays.first().map_or(0, |(_, column)| column.len());
+    Ok((0..len)
+        .map(|i| {
+            let mut record = JsonMap::with_capacity(arrays.len());
+            for (key, column) in &arrays {
+                record.insert((*key).clone(), column[i].clone());
+            }
+            JsonValue::Object(record)
+        })
+        .collect())
+}
+
+fn mismatch(path: JsonPath, expected: &'static str, value: &JsonValue) -> ColumnsError {
+    ColumnsError::TypeMismatch {
+        path,
+        expected,
+        found: value.type_name(),
+    }
+}
+
+/// Input to [`to_columns`] or [`from_columns`] that is not uniform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnsError {
+    /// The input, a record, or a column has the wrong type.
+    TypeMismatch {
+        /// Where the value is.
+        path: JsonPath,
+        /// The type required there.
+        expected: &'static str,
+        /// The JSON type that was found.
+        found: &'static str,
+    },
+    /// A record lacks a key that the first record has.
+    MissingKey {
+        /// The index of the record.
+        record: usize,
+        /// The missing key.
+        key: String,
+    },
+    /// A record has a key that the first record lacks.
+    UnexpectedKey {
+        /// The index of the record.
+        record: usize,
+        /// The extra key.
+        key: String,
+    },
+    /// A column's length differs from the first column's.
+    LengthMismatch {
+        /// The column with the wrong length.
+        column: String,
+        /// The length of the first column.
+        expected: usize,
+        /// The length of `column`.
+        found: usize,
+        /// The name of the first column.
+        first: String,
+    },
+}
+
+impl fmt::Display for ColumnsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnsError::TypeMismatch {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Expected {} at {:?}, found {}",
+                expected,
+                path.to_pointer(),
+                found
+            ),
+            ColumnsError::MissingKey { record, key } => {
+                write!(
+                    f,
+                    "Record {} lacks key {:?}, which record 0 has",
+                    record, key
+                )
+            }
+            ColumnsError::UnexpectedKey { record, key } => {
+                write!(
+                    f,
+                    "Record {} has key {:?}, which record 0 lacks",
+                    record, key
+                )
+            }
+            ColumnsError::LengthMismatch {
+                column,
+                expected,
+                found,
+                first,
+            } => write!(
+                f,
+                "Column {:?} has {} values but column {:?} has {}",
+                column, found, first, expected
+            ),
+        }
+    }
+}
+
+impl error::Error for ColumnsError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    #[test]
+    fn test_round_trip_and_empty_inputs() {
+        let rows = json!([{"a": 1, "b": [true]}, {"b": null, "a": 2}, {"a": 3, "b": "x"}]);
+        let columns = JsonValue::from(to_columns(&rows).unwrap());
+        assert_eq!(columns, json!({"a": [1, 2, 3], "b": [[true], null, "x"]}));
+        assert_eq!(JsonValue::from(from_columns(&columns).unwrap()), rows);
+
+        assert!(to_columns(&json!([])).unwrap().is_empty());
+        assert!(from_columns(&json!({})).unwrap().is_empty());
+        assert!(from_columns(&json!({"a": [], "b": []})).unwrap().is_empty());
+        assert_eq!(
+            JsonValue::from(to_columns(&json!([{}, {}])).unwrap()),
+            json!({})
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_uniform_input() {
+        let err = |rows: JsonValue| to_columns(&rows).unwrap_err().to_string();
+        assert_eq!(err(json!({})), r#"Expected array at "", found object"#);
+        assert_eq!(
+            err(json!([{"a": 1}, 2])),
+            r#"Expected object at "/1", found number"#
+        );
+        assert_eq!(
+            err(json!([{"a": 1, "b": 2}, {"b": 3}])),
+            r#"Record 1 lacks key "a", which record 0 has"#
+        );
+        assert_eq!(
+            err(json!([{"a": 1}, {"a": 2}, {"a": 3, "c": 4}])),
+            r#"Record 2 has key "c", which record 0 lacks"#
+        );
+
+        let err = |columns: JsonValue| from_columns(&columns).unwrap_err();
+        assert_eq!(
+            err(json!({"a": [1], "b/c": 2})).to_string(),
+            r#"Expected array at "/b~1c", found number"#
+        );
+        assert_eq!(
+            err(json!({"a": [1, 2], "b": [1, 2], "c": [1]})),
+            ColumnsError::LengthMismatch {
+                column: "c".to_string(),
+                expected: 2,
+                found: 1,
+                first: "a".to_string(),
+            }
+        );
+        assert_eq!(
+            err(json!({"a": [1, 2], "b": [1]})).to_string(),
+            r#"Column "b" has 1 values but column "a" has 2"#
+        );
+    }
+}
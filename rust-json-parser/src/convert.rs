@@ -0,0 +1,427 @@
+//! Derive-free conversions between JSON values and Rust types.
+//!
+//! [`FromJson`](crate::convert::FromJson) decodes a borrowed
+//! [`JsonValue`](crate::value::JsonValue) into a Rust type and
+//! [`ToJson`](crate::convert::ToJson) encodes one back. Both are
+//! implemented for primitives, `Option`, `Vec`, maps with string keys and
+//! tuples, so decoding a struct by hand takes one
+//! [`field`](crate::convert::field) call per member. Errors are
+//! [`AccessError`](crate::error::AccessError)s carrying the path to the
+//! offending value.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+use crate::error::AccessError;
+use crate::map::JsonMap;
+use crate::path::{JsonPath, Segment};
+use crate::value::JsonValue;
+
+/// Types that can be decoded from a [`JsonValue`].
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::convert::{FromJson, ToJson, field};
+/// use rust_json_parser::error::AccessError;
+/// use rust_json_parser::json;
+/// use rust_json_parser::value::JsonValue;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct User {
+///     name: String,
+///     age: u8,
+///     email: Option<String>,
+/// }
+///
+/// impl FromJson for User {
+///     fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+///         Ok(User {
+///             name: field(value, "name")?,
+///             age: field(value, "age")?,
+///             email: field(value, "email")?,
+///         })
+///     }
+/// }
+///
+/// impl ToJson for User {
+///     fn to_json(&self) -> JsonValue {
+///         json!({"name": self.name.to_json(), "age": self.age, "email": self.email.to_json()})
+///     }
+/// }
+///
+/// let users = Vec::<User>::from_json(&json!([{"name": "Alice", "age": 30}]))?;
+/// assert_eq!(users[0].email, None);
+/// assert_eq!(users.to_json().to_string(), r#"[{"name":"Alice","age":30,"email":null}]"#);
+///
+/// let err = Vec::<User>::from_json(&json!([{"name": "Bob", "age": 300}])).unwrap_err();
+/// assert_eq!(err.to_string(), r#"Expected integer at "0.age", found number"#);
+/// # Ok::<(), AccessError>(())
+/// ```
+pub trait FromJson: Sized {
+    /// Decodes `value`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessError::TypeMismatch`] if `value` or a value inside
+    /// it has the wrong type or is out of range, and
+    /// [`AccessError::Missing`] if a required member is absent. The path
+    /// is relative to `value`.
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError>;
+}
+
+/// Types that can be encoded as a [`JsonValue`].
+///
+/// See [`FromJson`] for an example.
+pub trait ToJson {
+    /// Encodes `self`.
+    fn to_json(&self) -> JsonValue;
+}
+
+/// Decodes the member `key` of `object`, for use in [`FromJson`]
+/// implementations.
+///
+/// A missing member (or a non-object) decodes as `null`, so `Option`
+/// fields are optional and everything else reports
+/// [`AccessError::Missing`].
+///
+/// # Errors
+///
+/// Returns [`AccessError`] with a path starting at `key`.
+pub fn field<T: FromJson>(object: &JsonValue, key: &str) -> Result<T, AccessError> {
+    match object.get(key) {
+        Some(value) => T::from_json(value).map_err(|e| e.within(key)),
+        None => T::from_json(&JsonValue::Null).map_err(|_| AccessError::Missing {
+            path: JsonPath::root().join(key),
+        }),
+    }
+}
+
+fn mismatch(expected: &'static str, value: &JsonValue) -> AccessError {
+    AccessError::TypeMismatch {
+        path: JsonPath::root(),
+        expected,
+        found: value.type_name(),
+    }
+}
+
+macro_rules! impl_scalar {
+    ($($t:ty),*) => {
+        $(
+            impl ToJson for $t {
+                fn to_json(&self) -> JsonValue {
+                    JsonValue::from(*self)
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar!(
+    bool, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize
+);
+
+// Integers follow `JsonValue::try_into_int`: only numbers stored as
+// integers that fit in the target type decode.
+macro_rules! impl_from_json_int {
+    ($($t:ty),*) => {
+        $(
+            impl FromJson for $t {
+                fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+                    value.try_into_int().map_err(|_| mismatch("integer", value))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_json_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl FromJson for bool {
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+        value.as_bool().ok_or_else(|| mismatch("boolean", value))
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+        value.as_f64().ok_or_else(|| mismatch("number", value))
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| mismatch("string", value))
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::from(self.as_str())
+    }
+}
+
+impl FromJson for f32 {
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+        f64::from_json(value).map(|f| f as f32)
+    }
+}
+
+impl ToJson for f32 {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::from(f64::from(*self))
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::from(self)
+    }
+}
+
+impl FromJson for JsonValue {
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for JsonValue {
+    fn to_json(&self) -> JsonValue {
+        self.clone()
+    }
+}
+
+impl FromJson for JsonMap {
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+        value
+            .as_object()
+            .cloned()
+            .ok_or_else(|| mismatch("object", value))
+    }
+}
+
+impl ToJson for JsonMap {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Object(self.clone())
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+        match value {
+            JsonValue::Null => Ok(None),
+            other => T::from_json(other).map(Some),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_ref().map_or(JsonValue::Null, ToJson::to_json)
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+        let elements = value.as_array().ok_or_else(|| mismatch("array", value))?;
+        elements
+            .iter()
+            .enumerate()
+            .map(|(i, element)| T::from_json(element).map_err(|e| e.within(i)))
+            .collect()
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+fn decode_members<T: FromJson, M: FromIterator<(String, T)>>(
+    value: &JsonValue,
+) -> Result<M, AccessError> {
+    let map = value.as_object().ok_or_else(|| mismatch("object", value))?;
+    map.iter()
+        .map(|(k, v)| {
+            Ok((
+                k.clone(),
+                T::from_json(v).map_err(|e| e.within(k.as_str()))?,
+            ))
+        })
+        .collect()
+}
+
+impl<T: FromJson, S: BuildHasher + Default> FromJson for HashMap<String, T, S> {
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+        decode_members(value)
+    }
+}
+
+/// Members are written in key order, so the output is deterministic.
+impl<K, T, S> ToJson for HashMap<K, T, S>
+where
+    K: AsRef<str> + Eq + Hash + Ord,
+    T: ToJson,
+{
+    fn to_json(&self) -> JsonValue {
+        let mut members: Vec<_> = self.iter().collect();
+        members.sort_by(|a, b| a.0.cmp(b.0));
+        JsonValue::Object(
+            members
+                .into_iter()
+                .map(|(k, v)| (k.as_ref().to_string(), v.to_json()))
+                .collect(),
+        )
+    }
+}
+
+impl<T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+        decode_members(value)
+    }
+}
+
+impl<K: AsRef<str>, T: ToJson> ToJson for BTreeMap<K, T> {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Object(
+            self.iter()
+                .map(|(k, v)| (k.as_ref().to_string(), v.to_json()))
+                .collect(),
+        )
+    }
+}
+
+// Tuples are arrays of exactly their length.
+macro_rules! impl_tuple {
+    ($len:literal => $($t:ident $i:tt),+) => {
+        impl<$($t: FromJson),+> FromJson for ($($t,)+) {
+            fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+                match value.as_array() {
+                    Some(elements) if elements.len() == $len => Ok((
+                        $($t::from_json(&elements[$i]).map_err(|e| e.within(Segment::Index($i)))?,)+
+                    )),
+                    _ => Err(mismatch(concat!("array of length ", $len), value)),
+                }
+            }
+        }
+
+        impl<$($t: ToJson),+> ToJson for ($($t,)+) {
+            fn to_json(&self) -> JsonValue {
+                JsonValue::Array(vec![$(self.$i.to_json()),+])
+            }
+        }
+    };
+}
+
+impl_tuple!(1 => A 0);
+impl_tuple!(2 => A 0, B 1);
+impl_tuple!(3 => A 0, B 1, C 2);
+impl_tuple!(4 => A 0, B 1, C 2, D 3);
+impl_tuple!(5 => A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(6 => A 0, B 1, C 2, D 3, E 4, F 5);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    #[test]
+    fn test_primitives_and_options() {
+        assert_eq!(u8::from_json(&json!(255)), Ok(255));
+        assert!(u8::from_json(&json!(256)).is_err());
+        assert_eq!(i16::from_json(&json!(-3)), Ok(-3));
+        assert!(i16::from_json(&json!(-3.0)).is_err());
+        assert_eq!(f32::from_json(&json!(0.5)), Ok(0.5));
+        assert_eq!(Option::<bool>::from_json(&json!(null)), Ok(None));
+        assert_eq!(Option::<bool>::from_json(&json!(true)), Ok(Some(true)));
+        assert_eq!(
+            String::from_json(&json!(1)).unwrap_err().to_string(),
+            r#"Expected string at "", found number"#
+        );
+        assert_eq!(Some("x").to_json(), json!("x"));
+        assert_eq!(None::<u64>.to_json(), json!(null));
+        assert_eq!(u64::MAX.to_json().as_u64(), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_collections_report_nested_paths() {
+        let value = json!({"a": [[1, "x"]], "b": [[2, "y"]]});
+        let decoded = BTreeMap::<String, Vec<(u32, String)>>::from_json(&value).unwrap();
+        assert_eq!(decoded["b"], [(2, "y".to_string())]);
+        assert_eq!(decoded.to_json(), value);
+
+        let err = HashMap::<String, Vec<(u32, u32)>>::from_json(&value).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"Expected integer at "a.0.1", found string"#
+        );
+        let err = <(u32,)>::from_json(&json!([1, 2])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"Expected array of length 1 at "", found array"#
+        );
+
+        let map = HashMap::from([("z", 1), ("a", 2), ("m", 3)]);
+        assert_eq!(map.to_json().to_string(), r#"{"a":2,"m":3,"z":1}"#);
+    }
+
+    #[test]
+    fn test_field() {
+        let value = json!({"n": 1, "s": null});
+        assert_eq!(field::<Option<u8>>(&value, "missing"), Ok(None));
+        assert_eq!(field::<Option<u8>>(&value, "s"), Ok(None));
+        assert_eq!(
+            field::<u8>(&value, "missing").unwrap_err().to_string(),
+            r#"No value at "missing""#
+        );
+        assert_eq!(
+            field::<u8>(&value, "s").unwrap_err().to_string(),
+            r#"Expected integer at "s", found null"#
+        );
+    }
+
+    #[test]
+    fn test_get_as_uses_from_json() {
+        #[derive(Debug, PartialEq)]
+        struct Point(i32, i32);
+
+        impl FromJson for Point {
+            fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+                Ok(Point(field(value, "x")?, field(value, "y")?))
+            }
+        }
+
+        let value = json!({"shape": {"points": [{"x": 1, "y": 2}, {"x": 3, "y": "4"}]}});
+        assert_eq!(
+            value.get_path_as::<Point>("shape.points.0"),
+            Ok(Point(1, 2))
+        );
+        assert_eq!(
+            value
+                .get_path_as::<Vec<Point>>("shape.points")
+                .unwrap_err()
+                .to_string(),
+            r#"Expected integer at "shape.points.1.y", found string"#
+        );
+        assert_eq!(
+            value.get_as::<Point>("shape").unwrap_err().to_string(),
+            r#"No value at "shape.x""#
+        );
+    }
+}
@@ -0,0 +1,495 @@
+//! Converting between arrays of flat objects and CSV.
+//!
+//! [`to_csv`](crate::csv::to_csv) writes one row per object under a header
+//! of every key seen, and [`from_csv`](crate::csv::from_csv) reads rows
+//! back into an array of objects keyed by the header.
+//! [`CsvOptions`](crate::csv::CsvOptions) sets the delimiter, fixes the
+//! columns instead of inferring them, and turns type inference off.
+//!
+//! Quoting (RFC 4180) carries the JSON types through a round trip: an
+//! unquoted empty cell is `null`, unquoted `true`, `false`, and JSON
+//! numbers are read as such, and everything else is a string. The writer
+//! quotes any string that would otherwise read back as another type,
+//! including the empty string. CSV has no nesting, so arrays and objects
+//! inside a record are written as JSON text and read back as strings; the
+//! [`ConversionReport`](crate::interop::ConversionReport) returned by the
+//! writer lists them along with anything it left out.
+
+use std::collections::HashSet;
+use std::error;
+use std::fmt;
+
+use crate::interop::ConversionReport;
+use crate::map::JsonMap;
+use crate::path::JsonPath;
+use crate::tokenizer::is_valid_json_number;
+use crate::value::{JsonNumber, JsonValue};
+
+/// CSV that could not be read into records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvError {
+    /// The 1-based line on which the offending record starts.
+    pub line: usize,
+    /// What was wrong.
+    pub message: String,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid CSV at line {}: {}", self.line, self.message)
+    }
+}
+
+impl error::Error for CsvError {}
+
+/// Settings for writing and reading CSV.
+///
+/// The default separates fields with `,`, infers the columns from the
+/// records' keys, and infers types when reading.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::csv::CsvOptions;
+/// use rust_json_parser::json;
+///
+/// let records = json!([
+///     {"id": 1, "name": "Ada; Countess", "tags": ["math"]},
+///     {"id": 2, "name": "Lin", "active": false, "note": null}
+/// ]);
+/// let options = CsvOptions::new().delimiter(';');
+/// let (csv, report) = options.to_csv(records.as_array().unwrap());
+/// assert_eq!(
+///     csv,
+///     "id;name;tags;active;note\n1;\"Ada; Countess\";\"[\"\"math\"\"]\";;\n2;Lin;;false;\n"
+/// );
+/// assert_eq!(report.to_string(), "\"/0/tags\": approximated (array written as JSON text)");
+///
+/// let back = options.from_csv(&csv)?;
+/// assert_eq!(back[1], json!({"id": 2, "name": "Lin", "tags": null, "active": false, "note": null}));
+/// assert_eq!(back[0]["tags"], json!("[\"math\"]"));
+/// # Ok::<(), rust_json_parser::csv::CsvError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    delimiter: char,
+    columns: Option<Vec<String>>,
+    infer_types: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            columns: None,
+            infer_types: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the character between fields, such as `;` or `\t`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `delimiter` is `"`, `\r`, or `\n`.
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        assert!(
+            !matches!(delimiter, '"' | '\r' | '\n'),
+            "{:?} cannot separate CSV fields",
+            delimiter
+        );
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Writes exactly these columns, in this order, instead of every key
+    /// of every record. Members under other keys are left out and
+    /// reported. Only affects writing.
+    pub fn columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Whether reading turns unquoted cells into nulls, booleans, and
+    /// numbers (the default) or keeps every cell as a string.
+    pub fn infer_types(mut self, infer: bool) -> Self {
+        self.infer_types = infer;
+        self
+    }
+
+    /// Writes `records` as CSV with a header row, ending every row with
+    /// `\n`.
+    ///
+    /// Without [`columns`](Self::columns), the header lists every key in
+    /// the order it first appears. Elements that are not objects are left
+    /// out, as is every record when there are no columns, and arrays and
+    /// objects inside records are written as JSON text; the report lists
+    /// these.
+    pub fn to_csv(&self, records: &[JsonValue]) -> (String, ConversionReport) {
+        let mut report = ConversionReport::new();
+        let columns = match &self.columns {
+            Some(columns) => columns.clone(),
+            None => {
+                let mut seen = HashSet::new();
+                records
+                    .iter()
+                    .filter_map(JsonValue::as_object)
+                    .flat_map(JsonMap::keys)
+                    .filter(|key| seen.insert(*key))
+                    .cloned()
+                    .collect()
+            }
+        };
+        let fixed: HashSet<&str> = columns.iter().map(String::as_str).collect();
+
+        let mut out = String::new();
+        let header: Vec<String> = columns
+            .iter()
+            .map(|column| self.quote(column, false))
+            .collect();
+        self.write_row(&mut out, &header);
+        for (i, record) in records.iter().enumerate() {
+            let path = JsonPath::root().join(i);
+            let Some(map) = record.as_object() else {
+                report.dropped(path, format!("{} is not a record", record.type_name()));
+                continue;
+            };
+            if columns.is_empty() {
+                // The row would be a blank line, which reads back as nothing.
+                report.dropped(path, "no columns to write");
+                continue;
+            }
+            for key in map.keys().filter(|key| !fixed.contains(key.as_str())) {
+                report.dropped(path.join(key.as_str()), "no column for this key");
+            }
+            let cells: Vec<String> = columns
+                .iter()
+                .map(|column| match map.get(column) {
+                    None | Some(JsonValue::Null) => String::new(),
+                    Some(JsonValue::String(s)) => self.quote(s, self.infer_types),
+                    Some(nested @ (JsonValue::Array(_) | JsonValue::Object(_))) => {
+                        report.approximated(
+                            path.join(column.as_str()),
+                            format!("{} written as JSON text", nested.type_name()),
+                        );
+                        self.quote(&nested.to_string(), false)
+                    }
+                    Some(scalar) => scalar.to_string(),
+                })
+                .collect();
+            self.write_row(&mut out, &cells);
+        }
+        (out, report)
+    }
+
+    /// Reads CSV with a header row into an array of objects.
+    ///
+    /// Blank lines are skipped, except under a single-column header, where
+    /// a blank line is a row with an empty cell. Every other row must have
+    /// as many fields as the header.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CsvError`] for an unterminated quoted field, text after a
+    /// closing quote, a repeated column name, or a row with the wrong
+    /// number of fields.
+    pub fn from_csv(&self, text: &str) -> Result<JsonValue, CsvError> {
+        let mut rows = Reader {
+            chars: text.chars().peekable(),
+            line: 1,
+            delimiter: self.delimiter,
+        };
+        let Some((line, header)) = rows.next_row(true)? else {
+            return Ok(JsonValue::Array(Vec::new()));
+        };
+        let mut seen = HashSet::new();
+        if let Some(field) = header.iter().find(|field| !seen.insert(&field.text)) {
+            return Err(CsvError {
+                line,
+                message: format!("column {:?} appears twice", field.text),
+            });
+        }
+
+        // With a single column, a blank line is a row with an empty cell.
+        let skip_blank = header.len() > 1;
+        let mut records = Vec::new();
+        while let Some((line, fields)) = rows.next_row(skip_blank)? {
+            if fields.len() != header.len() {
+                return Err(CsvError {
+                    line,
+                    message: format!(
+                        "row has {} fields but the header has {}",
+                        fields.len(),
+                        header.len()
+                    ),
+                });
+            }
+            let map: JsonMap = header
+                .iter()
+                .zip(fields)
+                .map(|(column, field)| (column.text.clone(), self.cell_value(field)))
+                .collect();
+            records.push(JsonValue::Object(map));
+        }
+        Ok(JsonValue::Array(records))
+    }
+
+    fn cell_value(&self, field: Field) -> JsonValue {
+        if field.quoted || !self.infer_types {
+            return JsonValue::String(field.text);
+        }
+        match field.text.as_str() {
+            "" => JsonValue::Null,
+            "true" => JsonValue::Boolean(true),
+            "false" => JsonValue::Boolean(false),
+            number if is_valid_json_number(number) => {
+                let value = number.parse().expect("valid JSON number");
+                JsonValue::Number(JsonNumber::from_lexeme(number, value))
+            }
+            _ => JsonValue::String(field.text),
+        }
+    }
+
+    // Quotes `text` if it contains anything special or, when `typed`, if
+    // reading it back unquoted would not give a string.
+    fn quote(&self, text: &str, typed: bool) -> String {
+        let special = text.contains([self.delimiter, '"', '\r', '\n']);
+        let ambiguous =
+            typed && (matches!(text, "" | "true" | "false") || is_valid_json_number(text));
+        if special || ambiguous {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn write_row(&self, out: &mut String, cells: &[String]) {
+        for (i, cell) in cells.iter().enumerate() {
+            if i > 0 {
+                out.push(self.delimiter);
+            }
+            out.push_str(cell);
+        }
+        out.push('\n');
+    }
+}
+
+/// Writes `records` as comma-separated CSV with the default
+/// [`CsvOptions`].
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::csv::to_csv;
+/// use rust_json_parser::json;
+///
+/// // "42" is quoted so that it reads back as a string.
+/// let records = json!([{"sku": "42", "qty": 3}, {"sku": "A-7", "qty": 1.5}]);
+/// let (csv, report) = to_csv(records.as_array().unwrap());
+/// assert_eq!(csv, "sku,qty\n\"42\",3\nA-7,1.5\n");
+/// assert!(report.is_lossless());
+/// ```
+pub fn to_csv(records: &[JsonValue]) -> (String, ConversionReport) {
+    CsvOptions::default().to_csv(records)
+}
+
+/// Reads comma-separated CSV with the default [`CsvOptions`].
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::csv::from_csv;
+/// use rust_json_parser::json;
+///
+/// let records = from_csv("sku,qty\r\n\"0042\",3\r\nA-7,\r\n")?;
+/// assert_eq!(records, json!([{"sku": "0042", "qty": 3}, {"sku": "A-7", "qty": null}]));
+/// # Ok::<(), rust_json_parser::csv::CsvError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`CsvError`] if `text` is not well-formed CSV with a header.
+pub fn from_csv(text: &str) -> Result<JsonValue, CsvError> {
+    CsvOptions::default().from_csv(text)
+}
+
+struct Field {
+    text: String,
+    quoted: bool,
+}
+
+struct Reader<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    delimiter: char,
+}
+
+impl Reader<'_> {
+    // Returns the next row and the line it starts on, passing over blank
+    // lines if `skip_blank` is set.
+    fn next_row(&mut self, skip_blank: bool) -> Result<Option<(usize, Vec<Field>)>, CsvError> {
+        loop {
+            if self.chars.peek().is_none() {
+                return Ok(None);
+            }
+            let start = self.line;
+            let mut fields = Vec::new();
+            loop {
+                let field = self.field(start)?;
+                fields.push(field);
+                match self.chars.next() {
+                    Some(c) if c == self.delimiter => continue,
+                    Some('\r') => {
+                        self.chars.next();
+                        self.line += 1;
+                    }
+                    Some('\n') => self.line += 1,
+                    _ => {}
+                }
+                break;
+            }
+            let blank = matches!(&fields[..], [field] if field.text.is_empty() && !field.quoted);
+            if !(blank && skip_blank) {
+                return Ok(Some((start, fields)));
+            }
+        }
+    }
+
+    // Reads one field, leaving the delimiter or line ending after it.
+    fn field(&mut self, start: usize) -> Result<Field, CsvError> {
+        let mut text = String::new();
+        if self.chars.peek() != Some(&'"') {
+            while let Some(&c) = self.chars.peek() {
+                if c == self.delimiter || c == '\n' || (c == '\r' && self.at_crlf()) {
+                    break;
+                }
+                text.push(c);
+                self.chars.next();
+            }
+            return Ok(Field {
+                text,
+                quoted: false,
+            });
+        }
+
+        self.chars.next();
+        loop {
+            match self.chars.next() {
+                None => {
+                    return Err(CsvError {
+                        line: start,
+                        message: "quoted field is never closed".to_string(),
+                    });
+                }
+                Some('"') if self.chars.peek() == Some(&'"') => {
+                    self.chars.next();
+                    text.push('"');
+                }
+                Some('"') => break,
+                Some(c) => {
+                    if c == '\n' {
+                        self.line += 1;
+                    }
+                    text.push(c);
+                }
+            }
+        }
+        match self.chars.peek().copied() {
+            None | Some('\n') => {}
+            Some('\r') if self.at_crlf() => {}
+            Some(c) if c == self.delimiter => {}
+            Some(_) => {
+                return Err(CsvError {
+                    line: self.line,
+                    message: "unexpected text after a closing quote".to_string(),
+                });
+            }
+        }
+        Ok(Field { text, quoted: true })
+    }
+
+    fn at_crlf(&self) -> bool {
+        let mut ahead = self.chars.clone();
+        ahead.next() == Some('\r') && ahead.next() == Some('\n')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interop::LossKind;
+    use crate::json;
+
+    #[test]
+    fn test_round_trip_keeps_types() {
+        let records = json!([
+            {"s": "", "n": "12", "b": "true", "q": "say \"hi\"", "multi": "a\nb", "x": 1e3},
+            {"s": null, "n": 12, "b": true, "q": "a,b", "multi": "\r", "x": -0.5}
+        ]);
+        let (csv, report) = to_csv(records.as_array().unwrap());
+        assert!(report.is_lossless());
+        assert_eq!(from_csv(&csv).unwrap(), records);
+
+        let strings = CsvOptions::new().infer_types(false).from_csv(&csv).unwrap();
+        assert_eq!(strings[1]["n"], json!("12"));
+        assert_eq!(strings[1]["s"], json!(""));
+
+        // A single column writes null as a blank line, which is still a row.
+        let (csv, report) = to_csv(&[json!({"a": null}), json!({"a": 1}), json!({})]);
+        assert_eq!(csv, "a\n\n1\n\n");
+        assert!(report.is_lossless());
+        assert_eq!(
+            from_csv(&csv).unwrap(),
+            json!([{"a": null}, {"a": 1}, {"a": null}])
+        );
+        let (csv, report) = to_csv(&[json!({}), json!({})]);
+        assert_eq!(csv, "\n");
+        assert_eq!(report.of_kind(LossKind::Dropped).count(), 2);
+        assert_eq!(from_csv(&csv).unwrap(), json!([]));
+    }
+
+    #[test]
+    fn test_fixed_columns_and_losses() {
+        let records = json!([{"a": 1, "b": {"c": 2}}, 7, {"z": true}]);
+        let (csv, report) = CsvOptions::new()
+            .columns(["b", "a"])
+            .to_csv(records.as_array().unwrap());
+        assert_eq!(csv, "b,a\n\"{\"\"c\"\":2}\",1\n,\n");
+        let dropped: Vec<String> = report
+            .of_kind(LossKind::Dropped)
+            .map(|loss| loss.path.to_pointer())
+            .collect();
+        assert_eq!(dropped, ["/1", "/2/z"]);
+        assert_eq!(report.of_kind(LossKind::Approximated).count(), 1);
+    }
+
+    #[test]
+    fn test_read_errors_and_blank_lines() {
+        assert_eq!(from_csv("").unwrap(), json!([]));
+        assert_eq!(
+            from_csv("a,b\n\n1,2\n\n").unwrap(),
+            json!([{"a": 1, "b": 2}])
+        );
+        let err = |text: &str| from_csv(text).unwrap_err();
+        assert_eq!(
+            err("a,b\n1,2\n3\n").to_string(),
+            "Invalid CSV at line 3: row has 1 fields but the header has 2"
+        );
+        assert_eq!(err("a,a\n").message, "column \"a\" appears twice");
+        assert_eq!(err("a\n\"x\ny\n").line, 2);
+        assert_eq!(
+            err("a\n\"x\"y\n").message,
+            "unexpected text after a closing quote"
+        );
+    }
+}
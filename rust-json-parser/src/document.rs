@@ -0,0 +1,184 @@
+//! Parsed documents that remember where they came from.
+//!
+//! Tools that load many files (layered configuration, fixture suites)
+//! often report problems long after parsing, when the original file name
+//! is no longer in scope. A [`ParsedDocument`](crate::document::ParsedDocument)
+//! keeps the root value together with its source name, the parser options
+//! that were used, and any warnings, so a later message can still say
+//! which file a value came from.
+
+use std::error;
+use std::fmt;
+
+use crate::error::JsonError;
+use crate::parser::{JsonParser, ParserOptions, Warning};
+use crate::value::JsonValue;
+
+/// A parse error annotated with the name of the document that failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentError {
+    /// The source name or URI of the document.
+    pub source: String,
+    /// The underlying parse error.
+    pub error: JsonError,
+}
+
+impl fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.source, self.error)
+    }
+}
+
+impl error::Error for DocumentError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A root [`JsonValue`] together with its provenance.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::document::ParsedDocument;
+///
+/// let input = "{\n  \"debug\": true,\n  \"debug\": false\n}";
+/// let doc = ParsedDocument::parse("overrides.json", input)?;
+/// assert_eq!(doc.source(), "overrides.json");
+/// assert_eq!(doc.root().get("debug").and_then(|v| v.as_bool()), Some(false));
+/// assert_eq!(
+///     doc.warning_messages(),
+///     [r#"overrides.json: duplicate key "debug" at line 3, column 3"#]
+/// );
+///
+/// let err = ParsedDocument::parse("broken.json", "[1,").unwrap_err();
+/// assert!(err.to_string().starts_with("broken.json: "));
+/// # Ok::<(), rust_json_parser::document::DocumentError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedDocument {
+    source: String,
+    options: ParserOptions,
+    warnings: Vec<Warning>,
+    root: JsonValue,
+}
+
+impl ParsedDocument {
+    /// Parses `input` with a default parser, recording `source` as its
+    /// name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DocumentError`] carrying `source` if the input is not
+    /// valid JSON.
+    pub fn parse(source: impl Into<String>, input: &str) -> Result<Self, DocumentError> {
+        Self::parse_with(&mut JsonParser::new(), source, input)
+    }
+
+    /// Parses `input` with an existing parser, recording its options and
+    /// the warnings it produced.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DocumentError`] carrying `source` if the input is not
+    /// valid JSON.
+    pub fn parse_with(
+        parser: &mut JsonParser,
+        source: impl Into<String>,
+        input: &str,
+    ) -> Result<Self, DocumentError> {
+        let source = source.into();
+        match parser.parse(input) {
+            Ok(root) => Ok(Self {
+                source,
+                options: parser.options(),
+                warnings: parser.take_warnings(),
+                root,
+            }),
+            Err(error) => Err(DocumentError { source, error }),
+        }
+    }
+
+    /// Wraps an already parsed value with no warnings and default options.
+    pub fn new(source: impl Into<String>, root: JsonValue) -> Self {
+        Self {
+            source: source.into(),
+            options: ParserOptions::default(),
+            warnings: Vec::new(),
+            root,
+        }
+    }
+
+    /// Returns the source name or URI.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the options the document was parsed with.
+    pub fn options(&self) -> ParserOptions {
+        self.options
+    }
+
+    /// Returns the warnings produced while parsing.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Formats each warning prefixed with the source name.
+    pub fn warning_messages(&self) -> Vec<String> {
+        self.warnings
+            .iter()
+            .map(|warning| format!("{}: {}", self.source, warning))
+            .collect()
+    }
+
+    /// Borrows the root value.
+    pub fn root(&self) -> &JsonValue {
+        &self.root
+    }
+
+    /// Mutably borrows the root value.
+    pub fn root_mut(&mut self) -> &mut JsonValue {
+        &mut self.root
+    }
+
+    /// Discards the metadata and returns the root value.
+    pub fn into_root(self) -> JsonValue {
+        self.root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_parser_options() {
+        let mut parser = JsonParser::new().preserve_raw_numbers(true);
+        let doc = ParsedDocument::parse_with(&mut parser, "a.json", "[1.50]").unwrap();
+        assert!(doc.options().preserve_raw_numbers);
+        assert_eq!(doc.root().to_string(), "[1.50]");
+        assert!(doc.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_error_keeps_source_and_cause() {
+        let err = ParsedDocument::parse("cfg/base.json", "{").unwrap_err();
+        assert_eq!(err.source, "cfg/base.json");
+        assert!(matches!(err.error, JsonError::UnexpectedEndOfInput { .. }));
+        assert!(error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn test_warnings_reset_between_parses() {
+        let mut parser = JsonParser::new();
+        let first = ParsedDocument::parse_with(&mut parser, "1", r#"{"k": 1, "k": 2}"#).unwrap();
+        assert_eq!(first.warnings().len(), 1);
+        let second = ParsedDocument::parse_with(&mut parser, "2", r#"{"k": 1}"#).unwrap();
+        assert!(second.warnings().is_empty());
+        assert_eq!(
+            second.into_root().get("k").and_then(|v| v.as_i64()),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! Type histograms over document streams, and schema drift between them.
+//!
+//! A [`TypeSummary`](crate::drift::TypeSummary) counts the JSON types seen
+//! at every path across any number of documents, with all elements of an
+//! array folded into one `*` segment: `/items/*/id`. Comparing the summary
+//! of today's responses from an upstream API against a stored baseline
+//! with [`drift_from`](crate::drift::TypeSummary::drift_from) reports
+//! fields that appeared or disappeared and fields whose types changed,
+//! before a consumer trips over them.
+//!
+//! Summaries convert to and from JSON with
+//! [`ToJson`](crate::convert::ToJson) and
+//! [`FromJson`](crate::convert::FromJson), so a baseline can be kept in a
+//! file next to the code that depends on it.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::convert::{FromJson, ToJson, field};
+use crate::error::AccessError;
+use crate::map::JsonMap;
+use crate::pointer::escape_segment;
+use crate::value::JsonValue;
+
+// Type names in the order counts are stored and listed.
+const TYPES: [&str; 6] = ["null", "boolean", "number", "string", "array", "object"];
+
+/// How many times each JSON type was seen at one path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TypeCounts {
+    counts: [usize; 6],
+}
+
+impl TypeCounts {
+    /// Returns how often the type named `type_name` (`"null"`,
+    /// `"boolean"`, `"number"`, `"string"`, `"array"` or `"object"`) was
+    /// seen. Unknown names count zero.
+    pub fn get(&self, type_name: &str) -> usize {
+        TYPES
+            .iter()
+            .position(|t| *t == type_name)
+            .map_or(0, |i| self.counts[i])
+    }
+
+    /// Returns the number of values seen, of any type.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the names of the types seen at least once, in the order
+    /// null, boolean, number, string, array, object.
+    pub fn types(&self) -> Vec<&'static str> {
+        TYPES
+            .iter()
+            .zip(self.counts)
+            .filter(|(_, count)| *count > 0)
+            .map(|(t, _)| *t)
+            .collect()
+    }
+
+    fn add(&mut self, value: &JsonValue) {
+        let i = match value {
+            JsonValue::Null => 0,
+            JsonValue::Boolean(_) => 1,
+            JsonValue::Number(_) => 2,
+            JsonValue::String(_) => 3,
+            JsonValue::Array(_) => 4,
+            JsonValue::Object(_) => 5,
+        };
+        self.counts[i] += 1;
+    }
+}
+
+/// Type histograms for every path seen across a set of documents.
+///
+/// Paths are JSON Pointers in which every array index is replaced by `*`.
+/// The root is the empty pointer.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::drift::TypeSummary;
+/// use rust_json_parser::parser::parse_json;
+///
+/// let baseline: TypeSummary = [
+///     parse_json(r#"{"id": 1, "tags": ["a"]}"#)?,
+///     parse_json(r#"{"id": 2, "tags": []}"#)?,
+/// ]
+/// .iter()
+/// .collect();
+/// assert_eq!(baseline.documents(), 2);
+/// assert_eq!(baseline.get("/id").unwrap().get("number"), 2);
+/// assert_eq!(baseline.get("/tags/*").unwrap().types(), ["string"]);
+///
+/// let mut today = TypeSummary::new();
+/// today.observe(&parse_json(r#"{"id": "3", "tags": ["b"], "extra": null}"#)?);
+/// let drift: Vec<String> = today.drift_from(&baseline).iter().map(|d| d.to_string()).collect();
+/// assert_eq!(
+///     drift,
+///     [
+///         "New path \"/extra\" with types null",
+///         "Types at \"/id\" changed from number to string",
+///     ]
+/// );
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeSummary {
+    documents: usize,
+    paths: BTreeMap<String, TypeCounts>,
+}
+
+impl TypeSummary {
+    /// Creates a summary of no documents.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts the type of every value in `document`.
+    pub fn observe(&mut self, document: &JsonValue) {
+        self.documents += 1;
+        self.visit(&mut String::new(), document);
+    }
+
+    fn visit(&mut self, path: &mut String, value: &JsonValue) {
+        self.paths.entry(path.clone()).or_default().add(value);
+        let len = path.len();
+        match value {
+            JsonValue::Array(elements) => {
+                path.push_str("/*");
+                for element in elements {
+                    self.visit(path, element);
+                }
+                path.truncate(len);
+            }
+            JsonValue::Object(map) => {
+                for (key, member) in map {
+                    path.push('/');
+                    path.push_str(&escape_segment(key));
+                    self.visit(path, member);
+                    path.truncate(len);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the number of documents observed.
+    pub fn documents(&self) -> usize {
+        self.documents
+    }
+
+    /// Returns the counts at `path`, or `None` if nothing was seen there.
+    pub fn get(&self, path: &str) -> Option<&TypeCounts> {
+        self.paths.get(path)
+    }
+
+    /// Iterates over every path and its counts, in path order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &TypeCounts)> {
+        self.paths
+            .iter()
+            .map(|(path, counts)| (path.as_str(), counts))
+    }
+
+    /// Lists how this summary differs from `baseline`, in path order.
+    ///
+    /// A path whose set of types differs is a [`Drift::TypesChanged`];
+    /// how often each type occurred does not matter. When a whole subtree
+    /// appears or disappears, only its topmost path is reported.
+    pub fn drift_from(&self, baseline: &TypeSummary) -> Vec<Drift> {
+        let mut paths: Vec<&String> = self.paths.keys().chain(baseline.paths.keys()).collect();
+        paths.sort();
+        paths.dedup();
+
+        let mut drift = Vec::new();
+        // Roots of the subtrees reported as added or removed.
+        let mut subtrees = BTreeSet::new();
+        for path in paths {
+            if path
+                .rmatch_indices('/')
+                .any(|(i, _)| subtrees.contains(&path[..i]))
+            {
+                continue;
+            }
+            match (baseline.paths.get(path), self.paths.get(path)) {
+                (None, Some(now)) => {
+                    subtrees.insert(path.as_str());
+                    drift.push(Drift::Added {
+                        path: path.clone(),
+                        types: now.types(),
+                    });
+                }
+                (Some(_), None) => {
+                    subtrees.insert(path.as_str());
+                    drift.push(Drift::Removed { path: path.clone() });
+                }
+                (Some(before), Some(now)) if before.types() != now.types() => {
+                    drift.push(Drift::TypesChanged {
+                        path: path.clone(),
+                        before: before.types(),
+                        after: now.types(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        drift
+    }
+}
+
+impl<'a> Extend<&'a JsonValue> for TypeSummary {
+    fn extend<I: IntoIterator<Item = &'a JsonValue>>(&mut self, documents: I) {
+        for document in documents {
+            self.observe(document);
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a JsonValue> for TypeSummary {
+    fn from_iter<I: IntoIterator<Item = &'a JsonValue>>(documents: I) -> Self {
+        let mut summary = Self::new();
+        summary.extend(documents);
+        summary
+    }
+}
+
+/// Written as `{"documents": n, "paths": {path: {type: count}}}`, listing
+/// only the types seen at each path.
+impl ToJson for TypeSummary {
+    fn to_json(&self) -> JsonValue {
+        let paths = self
+            .paths
+            .iter()
+            .map(|(path, counts)| {
+                let counts = TYPES
+                    .iter()
+                    .zip(counts.counts)
+                    .filter(|(_, count)| *count > 0)
+                    .map(|(t, count)| (t.to_string(), count.to_json()))
+                    .collect();
+                (path.clone(), JsonValue::Object(counts))
+            })
+            .collect();
+        let mut summary = JsonMap::new();
+        summary.insert("documents".to_string(), self.documents.to_json());
+        summary.insert("paths".to_string(), JsonValue::Object(paths));
+        JsonValue::Object(summary)
+    }
+}
+
+impl FromJson for TypeCounts {
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+        let mut counts = [0; 6];
+        for (count, t) in counts.iter_mut().zip(TYPES) {
+            *count = field::<Option<usize>>(value, t)?.unwrap_or(0);
+        }
+        Ok(Self { counts })
+    }
+}
+
+impl FromJson for TypeSummary {
+    fn from_json(value: &JsonValue) -> Result<Self, AccessError> {
+        Ok(Self {
+            documents: field(value, "documents")?,
+            paths: field(value, "paths")?,
+        })
+    }
+}
+
+/// One difference found by [`TypeSummary::drift_from`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    /// A path the baseline never saw.
+    Added {
+        /// The new path.
+        path: String,
+        /// The types now seen there.
+        types: Vec<&'static str>,
+    },
+    /// A path the baseline saw that no longer occurs.
+    Removed {
+        /// The missing path.
+        path: String,
+    },
+    /// A path seen with a different set of types than in the baseline.
+    TypesChanged {
+        /// The path.
+        path: String,
+        /// The types the baseline saw there.
+        before: Vec<&'static str>,
+        /// The types now seen there.
+        after: Vec<&'static str>,
+    },
+}
+
+impl Drift {
+    /// Returns the path the difference is at.
+    pub fn path(&self) -> &str {
+        match self {
+            Drift::Added { path, .. }
+            | Drift::Removed { path }
+            | Drift::TypesChanged { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Drift::Added { path, types } => {
+                write!(f, "New path {:?} with types {}", path, types.join(", "))
+            }
+            Drift::Removed { path } => write!(f, "Path {:?} no longer occurs", path),
+            Drift::TypesChanged {
+                path,
+                before,
+                after,
+            } => write!(
+                f,
+                "Types at {:?} changed from {} to {}",
+                path,
+                before.join(", "),
+                after.join(", ")
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    #[test]
+    fn test_histogram_folds_array_elements() {
+        let docs = [
+            json!({"a": [1, "x", null], "b~/c": true}),
+            json!({"a": [], "b~/c": false}),
+        ];
+        let summary: TypeSummary = docs.iter().collect();
+        let paths: Vec<&str> = summary.iter().map(|(p, _)| p).collect();
+        assert_eq!(paths, ["", "/a", "/a/*", "/b~0~1c"]);
+        let elements = summary.get("/a/*").unwrap();
+        assert_eq!(elements.types(), ["null", "number", "string"]);
+        assert_eq!(elements.total(), 3);
+        assert_eq!(summary.get("/b~0~1c").unwrap().get("boolean"), 2);
+        assert_eq!(summary.get("").unwrap().get("object"), 2);
+        assert_eq!(summary.get("").unwrap().get("nonsense"), 0);
+
+        let stored = summary.to_json();
+        assert_eq!(
+            stored["paths"]["/a/*"],
+            json!({"null": 1, "number": 1, "string": 1})
+        );
+        assert_eq!(TypeSummary::from_json(&stored), Ok(summary));
+    }
+
+    #[test]
+    fn test_drift_reports_topmost_changes() {
+        let baseline: TypeSummary = [json!({"old": {"x": 1, "y": [2]}, "keep": 1, "n": null})]
+            .iter()
+            .collect();
+        let today: TypeSummary = [
+            json!({"new": {"z": [true]}, "keep": 2, "n": null}),
+            json!({"keep": 3, "n": 4, "newer": 5}),
+        ]
+        .iter()
+        .collect();
+        let drift = today.drift_from(&baseline);
+        assert_eq!(
+            drift,
+            [
+                Drift::TypesChanged {
+                    path: "/n".to_string(),
+                    before: vec!["null"],
+                    after: vec!["null", "number"],
+                },
+                Drift::Added {
+                    path: "/new".to_string(),
+                    types: vec!["object"],
+                },
+                Drift::Added {
+                    path: "/newer".to_string(),
+                    types: vec!["number"],
+                },
+                Drift::Removed {
+                    path: "/old".to_string(),
+                },
+            ]
+        );
+        assert_eq!(drift[3].to_string(), r#"Path "/old" no longer occurs"#);
+        assert!(today.drift_from(&today).is_empty());
+
+        // "/a!" sorts between "/a" and "/a/b" but is not inside "/a".
+        let before: TypeSummary = [json!({"a!": 1})].iter().collect();
+        let after: TypeSummary = [json!({"a": {"b": 1}, "a!": 1})].iter().collect();
+        let drift = after.drift_from(&before);
+        let paths: Vec<&str> = drift.iter().map(Drift::path).collect();
+        assert_eq!(paths, ["/a"]);
+        assert_eq!(TypeSummary::new().drift_from(&before).len(), 1);
+    }
+}
@@ -0,0 +1,528 @@
+//! Configurable equivalence checks for comparing JSON values in tests.
+//!
+//! Real API responses carry request ids, timestamps, floating point noise,
+//! and arrays whose order is not part of the contract. An
+//! [`Equivalence`](crate::equivalence::Equivalence) describes which of
+//! those differences to tolerate, and the
+//! [`assert_equivalent!`](crate::assert_equivalent) macro turns a failed
+//! comparison into a readable panic listing every difference by JSON
+//! Pointer.
+//!
+//! Paths are JSON Pointers in which a `*` segment matches any object key
+//! or array index.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+use crate::path::{JsonPath, Segment};
+use crate::pointer::{parse_index, split_pointer};
+use crate::value::JsonValue;
+
+/// A single difference found by [`Equivalence::compare`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// Location of the differing value.
+    pub path: JsonPath,
+    /// A description of the difference.
+    pub message: String,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "/: {}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Rules describing which differences between two values to tolerate.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use rust_json_parser::assert_equivalent;
+/// use rust_json_parser::equivalence::Equivalence;
+/// use rust_json_parser::parser::parse_json;
+///
+/// let expected = parse_json(r#"{"tags": ["a", "b"], "score": 0.3, "at": "2024-01-01T00:00:00Z"}"#)?;
+/// let actual = parse_json(r#"{"tags": ["b", "a"], "score": 0.30000000000000004, "at": "2024-01-01T00:00:02Z"}"#)?;
+///
+/// let rules = Equivalence::new()
+///     .unordered("/tags")
+///     .tolerance(1e-9)
+///     .timestamp("/at", Duration::from_secs(5));
+/// assert_equivalent!(actual, expected, rules);
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Equivalence {
+    ignored: Vec<Vec<String>>,
+    unordered: Vec<Vec<String>>,
+    timestamps: Vec<(Vec<String>, Duration)>,
+    tolerance: f64,
+}
+
+impl Equivalence {
+    /// Creates rules requiring exact equality.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skips the value at `pointer`. A key missing on one side is also
+    /// tolerated.
+    pub fn ignore(mut self, pointer: &str) -> Self {
+        self.ignored.push(split_pointer(pointer));
+        self
+    }
+
+    /// Compares the array at `pointer` as a multiset, ignoring order.
+    /// Elements are paired so that as many as possible match, even when a
+    /// tolerance lets one element match several on the other side.
+    pub fn unordered(mut self, pointer: &str) -> Self {
+        self.unordered.push(split_pointer(pointer));
+        self
+    }
+
+    /// Treats numbers as equal when they differ by at most `tolerance`.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance.abs();
+        self
+    }
+
+    /// Treats the values at `pointer` as timestamps that may differ by up
+    /// to `max_skew`.
+    ///
+    /// Both RFC 3339 strings (`2024-01-01T12:00:00.5+02:00`) and numeric
+    /// Unix timestamps in seconds are understood.
+    pub fn timestamp(mut self, pointer: &str, max_skew: Duration) -> Self {
+        self.timestamps.push((split_pointer(pointer), max_skew));
+        self
+    }
+
+    /// Compares two values, returning every difference that is not
+    /// tolerated by the rules.
+    ///
+    /// # Errors
+    ///
+    /// Returns the list of differences when the values are not equivalent.
+    pub fn compare(&self, actual: &JsonValue, expected: &JsonValue) -> Result<(), Vec<Difference>> {
+        let mut differences = Vec::new();
+        self.compare_at(&mut JsonPath::root(), actual, expected, &mut differences);
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(differences)
+        }
+    }
+
+    /// Returns `true` if the values are equivalent under these rules.
+    pub fn is_equivalent(&self, actual: &JsonValue, expected: &JsonValue) -> bool {
+        let mut differences = Vec::new();
+        self.compare_at(&mut JsonPath::root(), actual, expected, &mut differences);
+        differences.is_empty()
+    }
+
+    fn compare_at(
+        &self,
+        path: &mut JsonPath,
+        actual: &JsonValue,
+        expected: &JsonValue,
+        out: &mut Vec<Difference>,
+    ) {
+        if matches_any(&self.ignored, path) {
+            return;
+        }
+        if let Some(skew) = self.timestamp_skew(path) {
+            match (timestamp_seconds(actual), timestamp_seconds(expected)) {
+                (Some(a), Some(e)) if (a - e).abs() <= skew.as_secs_f64() => {}
+                (Some(a), Some(e)) => out.push(difference(
+                    path,
+                    format!("timestamps differ by {}s (max {:?})", (a - e).abs(), skew),
+                )),
+                _ if actual == expected => {}
+                _ => out.push(difference(
+                    path,
+                    format!("expected timestamp {}, found {}", expected, actual),
+                )),
+            }
+            return;
+        }
+        match (actual, expected) {
+            (JsonValue::Object(a), JsonValue::Object(e)) => {
+                let mut keys: Vec<&String> = a.keys().chain(e.keys()).collect();
+                keys.sort();
+                keys.dedup();
+                for key in keys {
+                    path.push(key.as_str());
+                    match (a.get(key), e.get(key)) {
+                        (Some(av), Some(ev)) => self.compare_at(path, av, ev, out),
+                        _ if matches_any(&self.ignored, path) => {}
+                        (Some(av), None) => {
+                            out.push(difference(path, format!("unexpected member {}", av)))
+                        }
+                        (None, Some(ev)) => {
+                            out.push(difference(path, format!("missing member {}", ev)))
+                        }
+                        (None, None) => {}
+                    }
+                    path.pop();
+                }
+            }
+            (JsonValue::Array(a), JsonValue::Array(e)) if matches_any(&self.unordered, path) => {
+                self.compare_unordered(path, a, e, out);
+            }
+            (JsonValue::Array(a), JsonValue::Array(e)) => {
+                if a.len() != e.len() {
+                    out.push(difference(
+                        path,
+                        format!("expected {} elements, found {}", e.len(), a.len()),
+                    ));
+                }
+                for (i, (av, ev)) in a.iter().zip(e).enumerate() {
+                    path.push(i);
+                    self.compare_at(path, av, ev, out);
+                    path.pop();
+                }
+            }
+            (JsonValue::Number(a), JsonValue::Number(e))
+                if (a.as_f64() - e.as_f64()).abs() > self.tolerance =>
+            {
+                out.push(difference(path, format!("expected {}, found {}", e, a)));
+            }
+            (JsonValue::Number(_), JsonValue::Number(_)) => {}
+            (a, e) if a != e => out.push(difference(path, format!("expected {}, found {}", e, a))),
+            _ => {}
+        }
+    }
+
+    // Pairs each expected element with an equivalent actual one. A greedy
+    // pass pairs everything in the usual case; an element it leaves out is
+    // then paired along an augmenting path, so a tolerance or an ignored
+    // member cannot let an early element take a later one's only partner.
+    fn compare_unordered(
+        &self,
+        path: &mut JsonPath,
+        actual: &[JsonValue],
+        expected: &[JsonValue],
+        out: &mut Vec<Difference>,
+    ) {
+        // The expected element each actual element is paired with.
+        let mut partner: Vec<Option<usize>> = vec![None; actual.len()];
+        let mut paired = vec![false; expected.len()];
+        for (i, ev) in expected.iter().enumerate() {
+            let free = (0..actual.len())
+                .find(|&j| partner[j].is_none() && self.equivalent(path, i, &actual[j], ev));
+            if let Some(j) = free {
+                partner[j] = Some(i);
+                paired[i] = true;
+            }
+        }
+        for (i, paired) in paired.iter_mut().enumerate() {
+            if !*paired {
+                *paired = self.augment(path, i, actual, expected, &mut partner);
+            }
+        }
+
+        for (i, ev) in expected.iter().enumerate() {
+            if !paired[i] {
+                path.push(i);
+                out.push(difference(path, format!("no element matching {}", ev)));
+                path.pop();
+            }
+        }
+        for (j, av) in actual.iter().enumerate() {
+            if partner[j].is_none() {
+                path.push(j);
+                out.push(difference(path, format!("unexpected element {}", av)));
+                path.pop();
+            }
+        }
+    }
+
+    // Searches breadth first from the unpaired expected element `start`
+    // for a chain of pairs ending at an unpaired actual element, and
+    // shifts every pair along the chain so that `start` is paired too.
+    fn augment(
+        &self,
+        path: &mut JsonPath,
+        start: usize,
+        actual: &[JsonValue],
+        expected: &[JsonValue],
+        partner: &mut [Option<usize>],
+    ) -> bool {
+        // The expected element each actual element was reached from.
+        let mut reached_from: Vec<Option<usize>> = vec![None; actual.len()];
+        let mut queue = VecDeque::from([start]);
+        while let Some(i) = queue.pop_front() {
+            for j in 0..actual.len() {
+                if reached_from[j].is_some() || !self.equivalent(path, i, &actual[j], &expected[i])
+                {
+                    continue;
+                }
+                reached_from[j] = Some(i);
+                if let Some(next) = partner[j] {
+                    queue.push_back(next);
+                    continue;
+                }
+                let mut j = j;
+                loop {
+                    let i = reached_from[j].expect("reached above");
+                    let previous = partner.iter().position(|&p| p == Some(i));
+                    partner[j] = Some(i);
+                    match previous {
+                        Some(k) => j = k,
+                        None => return true,
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    // Whether `actual` matches the expected element at `index`.
+    fn equivalent(
+        &self,
+        path: &mut JsonPath,
+        index: usize,
+        actual: &JsonValue,
+        expected: &JsonValue,
+    ) -> bool {
+        let mut scratch = Vec::new();
+        path.push(index);
+        self.compare_at(path, actual, expected, &mut scratch);
+        path.pop();
+        scratch.is_empty()
+    }
+
+    fn timestamp_skew(&self, path: &JsonPath) -> Option<Duration> {
+        self.timestamps
+            .iter()
+            .find(|(pattern, _)| pattern_matches(pattern, path))
+            .map(|(_, skew)| *skew)
+    }
+}
+
+/// Asserts that two [`JsonValue`]s are equivalent, optionally under a set
+/// of [`Equivalence`](crate::equivalence::Equivalence) rules.
+///
+/// Without rules the values must be exactly equal. On failure the panic
+/// message lists every difference.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::assert_equivalent;
+/// use rust_json_parser::equivalence::Equivalence;
+/// use rust_json_parser::parser::parse_json;
+///
+/// let a = parse_json(r#"{"id": "x1", "ok": true}"#)?;
+/// let b = parse_json(r#"{"id": "x2", "ok": true}"#)?;
+/// assert_equivalent!(a, b, Equivalence::new().ignore("/id"));
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[macro_export]
+macro_rules! assert_equivalent {
+    ($actual:expr, $expected:expr $(,)?) => {
+        $crate::assert_equivalent!($actual, $expected, $crate::equivalence::Equivalence::new())
+    };
+    ($actual:expr, $expected:expr, $rules:expr $(,)?) => {
+        if let Err(differences) = $rules.compare(&$actual, &$expected) {
+            let lines: Vec<String> = differences.iter().map(|d| format!("  {}", d)).collect();
+            panic!("values are not equivalent:\n{}", lines.join("\n"));
+        }
+    };
+}
+
+fn difference(path: &JsonPath, message: String) -> Difference {
+    Difference {
+        path: path.clone(),
+        message,
+    }
+}
+
+fn pattern_matches(pattern: &[String], path: &JsonPath) -> bool {
+    pattern.len() == path.len()
+        && pattern.iter().zip(path).all(|(p, segment)| match segment {
+            _ if p == "*" => true,
+            Segment::Key(key) => p == key,
+            Segment::Index(index) => parse_index(p) == Some(*index),
+        })
+}
+
+fn matches_any(patterns: &[Vec<String>], path: &JsonPath) -> bool {
+    patterns.iter().any(|p| pattern_matches(p, path))
+}
+
+fn timestamp_seconds(value: &JsonValue) -> Option<f64> {
+    match value {
+        JsonValue::Number(n) => Some(n.as_f64()),
+        JsonValue::String(s) => parse_rfc3339(s),
+        _ => None,
+    }
+}
+
+/// Parses an RFC 3339 timestamp into seconds since the Unix epoch.
+fn parse_rfc3339(s: &str) -> Option<f64> {
+    let b = s.as_bytes();
+    let digits = |range: std::ops::Range<usize>| -> Option<i64> {
+        let part = s.get(range)?;
+        if part.bytes().all(|c| c.is_ascii_digit()) {
+            part.parse().ok()
+        } else {
+            None
+        }
+    };
+    if b.len() < 20 || b[4] != b'-' || b[7] != b'-' || b[13] != b':' || b[16] != b':' {
+        return None;
+    }
+    if !matches!(b[10], b'T' | b't' | b' ') {
+        return None;
+    }
+    let (year, month, day) = (digits(0..4)?, digits(5..7)?, digits(8..10)?);
+    let (hour, minute, second) = (digits(11..13)?, digits(14..16)?, digits(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+
+    let mut rest = &s[19..];
+    let mut fraction = 0.0;
+    if let Some(frac) = rest.strip_prefix('.') {
+        let len = frac.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return None;
+        }
+        fraction = format!("0.{}", &frac[..len]).parse().ok()?;
+        rest = &frac[len..];
+    }
+    let at = s.len() - rest.len();
+    let offset = match rest.as_bytes() {
+        b"Z" | b"z" => 0,
+        [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+            let seconds = digits(at + 1..at + 3)? * 3600 + digits(at + 4..at + 6)? * 60;
+            if *sign == b'-' { -seconds } else { seconds }
+        }
+        _ => return None,
+    };
+
+    // Days from civil date (Howard Hinnant's algorithm).
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let seconds = days * 86_400 + hour * 3600 + minute * 60 + second - offset;
+    Some(seconds as f64 + fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_exact_by_default() {
+        let a = parse_json(r#"{"a": [1, 2]}"#).unwrap();
+        let b = parse_json(r#"{"a": [1, 3]}"#).unwrap();
+        let differences = Equivalence::new().compare(&a, &b).unwrap_err();
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].path.to_string(), "/a/1");
+        assert!(Equivalence::new().is_equivalent(&a, &a));
+    }
+
+    #[test]
+    fn test_ignore_with_wildcard_and_missing_member() {
+        let a = parse_json(r#"{"items": [{"id": 1, "v": 1}, {"id": 2, "v": 2}]}"#).unwrap();
+        let b = parse_json(r#"{"items": [{"id": 9, "v": 1}, {"v": 2}]}"#).unwrap();
+        assert!(
+            Equivalence::new()
+                .ignore("/items/*/id")
+                .is_equivalent(&a, &b)
+        );
+    }
+
+    #[test]
+    fn test_unordered_arrays_as_multisets() {
+        let a = parse_json(r#"{"tags": ["x", "y", "x"]}"#).unwrap();
+        let b = parse_json(r#"{"tags": ["x", "x", "y"]}"#).unwrap();
+        let c = parse_json(r#"{"tags": ["x", "y", "y"]}"#).unwrap();
+        let rules = Equivalence::new().unordered("/tags");
+        assert!(rules.is_equivalent(&a, &b));
+        assert!(!Equivalence::new().is_equivalent(&a, &b));
+
+        let differences = rules.compare(&a, &c).unwrap_err();
+        assert_eq!(differences.len(), 2);
+    }
+
+    #[test]
+    fn test_unordered_pairs_beyond_first_match() {
+        // 1.0 matches both actual elements within the tolerance, but only
+        // pairing it with 0.96 leaves 1.04 for 1.05.
+        let actual = parse_json("[1.04, 0.96]").unwrap();
+        let expected = parse_json("[1.0, 1.05]").unwrap();
+        let rules = Equivalence::new().unordered("").tolerance(0.05);
+        assert!(rules.is_equivalent(&actual, &expected));
+
+        let actual = parse_json("[1.04, 0.96, 3, 1.0]").unwrap();
+        let expected = parse_json("[1.0, 1.05, 2, 0.98]").unwrap();
+        let differences = rules.compare(&actual, &expected).unwrap_err();
+        let messages: Vec<String> = differences.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            ["/2: no element matching 2", "/2: unexpected element 3"]
+        );
+    }
+
+    #[test]
+    fn test_numeric_tolerance() {
+        let a = JsonValue::Number((0.1 + 0.2).into());
+        let b = JsonValue::Number(0.3.into());
+        assert!(!Equivalence::new().is_equivalent(&a, &b));
+        assert!(Equivalence::new().tolerance(1e-9).is_equivalent(&a, &b));
+    }
+
+    #[test]
+    fn test_timestamp_fuzzing() {
+        let a = parse_json(r#"{"at": "2024-03-01T10:00:03Z"}"#).unwrap();
+        let b = parse_json(r#"{"at": "2024-03-01T12:00:00+02:00"}"#).unwrap();
+        let rules = Equivalence::new().timestamp("/at", Duration::from_secs(5));
+        assert!(rules.is_equivalent(&a, &b));
+
+        let strict = Equivalence::new().timestamp("/at", Duration::from_secs(1));
+        assert!(!strict.is_equivalent(&a, &b));
+    }
+
+    #[test]
+    fn test_parse_rfc3339() {
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), Some(0.0));
+        assert_eq!(parse_rfc3339("2000-03-01T00:00:00Z"), Some(951_868_800.0));
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:01.5Z"), Some(1.5));
+        assert_eq!(parse_rfc3339("1970-01-01T01:00:00+01:00"), Some(0.0));
+        assert_eq!(parse_rfc3339("not a date"), None);
+        assert_eq!(parse_rfc3339("2024-13-01T00:00:00Z"), None);
+        assert_eq!(parse_rfc3339("2024-01-01T00:00:00+€00"), None);
+        assert_eq!(parse_rfc3339("2024-01-01T00:00:00+1:000"), None);
+    }
+
+    #[test]
+    fn test_assert_equivalent_macro_passes() {
+        let a = parse_json(r#"{"b": 1, "a": 2}"#).unwrap();
+        let b = parse_json(r#"{"a": 2, "b": 1}"#).unwrap();
+        crate::assert_equivalent!(a, b);
+    }
+
+    #[test]
+    #[should_panic(expected = "/name: expected \"b\", found \"a\"")]
+    fn test_assert_equivalent_macro_panics() {
+        let a = parse_json(r#"{"name": "a"}"#).unwrap();
+        let b = parse_json(r#"{"name": "b"}"#).unwrap();
+        crate::assert_equivalent!(a, b);
+    }
+}
@@ -3,19 +3,27 @@
 //! This module defines [`JsonError`](crate::error::JsonError), the error enum
 //! returned by the tokenizer and parser when they encounter invalid JSON input.
 //! Every variant carries a `position` field indicating the byte offset in the
-//! input where the error was detected, making it straightforward to report
-//! precise error locations to the caller.
+//! input where the error was detected, along with the matching `line` and
+//! `column`, making it straightforward to report precise error locations to
+//! the caller.
 
 use std::error;
 use std::fmt;
+use std::fmt::Write;
+use std::io;
+use std::sync::Arc;
+
+use crate::limits::Limit;
+use crate::path::{JsonPath, Segment};
+use crate::tokenizer::{describe_whitespace, is_unicode_whitespace};
 
 /// Represents errors that can occur during JSON tokenization and parsing.
 ///
-/// `JsonError` is an enum with five variants, each describing a different
-/// category of failure. All variants carry a `position` field that records
-/// the byte offset in the input string where the error was detected, which
-/// is included in the human-readable message produced by the `Display`
-/// implementation.
+/// `JsonError` is an enum with thirteen variants, each describing a different
+/// kind of failure. All variants carry a `position` field that records
+/// the byte offset in the input string where the error was detected, plus
+/// the 1-based `line` and `column` of that offset. Both are included in the
+/// human-readable message produced by the `Display` implementation.
 ///
 /// This type implements both `Display` and `std::error::Error`, so it can
 /// be used with the `?` operator and integrated into broader error-handling
@@ -36,6 +44,12 @@ pub enum JsonError {
         found: String,
         /// Byte position in the input where the unexpected token was encountered.
         position: usize,
+        /// 1-based line of `position`, or 0 if the source text was not
+        /// available (e.g. when parsing pre-built tokens).
+        line: usize,
+        /// 1-based column of `position`, counted in characters, or 0 if
+        /// the source text was not available.
+        column: usize,
     },
     /// The JSON input ended before the parser finished reading a complete value.
     ///
@@ -49,6 +63,12 @@ pub enum JsonError {
         expected: String,
         /// Byte position in the input where the end of input was reached.
         position: usize,
+        /// 1-based line of `position`, or 0 if the source text was not
+        /// available (e.g. when parsing pre-built tokens).
+        line: usize,
+        /// 1-based column of `position`, counted in characters, or 0 if
+        /// the source text was not available.
+        column: usize,
     },
     /// A numeric literal in the JSON could not be parsed as a valid number.
     ///
@@ -62,6 +82,12 @@ pub enum JsonError {
         value: String,
         /// Byte position in the input where the invalid number starts.
         position: usize,
+        /// 1-based line of `position`, or 0 if the source text was not
+        /// available (e.g. when parsing pre-built tokens).
+        line: usize,
+        /// 1-based column of `position`, counted in characters, or 0 if
+        /// the source text was not available.
+        column: usize,
     },
     /// An invalid escape sequence was found inside a JSON string.
     ///
@@ -76,6 +102,12 @@ pub enum JsonError {
         /// Byte position in the input where the backslash of the invalid
         /// escape sequence is located.
         position: usize,
+        /// 1-based line of `position`, or 0 if the source text was not
+        /// available (e.g. when parsing pre-built tokens).
+        line: usize,
+        /// 1-based column of `position`, counted in characters, or 0 if
+        /// the source text was not available.
+        column: usize,
     },
     /// A `\uXXXX` unicode escape sequence contains invalid hexadecimal digits.
     ///
@@ -89,52 +121,1214 @@ pub enum JsonError {
         sequence: String,
         /// Byte position in the input where the `\u` escape begins.
         position: usize,
+        /// 1-based line of `position`, or 0 if the source text was not
+        /// available (e.g. when parsing pre-built tokens).
+        line: usize,
+        /// 1-based column of `position`, counted in characters, or 0 if
+        /// the source text was not available.
+        column: usize,
+    },
+    /// A `\uXXXX` escape encodes half of a UTF-16 surrogate pair without
+    /// the other half.
+    ///
+    /// Characters outside the Basic Multilingual Plane are escaped as a
+    /// high surrogate followed by a low one, as in `"\uD83D\uDE00"`. A
+    /// high surrogate not followed by a low surrogate escape, or a low
+    /// surrogate on its own, has no character to decode to.
+    UnpairedSurrogate {
+        /// The surrogate code unit that was found.
+        code_unit: u16,
+        /// Byte position in the input of the unpaired escape's hex digits.
+        position: usize,
+        /// 1-based line of `position`, or 0 if unknown.
+        line: usize,
+        /// 1-based column of `position`, counted in characters, or 0 if
+        /// unknown.
+        column: usize,
+    },
+    /// An object contains the same key twice and the parser was configured
+    /// to reject duplicates.
+    ///
+    /// Only reported with
+    /// [`DuplicateKeys::Error`](crate::parser::DuplicateKeys::Error); by
+    /// default a duplicate key is a warning and the last value wins.
+    DuplicateKey {
+        /// The repeated key.
+        key: String,
+        /// Byte position in the input of the second occurrence of the key.
+        position: usize,
+        /// 1-based line of `position`, or 0 if the source text was not
+        /// available (e.g. when parsing pre-built tokens).
+        line: usize,
+        /// 1-based column of `position`, counted in characters, or 0 if
+        /// the source text was not available.
+        column: usize,
+    },
+    /// Byte input is not valid UTF-8.
+    ///
+    /// Reported when parsing bytes with
+    /// [`parse_bytes`](crate::parser::parse_bytes) or reading strings
+    /// through a [`ReadTokenizer`](crate::reader::ReadTokenizer), unless
+    /// lossy decoding is enabled.
+    InvalidUtf8 {
+        /// Byte offset in the input of the first byte that is not part of
+        /// a valid UTF-8 sequence. The same value as
+        /// [`position`](JsonError::position).
+        byte_offset: usize,
+        /// 1-based line of `byte_offset`, or 0 if unknown.
+        line: usize,
+        /// 1-based column of `byte_offset`, counted in characters, or 0
+        /// if unknown.
+        column: usize,
+    },
+    /// Arrays and objects are nested more deeply than the parser allows.
+    ///
+    /// The limit is set with
+    /// [`JsonParser::max_depth`](crate::parser::JsonParser::max_depth) and
+    /// keeps hostile input such as `[[[[...]]]]` from exhausting the stack.
+    DepthLimitExceeded {
+        /// The configured maximum nesting depth.
+        max_depth: usize,
+        /// Byte position in the input of the bracket or brace that would
+        /// exceed the limit.
+        position: usize,
+        /// 1-based line of `position`, or 0 if the source text was not
+        /// available (e.g. when parsing pre-built tokens).
+        line: usize,
+        /// 1-based column of `position`, counted in characters, or 0 if
+        /// the source text was not available.
+        column: usize,
+    },
+    /// A string token is longer than the tokenizer allows.
+    ///
+    /// The limit is set with
+    /// [`Tokenizer::max_string_len`](crate::tokenizer::Tokenizer::max_string_len)
+    /// and stops a single huge string from being buffered in full.
+    StringTooLong {
+        /// The configured maximum length in bytes of the source text
+        /// between the quotes.
+        max_len: usize,
+        /// Byte position in the input of the opening quote.
+        position: usize,
+        /// 1-based line of `position`, or 0 if unknown.
+        line: usize,
+        /// 1-based column of `position`, counted in characters, or 0 if
+        /// unknown.
+        column: usize,
+    },
+    /// A number token is longer than the tokenizer allows.
+    ///
+    /// The limit is set with
+    /// [`Tokenizer::max_number_len`](crate::tokenizer::Tokenizer::max_number_len).
+    NumberTooLong {
+        /// The configured maximum length in bytes of the number's source
+        /// text.
+        max_len: usize,
+        /// Byte position in the input of the number's first byte.
+        position: usize,
+        /// 1-based line of `position`, or 0 if unknown.
+        line: usize,
+        /// 1-based column of `position`, counted in characters, or 0 if
+        /// unknown.
+        column: usize,
+    },
+    /// A value breaks one of the limits set for its path.
+    ///
+    /// The limits are set with
+    /// [`JsonParser::path_limits`](crate::parser::JsonParser::path_limits).
+    PathLimitExceeded {
+        /// The JSON Pointer of the value.
+        path: String,
+        /// The limit the value breaks.
+        limit: Limit,
+        /// Byte position in the input of the string, or of the first
+        /// element or member past the limit.
+        position: usize,
+        /// 1-based line of `position`, or 0 if the source text was not
+        /// available (e.g. when parsing pre-built tokens).
+        line: usize,
+        /// 1-based column of `position`, counted in characters, or 0 if
+        /// the source text was not available.
+        column: usize,
+    },
+    /// Reading the input failed.
+    ///
+    /// Built by converting an [`io::Error`] that does not wrap a
+    /// `JsonError`, e.g. one returned by
+    /// [`parse_from_reader`](crate::parser::parse_from_reader) when the
+    /// reader itself fails. [`source`](error::Error::source) returns the
+    /// underlying error.
+    Io {
+        /// The error the reader returned.
+        error: IoError,
+        /// Byte position in the input where reading failed, or 0 if
+        /// unknown.
+        position: usize,
+        /// 1-based line of `position`, or 0 if unknown.
+        line: usize,
+        /// 1-based column of `position`, counted in characters, or 0 if
+        /// unknown.
+        column: usize,
     },
 }
 
+/// An [`io::Error`] held by [`JsonError::Io`].
+///
+/// `io::Error` is neither `Clone` nor `PartialEq`, so it is shared behind
+/// an [`Arc`]. Two `IoError`s are equal if they have the same
+/// [`io::ErrorKind`] and message.
+#[derive(Debug, Clone)]
+pub struct IoError(Arc<io::Error>);
+
+impl IoError {
+    /// Returns the underlying error.
+    pub fn get_ref(&self) -> &io::Error {
+        &self.0
+    }
+
+    /// Returns the underlying error's kind.
+    pub fn kind(&self) -> io::ErrorKind {
+        self.0.kind()
+    }
+
+    /// Returns the underlying error, or a copy with the same kind and
+    /// message if it is still shared with a clone.
+    pub fn into_inner(self) -> io::Error {
+        Arc::try_unwrap(self.0)
+            .unwrap_or_else(|shared| io::Error::new(shared.kind(), shared.to_string()))
+    }
+}
+
+impl From<io::Error> for IoError {
+    fn from(error: io::Error) -> Self {
+        IoError(Arc::new(error))
+    }
+}
+
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+            || (self.kind() == other.kind() && self.0.to_string() == other.0.to_string())
+    }
+}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
 impl fmt::Display for JsonError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = Location(self);
         match self {
             JsonError::UnexpectedToken {
-                expected,
-                found,
-                position,
+                expected, found, ..
             } => {
                 write!(
                     f,
-                    "Unexpected token at position {}: expected {}, found {}",
-                    position, expected, found
+                    "Unexpected token at {}: expected {}, found {}",
+                    location, expected, found
+                )
+            }
+            JsonError::UnexpectedEndOfInput { expected, .. } => {
+                write!(
+                    f,
+                    "Unexpected end of input at {}: expected {}",
+                    location, expected
+                )
+            }
+            JsonError::InvalidNumber { value, .. } => {
+                write!(f, "Invalid number at {}: {}", location, value)
+            }
+            JsonError::InvalidEscape { char, .. } => {
+                write!(f, "Invalid escape sequence '\\{}' at {}", char, location)
+            }
+            JsonError::InvalidUnicode { sequence, .. } => {
+                write!(
+                    f,
+                    "Invalid unicode escape '\\u{}' at {}",
+                    sequence, location
                 )
             }
-            JsonError::UnexpectedEndOfInput { expected, position } => {
+            JsonError::UnpairedSurrogate { code_unit, .. } => {
                 write!(
                     f,
-                    "Unexpected end of input at position {}: expected {}",
-                    position, expected
+                    "Unpaired surrogate '\\u{:04X}' at {}",
+                    code_unit, location
                 )
             }
-            JsonError::InvalidNumber { value, position } => {
-                write!(f, "Invalid number at position {}: {}", position, value)
+            JsonError::DuplicateKey { key, .. } => {
+                write!(f, "Duplicate key {:?} at {}", key, location)
             }
-            JsonError::InvalidEscape { char, position } => {
+            JsonError::InvalidUtf8 { .. } => {
+                write!(f, "Invalid UTF-8 at {}", location)
+            }
+            JsonError::DepthLimitExceeded { max_depth, .. } => {
                 write!(
                     f,
-                    "Invalid escape sequence '\\{}' at position {}",
-                    char, position
+                    "Nesting deeper than {} levels at {}",
+                    max_depth, location
                 )
             }
-            JsonError::InvalidUnicode { sequence, position } => {
+            JsonError::StringTooLong { max_len, .. } => {
+                write!(f, "String longer than {} bytes at {}", max_len, location)
+            }
+            JsonError::NumberTooLong { max_len, .. } => {
+                write!(f, "Number longer than {} bytes at {}", max_len, location)
+            }
+            JsonError::PathLimitExceeded { path, limit, .. } => {
                 write!(
                     f,
-                    "Invalid unicode escape '\\u{}' at position {}",
-                    sequence, position
+                    "Value at {:?} has more than {} at {}",
+                    path, limit, location
                 )
             }
+            JsonError::Io { error, .. } => write!(f, "Failed to read input: {}", error),
         }
     }
 }
 
-impl error::Error for JsonError {}
+/// Converts a byte offset into a 1-based line and character column.
+///
+/// Offsets past the end of `source` are clamped to the end.
+pub(crate) fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let position = SourcePosition::from_byte_offset(source, offset);
+    (position.line, position.column)
+}
+
+/// A position in source text, measured in every unit tools disagree on.
+///
+/// Errors report byte offsets and character columns, but editors count
+/// differently: the Language Server Protocol and VS Code measure columns
+/// in UTF-16 code units, so a column after an emoji is one higher than
+/// its character count. Lines and columns are 1-based like
+/// [`JsonError::line`]; subtract one for LSP's 0-based `Position`.
+///
+/// Offsets past the end of the source clamp to the end, and offsets inside
+/// a character (or inside a UTF-16 surrogate pair) move back to its start.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::error::SourcePosition;
+/// use rust_json_parser::parser::parse_json;
+///
+/// let source = "{\"emoji\": \"😀\", @}";
+/// let err = parse_json(source).unwrap_err();
+/// assert_eq!((err.position(), err.column()), (18, 16));
+///
+/// let pos = err.source_position(source);
+/// assert_eq!((pos.byte, pos.char, pos.utf16), (18, 15, 16));
+/// assert_eq!((pos.line, pos.column, pos.utf16_column), (1, 16, 17));
+///
+/// // Map an editor position back to a byte offset.
+/// let from_editor = SourcePosition::from_line_utf16(source, 1, 17).unwrap();
+/// assert_eq!(from_editor, pos);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourcePosition {
+    /// Offset in bytes from the start of the source.
+    pub byte: usize,
+    /// Offset in characters (Unicode scalar values).
+    pub char: usize,
+    /// Offset in UTF-16 code units.
+    pub utf16: usize,
+    /// 1-based line.
+    pub line: usize,
+    /// 1-based column in characters.
+    pub column: usize,
+    /// 1-based column in UTF-16 code units.
+    pub utf16_column: usize,
+}
+
+impl SourcePosition {
+    /// Locates a byte offset.
+    pub fn from_byte_offset(source: &str, offset: usize) -> Self {
+        let mut end = offset.min(source.len());
+        while !source.is_char_boundary(end) {
+            end -= 1;
+        }
+        let before = &source[..end];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let in_line = &before[line_start..];
+        Self {
+            byte: end,
+            char: before.chars().count(),
+            utf16: utf16_len(before),
+            line: before.bytes().filter(|&b| b == b'\n').count() + 1,
+            column: in_line.chars().count() + 1,
+            utf16_column: utf16_len(in_line) + 1,
+        }
+    }
+
+    /// Locates an offset counted in characters.
+    pub fn from_char_offset(source: &str, offset: usize) -> Self {
+        let byte = source
+            .char_indices()
+            .nth(offset)
+            .map_or(source.len(), |(i, _)| i);
+        Self::from_byte_offset(source, byte)
+    }
+
+    /// Locates an offset counted in UTF-16 code units.
+    pub fn from_utf16_offset(source: &str, offset: usize) -> Self {
+        Self::from_byte_offset(source, utf16_to_byte(source, offset))
+    }
+
+    /// Locates a 1-based line and 1-based UTF-16 column, as reported by an
+    /// editor. Columns past the end of the line clamp to its end.
+    ///
+    /// Returns `None` if `line` is 0 or past the last line.
+    pub fn from_line_utf16(source: &str, line: usize, utf16_column: usize) -> Option<Self> {
+        let start = match line {
+            0 => return None,
+            1 => 0,
+            _ => source.match_indices('\n').nth(line - 2)?.0 + 1,
+        };
+        let text = &source[start..];
+        let text = &text[..text.find('\n').unwrap_or(text.len())];
+        let offset = utf16_to_byte(text, utf16_column.saturating_sub(1));
+        Some(Self::from_byte_offset(source, start + offset))
+    }
+}
+
+fn utf16_len(s: &str) -> usize {
+    s.chars().map(char::len_utf16).sum()
+}
+
+// Byte offset of the character containing UTF-16 offset `offset`, or the
+// end of `s`.
+fn utf16_to_byte(s: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in s.char_indices() {
+        units += c.len_utf16();
+        if units > offset {
+            return i;
+        }
+    }
+    s.len()
+}
+
+// Renders "line 3, column 7 (position 42)", or "position 42" when the
+// error was never located against its source text.
+struct Location<'a>(&'a JsonError);
+
+impl fmt::Display for Location<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let error = self.0;
+        if error.line() == 0 {
+            write!(f, "position {}", error.position())
+        } else {
+            write!(
+                f,
+                "line {}, column {} (position {})",
+                error.line(),
+                error.column(),
+                error.position()
+            )
+        }
+    }
+}
+
+impl error::Error for JsonError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            JsonError::Io { error, .. } => Some(error.get_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// A coarse, stable classification of [`JsonError`] variants.
+///
+/// Match on the category rather than on individual variants when only the
+/// broad cause matters, e.g. to decide whether to wait for more input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    /// The input is not well-formed JSON (an unexpected token).
+    Syntax,
+    /// The input ended before a complete value was read.
+    Eof,
+    /// A literal is malformed (an invalid number, escape, or unicode
+    /// sequence, or an unpaired surrogate), the input is not UTF-8, an
+    /// object repeats a key, or nesting or a token's length exceeds its
+    /// limit.
+    Data,
+    /// Reading the input failed.
+    Io,
+}
+
+/// A machine-readable code for each [`JsonError`] variant, plus
+/// [`Io`](JsonErrorKind::Io) for read failures.
+///
+/// Reader-based parsing such as
+/// [`parse_from_reader`](crate::parser::parse_from_reader) returns
+/// [`io::Error`], wrapping the [`JsonError`] when the input itself is at
+/// fault. Converting that `io::Error` with `JsonErrorKind::from` finds the
+/// wrapped error's kind, or gives `Io` when reading failed, so callers can
+/// branch on either source without downcasting or matching on messages.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+///
+/// use rust_json_parser::error::JsonErrorKind;
+/// use rust_json_parser::parser::{parse_from_reader, parse_json};
+///
+/// let err = parse_json(r#"{"a": 1, "a": 2"#).unwrap_err();
+/// assert_eq!(err.kind(), JsonErrorKind::UnexpectedEndOfInput);
+/// assert_eq!(err.kind().as_str(), "unexpected_end_of_input");
+///
+/// let err = parse_from_reader(&b"[1, 2,, 3]"[..]).unwrap_err();
+/// assert_eq!(JsonErrorKind::from(&err), JsonErrorKind::UnexpectedToken);
+///
+/// let err = io::Error::new(io::ErrorKind::ConnectionReset, "gone");
+/// assert_eq!(JsonErrorKind::from(&err), JsonErrorKind::Io);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JsonErrorKind {
+    /// [`JsonError::UnexpectedToken`].
+    UnexpectedToken,
+    /// [`JsonError::UnexpectedEndOfInput`].
+    UnexpectedEndOfInput,
+    /// [`JsonError::InvalidNumber`].
+    InvalidNumber,
+    /// [`JsonError::InvalidEscape`].
+    InvalidEscape,
+    /// [`JsonError::InvalidUnicode`].
+    InvalidUnicode,
+    /// [`JsonError::UnpairedSurrogate`].
+    UnpairedSurrogate,
+    /// [`JsonError::DuplicateKey`].
+    DuplicateKey,
+    /// [`JsonError::InvalidUtf8`].
+    InvalidUtf8,
+    /// [`JsonError::DepthLimitExceeded`].
+    DepthLimitExceeded,
+    /// [`JsonError::StringTooLong`].
+    StringTooLong,
+    /// [`JsonError::NumberTooLong`].
+    NumberTooLong,
+    /// [`JsonError::PathLimitExceeded`].
+    PathLimitExceeded,
+    /// [`JsonError::Io`], or an [`io::Error`] that wraps no
+    /// [`JsonError`].
+    Io,
+}
+
+impl JsonErrorKind {
+    /// Returns the kind as a stable `snake_case` code, suitable for logs
+    /// and API responses.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JsonErrorKind::UnexpectedToken => "unexpected_token",
+            JsonErrorKind::UnexpectedEndOfInput => "unexpected_end_of_input",
+            JsonErrorKind::InvalidNumber => "invalid_number",
+            JsonErrorKind::InvalidEscape => "invalid_escape",
+            JsonErrorKind::InvalidUnicode => "invalid_unicode",
+            JsonErrorKind::UnpairedSurrogate => "unpaired_surrogate",
+            JsonErrorKind::DuplicateKey => "duplicate_key",
+            JsonErrorKind::InvalidUtf8 => "invalid_utf8",
+            JsonErrorKind::DepthLimitExceeded => "depth_limit_exceeded",
+            JsonErrorKind::StringTooLong => "string_too_long",
+            JsonErrorKind::NumberTooLong => "number_too_long",
+            JsonErrorKind::PathLimitExceeded => "path_limit_exceeded",
+            JsonErrorKind::Io => "io",
+        }
+    }
+}
+
+impl fmt::Display for JsonErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Finds the kind of the [`JsonError`] wrapped in `error`, or
+/// [`JsonErrorKind::Io`] if it wraps none.
+impl From<&io::Error> for JsonErrorKind {
+    fn from(error: &io::Error) -> Self {
+        error
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<JsonError>())
+            .map_or(JsonErrorKind::Io, JsonError::kind)
+    }
+}
+
+impl JsonError {
+    /// Returns the byte offset in the input where the error was detected.
+    pub fn position(&self) -> usize {
+        self.location().0
+    }
+
+    /// Returns the 1-based line of the error, or 0 if unknown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let err = parse_json("{\n  \"a\": 1,\n  \"b\": @\n}").unwrap_err();
+    /// assert_eq!((err.line(), err.column()), (3, 8));
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "Unexpected token at line 3, column 8 (position 19): expected valid JSON token, found @"
+    /// );
+    /// ```
+    pub fn line(&self) -> usize {
+        self.location().1
+    }
+
+    /// Returns the 1-based column of the error in characters, or 0 if
+    /// unknown.
+    pub fn column(&self) -> usize {
+        self.location().2
+    }
+
+    /// Locates the error within `source`, the text it was parsed from, in
+    /// characters and UTF-16 code units as well as bytes.
+    ///
+    /// See [`SourcePosition`] for an example.
+    pub fn source_position(&self, source: &str) -> SourcePosition {
+        SourcePosition::from_byte_offset(source, self.position())
+    }
+
+    fn location(&self) -> (usize, usize, usize) {
+        match *self {
+            JsonError::UnexpectedToken {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::UnexpectedEndOfInput {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::InvalidNumber {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::InvalidEscape {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::InvalidUnicode {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::UnpairedSurrogate {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::DuplicateKey {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::InvalidUtf8 {
+                byte_offset: position,
+                line,
+                column,
+            }
+            | JsonError::DepthLimitExceeded {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::StringTooLong {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::NumberTooLong {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::PathLimitExceeded {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::Io {
+                position,
+                line,
+                column,
+                ..
+            } => (position, line, column),
+        }
+    }
+
+    fn location_mut(&mut self) -> (&mut usize, &mut usize, &mut usize) {
+        match self {
+            JsonError::UnexpectedToken {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::UnexpectedEndOfInput {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::InvalidNumber {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::InvalidEscape {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::InvalidUnicode {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::UnpairedSurrogate {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::DuplicateKey {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::InvalidUtf8 {
+                byte_offset: position,
+                line,
+                column,
+            }
+            | JsonError::DepthLimitExceeded {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::StringTooLong {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::NumberTooLong {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::PathLimitExceeded {
+                position,
+                line,
+                column,
+                ..
+            }
+            | JsonError::Io {
+                position,
+                line,
+                column,
+                ..
+            } => (position, line, column),
+        }
+    }
+
+    /// Replaces the position, e.g. to turn a token index into a byte
+    /// offset or to shift an offset within a sub-slice.
+    pub(crate) fn reposition(mut self, offset: usize) -> Self {
+        *self.location_mut().0 = offset;
+        self
+    }
+
+    /// Sets the line and column directly, for sources that track them
+    /// while reading instead of keeping the text around.
+    pub(crate) fn at_line_column(mut self, line: usize, column: usize) -> Self {
+        let (_, l, c) = self.location_mut();
+        (*l, *c) = (line, column);
+        self
+    }
+
+    /// Builds an [`InvalidUtf8`](JsonError::InvalidUtf8) error for
+    /// `bytes`, whose first `valid_up_to` bytes are valid UTF-8, located
+    /// within that valid prefix.
+    pub(crate) fn invalid_utf8(bytes: &[u8], valid_up_to: usize) -> Self {
+        let prefix = std::str::from_utf8(&bytes[..valid_up_to]).unwrap_or_default();
+        let (line, column) = line_column(prefix, valid_up_to);
+        JsonError::InvalidUtf8 {
+            byte_offset: valid_up_to,
+            line,
+            column,
+        }
+    }
+
+    /// Fills in the line and column of the byte position within `source`.
+    pub(crate) fn locate(mut self, source: &str) -> Self {
+        let (position, line, column) = self.location_mut();
+        (*line, *column) = line_column(source, *position);
+        self
+    }
+
+    /// Returns the machine-readable kind of this error, one per variant.
+    pub fn kind(&self) -> JsonErrorKind {
+        match self {
+            JsonError::UnexpectedToken { .. } => JsonErrorKind::UnexpectedToken,
+            JsonError::UnexpectedEndOfInput { .. } => JsonErrorKind::UnexpectedEndOfInput,
+            JsonError::InvalidNumber { .. } => JsonErrorKind::InvalidNumber,
+            JsonError::InvalidEscape { .. } => JsonErrorKind::InvalidEscape,
+            JsonError::InvalidUnicode { .. } => JsonErrorKind::InvalidUnicode,
+            JsonError::UnpairedSurrogate { .. } => JsonErrorKind::UnpairedSurrogate,
+            JsonError::DuplicateKey { .. } => JsonErrorKind::DuplicateKey,
+            JsonError::InvalidUtf8 { .. } => JsonErrorKind::InvalidUtf8,
+            JsonError::DepthLimitExceeded { .. } => JsonErrorKind::DepthLimitExceeded,
+            JsonError::StringTooLong { .. } => JsonErrorKind::StringTooLong,
+            JsonError::NumberTooLong { .. } => JsonErrorKind::NumberTooLong,
+            JsonError::PathLimitExceeded { .. } => JsonErrorKind::PathLimitExceeded,
+            JsonError::Io { .. } => JsonErrorKind::Io,
+        }
+    }
+
+    /// Returns the broad category of this error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::error::Category;
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let err = parse_json("[1, 2").unwrap_err();
+    /// assert_eq!(err.category(), Category::Eof);
+    /// ```
+    pub fn category(&self) -> Category {
+        match self {
+            JsonError::UnexpectedToken { .. } => Category::Syntax,
+            JsonError::UnexpectedEndOfInput { .. } => Category::Eof,
+            JsonError::InvalidNumber { .. }
+            | JsonError::InvalidEscape { .. }
+            | JsonError::InvalidUnicode { .. }
+            | JsonError::UnpairedSurrogate { .. }
+            | JsonError::DuplicateKey { .. }
+            | JsonError::InvalidUtf8 { .. }
+            | JsonError::DepthLimitExceeded { .. }
+            | JsonError::StringTooLong { .. }
+            | JsonError::NumberTooLong { .. }
+            | JsonError::PathLimitExceeded { .. } => Category::Data,
+            JsonError::Io { .. } => Category::Io,
+        }
+    }
+}
+
+/// Renders `error` the way a compiler would: the message, the offending
+/// line of `source` with carets under the error, and a hint when the
+/// mistake is a common one.
+///
+/// `source` must be the text the error came from; the error's position is
+/// located in it afresh, so errors that were never located work too. Very
+/// long lines, such as minified documents, are cut down to a window
+/// around the error.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::error::render;
+/// use rust_json_parser::parser::parse_json;
+///
+/// let source = "{\n  name: \"Ada\"\n}";
+/// let err = parse_json(source).unwrap_err();
+/// let rendered = render(&err, source);
+/// assert_eq!(
+///     rendered.lines().collect::<Vec<_>>(),
+///     [
+///         "error: Unexpected token at line 2, column 3 (position 4): expected valid JSON token, found name",
+///         "  |",
+///         "2 |   name: \"Ada\"",
+///         "  |   ^^^^",
+///         "  = help: did you mean to quote this key? `\"name\"`",
+///     ]
+/// );
+/// ```
+pub fn render(error: &JsonError, source: &str) -> String {
+    let at = SourcePosition::from_byte_offset(source, error.position());
+    let line_start = source[..at.byte].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[at.byte..]
+        .find('\n')
+        .map_or(source.len(), |i| at.byte + i);
+    let line = source[line_start..line_end].trim_end_matches('\r');
+    let width = underline_width(error, &source[at.byte..line_end]);
+
+    // Cut long lines down to a window around the error.
+    let chars: Vec<char> = line.chars().collect();
+    let column = at.column - 1;
+    let start = column
+        .saturating_sub(SNIPPET_WIDTH / 2)
+        .min(chars.len().saturating_sub(SNIPPET_WIDTH));
+    let end = chars.len().min(start + SNIPPET_WIDTH);
+    let mut shown: String = chars[start..end].iter().collect();
+    let mut indent: String = chars[start..column.min(end)]
+        .iter()
+        .map(|&c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    if start > 0 {
+        shown.insert_str(0, "...");
+        indent.insert_str(0, "   ");
+    }
+    if end < chars.len() {
+        shown.push_str("...");
+    }
+    let carets = width.min(end.saturating_sub(column)).max(1);
+
+    let number = at.line.to_string();
+    let gutter = " ".repeat(number.len());
+    let mut out = format!("error: {}\n", error);
+    let _ = writeln!(out, "{} |", gutter);
+    let _ = writeln!(out, "{} | {}", number, shown);
+    let _ = writeln!(out, "{} | {}{}", gutter, indent, "^".repeat(carets));
+    if let Some(hint) = hint(error, source) {
+        let _ = writeln!(out, "{} = help: {}", gutter, hint);
+    }
+    out
+}
+
+// Characters of the offending line shown around the error.
+const SNIPPET_WIDTH: usize = 80;
+
+// How many characters to underline, given the rest of the error's line.
+fn underline_width(error: &JsonError, rest: &str) -> usize {
+    match error {
+        JsonError::InvalidNumber { value, .. } if rest.starts_with(value.as_str()) => {
+            value.chars().count()
+        }
+        JsonError::UnexpectedToken { .. } => {
+            let word = rest
+                .chars()
+                .take_while(|&c| c.is_alphanumeric() || "_.+-".contains(c))
+                .count();
+            word.max(1)
+        }
+        _ => 1,
+    }
+}
+
+// Suggests a fix for the mistakes people make most often when writing
+// JSON by hand, judging by the error and the text around it.
+fn hint(error: &JsonError, source: &str) -> Option<String> {
+    let position = error.position().min(source.len());
+    let before = source.get(..position).unwrap_or("").trim_end();
+    let rest = source.get(position..).unwrap_or("");
+    let hint = match error {
+        JsonError::UnexpectedToken { expected, .. } => {
+            let word: String = rest
+                .chars()
+                .take_while(|&c| c.is_alphanumeric() || c == '_')
+                .collect();
+            let after_word = rest[word.len()..].trim_start();
+            match (expected.as_str(), rest.chars().next()?) {
+                (_, '\'') => "JSON strings and keys are quoted with double quotes".to_string(),
+                (_, '/') => "JSON does not allow comments".to_string(),
+                ("JSON value" | "string key", ']' | '}') if before.ends_with(',') => {
+                    "JSON does not allow a comma after the last element".to_string()
+                }
+                ("comma or closing bracket" | "comma or closing brace", _) => {
+                    "is a comma missing before this?".to_string()
+                }
+                ("colon", _) => "object keys are followed by a colon".to_string(),
+                ("end of input", _) => {
+                    "a document holds exactly one value; is a bracket closed twice?".to_string()
+                }
+                (_, c) if is_unicode_whitespace(c) => format!(
+                    "JSON allows only spaces, tabs, and line breaks here; \
+                     `JsonParser::unicode_whitespace` can skip this {}",
+                    describe_whitespace(c)
+                ),
+                (_, '.') => "numbers need a digit before the decimal point, e.g. `0.5`".to_string(),
+                _ if word.is_empty() => return None,
+                _ => match word.to_ascii_lowercase().as_str() {
+                    lower @ ("true" | "false" | "null") => {
+                        format!("JSON literals are lowercase: `{}`", lower)
+                    }
+                    "none" | "nil" | "undefined" => "JSON spells a missing value `null`".to_string(),
+                    "nan" | "infinity" => {
+                        "JSON has no NaN or Infinity; use `null` or a string".to_string()
+                    }
+                    _ if after_word.starts_with(':') => {
+                        format!("did you mean to quote this key? `\"{}\"`", word)
+                    }
+                    _ => format!("did you mean to quote this string? `\"{}\"`", word),
+                },
+            }
+        }
+        JsonError::UnexpectedEndOfInput { expected, .. } => match expected.as_str() {
+            "closing quote" => "this string is never closed".to_string(),
+            "end of comment" => "this comment is never closed".to_string(),
+            _ if before.is_empty() => "the input is empty".to_string(),
+            _ => "the input ends before every `[` and `{` is closed".to_string(),
+        },
+        JsonError::InvalidNumber { value, .. } => {
+            let digits = value.strip_prefix('-').unwrap_or(value);
+            if digits.len() > 1 && digits.starts_with('0') && digits.as_bytes()[1].is_ascii_digit() {
+                "JSON numbers cannot have leading zeros".to_string()
+            } else if digits.is_empty() {
+                "a minus sign must be followed by digits".to_string()
+            } else if digits.ends_with('.') || digits.contains(".e") || digits.contains(".E") {
+                "a decimal point must be followed by digits".to_string()
+            } else {
+                return None;
+            }
+        }
+        JsonError::InvalidEscape { .. } => "write a literal backslash as `\\\\`".to_string(),
+        JsonError::InvalidUnicode { .. } => {
+            "`\\u` must be followed by exactly four hex digits".to_string()
+        }
+        JsonError::UnpairedSurrogate { .. } => {
+            "characters outside the Basic Multilingual Plane are written as a pair, e.g. `\\uD83D\\uDE00`"
+                .to_string()
+        }
+        JsonError::DuplicateKey { .. } => "each key may appear only once in an object".to_string(),
+        JsonError::InvalidUtf8 { .. } => {
+            "`JsonParser::lossy_utf8` replaces invalid bytes instead of failing".to_string()
+        }
+        JsonError::DepthLimitExceeded { .. } => {
+            "raise the limit with `JsonParser::max_depth` if the nesting is intended".to_string()
+        }
+        JsonError::StringTooLong { .. } => {
+            "raise the limit with `JsonParser::max_string_len`".to_string()
+        }
+        JsonError::NumberTooLong { .. } => {
+            "raise the limit with `JsonParser::max_number_len`".to_string()
+        }
+        JsonError::PathLimitExceeded { .. } => {
+            "the limit is set for this path with `JsonParser::path_limits`".to_string()
+        }
+        JsonError::Io { .. } => return None,
+    };
+    Some(hint)
+}
+
+/// Converts into an [`io::Error`] so parse failures can be returned from
+/// `io::Result` functions with `?`.
+///
+/// [`Category::Eof`] maps to [`io::ErrorKind::UnexpectedEof`] and
+/// [`Category::Io`] gives back the wrapped read error; every other
+/// category maps to [`io::ErrorKind::InvalidData`]. The original error is
+/// kept as the inner error and can be recovered with
+/// [`io::Error::into_inner`] and `downcast`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+///
+/// use rust_json_parser::parser::parse_json;
+/// use rust_json_parser::value::JsonValue;
+///
+/// fn load(text: &str) -> io::Result<JsonValue> {
+///     Ok(parse_json(text)?)
+/// }
+///
+/// assert_eq!(load("{").unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+/// assert_eq!(load("@").unwrap_err().kind(), io::ErrorKind::InvalidData);
+/// ```
+impl From<JsonError> for io::Error {
+    fn from(error: JsonError) -> Self {
+        if let JsonError::Io { error, .. } = error {
+            return error.into_inner();
+        }
+        let kind = match error.category() {
+            Category::Eof => io::ErrorKind::UnexpectedEof,
+            Category::Syntax | Category::Data | Category::Io => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, error)
+    }
+}
+
+/// Converts an [`io::Error`], such as one returned by
+/// [`parse_from_reader`](crate::parser::parse_from_reader), so reader-based
+/// parsing can be used with `?` in functions returning `JsonError`.
+///
+/// If `error` wraps a `JsonError` that error is returned as is; otherwise
+/// the read failure becomes [`JsonError::Io`].
+///
+/// # Examples
+///
+/// ```
+/// use std::error::Error;
+/// use std::io;
+///
+/// use rust_json_parser::error::{JsonError, JsonErrorKind};
+/// use rust_json_parser::parser::parse_from_reader;
+/// use rust_json_parser::value::JsonValue;
+///
+/// fn load(reader: impl io::Read) -> Result<JsonValue, JsonError> {
+///     Ok(parse_from_reader(reader)?)
+/// }
+///
+/// let err = load(&b"[1, 2,, 3]"[..]).unwrap_err();
+/// assert_eq!(err.kind(), JsonErrorKind::UnexpectedToken);
+///
+/// struct Broken;
+/// impl io::Read for Broken {
+///     fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+///         Err(io::Error::new(io::ErrorKind::ConnectionReset, "gone"))
+///     }
+/// }
+/// let err = load(Broken).unwrap_err();
+/// assert_eq!(err.kind(), JsonErrorKind::Io);
+/// assert_eq!(err.to_string(), "Failed to read input: gone");
+/// let source = err.source().unwrap().downcast_ref::<io::Error>().unwrap();
+/// assert_eq!(source.kind(), io::ErrorKind::ConnectionReset);
+/// ```
+impl From<io::Error> for JsonError {
+    fn from(error: io::Error) -> Self {
+        if error.get_ref().is_some_and(|inner| inner.is::<JsonError>()) {
+            let inner = error.into_inner().expect("checked above");
+            return *inner.downcast::<JsonError>().expect("checked above");
+        }
+        JsonError::Io {
+            error: error.into(),
+            position: 0,
+            line: 0,
+            column: 0,
+        }
+    }
+}
+
+/// Errors returned by typed accessors such as
+/// [`JsonValue::get_as`](crate::value::JsonValue::get_as) and
+/// [`JsonValue::get_path_as`](crate::value::JsonValue::get_path_as).
+///
+/// Both variants carry the path that was requested, so a message like
+/// `expected integer at "user.age", found string` can be surfaced without
+/// further context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessError {
+    /// Nothing exists at the requested path.
+    Missing {
+        /// The path up to the first segment that could not be followed.
+        path: JsonPath,
+    },
+    /// A value exists at the path but cannot be converted to the
+    /// requested type.
+    TypeMismatch {
+        /// The path as given by the caller.
+        path: JsonPath,
+        /// The requested type (e.g., `"integer"`, `"string"`).
+        expected: &'static str,
+        /// The JSON type that was found (e.g., `"number"`, `"null"`).
+        found: &'static str,
+    },
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessError::Missing { path } => write!(f, "No value at {:?}", path.to_dotted()),
+            AccessError::TypeMismatch {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Expected {} at {:?}, found {}",
+                expected,
+                path.to_dotted(),
+                found
+            ),
+        }
+    }
+}
+
+impl error::Error for AccessError {}
+
+impl AccessError {
+    /// Prefixes the error's path with `segment`, for errors raised while
+    /// converting a child value.
+    pub(crate) fn within(self, segment: impl Into<Segment>) -> Self {
+        let prefix = |path: JsonPath| {
+            std::iter::once(segment.into())
+                .chain(path.segments().iter().cloned())
+                .collect()
+        };
+        match self {
+            AccessError::Missing { path } => AccessError::Missing { path: prefix(path) },
+            AccessError::TypeMismatch {
+                path,
+                expected,
+                found,
+            } => AccessError::TypeMismatch {
+                path: prefix(path),
+                expected,
+                found,
+            },
+        }
+    }
+}
+
+/// Errors returned by
+/// [`JsonValue::try_into_int`](crate::value::JsonValue::try_into_int).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntConversionError {
+    /// The value is not a number.
+    NotANumber {
+        /// The JSON type that was found (e.g., `"string"`).
+        found: &'static str,
+    },
+    /// The number has a fractional part or was written with a fraction
+    /// or exponent (`2.5`, `1.0`, `1e3`).
+    NotAnInteger {
+        /// The number as it appears in JSON output.
+        value: String,
+    },
+    /// The integer does not fit in the target type.
+    OutOfRange {
+        /// The number as it appears in JSON output.
+        value: String,
+        /// The name of the target type (e.g., `"u8"`).
+        target: &'static str,
+    },
+}
+
+impl fmt::Display for IntConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntConversionError::NotANumber { found } => {
+                write!(f, "Expected integer, found {}", found)
+            }
+            IntConversionError::NotAnInteger { value } => {
+                write!(f, "Expected integer, found {}", value)
+            }
+            IntConversionError::OutOfRange { value, target } => {
+                write!(f, "Integer {} is out of range for {}", value, target)
+            }
+        }
+    }
+}
+
+impl error::Error for IntConversionError {}
 
 #[cfg(test)]
 mod tests {
@@ -146,6 +1340,8 @@ mod tests {
             expected: "number".to_string(),
             found: "@".to_string(),
             position: 5,
+            line: 0,
+            column: 0,
         };
 
         // Error should be Debug-printable
@@ -158,6 +1354,8 @@ mod tests {
             expected: "valid JSON".to_string(),
             found: "@".to_string(),
             position: 0,
+            line: 0,
+            column: 0,
         };
 
         let message = format!("{}", error);
@@ -172,16 +1370,22 @@ mod tests {
             expected: "number".to_string(),
             found: "x".to_string(),
             position: 3,
+            line: 0,
+            column: 0,
         };
 
         let eof_error = JsonError::UnexpectedEndOfInput {
             expected: "closing quote".to_string(),
             position: 10,
+            line: 0,
+            column: 0,
         };
 
         let num_error = JsonError::InvalidNumber {
             value: "12.34.56".to_string(),
             position: 0,
+            line: 0,
+            column: 0,
         };
 
         // All variants should be Debug-printable
@@ -195,6 +1399,8 @@ mod tests {
         let error = JsonError::InvalidEscape {
             char: 'x',
             position: 7,
+            line: 0,
+            column: 0,
         };
 
         let message = format!("{}", error);
@@ -208,6 +1414,8 @@ mod tests {
         let error = JsonError::InvalidUnicode {
             sequence: "ZZZZ".to_string(),
             position: 3,
+            line: 0,
+            column: 0,
         };
 
         let message = format!("{}", error);
@@ -224,31 +1432,281 @@ mod tests {
             expected: "value".to_string(),
             found: "@".to_string(),
             position: 0,
+            line: 0,
+            column: 0,
         };
         assert!(error.source().is_none());
 
         let error = JsonError::UnexpectedEndOfInput {
             expected: "closing quote".to_string(),
             position: 5,
+            line: 0,
+            column: 0,
         };
         assert!(error.source().is_none());
 
         let error = JsonError::InvalidNumber {
             value: "1.2.3".to_string(),
             position: 0,
+            line: 0,
+            column: 0,
         };
         assert!(error.source().is_none());
 
         let error = JsonError::InvalidEscape {
             char: 'q',
             position: 2,
+            line: 0,
+            column: 0,
         };
         assert!(error.source().is_none());
 
         let error = JsonError::InvalidUnicode {
             sequence: "GHIJ".to_string(),
             position: 4,
+            line: 0,
+            column: 0,
         };
         assert!(error.source().is_none());
     }
+
+    #[test]
+    fn test_error_is_send_sync_static() {
+        fn assert_bounds<T: Send + Sync + 'static>() {}
+        assert_bounds::<JsonError>();
+        assert_bounds::<AccessError>();
+    }
+
+    #[test]
+    fn test_into_io_error() {
+        let error = JsonError::InvalidNumber {
+            value: "1.2.3".to_string(),
+            position: 4,
+            line: 0,
+            column: 0,
+        };
+        assert_eq!(error.category(), Category::Data);
+        let io_error: io::Error = error.clone().into();
+        assert_eq!(io_error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(io_error.to_string(), error.to_string());
+        let inner = io_error.into_inner().unwrap();
+        assert_eq!(inner.downcast_ref::<JsonError>(), Some(&error));
+
+        let eof = JsonError::UnexpectedEndOfInput {
+            expected: "value".to_string(),
+            position: 0,
+            line: 0,
+            column: 0,
+        };
+        assert_eq!(io::Error::from(eof).kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_from_io_error() {
+        use std::error::Error;
+
+        let error = JsonError::from(io::Error::new(io::ErrorKind::BrokenPipe, "pipe closed"));
+        assert_eq!(error.kind(), JsonErrorKind::Io);
+        assert_eq!(error.category(), Category::Io);
+        assert_eq!(error.to_string(), "Failed to read input: pipe closed");
+        let source = error.source().unwrap().downcast_ref::<io::Error>().unwrap();
+        assert_eq!(source.kind(), io::ErrorKind::BrokenPipe);
+        assert_eq!(error.clone(), error);
+
+        // Back to io::Error, the original kind and message survive.
+        let io_error = io::Error::from(error);
+        assert_eq!(io_error.kind(), io::ErrorKind::BrokenPipe);
+        assert_eq!(io_error.to_string(), "pipe closed");
+
+        // A wrapped JsonError is unwrapped rather than nested.
+        let eof = JsonError::UnexpectedEndOfInput {
+            expected: "value".to_string(),
+            position: 3,
+            line: 1,
+            column: 4,
+        };
+        assert_eq!(JsonError::from(io::Error::from(eof.clone())), eof);
+        assert!(eof.source().is_none());
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        use crate::parser::{JsonParser, parse_json};
+
+        let cases = [
+            ("@", JsonErrorKind::UnexpectedToken),
+            ("[1", JsonErrorKind::UnexpectedEndOfInput),
+            ("1.", JsonErrorKind::InvalidNumber),
+            (r#""\q""#, JsonErrorKind::InvalidEscape),
+            (r#""\u12x4""#, JsonErrorKind::InvalidUnicode),
+            (r#""\udc00""#, JsonErrorKind::UnpairedSurrogate),
+            (&"[".repeat(200), JsonErrorKind::DepthLimitExceeded),
+        ];
+        for (input, kind) in cases {
+            let error = parse_json(input).unwrap_err();
+            assert_eq!(error.kind(), kind, "{:?}", input);
+            // The kind survives the trip through io::Error.
+            assert_eq!(JsonErrorKind::from(&io::Error::from(error)), kind);
+        }
+        let error = JsonParser::new()
+            .duplicate_keys(crate::parser::DuplicateKeys::Error)
+            .parse(r#"{"a": 1, "a": 2}"#)
+            .unwrap_err();
+        assert_eq!(error.kind().to_string(), "duplicate_key");
+        let io_error = io::Error::other("disk on fire");
+        assert_eq!(JsonErrorKind::from(&io_error), JsonErrorKind::Io);
+    }
+
+    #[test]
+    fn test_line_column() {
+        let source = "[\n  \"héllo\", @]";
+        assert_eq!(line_column(source, 0), (1, 1));
+        assert_eq!(line_column(source, 2), (2, 1));
+        // Columns count characters, not bytes.
+        assert_eq!(line_column(source, source.find('@').unwrap()), (2, 12));
+        assert_eq!(line_column(source, 999), (2, 14));
+    }
+
+    #[test]
+    fn test_source_position_units() {
+        let source = "a😀b\néx\n";
+        let at = |byte| SourcePosition::from_byte_offset(source, byte);
+        let b = at(5);
+        assert_eq!(
+            (b.char, b.utf16, b.line, b.column, b.utf16_column),
+            (2, 3, 1, 3, 4)
+        );
+        // Inside the emoji and past the end.
+        assert_eq!(at(3), at(1));
+        assert_eq!(at(999).byte, source.len());
+        let x = at(source.find('x').unwrap());
+        assert_eq!(
+            (x.char, x.utf16, x.line, x.column, x.utf16_column),
+            (5, 6, 2, 2, 2)
+        );
+
+        assert_eq!(SourcePosition::from_char_offset(source, 2), b);
+        assert_eq!(SourcePosition::from_utf16_offset(source, 3), b);
+        assert_eq!(SourcePosition::from_utf16_offset(source, 2), at(1));
+        assert_eq!(SourcePosition::from_line_utf16(source, 1, 4), Some(b));
+        assert_eq!(SourcePosition::from_line_utf16(source, 2, 2), Some(x));
+        assert_eq!(SourcePosition::from_line_utf16(source, 2, 99), Some(at(10)));
+        assert_eq!(SourcePosition::from_line_utf16(source, 3, 1), Some(at(11)));
+        assert_eq!(SourcePosition::from_line_utf16(source, 4, 1), None);
+        assert_eq!(SourcePosition::from_line_utf16(source, 0, 1), None);
+    }
+
+    #[test]
+    fn test_located_display() {
+        let error = JsonError::UnexpectedEndOfInput {
+            expected: "']'".to_string(),
+            position: 4,
+            line: 0,
+            column: 0,
+        }
+        .locate("[1,\n");
+        assert_eq!((error.line(), error.column()), (2, 1));
+        assert_eq!(
+            error.to_string(),
+            "Unexpected end of input at line 2, column 1 (position 4): expected ']'"
+        );
+    }
+
+    #[test]
+    fn test_access_error_display() {
+        let error = AccessError::TypeMismatch {
+            path: JsonPath::parse_dotted("user.age"),
+            expected: "integer",
+            found: "string",
+        };
+        assert_eq!(
+            error.to_string(),
+            "Expected integer at \"user.age\", found string"
+        );
+
+        let error = AccessError::Missing {
+            path: JsonPath::parse_dotted("user"),
+        };
+        assert_eq!(error.to_string(), "No value at \"user\"");
+    }
+
+    #[test]
+    fn test_int_conversion_error_display() {
+        let error = IntConversionError::OutOfRange {
+            value: "300".to_string(),
+            target: "u8",
+        };
+        assert_eq!(error.to_string(), "Integer 300 is out of range for u8");
+        let error = IntConversionError::NotAnInteger {
+            value: "2.5".to_string(),
+        };
+        assert_eq!(error.to_string(), "Expected integer, found 2.5");
+    }
+
+    #[test]
+    fn test_render_hints_and_snippets() {
+        let help = |source: &str| {
+            let err = crate::parser::parse_json(source).unwrap_err();
+            let rendered = render(&err, source);
+            let help = rendered
+                .lines()
+                .find_map(|l| l.trim().strip_prefix("= help: "));
+            help.map(str::to_string)
+        };
+        assert_eq!(
+            help("[1, 2,]").as_deref(),
+            Some("JSON does not allow a comma after the last element")
+        );
+        assert_eq!(
+            help("{\"on\": True}").as_deref(),
+            Some("JSON literals are lowercase: `true`")
+        );
+        assert_eq!(
+            help("[1, 2 3]").as_deref(),
+            Some("is a comma missing before this?")
+        );
+        assert_eq!(
+            help("[007]").as_deref(),
+            Some("JSON numbers cannot have leading zeros")
+        );
+        assert_eq!(
+            help("[\"open").as_deref(),
+            Some("this string is never closed")
+        );
+        assert_eq!(
+            help("[1,\u{a0}2]").as_deref(),
+            Some(
+                "JSON allows only spaces, tabs, and line breaks here; \
+                 `JsonParser::unicode_whitespace` can skip this no-break space (U+00A0)"
+            )
+        );
+        assert_eq!(help("[@]"), None);
+
+        // Tabs are kept so the carets line up, and line endings dropped.
+        let source = "[\r\n\t\tyes\r\n]";
+        let err = crate::parser::parse_json(source).unwrap_err();
+        let rendered = render(&err, source);
+        assert!(
+            rendered.contains("\n2 | \t\tyes\n  | \t\t^^^\n"),
+            "{}",
+            rendered
+        );
+
+        // Long lines are cut to a window around the error.
+        let source = format!("[{}@]", "1, ".repeat(100));
+        let err = crate::parser::parse_json(&source).unwrap_err();
+        let snippet = render(&err, &source).lines().nth(2).unwrap().to_string();
+        assert!(snippet.starts_with("1 | ...") && snippet.ends_with("1, @]"));
+        assert_eq!(snippet.len(), "1 | ...".len() + SNIPPET_WIDTH);
+
+        // Unlocated errors are located in the source given.
+        let error = JsonError::UnexpectedToken {
+            expected: "JSON value".to_string(),
+            found: "x".to_string(),
+            position: 6,
+            line: 0,
+            column: 0,
+        };
+        assert!(render(&error, "[1,\n  x]").contains("\n2 |   x]\n  |   ^\n"));
+    }
 }
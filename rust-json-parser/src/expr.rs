@@ -0,0 +1,1345 @@
+//! jq-style expressions for selecting and transforming values.
+//!
+//! [`eval`](crate::expr::eval) runs a small subset of the jq language
+//! against a [`JsonValue`](crate::value::JsonValue). As in jq, an
+//! expression takes one input and produces any number of outputs, and
+//! `|` feeds every output of its left side into its right side.
+//!
+//! | Syntax | Meaning |
+//! |--------|---------|
+//! | `.` | the input |
+//! | `.name`, `."a key"`, `.[expr]` | object member or array element; `null` if absent |
+//! | `.[]` | every element or member value |
+//! | `.[from:to]` | array or string slice; negative bounds count from the end |
+//! | `..` | the input and every value inside it |
+//! | `a \| b`, `a, b` | pipe, and concatenation of outputs |
+//! | `expr?` | drops errors from `expr` |
+//! | `1`, `"text \(expr)"`, `true`, `false`, `null` | literals, with string interpolation |
+//! | `[expr]`, `{key: expr, name, (expr): expr}` | array and object construction |
+//! | `+ - * / %`, `== != < <= > >=`, `and`, `or` | arithmetic, comparison, logic |
+//! | `length`, `keys`, `type`, `add`, `not`, `tostring`, `tonumber`, `empty` | builtins on the input |
+//! | `map(f)`, `select(f)`, `has(key)`, `join(sep)` | builtins with an argument |
+//!
+//! `+` adds numbers and concatenates strings, arrays, and objects, with
+//! `null` as the identity; `-` also removes elements from arrays and `/`
+//! also splits strings. Values of different types compare in the order
+//! null, false, true, numbers, strings, arrays, objects. Only `null` and
+//! `false` are falsy.
+
+use std::cmp::Ordering;
+use std::error;
+use std::fmt;
+
+use crate::map::JsonMap;
+use crate::parser::DEFAULT_MAX_DEPTH;
+use crate::tokenizer::is_valid_json_number;
+use crate::value::JsonValue;
+
+/// Evaluates `expr` against `value`, returning every output in order.
+///
+/// Parse once with [`Expr::parse`] to run the same expression over many
+/// values.
+///
+/// # Errors
+///
+/// Returns [`ExprError::Syntax`] if `expr` does not parse, or
+/// [`ExprError::Eval`] if it fails on `value`, e.g. by indexing a number.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::expr::eval;
+/// use rust_json_parser::json;
+///
+/// let orders = json!({"orders": [
+///     {"id": 1, "item": "tea", "qty": 2, "price": 3.5},
+///     {"id": 2, "item": "cake", "qty": 1, "price": 12},
+///     {"id": 3, "item": "jam", "qty": 4, "price": 2}
+/// ]});
+/// let big = eval(r#".orders[] | select(.qty * .price >= 8) | "\(.id): \(.item)""#, &orders)?;
+/// assert_eq!(big, [json!("2: cake"), json!("3: jam")]);
+///
+/// let total = eval("[.orders[] | .qty * .price] | add", &orders)?;
+/// assert_eq!(total, [json!(27)]);
+///
+/// let summary = eval("{count: .orders | length, items: .orders | map(.item)}", &orders)?;
+/// assert_eq!(summary[0].to_string(), r#"{"count":3,"items":["tea","cake","jam"]}"#);
+/// # Ok::<(), rust_json_parser::expr::ExprError>(())
+/// ```
+pub fn eval(expr: &str, value: &JsonValue) -> Result<Vec<JsonValue>, ExprError> {
+    Expr::parse(expr)?.eval(value)
+}
+
+/// A parsed expression, reusable across inputs.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::expr::Expr;
+/// use rust_json_parser::json;
+///
+/// let names = Expr::parse(".users[] | .name")?;
+/// let outputs = names.eval(&json!({"users": [{"name": "a"}, {"name": "b"}]}))?;
+/// assert_eq!(outputs, [json!("a"), json!("b")]);
+/// # Ok::<(), rust_json_parser::expr::ExprError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    root: Node,
+}
+
+impl Expr {
+    /// Parses `source`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExprError::Syntax`] with the byte offset of the problem,
+    /// including for calls to unknown functions and for expressions that
+    /// nest more than [`DEFAULT_MAX_DEPTH`] levels deep.
+    pub fn parse(source: &str) -> Result<Self, ExprError> {
+        let mut parser = Parser {
+            source,
+            position: 0,
+            depth: 0,
+        };
+        let root = parser.pipe()?;
+        parser.skip_whitespace();
+        if parser.position < source.len() {
+            return Err(parser.error("expected end of expression"));
+        }
+        Ok(Self { root })
+    }
+
+    /// Evaluates the expression against `value`, returning every output
+    /// in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExprError::Eval`] for the first operation that fails
+    /// outside a `?`.
+    pub fn eval(&self, value: &JsonValue) -> Result<Vec<JsonValue>, ExprError> {
+        self.root.values(value)
+    }
+}
+
+/// Errors from parsing or evaluating an [`Expr`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExprError {
+    /// The expression is malformed.
+    Syntax {
+        /// Byte offset in the expression where the problem was found.
+        position: usize,
+        /// What was expected there.
+        message: String,
+    },
+    /// An operation is not defined for the values it was given.
+    Eval {
+        /// A description of the failure, e.g. `Cannot index number with "a"`.
+        message: String,
+    },
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExprError::Syntax { position, message } => {
+                write!(f, "Syntax error at position {}: {}", position, message)
+            }
+            ExprError::Eval { message } => f.write_str(message),
+        }
+    }
+}
+
+impl error::Error for ExprError {}
+
+fn fail<T>(message: String) -> Result<T, ExprError> {
+    Err(ExprError::Eval { message })
+}
+
+// Builtins and how many arguments each takes.
+const FUNCTIONS: [(&str, usize); 12] = [
+    ("length", 0),
+    ("keys", 0),
+    ("type", 0),
+    ("add", 0),
+    ("not", 0),
+    ("tostring", 0),
+    ("tonumber", 0),
+    ("empty", 0),
+    ("map", 1),
+    ("select", 1),
+    ("has", 1),
+    ("join", 1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+// Infix operators handled by `Parser::binary`.
+#[derive(Debug, Clone, Copy)]
+enum Infix {
+    Or,
+    And,
+    Op(Op),
+}
+
+// The precedence level of comparisons, which do not chain.
+const COMPARISON: u8 = 2;
+
+impl Infix {
+    // Binding strength, loosest first.
+    fn level(self) -> u8 {
+        match self {
+            Infix::Or => 0,
+            Infix::And => 1,
+            Infix::Op(Op::Eq | Op::Ne | Op::Lt | Op::Le | Op::Gt | Op::Ge) => COMPARISON,
+            Infix::Op(Op::Add | Op::Sub) => 3,
+            Infix::Op(Op::Mul | Op::Div | Op::Mod) => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Interpolated(Node),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Identity,
+    Recurse,
+    Literal(JsonValue),
+    // The target, and the key evaluated against the original input.
+    Index(Box<Node>, Box<Node>),
+    Slice(Box<Node>, Option<Box<Node>>, Option<Box<Node>>),
+    Iterate(Box<Node>),
+    Try(Box<Node>),
+    Format(Vec<Part>),
+    Array(Option<Box<Node>>),
+    Object(Vec<(Node, Node)>),
+    Neg(Box<Node>),
+    Pipe(Box<Node>, Box<Node>),
+    Comma(Box<Node>, Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Binary(Op, Box<Node>, Box<Node>),
+    Call(&'static str, Vec<Node>),
+}
+
+impl Node {
+    fn values(&self, input: &JsonValue) -> Result<Vec<JsonValue>, ExprError> {
+        let mut out = Vec::new();
+        self.eval(input, &mut out)?;
+        Ok(out)
+    }
+
+    fn eval(&self, input: &JsonValue, out: &mut Vec<JsonValue>) -> Result<(), ExprError> {
+        match self {
+            Node::Identity => out.push(input.clone()),
+            Node::Recurse => recurse(input, out),
+            Node::Literal(value) => out.push(value.clone()),
+            Node::Index(target, key) => {
+                let keys = key.values(input)?;
+                for target in target.values(input)? {
+                    for key in &keys {
+                        out.push(index(&target, key)?);
+                    }
+                }
+            }
+            Node::Slice(target, from, to) => {
+                let bound = |node: &Option<Box<Node>>| match node {
+                    Some(node) => node.values(input),
+                    None => Ok(vec![JsonValue::Null]),
+                };
+                let (froms, tos) = (bound(from)?, bound(to)?);
+                for target in target.values(input)? {
+                    for from in &froms {
+                        for to in &tos {
+                            out.push(slice(&target, from, to)?);
+                        }
+                    }
+                }
+            }
+            Node::Iterate(target) => {
+                for target in target.values(input)? {
+                    match target {
+                        JsonValue::Array(elements) => out.extend(elements),
+                        JsonValue::Object(map) => out.extend(map.values().cloned()),
+                        other => return fail(format!("Cannot iterate over {}", other.type_name())),
+                    }
+                }
+            }
+            Node::Try(inner) => {
+                // Outputs produced before the error are kept.
+                let _ = inner.eval(input, out);
+            }
+            Node::Format(parts) => {
+                let mut strings = vec![String::new()];
+                for part in parts {
+                    match part {
+                        Part::Text(text) => strings.iter_mut().for_each(|s| s.push_str(text)),
+                        Part::Interpolated(node) => {
+                            let values = node.values(input)?;
+                            strings = strings
+                                .iter()
+                                .flat_map(|s| values.iter().map(move |v| s.clone() + &text_of(v)))
+                                .collect();
+                        }
+                    }
+                }
+                out.extend(strings.into_iter().map(JsonValue::String));
+            }
+            Node::Array(None) => out.push(JsonValue::Array(Vec::new())),
+            Node::Array(Some(inner)) => out.push(JsonValue::Array(inner.values(input)?)),
+            Node::Object(entries) => {
+                let mut objects = vec![JsonMap::new()];
+                for (key, value) in entries {
+                    let mut keys = Vec::new();
+                    for key in key.values(input)? {
+                        match key {
+                            JsonValue::String(key) => keys.push(key),
+                            other => {
+                                return fail(format!(
+                                    "Object keys must be strings, not {}",
+                                    other.type_name()
+                                ));
+                            }
+                        }
+                    }
+                    let values = value.values(input)?;
+                    let mut next = Vec::with_capacity(objects.len() * keys.len() * values.len());
+                    for object in &objects {
+                        for key in &keys {
+                            for value in &values {
+                                let mut object = object.clone();
+                                object.insert(key.clone(), value.clone());
+                                next.push(object);
+                            }
+                        }
+                    }
+                    objects = next;
+                }
+                out.extend(objects.into_iter().map(JsonValue::Object));
+            }
+            Node::Neg(inner) => {
+                for value in inner.values(input)? {
+                    match value.as_f64() {
+                        Some(n) => out.push(JsonValue::from(-n)),
+                        None => return fail(format!("Cannot negate {}", value.type_name())),
+                    }
+                }
+            }
+            Node::Pipe(lhs, rhs) => {
+                for value in lhs.values(input)? {
+                    rhs.eval(&value, out)?;
+                }
+            }
+            Node::Comma(lhs, rhs) => {
+                lhs.eval(input, out)?;
+                rhs.eval(input, out)?;
+            }
+            Node::And(lhs, rhs) | Node::Or(lhs, rhs) => {
+                let is_and = matches!(self, Node::And(..));
+                for left in lhs.values(input)? {
+                    // `false and _` and `true or _` skip the right side.
+                    if truthy(&left) != is_and {
+                        out.push(JsonValue::Boolean(!is_and));
+                        continue;
+                    }
+                    for right in rhs.values(input)? {
+                        out.push(JsonValue::Boolean(truthy(&right)));
+                    }
+                }
+            }
+            Node::Binary(op, lhs, rhs) => {
+                let rights = rhs.values(input)?;
+                let lefts = lhs.values(input)?;
+                for right in &rights {
+                    for left in &lefts {
+                        out.push(apply(*op, left, right)?);
+                    }
+                }
+            }
+            Node::Call(name, args) => call(name, args, input, out)?,
+        }
+        Ok(())
+    }
+}
+
+fn truthy(value: &JsonValue) -> bool {
+    !matches!(value, JsonValue::Null | JsonValue::Boolean(false))
+}
+
+// Strings as they are, anything else as JSON.
+fn text_of(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn recurse(value: &JsonValue, out: &mut Vec<JsonValue>) {
+    out.push(value.clone());
+    match value {
+        JsonValue::Array(elements) => elements.iter().for_each(|e| recurse(e, out)),
+        JsonValue::Object(map) => map.values().for_each(|v| recurse(v, out)),
+        _ => {}
+    }
+}
+
+// Resolves a possibly negative index against a length, clamped to
+// `0..=len`.
+fn clamp_index(index: f64, len: usize) -> usize {
+    let index = index.floor();
+    let index = if index < 0.0 {
+        len as f64 + index
+    } else {
+        index
+    };
+    index.clamp(0.0, len as f64) as usize
+}
+
+fn index(target: &JsonValue, key: &JsonValue) -> Result<JsonValue, ExprError> {
+    match (target, key) {
+        (JsonValue::Null, JsonValue::String(_) | JsonValue::Number(_)) => Ok(JsonValue::Null),
+        (JsonValue::Object(map), JsonValue::String(key)) => {
+            Ok(map.get(key).cloned().unwrap_or(JsonValue::Null))
+        }
+        (JsonValue::Array(elements), JsonValue::Number(n)) => {
+            let n = n.as_f64().floor();
+            if n < 0.0 && -n > elements.len() as f64 {
+                return Ok(JsonValue::Null);
+            }
+            let i = clamp_index(n, elements.len());
+            Ok(elements.get(i).cloned().unwrap_or(JsonValue::Null))
+        }
+        (target, JsonValue::String(key)) => fail(format!(
+            "Cannot index {} with {:?}",
+            target.type_name(),
+            key
+        )),
+        (target, key) => fail(format!(
+            "Cannot index {} with {}",
+            target.type_name(),
+            key.type_name()
+        )),
+    }
+}
+
+fn slice(target: &JsonValue, from: &JsonValue, to: &JsonValue) -> Result<JsonValue, ExprError> {
+    let len = match target {
+        JsonValue::Null => return Ok(JsonValue::Null),
+        JsonValue::Array(elements) => elements.len(),
+        JsonValue::String(s) => s.chars().count(),
+        other => return fail(format!("Cannot slice {}", other.type_name())),
+    };
+    let bound = |value: &JsonValue, default: usize| match value {
+        JsonValue::Null => Ok(default),
+        JsonValue::Number(n) => Ok(clamp_index(n.as_f64(), len)),
+        other => fail(format!(
+            "Slice bounds must be numbers, not {}",
+            other.type_name()
+        )),
+    };
+    let start = bound(from, 0)?;
+    let end = bound(to, len)?.max(start);
+    Ok(match target {
+        JsonValue::Array(elements) => JsonValue::Array(elements[start..end].to_vec()),
+        JsonValue::String(s) => {
+            JsonValue::String(s.chars().skip(start).take(end - start).collect())
+        }
+        _ => unreachable!("checked above"),
+    })
+}
+
+fn rank(value: &JsonValue) -> u8 {
+    match value {
+        JsonValue::Null => 0,
+        JsonValue::Boolean(false) => 1,
+        JsonValue::Boolean(true) => 2,
+        JsonValue::Number(_) => 3,
+        JsonValue::String(_) => 4,
+        JsonValue::Array(_) => 5,
+        JsonValue::Object(_) => 6,
+    }
+}
+
+// jq's total order: by type, then numbers by value (NaN first), strings
+// by code point, arrays element-wise, and objects by their sorted keys,
+// then by the values under those keys.
+pub(crate) fn compare(a: &JsonValue, b: &JsonValue) -> Ordering {
+    match (a, b) {
+        (JsonValue::Number(x), JsonValue::Number(y)) => {
+            let (x, y) = (x.as_f64(), y.as_f64());
+            match (x.is_nan(), y.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => x.partial_cmp(&y).expect("not NaN"),
+            }
+        }
+        (JsonValue::String(x), JsonValue::String(y)) => x.cmp(y),
+        (JsonValue::Array(x), JsonValue::Array(y)) => x
+            .iter()
+            .zip(y)
+            .map(|(x, y)| compare(x, y))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        (JsonValue::Object(x), JsonValue::Object(y)) => {
+            let (xk, yk) = (sorted_keys(x), sorted_keys(y));
+            xk.cmp(&yk).then_with(|| {
+                xk.iter()
+                    .map(|k| compare(&x[k.as_str()], &y[k.as_str()]))
+                    .find(|o| o.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn sorted_keys(map: &JsonMap) -> Vec<&String> {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    keys
+}
+
+fn apply(op: Op, left: &JsonValue, right: &JsonValue) -> Result<JsonValue, ExprError> {
+    use JsonValue::{Array, Null, Number, Object, String};
+
+    let cannot = |verb: &str| {
+        fail(format!(
+            "Cannot {} {} and {}",
+            verb,
+            left.type_name(),
+            right.type_name()
+        ))
+    };
+    let numbers = || Some((left.as_f64()?, right.as_f64()?));
+    let result = match op {
+        Op::Eq => JsonValue::Boolean(compare(left, right).is_eq()),
+        Op::Ne => JsonValue::Boolean(compare(left, right).is_ne()),
+        Op::Lt => JsonValue::Boolean(compare(left, right).is_lt()),
+        Op::Le => JsonValue::Boolean(compare(left, right).is_le()),
+        Op::Gt => JsonValue::Boolean(compare(left, right).is_gt()),
+        Op::Ge => JsonValue::Boolean(compare(left, right).is_ge()),
+        Op::Add => match (left, right) {
+            (Null, other) | (other, Null) => other.clone(),
+            (Number(x), Number(y)) => JsonValue::from(x.as_f64() + y.as_f64()),
+            (String(x), String(y)) => JsonValue::String(format!("{}{}", x, y)),
+            (Array(x), Array(y)) => Array(x.iter().chain(y).cloned().collect()),
+            (Object(x), Object(y)) => {
+                let mut merged = x.clone();
+                for (key, value) in y {
+                    merged.insert(key.clone(), value.clone());
+                }
+                Object(merged)
+            }
+            _ => return cannot("add"),
+        },
+        Op::Sub => match (left, right) {
+            (Array(x), Array(y)) => Array(
+                x.iter()
+                    .filter(|e| !y.iter().any(|r| compare(e, r).is_eq()))
+                    .cloned()
+                    .collect(),
+            ),
+            _ => match numbers() {
+                Some((x, y)) => JsonValue::from(x - y),
+                None => return cannot("subtract"),
+            },
+        },
+        Op::Mul => match numbers() {
+            Some((x, y)) => JsonValue::from(x * y),
+            None => return cannot("multiply"),
+        },
+        Op::Div => match (left, right) {
+            (String(x), String(y)) => Array(x.split(y.as_str()).map(JsonValue::from).collect()),
+            _ => match numbers() {
+                Some((_, 0.0)) => return fail("Cannot divide by zero".to_string()),
+                Some((x, y)) => JsonValue::from(x / y),
+                None => return cannot("divide"),
+            },
+        },
+        Op::Mod => match numbers() {
+            Some((x, y)) => {
+                let (x, y) = (x.trunc() as i64, y.trunc() as i64);
+                if y == 0 {
+                    return fail("Cannot take a remainder by zero".to_string());
+                }
+                // `i64::MIN % -1` overflows, and large operands saturate
+                // to `i64::MIN`.
+                match x.checked_rem(y) {
+                    Some(remainder) => JsonValue::from(remainder as f64),
+                    None => {
+                        return fail(format!(
+                            "Cannot take the remainder of {} by {}",
+                            left, right
+                        ));
+                    }
+                }
+            }
+            None => return cannot("take the remainder of"),
+        },
+    };
+    Ok(result)
+}
+
+fn call(
+    name: &str,
+    args: &[Node],
+    input: &JsonValue,
+    out: &mut Vec<JsonValue>,
+) -> Result<(), ExprError> {
+    let value = match (name, args) {
+        ("empty", _) => return Ok(()),
+        ("length", _) => match input {
+            JsonValue::Null => JsonValue::from(0.0),
+            JsonValue::Number(n) => JsonValue::from(n.as_f64().abs()),
+            JsonValue::String(s) => JsonValue::from(s.chars().count() as f64),
+            JsonValue::Array(elements) => JsonValue::from(elements.len() as f64),
+            JsonValue::Object(map) => JsonValue::from(map.len() as f64),
+            JsonValue::Boolean(_) => return fail("boolean has no length".to_string()),
+        },
+        ("keys", _) => match input {
+            JsonValue::Object(map) => JsonValue::Array(
+                sorted_keys(map)
+                    .into_iter()
+                    .map(|k| JsonValue::from(k.as_str()))
+                    .collect(),
+            ),
+            JsonValue::Array(elements) => JsonValue::Array(
+                (0..elements.len())
+                    .map(|i| JsonValue::from(i as f64))
+                    .collect(),
+            ),
+            other => return fail(format!("{} has no keys", other.type_name())),
+        },
+        ("type", _) => JsonValue::from(input.type_name()),
+        ("not", _) => JsonValue::Boolean(!truthy(input)),
+        ("tostring", _) => JsonValue::String(text_of(input)),
+        ("tonumber", _) => match input {
+            JsonValue::Number(_) => input.clone(),
+            JsonValue::String(s) if is_valid_json_number(s) => {
+                JsonValue::from(s.parse::<f64>().expect("valid JSON number"))
+            }
+            other => return fail(format!("Cannot parse {} as a number", other)),
+        },
+        ("add", _) => {
+            let items: Vec<&JsonValue> = match input {
+                JsonValue::Array(elements) => elements.iter().collect(),
+                JsonValue::Object(map) => map.values().collect(),
+                other => return fail(format!("Cannot add up {}", other.type_name())),
+            };
+            items
+                .into_iter()
+                .try_fold(JsonValue::Null, |sum, item| apply(Op::Add, &sum, item))?
+        }
+        ("map", [f]) => {
+            let items: Vec<&JsonValue> = match input {
+                JsonValue::Array(elements) => elements.iter().collect(),
+                JsonValue::Object(map) => map.values().collect(),
+                other => return fail(format!("Cannot iterate over {}", other.type_name())),
+            };
+            let mut mapped = Vec::with_capacity(items.len());
+            for item in items {
+                f.eval(item, &mut mapped)?;
+            }
+            JsonValue::Array(mapped)
+        }
+        ("select", [f]) => {
+            for condition in f.values(input)? {
+                if truthy(&condition) {
+                    out.push(input.clone());
+                }
+            }
+            return Ok(());
+        }
+        ("has", [key]) => {
+            for key in key.values(input)? {
+                let has = match (input, &key) {
+                    (JsonValue::Object(map), JsonValue::String(k)) => map.contains_key(k),
+                    (JsonValue::Array(elements), JsonValue::Number(n)) => {
+                        let n = n.as_f64();
+                        n >= 0.0 && n < elements.len() as f64
+                    }
+                    _ => {
+                        return fail(format!(
+                            "Cannot check whether {} has a {} key",
+                            input.type_name(),
+                            key.type_name()
+                        ));
+                    }
+                };
+                out.push(JsonValue::Boolean(has));
+            }
+            return Ok(());
+        }
+        ("join", [separator]) => {
+            let JsonValue::Array(elements) = input else {
+                return fail(format!("Cannot join {}", input.type_name()));
+            };
+            for separator in separator.values(input)? {
+                let JsonValue::String(separator) = separator else {
+                    return fail(format!("Cannot join with {}", separator.type_name()));
+                };
+                let mut parts = Vec::with_capacity(elements.len());
+                for element in elements {
+                    parts.push(match element {
+                        JsonValue::Null => String::new(),
+                        JsonValue::Array(_) | JsonValue::Object(_) => {
+                            return fail(format!("Cannot join {}", element.type_name()));
+                        }
+                        other => text_of(other),
+                    });
+                }
+                out.push(JsonValue::String(parts.join(&separator)));
+            }
+            return Ok(());
+        }
+        _ => unreachable!("arity checked when parsing"),
+    };
+    out.push(value);
+    Ok(())
+}
+
+// A scannerless recursive descent parser, lowest precedence first:
+// `|`, `,`, `or`, `and`, comparisons, `+ -`, `* / %`, unary minus,
+// suffixes, and terms.
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+    // How deep the node being built will sit in the tree. Evaluating and
+    // dropping the tree recurse too, so chains of operators count as
+    // well as brackets.
+    depth: usize,
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> ExprError {
+        ExprError::Syntax {
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.source[self.position..]
+    }
+
+    fn descend(&mut self) -> Result<(), ExprError> {
+        if self.depth == DEFAULT_MAX_DEPTH {
+            return Err(self.error(&format!(
+                "expression nests more than {} levels deep",
+                DEFAULT_MAX_DEPTH
+            )));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    // Consumes `symbol` after optional whitespace.
+    fn eat(&mut self, symbol: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(symbol) {
+            self.position += symbol.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    // Consumes the word `keyword`, but not a longer identifier starting
+    // with it.
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        if rest.starts_with(keyword) && !rest[keyword.len()..].starts_with(is_ident_char) {
+            self.position += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), ExprError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", symbol)))
+        }
+    }
+
+    fn identifier(&mut self) -> Option<&'a str> {
+        let rest = &self.source[self.position..];
+        if !rest.starts_with(is_ident_start) {
+            return None;
+        }
+        let len = rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len());
+        self.position += len;
+        Some(&rest[..len])
+    }
+
+    fn pipe(&mut self) -> Result<Node, ExprError> {
+        let depth = self.depth;
+        self.descend()?;
+        let mut node = self.comma()?;
+        if self.eat("|") {
+            node = Node::Pipe(Box::new(node), Box::new(self.pipe()?));
+        }
+        self.depth = depth;
+        Ok(node)
+    }
+
+    fn comma(&mut self) -> Result<Node, ExprError> {
+        let depth = self.depth;
+        let mut lhs = self.binary(0)?;
+        while self.eat(",") {
+            self.descend()?;
+            lhs = Node::Comma(Box::new(lhs), Box::new(self.binary(0)?));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    // Parses operators from `or` up to `* / %` by precedence climbing,
+    // taking only those at `min` or tighter. Comparisons do not chain.
+    fn binary(&mut self, min: u8) -> Result<Node, ExprError> {
+        let depth = self.depth;
+        let mut lhs = self.unary()?;
+        let mut compared = false;
+        loop {
+            let start = self.position;
+            let Some(infix) = self.infix() else { break };
+            let level = infix.level();
+            if level < min || (compared && level == COMPARISON) {
+                self.position = start;
+                break;
+            }
+            self.descend()?;
+            let rhs = Box::new(self.binary(level + 1)?);
+            let lhs_node = Box::new(lhs);
+            lhs = match infix {
+                Infix::Or => Node::Or(lhs_node, rhs),
+                Infix::And => Node::And(lhs_node, rhs),
+                Infix::Op(op) => Node::Binary(op, lhs_node, rhs),
+            };
+            compared = level == COMPARISON;
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn infix(&mut self) -> Option<Infix> {
+        if self.eat_keyword("or") {
+            return Some(Infix::Or);
+        }
+        if self.eat_keyword("and") {
+            return Some(Infix::And);
+        }
+        // Two-character operators first, so `<=` is not read as `<`.
+        let ops = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+            ("+", Op::Add),
+            ("-", Op::Sub),
+            ("*", Op::Mul),
+            ("/", Op::Div),
+            ("%", Op::Mod),
+        ];
+        ops.into_iter()
+            .find(|(symbol, _)| self.eat(symbol))
+            .map(|(_, op)| Infix::Op(op))
+    }
+
+    fn unary(&mut self) -> Result<Node, ExprError> {
+        if self.eat("-") {
+            let depth = self.depth;
+            self.descend()?;
+            let node = Node::Neg(Box::new(self.unary()?));
+            self.depth = depth;
+            return Ok(node);
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Node, ExprError> {
+        let depth = self.depth;
+        let mut node = self.term()?;
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            let suffix =
+                rest.starts_with(['?', '[']) || (rest.starts_with('.') && !rest.starts_with(".."));
+            if !suffix {
+                self.depth = depth;
+                return Ok(node);
+            }
+            self.descend()?;
+            let rest = self.rest();
+            if rest.starts_with('?') {
+                self.position += 1;
+                node = Node::Try(Box::new(node));
+            } else if rest.starts_with(".[") {
+                self.position += 1;
+                node = self.bracket(node)?;
+            } else if rest.starts_with('[') {
+                node = self.bracket(node)?;
+            } else {
+                self.position += 1;
+                node = self.field(node)?;
+            }
+        }
+    }
+
+    // Parses the name after a `.`: an identifier or a string.
+    fn field(&mut self, target: Node) -> Result<Node, ExprError> {
+        let key = if self.peek() == Some('"') {
+            self.string()?
+        } else {
+            match self.identifier() {
+                Some(name) => Node::Literal(JsonValue::from(name)),
+                None => return Err(self.error("expected a field name")),
+            }
+        };
+        Ok(Node::Index(Box::new(target), Box::new(key)))
+    }
+
+    // Parses `[]`, `[expr]`, or `[from:to]` applied to `target`.
+    fn bracket(&mut self, target: Node) -> Result<Node, ExprError> {
+        self.expect("[")?;
+        if self.eat("]") {
+            return Ok(Node::Iterate(Box::new(target)));
+        }
+        let from = if self.eat(":") {
+            None
+        } else {
+            let key = self.pipe()?;
+            if self.eat("]") {
+                return Ok(Node::Index(Box::new(target), Box::new(key)));
+            }
+            self.expect(":")?;
+            Some(Box::new(key))
+        };
+        let to = if self.eat("]") {
+            None
+        } else {
+            let to = self.pipe()?;
+            self.expect("]")?;
+            Some(Box::new(to))
+        };
+        if from.is_none() && to.is_none() {
+            return Err(self.error("expected a slice bound"));
+        }
+        Ok(Node::Slice(Box::new(target), from, to))
+    }
+
+    fn term(&mut self) -> Result<Node, ExprError> {
+        self.skip_whitespace();
+        let start = self.position;
+        let rest = self.rest();
+        match self.peek() {
+            _ if rest.starts_with("..") => {
+                self.position += 2;
+                Ok(Node::Recurse)
+            }
+            Some('.') => {
+                self.position += 1;
+                match self.peek() {
+                    Some('"') => self.field(Node::Identity),
+                    Some(c) if is_ident_start(c) => self.field(Node::Identity),
+                    Some('[') => self.bracket(Node::Identity),
+                    _ => Ok(Node::Identity),
+                }
+            }
+            Some('"') => self.string(),
+            Some('0'..='9') => self.number(),
+            Some('(') => {
+                self.position += 1;
+                let inner = self.pipe()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Some('[') => {
+                self.position += 1;
+                if self.eat("]") {
+                    return Ok(Node::Array(None));
+                }
+                let inner = self.pipe()?;
+                self.expect("]")?;
+                Ok(Node::Array(Some(Box::new(inner))))
+            }
+            Some('{') => self.object(),
+            Some(c) if is_ident_start(c) => {
+                let name = self.identifier().expect("identifier start");
+                match name {
+                    "true" => return Ok(Node::Literal(JsonValue::Boolean(true))),
+                    "false" => return Ok(Node::Literal(JsonValue::Boolean(false))),
+                    "null" => return Ok(Node::Literal(JsonValue::Null)),
+                    _ => {}
+                }
+                let mut args = Vec::new();
+                if self.eat("(") {
+                    loop {
+                        args.push(self.pipe()?);
+                        if !self.eat(";") {
+                            break;
+                        }
+                    }
+                    self.expect(")")?;
+                }
+                match FUNCTIONS.iter().find(|(n, _)| *n == name) {
+                    Some((name, arity)) if *arity == args.len() => Ok(Node::Call(name, args)),
+                    _ => Err(ExprError::Syntax {
+                        position: start,
+                        message: format!("unknown function {}/{}", name, args.len()),
+                    }),
+                }
+            }
+            _ => Err(self.error("expected an expression")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Node, ExprError> {
+        let rest = self.rest();
+        let mut len = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let digits = |from: usize| {
+            rest[from..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len() - from)
+        };
+        if rest[len..].starts_with('.') && digits(len + 1) > 0 {
+            len += 1 + digits(len + 1);
+        }
+        if rest[len..].starts_with(['e', 'E']) {
+            let sign = usize::from(rest[len + 1..].starts_with(['+', '-']));
+            let exponent = digits(len + 1 + sign);
+            if exponent > 0 {
+                len += 1 + sign + exponent;
+            }
+        }
+        let n: f64 = rest[..len]
+            .parse()
+            .map_err(|_| self.error("invalid number"))?;
+        self.position += len;
+        Ok(Node::Literal(JsonValue::from(n)))
+    }
+
+    // Parses a string literal, which becomes a format node if it contains
+    // `\(expr)` interpolations.
+    fn string(&mut self) -> Result<Node, ExprError> {
+        self.position += 1; // opening quote
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.position += c.len_utf8();
+            match c {
+                '"' => break,
+                '\\' => {
+                    let escape = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.position += escape.len_utf8();
+                    match escape {
+                        '"' | '\\' | '/' => text.push(escape),
+                        'b' => text.push('\u{8}'),
+                        'f' => text.push('\u{c}'),
+                        'n' => text.push('\n'),
+                        'r' => text.push('\r'),
+                        't' => text.push('\t'),
+                        'u' => {
+                            let c = self
+                                .rest()
+                                .get(..4)
+                                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?;
+                            self.position += 4;
+                            text.push(c);
+                        }
+                        '(' => {
+                            if !text.is_empty() {
+                                parts.push(Part::Text(std::mem::take(&mut text)));
+                            }
+                            parts.push(Part::Interpolated(self.pipe()?));
+                            self.expect(")")?;
+                        }
+                        _ => {
+                            self.position -= escape.len_utf8();
+                            return Err(self.error("invalid escape"));
+                        }
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+        if parts.is_empty() {
+            return Ok(Node::Literal(JsonValue::String(text)));
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Node::Format(parts))
+    }
+
+    // Parses an object value, which may be a pipe but not a comma.
+    fn object_value(&mut self) -> Result<Node, ExprError> {
+        let depth = self.depth;
+        self.descend()?;
+        let mut node = self.binary(0)?;
+        if self.eat("|") {
+            node = Node::Pipe(Box::new(node), Box::new(self.object_value()?));
+        }
+        self.depth = depth;
+        Ok(node)
+    }
+
+    // Parses `{key: value, ...}`. Keys are identifiers, strings, or
+    // parenthesized expressions; `{name}` is short for `{name: .name}`.
+    fn object(&mut self) -> Result<Node, ExprError> {
+        self.position += 1; // opening brace
+        let mut entries = Vec::new();
+        if self.eat("}") {
+            return Ok(Node::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = match self.peek() {
+                Some('"') => self.string()?,
+                Some('(') => {
+                    self.position += 1;
+                    let key = self.pipe()?;
+                    self.expect(")")?;
+                    key
+                }
+                _ => match self.identifier() {
+                    Some(name) => Node::Literal(JsonValue::from(name)),
+                    None => return Err(self.error("expected an object key")),
+                },
+            };
+            let value = if self.eat(":") {
+                self.object_value()?
+            } else {
+                Node::Index(Box::new(Node::Identity), Box::new(key.clone()))
+            };
+            entries.push((key, value));
+            if self.eat("}") {
+                return Ok(Node::Object(entries));
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    fn run(expr: &str, input: &JsonValue) -> Vec<String> {
+        eval(expr, input)
+            .unwrap_or_else(|e| panic!("{}: {}", expr, e))
+            .iter()
+            .map(JsonValue::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_paths_and_iteration() {
+        let doc = json!({"a": {"b c": [10, 20, 30]}, "n": null, "s": "héllo"});
+        assert_eq!(run(".", &json!(1)), ["1"]);
+        assert_eq!(run(r#".a."b c"[1]"#, &doc), ["20"]);
+        assert_eq!(run(r#".a["b c"][-1]"#, &doc), ["30"]);
+        assert_eq!(
+            run(r#".a."b c".[5], .missing.deeper, .n[0]"#, &doc),
+            ["null"; 3]
+        );
+        assert_eq!(
+            run(r#".a."b c"[1:], .s[1:3], .a."b c"[:-2]"#, &doc),
+            ["[20,30]", "\"él\"", "[10]"]
+        );
+        assert_eq!(run(".a[] | .[]", &doc), ["10", "20", "30"]);
+        assert_eq!(run("[..] | length", &json!([1, [2, {"x": 3}]])), ["6"]);
+        assert_eq!(
+            run(".[]?, (.s | .x)?", &doc),
+            [r#"{"b c":[10,20,30]}"#, "null", "\"héllo\""]
+        );
+    }
+
+    #[test]
+    fn test_builtins_and_construction() {
+        let people = json!([
+            {"name": "ann", "age": 31, "tags": ["x"]},
+            {"name": "bo", "age": 17, "tags": []},
+            {"name": "cy", "age": 45}
+        ]);
+        assert_eq!(
+            run("map(select(.age >= 18) | .name)", &people),
+            [r#"["ann","cy"]"#]
+        );
+        assert_eq!(run("map(.age) | add / length", &people), ["31"]);
+        assert_eq!(
+            run(".[] | select(has(\"tags\") | not) | .name", &people),
+            ["\"cy\""]
+        );
+        assert_eq!(
+            run(".[0] | keys, (.tags | type)", &people),
+            [r#"["age","name","tags"]"#, "\"array\""]
+        );
+        assert_eq!(
+            run("map(.name) | join(\", \")", &people),
+            ["\"ann, bo, cy\""]
+        );
+        assert_eq!(
+            run(
+                ".[0] | {name, adult: .age > 18, (.name): 1, \"n\\(.age)\": [.tags[], empty]}",
+                &people
+            ),
+            [r#"{"name":"ann","adult":true,"ann":1,"n31":["x"]}"#]
+        );
+        assert_eq!(run("{a: (1, 2)} | .a", &json!(null)), ["1", "2"]);
+        assert_eq!(
+            run(r#""\(1, 2)-\(true)""#, &json!(null)),
+            ["\"1-true\"", "\"2-true\""]
+        );
+        assert_eq!(
+            run(r#""12" | tonumber + 1, (3 | tostring)"#, &json!(null)),
+            ["13", "\"3\""]
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        let null = json!(null);
+        assert_eq!(run("1 + 2 * 3 - -4 % 3", &null), ["8"]);
+        assert_eq!(run("(1 + 2) * 3, 7 / 2, 1e2", &null), ["9", "3.5", "100"]);
+        assert_eq!(
+            run(
+                r#""a" + "b", [1, 2, 3] - [2], {"a": 1} + {"b": 2}, null + 5"#,
+                &null
+            ),
+            ["\"ab\"", "[1,3]", r#"{"a":1,"b":2}"#, "5"]
+        );
+        assert_eq!(run(r#""a,b" / ",""#, &null), [r#"["a","b"]"#]);
+        assert_eq!(
+            run(
+                "null < false, false < 0, 1 < \"a\", [1] < [1, 0], {} < [], 1 == 1.0",
+                &null
+            ),
+            ["true", "true", "true", "true", "false", "true"]
+        );
+        assert_eq!(
+            run(
+                "true and (false, true), false and 1 + \"a\", 1 or .x.y",
+                &null
+            ),
+            ["false", "true", "false", "true"]
+        );
+        assert_eq!(
+            run(".[] | . > 1 and . < 4", &json!([1, 2, 3, 4])),
+            ["false", "true", "true", "false"]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |expr: &str, input: &JsonValue| eval(expr, input).unwrap_err().to_string();
+        assert_eq!(err(".a", &json!(1)), r#"Cannot index number with "a""#);
+        assert_eq!(err(".[]", &json!(true)), "Cannot iterate over boolean");
+        assert_eq!(
+            err("1 + \"a\"", &json!(null)),
+            "Cannot add number and string"
+        );
+        assert_eq!(err("1 / 0", &json!(null)), "Cannot divide by zero");
+        assert_eq!(
+            err("{(1): 2}", &json!(null)),
+            "Object keys must be strings, not number"
+        );
+        assert_eq!(
+            err(".a |", &json!(null)),
+            "Syntax error at position 4: expected an expression"
+        );
+        assert_eq!(
+            err("map(.a", &json!(null)),
+            "Syntax error at position 6: expected ')'"
+        );
+        assert_eq!(
+            err(". foo", &json!(null)),
+            "Syntax error at position 2: expected end of expression"
+        );
+        assert_eq!(
+            err("  frob(1)", &json!(null)),
+            "Syntax error at position 2: unknown function frob/1"
+        );
+        assert_eq!(
+            err(r#""\q""#, &json!(null)),
+            "Syntax error at position 2: invalid escape"
+        );
+    }
+
+    #[test]
+    fn test_remainder_overflow_and_nesting_limit() {
+        let err = |expr: &str, input: &JsonValue| eval(expr, input).unwrap_err().to_string();
+        assert_eq!(
+            err(".[0] % .[1]", &json!([i64::MIN, -1])),
+            "Cannot take the remainder of -9223372036854775808 by -1"
+        );
+        assert!(eval("-1e300 % -1", &json!(null)).is_err());
+        assert_eq!(eval("-7 % 2", &json!(null)).unwrap(), [json!(-1.0)]);
+
+        let nested = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+        assert_eq!(
+            err(&nested, &json!(null)),
+            "Syntax error at position 128: expression nests more than 128 levels deep"
+        );
+        let chain = vec!["1"; 100_000].join(" + ");
+        assert!(err(&chain, &json!(null)).contains("more than 128 levels"));
+        let negations = "-".repeat(100_000) + "1";
+        assert!(err(&negations, &json!(null)).contains("more than 128 levels"));
+        let objects = "{a: ".repeat(100_000);
+        assert!(err(&objects, &json!(null)).contains("more than 128 levels"));
+        let negations = "-".repeat(100_000) + "1";
+        assert!(err(&negations, &json!(null)).contains("more than 128 levels"));
+        let fine = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+        assert_eq!(eval(&fine, &json!(null)).unwrap(), [json!(1.0)]);
+    }
+}
@@ -0,0 +1,428 @@
+//! Composable rewrites of event streams.
+//!
+//! The adapters here wrap any iterator of
+//! [`Event`](crate::stream::Event)s, such as a
+//! [`JsonStreamParser`](crate::stream::JsonStreamParser), and yield a
+//! rewritten stream. They are added as methods by the
+//! [`EventFilter`](crate::filter::EventFilter) trait and can be stacked in
+//! front of an [`EventWriter`](crate::stream::EventWriter) to drop,
+//! select, or rename parts of a document in one streaming pass.
+//!
+//! Each adapter matches paths in the stream it receives, so an adapter
+//! stacked after a rename sees the new keys, and one stacked after a
+//! removal sees renumbered array indices. In a pattern, the key `*`
+//! matches any single key or index.
+
+use std::io;
+
+use crate::path::{JsonPath, Segment};
+use crate::stream::Event;
+
+/// Stream adapters for iterators of events.
+///
+/// Implemented for every iterator over `io::Result<Event>`. Errors from
+/// the underlying stream are passed through unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::filter::EventFilter;
+/// use rust_json_parser::path::JsonPath;
+/// use rust_json_parser::stream::{EventWriter, JsonStreamParser};
+///
+/// let input = br#"{"users": [{"name": "a", "password": "x"}], "debug": {"trace": 1}}"#;
+/// let events = JsonStreamParser::new(&input[..])
+///     .skip_paths([JsonPath::parse_dotted("users.*.password"), JsonPath::parse_dotted("debug")])
+///     .rename_keys(|_, key| (key == "name").then(|| "login".to_string()));
+/// let mut writer = EventWriter::new(Vec::new());
+/// for event in events {
+///     writer.write_event(&event?)?;
+/// }
+/// assert_eq!(writer.finish()?, br#"{"users":[{"login":"a"}]}"#);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub trait EventFilter: Iterator<Item = io::Result<Event>> + Sized {
+    /// Removes every value whose path matches one of `patterns`, together
+    /// with its subtree and, for object members, its key.
+    fn skip_paths<P>(self, patterns: P) -> SkipPaths<Self>
+    where
+        P: IntoIterator<Item = JsonPath>,
+    {
+        SkipPaths {
+            inner: self,
+            patterns: patterns.into_iter().collect(),
+            tracker: Tracker::default(),
+            skip: 0,
+        }
+    }
+
+    /// Keeps only values whose path matches one of `patterns`, with their
+    /// subtrees, plus the arrays and objects leading to them. Everything
+    /// else is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::filter::EventFilter;
+    /// use rust_json_parser::path::JsonPath;
+    /// use rust_json_parser::stream::{EventWriter, JsonStreamParser};
+    ///
+    /// let input = br#"{"id": 7, "items": [{"sku": "a", "qty": 1}, {"sku": "b"}], "note": "x"}"#;
+    /// let events = JsonStreamParser::new(&input[..])
+    ///     .keep_paths([JsonPath::parse_dotted("id"), JsonPath::parse_dotted("items.*.sku")]);
+    /// let mut writer = EventWriter::new(Vec::new());
+    /// for event in events {
+    ///     writer.write_event(&event?)?;
+    /// }
+    /// assert_eq!(writer.finish()?, br#"{"id":7,"items":[{"sku":"a"},{"sku":"b"}]}"#);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    fn keep_paths<P>(self, patterns: P) -> KeepPaths<Self>
+    where
+        P: IntoIterator<Item = JsonPath>,
+    {
+        KeepPaths {
+            inner: self,
+            patterns: patterns.into_iter().collect(),
+            tracker: Tracker::default(),
+            skip: 0,
+            keep: 0,
+            held: None,
+            queued: None,
+        }
+    }
+
+    /// Renames object keys. `rename` is called with the path of each
+    /// member (ending in the original key) and the key, and returns the
+    /// new key, or `None` to leave it unchanged.
+    fn rename_keys<F>(self, rename: F) -> RenameKeys<Self, F>
+    where
+        F: FnMut(&JsonPath, &str) -> Option<String>,
+    {
+        RenameKeys {
+            inner: self,
+            rename,
+            tracker: Tracker::default(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = io::Result<Event>>> EventFilter for I {}
+
+/// Adapter returned by [`EventFilter::skip_paths`].
+pub struct SkipPaths<I> {
+    inner: I,
+    patterns: Vec<JsonPath>,
+    tracker: Tracker,
+    // Depth inside a subtree being removed.
+    skip: usize,
+}
+
+impl<I: Iterator<Item = io::Result<Event>>> Iterator for SkipPaths<I> {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let event = match self.inner.next()? {
+                Ok(event) => event,
+                Err(e) => return Some(Err(e)),
+            };
+            self.tracker.enter(&event);
+            let emit = if self.skip > 0 {
+                self.skip = skip_depth(self.skip, &event);
+                false
+            } else if !is_end(&event) && matches_any(&self.patterns, &self.tracker.path) {
+                // A member's key shares its value's path, so the value is
+                // removed by this branch too.
+                self.skip = skip_depth(0, &event);
+                false
+            } else {
+                true
+            };
+            self.tracker.leave(&event);
+            if emit {
+                return Some(Ok(event));
+            }
+        }
+    }
+}
+
+/// Adapter returned by [`EventFilter::keep_paths`].
+pub struct KeepPaths<I> {
+    inner: I,
+    patterns: Vec<JsonPath>,
+    tracker: Tracker,
+    // Depth inside a subtree being removed.
+    skip: usize,
+    // Depth inside a matched subtree being passed through.
+    keep: usize,
+    // A key whose value is kept only if it turns out to be a container.
+    held: Option<Event>,
+    // An event to yield before reading further.
+    queued: Option<Event>,
+}
+
+impl<I: Iterator<Item = io::Result<Event>>> Iterator for KeepPaths<I> {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.queued.take() {
+            return Some(Ok(event));
+        }
+        loop {
+            let event = match self.inner.next()? {
+                Ok(event) => event,
+                Err(e) => return Some(Err(e)),
+            };
+            self.tracker.enter(&event);
+            let path = &self.tracker.path;
+            let emit = if self.skip > 0 {
+                self.skip = skip_depth(self.skip, &event);
+                false
+            } else if self.keep > 0 {
+                self.keep = skip_depth(self.keep, &event);
+                true
+            } else {
+                let held = self.held.take();
+                match event {
+                    Event::Key(_) if matches_any(&self.patterns, path) => true,
+                    Event::Key(_) => {
+                        if leads_to_any(&self.patterns, path) {
+                            self.held = Some(event.clone());
+                        }
+                        false
+                    }
+                    Event::StartArray | Event::StartObject if matches_any(&self.patterns, path) => {
+                        self.keep = 1;
+                        true
+                    }
+                    Event::StartArray | Event::StartObject
+                        if leads_to_any(&self.patterns, path) =>
+                    {
+                        if let Some(key) = held {
+                            self.tracker.leave(&event);
+                            self.queued = Some(event);
+                            return Some(Ok(key));
+                        }
+                        true
+                    }
+                    Event::StartArray | Event::StartObject => {
+                        self.skip = 1;
+                        false
+                    }
+                    Event::Value(_) => matches_any(&self.patterns, path),
+                    // Only containers leading to a match are open here.
+                    Event::EndArray | Event::EndObject => true,
+                }
+            };
+            self.tracker.leave(&event);
+            if emit {
+                return Some(Ok(event));
+            }
+        }
+    }
+}
+
+/// Adapter returned by [`EventFilter::rename_keys`].
+pub struct RenameKeys<I, F> {
+    inner: I,
+    rename: F,
+    tracker: Tracker,
+}
+
+impl<I, F> Iterator for RenameKeys<I, F>
+where
+    I: Iterator<Item = io::Result<Event>>,
+    F: FnMut(&JsonPath, &str) -> Option<String>,
+{
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut event = match self.inner.next()? {
+            Ok(event) => event,
+            Err(e) => return Some(Err(e)),
+        };
+        self.tracker.enter(&event);
+        let renamed = match &event {
+            Event::Key(key) => (self.rename)(&self.tracker.path, key),
+            _ => None,
+        };
+        self.tracker.leave(&event);
+        if let Some(key) = renamed {
+            event = Event::Key(key);
+        }
+        Some(Ok(event))
+    }
+}
+
+// Follows the path of the current value through an event stream.
+#[derive(Debug, Default)]
+struct Tracker {
+    path: JsonPath,
+    // One entry per open container: the next element index for arrays,
+    // `None` for objects.
+    frames: Vec<Option<usize>>,
+}
+
+impl Tracker {
+    // Called before handling `event`. Afterwards `path` is the path of the
+    // value the event starts, of the member a key introduces, or of the
+    // container an end event closes.
+    fn enter(&mut self, event: &Event) {
+        match event {
+            Event::Key(key) => self.path.push(key.as_str()),
+            Event::Value(_) | Event::StartArray | Event::StartObject => {
+                if let Some(Some(index)) = self.frames.last_mut() {
+                    self.path.push(*index);
+                    *index += 1;
+                }
+            }
+            Event::EndArray | Event::EndObject => {}
+        }
+    }
+
+    // Called after handling `event`.
+    fn leave(&mut self, event: &Event) {
+        match event {
+            Event::StartArray => self.frames.push(Some(0)),
+            Event::StartObject => self.frames.push(None),
+            Event::Key(_) => {}
+            Event::Value(_) => self.end_value(),
+            Event::EndArray | Event::EndObject => {
+                self.frames.pop();
+                self.end_value();
+            }
+        }
+    }
+
+    fn end_value(&mut self) {
+        if !self.frames.is_empty() {
+            self.path.pop();
+        }
+    }
+}
+
+fn is_end(event: &Event) -> bool {
+    matches!(event, Event::EndArray | Event::EndObject)
+}
+
+// Returns the subtree depth after `event`, for a walk that started at
+// `depth`.
+fn skip_depth(depth: usize, event: &Event) -> usize {
+    match event {
+        Event::StartArray | Event::StartObject => depth + 1,
+        Event::EndArray | Event::EndObject => depth - 1,
+        _ => depth,
+    }
+}
+
+pub(crate) fn segment_matches(pattern: &Segment, segment: &Segment) -> bool {
+    match (pattern, segment) {
+        (Segment::Key(p), _) if p == "*" => true,
+        (Segment::Key(p), Segment::Key(s)) => p == s,
+        (Segment::Index(p), Segment::Index(s)) => p == s,
+        // A dotted pattern like `a.0` parses `0` as an index, but it may
+        // name an object key.
+        (Segment::Index(p), Segment::Key(s)) => s.parse() == Ok(*p),
+        (Segment::Key(_), Segment::Index(_)) => false,
+    }
+}
+
+fn prefix_matches(pattern: &JsonPath, path: &JsonPath) -> bool {
+    pattern
+        .segments()
+        .iter()
+        .zip(path.segments())
+        .all(|(p, s)| segment_matches(p, s))
+}
+
+fn matches_any(patterns: &[JsonPath], path: &JsonPath) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| pattern.len() == path.len() && prefix_matches(pattern, path))
+}
+
+// Whether some pattern lies strictly inside the value at `path`.
+fn leads_to_any(patterns: &[JsonPath], path: &JsonPath) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| pattern.len() > path.len() && prefix_matches(pattern, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+    use crate::stream::{EventWriter, JsonStreamParser};
+
+    fn rewrite<'a, F, I>(input: &'a str, filter: F) -> String
+    where
+        F: FnOnce(JsonStreamParser<&'a [u8]>) -> I,
+        I: Iterator<Item = io::Result<Event>>,
+    {
+        let mut writer = EventWriter::new(Vec::new());
+        for event in filter(JsonStreamParser::new(input.as_bytes())) {
+            writer.write_event(&event.unwrap()).unwrap();
+        }
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    fn paths(dotted: &[&str]) -> Vec<JsonPath> {
+        dotted.iter().map(|p| JsonPath::parse_dotted(p)).collect()
+    }
+
+    const INPUT: &str = r#"{"a": {"b": [1, {"c": 2, "d": 3}], "e": []}, "f": "g", "0": null}"#;
+
+    #[test]
+    fn test_skip_paths() {
+        let skip = |dotted: &[&str]| rewrite(INPUT, |events| events.skip_paths(paths(dotted)));
+        assert_eq!(skip(&[]), parse_json(INPUT).unwrap().to_string());
+        assert_eq!(skip(&["a"]), r#"{"f":"g","0":null}"#);
+        assert_eq!(
+            skip(&["a.b.0", "a.b.1.d", "0"]),
+            r#"{"a":{"b":[{"c":2}],"e":[]},"f":"g"}"#
+        );
+        assert_eq!(
+            skip(&["*.b", "a.e", "missing"]),
+            r#"{"a":{},"f":"g","0":null}"#
+        );
+        assert_eq!(skip(&[""]), "");
+    }
+
+    #[test]
+    fn test_keep_paths() {
+        let keep = |dotted: &[&str]| rewrite(INPUT, |events| events.keep_paths(paths(dotted)));
+        assert_eq!(keep(&[]), "");
+        assert_eq!(keep(&[""]), parse_json(INPUT).unwrap().to_string());
+        assert_eq!(keep(&["a.b.1.d", "0"]), r#"{"a":{"b":[{"d":3}]},"0":null}"#);
+        assert_eq!(keep(&["a.e", "f"]), r#"{"a":{"e":[]},"f":"g"}"#);
+        // Containers leading to a pattern are kept even if it never matches.
+        assert_eq!(keep(&["a.b.*.x"]), r#"{"a":{"b":[{}]}}"#);
+        assert_eq!(
+            rewrite("[[1, 2], [3]]", |events| events.keep_paths(paths(&["*.1"]))),
+            "[[2],[]]"
+        );
+    }
+
+    #[test]
+    fn test_rename_keys_and_stacking() {
+        let output = rewrite(INPUT, |events| {
+            events
+                .rename_keys(|path, key| (path.len() > 1 || key == "f").then(|| key.to_uppercase()))
+                // Sees the renamed keys.
+                .skip_paths(paths(&["a.B"]))
+        });
+        assert_eq!(output, r#"{"a":{"E":[]},"F":"g","0":null}"#);
+    }
+
+    #[test]
+    fn test_errors_pass_through() {
+        let mut events = JsonStreamParser::new(&b"[1, {\"a\": @}]"[..])
+            .skip_paths(paths(&["1"]))
+            .keep_paths(paths(&["*"]));
+        assert_eq!(events.next().unwrap().unwrap(), Event::StartArray);
+        assert!(events.next().unwrap().is_ok());
+        assert!(events.next().unwrap().is_err());
+        assert!(events.next().is_none());
+    }
+}
@@ -0,0 +1,298 @@
+//! Flattening nested documents into single-level objects.
+//!
+//! Warehouses and search indexes often want one column or field per leaf
+//! value, named after the path to it: `{"a": {"b": [1]}}` becomes
+//! `{"a.b.0": 1}`. Systems disagree on the naming convention, so
+//! [`FlattenOptions`](crate::flatten::FlattenOptions) configures the
+//! separator between keys, how array indices are written, and what
+//! happens when two paths produce the same flattened key.
+
+use std::error;
+use std::fmt;
+
+use crate::map::JsonMap;
+use crate::path::{JsonPath, Segment};
+use crate::value::JsonValue;
+
+/// How array indices appear in flattened keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndexStyle {
+    /// Indices are keys like any other: `a.0.b`.
+    #[default]
+    Separated,
+    /// Indices are bracketed and attached to the previous key: `a[0].b`.
+    Bracketed,
+}
+
+/// What to do when two paths flatten to the same key, e.g. `{"a.b": 1}`
+/// and `{"a": {"b": 2}}` in one document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Collisions {
+    /// Fail with a [`FlattenError`].
+    #[default]
+    Error,
+    /// Keep both, appending `_1`, `_2`, ... to the later key until it is
+    /// unique.
+    Suffix,
+}
+
+/// Settings for [`flatten`](FlattenOptions::flatten).
+///
+/// The default joins keys with `.`, writes indices as plain keys, and
+/// reports collisions as errors.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::flatten::{Collisions, FlattenOptions, IndexStyle};
+/// use rust_json_parser::parser::parse_json;
+///
+/// let value = parse_json(r#"{"user": {"tags": ["a", "b"]}, "user.tags[0]": "x"}"#)?;
+/// let flat = FlattenOptions::new()
+///     .index_style(IndexStyle::Bracketed)
+///     .collisions(Collisions::Suffix)
+///     .flatten(&value)
+///     .unwrap();
+/// let keys: Vec<&str> = flat.keys().map(String::as_str).collect();
+/// assert_eq!(keys, ["user.tags[0]", "user.tags[1]", "user.tags[0]_1"]);
+///
+/// let flat = FlattenOptions::new().separator("__").flatten(&value).unwrap();
+/// assert!(flat.contains_key("user__tags__1"));
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlattenOptions {
+    separator: String,
+    index_style: IndexStyle,
+    collisions: Collisions,
+}
+
+impl Default for FlattenOptions {
+    fn default() -> Self {
+        Self {
+            separator: ".".to_string(),
+            index_style: IndexStyle::default(),
+            collisions: Collisions::default(),
+        }
+    }
+}
+
+impl FlattenOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the string placed between keys. Defaults to `.`.
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Sets how array indices are written.
+    pub fn index_style(mut self, style: IndexStyle) -> Self {
+        self.index_style = style;
+        self
+    }
+
+    /// Sets how colliding keys are handled.
+    pub fn collisions(mut self, collisions: Collisions) -> Self {
+        self.collisions = collisions;
+        self
+    }
+
+    /// Flattens `value` into an object with one member per leaf, in
+    /// document order.
+    ///
+    /// Leaves are scalars and empty arrays or objects. A scalar root
+    /// flattens to a single member with an empty key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FlattenError`] on the first collision when collisions
+    /// are set to [`Collisions::Error`].
+    pub fn flatten(&self, value: &JsonValue) -> Result<JsonMap, FlattenError> {
+        let mut flattener = Flattener {
+            options: self,
+            key: String::new(),
+            path: JsonPath::root(),
+            out: JsonMap::new(),
+        };
+        flattener.visit(value)?;
+        Ok(flattener.out)
+    }
+}
+
+/// Flattens `value` with the default [`FlattenOptions`].
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::flatten::flatten;
+/// use rust_json_parser::parser::parse_json;
+/// use rust_json_parser::value::JsonValue;
+///
+/// let flat = flatten(&parse_json(r#"{"a": {"b": [1, {}]}}"#)?).unwrap();
+/// assert_eq!(JsonValue::from(flat).to_string(), r#"{"a.b.0":1,"a.b.1":{}}"#);
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`FlattenError`] if two paths flatten to the same key.
+pub fn flatten(value: &JsonValue) -> Result<JsonMap, FlattenError> {
+    FlattenOptions::default().flatten(value)
+}
+
+/// Two paths in a document flattened to the same key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlattenError {
+    /// The flattened key.
+    pub key: String,
+    /// The path of the later value, whose key was already taken.
+    pub path: JsonPath,
+}
+
+impl fmt::Display for FlattenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Flattened key {:?} for {:?} is already taken",
+            self.key,
+            self.path.to_pointer()
+        )
+    }
+}
+
+impl error::Error for FlattenError {}
+
+struct Flattener<'o> {
+    options: &'o FlattenOptions,
+    // Flattened key of the value being visited.
+    key: String,
+    path: JsonPath,
+    out: JsonMap,
+}
+
+impl Flattener<'_> {
+    fn visit(&mut self, value: &JsonValue) -> Result<(), FlattenError> {
+        match value {
+            JsonValue::Array(elements) if !elements.is_empty() => {
+                for (i, element) in elements.iter().enumerate() {
+                    self.child(Segment::Index(i), element)?;
+                }
+                Ok(())
+            }
+            JsonValue::Object(map) if !map.is_empty() => {
+                for (k, v) in map.iter() {
+                    self.child(Segment::Key(k.clone()), v)?;
+                }
+                Ok(())
+            }
+            _ => self.emit(value),
+        }
+    }
+
+    fn child(&mut self, segment: Segment, value: &JsonValue) -> Result<(), FlattenError> {
+        let len = self.key.len();
+        match (&segment, self.options.index_style) {
+            (Segment::Index(i), IndexStyle::Bracketed) => {
+                self.key.push_str(&format!("[{}]", i));
+            }
+            _ => {
+                if !self.path.is_empty() {
+                    self.key.push_str(&self.options.separator);
+                }
+                self.key.push_str(&segment.to_string());
+            }
+        }
+        self.path.push(segment);
+        let result = self.visit(value);
+        self.path.pop();
+        self.key.truncate(len);
+        result
+    }
+
+    fn emit(&mut self, value: &JsonValue) -> Result<(), FlattenError> {
+        let mut key = self.key.clone();
+        if self.out.contains_key(&key) {
+            match self.options.collisions {
+                Collisions::Error => {
+                    return Err(FlattenError {
+                        key,
+                        path: self.path.clone(),
+                    });
+                }
+                Collisions::Suffix => {
+                    key = (1..)
+                        .map(|n| format!("{}_{}", self.key, n))
+                        .find(|candidate| !self.out.contains_key(candidate))
+                        .expect("unbounded suffix search");
+                }
+            }
+        }
+        self.out.insert(key, value.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    #[test]
+    fn test_index_styles_and_separator() {
+        let value = json!({"a": [{"b": 1}, [true]], "c": null});
+        let keys = |options: FlattenOptions| -> Vec<String> {
+            options.flatten(&value).unwrap().keys().cloned().collect()
+        };
+        assert_eq!(keys(FlattenOptions::new()), ["a.0.b", "a.1.0", "c"]);
+        assert_eq!(
+            keys(FlattenOptions::new().index_style(IndexStyle::Bracketed)),
+            ["a[0].b", "a[1][0]", "c"]
+        );
+        assert_eq!(
+            keys(FlattenOptions::new().separator("/")),
+            ["a/0/b", "a/1/0", "c"]
+        );
+    }
+
+    #[test]
+    fn test_roots_and_empty_containers() {
+        assert_eq!(
+            JsonValue::from(flatten(&json!(5)).unwrap()).to_string(),
+            r#"{"":5}"#
+        );
+        assert_eq!(
+            JsonValue::from(flatten(&json!({})).unwrap()).to_string(),
+            r#"{"":{}}"#
+        );
+        let bracketed = FlattenOptions::new().index_style(IndexStyle::Bracketed);
+        assert_eq!(
+            JsonValue::from(bracketed.flatten(&json!([[], "x"])).unwrap()).to_string(),
+            r#"{"[0]":[],"[1]":"x"}"#
+        );
+    }
+
+    #[test]
+    fn test_collisions() {
+        let value = json!({"a.b": 1, "a": {"b": 2}, "a.b_1": 3});
+        let err = flatten(&value).unwrap_err();
+        assert_eq!(err.key, "a.b");
+        assert_eq!(err.path.to_pointer(), "/a/b");
+        assert_eq!(
+            err.to_string(),
+            r#"Flattened key "a.b" for "/a/b" is already taken"#
+        );
+
+        let flat = FlattenOptions::new()
+            .collisions(Collisions::Suffix)
+            .flatten(&value)
+            .unwrap();
+        assert_eq!(
+            JsonValue::from(flat).to_string(),
+            r#"{"a.b":1,"a.b_1":2,"a.b_1_1":3}"#
+        );
+    }
+}
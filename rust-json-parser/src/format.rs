@@ -0,0 +1,581 @@
+//! Streaming pretty-printer driven by tokens.
+//!
+//! [`PrettyPrinter`](crate::format::PrettyPrinter) writes indented JSON
+//! directly from a [`Token`](crate::tokenizer::Token) stream without
+//! building a [`JsonValue`](crate::value::JsonValue) tree. Apart from the
+//! output writer it keeps only the current nesting depth, so reformatting
+//! a document costs the same memory whether it holds ten values or ten
+//! million.
+//!
+//! The layout matches the alternate `Debug` form of `JsonValue` (`{:#?}`):
+//! one element per line, `": "` after keys, and `[]` / `{}` for empty
+//! containers.
+//!
+//! [`FormatOptions`](crate::format::FormatOptions) formats a value tree
+//! instead, optionally in a relaxed JSON5 style for files people edit by
+//! hand, and with comments from a
+//! [`CommentHook`](crate::format::CommentHook) above chosen values.
+
+use std::io::{self, Write};
+
+use crate::path::{JsonPath, Segment};
+use crate::tokenizer::{Token, Tokenizer};
+use crate::value::{JsonFormat, JsonValue};
+
+/// Writes pretty-printed JSON one token at a time.
+///
+/// Tokens are written as they arrive; the printer does not check that the
+/// stream forms a valid document beyond rejecting unbalanced closers.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::format::PrettyPrinter;
+/// use rust_json_parser::tokenizer::Tokenizer;
+///
+/// let tokens = Tokenizer::new(r#"{"a":[1,2],"b":{}}"#).tokenize()?;
+/// let mut printer = PrettyPrinter::new(Vec::new(), 2);
+/// for token in &tokens {
+///     printer.write_token(token)?;
+/// }
+/// let output = String::from_utf8(printer.finish()?).unwrap();
+/// assert_eq!(output, "{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": {}\n}");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct PrettyPrinter<W: Write> {
+    out: W,
+    indent: usize,
+    depth: usize,
+    // An opening bracket was written and its newline is deferred until we
+    // know whether the container is empty.
+    pending_open: bool,
+}
+
+impl<W: Write> PrettyPrinter<W> {
+    /// Creates a printer writing to `out` with `indent` spaces per level.
+    pub fn new(out: W, indent: usize) -> Self {
+        Self {
+            out,
+            indent,
+            depth: 0,
+            pending_open: false,
+        }
+    }
+
+    /// Writes one token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails, or an
+    /// [`io::ErrorKind::InvalidData`] error for a closing bracket with no
+    /// matching opener.
+    pub fn write_token(&mut self, token: &Token) -> io::Result<()> {
+        let closing = matches!(token, Token::RightBrace | Token::RightBracket);
+        if std::mem::take(&mut self.pending_open) {
+            if closing {
+                self.depth -= 1;
+                return self.out.write_all(close_char(token));
+            }
+            self.newline()?;
+        }
+        match token {
+            Token::LeftBrace | Token::LeftBracket => {
+                self.out.write_all(if matches!(token, Token::LeftBrace) {
+                    b"{"
+                } else {
+                    b"["
+                })?;
+                self.depth += 1;
+                self.pending_open = true;
+                Ok(())
+            }
+            Token::RightBrace | Token::RightBracket => {
+                self.depth = self.depth.checked_sub(1).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "unbalanced closing bracket")
+                })?;
+                self.newline()?;
+                self.out.write_all(close_char(token))
+            }
+            Token::Comma => {
+                self.out.write_all(b",")?;
+                self.newline()
+            }
+            Token::Colon => self.out.write_all(b": "),
+            Token::String(s) => self.out.write_all(s.to_json_string().as_bytes()),
+            Token::Number(n) => write!(self.out, "{}", n),
+            Token::Boolean(b) => write!(self.out, "{}", b),
+            Token::Null => self.out.write_all(b"null"),
+        }
+    }
+
+    /// Flushes the writer and returns it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if flushing fails.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn newline(&mut self) -> io::Result<()> {
+        self.out.write_all(b"\n")?;
+        for _ in 0..self.depth * self.indent {
+            self.out.write_all(b" ")?;
+        }
+        Ok(())
+    }
+}
+
+fn close_char(token: &Token) -> &'static [u8] {
+    if matches!(token, Token::RightBrace) {
+        b"}"
+    } else {
+        b"]"
+    }
+}
+
+/// Pretty-prints a token stream to `out` with `indent` spaces per level.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::format::write_pretty;
+/// use rust_json_parser::tokenizer::Tokenizer;
+///
+/// let tokens = Tokenizer::new("[true, null]").tokenize()?;
+/// let mut out = Vec::new();
+/// write_pretty(tokens, &mut out, 4)?;
+/// assert_eq!(out, b"[\n    true,\n    null\n]");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if writing fails or the stream has unbalanced
+/// closing brackets.
+pub fn write_pretty<I, W>(tokens: I, out: W, indent: usize) -> io::Result<()>
+where
+    I: IntoIterator<Item = Token>,
+    W: Write,
+{
+    let mut printer = PrettyPrinter::new(out, indent);
+    for token in tokens {
+        printer.write_token(&token)?;
+    }
+    printer.finish().map(drop)
+}
+
+/// Tokenizes `input` and pretty-prints it to `out`.
+///
+/// Lexical errors are returned as [`io::ErrorKind::InvalidData`] wrapping
+/// the [`JsonError`](crate::error::JsonError). The structure of the input
+/// is not validated; use the parser first when that matters.
+///
+/// # Errors
+///
+/// Returns an error if the input cannot be tokenized or writing fails.
+pub fn reformat<W: Write>(input: &str, out: W, indent: usize) -> io::Result<()> {
+    let tokens = Tokenizer::new(input).tokenize()?;
+    write_pretty(tokens, out, indent)
+}
+
+/// Settings for formatting a [`JsonValue`] as text.
+///
+/// The default is strict, compact JSON, identical to `Display`. Setting
+/// `indent` gives the layout of `{:#?}`. The relaxed settings produce
+/// JSON5-style output for configuration files: [`relaxed`](Self::relaxed)
+/// leaves identifier keys unquoted and ends multi-line containers with a
+/// trailing comma.
+///
+/// A [`JsonParser`](crate::parser::JsonParser) with
+/// [`ParserOptions::lenient`](crate::parser::ParserOptions::lenient) reads
+/// single quotes and trailing commas back, but not unquoted keys; those
+/// need a JSON5 reader.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::format::FormatOptions;
+/// use rust_json_parser::parser::parse_json;
+///
+/// let value = parse_json(r#"{"port": 8080, "log-level": "info", "hosts": ["a"]}"#)?;
+/// assert_eq!(FormatOptions::default().format(&value), value.to_string());
+///
+/// let options = FormatOptions {
+///     single_quotes: true,
+///     ..FormatOptions::relaxed()
+/// };
+/// assert_eq!(
+///     options.format(&value),
+///     "{\n  port: 8080,\n  'log-level': 'info',\n  hosts: [\n    'a',\n  ],\n}"
+/// );
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Spaces per nesting level, with one element per line. `None` writes
+    /// everything on one line without spaces.
+    pub indent: Option<usize>,
+    /// Writes object keys that are identifiers (`[A-Za-z_$][A-Za-z0-9_$]*`)
+    /// without quotes.
+    pub unquoted_keys: bool,
+    /// Quotes strings and keys with `'` instead of `"`.
+    pub single_quotes: bool,
+    /// Writes a comma after the last element of every non-empty array and
+    /// object. Only applies when `indent` is set.
+    pub trailing_commas: bool,
+}
+
+impl FormatOptions {
+    /// JSON5-style settings: two-space indentation, unquoted identifier
+    /// keys, and trailing commas. Strings keep double quotes.
+    pub fn relaxed() -> Self {
+        Self {
+            indent: Some(2),
+            unquoted_keys: true,
+            trailing_commas: true,
+            ..Self::default()
+        }
+    }
+
+    /// Formats `value` with these settings.
+    pub fn format(&self, value: &JsonValue) -> String {
+        let mut out = String::new();
+        self.write_value(&mut out, value, 0, &mut None);
+        out
+    }
+
+    /// Formats `value` with a comment from `hook` above each value it
+    /// annotates, for JSONC files read by people.
+    ///
+    /// With `indent` set, each line of a comment becomes a `//` comment on
+    /// its own line, indented like the value, above an object member's key
+    /// or an array element. Without it, the comment is written inline as
+    /// `/* ... */`. Either way the output reads back with
+    /// [`JsonParser::allow_comments`](crate::parser::JsonParser::allow_comments).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::format::FormatOptions;
+    /// use rust_json_parser::json;
+    /// use rust_json_parser::path::JsonPath;
+    /// use rust_json_parser::value::JsonValue;
+    ///
+    /// let config = json!({"port": 8080, "legacy": {"timeout": 30}});
+    /// let options = FormatOptions { indent: Some(2), ..FormatOptions::default() };
+    /// let text = options.format_with_comments(&config, |path: &JsonPath, _: &JsonValue| {
+    ///     match path.to_pointer().as_str() {
+    ///         "" => Some("Generated by confgen; do not edit.".to_string()),
+    ///         "/legacy" => Some("Deprecated:\nremoved in v3.".to_string()),
+    ///         _ => None,
+    ///     }
+    /// });
+    /// assert_eq!(
+    ///     text,
+    ///     "// Generated by confgen; do not edit.\n{\n  \"port\": 8080,\n  // Deprecated:\n  // removed in v3.\n  \"legacy\": {\n    \"timeout\": 30\n  }\n}"
+    /// );
+    /// ```
+    pub fn format_with_comments(&self, value: &JsonValue, mut hook: impl CommentHook) -> String {
+        let mut comments = Some(Comments {
+            hook: &mut hook,
+            path: JsonPath::root(),
+        });
+        let mut out = String::new();
+        self.write_comment(&mut out, value, 0, &mut comments);
+        self.write_value(&mut out, value, 0, &mut comments);
+        out
+    }
+
+    fn write_value(
+        &self,
+        out: &mut String,
+        value: &JsonValue,
+        depth: usize,
+        comments: &mut Option<Comments>,
+    ) {
+        match value {
+            JsonValue::Array(elements) => {
+                let items: Vec<_> = elements.iter().enumerate().collect();
+                self.write_container(out, ('[', ']'), &items, depth, |out, &(i, element)| {
+                    self.write_item(out, Segment::Index(i), None, element, depth + 1, comments);
+                });
+            }
+            JsonValue::Object(map) => {
+                let members: Vec<_> = map.iter().collect();
+                self.write_container(out, ('{', '}'), &members, depth, |out, &(key, value)| {
+                    let segment = Segment::Key(key.clone());
+                    self.write_item(out, segment, Some(key), value, depth + 1, comments);
+                });
+            }
+            JsonValue::String(s) => self.write_string(out, s),
+            other => out.push_str(&other.to_string()),
+        }
+    }
+
+    fn write_item(
+        &self,
+        out: &mut String,
+        segment: Segment,
+        key: Option<&str>,
+        value: &JsonValue,
+        depth: usize,
+        comments: &mut Option<Comments>,
+    ) {
+        if let Some(comments) = comments {
+            comments.path.push(segment);
+        }
+        self.write_comment(out, value, depth, comments);
+        if let Some(key) = key {
+            self.write_key(out, key);
+            out.push_str(if self.indent.is_some() { ": " } else { ":" });
+        }
+        self.write_value(out, value, depth, comments);
+        if let Some(comments) = comments {
+            comments.path.pop();
+        }
+    }
+
+    // Writes the hook's comment for `value`, leaving the output where the
+    // value should start.
+    fn write_comment(
+        &self,
+        out: &mut String,
+        value: &JsonValue,
+        depth: usize,
+        comments: &mut Option<Comments>,
+    ) {
+        let Some(comments) = comments else {
+            return;
+        };
+        let Some(text) = comments.hook.comment(&comments.path, value) else {
+            return;
+        };
+        if self.indent.is_some() {
+            for line in text.lines() {
+                out.push_str("//");
+                if !line.is_empty() {
+                    out.push(' ');
+                    out.push_str(line);
+                }
+                self.newline(out, depth);
+            }
+        } else {
+            let text: Vec<&str> = text.lines().collect();
+            out.push_str("/* ");
+            out.push_str(&text.join(" ").replace("*/", "* /"));
+            out.push_str(" */");
+        }
+    }
+
+    fn write_container<T>(
+        &self,
+        out: &mut String,
+        (open, close): (char, char),
+        items: &[T],
+        depth: usize,
+        mut write_item: impl FnMut(&mut String, &T),
+    ) {
+        out.push(open);
+        if items.is_empty() {
+            out.push(close);
+            return;
+        }
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            self.newline(out, depth + 1);
+            write_item(out, item);
+        }
+        if self.trailing_commas && self.indent.is_some() {
+            out.push(',');
+        }
+        self.newline(out, depth);
+        out.push(close);
+    }
+
+    fn newline(&self, out: &mut String, depth: usize) {
+        if let Some(indent) = self.indent {
+            out.push('\n');
+            out.extend(std::iter::repeat_n(' ', indent * depth));
+        }
+    }
+
+    fn write_key(&self, out: &mut String, key: &str) {
+        if self.unquoted_keys && is_identifier(key) {
+            out.push_str(key);
+        } else {
+            self.write_string(out, key);
+        }
+    }
+
+    fn write_string(&self, out: &mut String, s: &str) {
+        if !self.single_quotes {
+            out.push_str(&s.to_string().to_json_string());
+            return;
+        }
+        out.push('\'');
+        for c in s.chars() {
+            match c {
+                '\'' => out.push_str("\\'"),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                '\u{8}' => out.push_str("\\b"),
+                '\u{c}' => out.push_str("\\f"),
+                c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => out.push(c),
+            }
+        }
+        out.push('\'');
+    }
+}
+
+/// Supplies the comments written by
+/// [`FormatOptions::format_with_comments`].
+///
+/// Closures taking the path and the value are hooks, so comments can be
+/// looked up in a table with
+/// `|path: &JsonPath, _: &JsonValue| notes.get(path).cloned()`.
+pub trait CommentHook {
+    /// Returns the comment to write above the value at `path`, or `None`.
+    /// The text may span several lines.
+    fn comment(&mut self, path: &JsonPath, value: &JsonValue) -> Option<String>;
+}
+
+impl<F: FnMut(&JsonPath, &JsonValue) -> Option<String>> CommentHook for F {
+    fn comment(&mut self, path: &JsonPath, value: &JsonValue) -> Option<String> {
+        self(path, value)
+    }
+}
+
+// The hook and the path of the value being written, which is only
+// tracked when there is a hook.
+struct Comments<'h> {
+    hook: &'h mut dyn CommentHook,
+    path: JsonPath,
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{JsonParser, ParserOptions, parse_json};
+
+    fn pretty(input: &str, indent: usize) -> String {
+        let mut out = Vec::new();
+        reformat(input, &mut out, indent).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_matches_tree_pretty_printer() {
+        let input = r#"{"name": "A\"B", "tags": [], "nested": {"x": [1.5, {"y": null}]}, "o": {}}"#;
+        let value = parse_json(input).unwrap();
+        assert_eq!(pretty(input, 2), format!("{:#?}", value));
+    }
+
+    #[test]
+    fn test_scalars_and_zero_indent() {
+        assert_eq!(pretty("  42 ", 2), "42");
+        assert_eq!(pretty("[1,[2]]", 0), "[\n1,\n[\n2\n]\n]");
+    }
+
+    #[test]
+    fn test_format_options_strict_layouts() {
+        let value = parse_json(r#"{"a": [1, "x\ty"], "b": {}, "c": []}"#).unwrap();
+        assert_eq!(FormatOptions::default().format(&value), value.to_string());
+        let indented = FormatOptions {
+            indent: Some(2),
+            ..FormatOptions::default()
+        };
+        assert_eq!(indented.format(&value), format!("{:#?}", value));
+    }
+
+    #[test]
+    fn test_format_options_relaxed() {
+        let value = parse_json(r#"{"ok_1": "it's", "1st": {"$x": []}, "": "q\""}"#).unwrap();
+        assert_eq!(
+            FormatOptions::relaxed().format(&value),
+            "{\n  ok_1: \"it's\",\n  \"1st\": {\n    $x: [],\n  },\n  \"\": \"q\\\"\",\n}"
+        );
+
+        // Single-quoted, trailing-comma output reads back leniently.
+        let options = FormatOptions {
+            unquoted_keys: false,
+            single_quotes: true,
+            ..FormatOptions::relaxed()
+        };
+        let text = options.format(&value);
+        assert!(text.contains(r"'it\'s'"));
+        let mut parser = JsonParser::with_options(ParserOptions::lenient());
+        assert_eq!(parser.parse(&text).unwrap(), value);
+        let control = JsonValue::from(vec!["\u{1}\u{8}\u{c}"]);
+        let text = options.format(&control);
+        assert!(text.contains(r"'\u0001\b\f'"));
+        assert_eq!(parser.parse(&text).unwrap(), control);
+
+        // Trailing commas need line breaks.
+        let compact = FormatOptions {
+            indent: None,
+            ..FormatOptions::relaxed()
+        };
+        assert_eq!(
+            compact.format(&value),
+            r#"{ok_1:"it's","1st":{$x:[]},"":"q\""}"#
+        );
+    }
+
+    #[test]
+    fn test_comments_read_back_as_jsonc() {
+        let value = parse_json(r#"{"a": [1, {"b": null}], "c": "x"}"#).unwrap();
+        let hook = |path: &JsonPath, value: &JsonValue| match path.to_pointer().as_str() {
+            "/a/1/b" => Some("first\n\nthird */ end".to_string()),
+            "/a/0" | "/c" => Some(format!("was {value}")),
+            _ => None,
+        };
+        let indented = FormatOptions {
+            indent: Some(2),
+            ..FormatOptions::default()
+        };
+        let pretty = indented.format_with_comments(&value, hook);
+        assert!(pretty.contains("\n      // first\n      //\n      // third */ end\n      \"b\""));
+        assert!(pretty.contains("\n  // was \"x\"\n  \"c\": \"x\""));
+
+        let compact = FormatOptions::default();
+        let text = compact.format_with_comments(&value, hook);
+        assert_eq!(
+            text,
+            r#"{"a":[/* was 1 */1,{/* first  third * / end */"b":null}],/* was "x" */"c":"x"}"#
+        );
+        let mut parser = JsonParser::new().allow_comments(true);
+        assert_eq!(parser.parse(&pretty).unwrap(), value);
+        assert_eq!(parser.parse(&text).unwrap(), value);
+        assert_eq!(
+            compact.format_with_comments(&value, |_: &JsonPath, _: &JsonValue| None),
+            compact.format(&value)
+        );
+    }
+
+    #[test]
+    fn test_unbalanced_closer_is_rejected() {
+        let err = write_pretty([Token::RightBracket], Vec::new(), 2).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_lexical_error_is_invalid_data() {
+        let err = reformat("[@]", Vec::new(), 2).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
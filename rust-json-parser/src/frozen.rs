@@ -0,0 +1,135 @@
+//! Immutable, cheaply shareable documents.
+//!
+//! Configuration parsed once and handed to many consumers is easy to
+//! modify by accident: one component "fixes up" a value and every other
+//! component sees the change. A [`FrozenJson`](crate::frozen::FrozenJson)
+//! rules that out at compile time -- it only hands out shared references
+//! -- and clones in O(1) by sharing the same tree behind an
+//! [`Arc`](std::sync::Arc).
+
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::value::JsonValue;
+
+/// A read-only, reference-counted [`JsonValue`].
+///
+/// Derefs to `JsonValue`, so every read accessor is available directly.
+/// There is no way to obtain a `&mut JsonValue`; to change a frozen
+/// document, [`thaw`](FrozenJson::thaw) it into an independent copy.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::parse_json;
+/// use rust_json_parser::value::JsonValue;
+///
+/// let config = parse_json(r#"{"retries": 3}"#)?.freeze();
+/// let shared = config.clone(); // no deep copy
+/// assert!(config.ptr_eq(&shared));
+/// assert_eq!(shared.get("retries").and_then(|v| v.as_i64()), Some(3));
+///
+/// let mut copy = config.thaw();
+/// copy.insert("retries", JsonValue::Number(5.0.into())).unwrap();
+/// assert_eq!(config.get("retries").and_then(|v| v.as_i64()), Some(3));
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct FrozenJson {
+    value: Arc<JsonValue>,
+}
+
+impl FrozenJson {
+    /// Freezes a value.
+    pub fn new(value: JsonValue) -> Self {
+        Self {
+            value: Arc::new(value),
+        }
+    }
+
+    /// Returns a mutable deep copy of the document.
+    pub fn thaw(&self) -> JsonValue {
+        JsonValue::clone(&self.value)
+    }
+
+    /// Returns the value, copying it only if other handles still share it.
+    pub fn into_inner(self) -> JsonValue {
+        Arc::try_unwrap(self.value).unwrap_or_else(|shared| JsonValue::clone(&shared))
+    }
+
+    /// Returns `true` if both handles share the same underlying document.
+    pub fn ptr_eq(&self, other: &FrozenJson) -> bool {
+        Arc::ptr_eq(&self.value, &other.value)
+    }
+}
+
+impl Deref for FrozenJson {
+    type Target = JsonValue;
+
+    fn deref(&self) -> &JsonValue {
+        &self.value
+    }
+}
+
+impl AsRef<JsonValue> for FrozenJson {
+    fn as_ref(&self) -> &JsonValue {
+        &self.value
+    }
+}
+
+impl From<JsonValue> for FrozenJson {
+    fn from(value: JsonValue) -> Self {
+        Self::new(value)
+    }
+}
+
+impl fmt::Debug for FrozenJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FrozenJson").field(&*self.value).finish()
+    }
+}
+
+impl fmt::Display for FrozenJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.value, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_shareable_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FrozenJson>();
+
+        let frozen = parse_json(r#"{"n": 1}"#).unwrap().freeze();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let frozen = frozen.clone();
+                std::thread::spawn(move || frozen.get("n").and_then(|v| v.as_i64()))
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Some(1));
+        }
+    }
+
+    #[test]
+    fn test_into_inner_copies_only_when_shared() {
+        let frozen = FrozenJson::from(JsonValue::Boolean(true));
+        let other = frozen.clone();
+        assert_eq!(frozen.into_inner(), JsonValue::Boolean(true));
+        assert_eq!(other.into_inner(), JsonValue::Boolean(true));
+    }
+
+    #[test]
+    fn test_display_and_debug() {
+        let frozen = parse_json("[1]").unwrap().freeze();
+        assert_eq!(frozen.to_string(), "[1]");
+        assert_eq!(format!("{:?}", frozen), "FrozenJson(Array([Number(1)]))");
+    }
+}
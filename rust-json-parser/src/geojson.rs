@@ -0,0 +1,624 @@
+//! Typed GeoJSON (RFC 7946) views over parsed JSON.
+//!
+//! The types in this module borrow from an existing
+//! [`JsonValue`](crate::value::JsonValue) instead of copying it into a
+//! separate object model. [`GeoJson::from_value`](crate::geojson::GeoJson::from_value)
+//! validates the whole document once -- object types, required members,
+//! and coordinate shapes -- after which the accessors can rely on the
+//! structure being well-formed.
+
+use std::error;
+use std::fmt;
+
+use crate::path::JsonPath;
+use crate::value::JsonValue;
+
+/// An error describing why a value is not valid GeoJSON.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoJsonError {
+    /// Location of the offending value (e.g. `/features/3/geometry`).
+    pub path: JsonPath,
+    /// A description of what was wrong.
+    pub reason: String,
+}
+
+impl fmt::Display for GeoJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "Invalid GeoJSON at /: {}", self.reason)
+        } else {
+            write!(f, "Invalid GeoJSON at {}: {}", self.path, self.reason)
+        }
+    }
+}
+
+impl error::Error for GeoJsonError {}
+
+fn invalid(path: &JsonPath, reason: impl Into<String>) -> GeoJsonError {
+    GeoJsonError {
+        path: path.clone(),
+        reason: reason.into(),
+    }
+}
+
+/// The seven geometry types defined by RFC 7946.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeometryKind {
+    /// A single position.
+    Point,
+    /// An array of positions.
+    MultiPoint,
+    /// Two or more positions forming a line.
+    LineString,
+    /// An array of line strings.
+    MultiLineString,
+    /// An array of closed linear rings; the first is the exterior.
+    Polygon,
+    /// An array of polygons.
+    MultiPolygon,
+    /// A heterogeneous list of geometries.
+    GeometryCollection,
+}
+
+impl GeometryKind {
+    fn from_name(name: &str) -> Option<GeometryKind> {
+        match name {
+            "Point" => Some(GeometryKind::Point),
+            "MultiPoint" => Some(GeometryKind::MultiPoint),
+            "LineString" => Some(GeometryKind::LineString),
+            "MultiLineString" => Some(GeometryKind::MultiLineString),
+            "Polygon" => Some(GeometryKind::Polygon),
+            "MultiPolygon" => Some(GeometryKind::MultiPolygon),
+            "GeometryCollection" => Some(GeometryKind::GeometryCollection),
+            _ => None,
+        }
+    }
+}
+
+/// A single coordinate: longitude, latitude, and optional altitude.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    /// Longitude (easting).
+    pub x: f64,
+    /// Latitude (northing).
+    pub y: f64,
+    /// Optional altitude.
+    pub z: Option<f64>,
+}
+
+impl Position {
+    // Only called on validated coordinates.
+    fn from_value(value: &JsonValue) -> Position {
+        let coords = value.as_array().map(Vec::as_slice).unwrap_or(&[]);
+        let number = |i: usize| coords.get(i).and_then(JsonValue::as_f64);
+        Position {
+            x: number(0).unwrap_or(0.0),
+            y: number(1).unwrap_or(0.0),
+            z: number(2),
+        }
+    }
+}
+
+/// A validated GeoJSON document.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeoJson<'a> {
+    /// A `FeatureCollection` object.
+    FeatureCollection(FeatureCollection<'a>),
+    /// A `Feature` object.
+    Feature(Feature<'a>),
+    /// A bare geometry object.
+    Geometry(Geometry<'a>),
+}
+
+impl<'a> GeoJson<'a> {
+    /// Validates a value as GeoJSON and returns a typed view of it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::geojson::{GeoJson, GeometryKind};
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let doc = parse_json(r#"{
+    ///     "type": "Feature",
+    ///     "geometry": {"type": "Point", "coordinates": [102.0, 0.5]},
+    ///     "properties": {"name": "Dinagat Islands"}
+    /// }"#)?;
+    /// let GeoJson::Feature(feature) = GeoJson::from_value(&doc).unwrap() else {
+    ///     panic!("expected a feature");
+    /// };
+    /// let geometry = feature.geometry().unwrap();
+    /// assert_eq!(geometry.kind(), GeometryKind::Point);
+    /// assert_eq!(geometry.point().map(|p| p.x), Some(102.0));
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GeoJsonError`] pointing at the first invalid member.
+    pub fn from_value(value: &'a JsonValue) -> Result<GeoJson<'a>, GeoJsonError> {
+        match type_name(value, &JsonPath::root())? {
+            "FeatureCollection" => {
+                FeatureCollection::from_value(value).map(GeoJson::FeatureCollection)
+            }
+            "Feature" => Feature::from_value(value).map(GeoJson::Feature),
+            _ => Geometry::from_value(value).map(GeoJson::Geometry),
+        }
+    }
+}
+
+/// A validated `FeatureCollection`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeatureCollection<'a> {
+    value: &'a JsonValue,
+}
+
+impl<'a> FeatureCollection<'a> {
+    /// Validates a value as a `FeatureCollection`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GeoJsonError`] if the value or any of its features is
+    /// invalid.
+    pub fn from_value(value: &'a JsonValue) -> Result<FeatureCollection<'a>, GeoJsonError> {
+        validate_feature_collection(value, &JsonPath::root())?;
+        Ok(FeatureCollection { value })
+    }
+
+    /// Returns the underlying JSON value.
+    pub fn as_value(&self) -> &'a JsonValue {
+        self.value
+    }
+
+    /// Returns the number of features in the collection.
+    pub fn len(&self) -> usize {
+        self.raw_features().len()
+    }
+
+    /// Returns `true` if the collection has no features.
+    pub fn is_empty(&self) -> bool {
+        self.raw_features().is_empty()
+    }
+
+    /// Iterates over the features in the collection.
+    pub fn features(&self) -> impl Iterator<Item = Feature<'a>> + 'a {
+        self.raw_features().iter().map(|value| Feature { value })
+    }
+
+    fn raw_features(&self) -> &'a [JsonValue] {
+        self.value
+            .get("features")
+            .and_then(JsonValue::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+}
+
+/// A validated `Feature`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Feature<'a> {
+    value: &'a JsonValue,
+}
+
+impl<'a> Feature<'a> {
+    /// Validates a value as a `Feature`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GeoJsonError`] if the value or its geometry is invalid.
+    pub fn from_value(value: &'a JsonValue) -> Result<Feature<'a>, GeoJsonError> {
+        validate_feature(value, &JsonPath::root())?;
+        Ok(Feature { value })
+    }
+
+    /// Returns the underlying JSON value.
+    pub fn as_value(&self) -> &'a JsonValue {
+        self.value
+    }
+
+    /// Returns the feature's geometry, or `None` for an unlocated feature.
+    pub fn geometry(&self) -> Option<Geometry<'a>> {
+        match self.value.get("geometry") {
+            Some(value @ JsonValue::Object(_)) => Some(Geometry { value }),
+            _ => None,
+        }
+    }
+
+    /// Returns the `properties` member, or `None` if it is `null`.
+    pub fn properties(&self) -> Option<&'a JsonValue> {
+        self.value.get("properties").filter(|p| !p.is_null())
+    }
+
+    /// Looks up a single property by name.
+    pub fn property(&self, name: &str) -> Option<&'a JsonValue> {
+        self.properties().and_then(|p| p.get(name))
+    }
+
+    /// Returns the feature identifier, if present.
+    pub fn id(&self) -> Option<&'a JsonValue> {
+        self.value.get("id")
+    }
+}
+
+/// A validated geometry object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geometry<'a> {
+    value: &'a JsonValue,
+}
+
+impl<'a> Geometry<'a> {
+    /// Validates a value as a geometry object.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GeoJsonError`] if the type is unknown or the coordinates
+    /// do not have the shape required by the type.
+    pub fn from_value(value: &'a JsonValue) -> Result<Geometry<'a>, GeoJsonError> {
+        validate_geometry(value, &JsonPath::root())?;
+        Ok(Geometry { value })
+    }
+
+    /// Returns the underlying JSON value.
+    pub fn as_value(&self) -> &'a JsonValue {
+        self.value
+    }
+
+    /// Returns the geometry type.
+    pub fn kind(&self) -> GeometryKind {
+        self.value
+            .get("type")
+            .and_then(JsonValue::as_str)
+            .and_then(GeometryKind::from_name)
+            .unwrap_or(GeometryKind::GeometryCollection)
+    }
+
+    /// Returns the raw `coordinates` member. `None` for collections.
+    pub fn coordinates(&self) -> Option<&'a JsonValue> {
+        self.value.get("coordinates")
+    }
+
+    /// Returns the position of a `Point`.
+    pub fn point(&self) -> Option<Position> {
+        match self.kind() {
+            GeometryKind::Point => self.coordinates().map(Position::from_value),
+            _ => None,
+        }
+    }
+
+    /// Returns the positions of a `LineString` or `MultiPoint`.
+    pub fn positions(&self) -> Option<Vec<Position>> {
+        match self.kind() {
+            GeometryKind::LineString | GeometryKind::MultiPoint => {
+                self.coordinates().map(positions)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the rings of a `Polygon` or the lines of a `MultiLineString`.
+    pub fn rings(&self) -> Option<Vec<Vec<Position>>> {
+        match self.kind() {
+            GeometryKind::Polygon | GeometryKind::MultiLineString => self.coordinates().map(rings),
+            _ => None,
+        }
+    }
+
+    /// Returns the polygons of a `MultiPolygon`.
+    pub fn polygons(&self) -> Option<Vec<Vec<Vec<Position>>>> {
+        match self.kind() {
+            GeometryKind::MultiPolygon => self
+                .coordinates()
+                .and_then(JsonValue::as_array)
+                .map(|polys| polys.iter().map(rings).collect()),
+            _ => None,
+        }
+    }
+
+    /// Iterates over the members of a `GeometryCollection`.
+    ///
+    /// Yields nothing for other geometry types.
+    pub fn geometries(&self) -> impl Iterator<Item = Geometry<'a>> + 'a {
+        self.value
+            .get("geometries")
+            .and_then(JsonValue::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+            .iter()
+            .map(|value| Geometry { value })
+    }
+}
+
+fn positions(value: &JsonValue) -> Vec<Position> {
+    value
+        .as_array()
+        .map(|items| items.iter().map(Position::from_value).collect())
+        .unwrap_or_default()
+}
+
+fn rings(value: &JsonValue) -> Vec<Vec<Position>> {
+    value
+        .as_array()
+        .map(|items| items.iter().map(positions).collect())
+        .unwrap_or_default()
+}
+
+fn type_name<'a>(value: &'a JsonValue, path: &JsonPath) -> Result<&'a str, GeoJsonError> {
+    if value.as_object().is_none() {
+        return Err(invalid(path, "expected an object"));
+    }
+    value
+        .get("type")
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| invalid(path, "missing string member \"type\""))
+}
+
+fn validate_feature_collection(value: &JsonValue, path: &JsonPath) -> Result<(), GeoJsonError> {
+    if type_name(value, path)? != "FeatureCollection" {
+        return Err(invalid(path, "expected type \"FeatureCollection\""));
+    }
+    let features = value
+        .get("features")
+        .and_then(JsonValue::as_array)
+        .ok_or_else(|| invalid(path, "missing array member \"features\""))?;
+    for (i, feature) in features.iter().enumerate() {
+        validate_feature(feature, &path.join("features").join(i))?;
+    }
+    Ok(())
+}
+
+fn validate_feature(value: &JsonValue, path: &JsonPath) -> Result<(), GeoJsonError> {
+    if type_name(value, path)? != "Feature" {
+        return Err(invalid(path, "expected type \"Feature\""));
+    }
+    match value.get("geometry") {
+        None => return Err(invalid(path, "missing member \"geometry\"")),
+        Some(JsonValue::Null) => {}
+        Some(geometry) => validate_geometry(geometry, &path.join("geometry"))?,
+    }
+    match value.get("properties") {
+        Some(JsonValue::Null | JsonValue::Object(_)) => {}
+        _ => {
+            return Err(invalid(
+                &path.join("properties"),
+                "expected an object or null",
+            ));
+        }
+    }
+    match value.get("id") {
+        None | Some(JsonValue::String(_) | JsonValue::Number(_)) => Ok(()),
+        Some(_) => Err(invalid(&path.join("id"), "expected a string or number")),
+    }
+}
+
+fn validate_geometry(value: &JsonValue, path: &JsonPath) -> Result<(), GeoJsonError> {
+    let name = type_name(value, path)?;
+    let kind = GeometryKind::from_name(name)
+        .ok_or_else(|| invalid(path, format!("unknown geometry type \"{}\"", name)))?;
+    if kind == GeometryKind::GeometryCollection {
+        let geometries = value
+            .get("geometries")
+            .and_then(JsonValue::as_array)
+            .ok_or_else(|| invalid(path, "missing array member \"geometries\""))?;
+        for (i, geometry) in geometries.iter().enumerate() {
+            validate_geometry(geometry, &path.join("geometries").join(i))?;
+        }
+        return Ok(());
+    }
+    let coords_path = path.join("coordinates");
+    let coords = value
+        .get("coordinates")
+        .ok_or_else(|| invalid(path, "missing member \"coordinates\""))?;
+    match kind {
+        GeometryKind::Point => validate_position(coords, &coords_path),
+        GeometryKind::MultiPoint => validate_each(coords, &coords_path, validate_position),
+        GeometryKind::LineString => validate_line(coords, &coords_path),
+        GeometryKind::MultiLineString => validate_each(coords, &coords_path, validate_line),
+        GeometryKind::Polygon => validate_polygon(coords, &coords_path),
+        GeometryKind::MultiPolygon => validate_each(coords, &coords_path, validate_polygon),
+        GeometryKind::GeometryCollection => Ok(()),
+    }
+}
+
+fn validate_each(
+    value: &JsonValue,
+    path: &JsonPath,
+    validate: fn(&JsonValue, &JsonPath) -> Result<(), GeoJsonError>,
+) -> Result<(), GeoJsonError> {
+    let items = value
+        .as_array()
+        .ok_or_else(|| invalid(path, "expected an array"))?;
+    for (i, item) in items.iter().enumerate() {
+        validate(item, &path.join(i))?;
+    }
+    Ok(())
+}
+
+fn validate_position(value: &JsonValue, path: &JsonPath) -> Result<(), GeoJsonError> {
+    let coords = value
+        .as_array()
+        .ok_or_else(|| invalid(path, "expected a position array"))?;
+    if coords.len() < 2 {
+        return Err(invalid(path, "a position needs at least two numbers"));
+    }
+    match coords.iter().position(|c| c.as_f64().is_none()) {
+        Some(i) => Err(invalid(&path.join(i), "expected a number")),
+        None => Ok(()),
+    }
+}
+
+fn validate_line(value: &JsonValue, path: &JsonPath) -> Result<(), GeoJsonError> {
+    validate_each(value, path, validate_position)?;
+    if value.as_array().map_or(0, Vec::len) < 2 {
+        return Err(invalid(path, "a line string needs at least two positions"));
+    }
+    Ok(())
+}
+
+fn validate_ring(value: &JsonValue, path: &JsonPath) -> Result<(), GeoJsonError> {
+    validate_each(value, path, validate_position)?;
+    let ring = value.as_array().map(Vec::as_slice).unwrap_or(&[]);
+    if ring.len() < 4 {
+        return Err(invalid(path, "a linear ring needs at least four positions"));
+    }
+    if ring.first() != ring.last() {
+        return Err(invalid(path, "a linear ring must be closed"));
+    }
+    Ok(())
+}
+
+fn validate_polygon(value: &JsonValue, path: &JsonPath) -> Result<(), GeoJsonError> {
+    validate_each(value, path, validate_ring)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn collection() -> JsonValue {
+        parse_json(
+            r#"{
+                "type": "FeatureCollection",
+                "features": [
+                    {
+                        "type": "Feature",
+                        "geometry": {"type": "Point", "coordinates": [102.0, 0.5, 12.0]},
+                        "properties": {"prop0": "value0"}
+                    },
+                    {
+                        "type": "Feature",
+                        "id": "line",
+                        "geometry": {
+                            "type": "LineString",
+                            "coordinates": [[102.0, 0.0], [103.0, 1.0], [104.0, 0.0]]
+                        },
+                        "properties": null
+                    },
+                    {
+                        "type": "Feature",
+                        "geometry": {
+                            "type": "Polygon",
+                            "coordinates": [[[100.0, 0.0], [101.0, 0.0], [101.0, 1.0], [100.0, 0.0]]]
+                        },
+                        "properties": {}
+                    }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_feature_collection_accessors() {
+        let doc = collection();
+        let fc = FeatureCollection::from_value(&doc).unwrap();
+        assert_eq!(fc.len(), 3);
+        assert!(!fc.is_empty());
+
+        let features: Vec<Feature> = fc.features().collect();
+        let point = features[0].geometry().unwrap().point().unwrap();
+        assert_eq!(
+            point,
+            Position {
+                x: 102.0,
+                y: 0.5,
+                z: Some(12.0)
+            }
+        );
+        assert_eq!(
+            features[0].property("prop0").and_then(|v| v.as_str()),
+            Some("value0")
+        );
+
+        assert_eq!(features[1].properties(), None);
+        assert_eq!(features[1].id().and_then(|v| v.as_str()), Some("line"));
+        let line = features[1].geometry().unwrap();
+        assert_eq!(line.kind(), GeometryKind::LineString);
+        assert_eq!(line.positions().unwrap().len(), 3);
+        assert!(line.point().is_none());
+
+        let polygon = features[2].geometry().unwrap();
+        assert_eq!(polygon.rings().unwrap()[0].len(), 4);
+    }
+
+    #[test]
+    fn test_from_value_dispatches_on_type() {
+        let doc = collection();
+        assert!(matches!(
+            GeoJson::from_value(&doc),
+            Ok(GeoJson::FeatureCollection(_))
+        ));
+
+        let point = parse_json(r#"{"type": "Point", "coordinates": [1, 2]}"#).unwrap();
+        assert!(matches!(
+            GeoJson::from_value(&point),
+            Ok(GeoJson::Geometry(_))
+        ));
+    }
+
+    #[test]
+    fn test_null_geometry_feature() {
+        let doc =
+            parse_json(r#"{"type": "Feature", "geometry": null, "properties": null}"#).unwrap();
+        let feature = Feature::from_value(&doc).unwrap();
+        assert!(feature.geometry().is_none());
+    }
+
+    #[test]
+    fn test_geometry_collection() {
+        let doc = parse_json(
+            r#"{"type": "GeometryCollection", "geometries": [
+                {"type": "Point", "coordinates": [0, 0]},
+                {"type": "MultiPoint", "coordinates": [[1, 1], [2, 2]]}
+            ]}"#,
+        )
+        .unwrap();
+        let geometry = Geometry::from_value(&doc).unwrap();
+        assert_eq!(geometry.kind(), GeometryKind::GeometryCollection);
+        let kinds: Vec<GeometryKind> = geometry.geometries().map(|g| g.kind()).collect();
+        assert_eq!(kinds, vec![GeometryKind::Point, GeometryKind::MultiPoint]);
+    }
+
+    #[test]
+    fn test_invalid_position_reports_path() {
+        let doc = parse_json(
+            r#"{"type": "FeatureCollection", "features": [
+                {"type": "Feature", "properties": null,
+                 "geometry": {"type": "Point", "coordinates": [1, "two"]}}
+            ]}"#,
+        )
+        .unwrap();
+        let err = GeoJson::from_value(&doc).unwrap_err();
+        assert_eq!(err.path.to_string(), "/features/0/geometry/coordinates/1");
+    }
+
+    #[test]
+    fn test_unclosed_ring_rejected() {
+        let doc =
+            parse_json(r#"{"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 1]]]}"#)
+                .unwrap();
+        let err = Geometry::from_value(&doc).unwrap_err();
+        assert_eq!(err.path.to_string(), "/coordinates/0");
+        assert!(err.reason.contains("closed"));
+    }
+
+    #[test]
+    fn test_short_line_string_rejected() {
+        let doc = parse_json(r#"{"type": "LineString", "coordinates": [[0, 0]]}"#).unwrap();
+        assert!(Geometry::from_value(&doc).is_err());
+    }
+
+    #[test]
+    fn test_unknown_type_rejected() {
+        let doc = parse_json(r#"{"type": "Circle", "coordinates": [0, 0]}"#).unwrap();
+        let err = GeoJson::from_value(&doc).unwrap_err();
+        assert!(err.to_string().contains("unknown geometry type \"Circle\""));
+    }
+
+    #[test]
+    fn test_feature_missing_properties_rejected() {
+        let doc = parse_json(r#"{"type": "Feature", "geometry": null}"#).unwrap();
+        let err = Feature::from_value(&doc).unwrap_err();
+        assert_eq!(err.path.to_string(), "/properties");
+    }
+}
@@ -0,0 +1,396 @@
+//! Stable identifiers for the values in a document.
+//!
+//! An audit trail that records "the `billing` object moved from
+//! `/account/billing` to `/billing`" needs a way to recognise a subtree in
+//! two versions of a document. [`NodeIds`](crate::identity::NodeIds) gives
+//! every value a [`ContentHash`](crate::identity::ContentHash), derived
+//! from the value alone, and a [`NodeId`](crate::identity::NodeId),
+//! derived from its path and its content hash.
+//! [`NodeIds::moves`](crate::identity::NodeIds::moves) pairs subtrees by
+//! content to report which ones moved between versions.
+//!
+//! Both hashes are deterministic: they do not depend on the process, the
+//! platform, or the crate's hash map seeds, so they can be stored and
+//! compared later. Content hashes follow
+//! [`JsonValue`](crate::value::JsonValue)'s equality: object member order
+//! does not matter, and `1` and `1.0` hash alike.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::path::{JsonPath, Segment};
+use crate::value::{JsonNumber, JsonValue};
+
+/// A hash of a value's content, independent of where it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ContentHash(pub u64);
+
+/// An identifier for a value at a particular path with particular
+/// content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub u64);
+
+impl fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// One value in a [`NodeIds`] table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// Where the value is.
+    pub path: JsonPath,
+    /// Hash of the path and the content.
+    pub id: NodeId,
+    /// Hash of the content alone.
+    pub content: ContentHash,
+    /// The number of elements or members; 0 for scalars.
+    pub children: usize,
+}
+
+/// A subtree found at a different path in a later version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    /// The path in the earlier version.
+    pub from: JsonPath,
+    /// The path in the later version.
+    pub to: JsonPath,
+    /// The content hash, the same in both versions.
+    pub content: ContentHash,
+}
+
+/// Identifiers for every value in a document, in document order.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::identity::NodeIds;
+/// use rust_json_parser::json;
+/// use rust_json_parser::path::JsonPath;
+///
+/// let v1 = json!({"account": {"billing": {"plan": "pro", "seats": 5}}, "name": "acme"});
+/// let v2 = json!({"name": "acme", "billing": {"seats": 5, "plan": "pro"}, "account": {}});
+/// let (old, new) = (NodeIds::build(&v1), NodeIds::build(&v2));
+///
+/// // Same content, same path: same id, whatever the member order.
+/// let name = JsonPath::parse_pointer("/name").unwrap();
+/// assert_eq!(old.get(&name).unwrap().id, new.get(&name).unwrap().id);
+///
+/// let moves = old.moves(&new);
+/// assert_eq!(moves.len(), 1);
+/// assert_eq!(moves[0].from.to_pointer(), "/account/billing");
+/// assert_eq!(moves[0].to.to_pointer(), "/billing");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct NodeIds {
+    nodes: Vec<Node>,
+    by_path: HashMap<JsonPath, usize>,
+}
+
+impl NodeIds {
+    /// Hashes every value in `root`, including `root` itself.
+    pub fn build(root: &JsonValue) -> Self {
+        let mut ids = Self::default();
+        ids.visit(root, &mut JsonPath::root());
+        ids.by_path = ids
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.path.clone(), i))
+            .collect();
+        ids
+    }
+
+    // Adds the value and its descendants in document order and returns
+    // its content hash, computed from its children's.
+    fn visit(&mut self, value: &JsonValue, path: &mut JsonPath) -> ContentHash {
+        let slot = self.nodes.len();
+        self.nodes.push(Node {
+            path: path.clone(),
+            id: NodeId(0),
+            content: ContentHash(0),
+            children: match value {
+                JsonValue::Array(elements) => elements.len(),
+                JsonValue::Object(map) => map.len(),
+                _ => 0,
+            },
+        });
+        let content = hash_content(value, |segment, child| {
+            path.push(segment);
+            let hash = self.visit(child, path);
+            path.pop();
+            hash
+        });
+        let mut id = Fnv::new();
+        id.write_str(&path.to_pointer());
+        id.write_u64(content.0);
+        let node = &mut self.nodes[slot];
+        node.content = content;
+        node.id = NodeId(id.finish());
+        content
+    }
+
+    /// Returns the node at `path`, or `None` if there is no value there.
+    pub fn get(&self, path: &JsonPath) -> Option<&Node> {
+        self.by_path.get(path).map(|&i| &self.nodes[i])
+    }
+
+    /// Returns every node whose content hashes to `content`, in document
+    /// order.
+    pub fn with_content(&self, content: ContentHash) -> impl Iterator<Item = &Node> {
+        self.nodes
+            .iter()
+            .filter(move |node| node.content == content)
+    }
+
+    /// Iterates over the nodes in document order, parents before their
+    /// children.
+    pub fn iter(&self) -> std::slice::Iter<'_, Node> {
+        self.nodes.iter()
+    }
+
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the table is empty. A table built from a
+    /// document always holds at least the root.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Finds the non-empty arrays and objects of this version that appear
+    /// at a different path in `newer`.
+    ///
+    /// A subtree counts as moved when its path in `newer` held different
+    /// content here, and its old path holds different content in `newer`.
+    /// Identical subtrees are paired in document order. Only the
+    /// outermost moved subtree is reported, not the values inside it.
+    /// Scalars and empty containers are too common to track and are never
+    /// reported.
+    pub fn moves(&self, newer: &NodeIds) -> Vec<Move> {
+        let mut candidates: HashMap<ContentHash, Vec<&Node>> = HashMap::new();
+        for node in &self.nodes {
+            let moved_away = newer
+                .get(&node.path)
+                .is_none_or(|there| there.content != node.content);
+            if moved_away {
+                candidates.entry(node.content).or_default().push(node);
+            }
+        }
+        for pending in candidates.values_mut() {
+            pending.reverse();
+        }
+
+        let mut moves: Vec<Move> = Vec::new();
+        for node in &newer.nodes {
+            let inside_move = moves
+                .iter()
+                .any(|m| node.path.segments().starts_with(m.to.segments()));
+            if inside_move || node.children == 0 {
+                continue;
+            }
+            let unchanged = self
+                .get(&node.path)
+                .is_some_and(|here| here.content == node.content);
+            if unchanged {
+                continue;
+            }
+            if let Some(from) = candidates.get_mut(&node.content).and_then(Vec::pop) {
+                moves.push(Move {
+                    from: from.path.clone(),
+                    to: node.path.clone(),
+                    content: node.content,
+                });
+            }
+        }
+        moves
+    }
+}
+
+/// Hashes a value's content the way [`NodeIds`] does, without recording
+/// its descendants.
+pub(crate) fn content_hash(value: &JsonValue) -> ContentHash {
+    hash_content(value, |_, child| content_hash(child))
+}
+
+// Hashes `value`, taking each child's hash from `child` so callers can
+// visit the children as they go.
+fn hash_content(
+    value: &JsonValue,
+    mut child: impl FnMut(Segment, &JsonValue) -> ContentHash,
+) -> ContentHash {
+    let mut hasher = Fnv::new();
+    match value {
+        JsonValue::Null => hasher.write(&[0]),
+        JsonValue::Boolean(b) => hasher.write(&[1, u8::from(*b)]),
+        JsonValue::Number(n) => {
+            hasher.write(&[2]);
+            hash_number(&mut hasher, n);
+        }
+        JsonValue::String(s) => {
+            hasher.write(&[3]);
+            hasher.write_str(s);
+        }
+        JsonValue::Array(elements) => {
+            hasher.write(&[4]);
+            hasher.write_u64(elements.len() as u64);
+            for (i, element) in elements.iter().enumerate() {
+                hasher.write_u64(child(Segment::Index(i), element).0);
+            }
+        }
+        JsonValue::Object(map) => {
+            // Sorted so member order does not change the hash.
+            let mut members: Vec<u64> = map
+                .iter()
+                .map(|(key, member)| {
+                    let mut pair = Fnv::new();
+                    pair.write_str(key);
+                    pair.write_u64(child(Segment::Key(key.clone()), member).0);
+                    pair.finish()
+                })
+                .collect();
+            members.sort_unstable();
+            hasher.write(&[5]);
+            hasher.write_u64(members.len() as u64);
+            for member in members {
+                hasher.write_u64(member);
+            }
+        }
+    }
+    ContentHash(hasher.finish())
+}
+
+fn hash_number(hasher: &mut Fnv, number: &JsonNumber) {
+    // Integral values hash as integers, so `1` and `1.0` agree like they
+    // compare.
+    let f = number.as_f64();
+    let integer = number
+        .as_i64()
+        .map(i128::from)
+        .or_else(|| number.as_u64().map(i128::from))
+        .or_else(|| (f.fract() == 0.0 && f.abs() < 1e38).then_some(f as i128));
+    match integer {
+        Some(i) => {
+            hasher.write(&[0]);
+            hasher.write(&i.to_le_bytes());
+        }
+        None => {
+            hasher.write(&[1]);
+            hasher.write_u64(f.to_bits());
+        }
+    }
+}
+
+// 64-bit FNV-1a with a final mix, chosen for being fixed across platforms
+// and releases rather than for speed.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    // Length-prefixed, so adjacent strings cannot run together.
+    fn write_str(&mut self, s: &str) {
+        self.write_u64(s.len() as u64);
+        self.write(s.as_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    fn pointer(p: &str) -> JsonPath {
+        JsonPath::parse_pointer(p).unwrap()
+    }
+
+    #[test]
+    fn test_hashes_follow_equality() {
+        let a = NodeIds::build(&json!({"x": 1, "y": [true, null, "s"]}));
+        let b = NodeIds::build(&json!({"y": [true, null, "s"], "x": 1.0}));
+        assert_eq!(a.len(), 6);
+        assert_eq!(a.get(&JsonPath::root()), b.get(&JsonPath::root()));
+        assert_ne!(
+            a.get(&pointer("/x")).unwrap().id,
+            a.get(&pointer("/y/0")).unwrap().id
+        );
+        // Fixed across runs and platforms.
+        let null = NodeIds::build(&json!(null));
+        assert_eq!(
+            null.iter().next().unwrap().content.to_string(),
+            "25fc6dd36ce04b20"
+        );
+
+        let c = NodeIds::build(&json!({"x": 1, "y": [true, "s", null]}));
+        assert_ne!(
+            a.get(&JsonPath::root()).unwrap().content,
+            c.get(&JsonPath::root()).unwrap().content
+        );
+        // Same content at different paths: same content hash, other id.
+        let nested = NodeIds::build(&json!({"a": {"x": 1}, "b": {"x": 1}}));
+        let (first, second) = (
+            nested.get(&pointer("/a")).unwrap(),
+            nested.get(&pointer("/b")).unwrap(),
+        );
+        assert_eq!(first.content, second.content);
+        assert_ne!(first.id, second.id);
+        assert_eq!(nested.with_content(first.content).count(), 2);
+    }
+
+    #[test]
+    fn test_moves() {
+        let old = NodeIds::build(&json!({
+            "items": [{"id": 1}, {"id": 2}, {"id": 3}],
+            "meta": {"tags": ["a"]},
+            "flag": true
+        }));
+        let new = NodeIds::build(&json!({
+            "items": [{"id": 3}, {"id": 1}],
+            "archive": {"meta": {"tags": ["a"]}},
+            "flag": false,
+            "also": true
+        }));
+        let moves: Vec<(String, String)> = old
+            .moves(&new)
+            .into_iter()
+            .map(|m| (m.from.to_pointer(), m.to.to_pointer()))
+            .collect();
+        assert_eq!(
+            moves,
+            [
+                ("/items/2".to_string(), "/items/0".to_string()),
+                ("/items/0".to_string(), "/items/1".to_string()),
+                ("/meta".to_string(), "/archive/meta".to_string()),
+            ]
+        );
+        assert!(old.moves(&old).is_empty());
+    }
+}
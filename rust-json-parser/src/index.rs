@@ -0,0 +1,171 @@
+//! Constant-time pointer lookups into a parsed document.
+//!
+//! [`JsonValue::pointer`](crate::value::JsonValue::pointer) walks the
+//! document one segment at a time, splitting and unescaping the pointer on
+//! every call. Code that resolves many pointers against the same document,
+//! such as a rules engine evaluating its conditions, can build a
+//! [`JsonIndex`](crate::index::JsonIndex) once and then look each pointer up
+//! with a single hash probe.
+
+use std::collections::HashMap;
+
+use crate::path::JsonPath;
+use crate::pointer::{escape_segment, parse_pointer};
+use crate::value::JsonValue;
+
+/// A hash index from the JSON Pointer of every value in a document to the
+/// value itself.
+///
+/// Keys are normalized pointers: `~` and `/` in member names are escaped
+/// as `~0` and `~1`, and array indices are written in decimal. Lookups
+/// resolve exactly like [`JsonValue::pointer`]. The index borrows the
+/// document, so it cannot go stale.
+///
+/// Building visits every value and stores one pointer string per value,
+/// so it pays off only when the same document is queried many times.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::index::JsonIndex;
+/// use rust_json_parser::parser::parse_json;
+/// use rust_json_parser::path::JsonPath;
+///
+/// let doc = parse_json(r#"{"user": {"roles": ["admin", "dev"], "a/b": 1}}"#)?;
+/// let index = JsonIndex::build(&doc);
+/// assert_eq!(index.len(), 6);
+/// assert_eq!(index.get("/user/roles/1").and_then(|v| v.as_str()), Some("dev"));
+/// assert_eq!(index.get("/user/a~1b").and_then(|v| v.as_i64()), Some(1));
+/// assert_eq!(index.get(""), Some(&doc));
+/// assert!(index.get("/user/roles/01").is_none());
+///
+/// let path = JsonPath::parse_dotted("user.roles.0");
+/// assert_eq!(index.at(&path).and_then(|v| v.as_str()), Some("admin"));
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonIndex<'a> {
+    nodes: HashMap<Box<str>, &'a JsonValue>,
+}
+
+impl<'a> JsonIndex<'a> {
+    /// Indexes every value in `root`, including `root` itself under the
+    /// empty pointer.
+    pub fn build(root: &'a JsonValue) -> Self {
+        let mut index = Self {
+            nodes: HashMap::new(),
+        };
+        index.insert(&mut String::new(), root);
+        index
+    }
+
+    fn insert(&mut self, pointer: &mut String, value: &'a JsonValue) {
+        self.nodes.insert(pointer.as_str().into(), value);
+        let len = pointer.len();
+        match value {
+            JsonValue::Array(elements) => {
+                for (i, element) in elements.iter().enumerate() {
+                    pointer.push('/');
+                    pointer.push_str(&i.to_string());
+                    self.insert(pointer, element);
+                    pointer.truncate(len);
+                }
+            }
+            JsonValue::Object(map) => {
+                for (key, member) in map {
+                    pointer.push('/');
+                    pointer.push_str(&escape_segment(key));
+                    self.insert(pointer, member);
+                    pointer.truncate(len);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the value at a JSON Pointer, or `None` if it does not
+    /// resolve.
+    pub fn get(&self, pointer: &str) -> Option<&'a JsonValue> {
+        if let Some(&value) = self.nodes.get(pointer) {
+            return Some(value);
+        }
+        // Only a `~` not followed by `0` or `1` can make a pointer that
+        // resolves differ from its normalized form.
+        if !pointer.contains('~') {
+            return None;
+        }
+        let normalized: String = parse_pointer(pointer)?
+            .iter()
+            .map(|segment| format!("/{}", escape_segment(segment)))
+            .collect();
+        self.nodes.get(normalized.as_str()).copied()
+    }
+
+    /// Returns the value at `path`, or `None` if it does not resolve.
+    pub fn at(&self, path: &JsonPath) -> Option<&'a JsonValue> {
+        self.nodes.get(path.to_pointer().as_str()).copied()
+    }
+
+    /// Returns `true` if `pointer` resolves.
+    pub fn contains(&self, pointer: &str) -> bool {
+        self.get(pointer).is_some()
+    }
+
+    /// Returns the number of indexed values.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if nothing is indexed. An index built from a
+    /// document always holds at least the root.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Iterates over every normalized pointer and its value, in no
+    /// particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &'a JsonValue)> + '_ {
+        self.nodes
+            .iter()
+            .map(|(pointer, &value)| (&**pointer, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    #[test]
+    fn test_matches_pointer_resolution() {
+        let doc = json!({"a": [1, {"b~c": null}], "": {"x/y": true}, "k~2": 3, "7": [0]});
+        let index = JsonIndex::build(&doc);
+        for (pointer, value) in index.iter() {
+            assert!(std::ptr::eq(doc.pointer(pointer).unwrap(), value));
+        }
+        for pointer in [
+            "/a/1/b~0c",
+            "//x~1y",
+            "/k~2",
+            "/k~02",
+            "/7/0",
+            "/a/01",
+            "/a/-",
+            "a",
+            "/z",
+        ] {
+            assert_eq!(index.get(pointer), doc.pointer(pointer), "{}", pointer);
+        }
+        assert_eq!(index.len(), 10);
+        assert!(index.contains("/a/0"));
+    }
+
+    #[test]
+    fn test_scalar_root() {
+        let doc = json!(5);
+        let index = JsonIndex::build(&doc);
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.at(&JsonPath::root()), Some(&doc));
+        assert!(index.get("/0").is_none());
+    }
+}
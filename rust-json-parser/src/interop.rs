@@ -0,0 +1,128 @@
+//! Reporting what a conversion to or from another format loses.
+//!
+//! Formats such as CSV, TOML, and YAML cannot hold every JSON document,
+//! and JSON cannot hold everything they can: CSV has no nesting, TOML has
+//! no `null`, and YAML allows keys that are not strings. A converter that
+//! has to drop or approximate a value records it in a
+//! [`ConversionReport`](crate::interop::ConversionReport) and returns the
+//! report alongside its output, so callers can decide whether the result
+//! is good enough instead of finding out from missing data later.
+
+use std::fmt;
+
+use crate::path::JsonPath;
+
+/// The kind of a [`Loss`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LossKind {
+    /// The value was left out of the output.
+    Dropped,
+    /// The value was replaced with the closest thing the target format
+    /// can hold, e.g. a nested object flattened into dotted columns or a
+    /// date stored as a string.
+    Approximated,
+}
+
+impl LossKind {
+    fn description(self) -> &'static str {
+        match self {
+            LossKind::Dropped => "dropped",
+            LossKind::Approximated => "approximated",
+        }
+    }
+}
+
+/// One value a conversion could not carry over exactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loss {
+    /// What happened to the value.
+    pub kind: LossKind,
+    /// Location of the value in the JSON side of the conversion.
+    pub path: JsonPath,
+    /// Why, in a few words, e.g. `"TOML has no null"`.
+    pub reason: String,
+}
+
+impl fmt::Display for Loss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}: {} ({})",
+            self.path.to_pointer(),
+            self.kind.description(),
+            self.reason
+        )
+    }
+}
+
+/// Every value a conversion dropped or approximated, in document order.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::interop::{ConversionReport, LossKind};
+/// use rust_json_parser::path::JsonPath;
+///
+/// let mut report = ConversionReport::new();
+/// assert!(report.is_lossless());
+/// report.dropped(JsonPath::root().join("owner"), "TOML has no null");
+/// report.approximated(JsonPath::root().join("tags").join(0), "nested array joined with ';'");
+/// assert_eq!(report.of_kind(LossKind::Dropped).count(), 1);
+/// assert_eq!(
+///     report.to_string(),
+///     "\"/owner\": dropped (TOML has no null)\n\"/tags/0\": approximated (nested array joined with ';')"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    /// The losses in the order they were recorded.
+    pub losses: Vec<Loss>,
+}
+
+impl ConversionReport {
+    /// Creates an empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if nothing was dropped or approximated.
+    pub fn is_lossless(&self) -> bool {
+        self.losses.is_empty()
+    }
+
+    /// Records that the value at `path` was left out.
+    pub fn dropped(&mut self, path: JsonPath, reason: impl Into<String>) {
+        self.record(LossKind::Dropped, path, reason);
+    }
+
+    /// Records that the value at `path` was approximated.
+    pub fn approximated(&mut self, path: JsonPath, reason: impl Into<String>) {
+        self.record(LossKind::Approximated, path, reason);
+    }
+
+    /// Iterates over the losses of one kind.
+    pub fn of_kind(&self, kind: LossKind) -> impl Iterator<Item = &Loss> {
+        self.losses.iter().filter(move |loss| loss.kind == kind)
+    }
+
+    fn record(&mut self, kind: LossKind, path: JsonPath, reason: impl Into<String>) {
+        self.losses.push(Loss {
+            kind,
+            path,
+            reason: reason.into(),
+        });
+    }
+}
+
+/// One loss per line.
+impl fmt::Display for ConversionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, loss) in self.losses.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", loss)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+//! Iterators over the children of arrays and objects.
+//!
+//! [`JsonValue::iter`](crate::value::JsonValue::iter) and the
+//! `IntoIterator` impls for `&JsonValue` and `JsonValue` walk the direct
+//! children of a value without matching on its variant first. Array
+//! elements and object members arrive as one [`Child`](crate::iter::Child)
+//! enum, so generic traversal code can use ordinary iterator combinators;
+//! scalars have no children and yield nothing.
+
+use std::iter::Enumerate;
+use std::{slice, vec};
+
+use crate::map;
+use crate::path::Segment;
+use crate::value::JsonValue;
+
+/// A direct child of an array or object: an element with its index, or a
+/// member with its key.
+///
+/// Borrowing iteration yields `Child<&String, &JsonValue>` and consuming
+/// iteration yields `Child<String, JsonValue>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Child<K, V> {
+    /// An array element and its index.
+    Element(usize, V),
+    /// An object member and its key.
+    Member(K, V),
+}
+
+impl<K, V> Child<K, V> {
+    /// Returns the child's value.
+    pub fn value(&self) -> &V {
+        match self {
+            Child::Element(_, value) | Child::Member(_, value) => value,
+        }
+    }
+
+    /// Consumes the child, returning its value.
+    pub fn into_value(self) -> V {
+        match self {
+            Child::Element(_, value) | Child::Member(_, value) => value,
+        }
+    }
+
+    /// Returns the member's key, or `None` for an array element.
+    pub fn key(&self) -> Option<&K> {
+        match self {
+            Child::Element(..) => None,
+            Child::Member(key, _) => Some(key),
+        }
+    }
+}
+
+impl<K: AsRef<str>, V> Child<K, V> {
+    /// Returns the path segment that leads from the parent to this child.
+    pub fn segment(&self) -> Segment {
+        match self {
+            Child::Element(i, _) => Segment::Index(*i),
+            Child::Member(key, _) => Segment::Key(key.as_ref().to_string()),
+        }
+    }
+}
+
+/// Borrowing iterator over the children of a [`JsonValue`], created by
+/// [`JsonValue::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    inner: IterInner<'a>,
+}
+
+#[derive(Debug, Clone)]
+enum IterInner<'a> {
+    Elements(Enumerate<slice::Iter<'a, JsonValue>>),
+    Members(map::Iter<'a>),
+    Empty,
+}
+
+impl<'a> Iter<'a> {
+    pub(crate) fn new(value: &'a JsonValue) -> Self {
+        let inner = match value {
+            JsonValue::Array(elements) => IterInner::Elements(elements.iter().enumerate()),
+            JsonValue::Object(map) => IterInner::Members(map.iter()),
+            _ => IterInner::Empty,
+        };
+        Self { inner }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Child<&'a String, &'a JsonValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            IterInner::Elements(elements) => elements.next().map(|(i, v)| Child::Element(i, v)),
+            IterInner::Members(members) => members.next().map(|(k, v)| Child::Member(k, v)),
+            IterInner::Empty => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            IterInner::Elements(elements) => elements.size_hint(),
+            IterInner::Members(members) => members.size_hint(),
+            IterInner::Empty => (0, Some(0)),
+        }
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// Consuming iterator over the children of a [`JsonValue`], created by
+/// its `IntoIterator` impl.
+#[derive(Debug)]
+pub struct IntoIter {
+    inner: IntoIterInner,
+}
+
+#[derive(Debug)]
+enum IntoIterInner {
+    Elements(Enumerate<vec::IntoIter<JsonValue>>),
+    Members(vec::IntoIter<(String, JsonValue)>),
+    Empty,
+}
+
+impl Iterator for IntoIter {
+    type Item = Child<String, JsonValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            IntoIterInner::Elements(elements) => elements.next().map(|(i, v)| Child::Element(i, v)),
+            IntoIterInner::Members(members) => members.next().map(|(k, v)| Child::Member(k, v)),
+            IntoIterInner::Empty => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            IntoIterInner::Elements(elements) => elements.size_hint(),
+            IntoIterInner::Members(members) => members.size_hint(),
+            IntoIterInner::Empty => (0, Some(0)),
+        }
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl<'a> IntoIterator for &'a JsonValue {
+    type Item = Child<&'a String, &'a JsonValue>;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Iter::new(self)
+    }
+}
+
+impl IntoIterator for JsonValue {
+    type Item = Child<String, JsonValue>;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let inner = match self {
+            JsonValue::Array(elements) => IntoIterInner::Elements(elements.into_iter().enumerate()),
+            JsonValue::Object(map) => IntoIterInner::Members(map.into_iter()),
+            _ => IntoIterInner::Empty,
+        };
+        IntoIter { inner }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    #[test]
+    fn test_borrowed_and_owned_children_agree() {
+        for value in [json!([1, "a", null]), json!({"x": 1, "y": [2]}), json!(3)] {
+            let borrowed: Vec<_> = value
+                .iter()
+                .map(|child| (child.segment(), child.value().to_string()))
+                .collect();
+            assert_eq!(value.iter().len(), borrowed.len());
+            let owned: Vec<_> = value
+                .clone()
+                .into_iter()
+                .map(|child| (child.segment(), child.into_value().to_string()))
+                .collect();
+            assert_eq!(borrowed, owned);
+        }
+
+        let value = json!({"x": 1, "y": [2]});
+        let keys: Vec<&String> = value.iter().filter_map(|c| c.key().copied()).collect();
+        assert_eq!(keys, ["x", "y"]);
+        assert_eq!(
+            json!(["a", "b"]).into_iter().last(),
+            Some(Child::Element(1, json!("b")))
+        );
+    }
+
+    #[test]
+    fn test_entries_and_elements_ignore_other_kinds() {
+        let object = json!({"a": 1});
+        let array = json!([true, false]);
+        assert_eq!(object.entries().count(), 1);
+        assert_eq!(object.elements().count(), 0);
+        assert_eq!(array.entries().count(), 0);
+        assert_eq!(
+            array
+                .elements()
+                .filter(|v| v.as_bool() == Some(true))
+                .count(),
+            1
+        );
+        assert_eq!(json!("s").iter().next(), None);
+    }
+}
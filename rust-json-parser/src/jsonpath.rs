@@ -0,0 +1,462 @@
+//! JSONPath queries (RFC 9535) without filter expressions.
+//!
+//! A JSONPath query such as `$.store.book[*].author` selects any number of
+//! values from a document, where a JSON Pointer locates exactly one.
+//! [`select`](crate::jsonpath::select) runs a query once;
+//! [`Query::parse`](crate::jsonpath::Query::parse) compiles it for reuse.
+//!
+//! | Syntax | Selects |
+//! |--------|---------|
+//! | `$` | the root |
+//! | `.name`, `['name']`, `["name"]` | an object member |
+//! | `[0]`, `[-1]` | an array element; negative indices count from the end |
+//! | `[start:end:step]` | a slice of an array, with any part optional |
+//! | `.*`, `[*]` | every member value or element |
+//! | `[0, 'name', 1:3]` | the union of several selectors, in order |
+//! | `..name`, `..*`, `..[0]` | the selectors applied at every depth |
+//!
+//! Filter expressions (`[?...]`) and function extensions are rejected when
+//! the query is parsed. Values are selected in document order, and a
+//! selector that matches nothing contributes nothing rather than failing.
+
+use std::error;
+use std::fmt;
+
+use crate::value::JsonValue;
+
+/// Runs the JSONPath `query` against `value`.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::json;
+/// use rust_json_parser::jsonpath::select;
+///
+/// let doc = json!({"users": [
+///     {"name": "Ada", "langs": ["en", "fr"]},
+///     {"name": "Lin", "langs": ["zh"]}
+/// ]});
+/// let names: Vec<&str> = select("$.users[*].name", &doc)?
+///     .into_iter()
+///     .filter_map(|v| v.as_str())
+///     .collect();
+/// assert_eq!(names, ["Ada", "Lin"]);
+///
+/// assert_eq!(select("$..langs[-1]", &doc)?, [&json!("fr"), &json!("zh")]);
+/// assert_eq!(select("$.users[5]", &doc)?, Vec::<&_>::new());
+/// assert!(select("$.users[?@.name]", &doc).is_err());
+/// # Ok::<(), rust_json_parser::jsonpath::QueryError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`QueryError`] if `query` is not valid or uses a filter.
+pub fn select<'v>(query: &str, value: &'v JsonValue) -> Result<Vec<&'v JsonValue>, QueryError> {
+    Ok(Query::parse(query)?.select(value))
+}
+
+/// A JSONPath query that failed to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    /// Byte offset in the query where the problem was found.
+    pub position: usize,
+    /// What was wrong.
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid JSONPath at position {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl error::Error for QueryError {}
+
+/// A parsed JSONPath query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    segments: Vec<QuerySegment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum QuerySegment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: i64,
+    },
+}
+
+impl Query {
+    /// Parses a query, which must start with `$`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QueryError`] if `query` is not valid JSONPath or uses a
+    /// filter expression.
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        Parser {
+            source: query,
+            pos: 0,
+        }
+        .query()
+    }
+
+    /// Returns the values the query selects from `value`, in document
+    /// order.
+    pub fn select<'v>(&self, value: &'v JsonValue) -> Vec<&'v JsonValue> {
+        let mut nodes = vec![value];
+        for segment in &self.segments {
+            let mut next = Vec::new();
+            for node in nodes {
+                match segment {
+                    QuerySegment::Child(selectors) => apply(selectors, node, &mut next),
+                    QuerySegment::Descendant(selectors) => descend(selectors, node, &mut next),
+                }
+            }
+            nodes = next;
+        }
+        nodes
+    }
+}
+
+fn descend<'v>(selectors: &[Selector], node: &'v JsonValue, out: &mut Vec<&'v JsonValue>) {
+    apply(selectors, node, out);
+    match node {
+        JsonValue::Array(elements) => {
+            for element in elements {
+                descend(selectors, element, out);
+            }
+        }
+        JsonValue::Object(map) => {
+            for value in map.values() {
+                descend(selectors, value, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn apply<'v>(selectors: &[Selector], node: &'v JsonValue, out: &mut Vec<&'v JsonValue>) {
+    for selector in selectors {
+        match (selector, node) {
+            (Selector::Name(name), JsonValue::Object(map)) => out.extend(map.get(name)),
+            (Selector::Wildcard, JsonValue::Object(map)) => out.extend(map.values()),
+            (Selector::Wildcard, JsonValue::Array(elements)) => out.extend(elements),
+            (Selector::Index(index), JsonValue::Array(elements)) => {
+                let len = elements.len() as i64;
+                let i = if *index < 0 { index + len } else { *index };
+                if (0..len).contains(&i) {
+                    out.push(&elements[i as usize]);
+                }
+            }
+            (&Selector::Slice { start, end, step }, JsonValue::Array(elements)) => {
+                let len = elements.len() as i64;
+                let normalize = |i: i64| if i < 0 { i + len } else { i };
+                if step > 0 {
+                    let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+                    let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+                    let mut i = lower;
+                    while i < upper {
+                        out.push(&elements[i as usize]);
+                        i = i.saturating_add(step);
+                    }
+                } else if step < 0 {
+                    let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+                    let lower = normalize(end.unwrap_or(-len - 1)).clamp(-1, len - 1);
+                    let mut i = upper;
+                    while lower < i {
+                        out.push(&elements[i as usize]);
+                        i = i.saturating_add(step);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn query(mut self) -> Result<Query, QueryError> {
+        if !self.eat("$") {
+            return self.fail("a query must start with `$`");
+        }
+        let mut segments = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.eat("..") {
+                let selectors = match self.peek() {
+                    Some('[') => self.bracketed()?,
+                    _ => vec![self.shorthand()?],
+                };
+                segments.push(QuerySegment::Descendant(selectors));
+            } else if self.eat(".") {
+                segments.push(QuerySegment::Child(vec![self.shorthand()?]));
+            } else if self.peek() == Some('[') {
+                segments.push(QuerySegment::Child(self.bracketed()?));
+            } else if self.peek().is_none() {
+                return Ok(Query { segments });
+            } else {
+                return self.fail("expected `.`, `..`, or `[`");
+            }
+        }
+    }
+
+    // `*` or a member name after `.` or `..`.
+    fn shorthand(&mut self) -> Result<Selector, QueryError> {
+        if self.eat("*") {
+            return Ok(Selector::Wildcard);
+        }
+        let rest = &self.source[self.pos..];
+        let len = rest
+            .char_indices()
+            .find(|&(i, c)| {
+                !(c.is_ascii_alphabetic()
+                    || c == '_'
+                    || !c.is_ascii()
+                    || (i > 0 && c.is_ascii_digit()))
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        if len == 0 {
+            return self.fail("expected a member name or `*`");
+        }
+        self.pos += len;
+        Ok(Selector::Name(rest[..len].to_string()))
+    }
+
+    fn bracketed(&mut self) -> Result<Vec<Selector>, QueryError> {
+        self.eat("[");
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.selector()?);
+            self.skip_whitespace();
+            if self.eat("]") {
+                return Ok(selectors);
+            }
+            if !self.eat(",") {
+                return self.fail("expected `,` or `]`");
+            }
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, QueryError> {
+        match self.peek() {
+            Some(quote @ ('\'' | '"')) => self.string(quote).map(Selector::Name),
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => self.fail("filter expressions are not supported"),
+            _ => {
+                let start = self.integer()?;
+                self.skip_whitespace();
+                if !self.eat(":") {
+                    return match start {
+                        Some(index) => Ok(Selector::Index(index)),
+                        None => self.fail("expected a name, index, slice, or `*`"),
+                    };
+                }
+                self.skip_whitespace();
+                let end = self.integer()?;
+                self.skip_whitespace();
+                let mut step = 1;
+                if self.eat(":") {
+                    self.skip_whitespace();
+                    step = self.integer()?.unwrap_or(1);
+                }
+                Ok(Selector::Slice { start, end, step })
+            }
+        }
+    }
+
+    fn integer(&mut self) -> Result<Option<i64>, QueryError> {
+        let rest = &self.source[self.pos..];
+        let len = rest
+            .char_indices()
+            .find(|&(i, c)| !(c.is_ascii_digit() || (i == 0 && c == '-')))
+            .map_or(rest.len(), |(i, _)| i);
+        if len == 0 {
+            return Ok(None);
+        }
+        let text = &rest[..len];
+        let digits = text.trim_start_matches('-');
+        if digits.is_empty() || (digits.len() > 1 && digits.starts_with('0')) || text == "-0" {
+            return self.fail(&format!("invalid integer `{}`", text));
+        }
+        match text.parse() {
+            Ok(n) => {
+                self.pos += len;
+                Ok(Some(n))
+            }
+            Err(_) => self.fail(&format!("integer `{}` is out of range", text)),
+        }
+    }
+
+    fn string(&mut self, quote: char) -> Result<String, QueryError> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return self.fail("unterminated string");
+            };
+            self.pos += c.len_utf8();
+            match c {
+                c if c == quote => return Ok(out),
+                '\\' => {
+                    let escaped = match self.peek() {
+                        Some(c @ ('\'' | '"' | '\\' | '/')) => c,
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.pos += 1;
+                            out.push(self.unicode_escape()?);
+                            continue;
+                        }
+                        _ => return self.fail("invalid escape"),
+                    };
+                    self.pos += 1;
+                    out.push(escaped);
+                }
+                c if c < ' ' => return self.fail("control character in string"),
+                c => out.push(c),
+            }
+        }
+    }
+
+    // The hex digits of a `\u` escape, and a low surrogate's if the first
+    // is a high surrogate.
+    fn unicode_escape(&mut self) -> Result<char, QueryError> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.eat("\\u") {
+                return self.fail("unpaired surrogate");
+            }
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return self.fail("unpaired surrogate");
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        match char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => self.fail("unpaired surrogate"),
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, QueryError> {
+        match self.source.get(self.pos..self.pos + 4) {
+            Some(digits) if digits.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                self.pos += 4;
+                Ok(u32::from_str_radix(digits, 16).expect("hex digits"))
+            }
+            _ => self.fail("expected four hex digits"),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        let found = self.source[self.pos..].starts_with(text);
+        if found {
+            self.pos += text.len();
+        }
+        found
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.source[self.pos..];
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    fn fail<T>(&self, message: &str) -> Result<T, QueryError> {
+        Err(QueryError {
+            position: self.pos,
+            message: message.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    fn strings(query: &str, value: &JsonValue) -> Vec<String> {
+        select(query, value)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_selectors() {
+        let doc = json!({"a": [0, 1, 2, 3, 4], "b c": {"d": true}, "ü": 1});
+        assert_eq!(strings("$", &doc).len(), 1);
+        assert_eq!(strings("$['b c'].d", &doc), ["true"]);
+        assert_eq!(strings("$[\"b c\"]['d']", &doc), ["true"]);
+        assert_eq!(strings("$.ü", &doc), ["1"]);
+        assert_eq!(strings("$.a[1:3]", &doc), ["1", "2"]);
+        assert_eq!(strings("$.a[::-2]", &doc), ["4", "2", "0"]);
+        assert_eq!(strings("$.a[-2:]", &doc), ["3", "4"]);
+        assert_eq!(strings("$.a[0:5:0]", &doc), Vec::<String>::new());
+        assert_eq!(strings("$.a[ 4 , 0, -1 ]", &doc), ["4", "0", "4"]);
+        assert_eq!(strings("$.a.b", &doc), Vec::<String>::new());
+        assert_eq!(strings("$.*", &doc).len(), 3);
+    }
+
+    #[test]
+    fn test_descendants_in_document_order() {
+        let doc = json!({"x": 1, "k": {"x": 2, "l": [{"x": 3}]}});
+        assert_eq!(strings("$..x", &doc), ["1", "2", "3"]);
+        assert_eq!(strings("$..[0].x", &doc), ["3"]);
+        assert_eq!(strings("$.k..*", &doc).len(), 4);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (query, position) in [
+            ("a", 0),
+            ("$.", 2),
+            ("$[01]", 2),
+            ("$[?@.a]", 2),
+            ("$['a'", 5),
+            ("$['\\x']", 4),
+            ("$[1 2]", 4),
+            ("$.a b", 4),
+        ] {
+            let err = Query::parse(query).unwrap_err();
+            assert_eq!(err.position, position, "{}: {}", query, err);
+        }
+        assert_eq!(
+            Query::parse("$[?@]").unwrap_err().to_string(),
+            "Invalid JSONPath at position 2: filter expressions are not supported"
+        );
+    }
+}
@@ -0,0 +1,717 @@
+//! JSON-RPC 2.0 message types.
+//!
+//! Typed views of the four JSON-RPC 2.0 message shapes -- requests,
+//! notifications, responses, and batches -- built on
+//! [`JsonValue`](crate::value::JsonValue). Messages are decoded with
+//! [`parse_message`](crate::jsonrpc::parse_message) (or
+//! [`Message::from_value`](crate::jsonrpc::Message::from_value)) and
+//! encoded back with `to_value()` or `Display`.
+//!
+//! Decoding failures are reported as an [`ErrorObject`](crate::jsonrpc::ErrorObject)
+//! carrying the standard error code, so a server can send the error
+//! straight back to the client.
+//!
+//! Two decoding modes are supported. [`Mode::Strict`](crate::jsonrpc::Mode::Strict)
+//! enforces the specification to the letter: the `"jsonrpc": "2.0"`
+//! member is required and unknown members are rejected.
+//! [`Mode::Lenient`](crate::jsonrpc::Mode::Lenient) tolerates a missing
+//! version member and ignores unknown members, which helps when talking
+//! to peers with sloppy implementations.
+
+use std::fmt;
+
+use crate::map::JsonMap;
+use crate::parser::parse_json;
+use crate::value::JsonValue;
+
+/// Invalid JSON was received by the server.
+pub const PARSE_ERROR: i64 = -32700;
+/// The JSON sent is not a valid request object.
+pub const INVALID_REQUEST: i64 = -32600;
+/// The method does not exist or is not available.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// Invalid method parameters.
+pub const INVALID_PARAMS: i64 = -32602;
+/// Internal JSON-RPC error.
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Controls how strictly incoming messages are checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Require `"jsonrpc": "2.0"` and reject unknown members.
+    #[default]
+    Strict,
+    /// Accept a missing version member and ignore unknown members.
+    Lenient,
+}
+
+/// A request identifier.
+///
+/// The specification allows strings, numbers without a fractional part,
+/// and `null` (which is only meaningful in error responses).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Id {
+    /// A numeric identifier.
+    Number(i64),
+    /// A string identifier.
+    String(String),
+    /// The `null` identifier, used when the request id could not be read.
+    Null,
+}
+
+impl Id {
+    fn from_value(value: &JsonValue) -> Option<Id> {
+        match value {
+            JsonValue::Null => Some(Id::Null),
+            JsonValue::String(s) => Some(Id::String(s.clone())),
+            JsonValue::Number(n) => n
+                .as_i64()
+                .or_else(|| {
+                    let f = n.as_f64();
+                    (f.fract() == 0.0 && f.abs() < 9.007_199_254_740_992e15).then_some(f as i64)
+                })
+                .map(Id::Number),
+            _ => None,
+        }
+    }
+
+    /// Converts the identifier into its JSON representation.
+    pub fn to_value(&self) -> JsonValue {
+        match self {
+            Id::Number(n) => JsonValue::Number((*n).into()),
+            Id::String(s) => JsonValue::String(s.clone()),
+            Id::Null => JsonValue::Null,
+        }
+    }
+}
+
+impl From<i64> for Id {
+    fn from(n: i64) -> Self {
+        Id::Number(n)
+    }
+}
+
+impl From<&str> for Id {
+    fn from(s: &str) -> Self {
+        Id::String(s.to_string())
+    }
+}
+
+impl From<String> for Id {
+    fn from(s: String) -> Self {
+        Id::String(s)
+    }
+}
+
+/// The error member of a failed response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorObject {
+    /// The error code. Codes from -32768 to -32000 are reserved.
+    pub code: i64,
+    /// A short description of the error.
+    pub message: String,
+    /// Optional additional information about the error.
+    pub data: Option<JsonValue>,
+}
+
+impl ErrorObject {
+    /// Creates an error object with the given code and message.
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// Attaches additional data to the error.
+    pub fn with_data(mut self, data: JsonValue) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Creates a `-32700 Parse error` object.
+    pub fn parse_error() -> Self {
+        Self::new(PARSE_ERROR, "Parse error")
+    }
+
+    /// Creates a `-32600 Invalid Request` object.
+    pub fn invalid_request() -> Self {
+        Self::new(INVALID_REQUEST, "Invalid Request")
+    }
+
+    /// Creates a `-32601 Method not found` object.
+    pub fn method_not_found() -> Self {
+        Self::new(METHOD_NOT_FOUND, "Method not found")
+    }
+
+    /// Creates a `-32602 Invalid params` object.
+    pub fn invalid_params() -> Self {
+        Self::new(INVALID_PARAMS, "Invalid params")
+    }
+
+    /// Creates a `-32603 Internal error` object.
+    pub fn internal_error() -> Self {
+        Self::new(INTERNAL_ERROR, "Internal error")
+    }
+
+    fn invalid_request_because(reason: &str) -> Self {
+        Self::invalid_request().with_data(JsonValue::String(reason.to_string()))
+    }
+
+    /// Converts the error object into its JSON representation.
+    pub fn to_value(&self) -> JsonValue {
+        let mut map = JsonMap::with_capacity(3);
+        map.insert(
+            "code".to_string(),
+            JsonValue::Number((self.code as f64).into()),
+        );
+        map.insert(
+            "message".to_string(),
+            JsonValue::String(self.message.clone()),
+        );
+        if let Some(data) = &self.data {
+            map.insert("data".to_string(), data.clone());
+        }
+        JsonValue::Object(map)
+    }
+
+    fn from_value(value: &JsonValue) -> Option<ErrorObject> {
+        let code = value.get("code")?.as_f64()?;
+        if code.fract() != 0.0 {
+            return None;
+        }
+        let message = value.get("message")?.as_str()?.to_string();
+        Some(ErrorObject {
+            code: code as i64,
+            message,
+            data: value.get("data").cloned(),
+        })
+    }
+}
+
+impl fmt::Display for ErrorObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JSON-RPC error {}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ErrorObject {}
+
+/// A method call that expects a response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    /// The identifier echoed back in the response.
+    pub id: Id,
+    /// The name of the method to invoke.
+    pub method: String,
+    /// The parameters, which must be an array or an object when present.
+    pub params: Option<JsonValue>,
+}
+
+impl Request {
+    /// Creates a request without parameters.
+    pub fn new(id: impl Into<Id>, method: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            method: method.into(),
+            params: None,
+        }
+    }
+
+    /// Sets the request parameters.
+    pub fn with_params(mut self, params: JsonValue) -> Self {
+        self.params = Some(params);
+        self
+    }
+
+    /// Converts the request into its JSON representation.
+    pub fn to_value(&self) -> JsonValue {
+        let mut map = call_members(&self.method, self.params.as_ref());
+        map.insert("id".to_string(), self.id.to_value());
+        JsonValue::Object(map)
+    }
+}
+
+/// A method call that does not expect a response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// The name of the method to invoke.
+    pub method: String,
+    /// The parameters, which must be an array or an object when present.
+    pub params: Option<JsonValue>,
+}
+
+impl Notification {
+    /// Creates a notification without parameters.
+    pub fn new(method: impl Into<String>) -> Self {
+        Self {
+            method: method.into(),
+            params: None,
+        }
+    }
+
+    /// Sets the notification parameters.
+    pub fn with_params(mut self, params: JsonValue) -> Self {
+        self.params = Some(params);
+        self
+    }
+
+    /// Converts the notification into its JSON representation.
+    pub fn to_value(&self) -> JsonValue {
+        JsonValue::Object(call_members(&self.method, self.params.as_ref()))
+    }
+}
+
+/// The reply to a [`Request`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// The identifier of the request this response answers.
+    pub id: Id,
+    /// The result on success, or the error object on failure.
+    pub outcome: Result<JsonValue, ErrorObject>,
+}
+
+impl Response {
+    /// Creates a successful response.
+    pub fn success(id: impl Into<Id>, result: JsonValue) -> Self {
+        Self {
+            id: id.into(),
+            outcome: Ok(result),
+        }
+    }
+
+    /// Creates an error response.
+    pub fn error(id: impl Into<Id>, error: ErrorObject) -> Self {
+        Self {
+            id: id.into(),
+            outcome: Err(error),
+        }
+    }
+
+    /// Converts the response into its JSON representation.
+    pub fn to_value(&self) -> JsonValue {
+        let mut map = JsonMap::with_capacity(3);
+        map.insert("jsonrpc".to_string(), JsonValue::String("2.0".to_string()));
+        match &self.outcome {
+            Ok(result) => map.insert("result".to_string(), result.clone()),
+            Err(error) => map.insert("error".to_string(), error.to_value()),
+        };
+        map.insert("id".to_string(), self.id.to_value());
+        JsonValue::Object(map)
+    }
+}
+
+impl From<Id> for JsonValue {
+    fn from(id: Id) -> Self {
+        id.to_value()
+    }
+}
+
+/// Any JSON-RPC 2.0 message, including batches.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// A method call expecting a response.
+    Request(Request),
+    /// A method call without a response.
+    Notification(Notification),
+    /// A reply to a request.
+    Response(Response),
+    /// Several messages sent together as a JSON array. Each element is
+    /// decoded on its own, so one invalid element does not hide the rest:
+    /// a server answers the valid ones and sends an error for each other.
+    Batch(Vec<Result<Message, ErrorObject>>),
+}
+
+impl Message {
+    /// Decodes a message from an already-parsed JSON value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::jsonrpc::{Id, Message, Mode};
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json(r#"{"jsonrpc": "2.0", "method": "ping", "id": 1}"#)?;
+    /// match Message::from_value(&value, Mode::Strict) {
+    ///     Ok(Message::Request(req)) => {
+    ///         assert_eq!(req.method, "ping");
+    ///         assert_eq!(req.id, Id::Number(1));
+    ///     }
+    ///     other => panic!("unexpected: {:?}", other),
+    /// }
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Invalid Request` [`ErrorObject`] if the value is not a
+    /// well-formed message under the given mode, or is an empty batch. The
+    /// error's `data` member describes what was wrong. Errors in the
+    /// elements of a batch are kept in [`Message::Batch`] instead.
+    pub fn from_value(value: &JsonValue, mode: Mode) -> Result<Message, ErrorObject> {
+        match value {
+            JsonValue::Array(items) => {
+                if items.is_empty() {
+                    return Err(ErrorObject::invalid_request_because("empty batch"));
+                }
+                let messages = items
+                    .iter()
+                    .map(|item| match item {
+                        JsonValue::Array(_) => {
+                            Err(ErrorObject::invalid_request_because("nested batch"))
+                        }
+                        _ => Message::from_value(item, mode),
+                    })
+                    .collect();
+                Ok(Message::Batch(messages))
+            }
+            JsonValue::Object(map) => decode_object(map, mode),
+            _ => Err(ErrorObject::invalid_request_because(
+                "message must be an object or array",
+            )),
+        }
+    }
+
+    /// Converts the message into its JSON representation.
+    ///
+    /// A batch element that failed to decode is written as the error
+    /// response a server sends for it, with a `null` id.
+    pub fn to_value(&self) -> JsonValue {
+        match self {
+            Message::Request(r) => r.to_value(),
+            Message::Notification(n) => n.to_value(),
+            Message::Response(r) => r.to_value(),
+            Message::Batch(messages) => JsonValue::Array(
+                messages
+                    .iter()
+                    .map(|message| match message {
+                        Ok(message) => message.to_value(),
+                        Err(error) => Response::error(Id::Null, error.clone()).to_value(),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_value())
+    }
+}
+
+/// Parses JSON text and decodes it as a JSON-RPC message.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::jsonrpc::{parse_message, Message, Mode, PARSE_ERROR};
+///
+/// let msg = parse_message(r#"{"jsonrpc": "2.0", "method": "exit"}"#, Mode::Strict);
+/// assert!(matches!(msg, Ok(Message::Notification(_))));
+///
+/// let err = parse_message("{not json", Mode::Strict).unwrap_err();
+/// assert_eq!(err.code, PARSE_ERROR);
+/// ```
+///
+/// # Errors
+///
+/// Returns a `Parse error` [`ErrorObject`] if the text is not valid JSON
+/// (with the parser's message in `data`), or an `Invalid Request` error if
+/// it is not a valid message.
+pub fn parse_message(input: &str, mode: Mode) -> Result<Message, ErrorObject> {
+    let value = parse_json(input)
+        .map_err(|e| ErrorObject::parse_error().with_data(JsonValue::String(e.to_string())))?;
+    Message::from_value(&value, mode)
+}
+
+fn call_members(method: &str, params: Option<&JsonValue>) -> JsonMap {
+    let mut map = JsonMap::with_capacity(4);
+    map.insert("jsonrpc".to_string(), JsonValue::String("2.0".to_string()));
+    map.insert("method".to_string(), JsonValue::String(method.to_string()));
+    if let Some(params) = params {
+        map.insert("params".to_string(), params.clone());
+    }
+    map
+}
+
+fn decode_object(map: &JsonMap, mode: Mode) -> Result<Message, ErrorObject> {
+    match map.get("jsonrpc") {
+        Some(JsonValue::String(v)) if v == "2.0" => {}
+        None if mode == Mode::Lenient => {}
+        _ => {
+            return Err(ErrorObject::invalid_request_because(
+                "\"jsonrpc\" must be \"2.0\"",
+            ));
+        }
+    }
+
+    if map.contains_key("method") {
+        decode_call(map, mode)
+    } else {
+        decode_response(map, mode)
+    }
+}
+
+fn decode_call(map: &JsonMap, mode: Mode) -> Result<Message, ErrorObject> {
+    if mode == Mode::Strict
+        && map
+            .keys()
+            .any(|k| !matches!(k.as_str(), "jsonrpc" | "method" | "params" | "id"))
+    {
+        return Err(ErrorObject::invalid_request_because("unknown member"));
+    }
+    let method = match map.get("method") {
+        Some(JsonValue::String(m)) => m.clone(),
+        _ => {
+            return Err(ErrorObject::invalid_request_because(
+                "\"method\" must be a string",
+            ));
+        }
+    };
+    let params = match map.get("params") {
+        None => None,
+        Some(p @ (JsonValue::Array(_) | JsonValue::Object(_))) => Some(p.clone()),
+        Some(_) => {
+            return Err(ErrorObject::invalid_request_because(
+                "\"params\" must be an array or object",
+            ));
+        }
+    };
+    match map.get("id") {
+        None => Ok(Message::Notification(Notification { method, params })),
+        Some(value) => {
+            let id = Id::from_value(value)
+                .ok_or_else(|| ErrorObject::invalid_request_because("invalid \"id\""))?;
+            Ok(Message::Request(Request { id, method, params }))
+        }
+    }
+}
+
+fn decode_response(map: &JsonMap, mode: Mode) -> Result<Message, ErrorObject> {
+    if mode == Mode::Strict
+        && map
+            .keys()
+            .any(|k| !matches!(k.as_str(), "jsonrpc" | "result" | "error" | "id"))
+    {
+        return Err(ErrorObject::invalid_request_because("unknown member"));
+    }
+    let id = map
+        .get("id")
+        .and_then(Id::from_value)
+        .ok_or_else(|| ErrorObject::invalid_request_because("invalid \"id\""))?;
+    let outcome = match (map.get("result"), map.get("error")) {
+        (Some(result), None) => Ok(result.clone()),
+        (None, Some(error)) => Err(ErrorObject::from_value(error)
+            .ok_or_else(|| ErrorObject::invalid_request_because("malformed \"error\""))?),
+        _ => {
+            return Err(ErrorObject::invalid_request_because(
+                "exactly one of \"result\" or \"error\" is required",
+            ));
+        }
+    };
+    Ok(Message::Response(Response { id, outcome }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request_with_params() {
+        let msg = parse_message(
+            r#"{"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": 1}"#,
+            Mode::Strict,
+        )
+        .unwrap();
+        let expected = Request::new(1, "subtract").with_params(JsonValue::Array(vec![
+            JsonValue::Number(42.0.into()),
+            JsonValue::Number(23.0.into()),
+        ]));
+        assert_eq!(msg, Message::Request(expected));
+    }
+
+    #[test]
+    fn test_parse_notification() {
+        let msg = parse_message(r#"{"jsonrpc": "2.0", "method": "update"}"#, Mode::Strict);
+        assert_eq!(msg, Ok(Message::Notification(Notification::new("update"))));
+    }
+
+    #[test]
+    fn test_parse_string_and_null_ids() {
+        let msg = parse_message(
+            r#"{"jsonrpc": "2.0", "method": "a", "id": "abc"}"#,
+            Mode::Strict,
+        );
+        assert_eq!(msg, Ok(Message::Request(Request::new("abc", "a"))));
+
+        let msg = parse_message(
+            r#"{"jsonrpc": "2.0", "error": {"code": -32700, "message": "Parse error"}, "id": null}"#,
+            Mode::Strict,
+        );
+        assert_eq!(
+            msg,
+            Ok(Message::Response(Response::error(
+                Id::Null,
+                ErrorObject::parse_error()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_fractional_id_rejected() {
+        let err = parse_message(
+            r#"{"jsonrpc": "2.0", "method": "a", "id": 1.5}"#,
+            Mode::Lenient,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_parse_success_response() {
+        let msg = parse_message(r#"{"jsonrpc": "2.0", "result": 19, "id": 1}"#, Mode::Strict);
+        assert_eq!(
+            msg,
+            Ok(Message::Response(Response::success(
+                1,
+                JsonValue::Number(19.0.into())
+            )))
+        );
+    }
+
+    #[test]
+    fn test_response_with_both_result_and_error() {
+        let err = parse_message(
+            r#"{"jsonrpc": "2.0", "result": 1, "error": {"code": 1, "message": "x"}, "id": 1}"#,
+            Mode::Strict,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        let msg = parse_message(
+            r#"[
+                {"jsonrpc": "2.0", "method": "sum", "params": [1, 2], "id": "1"},
+                {"jsonrpc": "2.0", "method": "notify_hello", "params": [7]}
+            ]"#,
+            Mode::Strict,
+        )
+        .unwrap();
+        match msg {
+            Message::Batch(items) => {
+                assert_eq!(items.len(), 2);
+                assert!(matches!(items[0], Ok(Message::Request(_))));
+                assert!(matches!(items[1], Ok(Message::Notification(_))));
+            }
+            other => panic!("Expected batch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_batch_keeps_valid_elements() {
+        let msg = parse_message(
+            r#"[
+                {"jsonrpc": "2.0", "method": "sum", "params": [1, 2], "id": 1},
+                {"foo": "boo"},
+                [{"jsonrpc": "2.0", "method": "ping"}],
+                {"jsonrpc": "2.0", "method": "notify_hello", "params": [7]}
+            ]"#,
+            Mode::Strict,
+        )
+        .unwrap();
+        let Message::Batch(items) = &msg else {
+            panic!("Expected batch, got {:?}", msg);
+        };
+        assert_eq!(items.len(), 4);
+        assert!(matches!(items[0], Ok(Message::Request(_))));
+        assert_eq!(items[1].as_ref().unwrap_err().code, INVALID_REQUEST);
+        assert_eq!(
+            items[2].as_ref().unwrap_err().data,
+            Some(JsonValue::String("nested batch".to_string()))
+        );
+        assert!(matches!(items[3], Ok(Message::Notification(_))));
+
+        let written = msg.to_value();
+        assert_eq!(written[1]["id"], JsonValue::Null);
+        assert_eq!(
+            written[1]["error"]["code"],
+            JsonValue::from(INVALID_REQUEST)
+        );
+    }
+
+    #[test]
+    fn test_empty_batch_is_invalid() {
+        let err = parse_message("[]", Mode::Strict).unwrap_err();
+        assert_eq!(err.code, INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_parse_error_code() {
+        let err = parse_message(r#"{"jsonrpc": "2.0", "method""#, Mode::Strict).unwrap_err();
+        assert_eq!(err.code, PARSE_ERROR);
+        assert!(err.data.is_some());
+    }
+
+    #[test]
+    fn test_strict_requires_version() {
+        let input = r#"{"method": "ping", "id": 1}"#;
+        assert_eq!(
+            parse_message(input, Mode::Strict).unwrap_err().code,
+            INVALID_REQUEST
+        );
+        assert_eq!(
+            parse_message(input, Mode::Lenient),
+            Ok(Message::Request(Request::new(1, "ping")))
+        );
+    }
+
+    #[test]
+    fn test_strict_rejects_unknown_members() {
+        let input = r#"{"jsonrpc": "2.0", "method": "ping", "id": 1, "extra": true}"#;
+        assert!(parse_message(input, Mode::Strict).is_err());
+        assert!(parse_message(input, Mode::Lenient).is_ok());
+    }
+
+    #[test]
+    fn test_wrong_version_rejected_in_both_modes() {
+        let input = r#"{"jsonrpc": "1.0", "method": "ping", "id": 1}"#;
+        assert!(parse_message(input, Mode::Strict).is_err());
+        assert!(parse_message(input, Mode::Lenient).is_err());
+    }
+
+    #[test]
+    fn test_scalar_params_rejected() {
+        let input = r#"{"jsonrpc": "2.0", "method": "ping", "params": 3, "id": 1}"#;
+        assert_eq!(
+            parse_message(input, Mode::Strict).unwrap_err().code,
+            INVALID_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_round_trip_through_display() {
+        let messages = vec![
+            Message::Request(Request::new(7, "add").with_params(JsonValue::Array(vec![]))),
+            Message::Notification(Notification::new("exit")),
+            Message::Response(Response::success("x", JsonValue::Boolean(true))),
+            Message::Response(Response::error(
+                3,
+                ErrorObject::method_not_found().with_data(JsonValue::String("add".to_string())),
+            )),
+        ];
+        for message in messages {
+            let text = message.to_string();
+            assert_eq!(parse_message(&text, Mode::Strict), Ok(message));
+        }
+    }
+
+    #[test]
+    fn test_error_object_display() {
+        let error = ErrorObject::invalid_params();
+        assert_eq!(error.to_string(), "JSON-RPC error -32602: Invalid params");
+    }
+}
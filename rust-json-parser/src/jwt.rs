@@ -0,0 +1,276 @@
+//! JWT header and payload decoding.
+//!
+//! A JSON Web Token is three base64url segments joined by dots:
+//! `header.payload.signature`. This module splits a token, decodes the
+//! header and payload segments, and parses each into a
+//! [`JsonValue`](crate::value::JsonValue) so tokens can be inspected while
+//! debugging.
+//!
+//! Signature verification is deliberately out of scope. The signature
+//! segment is returned as-is and nothing in this module says whether a
+//! token can be trusted.
+
+use std::error;
+use std::fmt;
+
+use crate::error::JsonError;
+use crate::parser::parse_json;
+use crate::value::JsonValue;
+
+/// A decoded JSON Web Token.
+///
+/// Holds the parsed header and payload plus the raw (still encoded)
+/// signature segment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jwt {
+    /// The parsed JOSE header (e.g. `{"alg": "HS256", "typ": "JWT"}`).
+    pub header: JsonValue,
+    /// The parsed claims set.
+    pub payload: JsonValue,
+    /// The base64url-encoded signature segment, not verified.
+    pub signature: String,
+}
+
+/// Identifies which segment of a token an error refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment {
+    /// The first segment, holding the JOSE header.
+    Header,
+    /// The second segment, holding the claims.
+    Payload,
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Header => write!(f, "header"),
+            Segment::Payload => write!(f, "payload"),
+        }
+    }
+}
+
+/// Errors that can occur while decoding a JWT.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JwtError {
+    /// The token does not consist of exactly three dot-separated segments.
+    Malformed {
+        /// The number of segments that were found.
+        segments: usize,
+    },
+    /// A segment contains a character outside the base64url alphabet, or
+    /// has a length that cannot be produced by base64url encoding.
+    InvalidBase64 {
+        /// The segment that failed to decode.
+        segment: Segment,
+        /// Byte offset within the segment where decoding failed.
+        position: usize,
+    },
+    /// A segment decoded to bytes that are not valid UTF-8.
+    InvalidUtf8 {
+        /// The segment that failed to decode.
+        segment: Segment,
+    },
+    /// A segment decoded to text that is not valid JSON.
+    Json {
+        /// The segment that failed to parse.
+        segment: Segment,
+        /// The underlying parse error.
+        error: JsonError,
+    },
+}
+
+impl fmt::Display for JwtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JwtError::Malformed { segments } => {
+                write!(f, "Malformed JWT: expected 3 segments, found {}", segments)
+            }
+            JwtError::InvalidBase64 { segment, position } => {
+                write!(
+                    f,
+                    "Invalid base64url in JWT {} at position {}",
+                    segment, position
+                )
+            }
+            JwtError::InvalidUtf8 { segment } => {
+                write!(f, "JWT {} is not valid UTF-8", segment)
+            }
+            JwtError::Json { segment, error } => {
+                write!(f, "JWT {} is not valid JSON: {}", segment, error)
+            }
+        }
+    }
+}
+
+impl error::Error for JwtError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            JwtError::Json { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes the header and payload of a JWT without verifying it.
+///
+/// Leading and trailing whitespace around the token is ignored, as is a
+/// `Bearer ` prefix copied from an `Authorization` header.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::jwt::decode;
+///
+/// let token = "eyJhbGciOiJIUzI1NiJ9.eyJzdWIiOiJhbGljZSJ9.c2ln";
+/// let jwt = decode(token)?;
+/// assert_eq!(jwt.header.get("alg").and_then(|v| v.as_str()), Some("HS256"));
+/// assert_eq!(jwt.payload.get("sub").and_then(|v| v.as_str()), Some("alice"));
+/// assert_eq!(jwt.signature, "c2ln");
+/// # Ok::<(), rust_json_parser::jwt::JwtError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`JwtError`] if the token does not have three segments, or if
+/// the header or payload is not valid base64url-encoded JSON.
+pub fn decode(token: &str) -> Result<Jwt, JwtError> {
+    let token = token.trim();
+    let token = token.strip_prefix("Bearer ").unwrap_or(token).trim_start();
+    let segments: Vec<&str> = token.split('.').collect();
+    if segments.len() != 3 {
+        return Err(JwtError::Malformed {
+            segments: segments.len(),
+        });
+    }
+    Ok(Jwt {
+        header: decode_segment(segments[0], Segment::Header)?,
+        payload: decode_segment(segments[1], Segment::Payload)?,
+        signature: segments[2].to_string(),
+    })
+}
+
+fn decode_segment(encoded: &str, segment: Segment) -> Result<JsonValue, JwtError> {
+    let bytes = base64url_decode(encoded)
+        .map_err(|position| JwtError::InvalidBase64 { segment, position })?;
+    let text = String::from_utf8(bytes).map_err(|_| JwtError::InvalidUtf8 { segment })?;
+    parse_json(&text).map_err(|error| JwtError::Json { segment, error })
+}
+
+/// Decodes unpadded (or padded) base64url, returning the offset of the
+/// first offending byte on failure.
+fn base64url_decode(input: &str) -> Result<Vec<u8>, usize> {
+    let trimmed = input.trim_end_matches('=');
+    let bytes = trimmed.as_bytes();
+    // A single leftover character carries only 6 bits and can't form a byte.
+    if bytes.len() % 4 == 1 {
+        return Err(bytes.len() - 1);
+    }
+    let mut output = Vec::with_capacity(bytes.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        let sextet = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'-' => 62,
+            b'_' => 63,
+            _ => return Err(i),
+        };
+        buffer = (buffer << 6) | sextet as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // {"alg":"HS256","typ":"JWT"} . {"sub":"1234567890","name":"John Doe","iat":1516239022}
+    const SAMPLE: &str = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.\
+        eyJzdWIiOiIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiaWF0IjoxNTE2MjM5MDIyfQ.\
+        SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c";
+
+    #[test]
+    fn test_decode_sample_token() {
+        let jwt = decode(SAMPLE).unwrap();
+        assert_eq!(
+            jwt.header.get("alg").and_then(|v| v.as_str()),
+            Some("HS256")
+        );
+        assert_eq!(jwt.header.get("typ").and_then(|v| v.as_str()), Some("JWT"));
+        assert_eq!(
+            jwt.payload.get("name").and_then(|v| v.as_str()),
+            Some("John Doe")
+        );
+        assert_eq!(
+            jwt.payload.get("iat").and_then(|v| v.as_f64()),
+            Some(1516239022.0)
+        );
+        assert_eq!(jwt.signature, "SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c");
+    }
+
+    #[test]
+    fn test_decode_bearer_prefix() {
+        let jwt = decode(&format!("  Bearer {}\n", SAMPLE)).unwrap();
+        assert_eq!(
+            jwt.header.get("alg").and_then(|v| v.as_str()),
+            Some("HS256")
+        );
+    }
+
+    #[test]
+    fn test_decode_wrong_segment_count() {
+        assert_eq!(decode("abc.def"), Err(JwtError::Malformed { segments: 2 }));
+        assert_eq!(decode("a.b.c.d"), Err(JwtError::Malformed { segments: 4 }));
+    }
+
+    #[test]
+    fn test_decode_invalid_base64() {
+        let result = decode("eyJ!bGc.e30.sig");
+        assert_eq!(
+            result,
+            Err(JwtError::InvalidBase64 {
+                segment: Segment::Header,
+                position: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_payload_not_json() {
+        // "not json" base64url-encoded
+        let result = decode("e30.bm90IGpzb24.sig");
+        assert!(matches!(
+            result,
+            Err(JwtError::Json {
+                segment: Segment::Payload,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_base64url_decode_padding_variants() {
+        assert_eq!(base64url_decode("YQ").unwrap(), b"a");
+        assert_eq!(base64url_decode("YQ==").unwrap(), b"a");
+        assert_eq!(base64url_decode("YWI").unwrap(), b"ab");
+        assert_eq!(base64url_decode("YWJj").unwrap(), b"abc");
+        assert_eq!(base64url_decode("").unwrap(), b"");
+        assert_eq!(base64url_decode("YWJjZ"), Err(4));
+    }
+
+    #[test]
+    fn test_jwt_error_display() {
+        let error = JwtError::InvalidUtf8 {
+            segment: Segment::Payload,
+        };
+        assert_eq!(error.to_string(), "JWT payload is not valid UTF-8");
+    }
+}
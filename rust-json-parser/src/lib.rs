@@ -26,15 +26,122 @@
 //! # Ok::<(), rust_json_parser::error::JsonError>(())
 //! ```
 
+/// Zero-copy tokenizer yielding strings borrowed from the input.
+pub mod borrowed;
+/// Caches for reusing parsed subtrees that repeat across documents.
+pub mod cache;
+/// Canonical JSON serialization (RFC 8785) for hashing and signing.
+pub mod canonical;
+/// Converting between arrays of records and objects of columns.
+pub mod columns;
+/// `FromJson`/`ToJson` conversions between values and Rust types.
+pub mod convert;
+/// Converting arrays of flat objects to and from CSV.
+pub mod csv;
+/// Parsed documents annotated with their source and parse warnings.
+pub mod document;
+/// Type histograms across documents and schema drift between them.
+pub mod drift;
+/// Configurable equivalence checks and `assert_equivalent!` for tests.
+#[cfg(feature = "test-support")]
+pub mod equivalence;
 /// Error types for JSON parsing.
 pub mod error;
+/// jq-style expressions for selecting and transforming values.
+pub mod expr;
+/// Composable path filters and key renames for event streams.
+pub mod filter;
+/// Flattening nested documents into single-level objects.
+pub mod flatten;
+/// Pretty-printing, streaming from tokens or relaxed from values.
+pub mod format;
+/// Immutable, cheaply shareable documents.
+pub mod frozen;
+/// Typed GeoJSON views and validation over parsed values.
+#[cfg(feature = "geojson")]
+pub mod geojson;
+/// Stable identifiers for values, for tracking subtrees across versions.
+pub mod identity;
+/// Hash index from JSON Pointers to values for repeated lookups.
+pub mod index;
+/// Reports of values lost converting to or from other formats.
+pub mod interop;
+/// Iterators over the children of arrays and objects.
+pub mod iter;
+/// JSONPath queries selecting any number of values from a document.
+pub mod jsonpath;
+/// JSON-RPC 2.0 requests, notifications, responses, and batches.
+pub mod jsonrpc;
+/// JWT header and payload decoding (no signature verification).
+#[cfg(feature = "jwt")]
+pub mod jwt;
+/// Size and cardinality limits on the values at chosen paths.
+pub mod limits;
+/// Insertion-ordered map used for JSON objects.
+pub mod map;
+/// Display adapter that masks leaf values to avoid leaking data in logs.
+pub mod masked;
+/// Recursively merging values for layered configuration.
+pub mod merge;
+/// MessagePack encoding and decoding of values.
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+/// Prefixing object keys to share one flat configuration namespace.
+pub mod namespace;
 /// Parser for converting tokens into JSON values.
 pub mod parser;
+/// JSON Patch (RFC 6902) application and generation.
+pub mod patch;
+/// Paths locating values inside a JSON document.
+pub mod path;
+/// Reusable storage for batches of records parsed from NDJSON or arrays.
+pub mod pool;
+/// Tokenize and build timings and allocation counts per parse.
+#[cfg(feature = "profiling")]
+pub mod profile;
+/// Converting objects to and from URL query strings.
+pub mod query_string;
+/// Tokenizer that reads its input incrementally from `std::io::Read`.
+pub mod reader;
+/// Parsing that collects every error instead of stopping at the first.
+pub mod recovery;
+/// Reporting what a document loses when parsed and re-serialized.
+pub mod roundtrip;
+/// Uniform random samples of records from NDJSON input.
+pub mod sample;
+/// Rewriting constructed values so they serialize to valid JSON.
+pub mod sanitize;
+/// JSON Schema (draft 2020-12 subset) validation.
+pub mod schema;
+/// Secret-holding JSON documents whose strings are zeroed on drop.
+#[cfg(feature = "zeroize")]
+pub mod secret;
+/// serde `Serialize`/`Deserialize` support and value conversions.
+#[cfg(feature = "serde")]
+pub mod serde;
+/// Union, intersection, and difference of arrays treated as sets.
+pub mod sets;
+/// Golden-file snapshot helpers for tests.
+#[cfg(feature = "test-support")]
+pub mod snapshot;
+/// Event-based pull parser for documents too large to materialize.
+pub mod stream;
+/// Structural tape with lazily materialized values.
+pub mod tape;
 /// Tokenizer for converting JSON text into tokens.
 pub mod tokenizer;
+/// Reading TOML configuration into values.
+pub mod toml;
+/// Access logging for finding unread keys.
+pub mod tracked;
+/// Documents whose edits are checked against a shape as they are made.
+pub mod typed;
+/// Checking that text is strict JSON without building a value.
+pub mod validate;
 /// JSON value types and accessor methods.
 pub mod value;
 
+mod pointer;
 #[cfg(feature = "python")]
 mod python_bindings;
 
@@ -55,7 +162,7 @@ mod integration_tests {
     #[test]
     fn test_parse_number_value() {
         let result = JsonParser::new().parse("42.5").unwrap();
-        assert_eq!(result, JsonValue::Number(42.5));
+        assert_eq!(result, JsonValue::Number(42.5.into()));
     }
 
     #[test]
@@ -89,7 +196,7 @@ mod integration_tests {
         assert_eq!(tokens[0], Token::LeftBrace);
         assert_eq!(tokens[1], Token::String("key".to_string()));
         assert_eq!(tokens[2], Token::Colon);
-        assert_eq!(tokens[3], Token::Number(123.0));
+        assert_eq!(tokens[3], Token::Number(123.0.into()));
         assert_eq!(tokens[4], Token::RightBrace);
     }
 
@@ -102,7 +209,7 @@ mod integration_tests {
             result.get("name"),
             Some(&JsonValue::String("Alice".to_string()))
         );
-        assert_eq!(result.get("age"), Some(&JsonValue::Number(30.0)));
+        assert_eq!(result.get("age"), Some(&JsonValue::Number(30.0.into())));
         assert_eq!(result.get("active"), Some(&JsonValue::Boolean(true)));
         assert_eq!(result.get("data"), Some(&JsonValue::Null));
 
@@ -118,13 +225,13 @@ mod integration_tests {
         let items = result.get("items").unwrap();
         let items_arr = items.as_array().unwrap();
         assert_eq!(items_arr.len(), 3);
-        assert_eq!(items_arr[0], JsonValue::Number(1.0));
-        assert_eq!(items_arr[1], JsonValue::Number(2.0));
-        assert_eq!(items_arr[2], JsonValue::Number(3.0));
+        assert_eq!(items_arr[0], JsonValue::Number(1.0.into()));
+        assert_eq!(items_arr[1], JsonValue::Number(2.0.into()));
+        assert_eq!(items_arr[2], JsonValue::Number(3.0.into()));
 
         // Verify "meta" is an object with "count" = 3
         let meta = result.get("meta").unwrap();
-        assert_eq!(meta.get("count"), Some(&JsonValue::Number(3.0)));
+        assert_eq!(meta.get("count"), Some(&JsonValue::Number(3.0.into())));
     }
 
     #[test]
@@ -134,8 +241,8 @@ mod integration_tests {
         let arr = result.as_array().unwrap();
         assert_eq!(arr.len(), 2);
 
-        assert_eq!(arr[0].get("id"), Some(&JsonValue::Number(1.0)));
-        assert_eq!(arr[1].get("id"), Some(&JsonValue::Number(2.0)));
+        assert_eq!(arr[0].get("id"), Some(&JsonValue::Number(1.0.into())));
+        assert_eq!(arr[1].get("id"), Some(&JsonValue::Number(2.0.into())));
     }
 
     #[test]
@@ -145,11 +252,10 @@ mod integration_tests {
         let array_output = array_result.to_string();
         assert_eq!(array_output, r#"[1,"two",true,null]"#);
 
-        // Object: HashMap ordering is non-deterministic, use contains
+        // Object: keys keep their input order
         let object_result = parse_json(r#"{"name": "Alice", "age": 30}"#).unwrap();
         let object_output = object_result.to_string();
-        assert!(object_output.contains(r#""name":"Alice""#));
-        assert!(object_output.contains(r#""age":30"#));
+        assert_eq!(object_output, r#"{"name":"Alice","age":30}"#);
 
         // Verify the Display output can be re-parsed
         let reparsed = parse_json(&array_output).unwrap();
@@ -167,9 +273,9 @@ mod integration_tests {
         assert_eq!(tokens[1], Token::String("key".to_string()));
         assert_eq!(tokens[2], Token::Colon);
         assert_eq!(tokens[3], Token::LeftBracket);
-        assert_eq!(tokens[4], Token::Number(1.0));
+        assert_eq!(tokens[4], Token::Number(1.0.into()));
         assert_eq!(tokens[5], Token::Comma);
-        assert_eq!(tokens[6], Token::Number(2.0));
+        assert_eq!(tokens[6], Token::Number(2.0.into()));
         assert_eq!(tokens[7], Token::RightBracket);
         assert_eq!(tokens[8], Token::RightBrace);
 
@@ -178,7 +284,7 @@ mod integration_tests {
         let items = result.get("key").unwrap();
         let arr = items.as_array().unwrap();
         assert_eq!(arr.len(), 2);
-        assert_eq!(arr[0], JsonValue::Number(1.0));
-        assert_eq!(arr[1], JsonValue::Number(2.0));
+        assert_eq!(arr[0], JsonValue::Number(1.0.into()));
+        assert_eq!(arr[1], JsonValue::Number(2.0.into()));
     }
 }
@@ -0,0 +1,224 @@
+//! Size and cardinality limits on the values at chosen paths.
+//!
+//! The parser's own limits, such as
+//! [`JsonParser::max_string_len`](crate::parser::JsonParser::max_string_len),
+//! apply everywhere in a document. An endpoint usually knows more about
+//! its payload: `/items` holds at most ten thousand elements, and
+//! `/description` is at most 64 KiB of text.
+//! [`PathLimits`](crate::limits::PathLimits) records such rules by JSON
+//! Pointer, and
+//! [`JsonParser::path_limits`](crate::parser::JsonParser::path_limits)
+//! enforces them while parsing, failing as soon as a value breaks one
+//! instead of after the whole document has been built.
+
+use std::fmt;
+
+use crate::filter::segment_matches;
+use crate::path::JsonPath;
+
+/// One limit on the values at a path, as reported by
+/// [`JsonError::PathLimitExceeded`](crate::error::JsonError::PathLimitExceeded).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// The most elements an array, or members an object, may have.
+    Items(usize),
+    /// The longest a string may be, in bytes after unescaping.
+    StringLen(usize),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Items(1) => f.write_str("1 item"),
+            Limit::Items(max) => write!(f, "{} items", max),
+            Limit::StringLen(1) => f.write_str("1 byte"),
+            Limit::StringLen(max) => write!(f, "{} bytes", max),
+        }
+    }
+}
+
+/// Limits on the values at chosen paths, enforced by
+/// [`JsonParser::path_limits`](crate::parser::JsonParser::path_limits).
+///
+/// Paths are JSON Pointers, in which a `*` segment matches any object key
+/// or array index. A limit applies only to values of its kind: an item
+/// limit to arrays and objects, a string limit to strings. When several
+/// rules of one kind match a path, the smallest limit holds.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::error::JsonError;
+/// use rust_json_parser::limits::{Limit, PathLimits};
+/// use rust_json_parser::parser::JsonParser;
+///
+/// let limits = PathLimits::new()
+///     .max_items("/items", 2)
+///     .max_string_len("/items/*/sku", 4)
+///     .max_string_len("/description", 64 * 1024);
+/// let mut parser = JsonParser::new().path_limits(limits);
+///
+/// assert!(parser.parse(r#"{"items": [{"sku": "a1"}, {"sku": "b2"}]}"#).is_ok());
+/// let err = parser.parse(r#"{"items": [{"sku": "a1"}, {"sku": "b2-xl"}]}"#).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     r#"Value at "/items/1/sku" has more than 4 bytes at line 1, column 35 (position 34)"#
+/// );
+/// assert!(matches!(
+///     parser.parse(r#"{"items": [1, 2, 3]}"#),
+///     Err(JsonError::PathLimitExceeded { limit: Limit::Items(2), position: 17, .. })
+/// ));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathLimits {
+    rules: Vec<(JsonPath, Limit)>,
+}
+
+impl PathLimits {
+    /// Creates an empty set of limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the arrays and objects at `pointer` to `max` elements or
+    /// members.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pointer` is not a JSON Pointer.
+    pub fn max_items(self, pointer: &str, max: usize) -> Self {
+        self.rule(pointer, Limit::Items(max))
+    }
+
+    /// Limits the strings at `pointer` to `max` bytes after unescaping.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pointer` is not a JSON Pointer.
+    pub fn max_string_len(self, pointer: &str, max: usize) -> Self {
+        self.rule(pointer, Limit::StringLen(max))
+    }
+
+    /// Returns `true` if there are no limits.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    fn rule(mut self, pointer: &str, limit: Limit) -> Self {
+        let pattern = JsonPath::parse_pointer(pointer)
+            .unwrap_or_else(|| panic!("{:?} is not a JSON Pointer", pointer));
+        self.rules.push((pattern, limit));
+        self
+    }
+
+    /// Returns the item limit for the container at `path`, if any.
+    pub(crate) fn items_at(&self, path: &JsonPath) -> Option<Limit> {
+        self.smallest(path, |limit| match limit {
+            Limit::Items(max) => Some(max),
+            Limit::StringLen(_) => None,
+        })
+        .map(Limit::Items)
+    }
+
+    /// Returns the length limit for the string at `path`, if any.
+    pub(crate) fn string_len_at(&self, path: &JsonPath) -> Option<Limit> {
+        self.smallest(path, |limit| match limit {
+            Limit::StringLen(max) => Some(max),
+            Limit::Items(_) => None,
+        })
+        .map(Limit::StringLen)
+    }
+
+    fn smallest(&self, path: &JsonPath, max: impl Fn(Limit) -> Option<usize>) -> Option<usize> {
+        self.rules
+            .iter()
+            .filter(|(pattern, _)| {
+                pattern.len() == path.len()
+                    && pattern
+                        .segments()
+                        .iter()
+                        .zip(path.segments())
+                        .all(|(p, s)| segment_matches(p, s))
+            })
+            .filter_map(|&(_, limit)| max(limit))
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::JsonParser;
+
+    #[test]
+    fn test_matching_rules() {
+        let limits = PathLimits::new()
+            .max_items("/a/*", 5)
+            .max_items("/a/0", 3)
+            .max_string_len("/a/0", 1)
+            .max_items("", 9);
+        let path = |pointer| JsonPath::parse_pointer(pointer).unwrap();
+        assert_eq!(limits.items_at(&path("/a/0")), Some(Limit::Items(3)));
+        assert_eq!(limits.items_at(&path("/a/x")), Some(Limit::Items(5)));
+        assert_eq!(
+            limits.string_len_at(&path("/a/0")),
+            Some(Limit::StringLen(1))
+        );
+        assert_eq!(limits.items_at(&path("")), Some(Limit::Items(9)));
+        assert_eq!(limits.items_at(&path("/a")), None);
+        assert_eq!(limits.string_len_at(&path("/a/1")), None);
+    }
+
+    #[test]
+    fn test_enforced_while_parsing() {
+        let limits = PathLimits::new()
+            .max_items("/tags", 2)
+            .max_items("/meta", 1)
+            .max_string_len("/tags/*", 3);
+        let mut parser = JsonParser::new().path_limits(limits);
+        assert!(
+            parser
+                .parse(r#"{"tags": ["a", "b"], "meta": {"k": 1, "k": 2}}"#)
+                .is_ok()
+        );
+        assert!(
+            parser
+                .parse(r#"{"other": [1, 2, 3, "long"], "tags": {"x": "abc"}}"#)
+                .is_ok()
+        );
+
+        let mut error = |input: &str| parser.parse(input).unwrap_err().to_string();
+        assert_eq!(
+            error(r#"{"meta": {"a": 1, "b": 2}}"#),
+            r#"Value at "/meta" has more than 1 item at line 1, column 19 (position 18)"#
+        );
+        assert_eq!(
+            error(r#"{"tags": ["a", "bcde"]}"#),
+            r#"Value at "/tags/1" has more than 3 bytes at line 1, column 16 (position 15)"#
+        );
+        assert_eq!(
+            error(r#"{"tags": ["a", "b", "c"]}"#),
+            r#"Value at "/tags" has more than 2 items at line 1, column 21 (position 20)"#
+        );
+    }
+
+    #[test]
+    fn test_survives_shrink_buffers() {
+        let limits = PathLimits::new().max_items("/items", 2);
+        let mut parser = JsonParser::new().path_limits(limits);
+        parser.shrink_buffers();
+        assert_eq!(
+            parser
+                .parse(r#"{"items":[1,2,3]}"#)
+                .unwrap_err()
+                .to_string(),
+            r#"Value at "/items" has more than 2 items at line 1, column 15 (position 14)"#
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "\"items\" is not a JSON Pointer")]
+    fn test_invalid_pointer_panics() {
+        let _ = PathLimits::new().max_items("items", 1);
+    }
+}
@@ -1,18 +1,254 @@
-//! JSON parser demo showcasing all features.
+//! Command line tool for checking and reformatting JSON, plus a demo
+//! showcasing all features.
+//!
+//! ```text
+//! rust-json-parser validate [FILE]
+//! rust-json-parser format [--indent N] [FILE]
+//! rust-json-parser minify [FILE]
+//! rust-json-parser get [--jsonpath] [--raw] [FILE] QUERY
+//! ```
+//!
+//! Input is read from `FILE`, or from standard input when it is missing or
+//! `-`. Run without arguments for the demo, or with `--benchmark` for the
+//! benchmark.
 
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
 use std::time::Instant;
 
-use rust_json_parser::error::JsonError;
-use rust_json_parser::parser::{JsonParser, parse_json};
+use rust_json_parser::error::{JsonError, render};
+use rust_json_parser::format::FormatOptions;
+use rust_json_parser::jsonpath::Query;
+use rust_json_parser::parser::{JsonParser, parse_bytes, parse_json};
 use rust_json_parser::tokenizer::Tokenizer;
+use rust_json_parser::validate::validate;
 use rust_json_parser::value::JsonValue;
 
-fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() > 1 && args[1] == "--benchmark" {
-        run_benchmark();
-        return;
+const USAGE: &str = "\
+Usage: rust-json-parser <COMMAND> [OPTIONS] [FILE]
+
+Commands:
+  validate            Check that the input is valid JSON
+  format              Pretty-print the input
+  minify              Print the input without insignificant whitespace
+  get <QUERY>         Print the value at a JSON Pointer such as /users/0/name
+  help                Print this message
+
+Options:
+  --indent <N>        Spaces per level for `format` (default 2)
+  --jsonpath          Read the `get` query as JSONPath, such as $.users[*].name,
+                      and print every value it selects
+  -r, --raw           Print strings from `get` without quotes
+
+Reads standard input when FILE is missing or `-`. Exits with 1 when the
+input is not valid JSON or `get` finds nothing, and 2 on usage or I/O
+errors.
+Run without arguments for a demo of the library, or with --benchmark.
+";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => {
+            run_demo();
+            ExitCode::SUCCESS
+        }
+        Some("--benchmark") => {
+            run_benchmark();
+            ExitCode::SUCCESS
+        }
+        Some(_) => ExitCode::from(run_command(&args)),
+    }
+}
+
+/// Runs a subcommand and returns the process exit status.
+fn run_command(args: &[String]) -> u8 {
+    let command = args[0].as_str();
+    if matches!(command, "help" | "-h" | "--help") {
+        print!("{}", USAGE);
+        return 0;
+    }
+    if !matches!(command, "validate" | "format" | "minify" | "get") {
+        return usage_error(&format!("unknown command `{}`", command));
+    }
+
+    let mut indent = 2;
+    let mut jsonpath = false;
+    let mut raw = false;
+    let mut positional = Vec::new();
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                return 0;
+            }
+            "--indent" if command == "format" => match rest.next().map(|n| n.parse()) {
+                Some(Ok(n)) => indent = n,
+                _ => return usage_error("--indent needs a number of spaces"),
+            },
+            "--jsonpath" if command == "get" => jsonpath = true,
+            "-r" | "--raw" if command == "get" => raw = true,
+            // Pointers start with `/`, so anything else is an option.
+            option if option.starts_with('-') && option != "-" => {
+                return usage_error(&format!("unknown option `{}`", option));
+            }
+            value => positional.push(value),
+        }
     }
+
+    let (file, query) = match (command, positional.as_slice()) {
+        ("get", [query]) => (None, Some(*query)),
+        ("get", [file, query]) => (Some(*file), Some(*query)),
+        ("get", _) => return usage_error("`get` needs a query and at most one file"),
+        (_, []) => (None, None),
+        (_, [file]) => (Some(*file), None),
+        _ => return usage_error("expected at most one file"),
+    };
+    let query = match query {
+        Some(query) if jsonpath => match Query::parse(query) {
+            Ok(parsed) => Some(Lookup::JsonPath(query, parsed)),
+            Err(e) => return usage_error(&e.to_string()),
+        },
+        Some(pointer) if pointer.is_empty() || pointer.starts_with('/') => {
+            Some(Lookup::Pointer(pointer))
+        }
+        Some(pointer) => {
+            return usage_error(&format!(
+                "`{}` is not a JSON Pointer; pointers start with `/`, or pass --jsonpath",
+                pointer
+            ));
+        }
+        None => None,
+    };
+
+    let name = file.filter(|&path| path != "-").unwrap_or("<stdin>");
+    let input = match read_input(file) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("error: cannot read {}: {}", name, e);
+            return 2;
+        }
+    };
+
+    let errors = match command {
+        "validate" => match std::str::from_utf8(&input) {
+            Ok(text) => validate(text).err(),
+            Err(_) => parse_bytes(&input).err().map(|e| vec![e]),
+        },
+        // Keeping the source digits stops `format` and `minify` from
+        // rounding numbers that do not fit an f64.
+        _ => match JsonParser::new()
+            .preserve_raw_numbers(true)
+            .parse_bytes(&input)
+        {
+            Ok(value) => {
+                let output = match &query {
+                    Some(lookup) => {
+                        let found = lookup.find(&value);
+                        if found.is_empty() {
+                            eprintln!("error: {} selects nothing in {}", lookup, name);
+                            return 1;
+                        }
+                        found
+                            .iter()
+                            .map(|v| match v {
+                                JsonValue::String(s) if raw => s.clone(),
+                                v => v.to_string(),
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    }
+                    None if command == "format" => {
+                        let options = FormatOptions {
+                            indent: Some(indent),
+                            ..Default::default()
+                        };
+                        options.format(&value)
+                    }
+                    None => value.to_string(),
+                };
+                // A closed pipe, as with `| head`, is not a failure.
+                match writeln!(io::stdout().lock(), "{}", output) {
+                    Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+                        eprintln!("error: cannot write output: {}", e);
+                        return 2;
+                    }
+                    _ => None,
+                }
+            }
+            Err(e) => Some(vec![e]),
+        },
+    };
+    match errors {
+        None => 0,
+        Some(errors) => {
+            report(name, &String::from_utf8_lossy(&input), &errors);
+            1
+        }
+    }
+}
+
+/// The query of the `get` subcommand.
+enum Lookup<'a> {
+    Pointer(&'a str),
+    JsonPath(&'a str, Query),
+}
+
+impl Lookup<'_> {
+    fn find<'v>(&self, value: &'v JsonValue) -> Vec<&'v JsonValue> {
+        match self {
+            Lookup::Pointer(pointer) => value.pointer(pointer).into_iter().collect(),
+            Lookup::JsonPath(_, query) => query.select(value),
+        }
+    }
+}
+
+impl fmt::Display for Lookup<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lookup::Pointer(pointer) => write!(f, "pointer `{}`", pointer),
+            Lookup::JsonPath(query, _) => write!(f, "JSONPath `{}`", query),
+        }
+    }
+}
+
+fn read_input(file: Option<&str>) -> io::Result<Vec<u8>> {
+    match file {
+        Some(path) if path != "-" => fs::read(path),
+        _ => {
+            let mut input = Vec::new();
+            io::stdin().lock().read_to_end(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+/// Prints each error with the offending line of the source, like a
+/// compiler would.
+fn report(name: &str, source: &str, errors: &[JsonError]) {
+    for error in errors {
+        let rendered = render(error, source);
+        let mut lines = rendered.lines();
+        if let Some(headline) = lines.next() {
+            eprintln!("{}", headline);
+        }
+        let at = error.source_position(source);
+        eprintln!(" --> {}:{}:{}", name, at.line, at.column);
+        for line in lines {
+            eprintln!("{}", line);
+        }
+    }
+}
+
+fn usage_error(message: &str) -> u8 {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    2
+}
+
+fn run_demo() {
     if let Some(document) = show_parsing() {
         show_object_access(&document);
         show_array_access(&document);
@@ -240,15 +476,25 @@ fn show_error_handling() {
         Err(JsonError::UnexpectedToken {
             expected,
             found,
-            position,
+            line,
+            column,
+            ..
         }) => {
             println!(
-                "    UnexpectedToken {{ expected: {:?}, found: {:?}, position: {} }}",
-                expected, found, position
+                "    UnexpectedToken {{ expected: {:?}, found: {:?}, line: {}, column: {} }}",
+                expected, found, line, column
             );
         }
         other => println!("    Unexpected: {:?}", other),
     }
+
+    println!("\n  Rendered with the source line:");
+    let config = "{\n  \"name\": \"demo\",\n  port: 8080\n}";
+    if let Err(e) = parse_json(config) {
+        for line in render(&e, config).lines() {
+            println!("    {}", line);
+        }
+    }
 }
 
 fn show_python_bindings() {
@@ -0,0 +1,503 @@
+//! Insertion-ordered map used for JSON objects.
+//!
+//! [`JsonMap`](crate::map::JsonMap) keeps members in the order they were
+//! inserted, so a parsed document serializes with its keys in the order
+//! they appeared in the input. Small objects are searched linearly; once
+//! an object grows past a few members a hash index is built alongside the
+//! entries so lookups stay O(1).
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::ops::Index;
+
+use crate::value::JsonValue;
+
+// Objects up to this size are searched linearly without a hash index.
+const LINEAR_SCAN_MAX: usize = 8;
+
+/// An insertion-ordered map from string keys to [`JsonValue`]s.
+///
+/// Inserting an existing key replaces its value in place, keeping the
+/// key's original position. Equality ignores order: two maps are equal if
+/// they have the same members.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::map::JsonMap;
+/// use rust_json_parser::value::JsonValue;
+///
+/// let mut map = JsonMap::new();
+/// map.insert("b".to_string(), JsonValue::Null);
+/// map.insert("a".to_string(), JsonValue::Boolean(true));
+/// let keys: Vec<&str> = map.keys().map(String::as_str).collect();
+/// assert_eq!(keys, ["b", "a"]);
+/// assert_eq!(JsonValue::Object(map).to_string(), r#"{"b":null,"a":true}"#);
+/// ```
+#[derive(Clone, Default)]
+pub struct JsonMap {
+    entries: Vec<(String, JsonValue)>,
+    // Positions in `entries` by key hash; empty while the map is small.
+    index: KeyIndex,
+}
+
+impl JsonMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty map with room for `capacity` members.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            index: KeyIndex::default(),
+        }
+    }
+
+    /// Returns the number of members.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the map has no members.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Counts the heap blocks held by the map itself, not its members.
+    #[cfg(feature = "profiling")]
+    pub(crate) fn allocations(&self) -> usize {
+        usize::from(self.entries.capacity() > 0) + usize::from(self.index.capacity() > 0)
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        if self.index.is_empty() {
+            self.entries.iter().position(|(k, _)| k == key)
+        } else {
+            self.index.find(&self.entries, key)
+        }
+    }
+
+    /// Returns `true` if the map contains `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Returns the value for `key`.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.position(key).map(|i| &self.entries[i].1)
+    }
+
+    /// Returns a mutable reference to the value for `key`.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        self.position(key).map(|i| &mut self.entries[i].1)
+    }
+
+    /// Inserts a member, returning the previous value if the key existed.
+    ///
+    /// A new key is appended at the end; an existing key keeps its
+    /// position.
+    pub fn insert(&mut self, key: String, value: JsonValue) -> Option<JsonValue> {
+        if let Some(i) = self.position(&key) {
+            return Some(std::mem::replace(&mut self.entries[i].1, value));
+        }
+        self.entries.push((key, value));
+        if !self.index.is_empty() {
+            self.index.push(&self.entries);
+        } else if self.entries.len() > LINEAR_SCAN_MAX {
+            self.index.rebuild(&self.entries);
+        }
+        None
+    }
+
+    /// Removes `key`, returning its value. Later members shift up one
+    /// position, preserving their relative order.
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        self.remove_full(key).map(|(_, value)| value)
+    }
+
+    // Removes `key`, returning the position it had along with its value.
+    pub(crate) fn remove_full(&mut self, key: &str) -> Option<(usize, JsonValue)> {
+        let position = self.position(key)?;
+        if self.entries.len() > LINEAR_SCAN_MAX + 1 {
+            self.index.remove(&self.entries, position);
+        } else {
+            self.index.clear();
+        }
+        let (_, value) = self.entries.remove(position);
+        Some((position, value))
+    }
+
+    // Inserts a key that is not in the map at `position`, shifting later
+    // members down; undoes `remove_full`.
+    pub(crate) fn insert_at(&mut self, position: usize, key: String, value: JsonValue) {
+        self.entries.insert(position, (key, value));
+        self.reindex();
+    }
+
+    /// Keeps only the members for which `keep` returns `true`, in order.
+    pub fn retain(&mut self, mut keep: impl FnMut(&String, &mut JsonValue) -> bool) {
+        let len = self.entries.len();
+        self.entries.retain_mut(|(k, v)| keep(k, v));
+        if self.entries.len() != len {
+            self.reindex();
+        }
+    }
+
+    /// Keeps the first `len` members, dropping the rest.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.entries.len() {
+            self.entries.truncate(len);
+            self.reindex();
+        }
+    }
+
+    /// Reorders members by ascending key.
+    pub fn sort_keys(&mut self) {
+        self.entries.sort_by(|a, b| a.0.cmp(&b.0));
+        self.reindex();
+    }
+
+    fn reindex(&mut self) {
+        if self.entries.len() <= LINEAR_SCAN_MAX {
+            self.index.clear();
+        } else {
+            self.index.rebuild(&self.entries);
+        }
+    }
+
+    /// Removes all members, returning them in order.
+    pub fn drain(&mut self) -> std::vec::Drain<'_, (String, JsonValue)> {
+        self.index.clear();
+        self.entries.drain(..)
+    }
+
+    /// Removes all members.
+    pub fn clear(&mut self) {
+        self.index.clear();
+        self.entries.clear();
+    }
+
+    /// Iterates over members in insertion order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Iterates over members in insertion order with mutable values.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&String, &mut JsonValue)> {
+        self.entries.iter_mut().map(|(k, v)| (&*k, v))
+    }
+
+    /// Iterates over keys in insertion order.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    /// Iterates over values in insertion order.
+    pub fn values(&self) -> impl Iterator<Item = &JsonValue> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    /// Iterates over mutable values in insertion order.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut JsonValue> {
+        self.entries.iter_mut().map(|(_, v)| v)
+    }
+}
+
+// An open-addressing hash table of positions in a map's entries. It keeps
+// each key's hash but not the key itself, so zeroizing the entries leaves
+// no copy of the key text behind.
+#[derive(Clone, Default)]
+struct KeyIndex {
+    hasher: RandomState,
+    // Linear probing over a power-of-two table at most half full.
+    slots: Vec<Slot>,
+}
+
+#[derive(Clone, Copy)]
+struct Slot {
+    position: usize,
+    hash: u64,
+}
+
+const EMPTY: Slot = Slot {
+    position: usize::MAX,
+    hash: 0,
+};
+
+impl KeyIndex {
+    fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    #[cfg(feature = "profiling")]
+    fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    fn clear(&mut self) {
+        self.slots.clear();
+    }
+
+    fn mask(&self) -> usize {
+        self.slots.len() - 1
+    }
+
+    fn find(&self, entries: &[(String, JsonValue)], key: &str) -> Option<usize> {
+        let hash = self.hasher.hash_one(key);
+        let mut i = hash as usize & self.mask();
+        loop {
+            let slot = self.slots[i];
+            if slot.position == usize::MAX {
+                return None;
+            }
+            if slot.hash == hash && entries[slot.position].0 == key {
+                return Some(slot.position);
+            }
+            i = (i + 1) & self.mask();
+        }
+    }
+
+    fn place(&mut self, position: usize, hash: u64) {
+        let mut i = hash as usize & self.mask();
+        while self.slots[i].position != usize::MAX {
+            i = (i + 1) & self.mask();
+        }
+        self.slots[i] = Slot { position, hash };
+    }
+
+    fn rebuild(&mut self, entries: &[(String, JsonValue)]) {
+        self.slots.clear();
+        self.slots
+            .resize((entries.len() * 2).next_power_of_two(), EMPTY);
+        for (position, (key, _)) in entries.iter().enumerate() {
+            let hash = self.hasher.hash_one(key);
+            self.place(position, hash);
+        }
+    }
+
+    // Indexes the last of `entries`, which was just appended.
+    fn push(&mut self, entries: &[(String, JsonValue)]) {
+        if entries.len() * 2 > self.slots.len() {
+            self.rebuild(entries);
+        } else if let Some((key, _)) = entries.last() {
+            let hash = self.hasher.hash_one(key);
+            self.place(entries.len() - 1, hash);
+        }
+    }
+
+    // Unindexes the member at `position`, which is about to be removed
+    // from `entries`, and moves later positions up one.
+    fn remove(&mut self, entries: &[(String, JsonValue)], position: usize) {
+        let mask = self.mask();
+        let mut hole = self.hasher.hash_one(&entries[position].0) as usize & mask;
+        while self.slots[hole].position != position {
+            hole = (hole + 1) & mask;
+        }
+        // Backward-shift deletion: pull each later slot of the probe run
+        // into the hole unless that would put it before its home slot.
+        let mut i = hole;
+        loop {
+            i = (i + 1) & mask;
+            let slot = self.slots[i];
+            if slot.position == usize::MAX {
+                break;
+            }
+            let home = slot.hash as usize & mask;
+            if i.wrapping_sub(home) & mask >= i.wrapping_sub(hole) & mask {
+                self.slots[hole] = slot;
+                hole = i;
+            }
+        }
+        self.slots[hole] = EMPTY;
+        for slot in &mut self.slots {
+            if slot.position != usize::MAX && slot.position > position {
+                slot.position -= 1;
+            }
+        }
+    }
+}
+
+/// Borrowing iterator over the members of a [`JsonMap`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    inner: std::slice::Iter<'a, (String, JsonValue)>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a String, &'a JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl<'a> IntoIterator for &'a JsonMap {
+    type Item = (&'a String, &'a JsonValue);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for JsonMap {
+    type Item = (String, JsonValue);
+    type IntoIter = std::vec::IntoIter<(String, JsonValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl FromIterator<(String, JsonValue)> for JsonMap {
+    fn from_iter<I: IntoIterator<Item = (String, JsonValue)>>(iter: I) -> Self {
+        let mut map = JsonMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl Extend<(String, JsonValue)> for JsonMap {
+    fn extend<I: IntoIterator<Item = (String, JsonValue)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl Index<&str> for JsonMap {
+    type Output = JsonValue;
+
+    /// # Panics
+    ///
+    /// Panics if `key` is not present.
+    fn index(&self, key: &str) -> &JsonValue {
+        self.get(key).expect("key not found in JsonMap")
+    }
+}
+
+impl PartialEq for JsonMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl fmt::Debug for JsonMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(n: f64) -> JsonValue {
+        JsonValue::Number(n.into())
+    }
+
+    #[test]
+    fn test_preserves_insertion_order() {
+        let mut map = JsonMap::new();
+        for key in ["z", "a", "m"] {
+            map.insert(key.to_string(), JsonValue::Null);
+        }
+        let keys: Vec<&String> = map.keys().collect();
+        assert_eq!(keys, ["z", "a", "m"]);
+    }
+
+    #[test]
+    fn test_insert_existing_keeps_position() {
+        let mut map = JsonMap::new();
+        map.insert("a".to_string(), number(1.0));
+        map.insert("b".to_string(), number(2.0));
+        assert_eq!(map.insert("a".to_string(), number(3.0)), Some(number(1.0)));
+        let pairs: Vec<(&String, &JsonValue)> = map.iter().collect();
+        assert_eq!(pairs[0], (&"a".to_string(), &number(3.0)));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn test_indexed_lookup_and_remove() {
+        let mut map: JsonMap = (0..20)
+            .map(|i| (format!("k{}", i), number(i as f64)))
+            .collect();
+        assert!(!map.index.is_empty());
+        assert_eq!(map.get("k15"), Some(&number(15.0)));
+        assert_eq!(map.remove("k3"), Some(number(3.0)));
+        assert_eq!(map.get("k4"), Some(&number(4.0)));
+        assert_eq!(map.get("k19"), Some(&number(19.0)));
+        assert!(!map.contains_key("k3"));
+        assert_eq!(map.keys().nth(3).map(String::as_str), Some("k4"));
+
+        for i in 4..20 {
+            map.remove(&format!("k{}", i));
+        }
+        assert!(map.index.is_empty());
+        assert_eq!(map.get("k2"), Some(&number(2.0)));
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    fn test_index_survives_removals() {
+        let mut map: JsonMap = (0..100)
+            .map(|i| (format!("k{}", i), number(i as f64)))
+            .collect();
+        for step in 0..92 {
+            let removed = format!("k{}", step * 37 % 100);
+            assert!(map.remove(&removed).is_some());
+            assert!(!map.contains_key(&removed));
+            for (position, key) in map.keys().enumerate() {
+                assert_eq!(map.position(key), Some(position));
+            }
+        }
+        assert_eq!(map.len(), 8);
+        assert!(map.index.is_empty());
+    }
+
+    #[test]
+    fn test_equality_ignores_order() {
+        let a: JsonMap = [
+            ("x".to_string(), number(1.0)),
+            ("y".to_string(), JsonValue::Null),
+        ]
+        .into_iter()
+        .collect();
+        let b: JsonMap = [
+            ("y".to_string(), JsonValue::Null),
+            ("x".to_string(), number(1.0)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(a, b);
+        let mut c = b.clone();
+        c.insert("z".to_string(), JsonValue::Null);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_get_mut_and_drain() {
+        let mut map = JsonMap::new();
+        map.insert("a".to_string(), number(1.0));
+        *map.get_mut("a").unwrap() = JsonValue::Boolean(true);
+        assert_eq!(map["a"], JsonValue::Boolean(true));
+        let drained: Vec<(String, JsonValue)> = map.drain().collect();
+        assert_eq!(drained.len(), 1);
+        assert!(map.is_empty());
+        assert!(map.get("a").is_none());
+    }
+}
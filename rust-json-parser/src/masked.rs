@@ -0,0 +1,169 @@
+//! Display adapter that hides leaf values.
+//!
+//! Logging a parsed payload is a common way to leak personal data.
+//! [`Masked`](crate::masked::Masked) wraps a
+//! [`JsonValue`](crate::value::JsonValue) and formats its structure and
+//! key names while replacing every leaf value with `***`. Values under
+//! explicitly allowlisted keys are printed as-is.
+
+use std::fmt;
+
+use crate::value::{JsonFormat, JsonValue};
+
+/// The placeholder printed in place of each hidden leaf value.
+pub const MASK: &str = "***";
+
+/// Formats a value with its leaf values replaced by [`MASK`].
+///
+/// Both `Display` and `Debug` produce the masked form, so the adapter is
+/// safe to pass to either `{}` or `{:?}` in log statements.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::masked::Masked;
+/// use rust_json_parser::parser::parse_json;
+///
+/// let value = parse_json(r#"{"user": {"email": "a@b.c"}, "tags": [1, 2]}"#)?;
+/// assert_eq!(
+///     Masked::new(&value).to_string(),
+///     r#"{"user":{"email":***},"tags":[***,***]}"#
+/// );
+/// assert_eq!(
+///     Masked::new(&value).allow(["tags"]).to_string(),
+///     r#"{"user":{"email":***},"tags":[1,2]}"#
+/// );
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Clone)]
+pub struct Masked<'a> {
+    value: &'a JsonValue,
+    allowed: Vec<String>,
+}
+
+impl<'a> Masked<'a> {
+    /// Wraps a value, masking every leaf.
+    pub fn new(value: &'a JsonValue) -> Self {
+        Self {
+            value,
+            allowed: Vec::new(),
+        }
+    }
+
+    /// Adds object keys whose values are printed unmasked.
+    ///
+    /// Keys match at any depth, and the whole subtree under a matching key
+    /// is shown.
+    pub fn allow<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed.extend(keys.into_iter().map(Into::into));
+        self
+    }
+
+    fn is_allowed(&self, key: &str) -> bool {
+        self.allowed.iter().any(|k| k == key)
+    }
+
+    fn write_masked(&self, f: &mut fmt::Formatter<'_>, value: &JsonValue) -> fmt::Result {
+        match value {
+            JsonValue::Array(arr) => {
+                f.write_str("[")?;
+                for (i, item) in arr.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    self.write_masked(f, item)?;
+                }
+                f.write_str("]")
+            }
+            JsonValue::Object(map) => {
+                f.write_str("{")?;
+                for (i, (key, item)) in map.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    f.write_str(&key.to_json_string())?;
+                    f.write_str(":")?;
+                    if self.is_allowed(key) {
+                        write!(f, "{}", item)?;
+                    } else {
+                        self.write_masked(f, item)?;
+                    }
+                }
+                f.write_str("}")
+            }
+            _ => f.write_str(MASK),
+        }
+    }
+}
+
+impl fmt::Display for Masked<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_masked(f, self.value)
+    }
+}
+
+impl fmt::Debug for Masked<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Masked({})", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_masks_scalar_root() {
+        assert_eq!(
+            Masked::new(&JsonValue::Number(42.0.into())).to_string(),
+            "***"
+        );
+        assert_eq!(Masked::new(&JsonValue::Null).to_string(), "***");
+    }
+
+    #[test]
+    fn test_masks_leaves_keeps_structure() {
+        let value = parse_json(r#"[{"password": "hunter2"}, [], {}]"#).unwrap();
+        assert_eq!(
+            Masked::new(&value).to_string(),
+            r#"[{"password":***},[],{}]"#
+        );
+    }
+
+    #[test]
+    fn test_allowlisted_key_shows_subtree() {
+        let value = parse_json(r#"{"meta": {"id": 7, "kind": "x"}}"#).unwrap();
+        assert_eq!(
+            Masked::new(&value).allow(["meta"]).to_string(),
+            r#"{"meta":{"id":7,"kind":"x"}}"#
+        );
+    }
+
+    #[test]
+    fn test_allowlist_matches_at_any_depth() {
+        let value = parse_json(r#"{"a": {"b": {"id": 1, "ssn": "123"}}}"#).unwrap();
+        let output = Masked::new(&value)
+            .allow(vec!["id".to_string()])
+            .to_string();
+        assert_eq!(output, r#"{"a":{"b":{"id":1,"ssn":***}}}"#);
+    }
+
+    #[test]
+    fn test_debug_is_masked() {
+        let value = parse_json(r#"{"token": "secret"}"#).unwrap();
+        let output = format!("{:?}", Masked::new(&value));
+        assert_eq!(output, r#"Masked({"token":***})"#);
+        assert!(!output.contains("secret"));
+    }
+
+    #[test]
+    fn test_key_names_are_escaped() {
+        let value = parse_json(r#"{"a\"b": 1}"#).unwrap();
+        assert_eq!(Masked::new(&value).to_string(), r#"{"a\"b":***}"#);
+    }
+}
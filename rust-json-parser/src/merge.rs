@@ -0,0 +1,85 @@
+//! Recursively merging one value into another.
+//!
+//! Configuration is usually layered: built-in defaults, then a file per
+//! environment, then command line flags, each overriding only the keys it
+//! mentions. [`JsonValue::deep_merge`](crate::value::JsonValue::deep_merge)
+//! merges objects key by key at every depth and lets the later layer win
+//! everywhere else, with a
+//! [`MergeStrategy`](crate::merge::MergeStrategy) choosing whether arrays
+//! are replaced or concatenated.
+
+use crate::value::JsonValue;
+
+/// How [`JsonValue::deep_merge`] combines two arrays at the same path.
+///
+/// Objects are always merged key by key, and any other pair of values is
+/// resolved in favour of the overriding one, including `null`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// The overriding array replaces the base array.
+    #[default]
+    Replace,
+    /// The overriding array's elements are appended to the base array.
+    Concat,
+}
+
+impl MergeStrategy {
+    pub(crate) fn merge(self, base: &mut JsonValue, overlay: JsonValue) {
+        match (base, overlay) {
+            (JsonValue::Object(base), JsonValue::Object(overlay)) => {
+                for (key, value) in overlay {
+                    match base.get_mut(&key) {
+                        Some(existing) => self.merge(existing, value),
+                        None => {
+                            base.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (JsonValue::Array(base), JsonValue::Array(overlay))
+                if self == MergeStrategy::Concat =>
+            {
+                base.extend(overlay);
+            }
+            (base, overlay) => *base = overlay,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    #[test]
+    fn test_merge_strategies() {
+        let base = json!({
+            "name": "svc",
+            "tags": ["a"],
+            "db": {"host": "localhost", "pool": {"min": 1, "max": 4}},
+            "debug": true
+        });
+        let overlay = json!({
+            "tags": ["b"],
+            "db": {"pool": {"max": 16}, "user": "app"},
+            "debug": null,
+            "extra": [1]
+        });
+
+        let mut replaced = base.clone();
+        replaced.deep_merge(overlay.clone(), MergeStrategy::Replace);
+        assert_eq!(
+            replaced.to_string(),
+            r#"{"name":"svc","tags":["b"],"db":{"host":"localhost","pool":{"min":1,"max":16},"user":"app"},"debug":null,"extra":[1]}"#
+        );
+
+        let mut concatenated = base.clone();
+        concatenated.deep_merge(overlay, MergeStrategy::Concat);
+        assert_eq!(concatenated["tags"], json!(["a", "b"]));
+        assert_eq!(concatenated["db"]["pool"], json!({"min": 1, "max": 16}));
+
+        let mut scalar = json!({"a": 1});
+        scalar.deep_merge(json!([1]), MergeStrategy::Concat);
+        assert_eq!(scalar, json!([1]));
+    }
+}
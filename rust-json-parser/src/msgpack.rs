@@ -0,0 +1,467 @@
+//! Converting values to and from MessagePack.
+//!
+//! MessagePack is a binary format with the same shape as JSON, used by many
+//! RPC systems.
+//! [`JsonValue::to_msgpack`](crate::value::JsonValue::to_msgpack) writes
+//! the smallest encoding of each value, and
+//! [`JsonValue::from_msgpack`](crate::value::JsonValue::from_msgpack) reads
+//! any encoding of the int, float, str, bin, array, and map families.
+//!
+//! | JSON | MessagePack |
+//! |------|-------------|
+//! | `null`, `true`, `false` | nil, true, false |
+//! | integers | positive/negative fixint, uint 8-64, int 8-64 |
+//! | other numbers | float 64 (float 32 is read too) |
+//! | strings | fixstr, str 8-32 |
+//! | arrays, objects | fixarray/array 16-32, fixmap/map 16-32 |
+//!
+//! Every JSON value has an exact MessagePack encoding, but not the other
+//! way around. Reading approximates what JSON cannot hold and lists it in
+//! the returned [`ConversionReport`](crate::interop::ConversionReport): bin
+//! becomes a base64 string, ext and NaN or infinite floats become `null`,
+//! map keys that are not strings become their JSON text, and str that is
+//! not UTF-8 has the offending bytes replaced with U+FFFD. A key repeated
+//! in one map keeps its last value and the earlier ones are reported as
+//! dropped.
+
+use std::error;
+use std::fmt;
+
+use crate::interop::ConversionReport;
+use crate::map::JsonMap;
+use crate::parser::DEFAULT_MAX_DEPTH;
+use crate::path::{JsonPath, Segment};
+use crate::value::{JsonNumber, JsonValue};
+
+/// MessagePack that could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MsgpackError {
+    /// Byte offset in the input where the problem was found.
+    pub position: usize,
+    /// What was wrong.
+    pub message: String,
+}
+
+impl fmt::Display for MsgpackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid MessagePack at byte {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl error::Error for MsgpackError {}
+
+pub(crate) fn encode(value: &JsonValue, out: &mut Vec<u8>) {
+    match value {
+        JsonValue::Null => out.push(0xc0),
+        JsonValue::Boolean(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+        JsonValue::Number(n) => encode_number(n, out),
+        JsonValue::String(s) => {
+            encode_len(s.len(), [0xa0, 0xd9, 0xda, 0xdb], 31, out);
+            out.extend_from_slice(s.as_bytes());
+        }
+        JsonValue::Array(elements) => {
+            encode_len(elements.len(), [0x90, 0x00, 0xdc, 0xdd], 15, out);
+            for element in elements {
+                encode(element, out);
+            }
+        }
+        JsonValue::Object(map) => {
+            encode_len(map.len(), [0x80, 0x00, 0xde, 0xdf], 15, out);
+            for (key, member) in map {
+                encode_len(key.len(), [0xa0, 0xd9, 0xda, 0xdb], 31, out);
+                out.extend_from_slice(key.as_bytes());
+                encode(member, out);
+            }
+        }
+    }
+}
+
+fn encode_number(n: &JsonNumber, out: &mut Vec<u8>) {
+    if let Some(u) = n.as_u64() {
+        match u {
+            0..=0x7f => out.push(u as u8),
+            0x80..=0xff => out.extend_from_slice(&[0xcc, u as u8]),
+            0x100..=0xffff => {
+                out.push(0xcd);
+                out.extend_from_slice(&(u as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                out.push(0xce);
+                out.extend_from_slice(&(u as u32).to_be_bytes());
+            }
+            _ => {
+                out.push(0xcf);
+                out.extend_from_slice(&u.to_be_bytes());
+            }
+        }
+    } else if let Some(i) = n.as_i64() {
+        // Negative: non-negative integers were handled above.
+        if i >= -32 {
+            out.push(i as u8);
+        } else if i >= i64::from(i8::MIN) {
+            out.extend_from_slice(&[0xd0, i as u8]);
+        } else if i >= i64::from(i16::MIN) {
+            out.push(0xd1);
+            out.extend_from_slice(&(i as i16).to_be_bytes());
+        } else if i >= i64::from(i32::MIN) {
+            out.push(0xd2);
+            out.extend_from_slice(&(i as i32).to_be_bytes());
+        } else {
+            out.push(0xd3);
+            out.extend_from_slice(&i.to_be_bytes());
+        }
+    } else {
+        out.push(0xcb);
+        out.extend_from_slice(&n.as_f64().to_be_bytes());
+    }
+}
+
+// Writes a str, array, or map header. `markers` holds the fix marker and
+// the 8-, 16-, and 32-bit markers; arrays and maps have no 8-bit form.
+fn encode_len(len: usize, markers: [u8; 4], fix_max: usize, out: &mut Vec<u8>) {
+    if len <= fix_max {
+        out.push(markers[0] | len as u8);
+    } else if len <= 0xff && markers[1] != 0 {
+        out.extend_from_slice(&[markers[1], len as u8]);
+    } else if len <= 0xffff {
+        out.push(markers[2]);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        let len = u32::try_from(len).expect("MessagePack lengths fit in 32 bits");
+        out.push(markers[3]);
+        out.extend_from_slice(&len.to_be_bytes());
+    }
+}
+
+pub(crate) fn decode(bytes: &[u8]) -> Result<(JsonValue, ConversionReport), MsgpackError> {
+    let mut decoder = Decoder {
+        bytes,
+        pos: 0,
+        path: JsonPath::root(),
+        report: ConversionReport::new(),
+    };
+    let value = decoder.value(0)?;
+    if decoder.pos < bytes.len() {
+        return decoder.fail("unexpected bytes after the value");
+    }
+    Ok((value, decoder.report))
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    path: JsonPath,
+    report: ConversionReport,
+}
+
+impl Decoder<'_> {
+    fn value(&mut self, depth: usize) -> Result<JsonValue, MsgpackError> {
+        let marker = self.take(1)?[0];
+        let value = match marker {
+            0x00..=0x7f => JsonValue::from(u64::from(marker)),
+            0x80..=0x8f => self.map(usize::from(marker & 0x0f), depth)?,
+            0x90..=0x9f => self.array(usize::from(marker & 0x0f), depth)?,
+            0xa0..=0xbf => self.str(usize::from(marker & 0x1f))?,
+            0xc0 => JsonValue::Null,
+            0xc2 => JsonValue::Boolean(false),
+            0xc3 => JsonValue::Boolean(true),
+            0xc4..=0xc6 => {
+                let len = self.len(marker - 0xc4)?;
+                let data = self.take(len)?;
+                let encoded = base64(data);
+                self.report
+                    .approximated(self.path.clone(), "binary data as base64");
+                JsonValue::String(encoded)
+            }
+            0xc7..=0xc9 => {
+                let len = self.len(marker - 0xc7)?;
+                self.ext(len)?
+            }
+            0xca => {
+                let bits = u32::from_be_bytes(self.array_of()?);
+                self.float(f64::from(f32::from_bits(bits)))
+            }
+            0xcb => {
+                let n = f64::from_be_bytes(self.array_of()?);
+                self.float(n)
+            }
+            0xcc => JsonValue::from(u64::from(self.take(1)?[0])),
+            0xcd => JsonValue::from(u64::from(u16::from_be_bytes(self.array_of()?))),
+            0xce => JsonValue::from(u64::from(u32::from_be_bytes(self.array_of()?))),
+            0xcf => JsonValue::from(u64::from_be_bytes(self.array_of()?)),
+            0xd0 => JsonValue::from(i64::from(self.take(1)?[0] as i8)),
+            0xd1 => JsonValue::from(i64::from(i16::from_be_bytes(self.array_of()?))),
+            0xd2 => JsonValue::from(i64::from(i32::from_be_bytes(self.array_of()?))),
+            0xd3 => JsonValue::from(i64::from_be_bytes(self.array_of()?)),
+            0xd4..=0xd8 => self.ext(1 << (marker - 0xd4))?,
+            0xd9..=0xdb => {
+                let len = self.len(marker - 0xd9)?;
+                self.str(len)?
+            }
+            0xdc | 0xdd => {
+                let len = self.len(marker - 0xdc + 1)?;
+                self.array(len, depth)?
+            }
+            0xde | 0xdf => {
+                let len = self.len(marker - 0xde + 1)?;
+                self.map(len, depth)?
+            }
+            0xe0..=0xff => JsonValue::from(i64::from(marker as i8)),
+            0xc1 => {
+                self.pos -= 1;
+                return self.fail("0xc1 is never used");
+            }
+        };
+        Ok(value)
+    }
+
+    fn float(&mut self, n: f64) -> JsonValue {
+        if n.is_finite() {
+            return JsonValue::Number(JsonNumber::new(n));
+        }
+        self.report
+            .approximated(self.path.clone(), "non-finite float as null");
+        JsonValue::Null
+    }
+
+    fn array(&mut self, len: usize, depth: usize) -> Result<JsonValue, MsgpackError> {
+        self.enter(depth)?;
+        // Every element takes at least a byte, so a corrupt length cannot
+        // make us allocate more than the input.
+        let mut elements = Vec::with_capacity(len.min(self.bytes.len() - self.pos));
+        for i in 0..len {
+            self.path.push(Segment::Index(i));
+            elements.push(self.value(depth + 1)?);
+            self.path.pop();
+        }
+        Ok(JsonValue::Array(elements))
+    }
+
+    fn map(&mut self, len: usize, depth: usize) -> Result<JsonValue, MsgpackError> {
+        self.enter(depth)?;
+        let mut map = JsonMap::with_capacity(len.min(self.bytes.len() - self.pos));
+        for _ in 0..len {
+            let start = self.pos;
+            let key = match self.value(depth + 1)? {
+                JsonValue::String(key) => key,
+                other => {
+                    let key = other.to_string();
+                    let mut path = self.path.clone();
+                    path.push(Segment::Key(key.clone()));
+                    self.report.approximated(
+                        path,
+                        format!(
+                            "{} map key at byte {} as a string",
+                            other.type_name(),
+                            start
+                        ),
+                    );
+                    key
+                }
+            };
+            self.path.push(Segment::Key(key.clone()));
+            let value = self.value(depth + 1)?;
+            if map.insert(key, value).is_some() {
+                self.report
+                    .dropped(self.path.clone(), "an earlier value for a repeated key");
+            }
+            self.path.pop();
+        }
+        Ok(JsonValue::Object(map))
+    }
+
+    fn str(&mut self, len: usize) -> Result<JsonValue, MsgpackError> {
+        let data = self.take(len)?;
+        let text = match std::str::from_utf8(data) {
+            Ok(text) => text.to_string(),
+            Err(_) => {
+                let text = String::from_utf8_lossy(data).into_owned();
+                self.report
+                    .approximated(self.path.clone(), "invalid UTF-8 replaced with U+FFFD");
+                text
+            }
+        };
+        Ok(JsonValue::String(text))
+    }
+
+    fn ext(&mut self, len: usize) -> Result<JsonValue, MsgpackError> {
+        let kind = self.take(1)?[0] as i8;
+        self.take(len)?;
+        self.report.approximated(
+            self.path.clone(),
+            format!("extension type {} as null", kind),
+        );
+        Ok(JsonValue::Null)
+    }
+
+    // Reads a length of 1, 2, or 4 bytes for `width` 0, 1, or 2.
+    fn len(&mut self, width: u8) -> Result<usize, MsgpackError> {
+        let len = match width {
+            0 => u32::from(self.take(1)?[0]),
+            1 => u32::from(u16::from_be_bytes(self.array_of()?)),
+            _ => u32::from_be_bytes(self.array_of()?),
+        };
+        Ok(len as usize)
+    }
+
+    fn enter(&self, depth: usize) -> Result<(), MsgpackError> {
+        if depth >= DEFAULT_MAX_DEPTH {
+            return self.fail(&format!("nested deeper than {}", DEFAULT_MAX_DEPTH));
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&[u8], MsgpackError> {
+        if self.bytes.len() - self.pos < len {
+            return self.fail("unexpected end of input");
+        }
+        let taken = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(taken)
+    }
+
+    fn array_of<const N: usize>(&mut self) -> Result<[u8; N], MsgpackError> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    fn fail<T>(&self, message: &str) -> Result<T, MsgpackError> {
+        Err(MsgpackError {
+            position: self.pos,
+            message: message.to_string(),
+        })
+    }
+}
+
+// Standard base64 with padding.
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interop::LossKind;
+    use crate::json;
+    use crate::parser::parse_json;
+
+    fn hex(value: &JsonValue) -> String {
+        value
+            .to_msgpack()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    #[test]
+    fn test_smallest_encodings() {
+        assert_eq!(hex(&json!(null)), "c0");
+        assert_eq!(hex(&json!([true, false])), "92c3c2");
+        assert_eq!(hex(&json!(127)), "7f");
+        assert_eq!(hex(&json!(128)), "cc80");
+        assert_eq!(hex(&json!(65536)), "ce00010000");
+        assert_eq!(hex(&json!(-32)), "e0");
+        assert_eq!(hex(&json!(-33)), "d0df");
+        assert_eq!(hex(&json!(-129)), "d1ff7f");
+        assert_eq!(hex(&json!(1.5)), "cb3ff8000000000000");
+        assert_eq!(hex(&json!({"a": "bc"})), "81a161a26263");
+        assert_eq!(hex(&json!("x".repeat(32)))[..4], *"d920");
+        assert_eq!(hex(&JsonValue::Array(vec![json!(0); 16]))[..6], *"dc0010");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let value = json!({
+            "ints": [0, -1, 255, -32768, 4294967296u64, u64::MAX, i64::MIN],
+            "floats": [0.5, -1e300],
+            "text": "h\u{e9}llo",
+            "long": "y".repeat(300),
+            "nested": {"deep": [[], {}, null]},
+            "many": (0..70000).collect::<Vec<i32>>()
+        });
+        let (back, report) = JsonValue::from_msgpack(&value.to_msgpack()).unwrap();
+        assert_eq!(back, value);
+        assert!(report.is_lossless());
+    }
+
+    #[test]
+    fn test_lossy_reads() {
+        // {1: bin "\x00\xff", "s": str "\xff", "e": fixext1, "s": 2.5f32}
+        let bytes = [
+            0x84, 0x01, 0xc4, 0x02, 0x00, 0xff, 0xa1, b's', 0xa1, 0xff, 0xa1, b'e', 0xd4, 0x05,
+            0x00, 0xa1, b's', 0xca, 0x40, 0x20, 0x00, 0x00,
+        ];
+        let (value, report) = JsonValue::from_msgpack(&bytes).unwrap();
+        assert_eq!(value, json!({"1": "AP8=", "s": 2.5, "e": null}));
+        let losses: Vec<String> = report.losses.iter().map(|l| l.to_string()).collect();
+        assert_eq!(
+            losses,
+            [
+                "\"/1\": approximated (number map key at byte 1 as a string)",
+                "\"/1\": approximated (binary data as base64)",
+                "\"/s\": approximated (invalid UTF-8 replaced with U+FFFD)",
+                "\"/e\": approximated (extension type 5 as null)",
+                "\"/s\": dropped (an earlier value for a repeated key)",
+            ]
+        );
+        assert_eq!(report.of_kind(LossKind::Dropped).count(), 1);
+        assert_eq!(base64(b"abcd"), "YWJjZA==");
+    }
+
+    #[test]
+    fn test_non_finite_floats_read_as_null() {
+        // [float64 NaN, float32 -inf, float64 1.5]
+        let mut bytes = vec![0x93, 0xcb];
+        bytes.extend(f64::NAN.to_be_bytes());
+        bytes.push(0xca);
+        bytes.extend(f32::NEG_INFINITY.to_be_bytes());
+        bytes.push(0xcb);
+        bytes.extend(1.5f64.to_be_bytes());
+        let (value, report) = JsonValue::from_msgpack(&bytes).unwrap();
+        assert_eq!(value, json!([null, null, 1.5]));
+        assert_eq!(parse_json(&value.to_string()).unwrap(), value);
+        let losses: Vec<String> = report.losses.iter().map(|l| l.to_string()).collect();
+        assert_eq!(
+            losses,
+            [
+                "\"/0\": approximated (non-finite float as null)",
+                "\"/1\": approximated (non-finite float as null)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |bytes: &[u8]| JsonValue::from_msgpack(bytes).unwrap_err();
+        assert_eq!(
+            err(&[0x92, 0x01]).to_string(),
+            "Invalid MessagePack at byte 2: unexpected end of input"
+        );
+        assert_eq!(err(&[0xc1]).position, 0);
+        assert_eq!(
+            err(&[0x01, 0x02]).message,
+            "unexpected bytes after the value"
+        );
+        assert_eq!(err(&[0xdd, 0xff, 0xff, 0xff, 0xff]).position, 5);
+        let deep = vec![0x91; DEFAULT_MAX_DEPTH + 1];
+        assert!(err(&deep).message.starts_with("nested deeper"));
+    }
+}
@@ -0,0 +1,196 @@
+//! Namespacing object keys with a prefix.
+//!
+//! Several configuration documents can share one flat namespace when each
+//! one's keys carry a prefix of its own: `{"port": 80}` from the `app`
+//! document becomes `{"app.port": 80}`.
+//! [`JsonValue::prefix_keys`](crate::value::JsonValue::prefix_keys) adds a
+//! prefix, [`combine`](crate::namespace::combine) gathers prefixed
+//! documents into one object, and
+//! [`JsonValue::strip_key_prefix`](crate::value::JsonValue::strip_key_prefix)
+//! takes a prefix off again. Keys are renamed in every object at any
+//! depth, and a rename that would make two members of one object share a
+//! key fails with a [`NamespaceError`](crate::namespace::NamespaceError)
+//! instead of silently dropping one of them.
+
+use std::collections::HashSet;
+use std::error;
+use std::fmt;
+
+use crate::map::JsonMap;
+use crate::path::{JsonPath, Segment};
+use crate::value::JsonValue;
+
+/// A renamed key that another member of the same object already has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceError {
+    /// The key after renaming.
+    pub key: String,
+    /// The path, before renaming, of the member whose new key was taken.
+    pub path: JsonPath,
+}
+
+impl fmt::Display for NamespaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Key {:?} for {:?} is already taken",
+            self.key,
+            self.path.to_pointer()
+        )
+    }
+}
+
+impl error::Error for NamespaceError {}
+
+/// Gathers objects into one, prefixing each one's keys at every depth
+/// with the prefix it is paired with.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::json;
+/// use rust_json_parser::namespace::combine;
+/// use rust_json_parser::value::JsonValue;
+///
+/// let app = json!({"port": 8080, "tls": {"cert": "a.pem"}});
+/// let db = json!({"port": 5432});
+/// let combined = combine([
+///     ("app.", app.as_object().unwrap().clone()),
+///     ("db.", db.as_object().unwrap().clone()),
+/// ])
+/// .unwrap();
+/// assert_eq!(
+///     JsonValue::from(combined).to_string(),
+///     r#"{"app.port":8080,"app.tls":{"app.cert":"a.pem"},"db.port":5432}"#
+/// );
+///
+/// // Prefixes that overlap can collide.
+/// let err = combine([
+///     ("a.", json!({"b.c": 1}).as_object().unwrap().clone()),
+///     ("a.b.", json!({"c": 2}).as_object().unwrap().clone()),
+/// ])
+/// .unwrap_err();
+/// assert_eq!(err.to_string(), r#"Key "a.b.c" for "/c" is already taken"#);
+/// ```
+///
+/// # Errors
+///
+/// Returns [`NamespaceError`] if two documents produce the same key. Its
+/// path is within the later document.
+pub fn combine<'a>(
+    documents: impl IntoIterator<Item = (&'a str, JsonMap)>,
+) -> Result<JsonMap, NamespaceError> {
+    let mut combined = JsonMap::new();
+    for (prefix, document) in documents {
+        for (key, mut value) in document {
+            let prefixed = format!("{}{}", prefix, key);
+            if combined.contains_key(&prefixed) {
+                let mut path = JsonPath::root();
+                path.push(Segment::Key(key));
+                return Err(NamespaceError {
+                    key: prefixed,
+                    path,
+                });
+            }
+            prefix_keys(&mut value, prefix);
+            combined.insert(prefixed, value);
+        }
+    }
+    Ok(combined)
+}
+
+pub(crate) fn prefix_keys(value: &mut JsonValue, prefix: &str) {
+    rename_keys(value, &|key| format!("{}{}", prefix, key));
+}
+
+pub(crate) fn strip_key_prefix(value: &mut JsonValue, prefix: &str) -> Result<(), NamespaceError> {
+    let strip = |key: &str| key.strip_prefix(prefix).unwrap_or(key).to_string();
+    // Check everything first so a failure leaves the value untouched.
+    check_renames(value, &strip, &mut JsonPath::root())?;
+    rename_keys(value, &strip);
+    Ok(())
+}
+
+fn rename_keys(value: &mut JsonValue, rename: &dyn Fn(&str) -> String) {
+    match value {
+        JsonValue::Object(map) => {
+            let members: Vec<(String, JsonValue)> = map.drain().collect();
+            for (key, mut value) in members {
+                rename_keys(&mut value, rename);
+                map.insert(rename(&key), value);
+            }
+        }
+        JsonValue::Array(elements) => {
+            for element in elements {
+                rename_keys(element, rename);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_renames(
+    value: &JsonValue,
+    rename: &dyn Fn(&str) -> String,
+    path: &mut JsonPath,
+) -> Result<(), NamespaceError> {
+    match value {
+        JsonValue::Object(map) => {
+            let mut seen = HashSet::with_capacity(map.len());
+            for (key, value) in map.iter() {
+                let renamed = rename(key);
+                path.push(Segment::Key(key.clone()));
+                if !seen.insert(renamed.clone()) {
+                    return Err(NamespaceError {
+                        key: renamed,
+                        path: path.clone(),
+                    });
+                }
+                check_renames(value, rename, path)?;
+                path.pop();
+            }
+        }
+        JsonValue::Array(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                path.push(Segment::Index(i));
+                check_renames(element, rename, path)?;
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+
+    #[test]
+    fn test_prefix_and_strip_round_trip() {
+        let original = json!({"port": 80, "hosts": [{"name": "a"}], "tls": {}});
+        let mut value = original.clone();
+        value.prefix_keys("app.");
+        assert_eq!(
+            value.to_string(),
+            r#"{"app.port":80,"app.hosts":[{"app.name":"a"}],"app.tls":{}}"#
+        );
+        value.strip_key_prefix("app.").unwrap();
+        assert_eq!(value.to_string(), original.to_string());
+
+        // Keys without the prefix are kept as they are.
+        let mut mixed = json!({"app.port": 80, "debug": true});
+        mixed.strip_key_prefix("app.").unwrap();
+        assert_eq!(mixed, json!({"port": 80, "debug": true}));
+    }
+
+    #[test]
+    fn test_strip_collision_leaves_value_untouched() {
+        let mut value = json!({"a": 1, "nested": [{"x.b": 2, "b": 3}]});
+        let before = value.clone();
+        let err = value.strip_key_prefix("x.").unwrap_err();
+        assert_eq!(err.key, "b");
+        assert_eq!(err.path.to_pointer(), "/nested/0/b");
+        assert_eq!(value.to_string(), before.to_string());
+    }
+}
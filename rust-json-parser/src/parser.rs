@@ -3,17 +3,34 @@
 //! This module implements the second phase of the two-phase JSON parsing
 //! pipeline. It takes the token stream produced by
 //! [`crate::tokenizer::Tokenizer`] and builds a
-//! [`JsonValue`](crate::value::JsonValue) tree using recursive descent.
+//! [`JsonValue`](crate::value::JsonValue) tree using recursive descent,
+//! pulling each token from the tokenizer as it needs it rather than
+//! tokenizing the whole input first.
 //!
 //! The main entry point is the [`parse_json`](crate::parser::parse_json)
 //! convenience function, which tokenizes and parses in a single call.
 //! For more control, create a [`JsonParser`](crate::parser::JsonParser)
 //! directly.
 
-use std::collections::HashMap;
-
-use crate::error::JsonError;
-use crate::tokenizer::{Token, Tokenizer};
+use std::cell::Cell;
+use std::fmt;
+use std::io::{self, Read};
+use std::ops::Range;
+use std::sync::{PoisonError, RwLock};
+#[cfg(feature = "profiling")]
+use std::time::Instant;
+
+use crate::cache::SubtreeCache;
+use crate::error::{JsonError, JsonErrorKind, line_column};
+use crate::limits::{Limit, PathLimits};
+use crate::map::JsonMap;
+use crate::path::{JsonPath, Segment};
+#[cfg(feature = "profiling")]
+use crate::profile::{self, ParseProfile};
+use crate::reader::ReadTokenizer;
+use crate::recovery::{self, Recovered};
+use crate::stream::{Event, JsonStreamParser};
+use crate::tokenizer::{DigitSeparators, Token, Tokenizer, UnicodeWhitespace, describe_whitespace};
 use crate::value::JsonValue;
 
 /// Parses a JSON string into a [`JsonValue`].
@@ -37,6 +54,186 @@ pub fn parse_json(input: &str) -> Result<JsonValue, JsonError> {
     JsonParser::new().parse(input)
 }
 
+/// Parses JSON from raw bytes, such as a file's contents or a network
+/// buffer.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::error::JsonError;
+/// use rust_json_parser::parser::parse_bytes;
+///
+/// assert!(parse_bytes(br#"{"ok": true}"#).is_ok());
+/// assert!(matches!(
+///     parse_bytes(b"[\"\xff\"]"),
+///     Err(JsonError::InvalidUtf8 { byte_offset: 2, .. })
+/// ));
+/// ```
+///
+/// # Errors
+///
+/// Returns [`JsonError::InvalidUtf8`] if the input is not valid UTF-8, or
+/// another [`JsonError`] if it is not valid JSON.
+pub fn parse_bytes(input: &[u8]) -> Result<JsonValue, JsonError> {
+    JsonParser::new().parse_bytes(input)
+}
+
+/// Parses a JSON document read from `reader`, such as a file or socket.
+///
+/// The input is read in chunks through a
+/// [`JsonStreamParser`] and the tree is built from its events, so the
+/// document text is never held in memory as a whole.
+///
+/// Like [`JsonParser::new`], parsing starts from
+/// [`ParserOptions::current`], of which the reader honors raw numbers,
+/// lossy UTF-8, the nesting depth, and the string and number length
+/// limits. The syntax extensions are not supported, so the input must be
+/// RFC 8259 JSON, and duplicate keys keep the last value.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::ErrorKind;
+///
+/// use rust_json_parser::parser::parse_from_reader;
+///
+/// let value = parse_from_reader(&br#"{"ids": [1, 2], "meta": {"ok": true}}"#[..])?;
+/// assert_eq!(value.to_string(), r#"{"ids":[1,2],"meta":{"ok":true}}"#);
+///
+/// let err = parse_from_reader(&b"[1 2]"[..]).unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::InvalidData);
+/// let err = parse_from_reader(&b"[1,"[..]).unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if reading fails, or an error wrapping the
+/// [`JsonError`] if the input is not valid JSON or exceeds a limit: of
+/// kind [`io::ErrorKind::UnexpectedEof`] when the input ends too early and
+/// [`io::ErrorKind::InvalidData`] otherwise.
+pub fn parse_from_reader(reader: impl Read) -> io::Result<JsonValue> {
+    let options = ParserOptions::current();
+    let tokenizer = ReadTokenizer::new(reader)
+        .preserve_raw_numbers(options.preserve_raw_numbers)
+        .lossy_utf8(options.lossy_utf8)
+        .max_string_len(options.max_string_len)
+        .max_number_len(options.max_number_len);
+    let events = JsonStreamParser::from_tokenizer(tokenizer).max_depth(options.max_depth);
+    // Containers still open, each with the key it will be stored under in
+    // its parent.
+    let mut stack: Vec<(JsonValue, Option<String>)> = Vec::new();
+    let mut key = None;
+    let mut root = None;
+    for event in events {
+        let value = match event? {
+            Event::StartArray => {
+                stack.push((JsonValue::Array(Vec::new()), key.take()));
+                continue;
+            }
+            Event::StartObject => {
+                stack.push((JsonValue::Object(JsonMap::new()), key.take()));
+                continue;
+            }
+            Event::Key(k) => {
+                key = Some(k);
+                continue;
+            }
+            Event::Value(value) => value,
+            Event::EndArray | Event::EndObject => {
+                let (container, parent_key) = stack.pop().expect("container was opened");
+                key = parent_key;
+                container
+            }
+        };
+        match stack.last_mut() {
+            Some((JsonValue::Array(elements), _)) => elements.push(value),
+            Some((JsonValue::Object(map), _)) => {
+                map.insert(key.take().expect("key precedes value"), value);
+            }
+            _ => root = Some(value),
+        }
+    }
+    root.ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "no JSON value"))
+}
+
+/// Parses many independent documents, reusing one parser's buffers.
+///
+/// Results are returned in input order, one per document, so a malformed
+/// document does not stop the rest of the batch. Object keys are owned
+/// `String`s in [`JsonValue`], so each document still allocates its own
+/// keys; the saving comes from reusing the tokenizer's input copy.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::parse_batch;
+///
+/// let results = parse_batch([r#"{"id": 1}"#, "[", "true"]);
+/// assert_eq!(results.len(), 3);
+/// assert!(results[0].is_ok());
+/// assert!(results[1].is_err());
+/// ```
+pub fn parse_batch<'a, I>(inputs: I) -> Vec<Result<JsonValue, JsonError>>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut parser = JsonParser::new();
+    inputs
+        .into_iter()
+        .map(|input| parser.parse(input))
+        .collect()
+}
+
+/// Parses many independent documents across `threads` worker threads.
+///
+/// The inputs are split into contiguous chunks and each worker reuses its
+/// own parser for its chunk. Results are returned in input order. A
+/// `threads` value of `0` or `1` parses on the calling thread. Workers
+/// parse with the caller's [`ParserOptions::current`], so the thread count
+/// never changes the results.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::parse_batch_threaded;
+///
+/// let inputs: Vec<String> = (0..100).map(|i| format!(r#"{{"n": {}}}"#, i)).collect();
+/// let results = parse_batch_threaded(inputs.iter().map(String::as_str), 4);
+/// assert_eq!(results.len(), 100);
+/// assert!(results.iter().all(Result::is_ok));
+/// ```
+pub fn parse_batch_threaded<'a, I>(inputs: I, threads: usize) -> Vec<Result<JsonValue, JsonError>>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let inputs: Vec<&str> = inputs.into_iter().collect();
+    if threads <= 1 || inputs.len() <= 1 {
+        return parse_batch(inputs);
+    }
+    let options = ParserOptions::current();
+    let chunk_size = inputs.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = inputs
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut parser = JsonParser::with_options(options);
+                    chunk
+                        .iter()
+                        .map(|input| parser.parse(input))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("parser thread panicked"))
+            .collect()
+    })
+}
+
 /// A recursive descent parser that converts a token stream into a JSON
 /// value tree.
 ///
@@ -54,9 +251,321 @@ pub fn parse_json(input: &str) -> Result<JsonValue, JsonError> {
 /// # Ok::<(), rust_json_parser::error::JsonError>(())
 /// ```
 pub struct JsonParser {
+    // Pre-built tokens, reversed so pop() yields them front to back.
     tokens: Vec<Token>,
     tokenizer: Tokenizer,
     total_count: usize,
+    // Whether tokens are pulled from the tokenizer rather than `tokens`.
+    streaming: bool,
+    // The next token, and its byte offset when streaming or its index
+    // among the pre-built tokens otherwise.
+    next: Option<Token>,
+    next_position: usize,
+    // A tokenizer error, which ends the token stream when streaming.
+    lex_error: Option<JsonError>,
+    // Values claimed by `literal_extension`, for the next token and the
+    // one just consumed. Either token is a `Token::Null` placeholder.
+    next_literal: Option<JsonValue>,
+    literal: Option<JsonValue>,
+    warnings: Vec<Warning>,
+    empty_document: EmptyDocument,
+    allow_trailing_commas: bool,
+    duplicate_keys: DuplicateKeys,
+    lossy_utf8: bool,
+    max_depth: usize,
+    // Arrays and objects currently open while building.
+    depth: usize,
+    subtree_cache: Option<Box<dyn SubtreeCache + Send>>,
+    literal_extension: Option<Box<dyn LiteralExtension + Send>>,
+    path_limits: Option<PathLimits>,
+    // The path of the value being built, tracked only with path limits.
+    path: JsonPath,
+    #[cfg(feature = "profiling")]
+    profile: ParseProfile,
+}
+
+/// The settings a [`JsonParser`] was configured with.
+///
+/// Returned by [`JsonParser::options`] so the configuration can be
+/// recorded alongside a parse result, e.g. in a
+/// [`ParsedDocument`](crate::document::ParsedDocument), and accepted by
+/// [`JsonParser::with_options`]. The default is strict RFC 8259 parsing.
+///
+/// A program that wants different settings everywhere, e.g. a config
+/// loader that always accepts comments, can install them as the process
+/// or thread default with [`set_process_default`](Self::set_process_default),
+/// [`set_thread_default`](Self::set_thread_default), or
+/// [`scoped`](Self::scoped). [`JsonParser::new`], and with it
+/// [`parse_json`] and the other convenience functions, starts from
+/// [`current`](Self::current).
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::{JsonParser, ParserOptions};
+///
+/// let config = r#"{
+///     // Listen on all interfaces.
+///     'host': "0.0.0.0",
+///     "ports": [80, 443,],
+/// }"#;
+/// let mut parser = JsonParser::with_options(ParserOptions::lenient());
+/// let value = parser.parse(config)?;
+/// assert_eq!(value.get("host").and_then(|v| v.as_str()), Some("0.0.0.0"));
+/// assert_eq!(parser.options(), ParserOptions::lenient());
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Whether numbers keep their source text. See
+    /// [`JsonParser::preserve_raw_numbers`].
+    pub preserve_raw_numbers: bool,
+    /// What empty or whitespace-only input parses to. See
+    /// [`JsonParser::empty_document`].
+    pub empty_document: EmptyDocument,
+    /// Whether `//` and `/* */` comments are skipped. See
+    /// [`JsonParser::allow_comments`].
+    pub allow_comments: bool,
+    /// Whether a comma may follow the last array element or object
+    /// member. See [`JsonParser::allow_trailing_commas`].
+    pub allow_trailing_commas: bool,
+    /// Whether strings may be delimited by single quotes. See
+    /// [`JsonParser::allow_single_quotes`].
+    pub allow_single_quotes: bool,
+    /// Which value a repeated object key keeps. See
+    /// [`JsonParser::duplicate_keys`].
+    pub duplicate_keys: DuplicateKeys,
+    /// Whether invalid UTF-8 in byte input is replaced instead of
+    /// rejected. See [`JsonParser::lossy_utf8`].
+    pub lossy_utf8: bool,
+    /// How deeply arrays and objects may nest. See
+    /// [`JsonParser::max_depth`].
+    pub max_depth: usize,
+    /// The longest string token accepted, in bytes. See
+    /// [`JsonParser::max_string_len`].
+    pub max_string_len: usize,
+    /// The longest number token accepted, in bytes. See
+    /// [`JsonParser::max_number_len`].
+    pub max_number_len: usize,
+    /// Which separators may appear between the digits of a number. See
+    /// [`JsonParser::digit_separators`].
+    pub digit_separators: DigitSeparators,
+    /// What happens to Unicode whitespace between tokens. See
+    /// [`JsonParser::unicode_whitespace`].
+    pub unicode_whitespace: UnicodeWhitespace,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            preserve_raw_numbers: false,
+            empty_document: EmptyDocument::default(),
+            allow_comments: false,
+            allow_trailing_commas: false,
+            allow_single_quotes: false,
+            duplicate_keys: DuplicateKeys::default(),
+            lossy_utf8: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_string_len: usize::MAX,
+            max_number_len: usize::MAX,
+            digit_separators: DigitSeparators::None,
+            unicode_whitespace: UnicodeWhitespace::Error,
+        }
+    }
+}
+
+/// The nesting limit of a new [`JsonParser`]. Deep enough for any
+/// realistic document, shallow enough to stay well within a thread's
+/// stack.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+static PROCESS_DEFAULT: RwLock<Option<ParserOptions>> = RwLock::new(None);
+
+thread_local! {
+    static THREAD_DEFAULT: Cell<Option<ParserOptions>> = const { Cell::new(None) };
+}
+
+impl ParserOptions {
+    /// Options for JSONC-style files: comments, trailing commas, and
+    /// single-quoted strings are all accepted.
+    pub fn lenient() -> Self {
+        Self {
+            allow_comments: true,
+            allow_trailing_commas: true,
+            allow_single_quotes: true,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the options a [`JsonParser::new`] parser starts with: the
+    /// calling thread's default if one is set, else the process default
+    /// if one is set, else [`ParserOptions::default`].
+    pub fn current() -> Self {
+        THREAD_DEFAULT
+            .get()
+            .or_else(|| {
+                *PROCESS_DEFAULT
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Sets the default for every thread that has no thread default of
+    /// its own, returning the previous one. `None` restores
+    /// [`ParserOptions::default`].
+    ///
+    /// Meant to be called once at startup, by the binary rather than by a
+    /// library, which cannot know what else in the process parses JSON.
+    pub fn set_process_default(options: Option<Self>) -> Option<Self> {
+        let mut default = PROCESS_DEFAULT
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        std::mem::replace(&mut *default, options)
+    }
+
+    /// Sets the default for the calling thread, overriding the process
+    /// default, and returns the previous one. `None` falls back to the
+    /// process default again.
+    pub fn set_thread_default(options: Option<Self>) -> Option<Self> {
+        THREAD_DEFAULT.replace(options)
+    }
+
+    /// Runs `f` with these options as the calling thread's default,
+    /// restoring the previous default afterwards, even if `f` panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::{ParserOptions, parse_json};
+    ///
+    /// let load = || parse_json("[1, 2,] // generated");
+    /// assert!(load().is_err());
+    /// let value = ParserOptions::lenient().scoped(load)?;
+    /// assert_eq!(value.to_string(), "[1,2]");
+    /// assert!(load().is_err());
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn scoped<R>(self, f: impl FnOnce() -> R) -> R {
+        struct Restore(Option<ParserOptions>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                THREAD_DEFAULT.set(self.0);
+            }
+        }
+
+        let _restore = Restore(Self::set_thread_default(Some(self)));
+        f()
+    }
+}
+
+/// How a [`JsonParser`] treats input that contains no value at all.
+///
+/// Strict JSON requires exactly one value, so the default is an error.
+/// Configuration loaders often want an empty file to mean "nothing
+/// configured" instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmptyDocument {
+    /// Empty input is an [`UnexpectedEndOfInput`](JsonError::UnexpectedEndOfInput)
+    /// error.
+    #[default]
+    Error,
+    /// Empty input parses to `null`.
+    Null,
+    /// Empty input parses to `{}`.
+    EmptyObject,
+    /// Empty input parses to `[]`.
+    EmptyArray,
+}
+
+impl EmptyDocument {
+    /// Returns the value empty input parses to, or `None` for
+    /// [`Error`](EmptyDocument::Error).
+    pub fn value(self) -> Option<JsonValue> {
+        match self {
+            EmptyDocument::Error => None,
+            EmptyDocument::Null => Some(JsonValue::Null),
+            EmptyDocument::EmptyObject => Some(JsonValue::Object(JsonMap::new())),
+            EmptyDocument::EmptyArray => Some(JsonValue::Array(Vec::new())),
+        }
+    }
+}
+
+/// What a [`JsonParser`] does when an object repeats a key.
+///
+/// RFC 8259 leaves the behavior open, and parsers that disagree about
+/// which value wins can be played against each other, e.g. when one
+/// service validates a document and another acts on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Keep the first value and ignore later ones. Each repeat is
+    /// reported as a [`Warning`].
+    FirstWins,
+    /// Keep the last value, at the key's first position. Each repeat is
+    /// reported as a [`Warning`].
+    #[default]
+    LastWins,
+    /// Fail with [`JsonError::DuplicateKey`].
+    Error,
+}
+
+/// A non-fatal problem noticed while parsing valid JSON.
+///
+/// Duplicate object keys are reported as warnings unless the parser is
+/// set to reject them: the document parses, and the
+/// [`DuplicateKeys`] policy decides which value is kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// A description of the problem.
+    pub message: String,
+    /// Byte offset in the input the warning refers to. When parsing
+    /// pre-built tokens this is a token index instead.
+    pub position: usize,
+    /// 1-based line of `position`, or 0 if the source text was not
+    /// available.
+    pub line: usize,
+    /// 1-based column of `position` in characters, or 0 if the source text
+    /// was not available.
+    pub column: usize,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{} at position {}", self.message, self.position)
+        } else {
+            write!(
+                f,
+                "{} at line {}, column {}",
+                self.message, self.line, self.column
+            )
+        }
+    }
+}
+
+/// Claims text the tokenizer does not recognize, for JSON dialects with
+/// extra literals such as `NaN`, `@date 2024-01-01`, or bare enum names.
+///
+/// Installed with [`JsonParser::literal_extension`], it is offered the
+/// input wherever a value starts with something that is not JSON. Closures
+/// taking the rest of the input are extensions.
+pub trait LiteralExtension {
+    /// Parses a literal at the start of `rest`, which runs from the
+    /// unrecognized text to the end of the input. Returns the value and
+    /// the number of bytes it spans, or `None` to leave the text an error.
+    /// A length of zero, past the end of `rest`, or inside a character is
+    /// treated as `None`.
+    fn parse_literal(&mut self, rest: &str) -> Option<(JsonValue, usize)>;
+}
+
+impl<F> LiteralExtension for F
+where
+    F: FnMut(&str) -> Option<(JsonValue, usize)>,
+{
+    fn parse_literal(&mut self, rest: &str) -> Option<(JsonValue, usize)> {
+        self(rest)
+    }
 }
 
 impl Default for JsonParser {
@@ -65,10 +574,17 @@ impl Default for JsonParser {
     }
 }
 
+impl FromIterator<Token> for JsonParser {
+    fn from_iter<I: IntoIterator<Item = Token>>(iter: I) -> Self {
+        Self::from_tokens(iter.into_iter().collect())
+    }
+}
+
 impl JsonParser {
-    /// Creates a new parser with empty internal buffers.
+    /// Creates a new parser with empty internal buffers, configured with
+    /// [`ParserOptions::current`].
     ///
-    /// The parser reuses its token buffer across multiple calls to
+    /// The parser reuses its buffers across multiple calls to
     /// [`parse`](Self::parse), avoiding repeated allocation.
     ///
     /// # Examples
@@ -79,25 +595,554 @@ impl JsonParser {
     ///
     /// let mut parser = JsonParser::new();
     /// let v1 = parser.parse("[1, 2, 3]")?;
-    /// assert_eq!(v1.get_index(0), Some(&JsonValue::Number(1.0)));
+    /// assert_eq!(v1.get_index(0), Some(&JsonValue::Number(1.0.into())));
     ///
     /// let v2 = parser.parse("true")?;
     /// assert_eq!(v2, JsonValue::Boolean(true));
     /// # Ok::<(), rust_json_parser::error::JsonError>(())
     /// ```
     pub fn new() -> Self {
+        Self::with_options(ParserOptions::current())
+    }
+
+    // A parser with the built-in defaults, ignoring any installed ones.
+    fn unconfigured() -> Self {
         Self {
             tokens: Vec::new(),
             tokenizer: Tokenizer::new(""),
             total_count: 0,
+            streaming: false,
+            next: None,
+            next_position: 0,
+            lex_error: None,
+            next_literal: None,
+            literal: None,
+            warnings: Vec::new(),
+            empty_document: EmptyDocument::Error,
+            allow_trailing_commas: false,
+            duplicate_keys: DuplicateKeys::LastWins,
+            lossy_utf8: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            depth: 0,
+            subtree_cache: None,
+            literal_extension: None,
+            path_limits: None,
+            path: JsonPath::root(),
+            #[cfg(feature = "profiling")]
+            profile: ParseProfile::default(),
+        }
+    }
+
+    /// Creates a parser configured with `options`.
+    ///
+    /// Equivalent to calling each builder method on [`new`](Self::new);
+    /// see [`ParserOptions`] for an example.
+    pub fn with_options(options: ParserOptions) -> Self {
+        Self::unconfigured()
+            .preserve_raw_numbers(options.preserve_raw_numbers)
+            .empty_document(options.empty_document)
+            .allow_comments(options.allow_comments)
+            .allow_trailing_commas(options.allow_trailing_commas)
+            .allow_single_quotes(options.allow_single_quotes)
+            .duplicate_keys(options.duplicate_keys)
+            .lossy_utf8(options.lossy_utf8)
+            .max_depth(options.max_depth)
+            .max_string_len(options.max_string_len)
+            .max_number_len(options.max_number_len)
+            .digit_separators(options.digit_separators)
+            .unicode_whitespace(options.unicode_whitespace)
+    }
+
+    /// Creates a parser over an already tokenized input.
+    ///
+    /// Tools that have run the [`Tokenizer`] themselves (for highlighting,
+    /// linting, or rewriting tokens) can hand the tokens over and call
+    /// [`parse_tokens`](Self::parse_tokens) without tokenizing the input a
+    /// second time. A parser can also be collected from a token iterator.
+    ///
+    /// Error positions refer to token indices within `tokens`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::JsonParser;
+    /// use rust_json_parser::tokenizer::{Token, Tokenizer};
+    ///
+    /// let tokens = Tokenizer::new(r#"{"a": [1, 2]}"#).tokenize()?;
+    /// let value = JsonParser::from_tokens(tokens).parse_tokens()?;
+    /// assert!(value.get("a").is_some());
+    ///
+    /// let mut parser: JsonParser = [Token::LeftBracket, Token::Null, Token::RightBracket]
+    ///     .into_iter()
+    ///     .collect();
+    /// assert_eq!(parser.parse_tokens()?.to_string(), "[null]");
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn from_tokens(mut tokens: Vec<Token>) -> Self {
+        let total_count = tokens.len();
+        tokens.reverse();
+        Self {
+            tokens,
+            total_count,
+            ..Self::new()
         }
     }
 
+    /// Releases the memory held by the internal buffers.
+    ///
+    /// Buffers grow to fit the largest document parsed so far; call this
+    /// after an unusually large input to return that memory.
+    pub fn shrink_buffers(&mut self) {
+        let subtree_cache = self.subtree_cache.take();
+        let literal_extension = self.literal_extension.take();
+        let path_limits = self.path_limits.take();
+        *self = Self::with_options(self.options());
+        self.subtree_cache = subtree_cache;
+        self.literal_extension = literal_extension;
+        self.path_limits = path_limits;
+    }
+
+    /// Keeps the exact source text of every number so that `Display`
+    /// reproduces the original digits. See
+    /// [`JsonNumber`](crate::value::JsonNumber).
+    ///
+    /// Off by default: the raw text costs an extra allocation per number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::JsonParser;
+    ///
+    /// let input = r#"{"price":10.00}"#;
+    /// let value = JsonParser::new().preserve_raw_numbers(true).parse(input)?;
+    /// assert_eq!(value.to_string(), input);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn preserve_raw_numbers(mut self, preserve: bool) -> Self {
+        self.tokenizer.preserve_raw_numbers = preserve;
+        self
+    }
+
+    /// Sets what empty or whitespace-only input parses to.
+    ///
+    /// By default such input is an error, as RFC 8259 requires.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::{EmptyDocument, JsonParser};
+    /// use rust_json_parser::value::JsonValue;
+    ///
+    /// assert!(JsonParser::new().parse(" \n").is_err());
+    ///
+    /// let mut parser = JsonParser::new().empty_document(EmptyDocument::EmptyObject);
+    /// let overrides = parser.parse(" \n")?;
+    /// assert_eq!(overrides.as_object().map(|m| m.len()), Some(0));
+    /// assert_eq!(parser.parse("null")?, JsonValue::Null);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn empty_document(mut self, policy: EmptyDocument) -> Self {
+        self.empty_document = policy;
+        self
+    }
+
+    /// Skips `//` line comments and `/* */` block comments. Off by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::JsonParser;
+    ///
+    /// let input = "[1, /* two */ 2] // done";
+    /// assert!(JsonParser::new().parse(input).is_err());
+    /// let value = JsonParser::new().allow_comments(true).parse(input)?;
+    /// assert_eq!(value.to_string(), "[1,2]");
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.tokenizer.allow_comments = allow;
+        self
+    }
+
+    /// Accepts a comma after the last element of an array or the last
+    /// member of an object. Off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::JsonParser;
+    ///
+    /// let input = r#"{"a": [1, 2,],}"#;
+    /// assert!(JsonParser::new().parse(input).is_err());
+    /// let value = JsonParser::new().allow_trailing_commas(true).parse(input)?;
+    /// assert_eq!(value.to_string(), r#"{"a":[1,2]}"#);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn allow_trailing_commas(mut self, allow: bool) -> Self {
+        self.allow_trailing_commas = allow;
+        self
+    }
+
+    /// Accepts strings delimited by single quotes, in keys and values.
+    /// Off by default. See
+    /// [`Tokenizer::allow_single_quotes`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::JsonParser;
+    ///
+    /// let value = JsonParser::new().allow_single_quotes(true).parse("{'a': 'b'}")?;
+    /// assert_eq!(value.get("a").and_then(|v| v.as_str()), Some("b"));
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn allow_single_quotes(mut self, allow: bool) -> Self {
+        self.tokenizer.allow_single_quotes = allow;
+        self
+    }
+
+    /// Accepts numbers written with digit separators, such as `1_000_000`
+    /// or `1,000`, and reads them without the separators. Each number read
+    /// this way is reported as a [`Warning`], so sloppy feeds can be
+    /// ingested without losing track of what was changed. Off by default;
+    /// see [`DigitSeparators`] for what each setting allows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::JsonParser;
+    /// use rust_json_parser::tokenizer::DigitSeparators;
+    ///
+    /// let mut parser = JsonParser::new().digit_separators(DigitSeparators::Commas);
+    /// let value = parser.parse(r#"{"population": 8,336,817, "area": 783.8, "id": 4_000}"#)?;
+    /// assert_eq!(value.to_string(), r#"{"population":8336817,"area":783.8,"id":4000}"#);
+    /// let warnings: Vec<String> = parser.warnings().iter().map(|w| w.to_string()).collect();
+    /// assert_eq!(
+    ///     warnings,
+    ///     [
+    ///         r#"number "8,336,817" read as 8336817 at line 1, column 16"#,
+    ///         r#"number "4_000" read as 4000 at line 1, column 48"#,
+    ///     ]
+    /// );
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn digit_separators(mut self, separators: DigitSeparators) -> Self {
+        self.tokenizer.digit_separators = separators;
+        self
+    }
+
+    /// Chooses what happens to Unicode whitespace between tokens, such as
+    /// the no-break spaces in JSON pasted from a word processor. Under
+    /// [`UnicodeWhitespace::Warn`] each run of it is skipped and reported
+    /// as a [`Warning`]. Rejected by default, with an error naming the
+    /// character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::JsonParser;
+    /// use rust_json_parser::tokenizer::UnicodeWhitespace;
+    ///
+    /// let pasted = "{\"a\":\u{a0}1,\n\u{a0}\u{a0}\"b\": 2}";
+    /// let err = JsonParser::new().parse(pasted).unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "Unexpected token at line 1, column 6 (position 5): expected valid JSON token, found no-break space (U+00A0)"
+    /// );
+    ///
+    /// let mut parser = JsonParser::new().unicode_whitespace(UnicodeWhitespace::Warn);
+    /// assert_eq!(parser.parse(pasted)?.to_string(), r#"{"a":1,"b":2}"#);
+    /// let warnings: Vec<String> = parser.warnings().iter().map(|w| w.to_string()).collect();
+    /// assert_eq!(
+    ///     warnings,
+    ///     [
+    ///         "no-break space (U+00A0) read as whitespace at line 1, column 6",
+    ///         "no-break space (U+00A0) read as whitespace at line 2, column 1",
+    ///     ]
+    /// );
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn unicode_whitespace(mut self, policy: UnicodeWhitespace) -> Self {
+        self.tokenizer.unicode_whitespace = policy;
+        self
+    }
+
+    /// Sets which value a repeated object key keeps, or makes repeats an
+    /// error. Defaults to [`DuplicateKeys::LastWins`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::error::JsonError;
+    /// use rust_json_parser::parser::{DuplicateKeys, JsonParser};
+    ///
+    /// let input = r#"{"role": "user", "role": "admin"}"#;
+    /// let mut parser = JsonParser::new().duplicate_keys(DuplicateKeys::FirstWins);
+    /// let value = parser.parse(input)?;
+    /// assert_eq!(value.get("role").and_then(|v| v.as_str()), Some("user"));
+    /// assert_eq!(parser.warnings().len(), 1);
+    ///
+    /// let mut parser = JsonParser::new().duplicate_keys(DuplicateKeys::Error);
+    /// match parser.parse(input) {
+    ///     Err(JsonError::DuplicateKey { key, position, .. }) => {
+    ///         assert_eq!((key.as_str(), position), ("role", 17));
+    ///     }
+    ///     other => panic!("unexpected result {:?}", other),
+    /// }
+    /// # Ok::<(), JsonError>(())
+    /// ```
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Makes [`parse_bytes`](Self::parse_bytes) replace invalid UTF-8
+    /// sequences with U+FFFD and carry on, instead of failing with
+    /// [`JsonError::InvalidUtf8`]. Off by default.
+    ///
+    /// Error positions then refer to the repaired text, which is longer
+    /// than the input by two bytes per replacement.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::JsonParser;
+    ///
+    /// let mut parser = JsonParser::new().lossy_utf8(true);
+    /// let value = parser.parse_bytes(b"[\"caf\xe9\"]")?;
+    /// assert_eq!(value.get_index(0).and_then(|v| v.as_str()), Some("caf\u{FFFD}"));
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn lossy_utf8(mut self, lossy: bool) -> Self {
+        self.lossy_utf8 = lossy;
+        self
+    }
+
+    /// Sets how many arrays and objects may be nested inside each other,
+    /// so `[[1]]` needs a depth of 2. Deeper input fails with
+    /// [`JsonError::DepthLimitExceeded`] instead of overflowing the stack.
+    /// Defaults to [`DEFAULT_MAX_DEPTH`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::error::JsonError;
+    /// use rust_json_parser::parser::JsonParser;
+    ///
+    /// let mut parser = JsonParser::new().max_depth(2);
+    /// assert!(parser.parse("[[1]]").is_ok());
+    /// assert!(matches!(
+    ///     parser.parse("[[[1]]]"),
+    ///     Err(JsonError::DepthLimitExceeded { max_depth: 2, position: 2, .. })
+    /// ));
+    ///
+    /// let hostile = "[".repeat(1_000_000);
+    /// assert!(JsonParser::new().parse(&hostile).is_err());
+    /// ```
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Rejects string tokens longer than `max` bytes with
+    /// [`JsonError::StringTooLong`], as
+    /// [`Tokenizer::max_string_len`] does. Unlimited by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::error::JsonError;
+    /// use rust_json_parser::parser::JsonParser;
+    ///
+    /// let mut parser = JsonParser::new().max_string_len(8);
+    /// assert!(parser.parse(r#"{"name": "Ada"}"#).is_ok());
+    /// assert!(matches!(
+    ///     parser.parse(r#"{"name": "Ada Lovelace"}"#),
+    ///     Err(JsonError::StringTooLong { max_len: 8, position: 9, .. })
+    /// ));
+    /// ```
+    pub fn max_string_len(mut self, max: usize) -> Self {
+        self.tokenizer.max_string_len = max;
+        self
+    }
+
+    /// Rejects number tokens longer than `max` bytes with
+    /// [`JsonError::NumberTooLong`], as
+    /// [`Tokenizer::max_number_len`] does. Unlimited by default.
+    pub fn max_number_len(mut self, max: usize) -> Self {
+        self.tokenizer.max_number_len = max;
+        self
+    }
+
+    /// Installs a cache of parsed subtrees, consulted with the source text
+    /// of every array and object before it is built. See
+    /// [`cache`](crate::cache).
+    ///
+    /// A cached subtree is used as is: it is not checked against
+    /// [`max_depth`](Self::max_depth) again, and duplicate-key warnings
+    /// inside it are only reported when it is first built. The cache is
+    /// only used by [`parse`](Self::parse) and the methods built on it;
+    /// [`parse_tokens`](Self::parse_tokens) has no source text to key on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use rust_json_parser::cache::{HashCache, SubtreeCache};
+    /// use rust_json_parser::parser::JsonParser;
+    /// use rust_json_parser::value::JsonValue;
+    ///
+    /// // A HashCache that also logs every lookup.
+    /// struct Logged(HashCache, Arc<Mutex<Vec<String>>>);
+    ///
+    /// impl SubtreeCache for Logged {
+    ///     fn get(&mut self, raw: &str) -> Option<JsonValue> {
+    ///         self.1.lock().unwrap().push(raw.to_string());
+    ///         self.0.get(raw)
+    ///     }
+    ///     fn insert(&mut self, raw: &str, value: &JsonValue) {
+    ///         self.0.insert(raw, value);
+    ///     }
+    /// }
+    ///
+    /// let log = Arc::new(Mutex::new(Vec::new()));
+    /// let mut parser = JsonParser::new().subtree_cache(Logged(HashCache::new(), log.clone()));
+    /// parser.parse(r#"[{"a": [1]}, {"a": [1]}]"#)?;
+    /// // The second object is a hit, so its array is never looked up.
+    /// assert_eq!(
+    ///     *log.lock().unwrap(),
+    ///     [r#"[{"a": [1]}, {"a": [1]}]"#, r#"{"a": [1]}"#, "[1]", r#"{"a": [1]}"#]
+    /// );
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn subtree_cache(mut self, cache: impl SubtreeCache + Send + 'static) -> Self {
+        self.subtree_cache = Some(Box::new(cache));
+        self
+    }
+
+    /// Removes and returns the installed subtree cache, if any.
+    pub fn take_subtree_cache(&mut self) -> Option<Box<dyn SubtreeCache + Send>> {
+        self.subtree_cache.take()
+    }
+
+    /// Lets `extension` parse values the tokenizer does not recognize,
+    /// instead of failing with an [`UnexpectedToken`](JsonError::UnexpectedToken)
+    /// or [`InvalidNumber`](JsonError::InvalidNumber) error.
+    ///
+    /// The extension is offered the input wherever an unknown word, a
+    /// character that cannot start a token, or a malformed number begins.
+    /// Claimed literals can only be values, not object keys. Like the
+    /// subtree cache, it is used by [`parse`](Self::parse) and the methods
+    /// built on it, not by [`parse_tokens`](Self::parse_tokens) or
+    /// [`parse_with_recovery`](Self::parse_with_recovery).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::JsonParser;
+    /// use rust_json_parser::value::JsonValue;
+    ///
+    /// // NaN, and `@date` followed by an ISO date, kept as a string.
+    /// let mut parser = JsonParser::new().literal_extension(|rest: &str| {
+    ///     if rest.starts_with("NaN") {
+    ///         return Some((JsonValue::from(f64::NAN), 3));
+    ///     }
+    ///     let date = rest.strip_prefix("@date ")?.get(..10)?;
+    ///     Some((JsonValue::from(date), "@date ".len() + 10))
+    /// });
+    /// let value = parser.parse(r#"{"due": @date 2024-01-01, "score": NaN}"#)?;
+    /// assert_eq!(value["due"].as_str(), Some("2024-01-01"));
+    /// assert!(value["score"].as_f64().unwrap().is_nan());
+    ///
+    /// // Text the extension declines is still an error.
+    /// let err = parser.parse("[Infinity]").unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "Unexpected token at line 1, column 2 (position 1): expected valid JSON token, found I"
+    /// );
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn literal_extension(mut self, extension: impl LiteralExtension + Send + 'static) -> Self {
+        self.literal_extension = Some(Box::new(extension));
+        self
+    }
+
+    /// Enforces `limits` on the values at chosen paths, failing with
+    /// [`PathLimitExceeded`](JsonError::PathLimitExceeded) at the first
+    /// value that breaks one. See [`PathLimits`] for an example.
+    ///
+    /// Limits are checked as values are read: an array fails at its first
+    /// element past the limit, before that element is parsed. They are
+    /// not part of [`options`](Self::options) and are not enforced by
+    /// [`parse_with_recovery`](Self::parse_with_recovery). While limits
+    /// are set, the subtree cache is not used, since a cached subtree may
+    /// have been checked against different paths. Empty limits remove
+    /// any set before.
+    pub fn path_limits(mut self, limits: PathLimits) -> Self {
+        self.path_limits = (!limits.is_empty()).then_some(limits);
+        self
+    }
+
+    /// Returns the options this parser is configured with.
+    pub fn options(&self) -> ParserOptions {
+        ParserOptions {
+            preserve_raw_numbers: self.tokenizer.preserve_raw_numbers,
+            empty_document: self.empty_document,
+            allow_comments: self.tokenizer.allow_comments,
+            allow_trailing_commas: self.allow_trailing_commas,
+            allow_single_quotes: self.tokenizer.allow_single_quotes,
+            duplicate_keys: self.duplicate_keys,
+            lossy_utf8: self.lossy_utf8,
+            max_depth: self.max_depth,
+            max_string_len: self.tokenizer.max_string_len,
+            max_number_len: self.tokenizer.max_number_len,
+            digit_separators: self.tokenizer.digit_separators,
+            unicode_whitespace: self.tokenizer.unicode_whitespace,
+        }
+    }
+
+    /// Returns the warnings collected by the most recent parse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::JsonParser;
+    ///
+    /// let mut parser = JsonParser::new();
+    /// let value = parser.parse(r#"{"a": 1, "a": 2}"#)?;
+    /// assert_eq!(value.get("a").and_then(|v| v.as_i64()), Some(2));
+    /// assert_eq!(parser.warnings()[0].message, r#"duplicate key "a""#);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Takes the warnings collected by the most recent parse, leaving
+    /// the list empty.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Returns the timings and allocation counts of the most recent parse,
+    /// successful or not. See [`ParseProfile`].
+    #[cfg(feature = "profiling")]
+    pub fn profile(&self) -> &ParseProfile {
+        &self.profile
+    }
+
     /// Tokenizes and parses a JSON string into a [`JsonValue`].
     ///
-    /// Each call clears the internal buffer (keeping its heap allocation)
-    /// and re-tokenizes the input, making it efficient to parse many inputs
-    /// in a loop.
+    /// Tokens are scanned one at a time as the tree is built, in a single
+    /// pass over the input; no token buffer is materialized. The copy of
+    /// the input keeps its heap allocation between calls, making it
+    /// efficient to parse many inputs in a loop.
+    ///
+    /// Errors are the same as tokenizing everything first would give: a
+    /// malformed token anywhere in the input is reported in preference to
+    /// a structural error before it.
     ///
     /// # Examples
     ///
@@ -114,18 +1159,167 @@ impl JsonParser {
     ///
     /// Returns [`JsonError`] if the input is not valid JSON.
     pub fn parse(&mut self, input: &str) -> Result<JsonValue, JsonError> {
+        #[cfg(feature = "profiling")]
+        {
+            self.profile = ParseProfile::default();
+        }
+        self.tokens.clear();
+        self.tokenizer.reset(input);
+        self.streaming = true;
+        self.lex_error = None;
+        let result = self.build();
+        // The tokenizer reports problems in the text before the parser
+        // reports problems in the structure, wherever they occur.
+        let lex_error = match &result {
+            Ok(_) => self.lex_error.take(),
+            Err(_) => self.lex_error.take().or_else(|| self.rest_lex_error()),
+        };
+        if !self.tokenizer.normalized.is_empty() {
+            for range in self.tokenizer.normalized.drain(..) {
+                let lexeme = &input[range.clone()];
+                let digits: String = lexeme.chars().filter(|c| !matches!(c, '_' | ',')).collect();
+                self.warnings.push(Warning {
+                    message: format!("number {:?} read as {}", lexeme, digits),
+                    position: range.start,
+                    line: 0,
+                    column: 0,
+                });
+            }
+            self.warnings.sort_by_key(|warning| warning.position);
+        }
+        if !self.tokenizer.skipped_whitespace.is_empty() {
+            for range in self.tokenizer.skipped_whitespace.drain(..) {
+                let mut names: Vec<String> = Vec::new();
+                for name in input[range.clone()].chars().map(describe_whitespace) {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                self.warnings.push(Warning {
+                    message: format!("{} read as whitespace", names.join(" and ")),
+                    position: range.start,
+                    line: 0,
+                    column: 0,
+                });
+            }
+            self.warnings.sort_by_key(|warning| warning.position);
+        }
+        for warning in &mut self.warnings {
+            (warning.line, warning.column) = line_column(input, warning.position);
+        }
+        match lex_error {
+            Some(e) => Err(e.locate(input)),
+            None => result.map_err(|e| e.locate(input)),
+        }
+    }
+
+    /// Parses `input` with this parser's options, recording every error
+    /// and carrying on instead of stopping at the first. See
+    /// [`recovery`] for how parsing resumes.
+    ///
+    /// Nothing is reported through [`warnings`](Self::warnings), and the
+    /// subtree cache is not used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::{JsonParser, ParserOptions};
+    ///
+    /// let mut parser = JsonParser::with_options(ParserOptions::lenient());
+    /// let recovered = parser.parse_with_recovery("{'a': 1, // ok\n 'b': [2,, 3],}");
+    /// assert_eq!(recovered.value.to_string(), r#"{"a":1,"b":[2,null,3]}"#);
+    /// assert_eq!(recovered.errors.len(), 1);
+    /// assert_eq!((recovered.errors[0].line(), recovered.errors[0].column()), (2, 10));
+    /// ```
+    pub fn parse_with_recovery(&mut self, input: &str) -> Recovered {
+        recovery::recover(input, self.options())
+    }
+
+    /// Checks that `input` is UTF-8 and parses it like
+    /// [`parse`](Self::parse).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::InvalidUtf8`] with the offset of the first
+    /// invalid byte, unless [`lossy_utf8`](Self::lossy_utf8) is set, or
+    /// another [`JsonError`] if the text is not valid JSON.
+    pub fn parse_bytes(&mut self, input: &[u8]) -> Result<JsonValue, JsonError> {
+        match std::str::from_utf8(input) {
+            Ok(text) => self.parse(text),
+            Err(_) if self.lossy_utf8 => self.parse(&String::from_utf8_lossy(input)),
+            Err(e) => Err(JsonError::invalid_utf8(input, e.valid_up_to())),
+        }
+    }
+
+    /// Overwrites the retained input copy and any unconsumed string tokens
+    /// with zeros, so no plaintext outlives the parse.
+    #[cfg(feature = "zeroize")]
+    pub(crate) fn zeroize_buffers(&mut self) {
+        use zeroize::Zeroize;
+        for token in self.tokens.iter_mut().chain(&mut self.next) {
+            if let Token::String(s) = token {
+                s.zeroize();
+            }
+        }
         self.tokens.clear();
-        self.tokenizer.retokenize(input, &mut self.tokens)?;
-        self.total_count = self.tokens.len();
-        self.tokens.reverse();
-        self.parse_tokens()
+        self.next = None;
+        self.tokenizer.zeroize_input();
     }
 
     /// Walks the token stream and returns the top-level JSON value.
     ///
     /// After parsing the first value, verifies that no trailing tokens
-    /// remain. Exactly one JSON value is expected per input.
-    fn parse_tokens(&mut self) -> Result<JsonValue, JsonError> {
+    /// remain. Exactly one JSON value is expected per input. Use this
+    /// with a parser created by [`from_tokens`](Self::from_tokens);
+    /// [`parse`](Self::parse) calls it internally.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError`] if the tokens do not form exactly one valid
+    /// JSON value.
+    pub fn parse_tokens(&mut self) -> Result<JsonValue, JsonError> {
+        #[cfg(feature = "profiling")]
+        {
+            self.profile = ParseProfile::default();
+        }
+        self.streaming = false;
+        self.build()
+    }
+
+    // Builds the document from the token source, recording how long it
+    // took when profiling.
+    fn build(&mut self) -> Result<JsonValue, JsonError> {
+        #[cfg(feature = "profiling")]
+        if !self.streaming {
+            self.profile.peak_tokens = self.tokens.len();
+            self.profile.allocations += profile::token_allocations(&self.tokens);
+        }
+        #[cfg(feature = "profiling")]
+        let started = Instant::now();
+        self.pull();
+        #[cfg(feature = "profiling")]
+        if self.streaming {
+            self.profile.peak_tokens = usize::from(self.next.is_some());
+        }
+        let result = self.parse_document();
+        #[cfg(feature = "profiling")]
+        {
+            self.profile.build = started.elapsed().saturating_sub(self.profile.tokenize);
+            if let Ok(value) = &result {
+                self.profile.allocations += profile::container_allocations(value);
+            }
+        }
+        result
+    }
+
+    fn parse_document(&mut self) -> Result<JsonValue, JsonError> {
+        self.warnings.clear();
+        self.depth = 0;
+        if self.is_at_end()
+            && let Some(value) = self.empty_document.value()
+        {
+            return Ok(value);
+        }
         let value = self.parse_value()?;
         if !self.is_at_end() {
             let position = self.consumed();
@@ -137,6 +1331,8 @@ impl JsonParser {
                     None => "<no token>".to_string(),
                 },
                 position,
+                line: 0,
+                column: 0,
             });
         }
         Ok(value)
@@ -144,33 +1340,102 @@ impl JsonParser {
 
     fn parse_value(&mut self) -> Result<JsonValue, JsonError> {
         match self.peek() {
-            Some(Token::LeftBracket) => self.parse_array(),
-            Some(Token::LeftBrace) => self.parse_object(),
+            Some(Token::LeftBracket) => self.parse_nested(Self::parse_array),
+            Some(Token::LeftBrace) => self.parse_nested(Self::parse_object),
             _ => {
                 let position = self.consumed();
                 match self.advance() {
-                    Some(Token::String(s)) => Ok(JsonValue::String(s)),
+                    Some(Token::String(s)) => {
+                        if let Some(limit @ Limit::StringLen(max)) =
+                            self.limit(PathLimits::string_len_at)
+                            && s.len() > max
+                        {
+                            return Err(self.limit_exceeded(limit, position));
+                        }
+                        Ok(JsonValue::String(s))
+                    }
                     Some(Token::Number(n)) => Ok(JsonValue::Number(n)),
                     Some(Token::Boolean(b)) => Ok(JsonValue::Boolean(b)),
-                    Some(Token::Null) => Ok(JsonValue::Null),
+                    Some(Token::Null) => Ok(self.literal.take().unwrap_or(JsonValue::Null)),
                     Some(other) => Err(JsonError::UnexpectedToken {
                         expected: "JSON value".to_string(),
                         found: format!("{:?}", other),
                         position,
+                        line: 0,
+                        column: 0,
                     }),
                     None => Err(JsonError::UnexpectedEndOfInput {
                         expected: "JSON value".to_string(),
                         position,
+                        line: 0,
+                        column: 0,
                     }),
                 }
             }
         }
     }
 
+    // Parses an array or object one level deeper, enforcing `max_depth`
+    // and consulting the subtree cache when there is one.
+    fn parse_nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<JsonValue, JsonError>,
+    ) -> Result<JsonValue, JsonError> {
+        if self.depth >= self.max_depth {
+            return Err(JsonError::DepthLimitExceeded {
+                max_depth: self.max_depth,
+                position: self.consumed(),
+                line: 0,
+                column: 0,
+            });
+        }
+        let subtree = self.subtree();
+        if let Some(raw) = &subtree
+            && let Some(cache) = &mut self.subtree_cache
+            && let Some(value) = cache.get(&self.tokenizer.input()[raw.clone()])
+        {
+            self.tokenizer.seek(raw.end);
+            self.pull();
+            return Ok(value);
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        if let (Ok(value), Some(raw), Some(cache)) = (&result, subtree, &mut self.subtree_cache) {
+            cache.insert(&self.tokenizer.input()[raw], value);
+        }
+        result
+    }
+
+    // Returns the source text range of the container starting at the next
+    // token, when a subtree cache needs it. Scans ahead and rewinds.
+    fn subtree(&mut self) -> Option<Range<usize>> {
+        if self.subtree_cache.is_none() || !self.streaming || self.path_limits.is_some() {
+            return None;
+        }
+        let resume = self.tokenizer.position();
+        let mut depth = 1;
+        let end = loop {
+            match self.tokenizer.next_token() {
+                Ok(Some((Token::LeftBracket | Token::LeftBrace, _))) => depth += 1,
+                Ok(Some((Token::RightBracket | Token::RightBrace, _))) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break Some(self.tokenizer.position());
+                    }
+                }
+                Ok(Some(_)) => {}
+                Ok(None) | Err(_) => break None,
+            }
+        };
+        self.tokenizer.seek(resume);
+        Some(self.next_position..end?)
+    }
+
     fn parse_array(&mut self) -> Result<JsonValue, JsonError> {
         self.advance(); // consume opening '['
         // TODO: estimate, ~2 tokens per element (value + comma), cap at 64 to avoid over-alloc on large files
-        let estimate = self.tokens.len() / 2;
+        let estimate = self.remaining() / 2;
         let mut elements: Vec<JsonValue> = Vec::with_capacity(estimate.min(64));
 
         // Empty array case
@@ -179,9 +1444,15 @@ impl JsonParser {
             return Ok(JsonValue::Array(elements));
         }
 
+        let max_items = self.limit(PathLimits::items_at);
         loop {
             // Parse the next element
-            let value = self.parse_value()?;
+            if let Some(limit @ Limit::Items(max)) = max_items
+                && elements.len() == max
+            {
+                return Err(self.limit_exceeded(limit, self.consumed()));
+            }
+            let value = self.enter(Segment::Index(elements.len()), Self::parse_value)?;
             elements.push(value);
 
             // Check what follows the element
@@ -190,10 +1461,16 @@ impl JsonParser {
                     self.advance(); // consume comma
                     // Check for trailing comma
                     if matches!(self.peek(), Some(Token::RightBracket)) {
+                        if self.allow_trailing_commas {
+                            self.advance(); // consume closing ']'
+                            break;
+                        }
                         return Err(JsonError::UnexpectedToken {
                             expected: "JSON value".to_string(),
                             found: "]".to_string(),
                             position: self.consumed(),
+                            line: 0,
+                            column: 0,
                         });
                     }
                 }
@@ -206,12 +1483,16 @@ impl JsonParser {
                         expected: "comma or closing bracket".to_string(),
                         found: format!("{:?}", other),
                         position: self.consumed(),
+                        line: 0,
+                        column: 0,
                     });
                 }
                 None => {
                     return Err(JsonError::UnexpectedEndOfInput {
                         expected: "comma or closing bracket".to_string(),
                         position: self.consumed(),
+                        line: 0,
+                        column: 0,
                     });
                 }
             }
@@ -223,8 +1504,8 @@ impl JsonParser {
     fn parse_object(&mut self) -> Result<JsonValue, JsonError> {
         self.advance(); // consume opening '{'
         // TODO: estimate, ~4 tokens per entry (key + colon + value + comma), cap at 16 to avoid over-alloc
-        let estimate = self.tokens.len() / 4;
-        let mut map: HashMap<String, JsonValue> = HashMap::with_capacity(estimate.min(16));
+        let estimate = self.remaining() / 4;
+        let mut map: JsonMap = JsonMap::with_capacity(estimate.min(16));
 
         // Empty object case
         if matches!(self.peek(), Some(Token::RightBrace)) {
@@ -232,22 +1513,28 @@ impl JsonParser {
             return Ok(JsonValue::Object(map));
         }
 
+        let max_items = self.limit(PathLimits::items_at);
+
         loop {
             // Expect a string key
-            let position = self.consumed();
+            let key_position = self.consumed();
             let key = match self.advance() {
                 Some(Token::String(s)) => s,
                 Some(other) => {
                     return Err(JsonError::UnexpectedToken {
                         expected: "string key".to_string(),
                         found: format!("{:?}", other),
-                        position,
+                        position: key_position,
+                        line: 0,
+                        column: 0,
                     });
                 }
                 None => {
                     return Err(JsonError::UnexpectedEndOfInput {
                         expected: "string key".to_string(),
-                        position,
+                        position: key_position,
+                        line: 0,
+                        column: 0,
                     });
                 }
             };
@@ -261,19 +1548,53 @@ impl JsonParser {
                         expected: "colon".to_string(),
                         found: format!("{:?}", other),
                         position,
+                        line: 0,
+                        column: 0,
                     });
                 }
                 None => {
                     return Err(JsonError::UnexpectedEndOfInput {
                         expected: "colon".to_string(),
                         position,
+                        line: 0,
+                        column: 0,
+                    });
+                }
+            }
+
+            let duplicate = map.contains_key(&key);
+            if let Some(limit @ Limit::Items(max)) = max_items
+                && !duplicate
+                && map.len() == max
+            {
+                return Err(self.limit_exceeded(limit, key_position));
+            }
+            if duplicate {
+                if self.duplicate_keys == DuplicateKeys::Error {
+                    return Err(JsonError::DuplicateKey {
+                        key,
+                        position: key_position,
+                        line: 0,
+                        column: 0,
                     });
                 }
+                self.warnings.push(Warning {
+                    message: format!("duplicate key {:?}", key),
+                    position: key_position,
+                    line: 0,
+                    column: 0,
+                });
             }
 
             // Parse the value
-            let value = self.parse_value()?;
-            map.insert(key, value);
+            let value = if self.path_limits.is_some() {
+                self.enter(Segment::Key(key.clone()), Self::parse_value)?
+            } else {
+                self.parse_value()?
+            };
+            if !(duplicate && self.duplicate_keys == DuplicateKeys::FirstWins) {
+                map.insert(key, value);
+            }
 
             // Check what follows the value
             match self.peek() {
@@ -281,10 +1602,16 @@ impl JsonParser {
                     self.advance(); // consume comma
                     // Check for trailing comma
                     if matches!(self.peek(), Some(Token::RightBrace)) {
+                        if self.allow_trailing_commas {
+                            self.advance(); // consume closing '}'
+                            break;
+                        }
                         return Err(JsonError::UnexpectedToken {
                             expected: "string key".to_string(),
                             found: "}".to_string(),
                             position: self.consumed(),
+                            line: 0,
+                            column: 0,
                         });
                     }
                 }
@@ -297,12 +1624,16 @@ impl JsonParser {
                         expected: "comma or closing brace".to_string(),
                         found: format!("{:?}", other),
                         position: self.consumed(),
+                        line: 0,
+                        column: 0,
                     });
                 }
                 None => {
                     return Err(JsonError::UnexpectedEndOfInput {
                         expected: "comma or closing brace".to_string(),
                         position: self.consumed(),
+                        line: 0,
+                        column: 0,
                     });
                 }
             }
@@ -311,30 +1642,170 @@ impl JsonParser {
         Ok(JsonValue::Object(map))
     }
 
-    // Reversed so pop() yields front-to-back without cloning.
+    // Moves the following token into `next`, pulling it from the
+    // tokenizer when streaming.
+    fn pull(&mut self) {
+        if !self.streaming {
+            self.next_position = self.total_count - self.tokens.len();
+            self.next = self.tokens.pop();
+            return;
+        }
+        #[cfg(feature = "profiling")]
+        let started = Instant::now();
+        match self.tokenizer.next_token() {
+            Ok(Some((token, start))) => {
+                self.next = Some(token);
+                self.next_position = start;
+            }
+            Ok(None) => {
+                self.next = None;
+                self.next_position = self.tokenizer.input().len();
+            }
+            Err(e) => match self.claim_literal(&e) {
+                Some(value) => {
+                    self.next = Some(Token::Null);
+                    self.next_literal = Some(value);
+                    self.next_position = e.position();
+                }
+                None => {
+                    self.lex_error = Some(e);
+                    self.next = None;
+                    self.next_position = self.tokenizer.input().len();
+                }
+            },
+        }
+        #[cfg(feature = "profiling")]
+        {
+            self.profile.tokenize += started.elapsed();
+            self.profile.allocations += profile::token_allocations(self.next.as_slice());
+        }
+    }
+
+    // Scans the unread input for a tokenizer error, after the parser
+    // failed before reaching it.
+    fn rest_lex_error(&mut self) -> Option<JsonError> {
+        loop {
+            match self.tokenizer.next_token() {
+                Ok(Some(_)) => {}
+                Ok(None) => return None,
+                Err(e) if self.claim_literal(&e).is_some() => {}
+                Err(e) => return Some(e),
+            }
+        }
+    }
+
+    // Offers the text a tokenizer error starts at to the literal
+    // extension. On success the tokenizer resumes after the literal.
+    fn claim_literal(&mut self, error: &JsonError) -> Option<JsonValue> {
+        let extension = self.literal_extension.as_mut()?;
+        let claimable = match error {
+            JsonError::UnexpectedToken { expected, .. } => expected == "valid JSON token",
+            _ => error.kind() == JsonErrorKind::InvalidNumber,
+        };
+        if !claimable {
+            return None;
+        }
+        let start = error.position();
+        let rest = &self.tokenizer.input()[start..];
+        let (value, len) = extension.parse_literal(rest)?;
+        if len == 0 || !rest.is_char_boundary(len) {
+            return None;
+        }
+        self.tokenizer.seek(start + len);
+        Some(value)
+    }
+
     fn advance(&mut self) -> Option<Token> {
-        self.tokens.pop()
+        let token = self.next.take()?;
+        self.literal = self.next_literal.take();
+        self.pull();
+        Some(token)
     }
 
     fn peek(&self) -> Option<&Token> {
-        self.tokens.last()
+        self.next.as_ref()
     }
 
     fn is_at_end(&self) -> bool {
-        self.tokens.is_empty()
+        self.next.is_none()
+    }
+
+    // The position of the next token: a byte offset when streaming, a
+    // token index otherwise.
+    // Parses a value one path segment deeper, tracking the path when
+    // there are path limits to check.
+    fn enter(
+        &mut self,
+        segment: Segment,
+        parse: fn(&mut Self) -> Result<JsonValue, JsonError>,
+    ) -> Result<JsonValue, JsonError> {
+        if self.path_limits.is_none() {
+            return parse(self);
+        }
+        self.path.push(segment);
+        let result = parse(self);
+        self.path.pop();
+        result
+    }
+
+    // Looks up the path limit of one kind for the value being built.
+    fn limit(&self, lookup: fn(&PathLimits, &JsonPath) -> Option<Limit>) -> Option<Limit> {
+        lookup(self.path_limits.as_ref()?, &self.path)
+    }
+
+    fn limit_exceeded(&self, limit: Limit, position: usize) -> JsonError {
+        JsonError::PathLimitExceeded {
+            path: self.path.to_pointer(),
+            limit,
+            position,
+            line: 0,
+            column: 0,
+        }
     }
 
     fn consumed(&self) -> usize {
-        self.total_count - self.tokens.len()
+        self.next_position
+    }
+
+    // Roughly how many tokens are left, for sizing containers. Assumes
+    // ~3 bytes per token when streaming.
+    fn remaining(&self) -> usize {
+        let buffered = if self.streaming {
+            (self.tokenizer.input().len() - self.tokenizer.position()) / 3
+        } else {
+            self.tokens.len()
+        };
+        buffered + usize::from(self.next.is_some())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cache::HashCache;
+    use crate::json;
 
     // --- parse_json free function ---
 
+    #[test]
+    fn test_parser_errors_report_byte_location() {
+        let err = parse_json("[1,\n 2 3]").unwrap_err();
+        assert_eq!(err.position(), 7);
+        assert_eq!((err.line(), err.column()), (2, 4));
+
+        let err = parse_json("{\"a\": 1,\n").unwrap_err();
+        assert!(matches!(err, JsonError::UnexpectedEndOfInput { .. }));
+        assert_eq!((err.position(), err.line(), err.column()), (9, 2, 1));
+    }
+
+    #[test]
+    fn test_from_tokens_errors_keep_token_index() {
+        let err = JsonParser::from_tokens(vec![Token::Comma])
+            .parse_tokens()
+            .unwrap_err();
+        assert_eq!((err.position(), err.line()), (0, 0));
+    }
+
     #[test]
     fn test_parse_json_string() {
         let result = parse_json(r#""hello""#).unwrap();
@@ -344,7 +1815,7 @@ mod tests {
     #[test]
     fn test_parse_json_number() {
         let result = parse_json("42").unwrap();
-        assert_eq!(result, JsonValue::Number(42.0));
+        assert_eq!(result, JsonValue::Number(42.0.into()));
     }
 
     #[test]
@@ -364,13 +1835,13 @@ mod tests {
     #[test]
     fn test_parse_number() {
         let result = JsonParser::new().parse("42.5").unwrap();
-        assert_eq!(result, JsonValue::Number(42.5));
+        assert_eq!(result, JsonValue::Number(42.5.into()));
 
         let result = JsonParser::new().parse("0").unwrap();
-        assert_eq!(result, JsonValue::Number(0.0));
+        assert_eq!(result, JsonValue::Number(0.0.into()));
 
         let result = JsonParser::new().parse("-10").unwrap();
-        assert_eq!(result, JsonValue::Number(-10.0));
+        assert_eq!(result, JsonValue::Number((-10.0).into()));
     }
 
     #[test]
@@ -394,7 +1865,9 @@ mod tests {
         assert!(result.is_err());
 
         match result {
-            Err(JsonError::UnexpectedEndOfInput { expected, position }) => {
+            Err(JsonError::UnexpectedEndOfInput {
+                expected, position, ..
+            }) => {
                 assert_eq!(expected, "JSON value");
                 assert_eq!(position, 0);
             }
@@ -427,14 +1900,15 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_all_primitives() {
         let test_cases = vec![
             ("null", JsonValue::Null),
             ("true", JsonValue::Boolean(true)),
             ("false", JsonValue::Boolean(false)),
-            ("42", JsonValue::Number(42.0)),
-            ("-3.14", JsonValue::Number(-3.14)),
-            ("0", JsonValue::Number(0.0)),
+            ("42", JsonValue::Number(42.0.into())),
+            ("-3.14", JsonValue::Number((-3.14).into())),
+            ("0", JsonValue::Number(0.0.into())),
             (r#""hello""#, JsonValue::String("hello".to_string())),
         ];
 
@@ -447,7 +1921,7 @@ mod tests {
     #[test]
     fn test_parse_with_whitespace() {
         let result = JsonParser::new().parse("  42  ").unwrap();
-        assert_eq!(result, JsonValue::Number(42.0));
+        assert_eq!(result, JsonValue::Number(42.0.into()));
 
         let result = JsonParser::new().parse("\n\ttrue\n").unwrap();
         assert_eq!(result, JsonValue::Boolean(true));
@@ -518,9 +1992,10 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_parse_negative_number() {
         let result = JsonParser::new().parse("-3.14").unwrap();
-        assert_eq!(result, JsonValue::Number(-3.14));
+        assert_eq!(result, JsonValue::Number((-3.14).into()));
     }
 
     #[test]
@@ -561,6 +2036,25 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_empty_document_policy() {
+        for (policy, expected) in [
+            (EmptyDocument::Null, "null"),
+            (EmptyDocument::EmptyObject, "{}"),
+            (EmptyDocument::EmptyArray, "[]"),
+        ] {
+            let mut parser = JsonParser::new().empty_document(policy);
+            assert_eq!(parser.parse("").unwrap().to_string(), expected);
+            assert_eq!(parser.parse(" \t\r\n").unwrap().to_string(), expected);
+            assert_eq!(parser.options().empty_document, policy);
+            // Non-empty input is unaffected, including invalid input.
+            assert_eq!(parser.parse("1").unwrap(), JsonValue::Number(1.0.into()));
+            assert!(parser.parse(",").is_err());
+        }
+        let mut parser = JsonParser::from_tokens(Vec::new()).empty_document(EmptyDocument::Null);
+        assert_eq!(parser.parse_tokens(), Ok(JsonValue::Null));
+    }
+
     // --- Trailing tokens ---
 
     #[test]
@@ -581,7 +2075,10 @@ mod tests {
     #[test]
     fn test_parse_array_single_element() {
         let result = parse_json("[42]").unwrap();
-        assert_eq!(result, JsonValue::Array(vec![JsonValue::Number(42.0)]));
+        assert_eq!(
+            result,
+            JsonValue::Array(vec![JsonValue::Number(42.0.into())])
+        );
     }
 
     #[test]
@@ -590,9 +2087,9 @@ mod tests {
         assert_eq!(
             result,
             JsonValue::Array(vec![
-                JsonValue::Number(1.0),
-                JsonValue::Number(2.0),
-                JsonValue::Number(3.0),
+                JsonValue::Number(1.0.into()),
+                JsonValue::Number(2.0.into()),
+                JsonValue::Number(3.0.into()),
             ])
         );
     }
@@ -603,7 +2100,7 @@ mod tests {
         assert_eq!(
             result,
             JsonValue::Array(vec![
-                JsonValue::Number(1.0),
+                JsonValue::Number(1.0.into()),
                 JsonValue::String("two".to_string()),
                 JsonValue::Boolean(true),
                 JsonValue::Null,
@@ -617,9 +2114,9 @@ mod tests {
         assert_eq!(
             result,
             JsonValue::Array(vec![
-                JsonValue::Number(1.0),
-                JsonValue::Number(2.0),
-                JsonValue::Number(3.0),
+                JsonValue::Number(1.0.into()),
+                JsonValue::Number(2.0.into()),
+                JsonValue::Number(3.0.into()),
             ])
         );
     }
@@ -630,8 +2127,14 @@ mod tests {
         assert_eq!(
             result,
             JsonValue::Array(vec![
-                JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Number(2.0)]),
-                JsonValue::Array(vec![JsonValue::Number(3.0), JsonValue::Number(4.0)]),
+                JsonValue::Array(vec![
+                    JsonValue::Number(1.0.into()),
+                    JsonValue::Number(2.0.into())
+                ]),
+                JsonValue::Array(vec![
+                    JsonValue::Number(3.0.into()),
+                    JsonValue::Number(4.0.into())
+                ]),
             ])
         );
     }
@@ -642,7 +2145,7 @@ mod tests {
         assert_eq!(
             result,
             JsonValue::Array(vec![JsonValue::Array(vec![JsonValue::Array(vec![
-                JsonValue::Number(1.0)
+                JsonValue::Number(1.0.into())
             ])])])
         );
     }
@@ -750,7 +2253,7 @@ mod tests {
         let result = parse_json("[1, 2, 3]").unwrap();
         let arr = result.as_array().unwrap();
         assert_eq!(arr.len(), 3);
-        assert_eq!(arr[0], JsonValue::Number(1.0));
+        assert_eq!(arr[0], JsonValue::Number(1.0.into()));
     }
 
     #[test]
@@ -768,13 +2271,13 @@ mod tests {
     #[test]
     fn test_parse_empty_object() {
         let result = parse_json("{}").unwrap();
-        assert_eq!(result, JsonValue::Object(HashMap::new()));
+        assert_eq!(result, JsonValue::Object(JsonMap::new()));
     }
 
     #[test]
     fn test_parse_object_single_key() {
         let result = parse_json(r#"{"key": "value"}"#).unwrap();
-        let mut expected = HashMap::new();
+        let mut expected = JsonMap::new();
         expected.insert("key".to_string(), JsonValue::String("value".to_string()));
         assert_eq!(result, JsonValue::Object(expected));
     }
@@ -786,14 +2289,14 @@ mod tests {
             result.get("name"),
             Some(&JsonValue::String("Alice".to_string()))
         );
-        assert_eq!(result.get("age"), Some(&JsonValue::Number(30.0)));
+        assert_eq!(result.get("age"), Some(&JsonValue::Number(30.0.into())));
     }
 
     #[test]
     fn test_parse_nested_object() {
         let result = parse_json(r#"{"outer": {"inner": 1}}"#).unwrap();
         let outer = result.get("outer").unwrap();
-        assert_eq!(outer.get("inner"), Some(&JsonValue::Number(1.0)));
+        assert_eq!(outer.get("inner"), Some(&JsonValue::Number(1.0.into())));
     }
 
     #[test]
@@ -803,9 +2306,9 @@ mod tests {
         assert_eq!(
             items,
             &JsonValue::Array(vec![
-                JsonValue::Number(1.0),
-                JsonValue::Number(2.0),
-                JsonValue::Number(3.0),
+                JsonValue::Number(1.0.into()),
+                JsonValue::Number(2.0.into()),
+                JsonValue::Number(3.0.into()),
             ])
         );
     }
@@ -815,8 +2318,8 @@ mod tests {
         let result = parse_json(r#"[{"a": 1}, {"b": 2}]"#).unwrap();
         let arr = result.as_array().unwrap();
         assert_eq!(arr.len(), 2);
-        assert_eq!(arr[0].get("a"), Some(&JsonValue::Number(1.0)));
-        assert_eq!(arr[1].get("b"), Some(&JsonValue::Number(2.0)));
+        assert_eq!(arr[0].get("a"), Some(&JsonValue::Number(1.0.into())));
+        assert_eq!(arr[1].get("b"), Some(&JsonValue::Number(2.0.into())));
     }
 
     #[test]
@@ -850,6 +2353,249 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_trailing_commas_option() {
+        let mut parser = JsonParser::new().allow_trailing_commas(true);
+        assert_eq!(
+            parser
+                .parse(r#"{"a": [1, 2,], "b": {},}"#)
+                .unwrap()
+                .to_string(),
+            r#"{"a":[1,2],"b":{}}"#
+        );
+        // Only one comma, and only after an element.
+        for input in ["[,]", "[1,,]", "{,}", r#"{"a": 1,,}"#] {
+            assert!(parser.parse(input).is_err(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_lenient_options() {
+        let input = "/* settings */ {'name': 'x', // inline\n 'tags': ['a',],}";
+        assert!(parse_json(input).is_err());
+
+        let mut parser = JsonParser::with_options(ParserOptions::lenient());
+        let value = parser.parse(input).unwrap();
+        assert_eq!(value.to_string(), r#"{"name":"x","tags":["a"]}"#);
+
+        parser.shrink_buffers();
+        assert_eq!(parser.options(), ParserOptions::lenient());
+        assert!(parser.parse(input).is_ok());
+
+        // Errors after a comment still point at the right line.
+        let err = parser.parse("// one\n// two\n[1 2]").unwrap_err();
+        assert_eq!((err.line(), err.column()), (3, 4));
+    }
+
+    #[test]
+    fn test_thread_default_options() {
+        let input = "[1, /* two */ 2]";
+        let comments = ParserOptions {
+            allow_comments: true,
+            ..ParserOptions::default()
+        };
+        assert_eq!(ParserOptions::set_thread_default(Some(comments)), None);
+        assert_eq!(JsonParser::new().options(), comments);
+        assert!(parse_json(input).is_ok());
+        assert!(
+            JsonParser::with_options(ParserOptions::default())
+                .parse(input)
+                .is_err()
+        );
+        // Other threads keep their own defaults.
+        assert!(
+            std::thread::spawn(move || parse_json(input))
+                .join()
+                .unwrap()
+                .is_err()
+        );
+
+        let nested = ParserOptions::lenient().scoped(|| {
+            assert_eq!(ParserOptions::current(), ParserOptions::lenient());
+            parse_json("['a',]")
+        });
+        assert!(nested.is_ok());
+        assert_eq!(ParserOptions::current(), comments);
+        let panicked = std::panic::catch_unwind(|| {
+            ParserOptions::default().scoped(|| panic!("unwinding"));
+        });
+        assert!(panicked.is_err());
+        assert_eq!(ParserOptions::current(), comments);
+
+        assert_eq!(ParserOptions::set_thread_default(None), Some(comments));
+        assert!(parse_json(input).is_err());
+    }
+
+    #[test]
+    fn test_parse_bytes_utf8() {
+        let value = parse_bytes("{\"name\": \"Zoë\"}".as_bytes()).unwrap();
+        assert_eq!(value.get("name").and_then(|v| v.as_str()), Some("Zoë"));
+
+        let err = parse_bytes(b"{\n  \"\xc3\xa9\": \"\xc3\"\n}").unwrap_err();
+        assert_eq!(
+            err,
+            JsonError::InvalidUtf8 {
+                byte_offset: 11,
+                line: 2,
+                column: 9,
+            }
+        );
+        assert_eq!(err.position(), 11);
+        assert_eq!(
+            err.to_string(),
+            "Invalid UTF-8 at line 2, column 9 (position 11)"
+        );
+
+        let mut parser = JsonParser::with_options(ParserOptions {
+            lossy_utf8: true,
+            ..ParserOptions::default()
+        });
+        let value = parser.parse_bytes(b"[\"a\xffb\", \"\xf0\x9f\"]").unwrap();
+        assert_eq!(value.to_string(), "[\"a\u{FFFD}b\",\"\u{FFFD}\"]");
+        // Lossy decoding does not make invalid JSON valid.
+        assert!(parser.parse_bytes(b"[\xff]").is_err());
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        let mut parser = JsonParser::new();
+        assert!(parser.parse(&nested(DEFAULT_MAX_DEPTH)).is_ok());
+        let err = parser.parse(&nested(DEFAULT_MAX_DEPTH + 1)).unwrap_err();
+        assert_eq!(err.position(), DEFAULT_MAX_DEPTH);
+        assert_eq!(
+            err.to_string(),
+            "Nesting deeper than 128 levels at line 1, column 129 (position 128)"
+        );
+
+        // Objects count too, and the depth resets between documents.
+        let mut parser = JsonParser::new().max_depth(1);
+        assert!(matches!(
+            parser.parse(r#"{"a": {}}"#),
+            Err(JsonError::DepthLimitExceeded { position: 6, .. })
+        ));
+        assert!(parser.parse(r#"[1, 2]"#).is_ok());
+        assert!(parser.parse("0").is_ok());
+        assert!(JsonParser::new().max_depth(0).parse("[]").is_err());
+
+        // Far deeper than the stack could take without the limit.
+        assert!(parse_json(&"[".repeat(1_000_000)).is_err());
+        assert!(parse_json(&"{\"a\":".repeat(1_000_000)).is_err());
+    }
+
+    #[test]
+    fn test_token_length_limits() {
+        let options = ParserOptions {
+            max_string_len: 4,
+            max_number_len: 3,
+            ..ParserOptions::default()
+        };
+        let mut parser = JsonParser::with_options(options);
+        assert_eq!(parser.options(), options);
+        assert!(parser.parse(r#"{"key": [123, "abcd"]}"#).is_ok());
+        let err = parser.parse("{\n  \"long key\": 1}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "String longer than 4 bytes at line 2, column 3 (position 4)"
+        );
+        assert!(matches!(
+            parser.parse("[1234]"),
+            Err(JsonError::NumberTooLong { max_len: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_subtree_cache() {
+        let mut parser = JsonParser::new().subtree_cache(HashCache::new());
+        let first = parser
+            .parse(r#"{"s": {"k": [1, 2]}, "t": {"k": [1, 2]}}"#)
+            .unwrap();
+        let second = parser.parse(r#"[{"k": [1, 2]}, {"k": [3]}]"#).unwrap();
+        assert_eq!(first, json!({"s": {"k": [1, 2]}, "t": {"k": [1, 2]}}));
+        assert_eq!(second, json!([{"k": [1, 2]}, {"k": [3]}]));
+
+        // Errors after a hit are still reported at the right place.
+        let err = parser.parse(r#"[{"k": [1, 2]} 1]"#).unwrap_err();
+        assert_eq!(err.position(), 15);
+
+        // The cache survives shrink_buffers and can be taken back out.
+        parser.shrink_buffers();
+        assert!(parser.take_subtree_cache().is_some());
+        assert!(parser.take_subtree_cache().is_none());
+
+        // Pre-built tokens have no source text, so the cache is skipped.
+        let tokens = Tokenizer::new("[[1]]").tokenize().unwrap();
+        let mut parser = JsonParser::from_tokens(tokens).subtree_cache(HashCache::new());
+        assert_eq!(parser.parse_tokens().unwrap(), json!([[1]]));
+    }
+
+    #[test]
+    fn test_literal_extension() {
+        // Upper-case enum names and signed infinities.
+        let literal = |rest: &str| {
+            if rest.starts_with("-Infinity") {
+                return Some((JsonValue::from(f64::NEG_INFINITY), "-Infinity".len()));
+            }
+            let len = rest
+                .find(|c: char| !c.is_ascii_uppercase())
+                .unwrap_or(rest.len());
+            Some((JsonValue::from(format!("enum:{}", &rest[..len])), len))
+        };
+        let mut parser = JsonParser::new().literal_extension(literal);
+        let value = parser.parse("[RED,GREEN , -Infinity, null]").unwrap();
+        assert_eq!(value[0], json!("enum:RED"));
+        assert_eq!(value[1], json!("enum:GREEN"));
+        assert_eq!(value[2].as_f64(), Some(f64::NEG_INFINITY));
+        assert_eq!(value[3], JsonValue::Null);
+        assert_eq!(parser.parse("BLUE").unwrap(), json!("enum:BLUE"));
+
+        // Literals are values only, and a structural error is not hidden
+        // by a literal after it.
+        let err = parser.parse("{RED: 1}").unwrap_err();
+        assert_eq!(
+            (err.position(), err.to_string().contains("string key")),
+            (1, true)
+        );
+        let err = parser.parse("[1 2, RED]").unwrap_err();
+        assert_eq!(err.position(), 3);
+        // A zero-length claim is a decline.
+        let err = parser.parse("[@]").unwrap_err();
+        assert!(matches!(err, JsonError::UnexpectedToken { ref found, .. } if found == "@"));
+
+        parser.shrink_buffers();
+        assert!(parser.parse("[RED]").is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_key_policies() {
+        let input = r#"{"a": 1, "b": 2, "a": {"x": 3}}"#;
+        let last = JsonParser::new().parse(input).unwrap();
+        assert_eq!(last.to_string(), r#"{"a":{"x":3},"b":2}"#);
+
+        let mut parser = JsonParser::new().duplicate_keys(DuplicateKeys::FirstWins);
+        assert_eq!(parser.parse(input).unwrap().to_string(), r#"{"a":1,"b":2}"#);
+        assert_eq!(parser.warnings()[0].position, 17);
+
+        let mut parser = JsonParser::new().duplicate_keys(DuplicateKeys::Error);
+        let err = parser.parse(input).unwrap_err();
+        assert_eq!(
+            err,
+            JsonError::DuplicateKey {
+                key: "a".to_string(),
+                position: 17,
+                line: 1,
+                column: 18,
+            }
+        );
+        assert_eq!(err.category(), crate::error::Category::Data);
+        assert_eq!(
+            err.to_string(),
+            r#"Duplicate key "a" at line 1, column 18 (position 17)"#
+        );
+        // Keys only clash within one object.
+        assert!(parser.parse(r#"[{"a": 1}, {"a": {"a": 2}}]"#).is_ok());
+    }
+
     #[test]
     fn test_error_missing_colon() {
         let result = parse_json(r#"{"key" 1}"#);
@@ -867,4 +2613,284 @@ mod tests {
         let result = parse_json(r#"{"a": 1 "b": 2}"#);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_does_not_buffer_tokens() {
+        let mut parser = JsonParser::new();
+        parser.parse(r#"["a\nb", {"k\u0041": [1, 2, 3]}]"#).unwrap();
+        assert_eq!(parser.tokens.capacity(), 0);
+        for _ in 0..10 {
+            let value = parser.parse(r#"{"x": "y\tz"}"#).unwrap();
+            assert_eq!(value.get("x").and_then(|v| v.as_str()), Some("y\tz"));
+        }
+
+        parser.shrink_buffers();
+        assert_eq!(parser.parse("1").unwrap(), JsonValue::Number(1.0.into()));
+    }
+
+    #[test]
+    fn test_errors_match_tokenizing_first() {
+        // Each input has a structural error before a lexical one; the
+        // lexical error wins, as it would if the input were tokenized up
+        // front.
+        for (input, position) in [
+            ("[1 2 @]", 5),
+            (r#"{"a" 1, "b": "\x"}"#, 15),
+            ("1 2 tru", 4),
+            ("[1] @", 4),
+            ("@", 0),
+        ] {
+            let err = parse_json(input).unwrap_err();
+            let lexical = Tokenizer::new(input).tokenize().unwrap_err();
+            assert_eq!(err, lexical, "input {:?}", input);
+            assert_eq!(err.position(), position);
+        }
+        let err = JsonParser::new().max_depth(1).parse("[[01]]").unwrap_err();
+        assert!(matches!(err, JsonError::InvalidNumber { position: 2, .. }));
+        let err = JsonParser::new()
+            .empty_document(EmptyDocument::Null)
+            .parse(" @")
+            .unwrap_err();
+        assert_eq!(err.position(), 1);
+    }
+
+    #[test]
+    fn test_from_tokens() {
+        let tokens = Tokenizer::new(r#"{"k": [true, null]}"#).tokenize().unwrap();
+        let value = JsonParser::from_tokens(tokens.clone())
+            .parse_tokens()
+            .unwrap();
+        assert_eq!(value, parse_json(r#"{"k": [true, null]}"#).unwrap());
+
+        let mut collected: JsonParser = tokens.into_iter().collect();
+        assert!(collected.parse_tokens().is_ok());
+        // All tokens are consumed by the first call.
+        assert!(matches!(
+            collected.parse_tokens(),
+            Err(JsonError::UnexpectedEndOfInput { .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_tokens_errors_use_token_index() {
+        let tokens = vec![Token::LeftBracket, Token::Null, Token::Null];
+        match JsonParser::from_tokens(tokens).parse_tokens() {
+            Err(JsonError::UnexpectedToken { position, .. }) => assert_eq!(position, 2),
+            other => panic!("Expected UnexpectedToken, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_raw_numbers_round_trip() {
+        let mut parser = JsonParser::new().preserve_raw_numbers(true);
+        for input in [
+            "1.50",
+            "-0",
+            "0.1000",
+            "[1.0,-2.50,3]",
+            r#"{"big":12345678901234567890}"#,
+        ] {
+            let value = parser.parse(input).unwrap();
+            assert_eq!(value.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_raw_numbers_off_by_default() {
+        let value = parse_json("1.50").unwrap();
+        match &value {
+            JsonValue::Number(n) => assert_eq!(n.as_raw_str(), None),
+            other => panic!("Expected number, got {:?}", other),
+        }
+        assert_eq!(value.to_string(), "1.5");
+    }
+
+    #[test]
+    fn test_raw_numbers_compare_by_value() {
+        let raw = JsonParser::new()
+            .preserve_raw_numbers(true)
+            .parse("[1.00]")
+            .unwrap();
+        assert_eq!(raw, parse_json("[1]").unwrap());
+        match raw.get_index(0) {
+            Some(JsonValue::Number(n)) => {
+                assert_eq!(n.as_raw_str(), Some("1.00"));
+                assert_eq!(n.as_f64(), 1.0);
+            }
+            other => panic!("Expected number, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_from_reader_matches_parse_json() {
+        let input = r#"{"a": [1, {"b": [[], {}]}, "x\ty"], "c": null, "a": -2.5e3}"#;
+        let value = parse_from_reader(input.as_bytes()).unwrap();
+        assert_eq!(value, parse_json(input).unwrap());
+        assert_eq!(value.to_string(), r#"{"a":-2500,"c":null}"#);
+        assert_eq!(parse_from_reader(&b" 7 "[..]).unwrap(), json!(7));
+
+        for (bad, kind) in [
+            ("", io::ErrorKind::UnexpectedEof),
+            ("[1,", io::ErrorKind::UnexpectedEof),
+            ("[1] 2", io::ErrorKind::InvalidData),
+            ("{\"a\" 1}", io::ErrorKind::InvalidData),
+            ("\"\\x\"", io::ErrorKind::InvalidData),
+        ] {
+            let err = parse_from_reader(bad.as_bytes()).unwrap_err();
+            assert_eq!(err.kind(), kind, "{:?}", bad);
+            let inner = err.get_ref().and_then(|e| e.downcast_ref::<JsonError>());
+            assert_eq!(inner, parse_json(bad).err().as_ref(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_parse_from_reader_limits() {
+        let hostile = "[".repeat(1_000_000);
+        let err = parse_from_reader(hostile.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let inner = err.get_ref().and_then(|e| e.downcast_ref::<JsonError>());
+        assert_eq!(inner, parse_json(&hostile).err().as_ref());
+
+        let options = ParserOptions {
+            preserve_raw_numbers: true,
+            max_depth: 2,
+            max_string_len: 3,
+            ..ParserOptions::default()
+        };
+        options.scoped(|| {
+            let value = parse_from_reader(&b"[[1.50]]"[..]).unwrap();
+            assert_eq!(value.to_string(), "[[1.50]]");
+            for bad in ["[[[1]]]", r#"["abcd"]"#] {
+                let err = parse_from_reader(bad.as_bytes()).unwrap_err();
+                let inner = err.get_ref().and_then(|e| e.downcast_ref::<JsonError>());
+                assert_eq!(inner, parse_json(bad).err().as_ref(), "{:?}", bad);
+            }
+        });
+    }
+
+    #[test]
+    fn test_parse_batch_preserves_order_and_errors() {
+        let results = parse_batch(["1", "{", r#""s""#, "[]"]);
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0], Ok(JsonValue::Number(1.0.into())));
+        assert!(results[1].is_err());
+        assert_eq!(results[2], Ok(JsonValue::String("s".to_string())));
+        assert_eq!(results[3], Ok(JsonValue::Array(vec![])));
+    }
+
+    #[test]
+    fn test_parse_batch_threaded_matches_sequential() {
+        let inputs: Vec<String> = (0..37)
+            .map(|i| {
+                if i % 5 == 0 {
+                    format!("[{}", i)
+                } else {
+                    format!("[{}]", i)
+                }
+            })
+            .collect();
+        let sequential = parse_batch(inputs.iter().map(String::as_str));
+        for threads in [0, 1, 3, 8, 64] {
+            let threaded = parse_batch_threaded(inputs.iter().map(String::as_str), threads);
+            assert_eq!(threaded, sequential);
+        }
+        assert!(parse_batch_threaded(std::iter::empty(), 4).is_empty());
+    }
+
+    #[test]
+    fn test_parse_batch_threaded_uses_caller_defaults() {
+        let inputs = ["[1,]", "[2,]", "[3,]"];
+        for threads in [1, 3] {
+            let results = ParserOptions::lenient().scoped(|| parse_batch_threaded(inputs, threads));
+            assert!(results.iter().all(Result::is_ok), "threads = {}", threads);
+        }
+    }
+
+    #[test]
+    fn test_digit_separators() {
+        let parse = |separators, input: &str| {
+            let mut parser = JsonParser::new().digit_separators(separators);
+            parser
+                .parse(input)
+                .map(|v| (v.to_string(), parser.warnings().len()))
+        };
+        let commas = |input| parse(DigitSeparators::Commas, input).unwrap();
+        assert_eq!(commas("[1,234]"), ("[1234]".to_string(), 1));
+        assert_eq!(
+            commas("[-12,345,678.25e1]"),
+            ("[-123456782.5]".to_string(), 1)
+        );
+        assert_eq!(
+            commas("[1,23, 0,123, 1234,567]"),
+            ("[1,23,0,123,1234,567]".to_string(), 0)
+        );
+        assert_eq!(
+            commas("[1,234,56, 1.5,0]"),
+            ("[1234,56,1.5,0]".to_string(), 1)
+        );
+        assert_eq!(
+            commas("[1_0,000, 7_7.5_5e1_0]"),
+            ("[10000,775500000000]".to_string(), 2)
+        );
+
+        let underscores = |input| parse(DigitSeparators::Underscores, input);
+        assert_eq!(underscores("[1_000]").unwrap(), ("[1000]".to_string(), 1));
+        assert_eq!(underscores("[1,234]").unwrap(), ("[1,234]".to_string(), 0));
+        for bad in ["[1__0]", "[1_]", "[_1]", "[0_1]", "[1_.5]"] {
+            assert!(underscores(bad).is_err(), "{}", bad);
+        }
+        assert!(parse(DigitSeparators::None, "[1_000]").is_err());
+
+        let options = ParserOptions {
+            digit_separators: DigitSeparators::Underscores,
+            ..ParserOptions::default()
+        };
+        let recovered = JsonParser::with_options(options).parse_with_recovery("[1_000, 2__0]");
+        assert_eq!(recovered.value.to_string(), "[1000,2]");
+        assert_eq!(recovered.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_unicode_whitespace() {
+        let input = "\u{feff}[\u{c}1,\u{a0}\u{200b}\u{a0}\"\u{a0}\"\u{3000}]";
+        let err = parse_json(input).unwrap_err();
+        assert_eq!(err.position(), 0);
+        assert!(err.to_string().ends_with("found byte order mark (U+FEFF)"));
+
+        let mut parser = JsonParser::new().unicode_whitespace(UnicodeWhitespace::Warn);
+        let value = parser.parse(input).unwrap();
+        assert_eq!(
+            value,
+            JsonValue::from(vec![JsonValue::from(1), "\u{a0}".into()])
+        );
+        let warnings: Vec<_> = parser
+            .warnings()
+            .iter()
+            .map(|w| (w.message.as_str(), w.column))
+            .collect();
+        assert_eq!(
+            warnings,
+            [
+                ("byte order mark (U+FEFF) read as whitespace", 1),
+                ("form feed (U+000C) read as whitespace", 3),
+                (
+                    "no-break space (U+00A0) and zero-width space (U+200B) read as whitespace",
+                    6
+                ),
+                ("ideographic space (U+3000) read as whitespace", 12),
+            ]
+        );
+
+        let options = ParserOptions {
+            unicode_whitespace: UnicodeWhitespace::Allow,
+            ..ParserOptions::default()
+        };
+        let mut parser = JsonParser::with_options(options);
+        assert_eq!(parser.options(), options);
+        assert_eq!(parser.parse(input).unwrap(), value);
+        assert!(parser.warnings().is_empty());
+        let recovered = parser.parse_with_recovery("[1,\u{a0}2 3]");
+        assert_eq!(recovered.value.to_string(), "[1,2,3]");
+        assert_eq!(recovered.errors.len(), 1);
+    }
 }
@@ -0,0 +1,652 @@
+//! JSON Patch (RFC 6902): applying and generating edits.
+//!
+//! A [`Patch`](crate::patch::Patch) is an ordered list of
+//! [`Operation`](crate::patch::Operation)s (`add`, `remove`, `replace`,
+//! `move`, `copy`, and `test`) addressed by JSON Pointers. Patches can be
+//! read from and written to their standard JSON form, applied to a
+//! [`JsonValue`](crate::value::JsonValue), and computed between two values
+//! with [`diff`](crate::patch::diff).
+
+use std::error;
+use std::fmt;
+
+use crate::json;
+use crate::map::JsonMap;
+use crate::path::{JsonPath, Segment};
+use crate::value::JsonValue;
+
+/// One JSON Patch operation.
+///
+/// Paths are stored as [`JsonPath`]s; in an `add` to an array, the final
+/// segment may be the key `-` to append.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// Inserts a value into an object or array, or replaces the root.
+    Add {
+        /// Where to insert.
+        path: JsonPath,
+        /// The value to insert.
+        value: JsonValue,
+    },
+    /// Removes the value at `path`.
+    Remove {
+        /// The value to remove.
+        path: JsonPath,
+    },
+    /// Replaces the existing value at `path`.
+    Replace {
+        /// The value to replace.
+        path: JsonPath,
+        /// The new value.
+        value: JsonValue,
+    },
+    /// Removes the value at `from` and adds it at `path`.
+    Move {
+        /// The value to move.
+        from: JsonPath,
+        /// Where to add it.
+        path: JsonPath,
+    },
+    /// Adds a copy of the value at `from` at `path`.
+    Copy {
+        /// The value to copy.
+        from: JsonPath,
+        /// Where to add the copy.
+        path: JsonPath,
+    },
+    /// Fails the patch unless the value at `path` equals `value`.
+    Test {
+        /// The value to check.
+        path: JsonPath,
+        /// The expected value.
+        value: JsonValue,
+    },
+}
+
+impl Operation {
+    /// Returns the operation name used in the `"op"` member.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Add { .. } => "add",
+            Operation::Remove { .. } => "remove",
+            Operation::Replace { .. } => "replace",
+            Operation::Move { .. } => "move",
+            Operation::Copy { .. } => "copy",
+            Operation::Test { .. } => "test",
+        }
+    }
+
+    /// Returns the operation in its JSON form.
+    pub fn to_json(&self) -> JsonValue {
+        match self {
+            Operation::Add { path, value }
+            | Operation::Replace { path, value }
+            | Operation::Test { path, value } => {
+                json!({ "op": self.name(), "path": path.to_pointer(), "value": value.clone() })
+            }
+            Operation::Remove { path } => json!({ "op": "remove", "path": path.to_pointer() }),
+            Operation::Move { from, path } | Operation::Copy { from, path } => json!({
+                "op": self.name(),
+                "from": from.to_pointer(),
+                "path": path.to_pointer(),
+            }),
+        }
+    }
+
+    fn from_json(value: &JsonValue, index: usize) -> Result<Self, PatchError> {
+        let invalid = |reason: String| PatchError::Invalid { index, reason };
+        let object = value
+            .as_object()
+            .ok_or_else(|| invalid(format!("operation is {}, not an object", value.type_name())))?;
+        let pointer = |name: &str| -> Result<JsonPath, PatchError> {
+            let text = object
+                .get(name)
+                .and_then(JsonValue::as_str)
+                .ok_or_else(|| invalid(format!("missing string member {:?}", name)))?;
+            JsonPath::parse_pointer(text)
+                .ok_or_else(|| invalid(format!("{:?} is not a JSON Pointer", text)))
+        };
+        let member = |name: &str| -> Result<JsonValue, PatchError> {
+            object
+                .get(name)
+                .cloned()
+                .ok_or_else(|| invalid(format!("missing member {:?}", name)))
+        };
+        let op = object
+            .get("op")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| invalid("missing string member \"op\"".to_string()))?;
+        Ok(match op {
+            "add" => Operation::Add {
+                path: pointer("path")?,
+                value: member("value")?,
+            },
+            "remove" => Operation::Remove {
+                path: pointer("path")?,
+            },
+            "replace" => Operation::Replace {
+                path: pointer("path")?,
+                value: member("value")?,
+            },
+            "move" => Operation::Move {
+                from: pointer("from")?,
+                path: pointer("path")?,
+            },
+            "copy" => Operation::Copy {
+                from: pointer("from")?,
+                path: pointer("path")?,
+            },
+            "test" => Operation::Test {
+                path: pointer("path")?,
+                value: member("value")?,
+            },
+            other => return Err(invalid(format!("unknown op {:?}", other))),
+        })
+    }
+
+    fn apply(&self, target: &mut JsonValue, index: usize) -> Result<(), PatchError> {
+        match self {
+            Operation::Add { path, value } => add(target, path, value.clone(), index),
+            Operation::Remove { path } => remove(target, path, index).map(drop),
+            Operation::Replace { path, value } => {
+                *resolve_mut(target, path, index)? = value.clone();
+                Ok(())
+            }
+            Operation::Move { from, path } => {
+                if from == path {
+                    return resolve_mut(target, from, index).map(drop);
+                }
+                if path.segments().starts_with(from.segments()) {
+                    return Err(PatchError::Invalid {
+                        index,
+                        reason: format!("cannot move {} into its own child {}", from, path),
+                    });
+                }
+                let value = remove(target, from, index)?;
+                add(target, path, value, index)
+            }
+            Operation::Copy { from, path } => {
+                let value = resolve_mut(target, from, index)?.clone();
+                add(target, path, value, index)
+            }
+            Operation::Test { path, value } => {
+                if resolve_mut(target, path, index)? == value {
+                    Ok(())
+                } else {
+                    Err(PatchError::TestFailed {
+                        index,
+                        path: path.clone(),
+                    })
+                }
+            }
+        }
+    }
+}
+
+/// An ordered list of [`Operation`]s.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::parse_json;
+/// use rust_json_parser::patch::Patch;
+///
+/// let mut doc = parse_json(r#"{"name": "a", "tags": ["x"]}"#)?;
+/// let patch = Patch::from_json(&parse_json(r#"[
+///     {"op": "test", "path": "/name", "value": "a"},
+///     {"op": "replace", "path": "/name", "value": "b"},
+///     {"op": "add", "path": "/tags/-", "value": "y"}
+/// ]"#)?)
+/// .unwrap();
+/// patch.apply(&mut doc).unwrap();
+/// assert_eq!(doc.to_string(), r#"{"name":"b","tags":["x","y"]}"#);
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Patch {
+    operations: Vec<Operation>,
+}
+
+impl Patch {
+    /// Creates an empty patch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a patch from its JSON form, an array of operation objects.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PatchError::Invalid`] if the value is not an array or an
+    /// operation is malformed.
+    pub fn from_json(value: &JsonValue) -> Result<Self, PatchError> {
+        let items = value.as_array().ok_or_else(|| PatchError::Invalid {
+            index: 0,
+            reason: format!("patch is {}, not an array", value.type_name()),
+        })?;
+        let operations = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| Operation::from_json(item, index))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { operations })
+    }
+
+    /// Returns the patch in its JSON form.
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.operations.iter().map(Operation::to_json).collect())
+    }
+
+    /// Returns the operations in order.
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Returns the number of operations.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Returns `true` if the patch has no operations.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Appends an operation.
+    pub fn push(&mut self, operation: Operation) {
+        self.operations.push(operation);
+    }
+
+    /// Applies every operation in order.
+    ///
+    /// Application is atomic: if any operation fails, `target` is left
+    /// unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first operation that fails.
+    pub fn apply(&self, target: &mut JsonValue) -> Result<(), PatchError> {
+        let mut patched = target.clone();
+        for (index, operation) in self.operations.iter().enumerate() {
+            operation.apply(&mut patched, index)?;
+        }
+        *target = patched;
+        Ok(())
+    }
+}
+
+impl FromIterator<Operation> for Patch {
+    fn from_iter<I: IntoIterator<Item = Operation>>(iter: I) -> Self {
+        Self {
+            operations: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl fmt::Display for Patch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_json(), f)
+    }
+}
+
+/// Errors from reading or applying a [`Patch`].
+///
+/// Every variant carries the index of the operation that failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchError {
+    /// The patch document or an operation in it is malformed, or an
+    /// operation is impossible (moving a value into its own child).
+    Invalid {
+        /// Index of the offending operation.
+        index: usize,
+        /// What is wrong with it.
+        reason: String,
+    },
+    /// A `path` or `from` does not resolve, or its parent does not exist.
+    Missing {
+        /// Index of the offending operation.
+        index: usize,
+        /// The path that could not be resolved.
+        path: JsonPath,
+    },
+    /// A `test` operation found a different value.
+    TestFailed {
+        /// Index of the offending operation.
+        index: usize,
+        /// The path that was tested.
+        path: JsonPath,
+    },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::Invalid { index, reason } => {
+                write!(f, "Invalid patch operation {}: {}", index, reason)
+            }
+            PatchError::Missing { index, path } => {
+                write!(
+                    f,
+                    "Patch operation {}: no value at {:?}",
+                    index,
+                    path.to_pointer()
+                )
+            }
+            PatchError::TestFailed { index, path } => {
+                write!(
+                    f,
+                    "Patch operation {}: test failed at {:?}",
+                    index,
+                    path.to_pointer()
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for PatchError {}
+
+/// Computes a patch that turns `from` into `to`.
+///
+/// Objects are compared member by member and arrays element by element,
+/// so unchanged parts produce no operations. Array elements are matched
+/// by position: growth and shrinkage happen at the end, and an insertion
+/// in the middle shows up as replacements. Applying the result to `from`
+/// always yields a value equal to `to`.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::parse_json;
+/// use rust_json_parser::patch::diff;
+///
+/// let from = parse_json(r#"{"a": 1, "b": [1, 2], "c": true}"#)?;
+/// let to = parse_json(r#"{"a": 2, "b": [1], "d": null}"#)?;
+/// let patch = diff(&from, &to);
+/// assert_eq!(
+///     patch.to_string(),
+///     r#"[{"op":"replace","path":"/a","value":2},{"op":"remove","path":"/b/1"},{"op":"remove","path":"/c"},{"op":"add","path":"/d","value":null}]"#
+/// );
+///
+/// let mut patched = from.clone();
+/// patch.apply(&mut patched).unwrap();
+/// assert_eq!(patched, to);
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+pub fn diff(from: &JsonValue, to: &JsonValue) -> Patch {
+    let mut patch = Patch::new();
+    diff_into(from, to, &mut JsonPath::root(), &mut patch);
+    patch
+}
+
+fn diff_into(from: &JsonValue, to: &JsonValue, path: &mut JsonPath, patch: &mut Patch) {
+    if from == to {
+        return;
+    }
+    match (from, to) {
+        (JsonValue::Object(a), JsonValue::Object(b)) => diff_objects(a, b, path, patch),
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            for (index, (x, y)) in a.iter().zip(b).enumerate() {
+                path.push(index);
+                diff_into(x, y, path, patch);
+                path.pop();
+            }
+            // Remove from the back so earlier indices stay valid.
+            for index in (b.len()..a.len()).rev() {
+                patch.push(Operation::Remove {
+                    path: path.join(index),
+                });
+            }
+            for (index, value) in b.iter().enumerate().skip(a.len()) {
+                patch.push(Operation::Add {
+                    path: path.join(index),
+                    value: value.clone(),
+                });
+            }
+        }
+        _ => patch.push(Operation::Replace {
+            path: path.clone(),
+            value: to.clone(),
+        }),
+    }
+}
+
+fn diff_objects(from: &JsonMap, to: &JsonMap, path: &mut JsonPath, patch: &mut Patch) {
+    for (key, a) in from {
+        path.push(key.as_str());
+        match to.get(key) {
+            Some(b) => diff_into(a, b, path, patch),
+            None => patch.push(Operation::Remove { path: path.clone() }),
+        }
+        path.pop();
+    }
+    for (key, b) in to {
+        if !from.contains_key(key) {
+            patch.push(Operation::Add {
+                path: path.join(key.as_str()),
+                value: b.clone(),
+            });
+        }
+    }
+}
+
+fn missing(path: &JsonPath, index: usize) -> PatchError {
+    PatchError::Missing {
+        index,
+        path: path.clone(),
+    }
+}
+
+fn resolve_mut<'a>(
+    target: &'a mut JsonValue,
+    path: &JsonPath,
+    index: usize,
+) -> Result<&'a mut JsonValue, PatchError> {
+    path.segments()
+        .iter()
+        .try_fold(target, |current, segment| child_mut(current, segment))
+        .ok_or_else(|| missing(path, index))
+}
+
+fn child_mut<'a>(value: &'a mut JsonValue, segment: &Segment) -> Option<&'a mut JsonValue> {
+    match (value, segment) {
+        (JsonValue::Object(map), segment) => map.get_mut(&segment.to_string()),
+        (JsonValue::Array(arr), Segment::Index(i)) => arr.get_mut(*i),
+        _ => None,
+    }
+}
+
+fn parent_mut<'a, 'p>(
+    target: &'a mut JsonValue,
+    path: &'p JsonPath,
+    index: usize,
+) -> Result<(&'a mut JsonValue, &'p Segment), PatchError> {
+    let (last, parents) = path
+        .segments()
+        .split_last()
+        .ok_or_else(|| missing(path, index))?;
+    let parent = parents
+        .iter()
+        .try_fold(target, |current, segment| child_mut(current, segment))
+        .ok_or_else(|| missing(path, index))?;
+    Ok((parent, last))
+}
+
+fn add(
+    target: &mut JsonValue,
+    path: &JsonPath,
+    value: JsonValue,
+    index: usize,
+) -> Result<(), PatchError> {
+    if path.is_empty() {
+        *target = value;
+        return Ok(());
+    }
+    let (parent, last) = parent_mut(target, path, index)?;
+    match (parent, last) {
+        (JsonValue::Object(map), segment) => {
+            map.insert(segment.to_string(), value);
+        }
+        (JsonValue::Array(arr), Segment::Key(key)) if key == "-" => arr.push(value),
+        (JsonValue::Array(arr), Segment::Index(i)) if *i <= arr.len() => arr.insert(*i, value),
+        _ => return Err(missing(path, index)),
+    }
+    Ok(())
+}
+
+fn remove(target: &mut JsonValue, path: &JsonPath, index: usize) -> Result<JsonValue, PatchError> {
+    if path.is_empty() {
+        return Err(PatchError::Invalid {
+            index,
+            reason: "cannot remove the root".to_string(),
+        });
+    }
+    let (parent, last) = parent_mut(target, path, index)?;
+    match (parent, last) {
+        (JsonValue::Object(map), segment) => map.remove(&segment.to_string()),
+        (JsonValue::Array(arr), Segment::Index(i)) if *i < arr.len() => Some(arr.remove(*i)),
+        _ => None,
+    }
+    .ok_or_else(|| missing(path, index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn patch(text: &str) -> Patch {
+        Patch::from_json(&parse_json(text).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_rfc_examples() {
+        let cases = [
+            (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#,
+                r#"{"foo":"bar","baz":"qux"}"#,
+            ),
+            (
+                r#"{"foo": ["bar", "baz"]}"#,
+                r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#,
+                r#"{"foo":["bar","qux","baz"]}"#,
+            ),
+            (
+                r#"{"baz": "qux", "foo": "bar"}"#,
+                r#"[{"op": "remove", "path": "/baz"}]"#,
+                r#"{"foo":"bar"}"#,
+            ),
+            (
+                r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+                r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+                r#"{"foo":{"bar":"baz"},"qux":{"corge":"grault","thud":"fred"}}"#,
+            ),
+            (
+                r#"{"foo": ["all", "grass", "cows", "eat"]}"#,
+                r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#,
+                r#"{"foo":["all","cows","eat","grass"]}"#,
+            ),
+            (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "copy", "from": "/foo", "path": "/~1"}]"#,
+                r#"{"foo":"bar","/":"bar"}"#,
+            ),
+            (
+                r#"{"a": 1}"#,
+                r#"[{"op": "replace", "path": "", "value": [1]}]"#,
+                "[1]",
+            ),
+        ];
+        for (doc, ops, expected) in cases {
+            let mut value = parse_json(doc).unwrap();
+            patch(ops).apply(&mut value).unwrap();
+            assert_eq!(value.to_string(), expected, "{}", ops);
+        }
+    }
+
+    #[test]
+    fn test_failure_leaves_target_unchanged() {
+        let original = parse_json(r#"{"a": [1, 2]}"#).unwrap();
+        let cases = [
+            (
+                r#"[{"op": "remove", "path": "/a/0"}, {"op": "test", "path": "/a/0", "value": 1}]"#,
+                PatchError::TestFailed {
+                    index: 1,
+                    path: JsonPath::parse_pointer("/a/0").unwrap(),
+                },
+            ),
+            (
+                r#"[{"op": "add", "path": "/a/5", "value": 0}]"#,
+                PatchError::Missing {
+                    index: 0,
+                    path: JsonPath::parse_pointer("/a/5").unwrap(),
+                },
+            ),
+            (
+                r#"[{"op": "replace", "path": "/b", "value": 0}]"#,
+                PatchError::Missing {
+                    index: 0,
+                    path: JsonPath::parse_pointer("/b").unwrap(),
+                },
+            ),
+        ];
+        for (ops, expected) in cases {
+            let mut value = original.clone();
+            assert_eq!(patch(ops).apply(&mut value), Err(expected));
+            assert_eq!(value, original);
+        }
+        let mut value = original.clone();
+        let err = patch(r#"[{"op": "move", "from": "/a", "path": "/a/0"}]"#)
+            .apply(&mut value)
+            .unwrap_err();
+        assert!(matches!(err, PatchError::Invalid { index: 0, .. }));
+    }
+
+    #[test]
+    fn test_malformed_operations() {
+        let cases = [
+            (r#"{"op": "add"}"#, "patch is object, not an array"),
+            (r#"[1]"#, "operation is number, not an object"),
+            (
+                r#"[{"op": "add", "path": "/a"}]"#,
+                r#"missing member "value""#,
+            ),
+            (r#"[{"op": "jump", "path": "/a"}]"#, r#"unknown op "jump""#),
+            (
+                r#"[{"op": "remove", "path": "a"}]"#,
+                r#""a" is not a JSON Pointer"#,
+            ),
+        ];
+        for (text, reason) in cases {
+            match Patch::from_json(&parse_json(text).unwrap()) {
+                Err(PatchError::Invalid { reason: r, .. }) => assert_eq!(r, reason),
+                other => panic!("Expected Invalid for {}, got {:?}", text, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_diff_round_trips() {
+        let pairs = [
+            (
+                r#"{"a": [1, 2, 3], "b": {"c": 1}}"#,
+                r#"{"a": [1, 5], "b": {"c": 1, "d": 2}}"#,
+            ),
+            (r#"[1]"#, r#"[1, [2], {"x": null}]"#),
+            (r#"{"a~b": 1, "c/d": 2}"#, r#"{"a~b": 2}"#),
+            ("1", r#"{"a": 1}"#),
+            ("null", "null"),
+        ];
+        for (a, b) in pairs {
+            let (a, b) = (parse_json(a).unwrap(), parse_json(b).unwrap());
+            let generated = diff(&a, &b);
+            let reread = Patch::from_json(&parse_json(&generated.to_string()).unwrap()).unwrap();
+            assert_eq!(reread, generated);
+            let mut patched = a.clone();
+            generated.apply(&mut patched).unwrap();
+            assert_eq!(patched, b);
+        }
+        assert!(diff(&JsonValue::Null, &JsonValue::Null).is_empty());
+    }
+}
@@ -0,0 +1,282 @@
+//! Locations inside a JSON document.
+//!
+//! A [`JsonPath`](crate::path::JsonPath) is a sequence of object keys and
+//! array indices leading from the root of a document to one of its values.
+//! Paths can be parsed from JSON Pointer syntax (`/users/0/name`) or from
+//! the dotted syntax accepted by
+//! [`JsonValue::get_path_as`](crate::value::JsonValue::get_path_as)
+//! (`users.0.name`), and printed back in either form.
+//!
+//! Errors, equivalence differences, and validation reports carry a
+//! `JsonPath` rather than a preformatted string, so callers can inspect the
+//! segments or choose how to render them.
+
+use std::fmt;
+
+use crate::pointer::{escape_segment, parse_index, parse_pointer};
+
+/// One step in a [`JsonPath`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Segment {
+    /// An object member name.
+    Key(String),
+    /// An array position.
+    Index(usize),
+}
+
+impl Segment {
+    /// Parses a single reference token: decimal digits without leading
+    /// zeros become an [`Index`](Segment::Index), anything else a
+    /// [`Key`](Segment::Key).
+    fn from_token(token: String) -> Self {
+        match parse_index(&token) {
+            Some(index) => Segment::Index(index),
+            None => Segment::Key(token),
+        }
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Key(key) => f.write_str(key),
+            Segment::Index(index) => write!(f, "{}", index),
+        }
+    }
+}
+
+impl From<&str> for Segment {
+    fn from(key: &str) -> Self {
+        Segment::Key(key.to_string())
+    }
+}
+
+impl From<String> for Segment {
+    fn from(key: String) -> Self {
+        Segment::Key(key)
+    }
+}
+
+impl From<usize> for Segment {
+    fn from(index: usize) -> Self {
+        Segment::Index(index)
+    }
+}
+
+/// A location in a JSON document, as a sequence of [`Segment`]s.
+///
+/// The empty path refers to the root. `Display` prints the path as a JSON
+/// Pointer; use [`to_dotted`](JsonPath::to_dotted) for the dotted form.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::parse_json;
+/// use rust_json_parser::path::{JsonPath, Segment};
+///
+/// let path = JsonPath::parse_pointer("/users/0/name").unwrap();
+/// assert_eq!(path.segments()[1], Segment::Index(0));
+/// assert_eq!(path.to_string(), "/users/0/name");
+/// assert_eq!(path.to_dotted(), "users.0.name");
+///
+/// let doc = parse_json(r#"{"users": [{"name": "Alice"}]}"#)?;
+/// assert_eq!(doc.at(&path).and_then(|v| v.as_str()), Some("Alice"));
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    /// Creates the root path.
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Parses a JSON Pointer (RFC 6901).
+    ///
+    /// Reference tokens that look like array indices become
+    /// [`Segment::Index`]; resolving an index against an object looks up
+    /// its decimal form as a key, so no information is lost.
+    ///
+    /// Returns `None` if the pointer is neither empty nor starts with `/`.
+    pub fn parse_pointer(pointer: &str) -> Option<Self> {
+        let segments = parse_pointer(pointer)?
+            .into_iter()
+            .map(Segment::from_token)
+            .collect();
+        Some(Self { segments })
+    }
+
+    /// Parses a dot-separated path such as `users.0.name`.
+    ///
+    /// The empty string is the root. Dotted paths cannot express keys that
+    /// contain `.`; use [`parse_pointer`](JsonPath::parse_pointer) for those.
+    pub fn parse_dotted(path: &str) -> Self {
+        if path.is_empty() {
+            return Self::root();
+        }
+        let segments = path
+            .split('.')
+            .map(|token| Segment::from_token(token.to_string()))
+            .collect();
+        Self { segments }
+    }
+
+    /// Returns the segments from the root outward.
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Returns the number of segments.
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns `true` for the root path.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Returns the path to the parent value, or `None` for the root.
+    pub fn parent(&self) -> Option<JsonPath> {
+        let (_, parent) = self.segments.split_last()?;
+        Some(Self {
+            segments: parent.to_vec(),
+        })
+    }
+
+    /// Returns the last segment, or `None` for the root.
+    pub fn last(&self) -> Option<&Segment> {
+        self.segments.last()
+    }
+
+    /// Appends a segment.
+    pub fn push(&mut self, segment: impl Into<Segment>) {
+        self.segments.push(segment.into());
+    }
+
+    /// Removes and returns the last segment.
+    pub fn pop(&mut self) -> Option<Segment> {
+        self.segments.pop()
+    }
+
+    /// Returns a copy of this path with `segment` appended.
+    pub fn join(&self, segment: impl Into<Segment>) -> JsonPath {
+        let mut path = self.clone();
+        path.push(segment);
+        path
+    }
+
+    /// Formats the path as a JSON Pointer (`""` for the root).
+    pub fn to_pointer(&self) -> String {
+        self.to_string()
+    }
+
+    /// Formats the path in dotted syntax (`""` for the root).
+    ///
+    /// Keys are written as-is, so the result is ambiguous for keys that
+    /// contain `.`.
+    pub fn to_dotted(&self) -> String {
+        let segments: Vec<String> = self.segments.iter().map(Segment::to_string).collect();
+        segments.join(".")
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            match segment {
+                Segment::Key(key) => write!(f, "/{}", escape_segment(key))?,
+                Segment::Index(index) => write!(f, "/{}", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<Segment> for JsonPath {
+    fn from_iter<I: IntoIterator<Item = Segment>>(iter: I) -> Self {
+        Self {
+            segments: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<Segment> for JsonPath {
+    fn extend<I: IntoIterator<Item = Segment>>(&mut self, iter: I) {
+        self.segments.extend(iter);
+    }
+}
+
+impl<'a> IntoIterator for &'a JsonPath {
+    type Item = &'a Segment;
+    type IntoIter = std::slice::Iter<'a, Segment>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.segments.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::JsonValue;
+
+    #[test]
+    fn test_pointer_round_trip() {
+        let path = JsonPath::parse_pointer("/a~1b/~0c/3/007").unwrap();
+        assert_eq!(
+            path.segments(),
+            [
+                Segment::Key("a/b".to_string()),
+                Segment::Key("~c".to_string()),
+                Segment::Index(3),
+                Segment::Key("007".to_string()),
+            ]
+        );
+        assert_eq!(path.to_string(), "/a~1b/~0c/3/007");
+        assert_eq!(JsonPath::parse_pointer("no-slash"), None);
+        assert_eq!(JsonPath::parse_pointer("").unwrap(), JsonPath::root());
+    }
+
+    #[test]
+    fn test_dotted_syntax() {
+        let path = JsonPath::parse_dotted("user.tags.0");
+        assert_eq!(path.len(), 3);
+        assert_eq!(path.last(), Some(&Segment::Index(0)));
+        assert_eq!(path.to_dotted(), "user.tags.0");
+        assert_eq!(path.to_pointer(), "/user/tags/0");
+        assert!(JsonPath::parse_dotted("").is_empty());
+    }
+
+    #[test]
+    fn test_build_and_parent() {
+        let mut path = JsonPath::root();
+        path.push("items");
+        path.push(2);
+        assert_eq!(path.join("id").to_string(), "/items/2/id");
+        assert_eq!(
+            path.parent().map(|p| p.to_string()),
+            Some("/items".to_string())
+        );
+        assert_eq!(path.pop(), Some(Segment::Index(2)));
+        assert_eq!(JsonPath::root().parent(), None);
+    }
+
+    #[test]
+    fn test_at_resolves_segments() {
+        let doc = crate::parser::parse_json(r#"{"a": [{"b": null}], "1": true}"#).unwrap();
+        assert_eq!(
+            doc.at(&JsonPath::parse_pointer("/a/0/b").unwrap()),
+            Some(&JsonValue::Null)
+        );
+        assert_eq!(
+            doc.at(&JsonPath::parse_pointer("/1").unwrap()),
+            Some(&JsonValue::Boolean(true))
+        );
+        assert_eq!(doc.at(&JsonPath::parse_pointer("/a/x").unwrap()), None);
+        assert_eq!(doc.at(&JsonPath::root()), Some(&doc));
+    }
+}
@@ -0,0 +1,70 @@
+//! JSON Pointer (RFC 6901) helpers shared across modules.
+
+/// Splits a pointer into unescaped reference tokens.
+///
+/// Lenient: a missing leading `/` is tolerated, which suits pointer
+/// patterns written by hand in test rules.
+#[cfg_attr(not(feature = "test-support"), allow(dead_code))]
+pub(crate) fn split_pointer(pointer: &str) -> Vec<String> {
+    if pointer.is_empty() {
+        return Vec::new();
+    }
+    pointer
+        .trim_start_matches('/')
+        .split('/')
+        .map(unescape_segment)
+        .collect()
+}
+
+/// Parses a pointer strictly: it must be empty or start with `/`.
+pub(crate) fn parse_pointer(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    let rest = pointer.strip_prefix('/')?;
+    Some(rest.split('/').map(unescape_segment).collect())
+}
+
+/// Parses an array index token: decimal digits without leading zeros.
+pub(crate) fn parse_index(segment: &str) -> Option<usize> {
+    if segment.is_empty()
+        || (segment.len() > 1 && segment.starts_with('0'))
+        || !segment.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    segment.parse().ok()
+}
+
+pub(crate) fn escape_segment(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape_segment(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pointer() {
+        assert_eq!(parse_pointer(""), Some(vec![]));
+        assert_eq!(
+            parse_pointer("/a~1b/~01"),
+            Some(vec!["a/b".to_string(), "~1".to_string()])
+        );
+        assert_eq!(parse_pointer("/"), Some(vec![String::new()]));
+        assert_eq!(parse_pointer("a"), None);
+    }
+
+    #[test]
+    fn test_parse_index() {
+        assert_eq!(parse_index("0"), Some(0));
+        assert_eq!(parse_index("12"), Some(12));
+        assert_eq!(parse_index("01"), None);
+        assert_eq!(parse_index("-1"), None);
+        assert_eq!(parse_index(""), None);
+    }
+}
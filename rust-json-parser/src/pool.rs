@@ -0,0 +1,681 @@
+//! Batch parsing into reusable pooled storage.
+//!
+//! Parsing each record of a large batch into its own
+//! [`JsonValue`](crate::value::JsonValue) allocates every string, array,
+//! and object separately, and freeing the batch frees each of them again.
+//! A [`ValuePool`](crate::pool::ValuePool) instead stores every record of
+//! a batch in two flat buffers: one for the values and one for the text
+//! of all strings and keys. Records are read through borrowed
+//! [`PooledValue`](crate::pool::PooledValue) cursors, and
+//! [`ValuePool::clear`](crate::pool::ValuePool::clear) drops the whole
+//! batch at once while keeping the buffers for the next one. After the
+//! first few batches have grown the buffers, parsing allocates nothing.
+
+use std::ops::Range;
+
+use crate::error::JsonError;
+use crate::tape::Kind;
+use crate::tokenizer::{
+    Token, decode_unicode_escape, escape_char, is_number_byte, keyword_token, number_token,
+};
+use crate::value::{JsonNumber, JsonValue};
+
+#[derive(Debug, Clone)]
+enum Node {
+    Null,
+    Boolean(bool),
+    Number(JsonNumber),
+    // Byte range in the pool's text buffer. Object keys are stored as
+    // string nodes directly before their values.
+    String(Range<usize>),
+    // `next` is the index of the first node after the subtree.
+    Array { len: usize, next: usize },
+    Object { len: usize, next: usize },
+}
+
+/// Reusable storage for a batch of parsed records.
+///
+/// Fill the pool with [`parse_ndjson`](Self::parse_ndjson) or
+/// [`parse_array`](Self::parse_array), read the records, then
+/// [`clear`](Self::clear) it before the next batch. Each parse call either
+/// adds all of its records or, on error, none of them.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::pool::ValuePool;
+///
+/// let mut pool = ValuePool::new();
+/// let mut total = 0.0;
+/// for batch in ["{\"n\": 1}\n{\"n\": 2}\n", "{\"n\": 3}\n"] {
+///     pool.clear();
+///     pool.parse_ndjson(batch)?;
+///     for record in pool.records() {
+///         total += record.get("n").and_then(|n| n.as_f64()).unwrap_or(0.0);
+///     }
+/// }
+/// assert_eq!(total, 6.0);
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ValuePool {
+    nodes: Vec<Node>,
+    text: String,
+    // Index of each record's root node.
+    records: Vec<usize>,
+}
+
+impl ValuePool {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a pool with room for `values` values and `text` bytes of
+    /// string and key text, so that the first batch does not grow the
+    /// buffers step by step.
+    pub fn with_capacity(values: usize, text: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(values),
+            text: String::with_capacity(text),
+            records: Vec::new(),
+        }
+    }
+
+    /// Parses newline-delimited JSON, adding one record per non-blank
+    /// line. Returns the number of records added.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError`] if any line is not exactly one valid JSON
+    /// value. Positions refer to `input` as a whole. No records are added
+    /// on error.
+    pub fn parse_ndjson(&mut self, input: &str) -> Result<usize, JsonError> {
+        let mark = self.mark();
+        let mut offset = 0;
+        for line in input.split_inclusive('\n') {
+            let start = offset;
+            offset += line.len();
+            if line.trim_ascii().is_empty() {
+                continue;
+            }
+            let mut builder = Builder {
+                input: &input[..offset],
+                position: start,
+                pool: self,
+            };
+            if let Err(e) = builder.record() {
+                self.rollback(mark);
+                return Err(e.locate(input));
+            }
+        }
+        Ok(self.records.len() - mark.2)
+    }
+
+    /// Parses a JSON array, adding each of its elements as a record.
+    /// Returns the number of records added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::pool::ValuePool;
+    ///
+    /// let mut pool = ValuePool::new();
+    /// assert_eq!(pool.parse_array(r#"[{"id": "a"}, {"id": "b"}]"#)?, 2);
+    /// assert_eq!(pool.get(1).and_then(|r| r.get("id")).and_then(|id| id.as_str()), Some("b"));
+    /// assert!(pool.parse_array(r#"{"id": "c"}"#).is_err());
+    /// assert_eq!(pool.len(), 2);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError`] if the input is not a single valid JSON
+    /// array. No records are added on error.
+    pub fn parse_array(&mut self, input: &str) -> Result<usize, JsonError> {
+        let mark = self.mark();
+        let mut builder = Builder {
+            input,
+            position: 0,
+            pool: self,
+        };
+        if let Err(e) = builder.records() {
+            self.rollback(mark);
+            return Err(e.locate(input));
+        }
+        Ok(self.records.len() - mark.2)
+    }
+
+    /// Returns the number of records in the pool.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns `true` if the pool holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the record at `index`.
+    pub fn get(&self, index: usize) -> Option<PooledValue<'_>> {
+        self.records.get(index).map(|&node| PooledValue {
+            pool: self,
+            index: node,
+        })
+    }
+
+    /// Iterates over the records in the order they were parsed.
+    pub fn records(&self) -> impl Iterator<Item = PooledValue<'_>> {
+        self.records.iter().map(|&node| PooledValue {
+            pool: self,
+            index: node,
+        })
+    }
+
+    /// Drops every record at once, keeping the buffers for reuse.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.text.clear();
+        self.records.clear();
+    }
+
+    fn mark(&self) -> (usize, usize, usize) {
+        (self.nodes.len(), self.text.len(), self.records.len())
+    }
+
+    fn rollback(&mut self, (nodes, text, records): (usize, usize, usize)) {
+        self.nodes.truncate(nodes);
+        self.text.truncate(text);
+        self.records.truncate(records);
+    }
+
+    // Index of the first node after the subtree rooted at `index`.
+    fn next(&self, index: usize) -> usize {
+        match &self.nodes[index] {
+            Node::Array { next, .. } | Node::Object { next, .. } => *next,
+            _ => index + 1,
+        }
+    }
+}
+
+/// A cursor onto one value stored in a [`ValuePool`].
+///
+/// Reading never allocates; strings are borrowed from the pool.
+#[derive(Debug, Clone, Copy)]
+pub struct PooledValue<'p> {
+    pool: &'p ValuePool,
+    index: usize,
+}
+
+impl<'p> PooledValue<'p> {
+    fn node(&self) -> &'p Node {
+        &self.pool.nodes[self.index]
+    }
+
+    /// Returns the JSON type of this value.
+    pub fn kind(&self) -> Kind {
+        match self.node() {
+            Node::Null => Kind::Null,
+            Node::Boolean(_) => Kind::Boolean,
+            Node::Number(_) => Kind::Number,
+            Node::String(_) => Kind::String,
+            Node::Array { .. } => Kind::Array,
+            Node::Object { .. } => Kind::Object,
+        }
+    }
+
+    /// Returns the number of elements or members, or `0` for scalars.
+    pub fn len(&self) -> usize {
+        match self.node() {
+            Node::Array { len, .. } | Node::Object { len, .. } => *len,
+            _ => 0,
+        }
+    }
+
+    /// Returns `true` if this is an empty container or a scalar.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if this value is `null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self.node(), Node::Null)
+    }
+
+    /// Returns the boolean, if this is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.node() {
+            Node::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the number, if this is one.
+    pub fn as_number(&self) -> Option<&'p JsonNumber> {
+        match self.node() {
+            Node::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as an `f64`, if this is a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(JsonNumber::as_f64)
+    }
+
+    /// Returns the number as an `i64`, if this is an integer in range.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(JsonNumber::as_i64)
+    }
+
+    /// Returns the string, if this is one.
+    pub fn as_str(&self) -> Option<&'p str> {
+        match self.node() {
+            Node::String(range) => Some(&self.pool.text[range.clone()]),
+            _ => None,
+        }
+    }
+
+    /// Looks up an object member by key. If the key appears more than
+    /// once, the last occurrence wins, as in [`JsonParser`].
+    ///
+    /// [`JsonParser`]: crate::parser::JsonParser
+    pub fn get(&self, key: &str) -> Option<PooledValue<'p>> {
+        self.members()
+            .filter(|(k, _)| *k == key)
+            .last()
+            .map(|(_, v)| v)
+    }
+
+    /// Looks up an array element by index.
+    pub fn get_index(&self, index: usize) -> Option<PooledValue<'p>> {
+        self.elements().nth(index)
+    }
+
+    /// Iterates over the elements of an array. Yields nothing for other
+    /// kinds.
+    pub fn elements(&self) -> impl Iterator<Item = PooledValue<'p>> + 'p {
+        let pool = self.pool;
+        let (mut i, end) = match self.node() {
+            Node::Array { next, .. } => (self.index + 1, *next),
+            _ => (0, 0),
+        };
+        std::iter::from_fn(move || {
+            if i >= end {
+                return None;
+            }
+            let value = PooledValue { pool, index: i };
+            i = pool.next(i);
+            Some(value)
+        })
+    }
+
+    /// Iterates over the members of an object as `(key, value)` pairs.
+    /// Yields nothing for other kinds.
+    pub fn members(&self) -> impl Iterator<Item = (&'p str, PooledValue<'p>)> + 'p {
+        let pool = self.pool;
+        let (mut i, end) = match self.node() {
+            Node::Object { next, .. } => (self.index + 1, *next),
+            _ => (0, 0),
+        };
+        std::iter::from_fn(move || {
+            if i >= end {
+                return None;
+            }
+            let key = PooledValue { pool, index: i }.as_str()?;
+            let value = PooledValue { pool, index: i + 1 };
+            i = pool.next(i + 1);
+            Some((key, value))
+        })
+    }
+
+    /// Copies this value and its subtree out of the pool into an owned
+    /// [`JsonValue`].
+    pub fn to_value(&self) -> JsonValue {
+        match self.node() {
+            Node::Null => JsonValue::Null,
+            Node::Boolean(b) => JsonValue::Boolean(*b),
+            Node::Number(n) => JsonValue::Number(n.clone()),
+            Node::String(range) => JsonValue::String(self.pool.text[range.clone()].to_string()),
+            Node::Array { .. } => JsonValue::Array(self.elements().map(|e| e.to_value()).collect()),
+            Node::Object { .. } => JsonValue::Object(
+                self.members()
+                    .map(|(k, v)| (k.to_string(), v.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+struct Builder<'a, 'p> {
+    input: &'a str,
+    position: usize,
+    pool: &'p mut ValuePool,
+}
+
+impl Builder<'_, '_> {
+    // One record spanning the rest of the input.
+    fn record(&mut self) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        self.pool.records.push(self.pool.nodes.len());
+        self.value()?;
+        self.end()
+    }
+
+    // One record per element of an array spanning the whole input.
+    fn records(&mut self) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        if self.peek() != Some(b'[') {
+            return Err(self.unexpected("array"));
+        }
+        self.position += 1;
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return self.end();
+        }
+        loop {
+            self.pool.records.push(self.pool.nodes.len());
+            self.value()?;
+            if !self.separator(b']', "comma or closing bracket")? {
+                return self.end();
+            }
+        }
+    }
+
+    fn end(&mut self) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        if self.position < self.input.len() {
+            return Err(self.unexpected("end of input"));
+        }
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\n' | b'\t' | b'\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.position).copied()
+    }
+
+    fn unexpected(&self, expected: &str) -> JsonError {
+        match self.input[self.position..].chars().next() {
+            Some(c) => JsonError::UnexpectedToken {
+                expected: expected.to_string(),
+                found: c.to_string(),
+                position: self.position,
+                line: 0,
+                column: 0,
+            },
+            None => JsonError::UnexpectedEndOfInput {
+                expected: expected.to_string(),
+                position: self.position,
+                line: 0,
+                column: 0,
+            },
+        }
+    }
+
+    // After an element or member: consumes a comma and returns `true`, or
+    // consumes `close` and returns `false`.
+    fn separator(&mut self, close: u8, expected: &str) -> Result<bool, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b',') => {
+                self.position += 1;
+                self.skip_whitespace();
+                Ok(true)
+            }
+            Some(b) if b == close => {
+                self.position += 1;
+                Ok(false)
+            }
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    fn value(&mut self) -> Result<(), JsonError> {
+        let start = self.position;
+        let node = match self.peek() {
+            Some(b'[') => return self.array(),
+            Some(b'{') => return self.object(),
+            Some(b'"') => Node::String(self.string()?),
+            Some(b'0'..=b'9' | b'-') => {
+                let mut prev = 0u8;
+                while let Some(b) = self.peek().filter(|&b| is_number_byte(b, prev)) {
+                    self.position += 1;
+                    prev = b;
+                }
+                Node::Number(number_token(
+                    &self.input[start..self.position],
+                    start,
+                    false,
+                )?)
+            }
+            Some(b't' | b'f' | b'n') => {
+                while let Some(b'a'..=b'z') = self.peek() {
+                    self.position += 1;
+                }
+                match keyword_token(&self.input[start..self.position], start)? {
+                    Token::Boolean(b) => Node::Boolean(b),
+                    _ => Node::Null,
+                }
+            }
+            _ => return Err(self.unexpected("JSON value")),
+        };
+        self.pool.nodes.push(node);
+        Ok(())
+    }
+
+    // Decodes a string into the pool's text buffer and returns its range.
+    fn string(&mut self) -> Result<Range<usize>, JsonError> {
+        let string_start = self.position;
+        let text_start = self.pool.text.len();
+        self.position += 1; // opening quote
+        let mut seg_start = self.position;
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    self.pool
+                        .text
+                        .push_str(&self.input[seg_start..self.position]);
+                    self.position += 1;
+                    return Ok(text_start..self.pool.text.len());
+                }
+                Some(b'\\') => {
+                    self.pool
+                        .text
+                        .push_str(&self.input[seg_start..self.position]);
+                    self.position += 1;
+                    let ch = self.escape()?;
+                    self.pool.text.push(ch);
+                    seg_start = self.position;
+                }
+                Some(_) => self.position += 1,
+                None => {
+                    return Err(JsonError::UnexpectedEndOfInput {
+                        expected: "closing quote".to_string(),
+                        position: string_start,
+                        line: 0,
+                        column: 0,
+                    });
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, JsonError> {
+        match self.peek() {
+            Some(b'u') => {
+                let (ch, end) = decode_unicode_escape(self.input, self.position + 1)?;
+                self.position = end;
+                Ok(ch)
+            }
+            Some(b) => {
+                let ch = escape_char(b, self.position)?;
+                self.position += 1;
+                Ok(ch)
+            }
+            None => Err(JsonError::UnexpectedEndOfInput {
+                expected: "escape character".to_string(),
+                position: self.position,
+                line: 0,
+                column: 0,
+            }),
+        }
+    }
+
+    // Pushes a container node and returns its index; `finish` fills in its
+    // length and extent once the children are built.
+    fn open(&mut self, node: Node) -> usize {
+        self.position += 1; // '[' or '{'
+        self.skip_whitespace();
+        self.pool.nodes.push(node);
+        self.pool.nodes.len() - 1
+    }
+
+    fn finish(&mut self, index: usize, count: usize) {
+        let end = self.pool.nodes.len();
+        if let Node::Array { len, next } | Node::Object { len, next } = &mut self.pool.nodes[index]
+        {
+            *len = count;
+            *next = end;
+        }
+    }
+
+    fn array(&mut self) -> Result<(), JsonError> {
+        let index = self.open(Node::Array { len: 0, next: 0 });
+        let mut len = 0;
+        if self.peek() == Some(b']') {
+            self.position += 1;
+        } else {
+            loop {
+                self.value()?;
+                len += 1;
+                if !self.separator(b']', "comma or closing bracket")? {
+                    break;
+                }
+            }
+        }
+        self.finish(index, len);
+        Ok(())
+    }
+
+    fn object(&mut self) -> Result<(), JsonError> {
+        let index = self.open(Node::Object { len: 0, next: 0 });
+        let mut len = 0;
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+        } else {
+            loop {
+                if self.peek() != Some(b'"') {
+                    return Err(self.unexpected("string key"));
+                }
+                let key = self.string()?;
+                self.pool.nodes.push(Node::String(key));
+                self.skip_whitespace();
+                if self.peek() != Some(b':') {
+                    return Err(self.unexpected("colon"));
+                }
+                self.position += 1;
+                self.skip_whitespace();
+                self.value()?;
+                len += 1;
+                if !self.separator(b'}', "comma or closing brace")? {
+                    break;
+                }
+            }
+        }
+        self.finish(index, len);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_records_match_parser() {
+        let lines = [
+            r#"{"id": 1, "tags": ["a", "b\nc"], "meta": {"ok": true, "x": null}}"#,
+            r#"[-1.5e3, "é", {}, []]"#,
+            r#""plain""#,
+        ];
+        let mut pool = ValuePool::new();
+        assert_eq!(pool.parse_ndjson(&lines.join("\r\n\n")).unwrap(), 3);
+        for (record, line) in pool.records().zip(lines) {
+            assert_eq!(record.to_value(), parse_json(line).unwrap());
+        }
+
+        let record = pool.get(0).unwrap();
+        assert_eq!(record.kind(), Kind::Object);
+        assert_eq!(record.len(), 3);
+        let tags = record.get("tags").unwrap();
+        assert_eq!(tags.get_index(1).and_then(|t| t.as_str()), Some("b\nc"));
+        assert!(tags.get_index(2).is_none());
+        assert_eq!(
+            record
+                .get("meta")
+                .and_then(|m| m.get("ok"))
+                .and_then(|v| v.as_bool()),
+            Some(true)
+        );
+        assert_eq!(record.get("id").and_then(|v| v.as_i64()), Some(1));
+        assert!(pool.get(3).is_none());
+    }
+
+    #[test]
+    fn test_parse_array_records() {
+        let mut pool = ValuePool::new();
+        assert_eq!(pool.parse_array(" [ ] ").unwrap(), 0);
+        assert_eq!(pool.parse_array(r#"[1, {"a": [2]}, "s"]"#).unwrap(), 3);
+        let values: Vec<JsonValue> = pool.records().map(|r| r.to_value()).collect();
+        assert_eq!(
+            values,
+            parse_json(r#"[1, {"a": [2]}, "s"]"#)
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .clone()
+        );
+    }
+
+    #[test]
+    fn test_error_rolls_back_batch() {
+        let mut pool = ValuePool::new();
+        pool.parse_ndjson("{\"a\": 1}\n").unwrap();
+        let err = pool
+            .parse_ndjson("[1]\n{\"b\": 2}\n{\"c\" 3}\n")
+            .unwrap_err();
+        assert!(matches!(err, JsonError::UnexpectedToken { .. }));
+        assert_eq!((err.line(), err.column()), (3, 6));
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.get(0).unwrap().to_value().to_string(), r#"{"a":1}"#);
+
+        let err = pool.parse_array("[1, 2").unwrap_err();
+        assert!(matches!(err, JsonError::UnexpectedEndOfInput { .. }));
+        assert!(pool.parse_array("[1] 2").is_err());
+        assert!(pool.parse_ndjson("1 2\n").is_err());
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn test_clear_reuses_buffers() {
+        let mut pool = ValuePool::with_capacity(64, 64);
+        let batch = "{\"name\": \"alpha\"}\n{\"name\": \"beta\"}\n";
+        pool.parse_ndjson(batch).unwrap();
+        let capacity = (pool.nodes.capacity(), pool.text.capacity());
+        for _ in 0..10 {
+            pool.clear();
+            assert!(pool.is_empty());
+            pool.parse_ndjson(batch).unwrap();
+        }
+        assert_eq!(pool.len(), 2);
+        assert_eq!((pool.nodes.capacity(), pool.text.capacity()), capacity);
+    }
+}
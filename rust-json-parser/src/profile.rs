@@ -0,0 +1,178 @@
+//! Per-parse performance reports.
+//!
+//! With the `profiling` feature enabled, every
+//! [`JsonParser`](crate::parser::JsonParser) records a
+//! [`ParseProfile`](crate::profile::ParseProfile) for its most recent
+//! parse: how long tokenizing and tree building took, how many heap
+//! blocks the parse allocated, and how many tokens it buffered. Services
+//! can log or aggregate these to attribute a regression to the tokenizer
+//! or the tree builder without attaching an external profiler.
+
+use std::fmt;
+use std::ops::AddAssign;
+use std::time::Duration;
+
+use crate::tokenizer::Token;
+use crate::value::JsonValue;
+
+/// Timing and allocation figures for one parse.
+///
+/// Returned by [`JsonParser::profile`](crate::parser::JsonParser::profile).
+/// Profiles of several parses can be summed with `+=`: durations and
+/// allocations add up, and `peak_tokens` keeps the largest value.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::JsonParser;
+/// use rust_json_parser::profile::ParseProfile;
+///
+/// let mut parser = JsonParser::new();
+/// let mut total = ParseProfile::default();
+/// for message in [r#"{"id": 1}"#, r#"["a", "b", "c"]"#] {
+///     parser.parse(message)?;
+///     total += *parser.profile();
+/// }
+/// assert_eq!(total.peak_tokens, 1);
+/// assert!(total.allocations >= 4);
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseProfile {
+    /// Time spent turning the input text into tokens. Zero when parsing
+    /// pre-built tokens.
+    pub tokenize: Duration,
+    /// Time spent building the value tree from tokens.
+    pub build: Duration,
+    /// Heap blocks allocated by the parse: one per non-empty string and
+    /// preserved raw number, one per non-empty array, one or two per
+    /// non-empty object (members plus hash index). Reallocations while a
+    /// container grows are not counted.
+    pub allocations: usize,
+    /// The number of tokens buffered at once: one when
+    /// [`parse`](crate::parser::JsonParser::parse) pulls them as it goes,
+    /// all of them when parsing pre-built tokens.
+    pub peak_tokens: usize,
+}
+
+impl ParseProfile {
+    /// Returns the total time spent, tokenizing and building together.
+    pub fn total(&self) -> Duration {
+        self.tokenize + self.build
+    }
+}
+
+impl AddAssign for ParseProfile {
+    fn add_assign(&mut self, other: Self) {
+        self.tokenize += other.tokenize;
+        self.build += other.build;
+        self.allocations += other.allocations;
+        self.peak_tokens = self.peak_tokens.max(other.peak_tokens);
+    }
+}
+
+impl fmt::Display for ParseProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tokenize {:?}, build {:?}, {} allocations, {} tokens",
+            self.tokenize, self.build, self.allocations, self.peak_tokens
+        )
+    }
+}
+
+/// Counts the heap blocks owned by freshly produced tokens.
+pub(crate) fn token_allocations(tokens: &[Token]) -> usize {
+    tokens
+        .iter()
+        .filter(|token| match token {
+            Token::String(s) => s.capacity() > 0,
+            Token::Number(n) => n.as_raw_str().is_some(),
+            _ => false,
+        })
+        .count()
+}
+
+/// Counts the heap blocks owned by containers in a built tree. Strings are
+/// not counted here: they were moved out of tokens already counted.
+pub(crate) fn container_allocations(value: &JsonValue) -> usize {
+    match value {
+        JsonValue::Array(elements) => {
+            usize::from(elements.capacity() > 0)
+                + elements.iter().map(container_allocations).sum::<usize>()
+        }
+        JsonValue::Object(map) => {
+            map.allocations() + map.values().map(container_allocations).sum::<usize>()
+        }
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::JsonParser;
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn test_profile_counts() {
+        let mut parser = JsonParser::new();
+        parser.parse(r#"{"a": ["x", ""], "b": {}}"#).unwrap();
+        let profile = *parser.profile();
+        // Tokens are pulled one at a time rather than buffered.
+        assert_eq!(profile.peak_tokens, 1);
+        // Keys "a", "b" and "x"; the outer object and the array.
+        assert_eq!(profile.allocations, 5);
+        assert_eq!(profile.total(), profile.tokenize + profile.build);
+
+        parser.parse("[1]").unwrap();
+        assert_eq!(parser.profile().peak_tokens, 1);
+        assert_eq!(parser.profile().allocations, 1);
+    }
+
+    #[test]
+    fn test_profile_raw_numbers_and_tokens() {
+        let mut parser = JsonParser::new().preserve_raw_numbers(true);
+        parser.parse("[1.0, 2]").unwrap();
+        assert_eq!(parser.profile().allocations, 3);
+
+        let tokens = Tokenizer::new(r#"["s"]"#).tokenize().unwrap();
+        let mut parser = JsonParser::from_tokens(tokens);
+        parser.parse_tokens().unwrap();
+        let profile = parser.profile();
+        assert_eq!(profile.tokenize, Duration::ZERO);
+        assert_eq!(profile.peak_tokens, 3);
+        assert_eq!(profile.allocations, 2);
+    }
+
+    #[test]
+    fn test_profile_recorded_on_error() {
+        let mut parser = JsonParser::new();
+        assert!(parser.parse(r#"["a" "b"]"#).is_err());
+        assert_eq!(parser.profile().peak_tokens, 1);
+        assert_eq!(parser.profile().allocations, 2);
+    }
+
+    #[test]
+    fn test_profile_add_assign() {
+        let mut total = ParseProfile {
+            tokenize: Duration::from_millis(1),
+            build: Duration::from_millis(2),
+            allocations: 3,
+            peak_tokens: 10,
+        };
+        total += ParseProfile {
+            tokenize: Duration::from_millis(4),
+            build: Duration::ZERO,
+            allocations: 1,
+            peak_tokens: 7,
+        };
+        assert_eq!(total.total(), Duration::from_millis(7));
+        assert_eq!(total.allocations, 4);
+        assert_eq!(total.peak_tokens, 10);
+        assert_eq!(
+            total.to_string(),
+            "tokenize 5ms, build 2ms, 4 allocations, 10 tokens"
+        );
+    }
+}
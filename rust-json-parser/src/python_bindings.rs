@@ -1,6 +1,5 @@
 //! Python bindings for the JSON parser using PyO3.
 
-use std::collections::HashMap;
 use std::fs;
 use std::time::Instant;
 
@@ -9,8 +8,9 @@ use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 
 use crate::error::JsonError;
-use crate::value::JsonFormat;
+use crate::map::JsonMap;
 use crate::value::JsonValue;
+use crate::value::pretty_print;
 
 /// Convert JsonValue to Python native types.
 impl<'py> IntoPyObject<'py> for JsonValue {
@@ -22,7 +22,7 @@ impl<'py> IntoPyObject<'py> for JsonValue {
         match self {
             JsonValue::Null => Ok(py.None().into_bound(py)),
             JsonValue::Boolean(b) => Ok(b.into_pyobject(py)?.to_owned().into_any()),
-            JsonValue::Number(n) => Ok(n.into_pyobject(py)?.to_owned().into_any()),
+            JsonValue::Number(n) => Ok(n.as_f64().into_pyobject(py)?.to_owned().into_any()),
             JsonValue::String(s) => Ok(s.into_pyobject(py)?.into_any()),
             JsonValue::Array(arr) => {
                 let py_list = PyList::empty(py);
@@ -76,7 +76,7 @@ fn py_to_json_value(obj: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
         return Ok(JsonValue::Boolean(b));
     }
     if let Ok(n) = obj.extract::<f64>() {
-        return Ok(JsonValue::Number(n));
+        return Ok(JsonValue::Number(n.into()));
     }
     if let Ok(s) = obj.extract::<String>() {
         return Ok(JsonValue::String(s));
@@ -89,7 +89,7 @@ fn py_to_json_value(obj: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
         return Ok(JsonValue::Array(items));
     }
     if let Ok(dict) = obj.downcast::<PyDict>() {
-        let mut map = HashMap::with_capacity(dict.len());
+        let mut map = JsonMap::with_capacity(dict.len());
         for (key, value) in dict.iter() {
             let key_str = key.extract::<String>()?;
             map.insert(key_str, py_to_json_value(&value)?);
@@ -116,67 +116,6 @@ fn dumps(obj: &Bound<'_, PyAny>, indent: Option<usize>) -> PyResult<String> {
     }
 }
 
-/// Recursively format a JsonValue with indentation.
-///
-/// Primitives reuse Display. Array and Object need custom handling
-/// for indentation and sorted keys.
-fn pretty_print(value: &JsonValue, indent_size: usize, depth: usize) -> String {
-    match value {
-        JsonValue::Array(arr) => {
-            if arr.is_empty() {
-                return "[]".to_string();
-            }
-            let inner_indent = " ".repeat(indent_size * (depth + 1));
-            let outer_indent = " ".repeat(indent_size * depth);
-            // TODO: estimate, +8 per element (~6 value + comma + newline), +4 for "[\n" and "\n]"
-            let mut result = String::with_capacity(
-                arr.len() * (inner_indent.len() + 8) + outer_indent.len() + 4,
-            );
-            result.push_str("[\n");
-            for (i, item) in arr.iter().enumerate() {
-                result.push_str(&inner_indent);
-                result.push_str(&pretty_print(item, indent_size, depth + 1));
-                if i < arr.len() - 1 {
-                    result.push(',');
-                }
-                result.push('\n');
-            }
-            result.push_str(&outer_indent);
-            result.push(']');
-            result
-        }
-        JsonValue::Object(map) => {
-            if map.is_empty() {
-                return "{}".to_string();
-            }
-            let inner_indent = " ".repeat(indent_size * (depth + 1));
-            let outer_indent = " ".repeat(indent_size * depth);
-            // TODO: estimate, +20 per entry (~6 key + 2 ": " + ~8 value + comma + newline), +4 for "{\n" and "\n}"
-            let mut result = String::with_capacity(
-                map.len() * (inner_indent.len() + 20) + outer_indent.len() + 4,
-            );
-            result.push_str("{\n");
-            let mut entries: Vec<(&String, &JsonValue)> = map.iter().collect();
-            entries.sort_by_key(|(k, _)| *k);
-            for (i, (key, val)) in entries.iter().enumerate() {
-                result.push_str(&inner_indent);
-                result.push_str(&key.to_json_string());
-                result.push_str(": ");
-                result.push_str(&pretty_print(val, indent_size, depth + 1));
-                if i < entries.len() - 1 {
-                    result.push(',');
-                }
-                result.push('\n');
-            }
-            result.push_str(&outer_indent);
-            result.push('}');
-            result
-        }
-        // Null, Boolean, Number, String: reuse Display
-        _ => value.to_string(),
-    }
-}
-
 /// Benchmarks JSON parsing performance comparing Rust, Python json, and simplejson.
 ///
 /// Runs the specified number of iterations for each parser and returns
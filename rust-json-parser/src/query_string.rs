@@ -0,0 +1,446 @@
+//! Converting between objects and URL query strings.
+//!
+//! [`to_query_string`](crate::query_string::to_query_string) writes an
+//! object as `name=value` pairs joined by `&`, and
+//! [`from_query_string`](crate::query_string::from_query_string) reads them
+//! back. Nesting uses the bracket notation of PHP and Rails: `a[b]=1` is
+//! member `b` of object `a`, `a[0]=1` is element 0 of array `a`, and
+//! `a[]=1` appends to array `a`. A name repeated without brackets collects
+//! its values into an array, as `tag=x&tag=y` does in most web frameworks.
+//!
+//! Query strings have no types. Numbers and booleans are written as their
+//! JSON text, and every value reads back as a string. The
+//! [`ConversionReport`](crate::interop::ConversionReport) returned by the
+//! writer lists what does not come back with the same structure: nulls,
+//! empty arrays and objects, and keys that read back as brackets.
+
+use std::error;
+use std::fmt::{self, Write};
+
+use crate::interop::ConversionReport;
+use crate::map::JsonMap;
+use crate::parser::DEFAULT_MAX_DEPTH;
+use crate::path::{JsonPath, Segment};
+use crate::value::JsonValue;
+
+/// A query string parameter that could not be placed in the result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryStringError {
+    /// The parameter's name, decoded.
+    pub key: String,
+    /// What was wrong.
+    pub message: String,
+}
+
+impl fmt::Display for QueryStringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid query string parameter {:?}: {}",
+            self.key, self.message
+        )
+    }
+}
+
+impl error::Error for QueryStringError {}
+
+/// Writes the members of `value` as a query string, without a leading `?`.
+///
+/// Arrays of scalars are written with `[]` and any other array with
+/// element indices. Everything but ASCII letters, digits, and `-._~` is
+/// percent-encoded, except the brackets that give the nesting. The report
+/// lists nulls (written as empty values), empty arrays and objects (left
+/// out), keys that can read back as nesting, and a `value` that is not
+/// an object.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::json;
+/// use rust_json_parser::query_string::to_query_string;
+///
+/// let params = json!({"q": "json & rust", "page": 2, "sort": ["-date", "title"],
+///                     "filter": {"tags": [{"name": "cli"}], "draft": null}});
+/// let (query, report) = to_query_string(&params);
+/// assert_eq!(
+///     query,
+///     "q=json%20%26%20rust&page=2&sort[]=-date&sort[]=title&filter[tags][0][name]=cli&filter[draft]="
+/// );
+/// assert_eq!(
+///     report.to_string(),
+///     "\"/filter/draft\": approximated (null written as an empty value)"
+/// );
+/// ```
+pub fn to_query_string(value: &JsonValue) -> (String, ConversionReport) {
+    let mut report = ConversionReport::new();
+    let mut out = String::new();
+    let JsonValue::Object(map) = value else {
+        report.dropped(
+            JsonPath::root(),
+            format!("{} is not an object of parameters", value.type_name()),
+        );
+        return (out, report);
+    };
+    let mut path = JsonPath::root();
+    for (key, member) in map {
+        path.push(Segment::Key(key.clone()));
+        if key.contains(['[', ']']) {
+            report.approximated(path.clone(), "brackets in the key read back as nesting");
+        }
+        let mut name = String::new();
+        encode(&mut name, key);
+        write_pairs(&mut out, &mut name, member, &mut path, &mut report);
+        path.pop();
+    }
+    (out, report)
+}
+
+/// Reads a query string into an object, with or without a leading `?`.
+///
+/// `+` is read as a space, and percent-encoded bytes that are not valid
+/// UTF-8 are replaced with U+FFFD. A `%` not followed by two hex digits is
+/// kept as it is. Parameters without `=` have the empty string as their
+/// value.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::json;
+/// use rust_json_parser::query_string::from_query_string;
+///
+/// let value = from_query_string("?a=1&b[]=2&b[]=3&c[d][e]=x+y&tag=%F0%9F%A6%80&tag=z")?;
+/// assert_eq!(
+///     value,
+///     json!({"a": "1", "b": ["2", "3"], "c": {"d": {"e": "x y"}}, "tag": ["🦀", "z"]})
+/// );
+///
+/// let err = from_query_string("a=1&a[b]=2").unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     r#"Invalid query string parameter "a[b]": cannot add member "b" to a string"#
+/// );
+/// # Ok::<(), rust_json_parser::query_string::QueryStringError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`QueryStringError`] if a parameter's brackets conflict with
+/// an earlier one, give an index past the end of an array, or nest more
+/// than [`DEFAULT_MAX_DEPTH`] levels deep.
+pub fn from_query_string(query: &str) -> Result<JsonValue, QueryStringError> {
+    let query = query.strip_prefix('?').unwrap_or(query);
+    let mut root = JsonValue::Object(JsonMap::new());
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = decode(key);
+        let steps = parse_key(&key);
+        if steps.len() > DEFAULT_MAX_DEPTH {
+            return Err(QueryStringError {
+                message: format!("nests deeper than {} levels", DEFAULT_MAX_DEPTH),
+                key,
+            });
+        }
+        insert(&mut root, steps, decode(value))
+            .map_err(|message| QueryStringError { key, message })?;
+    }
+    Ok(root)
+}
+
+// Writes the pairs for `value` under the encoded `name`, returning whether
+// there were any.
+fn write_pairs(
+    out: &mut String,
+    name: &mut String,
+    value: &JsonValue,
+    path: &mut JsonPath,
+    report: &mut ConversionReport,
+) -> bool {
+    let len = name.len();
+    let mut wrote = false;
+    match value {
+        JsonValue::Array(items) if items.is_empty() => {
+            report.dropped(path.clone(), "empty array has no query string form");
+        }
+        JsonValue::Object(map) if map.is_empty() => {
+            report.dropped(path.clone(), "empty object has no query string form");
+        }
+        JsonValue::Array(items) => {
+            let indexed = items
+                .iter()
+                .any(|item| matches!(item, JsonValue::Array(_) | JsonValue::Object(_)));
+            // Elements that write nothing take no index, so the ones after
+            // them still read back without a gap.
+            let mut next = 0;
+            for (i, item) in items.iter().enumerate() {
+                path.push(Segment::Index(i));
+                if indexed {
+                    let _ = write!(name, "[{}]", next);
+                } else {
+                    name.push_str("[]");
+                }
+                if write_pairs(out, name, item, path, report) {
+                    next += 1;
+                    wrote = true;
+                }
+                name.truncate(len);
+                path.pop();
+            }
+        }
+        JsonValue::Object(map) => {
+            for (key, member) in map {
+                path.push(Segment::Key(key.clone()));
+                if key.contains(['[', ']']) {
+                    report.approximated(path.clone(), "brackets in the key read back as nesting");
+                } else if !matches!(step(key), Step::Key(_)) {
+                    report.approximated(path.clone(), "the key can read back as an array index");
+                }
+                name.push('[');
+                encode(name, key);
+                name.push(']');
+                wrote |= write_pairs(out, name, member, path, report);
+                name.truncate(len);
+                path.pop();
+            }
+        }
+        scalar => {
+            if !out.is_empty() {
+                out.push('&');
+            }
+            out.push_str(name);
+            out.push('=');
+            match scalar {
+                JsonValue::String(s) => encode(out, s),
+                JsonValue::Null => {
+                    report.approximated(path.clone(), "null written as an empty value");
+                }
+                other => encode(out, &other.to_string()),
+            }
+            wrote = true;
+        }
+    }
+    wrote
+}
+
+// One bracketed part of a parameter name.
+enum Step {
+    Key(String),
+    Index(usize),
+    Append,
+}
+
+// Splits `name[a][0][]` into its steps. A name that does not have that
+// form, such as `a[b` or `[a]`, is a single key.
+fn parse_key(key: &str) -> Vec<Step> {
+    if let Some(open) = key.find('[')
+        && open > 0
+    {
+        let mut steps = vec![Step::Key(key[..open].to_string())];
+        let mut rest = &key[open..];
+        while let Some(inner) = rest.strip_prefix('[') {
+            let Some(close) = inner.find(']') else {
+                break;
+            };
+            steps.push(step(&inner[..close]));
+            rest = &inner[close + 1..];
+        }
+        if rest.is_empty() {
+            return steps;
+        }
+    }
+    vec![Step::Key(key.to_string())]
+}
+
+fn step(segment: &str) -> Step {
+    if segment.is_empty() {
+        return Step::Append;
+    }
+    let canonical = segment == "0" || !segment.starts_with('0');
+    match segment.parse() {
+        Ok(index) if canonical && segment.bytes().all(|b| b.is_ascii_digit()) => Step::Index(index),
+        _ => Step::Key(segment.to_string()),
+    }
+}
+
+fn insert(root: &mut JsonValue, steps: Vec<Step>, value: String) -> Result<(), String> {
+    let mut current = root;
+    let mut steps = steps.into_iter().peekable();
+    while let Some(step) = steps.next() {
+        // A new container's type is decided by the step into it; the
+        // last step gets a placeholder for the value.
+        let empty = match steps.peek() {
+            None => JsonValue::Null,
+            Some(Step::Key(_)) => JsonValue::Object(JsonMap::new()),
+            Some(_) => JsonValue::Array(Vec::new()),
+        };
+        // Digits in brackets index an array, but name a member of an
+        // object that already exists.
+        let step = match (step, &*current) {
+            (Step::Index(i), JsonValue::Object(_)) => Step::Key(i.to_string()),
+            (step, _) => step,
+        };
+        current = match (step, current) {
+            (Step::Key(key), JsonValue::Object(map)) => {
+                if !map.contains_key(&key) {
+                    map.insert(key.clone(), empty);
+                }
+                map.get_mut(&key).expect("inserted above")
+            }
+            (Step::Index(i), JsonValue::Array(items)) => {
+                if i > items.len() {
+                    return Err(format!(
+                        "index {} is past the end of an array of {}",
+                        i,
+                        items.len()
+                    ));
+                }
+                if i == items.len() {
+                    items.push(empty);
+                }
+                &mut items[i]
+            }
+            (Step::Append, JsonValue::Array(items)) => {
+                items.push(empty);
+                items.last_mut().expect("pushed above")
+            }
+            (step, other) => {
+                let step = match step {
+                    Step::Key(key) => format!("member {:?}", key),
+                    Step::Index(i) => format!("element {}", i),
+                    Step::Append => "an element".to_string(),
+                };
+                let article = if matches!(other, JsonValue::Array(_) | JsonValue::Object(_)) {
+                    "an"
+                } else {
+                    "a"
+                };
+                return Err(format!(
+                    "cannot add {} to {} {}",
+                    step,
+                    article,
+                    other.type_name()
+                ));
+            }
+        };
+    }
+    match current {
+        JsonValue::Null => *current = JsonValue::String(value),
+        JsonValue::String(_) => {
+            let first = std::mem::replace(current, JsonValue::Null);
+            *current = JsonValue::Array(vec![first, JsonValue::String(value)]);
+        }
+        JsonValue::Array(items) => items.push(JsonValue::String(value)),
+        _ => return Err("cannot give an object a value".to_string()),
+    }
+    Ok(())
+}
+
+fn encode(out: &mut String, text: &str) {
+    for &b in text.as_bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            let _ = write!(out, "%{:02X}", b);
+        }
+    }
+}
+
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if let Some(byte) = bytes.get(i + 1..i + 3).and_then(hex_byte) => {
+                out.push(byte);
+                i += 2;
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    match String::from_utf8(out) {
+        Ok(text) => text,
+        Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+    }
+}
+
+fn hex_byte(pair: &[u8]) -> Option<u8> {
+    if !pair.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interop::LossKind;
+    use crate::json;
+
+    #[test]
+    fn test_round_trip_of_nested_strings() {
+        let value = json!({
+            "user": {"name": "Zoë O'Neil", "roles": ["admin", "a&b=c"]},
+            "items": [{"id": "1", "opts": ["x"]}, [], {"id": "2"}, ["p", "q"]],
+            "": "empty name",
+            "plus": "1+1 %"
+        });
+        let (query, report) = to_query_string(&value);
+        assert_eq!(report.of_kind(LossKind::Dropped).count(), 1);
+        assert_eq!(report.losses.len(), 1);
+        let mut expected = value.clone();
+        expected["items"].as_array_mut().unwrap().remove(1);
+        assert_eq!(from_query_string(&query).unwrap(), expected);
+        assert!(query.contains("items[1][id]=2&items[2][]=p"));
+    }
+
+    #[test]
+    fn test_reported_keys_and_roots() {
+        let (query, report) = to_query_string(&json!({"a[b]": 1, "o": {"0": true, "": "e"}}));
+        assert_eq!(query, "a%5Bb%5D=1&o[0]=true&o[]=e");
+        let paths: Vec<String> = report.losses.iter().map(|l| l.path.to_pointer()).collect();
+        assert_eq!(paths, ["/a[b]", "/o/0", "/o/"]);
+        assert_eq!(
+            from_query_string(&query).unwrap(),
+            json!({"a": {"b": "1"}, "o": ["true", "e"]})
+        );
+
+        let (query, report) = to_query_string(&json!([1]));
+        assert!(query.is_empty());
+        assert!(!report.is_lossless());
+        assert_eq!(to_query_string(&json!({})).0, "");
+    }
+
+    #[test]
+    fn test_reading_edge_cases() {
+        assert_eq!(
+            from_query_string("a&&b=&c=%zz%4&d[=1&[e]=2&f[x]y=3&g=1=2").unwrap(),
+            json!({"a": "", "b": "", "c": "%zz%4", "d[": "1", "[e]": "2", "f[x]y": "3", "g": "1=2"})
+        );
+        assert_eq!(
+            from_query_string("a[]=1&a=2&a[2]=3&b=%FF").unwrap(),
+            json!({"a": ["1", "2", "3"], "b": "\u{fffd}"})
+        );
+        assert_eq!(
+            from_query_string("a[01]=1&a[1]=2").unwrap(),
+            json!({"a": {"01": "1", "1": "2"}})
+        );
+        assert_eq!(from_query_string("").unwrap(), json!({}));
+
+        let message = |query: &str| from_query_string(query).unwrap_err().message;
+        assert_eq!(
+            message("a[2]=1"),
+            "index 2 is past the end of an array of 0"
+        );
+        assert_eq!(
+            message("a[x]=1&a[]=2"),
+            "cannot add an element to an object"
+        );
+        assert_eq!(message("a[x]=1&a=2"), "cannot give an object a value");
+        assert_eq!(
+            message(&format!("a{}=1", "[]".repeat(200))),
+            "nests deeper than 128 levels"
+        );
+    }
+}
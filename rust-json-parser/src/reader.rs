@@ -0,0 +1,726 @@
+//! Tokenizer that pulls its input from any [`std::io::Read`].
+//!
+//! [`Tokenizer`](crate::tokenizer::Tokenizer) needs the whole document in
+//! memory before it starts. A [`ReadTokenizer`](crate::reader::ReadTokenizer)
+//! instead reads the source in fixed-size chunks and produces one token at
+//! a time, so files and sockets can be tokenized without first loading
+//! them into a `String`. Only the current chunk and the token being built
+//! are held in memory.
+//!
+//! Wrapping the source in a [`DigestReader`](crate::reader::DigestReader)
+//! computes a checksum of the raw bytes in the same pass, so large files
+//! need not be read twice to be both validated and fingerprinted.
+
+use std::io::{self, Read};
+
+use crate::error::{JsonError, line_column};
+use crate::tokenizer::{
+    Token, decode_unicode_escape, escape_char, is_number_byte, keyword_token, number_token,
+    number_too_long, string_too_long,
+};
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Produces [`Token`]s on demand from a byte source.
+///
+/// Tokens are read with [`next_token`](Self::next_token) or by iterating.
+/// Errors are reported as [`io::Error`]: read failures pass through
+/// unchanged, and malformed JSON becomes an error wrapping the
+/// [`JsonError`] (see its `From` conversion), with the line and column
+/// already filled in. Strings that are not valid UTF-8 are reported as
+/// [`JsonError::InvalidUtf8`] unless [`lossy_utf8`](Self::lossy_utf8) is
+/// set. After the first error, iteration ends.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::reader::ReadTokenizer;
+/// use rust_json_parser::tokenizer::Token;
+///
+/// let input = br#"{"ids": [1, 2]}"#;
+/// let tokens: Vec<Token> = ReadTokenizer::new(&input[..]).collect::<Result<_, _>>()?;
+/// assert_eq!(tokens.len(), 9);
+/// assert_eq!(tokens[1], Token::String("ids".to_string()));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct ReadTokenizer<R> {
+    reader: R,
+    buffer: Box<[u8]>,
+    // Unconsumed bytes are buffer[cursor..filled].
+    cursor: usize,
+    filled: usize,
+    // Byte offset, 1-based line, and 1-based character column of the
+    // next unconsumed byte.
+    position: usize,
+    line: usize,
+    column: usize,
+    // Mark of the first byte of the last token, or of the end of input.
+    token_start: (usize, usize, usize),
+    // Reused for the bytes of the token being built.
+    scratch: Vec<u8>,
+    preserve_raw_numbers: bool,
+    lossy_utf8: bool,
+    max_string_len: usize,
+    max_number_len: usize,
+    finished: bool,
+}
+
+impl<R: Read> ReadTokenizer<R> {
+    /// Creates a tokenizer that reads from `reader`.
+    ///
+    /// Nothing is read until the first token is requested. The reader is
+    /// read in chunks, so wrapping it in a `BufReader` is unnecessary.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: vec![0; CHUNK_SIZE].into_boxed_slice(),
+            cursor: 0,
+            filled: 0,
+            position: 0,
+            line: 1,
+            column: 1,
+            token_start: (0, 1, 1),
+            scratch: Vec::new(),
+            preserve_raw_numbers: false,
+            lossy_utf8: false,
+            max_string_len: usize::MAX,
+            max_number_len: usize::MAX,
+            finished: false,
+        }
+    }
+
+    /// Keeps the exact source text of every number token, as
+    /// [`Tokenizer::preserve_raw_numbers`](crate::tokenizer::Tokenizer::preserve_raw_numbers)
+    /// does.
+    pub fn preserve_raw_numbers(mut self, preserve: bool) -> Self {
+        self.preserve_raw_numbers = preserve;
+        self
+    }
+
+    /// Replaces invalid UTF-8 in strings with U+FFFD instead of failing,
+    /// as [`JsonParser::lossy_utf8`](crate::parser::JsonParser::lossy_utf8)
+    /// does. Positions still refer to the input bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::reader::ReadTokenizer;
+    /// use rust_json_parser::tokenizer::Token;
+    ///
+    /// let mut tokenizer = ReadTokenizer::new(&b"\"\xff\""[..]).lossy_utf8(true);
+    /// assert_eq!(tokenizer.next_token()?, Some(Token::String("\u{FFFD}".to_string())));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn lossy_utf8(mut self, lossy: bool) -> Self {
+        self.lossy_utf8 = lossy;
+        self
+    }
+
+    /// Rejects longer strings, as
+    /// [`Tokenizer::max_string_len`](crate::tokenizer::Tokenizer::max_string_len)
+    /// does. A string is abandoned as soon as it passes the limit, so an
+    /// unterminated or enormous string never grows the buffer beyond
+    /// `max` bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::reader::ReadTokenizer;
+    ///
+    /// let huge = std::iter::once(b'"').chain(std::iter::repeat(b'a')).take(1 << 20);
+    /// let input: Vec<u8> = huge.collect();
+    /// let err = ReadTokenizer::new(&input[..]).max_string_len(1024).next_token().unwrap_err();
+    /// assert_eq!(err.to_string(), "String longer than 1024 bytes at line 1, column 1 (position 0)");
+    /// ```
+    pub fn max_string_len(mut self, max: usize) -> Self {
+        self.max_string_len = max;
+        self
+    }
+
+    /// Rejects longer numbers, as
+    /// [`Tokenizer::max_number_len`](crate::tokenizer::Tokenizer::max_number_len)
+    /// does.
+    pub fn max_number_len(mut self, max: usize) -> Self {
+        self.max_number_len = max;
+        self
+    }
+
+    /// Returns the byte offset of the next unread input byte.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the next token, or `None` at the end of the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::reader::ReadTokenizer;
+    /// use rust_json_parser::tokenizer::Token;
+    ///
+    /// let mut tokenizer = ReadTokenizer::new(&b"[true]"[..]);
+    /// assert_eq!(tokenizer.next_token()?, Some(Token::LeftBracket));
+    /// assert_eq!(tokenizer.next_token()?, Some(Token::Boolean(true)));
+    /// assert_eq!(tokenizer.next_token()?, Some(Token::RightBracket));
+    /// assert_eq!(tokenizer.next_token()?, None);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails, the input is not valid JSON at
+    /// the lexical level, or a string is not valid UTF-8.
+    pub fn next_token(&mut self) -> io::Result<Option<Token>> {
+        loop {
+            let next = self.peek()?;
+            self.token_start = self.mark();
+            let Some(b) = next else {
+                return Ok(None);
+            };
+            let token = match b {
+                b' ' | b'\n' | b'\t' | b'\r' => {
+                    self.advance();
+                    continue;
+                }
+                b'{' => self.single(Token::LeftBrace),
+                b'}' => self.single(Token::RightBrace),
+                b'[' => self.single(Token::LeftBracket),
+                b']' => self.single(Token::RightBracket),
+                b':' => self.single(Token::Colon),
+                b',' => self.single(Token::Comma),
+                b'"' => self.read_string()?,
+                b't' | b'f' | b'n' => self.read_keyword()?,
+                b'0'..=b'9' | b'-' | b'.' => self.read_number()?,
+                other => {
+                    return Err(self.locate(JsonError::UnexpectedToken {
+                        expected: "valid JSON token".to_string(),
+                        found: (other as char).to_string(),
+                        position: self.position,
+                        line: 0,
+                        column: 0,
+                    }));
+                }
+            };
+            return Ok(Some(token));
+        }
+    }
+
+    fn single(&mut self, token: Token) -> Token {
+        self.advance();
+        token
+    }
+
+    fn read_string(&mut self) -> io::Result<Token> {
+        let start = self.mark();
+        self.advance(); // consume opening quote
+        self.scratch.clear();
+        // Raw bytes since the last escape, checked for UTF-8 before the
+        // next escape or the closing quote: scratch[segment.0..] starts at
+        // input mark segment.1.
+        let mut segment = (0, self.mark());
+        loop {
+            if self.position - start.0 - 1 > self.max_string_len {
+                return Err(located(
+                    string_too_long(self.max_string_len, start.0),
+                    start,
+                ));
+            }
+            match self.peek()? {
+                Some(b'"') => {
+                    self.check_utf8(segment)?;
+                    self.advance();
+                    break;
+                }
+                Some(b'\\') => {
+                    self.check_utf8(segment)?;
+                    self.advance();
+                    self.read_escape()?;
+                    segment = (self.scratch.len(), self.mark());
+                }
+                Some(b) => {
+                    self.scratch.push(b);
+                    self.advance();
+                }
+                None => {
+                    return Err(located(
+                        JsonError::UnexpectedEndOfInput {
+                            expected: "closing quote".to_string(),
+                            position: start.0,
+                            line: 0,
+                            column: 0,
+                        },
+                        start,
+                    ));
+                }
+            }
+        }
+        match std::str::from_utf8(&self.scratch) {
+            Ok(s) => Ok(Token::String(s.to_owned())),
+            // Only reachable in lossy mode; strict mode checked every
+            // segment above.
+            Err(_) => Ok(Token::String(
+                String::from_utf8_lossy(&self.scratch).into_owned(),
+            )),
+        }
+    }
+
+    // Fails with `InvalidUtf8` if the raw string bytes since `start` are
+    // not valid UTF-8, unless decoding lossily.
+    fn check_utf8(&self, (from, start): (usize, (usize, usize, usize))) -> io::Result<()> {
+        if self.lossy_utf8 {
+            return Ok(());
+        }
+        let bytes = &self.scratch[from..];
+        let Err(e) = std::str::from_utf8(bytes) else {
+            return Ok(());
+        };
+        let valid = e.valid_up_to();
+        let prefix = std::str::from_utf8(&bytes[..valid]).unwrap_or_default();
+        let (lines, column) = line_column(prefix, valid);
+        let mark = if lines == 1 {
+            (start.0 + valid, start.1, start.2 + column - 1)
+        } else {
+            (start.0 + valid, start.1 + lines - 1, column)
+        };
+        let error = JsonError::InvalidUtf8 {
+            byte_offset: mark.0,
+            line: 0,
+            column: 0,
+        };
+        Err(located(error, mark))
+    }
+
+    fn read_escape(&mut self) -> io::Result<()> {
+        let ch = match self.peek()? {
+            Some(b'u') => {
+                self.advance();
+                let hex_start = self.mark();
+                // Four hex digits, followed by a second `\uXXXX` when they
+                // are a high surrogate.
+                let mut escape = String::with_capacity(10);
+                self.read_raw(&mut escape, 4)?;
+                if matches!(u16::from_str_radix(&escape, 16), Ok(0xD800..=0xDBFF)) {
+                    self.read_raw(&mut escape, 6)?;
+                }
+                decode_unicode_escape(&escape, 0)
+                    .map_err(|e| {
+                        // Everything before the error is ASCII.
+                        let offset = e.position();
+                        let mark = (hex_start.0 + offset, hex_start.1, hex_start.2 + offset);
+                        located(e.reposition(mark.0), mark)
+                    })?
+                    .0
+            }
+            Some(b) => {
+                let ch = escape_char(b, self.position).map_err(|e| self.locate(e))?;
+                self.advance();
+                ch
+            }
+            None => {
+                return Err(self.locate(JsonError::UnexpectedEndOfInput {
+                    expected: "escape character".to_string(),
+                    position: self.position,
+                    line: 0,
+                    column: 0,
+                }));
+            }
+        };
+        let mut utf8 = [0; 4];
+        self.scratch
+            .extend_from_slice(ch.encode_utf8(&mut utf8).as_bytes());
+        Ok(())
+    }
+
+    // Appends up to `n` more input bytes to `out`, one char per byte.
+    fn read_raw(&mut self, out: &mut String, n: usize) -> io::Result<()> {
+        for _ in 0..n {
+            let Some(b) = self.peek()? else { break };
+            out.push(b as char);
+            self.advance();
+        }
+        Ok(())
+    }
+
+    fn read_keyword(&mut self) -> io::Result<Token> {
+        let start = self.mark();
+        self.scratch.clear();
+        while let Some(b) = self.peek()?.filter(u8::is_ascii_lowercase) {
+            self.scratch.push(b);
+            self.advance();
+        }
+        let word = String::from_utf8_lossy(&self.scratch);
+        keyword_token(&word, start.0).map_err(|e| located(e, start))
+    }
+
+    fn read_number(&mut self) -> io::Result<Token> {
+        let start = self.mark();
+        self.scratch.clear();
+        let mut prev = 0u8;
+        while let Some(b) = self.peek()?.filter(|&b| is_number_byte(b, prev)) {
+            if self.scratch.len() == self.max_number_len {
+                return Err(located(
+                    number_too_long(self.max_number_len, start.0),
+                    start,
+                ));
+            }
+            self.scratch.push(b);
+            self.advance();
+            prev = b;
+        }
+        let lexeme = String::from_utf8_lossy(&self.scratch);
+        number_token(&lexeme, start.0, self.preserve_raw_numbers)
+            .map(Token::Number)
+            .map_err(|e| located(e, start))
+    }
+
+    /// Returns the position, line, and column where the last token read
+    /// started, or of the end of input once it is reached.
+    pub(crate) fn token_start(&self) -> (usize, usize, usize) {
+        self.token_start
+    }
+
+    // Position, line, and column of the next unconsumed byte.
+    fn mark(&self) -> (usize, usize, usize) {
+        (self.position, self.line, self.column)
+    }
+
+    fn locate(&self, error: JsonError) -> io::Error {
+        located(error, self.mark())
+    }
+
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        if self.cursor == self.filled {
+            self.cursor = 0;
+            self.filled = 0;
+            loop {
+                match self.reader.read(&mut self.buffer) {
+                    Ok(n) => {
+                        self.filled = n;
+                        break;
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        Ok(self.buffer[..self.filled].get(self.cursor).copied())
+    }
+
+    // Consumes the byte last returned by `peek`.
+    fn advance(&mut self) {
+        let b = self.buffer[self.cursor];
+        self.cursor += 1;
+        self.position += 1;
+        if b == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else if b & 0xC0 != 0x80 {
+            // Count characters, not UTF-8 continuation bytes.
+            self.column += 1;
+        }
+    }
+}
+
+pub(crate) fn located(error: JsonError, (_, line, column): (usize, usize, usize)) -> io::Error {
+    error.at_line_column(line, column).into()
+}
+
+impl<R: Read> Iterator for ReadTokenizer<R> {
+    type Item = io::Result<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = self.next_token().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.finished = true;
+        }
+        result
+    }
+}
+
+/// Receives the raw bytes of an input as a [`DigestReader`] reads them.
+///
+/// Closures taking `&[u8]` are digests, so any hasher can be plugged in
+/// with `|bytes: &[u8]| hasher.update(bytes)`.
+pub trait Digest {
+    /// Feeds the next bytes of the input, in order.
+    fn update(&mut self, bytes: &[u8]);
+}
+
+impl<F: FnMut(&[u8])> Digest for F {
+    fn update(&mut self, bytes: &[u8]) {
+        self(bytes)
+    }
+}
+
+/// A reader that passes every byte it reads to a [`Digest`] on the way
+/// through.
+///
+/// Pass it by `&mut` to [`parse_from_reader`](crate::parser::parse_from_reader),
+/// a [`ReadTokenizer`], or a
+/// [`JsonStreamParser`](crate::stream::JsonStreamParser), then call
+/// [`finish`](Self::finish) to digest anything the parser did not read,
+/// such as the rest of the input after a syntax error.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::parse_from_reader;
+/// use rust_json_parser::reader::{Digest, DigestReader};
+///
+/// struct Fnv1a(u64);
+///
+/// impl Digest for Fnv1a {
+///     fn update(&mut self, bytes: &[u8]) {
+///         for &b in bytes {
+///             self.0 = (self.0 ^ u64::from(b)).wrapping_mul(0x100000001b3);
+///         }
+///     }
+/// }
+///
+/// let file = br#"{"archive": [1, 2, 3]}"#;
+/// let mut input = DigestReader::new(&file[..], Fnv1a(0xcbf29ce484222325));
+/// let value = parse_from_reader(&mut input)?;
+/// assert_eq!(input.bytes_read(), file.len() as u64);
+/// let hash = input.finish()?;
+///
+/// let mut expected = Fnv1a(0xcbf29ce484222325);
+/// expected.update(file);
+/// assert_eq!(hash.0, expected.0);
+/// assert_eq!(value["archive"][2].as_f64(), Some(3.0));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct DigestReader<R, D> {
+    reader: R,
+    digest: D,
+    bytes_read: u64,
+}
+
+impl<R: Read, D: Digest> DigestReader<R, D> {
+    /// Creates a reader that feeds everything read from `reader` to
+    /// `digest`.
+    pub fn new(reader: R, digest: D) -> Self {
+        Self {
+            reader,
+            digest,
+            bytes_read: 0,
+        }
+    }
+
+    /// Returns the number of bytes read, and digested, so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Returns the digest.
+    pub fn digest(&self) -> &D {
+        &self.digest
+    }
+
+    /// Reads the rest of the input into the digest and returns it, so the
+    /// digest covers the whole input however far parsing got.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from the underlying reader.
+    pub fn finish(mut self) -> io::Result<D> {
+        io::copy(&mut self, &mut io::sink())?;
+        Ok(self.digest)
+    }
+
+    /// Returns the underlying reader and the digest, without reading
+    /// further.
+    pub fn into_parts(self) -> (R, D) {
+        (self.reader, self.digest)
+    }
+}
+
+impl<R: Read, D: Digest> Read for DigestReader<R, D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.digest.update(&buf[..n]);
+        self.bytes_read += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::Tokenizer;
+
+    // Hands out one byte per read call, so every token crosses a chunk
+    // boundary.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some((&first, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = first;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    fn json_error(error: io::Error) -> JsonError {
+        *error.into_inner().unwrap().downcast::<JsonError>().unwrap()
+    }
+
+    #[test]
+    fn test_matches_in_memory_tokenizer() {
+        let input = r#"{"name": "Zoë \"z\" é\n", "n": [-1.5e3, 0, 42], "ok": true, "x": null}"#;
+        let expected = Tokenizer::new(input).tokenize().unwrap();
+        let chunked: Vec<Token> = ReadTokenizer::new(input.as_bytes())
+            .collect::<io::Result<_>>()
+            .unwrap();
+        let trickled: Vec<Token> = ReadTokenizer::new(Trickle(input.as_bytes()))
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(chunked, expected);
+        assert_eq!(trickled, expected);
+    }
+
+    #[test]
+    fn test_error_reports_line_and_column() {
+        let input = "[\n  \"é\", 01\n]";
+        let mut tokenizer = ReadTokenizer::new(Trickle(input.as_bytes()));
+        let error = tokenizer.find_map(Result::err).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let error = json_error(error);
+        assert_eq!(error, Tokenizer::new(input).tokenize().unwrap_err());
+        assert_eq!((error.line(), error.column()), (2, 8));
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_unterminated_string_is_eof() {
+        let error = ReadTokenizer::new(&br#"["abc"#[..])
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(json_error(error).position(), 1);
+    }
+
+    #[test]
+    fn test_invalid_utf8_in_string() {
+        let error = ReadTokenizer::new(&b"\"\xff\""[..])
+            .next_token()
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(
+            json_error(error),
+            JsonError::InvalidUtf8 { byte_offset: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_invalid_utf8_matches_parse_bytes() {
+        let inputs: [&[u8]; 3] = [
+            b"[\"ok\", \"\xc3\xa9\xc3\"]",
+            b"{\"a\\n\xe9\": 1}",
+            b"[\n\"line\nbreak \xf0\x9f\x98\"]",
+        ];
+        for input in inputs {
+            let expected = crate::parser::parse_bytes(input).unwrap_err();
+            let error = ReadTokenizer::new(Trickle(input))
+                .find_map(Result::err)
+                .unwrap();
+            assert_eq!(json_error(error), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_surrogate_pairs_match_in_memory_tokenizer() {
+        for input in [
+            r#"["\ud83d\ude00", "x\uD834\uDD1Ey"]"#,
+            "[\n \"\\ud83d\\n\"]",
+            r#"["\udc00"]"#,
+            r#"["\ud800\ud800"]"#,
+            r#"["\ud800\u12"]"#,
+            r#""\ud800\"#,
+        ] {
+            let expected = Tokenizer::new(input)
+                .tokenize()
+                .map_err(|e| e.locate(input));
+            let actual = ReadTokenizer::new(Trickle(input.as_bytes()))
+                .collect::<io::Result<Vec<Token>>>()
+                .map_err(json_error);
+            assert_eq!(actual, expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_lossy_utf8() {
+        let tokens: Vec<Token> = ReadTokenizer::new(&b"[\"a\xffb\\n\xfe\"]"[..])
+            .lossy_utf8(true)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(tokens[1], Token::String("a\u{FFFD}b\n\u{FFFD}".to_string()));
+    }
+
+    #[test]
+    fn test_read_errors_pass_through() {
+        struct Broken;
+        impl Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::ConnectionReset, "gone"))
+            }
+        }
+        let error = ReadTokenizer::new(Broken).next_token().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionReset);
+    }
+
+    #[test]
+    fn test_token_length_limits() {
+        let error = ReadTokenizer::new(Trickle(b"[\"ab\", \n \"abc\"]"))
+            .max_string_len(2)
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(
+            json_error(error),
+            JsonError::StringTooLong {
+                max_len: 2,
+                position: 9,
+                line: 2,
+                column: 2,
+            }
+        );
+
+        let tokens = |max| {
+            ReadTokenizer::new(&b"[12345, 1e10]"[..])
+                .max_number_len(max)
+                .collect::<io::Result<Vec<Token>>>()
+        };
+        assert!(tokens(5).is_ok());
+        let error = json_error(tokens(4).unwrap_err());
+        assert!(matches!(
+            error,
+            JsonError::NumberTooLong { position: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_digest_reader_sees_every_byte() {
+        let input = b"[1, 2,, 3] trailing bytes";
+        let mut seen = Vec::new();
+        let mut reader =
+            DigestReader::new(Trickle(input), |bytes: &[u8]| seen.extend_from_slice(bytes));
+        let err = crate::parser::parse_from_reader(&mut reader).unwrap_err();
+        assert_eq!(
+            json_error(err).kind(),
+            crate::error::JsonErrorKind::UnexpectedToken
+        );
+        assert!(reader.bytes_read() < input.len() as u64);
+        let _ = reader.finish().unwrap();
+        assert_eq!(seen, input);
+    }
+}
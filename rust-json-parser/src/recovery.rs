@@ -0,0 +1,581 @@
+//! Parsing that keeps going after errors, for editors and linters.
+//!
+//! [`JsonParser::parse`](crate::parser::JsonParser::parse) stops at the
+//! first problem. [`parse_with_recovery`](crate::recovery::parse_with_recovery)
+//! instead records each error, resynchronizes at the next comma or
+//! closing bracket, and carries on, so one pass reports every problem in a
+//! file along with a best-effort value for what could be read.
+//!
+//! Recovery follows a few simple rules. A malformed token is reported once
+//! and skipped, and a missing value becomes `null`. A missing comma or
+//! colon is reported and assumed. Anything else unexpected is skipped up
+//! to the next comma or closer of the enclosing container. A closer that
+//! belongs to an outer container also closes the inner ones.
+
+use crate::error::{JsonError, JsonErrorKind};
+use crate::map::JsonMap;
+use crate::parser::{DuplicateKeys, JsonParser, ParserOptions};
+use crate::tokenizer::{Token, Tokenizer};
+use crate::value::JsonValue;
+
+/// Parses `input` with default options, collecting every error instead of
+/// stopping at the first.
+///
+/// Equivalent to `JsonParser::new().parse_with_recovery(input)`; see
+/// [`JsonParser::parse_with_recovery`] to recover with other options.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::recovery::parse_with_recovery;
+///
+/// let recovered = parse_with_recovery(r#"{"a": [1 2], "b": tru, "c": 3,}"#);
+/// assert_eq!(recovered.value.to_string(), r#"{"a":[1,2],"b":null,"c":3}"#);
+/// let messages: Vec<String> = recovered.errors.iter().map(|e| e.to_string()).collect();
+/// assert_eq!(
+///     messages,
+///     [
+///         "Unexpected token at line 1, column 10 (position 9): expected comma or closing bracket, found Number(2)",
+///         "Unexpected token at line 1, column 19 (position 18): expected valid JSON token, found tru",
+///         "Unexpected token at line 1, column 31 (position 30): expected string key, found }",
+///     ]
+/// );
+/// ```
+pub fn parse_with_recovery(input: &str) -> Recovered {
+    JsonParser::new().parse_with_recovery(input)
+}
+
+/// The outcome of [`parse_with_recovery`]: whatever could be read, and
+/// every error met on the way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recovered {
+    /// The value built from the input, with `null` in place of values that
+    /// could not be read. Equal to what
+    /// [`JsonParser::parse`](crate::parser::JsonParser::parse) returns when
+    /// `errors` is empty.
+    pub value: JsonValue,
+    /// The errors in input order, with line and column filled in.
+    pub errors: Vec<JsonError>,
+}
+
+impl Recovered {
+    /// Returns `true` if the input parsed without errors.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+pub(crate) fn recover(input: &str, options: ParserOptions) -> Recovered {
+    let tokenizer = Tokenizer::new(input)
+        .preserve_raw_numbers(options.preserve_raw_numbers)
+        .allow_comments(options.allow_comments)
+        .allow_single_quotes(options.allow_single_quotes)
+        .max_string_len(options.max_string_len)
+        .max_number_len(options.max_number_len)
+        .digit_separators(options.digit_separators)
+        .unicode_whitespace(options.unicode_whitespace);
+    let mut recovery = Recovery {
+        tokenizer,
+        options,
+        next: None,
+        next_position: 0,
+        open: Vec::new(),
+        errors: Vec::new(),
+        eof_reported: false,
+    };
+    recovery.pull();
+    let value = recovery.parse_document();
+    let mut errors = recovery.errors;
+    errors.sort_by_key(JsonError::position);
+    Recovered {
+        value,
+        errors: errors.into_iter().map(|e| e.locate(input)).collect(),
+    }
+}
+
+// A token, or the place of a malformed one that was already reported.
+enum Item {
+    Token(Token),
+    Invalid,
+}
+
+struct Recovery {
+    tokenizer: Tokenizer,
+    options: ParserOptions,
+    next: Option<Item>,
+    next_position: usize,
+    // The closer of each open container, innermost last.
+    open: Vec<Token>,
+    errors: Vec<JsonError>,
+    eof_reported: bool,
+}
+
+impl Recovery {
+    fn parse_document(&mut self) -> JsonValue {
+        if self.next.is_none()
+            && let Some(value) = self.options.empty_document.value()
+        {
+            return value;
+        }
+        let value = self.parse_value();
+        if self.next.is_some() {
+            self.unexpected("end of input");
+        }
+        value
+    }
+
+    fn parse_value(&mut self) -> JsonValue {
+        match &self.next {
+            Some(Item::Token(Token::LeftBracket)) => self.parse_nested(Token::RightBracket),
+            Some(Item::Token(Token::LeftBrace)) => self.parse_nested(Token::RightBrace),
+            Some(Item::Token(Token::Colon)) => {
+                self.unexpected("JSON value");
+                self.advance();
+                JsonValue::Null
+            }
+            Some(Item::Token(Token::Comma | Token::RightBracket | Token::RightBrace)) | None => {
+                self.unexpected("JSON value");
+                JsonValue::Null
+            }
+            Some(_) => match self.advance() {
+                Some(Item::Token(Token::String(s))) => JsonValue::String(s),
+                Some(Item::Token(Token::Number(n))) => JsonValue::Number(n),
+                Some(Item::Token(Token::Boolean(b))) => JsonValue::Boolean(b),
+                _ => JsonValue::Null,
+            },
+        }
+    }
+
+    fn parse_nested(&mut self, closer: Token) -> JsonValue {
+        if self.open.len() >= self.options.max_depth {
+            self.errors.push(JsonError::DepthLimitExceeded {
+                max_depth: self.options.max_depth,
+                position: self.next_position,
+                line: 0,
+                column: 0,
+            });
+            self.skip_container();
+            return JsonValue::Null;
+        }
+        self.advance(); // consume the opener
+        self.open.push(closer);
+        let value = if self.open.last() == Some(&Token::RightBracket) {
+            self.parse_array()
+        } else {
+            self.parse_object()
+        };
+        self.open.pop();
+        value
+    }
+
+    fn parse_array(&mut self) -> JsonValue {
+        let mut elements = Vec::new();
+        if self.at(&Token::RightBracket) {
+            self.advance();
+            return JsonValue::Array(elements);
+        }
+        loop {
+            elements.push(self.parse_value());
+            if !self.separator(&Token::RightBracket, "JSON value") {
+                break;
+            }
+        }
+        JsonValue::Array(elements)
+    }
+
+    fn parse_object(&mut self) -> JsonValue {
+        let mut map = JsonMap::new();
+        if self.at(&Token::RightBrace) {
+            self.advance();
+            return JsonValue::Object(map);
+        }
+        loop {
+            let key_position = self.next_position;
+            let key = match &self.next {
+                Some(Item::Token(Token::String(_))) => match self.advance() {
+                    Some(Item::Token(Token::String(key))) => Some(key),
+                    _ => unreachable!("peeked a string"),
+                },
+                Some(Item::Invalid) => {
+                    self.advance();
+                    None
+                }
+                _ => {
+                    self.unexpected("string key");
+                    self.skip_to_separator();
+                    match &self.next {
+                        Some(Item::Token(Token::Comma)) => {
+                            self.advance();
+                            continue;
+                        }
+                        Some(Item::Token(Token::RightBrace)) => {
+                            self.advance();
+                        }
+                        _ => {}
+                    }
+                    break;
+                }
+            };
+            if self.at(&Token::Colon) {
+                self.advance();
+            } else {
+                self.unexpected("colon");
+            }
+            if !self.starts_value() && !self.at(&Token::Colon) {
+                // The value is missing; leave the member out.
+                self.unexpected("JSON value");
+                if self.separator(&Token::RightBrace, "string key") {
+                    continue;
+                }
+                break;
+            }
+            let value = self.parse_value();
+            if let Some(key) = key {
+                let duplicate = map.contains_key(&key);
+                if duplicate && self.options.duplicate_keys == DuplicateKeys::Error {
+                    self.errors.push(JsonError::DuplicateKey {
+                        key: key.clone(),
+                        position: key_position,
+                        line: 0,
+                        column: 0,
+                    });
+                }
+                if !(duplicate && self.options.duplicate_keys == DuplicateKeys::FirstWins) {
+                    map.insert(key, value);
+                }
+            }
+            if !self.separator(&Token::RightBrace, "string key") {
+                break;
+            }
+        }
+        JsonValue::Object(map)
+    }
+
+    // Reads what follows a value in a container closed by `closer`.
+    // Returns `true` if another entry follows, `false` once the container
+    // has ended. `entry` is what a comma promises.
+    fn separator(&mut self, closer: &Token, entry: &str) -> bool {
+        let expected = if *closer == Token::RightBracket {
+            "comma or closing bracket"
+        } else {
+            "comma or closing brace"
+        };
+        loop {
+            match &self.next {
+                Some(Item::Token(Token::Comma)) => {
+                    self.advance();
+                    if !self.at(closer) {
+                        return true;
+                    }
+                    if !self.options.allow_trailing_commas {
+                        self.errors.push(JsonError::UnexpectedToken {
+                            expected: entry.to_string(),
+                            found: if *closer == Token::RightBracket {
+                                "]"
+                            } else {
+                                "}"
+                            }
+                            .to_string(),
+                            position: self.next_position,
+                            line: 0,
+                            column: 0,
+                        });
+                    }
+                    self.advance();
+                    return false;
+                }
+                Some(Item::Token(token)) if token == closer => {
+                    self.advance();
+                    return false;
+                }
+                Some(Item::Token(token @ (Token::RightBracket | Token::RightBrace)))
+                    if self.open.contains(token) =>
+                {
+                    // Closes an outer container too; leave it for that one.
+                    self.unexpected(expected);
+                    return false;
+                }
+                None => {
+                    self.unexpected(expected);
+                    return false;
+                }
+                Some(Item::Invalid) => {
+                    self.advance();
+                }
+                _ if self.starts_value() => {
+                    // Assume a missing comma.
+                    self.unexpected(expected);
+                    return true;
+                }
+                _ => {
+                    self.unexpected(expected);
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    // Skips tokens up to the next comma or closer at this nesting level.
+    fn skip_to_separator(&mut self) {
+        let mut depth = 0usize;
+        loop {
+            match &self.next {
+                None => return,
+                Some(Item::Token(Token::LeftBracket | Token::LeftBrace)) => depth += 1,
+                Some(Item::Token(Token::RightBracket | Token::RightBrace)) => {
+                    if depth == 0 {
+                        return;
+                    }
+                    depth -= 1;
+                }
+                Some(Item::Token(Token::Comma)) if depth == 0 => return,
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
+    // Skips the container starting at the next token, closer included.
+    fn skip_container(&mut self) {
+        let mut depth = 0usize;
+        while let Some(item) = self.advance() {
+            match item {
+                Item::Token(Token::LeftBracket | Token::LeftBrace) => depth += 1,
+                Item::Token(Token::RightBracket | Token::RightBrace) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn starts_value(&self) -> bool {
+        matches!(
+            self.next,
+            Some(Item::Invalid)
+                | Some(Item::Token(
+                    Token::LeftBracket
+                        | Token::LeftBrace
+                        | Token::String(_)
+                        | Token::Number(_)
+                        | Token::Boolean(_)
+                        | Token::Null
+                ))
+        )
+    }
+
+    fn at(&self, token: &Token) -> bool {
+        matches!(&self.next, Some(Item::Token(next)) if next == token)
+    }
+
+    // Reports the next token as unexpected. Malformed tokens were
+    // reported when they were read, and each position and the end of
+    // input only once.
+    fn unexpected(&mut self, expected: &str) {
+        if self
+            .errors
+            .last()
+            .is_some_and(|e| e.position() == self.next_position)
+        {
+            return;
+        }
+        let error = match &self.next {
+            Some(Item::Invalid) => return,
+            Some(Item::Token(token)) => JsonError::UnexpectedToken {
+                expected: expected.to_string(),
+                found: format!("{:?}", token),
+                position: self.next_position,
+                line: 0,
+                column: 0,
+            },
+            None if self.eof_reported => return,
+            None => {
+                self.eof_reported = true;
+                JsonError::UnexpectedEndOfInput {
+                    expected: expected.to_string(),
+                    position: self.next_position,
+                    line: 0,
+                    column: 0,
+                }
+            }
+        };
+        self.errors.push(error);
+    }
+
+    fn advance(&mut self) -> Option<Item> {
+        let item = self.next.take();
+        self.pull();
+        item
+    }
+
+    fn pull(&mut self) {
+        match self.tokenizer.next_token() {
+            Ok(Some((token, start))) => {
+                self.next = Some(Item::Token(token));
+                self.next_position = start;
+            }
+            Ok(None) => {
+                self.next = None;
+                self.next_position = self.tokenizer.input().len();
+            }
+            Err(error) => {
+                let position = error.position();
+                let resume = self.resume_after(&error);
+                if error.kind() == JsonErrorKind::UnexpectedEndOfInput {
+                    self.eof_reported = true;
+                }
+                self.errors.push(error);
+                self.tokenizer.seek(resume);
+                self.next = Some(Item::Invalid);
+                self.next_position = position;
+            }
+        }
+    }
+
+    // Where to carry on scanning after a malformed token: past the
+    // closing quote of a bad string, or else at the next delimiter. An
+    // unterminated string or comment runs to the end of the input.
+    fn resume_after(&self, error: &JsonError) -> usize {
+        let bytes = self.tokenizer.input().as_bytes();
+        if error.kind() == JsonErrorKind::UnexpectedEndOfInput {
+            return bytes.len();
+        }
+        let mut i = (error.position() + 1).min(bytes.len());
+        let in_string = matches!(
+            error.kind(),
+            JsonErrorKind::InvalidEscape
+                | JsonErrorKind::InvalidUnicode
+                | JsonErrorKind::UnpairedSurrogate
+                | JsonErrorKind::StringTooLong
+        );
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' if in_string => i += 1,
+                b'"' if in_string => return i + 1,
+                b'\'' if in_string && self.options.allow_single_quotes => return i + 1,
+                b',' | b':' | b'[' | b']' | b'{' | b'}' | b'"' | b' ' | b'\t' | b'\r' | b'\n'
+                    if !in_string =>
+                {
+                    return i;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        bytes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+    use crate::parser::parse_json;
+
+    fn positions(recovered: &Recovered) -> Vec<usize> {
+        recovered.errors.iter().map(JsonError::position).collect()
+    }
+
+    #[test]
+    fn test_valid_input_matches_parse() {
+        for input in ["[]", r#"{"a": [1, {"b": null}], "c": "é"}"#, " 7 "] {
+            let recovered = parse_with_recovery(input);
+            assert!(recovered.is_ok(), "{:?}", input);
+            assert_eq!(recovered.value, parse_json(input).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_first_error_matches_parse() {
+        for input in [
+            "[1,]",
+            "[1 2]",
+            r#"{"a" 1}"#,
+            "{1: 2}",
+            "[1}",
+            "[",
+            "",
+            "[1] 2",
+        ] {
+            let recovered = parse_with_recovery(input);
+            assert_eq!(
+                recovered.errors.first(),
+                parse_json(input).err().as_ref(),
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_recovers_values_around_errors() {
+        let cases = [
+            (r#"[1, @, 3]"#, json!([1, null, 3]), vec![4]),
+            (r#"[1, 2x, 3]"#, json!([1, 2, 3]), vec![5]),
+            (r#"["a\qb", "c"]"#, json!([null, "c"]), vec![4]),
+            (r#"{"a": , "b": 2}"#, json!({"b": 2}), vec![6]),
+            (
+                r#"{"a": 1, 5: 6, "b": 2}"#,
+                json!({"a": 1, "b": 2}),
+                vec![9],
+            ),
+            (r#"{"a": [1, 2}"#, json!({"a": [1, 2]}), vec![11]),
+            (r#"[{"a": 1]"#, json!([{"a": 1}]), vec![8]),
+            (r#"[1, 2"#, json!([1, 2]), vec![5]),
+            (r#"{"a": [1, "#, json!({"a": [1, null]}), vec![10]),
+            ("[:]", json!([null]), vec![1]),
+            ("[1]]", json!([1]), vec![3]),
+            (r#"["abc, 1]"#, json!([null]), vec![1]),
+        ];
+        for (input, value, errors) in cases {
+            let recovered = parse_with_recovery(input);
+            assert_eq!(recovered.value, value, "{:?}", input);
+            assert_eq!(positions(&recovered), errors, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_reports_every_error_with_location() {
+        let input = "{\n  \"a\": 01,\n  \"b\": [true false],\n  \"c\": \"ok\"\n  \"d\": nul\n}";
+        let recovered = parse_with_recovery(input);
+        let lines: Vec<(usize, usize)> = recovered
+            .errors
+            .iter()
+            .map(|e| (e.line(), e.column()))
+            .collect();
+        assert_eq!(lines, [(2, 8), (3, 14), (5, 3), (5, 8)]);
+        assert_eq!(
+            recovered.value,
+            json!({"a": null, "b": [true, false], "c": "ok", "d": null})
+        );
+    }
+
+    #[test]
+    fn test_respects_parser_options() {
+        let input = "{// note\n 'a': [1,], 'a': 2,}";
+        let recovered =
+            JsonParser::with_options(ParserOptions::lenient()).parse_with_recovery(input);
+        assert!(recovered.is_ok());
+        assert_eq!(recovered.value, json!({"a": 2}));
+
+        let recovered = JsonParser::new()
+            .duplicate_keys(DuplicateKeys::Error)
+            .max_depth(2)
+            .parse_with_recovery(r#"{"a": 1, "a": 2, "b": [[3]], "c": 4}"#);
+        assert_eq!(recovered.value, json!({"a": 2, "b": [null], "c": 4}));
+        assert_eq!(
+            recovered
+                .errors
+                .iter()
+                .map(JsonError::kind)
+                .collect::<Vec<_>>(),
+            [
+                JsonErrorKind::DuplicateKey,
+                JsonErrorKind::DepthLimitExceeded
+            ]
+        );
+    }
+}
@@ -0,0 +1,401 @@
+//! Checking what a document loses when it is parsed and written back.
+//!
+//! Rewriting a hand-maintained file through the parser is only safe if the
+//! rewritten text means the same thing.
+//! [`round_trip_check`](crate::roundtrip::round_trip_check) parses a
+//! document, serializes it compactly, and compares the two texts value by
+//! value, reporting each [`Change`](crate::roundtrip::Change) the trip
+//! introduced: numbers whose decimal value changed, duplicate members that
+//! were dropped, object members that moved, and strings whose escapes were
+//! rewritten.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::error::JsonError;
+use crate::parser::JsonParser;
+use crate::path::JsonPath;
+use crate::tokenizer::{Token, Tokenizer, matching_close};
+use crate::value::JsonValue;
+
+/// The kind of a [`Change`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// A number was written with a different decimal value, because it
+    /// could not be represented exactly.
+    PrecisionLoss,
+    /// A member was dropped because a later member had the same key.
+    DuplicateKey,
+    /// The members of an object were written in a different order.
+    KeyOrder,
+    /// A string was written with different escape sequences. The string
+    /// itself is unchanged.
+    EscapeNormalized,
+}
+
+impl ChangeKind {
+    /// Returns `true` if the change alters what the document means, which
+    /// is every kind except [`EscapeNormalized`](Self::EscapeNormalized).
+    pub fn is_semantic(self) -> bool {
+        self != ChangeKind::EscapeNormalized
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            ChangeKind::PrecisionLoss => "precision loss",
+            ChangeKind::DuplicateKey => "duplicate key dropped",
+            ChangeKind::KeyOrder => "keys reordered",
+            ChangeKind::EscapeNormalized => "escapes normalized",
+        }
+    }
+}
+
+/// One difference between a document and its re-serialized form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// What changed.
+    pub kind: ChangeKind,
+    /// Location of the changed value. For
+    /// [`KeyOrder`](ChangeKind::KeyOrder) this is the object.
+    pub path: JsonPath,
+    /// The source text. For [`KeyOrder`](ChangeKind::KeyOrder), the keys
+    /// in source order as a JSON array; for
+    /// [`DuplicateKey`](ChangeKind::DuplicateKey), the dropped value.
+    pub original: String,
+    /// The serialized text, in the same form as `original`. For
+    /// [`DuplicateKey`](ChangeKind::DuplicateKey), the value that was kept.
+    pub serialized: String,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "/".to_string()
+        } else {
+            self.path.to_string()
+        };
+        write!(
+            f,
+            "{}: {}: {} -> {}",
+            path,
+            self.kind.description(),
+            self.original,
+            self.serialized
+        )
+    }
+}
+
+/// The result of [`round_trip_check`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundTripReport {
+    /// The document serialized compactly.
+    pub output: String,
+    /// Every change, in source order.
+    pub changes: Vec<Change>,
+}
+
+impl RoundTripReport {
+    /// Returns `true` if no change alters what the document means.
+    /// Rewritten escapes are allowed.
+    pub fn is_lossless(&self) -> bool {
+        !self.changes.iter().any(|change| change.kind.is_semantic())
+    }
+
+    /// Iterates over the changes of one kind.
+    pub fn changes_of(&self, kind: ChangeKind) -> impl Iterator<Item = &Change> {
+        self.changes
+            .iter()
+            .filter(move |change| change.kind == kind)
+    }
+}
+
+/// Parses `input` with a default [`JsonParser`], serializes the result
+/// compactly, and reports what changed.
+///
+/// Whitespace, and number spellings with the same decimal value (`1.0`
+/// and `1`, `1e2` and `100`), are not changes.
+///
+/// # Errors
+///
+/// Returns [`JsonError`] if `input` is not valid JSON.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::roundtrip::{ChangeKind, round_trip_check};
+///
+/// let report = round_trip_check(r#"{"id": 12345678901234567890123, "name": "caf\u00e9"}"#)?;
+/// assert!(!report.is_lossless());
+/// assert_eq!(
+///     report.changes[0].to_string(),
+///     "/id: precision loss: 12345678901234567890123 -> 12345678901234567741440"
+/// );
+/// assert_eq!(report.changes[1].kind, ChangeKind::EscapeNormalized);
+///
+/// let report = round_trip_check("{\n  \"debug\": 1.50\n}")?;
+/// assert!(report.is_lossless());
+/// assert_eq!(report.output, r#"{"debug":1.5}"#);
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+pub fn round_trip_check(input: &str) -> Result<RoundTripReport, JsonError> {
+    let value = JsonParser::new().parse(input)?;
+    let mut tokenizer = Tokenizer::new(input);
+    let tokens = tokenizer.tokenize()?;
+    let mut checker = Checker {
+        input,
+        tokens: &tokens,
+        spans: tokenizer.spans(),
+        path: JsonPath::root(),
+        changes: Vec::new(),
+    };
+    checker.check(0, Some(&value));
+    Ok(RoundTripReport {
+        output: value.to_string(),
+        changes: checker.changes,
+    })
+}
+
+struct Checker<'a> {
+    input: &'a str,
+    tokens: &'a [Token],
+    spans: &'a [Range<usize>],
+    path: JsonPath,
+    changes: Vec<Change>,
+}
+
+impl Checker<'_> {
+    // Checks the value starting at token `at` against `value`, its parsed
+    // form, or `None` if it was dropped. Returns the index after it.
+    fn check(&mut self, at: usize, value: Option<&JsonValue>) -> usize {
+        match &self.tokens[at] {
+            Token::LeftBracket => self.check_array(at, value),
+            Token::LeftBrace => self.check_object(at, value),
+            Token::String(s) => {
+                self.compare(
+                    ChangeKind::EscapeNormalized,
+                    at,
+                    JsonValue::from(s.as_str()),
+                );
+                at + 1
+            }
+            Token::Number(n) => {
+                let serialized = JsonValue::Number(n.clone()).to_string();
+                if decimal(self.raw(at)) != decimal(&serialized) {
+                    self.push(
+                        ChangeKind::PrecisionLoss,
+                        self.raw(at).to_string(),
+                        serialized,
+                    );
+                }
+                at + 1
+            }
+            _ => at + 1,
+        }
+    }
+
+    fn check_array(&mut self, open: usize, value: Option<&JsonValue>) -> usize {
+        let elements = value.and_then(JsonValue::as_array);
+        let mut at = open + 1;
+        let mut index = 0;
+        loop {
+            match self.tokens[at] {
+                Token::RightBracket => return at + 1,
+                Token::Comma => at += 1,
+                _ => {
+                    self.path.push(index);
+                    at = self.check(at, elements.and_then(|e| e.get(index)));
+                    self.path.pop();
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    fn check_object(&mut self, open: usize, value: Option<&JsonValue>) -> usize {
+        let close = matching_close(self.tokens, open).unwrap_or(self.tokens.len() - 1);
+        let map = value.and_then(JsonValue::as_object);
+
+        // Key token indices in source order.
+        let mut members = Vec::new();
+        let mut at = open + 1;
+        while at < close {
+            if let Token::String(_) = self.tokens[at] {
+                members.push(at);
+                at = self.skip(at + 2);
+            } else {
+                at += 1;
+            }
+        }
+
+        let mut kept = Vec::new();
+        for (i, &key_at) in members.iter().enumerate() {
+            let Token::String(key) = &self.tokens[key_at] else {
+                unreachable!("member starts with a key");
+            };
+            let dropped = members[i + 1..]
+                .iter()
+                .any(|&later| matches!(&self.tokens[later], Token::String(k) if k == key));
+            self.path.push(key.as_str());
+            self.compare(
+                ChangeKind::EscapeNormalized,
+                key_at,
+                JsonValue::from(key.as_str()),
+            );
+            if dropped {
+                let kept_value = map.and_then(|m| m.get(key));
+                let end = self.skip(key_at + 2);
+                let original =
+                    self.input[self.spans[key_at + 2].start..self.spans[end - 1].end].to_string();
+                self.push(
+                    ChangeKind::DuplicateKey,
+                    original,
+                    kept_value.map_or_else(String::new, JsonValue::to_string),
+                );
+                self.check(key_at + 2, None);
+            } else {
+                kept.push(key.as_str());
+                self.check(key_at + 2, map.and_then(|m| m.get(key)));
+            }
+            self.path.pop();
+        }
+
+        if let Some(map) = map {
+            let serialized: Vec<&str> = map.keys().map(String::as_str).collect();
+            if kept != serialized {
+                self.push(ChangeKind::KeyOrder, key_list(&kept), key_list(&serialized));
+            }
+        }
+        close + 1
+    }
+
+    // Returns the index after the value starting at token `at`.
+    fn skip(&self, at: usize) -> usize {
+        match self.tokens[at] {
+            Token::LeftBrace | Token::LeftBracket => {
+                matching_close(self.tokens, at).map_or(self.tokens.len(), |close| close + 1)
+            }
+            _ => at + 1,
+        }
+    }
+
+    fn raw(&self, at: usize) -> &str {
+        &self.input[self.spans[at].clone()]
+    }
+
+    fn compare(&mut self, kind: ChangeKind, at: usize, value: JsonValue) {
+        let serialized = value.to_string();
+        if self.raw(at) != serialized {
+            self.push(kind, self.raw(at).to_string(), serialized);
+        }
+    }
+
+    fn push(&mut self, kind: ChangeKind, original: String, serialized: String) {
+        self.changes.push(Change {
+            kind,
+            path: self.path.clone(),
+            original,
+            serialized,
+        });
+    }
+}
+
+fn key_list(keys: &[&str]) -> String {
+    JsonValue::Array(keys.iter().map(|&k| JsonValue::from(k)).collect()).to_string()
+}
+
+// Reduces a number literal to its exact decimal value: sign, significant
+// digits without leading or trailing zeros, and a power of ten. All zeros
+// compare equal.
+fn decimal(literal: &str) -> (bool, String, i64) {
+    let (negative, unsigned) = match literal.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, literal),
+    };
+    let (mantissa, mut exponent) = match unsigned.find(['e', 'E']) {
+        Some(i) => {
+            let exp = &unsigned[i + 1..];
+            let saturated = if exp.starts_with('-') {
+                i64::MIN / 2
+            } else {
+                i64::MAX / 2
+            };
+            (&unsigned[..i], exp.parse::<i64>().unwrap_or(saturated))
+        }
+        None => (unsigned, 0),
+    };
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    exponent -= frac_part.len() as i64;
+    let digits = format!("{}{}", int_part, frac_part);
+    let digits = digits.trim_start_matches('0');
+    let significant = digits.trim_end_matches('0');
+    if significant.is_empty() {
+        return (false, String::new(), 0);
+    }
+    exponent += (digits.len() - significant.len()) as i64;
+    (negative, significant.to_string(), exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<(ChangeKind, String)> {
+        round_trip_check(input)
+            .unwrap()
+            .changes
+            .into_iter()
+            .map(|change| (change.kind, change.path.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_documents_have_no_changes() {
+        let report = round_trip_check(" [1.0, 1e2, -0, 0.5, \"a\", {\"k\": null}] ").unwrap();
+        assert!(report.changes.is_empty());
+        assert_eq!(report.output, r#"[1,100,-0,0.5,"a",{"k":null}]"#);
+    }
+
+    #[test]
+    fn test_precision_loss() {
+        assert_eq!(
+            kinds("[0.1, 1e23, 9007199254740993, 18446744073709551617, 1.00000000000000000001]"),
+            [
+                (ChangeKind::PrecisionLoss, "/1".to_string()),
+                (ChangeKind::PrecisionLoss, "/3".to_string()),
+                (ChangeKind::PrecisionLoss, "/4".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_keys_and_order() {
+        let report = round_trip_check(r#"{"a": {"x": 1}, "b": 2, "a": [3]}"#).unwrap();
+        let changes: Vec<String> = report.changes.iter().map(Change::to_string).collect();
+        assert_eq!(
+            changes,
+            [
+                r#"/a: duplicate key dropped: {"x": 1} -> [3]"#,
+                r#"/: keys reordered: ["b","a"] -> ["a","b"]"#,
+            ]
+        );
+        assert!(!report.is_lossless());
+    }
+
+    #[test]
+    fn test_escape_normalization_in_keys_and_values() {
+        let report = round_trip_check(r#"{"\u0061": "a\/b"}"#).unwrap();
+        assert_eq!(
+            report
+                .changes_of(ChangeKind::EscapeNormalized)
+                .map(|change| (change.original.as_str(), change.serialized.as_str()))
+                .collect::<Vec<_>>(),
+            [(r#""\u0061""#, r#""a""#), (r#""a\/b""#, r#""a/b""#)]
+        );
+        assert!(report.is_lossless());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(round_trip_check("[1,").is_err());
+    }
+}
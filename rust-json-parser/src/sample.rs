@@ -0,0 +1,251 @@
+//! Uniform random samples of NDJSON records.
+//!
+//! Looking at a few hundred records is often enough to understand a log
+//! file, but a file of billions of lines cannot be parsed into memory to
+//! pick them. A [`Sampler`](crate::sample::Sampler) streams newline
+//! delimited JSON from any [`BufRead`](std::io::BufRead) and keeps a
+//! reservoir of records, so every valid record is equally likely to be
+//! chosen whatever the length of the input. Each line is checked with the
+//! same tree-free pass as [`validate`](crate::validate::validate), and
+//! only lines that enter the reservoir are parsed into values.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufRead};
+
+use crate::error::JsonError;
+use crate::parser::{JsonParser, ParserOptions};
+use crate::validate::is_valid;
+use crate::value::JsonValue;
+
+/// A record chosen by a [`Sampler`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampledRecord {
+    /// The 1-based line number of the record in the input.
+    pub line: u64,
+    /// The parsed record.
+    pub value: JsonValue,
+}
+
+/// Counts gathered over the whole input while sampling.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SampleStats {
+    /// Lines read, including blank and invalid ones.
+    pub lines: u64,
+    /// Valid records, the population the sample was drawn from.
+    pub records: u64,
+    /// Lines containing only whitespace, which are skipped.
+    pub blank_lines: u64,
+    /// Non-blank lines that are not exactly one valid JSON value.
+    pub invalid_lines: u64,
+    /// Bytes read, including line terminators.
+    pub bytes: u64,
+    /// Length in bytes of the shortest and longest valid record, without
+    /// its line terminator, or `None` if there were no records.
+    pub record_len: Option<(usize, usize)>,
+    /// The line number and error of the first invalid line. Positions in
+    /// the error are relative to the start of that line.
+    pub first_error: Option<(u64, JsonError)>,
+}
+
+/// The result of [`Sampler::sample`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// The chosen records in input order: all of them if there were no
+    /// more than the sample size.
+    pub records: Vec<SampledRecord>,
+    /// Counts over the whole input.
+    pub stats: SampleStats,
+}
+
+/// Draws a fixed-size uniform random sample from NDJSON input.
+///
+/// Lines must be strict JSON, as [`validate`](crate::validate::validate)
+/// checks it; invalid lines are counted in [`SampleStats`] and never
+/// sampled. Without a [`seed`](Self::seed) each run draws a different
+/// sample.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::sample::Sampler;
+///
+/// let log: String = (1..=1000)
+///     .map(|i| format!("{{\"id\": {}, \"ok\": {}}}\n", i, i % 7 != 0))
+///     .chain(["not json\n".to_string()])
+///     .collect();
+///
+/// let sample = Sampler::new(10).seed(42).sample(log.as_bytes())?;
+/// assert_eq!(sample.records.len(), 10);
+/// assert!(sample.records.windows(2).all(|w| w[0].line < w[1].line));
+/// assert_eq!(sample.stats.records, 1000);
+/// assert_eq!(sample.stats.invalid_lines, 1);
+/// assert_eq!(sample.stats.first_error.as_ref().map(|(line, _)| *line), Some(1001));
+///
+/// // The same seed draws the same sample.
+/// let again = Sampler::new(10).seed(42).sample(log.as_bytes())?;
+/// assert_eq!(sample, again);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    size: usize,
+    seed: Option<u64>,
+}
+
+impl Sampler {
+    /// Creates a sampler keeping up to `size` records.
+    pub fn new(size: usize) -> Self {
+        Self { size, seed: None }
+    }
+
+    /// Seeds the random choices, so the same input always gives the same
+    /// sample.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Reads `input` to the end and returns the sample and statistics.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from reading `input`. Invalid lines are not
+    /// errors.
+    pub fn sample(&self, mut input: impl BufRead) -> io::Result<Sample> {
+        let seed = self
+            .seed
+            .unwrap_or_else(|| RandomState::new().build_hasher().finish());
+        let mut rng = SplitMix64(seed);
+        let mut reservoir: Vec<SampledRecord> = Vec::with_capacity(self.size.min(1 << 16));
+        let mut stats = SampleStats::default();
+        // Strict, like `is_valid`, whatever the process defaults are.
+        let mut parser = JsonParser::with_options(ParserOptions::default());
+        let mut buffer = Vec::new();
+        loop {
+            buffer.clear();
+            let read = input.read_until(b'\n', &mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            stats.lines += 1;
+            stats.bytes += read as u64;
+            let line = buffer.trim_ascii_end();
+            if line.trim_ascii_start().is_empty() {
+                stats.blank_lines += 1;
+                continue;
+            }
+            let valid = std::str::from_utf8(line).is_ok_and(is_valid);
+            if !valid {
+                stats.invalid_lines += 1;
+                if stats.first_error.is_none()
+                    && let Err(error) = parser.parse_bytes(line)
+                {
+                    stats.first_error = Some((stats.lines, error));
+                }
+                continue;
+            }
+
+            // Algorithm R: the n-th record replaces a random slot with
+            // probability size / n.
+            let slot = if reservoir.len() < self.size {
+                Some(reservoir.len())
+            } else {
+                let j = rng.below(stats.records + 1);
+                (j < self.size as u64).then_some(j as usize)
+            };
+            stats.records += 1;
+            stats.record_len = Some(match stats.record_len {
+                None => (line.len(), line.len()),
+                Some((min, max)) => (min.min(line.len()), max.max(line.len())),
+            });
+            if let Some(slot) = slot {
+                let record = SampledRecord {
+                    line: stats.lines,
+                    value: parser.parse_bytes(line).expect("validated line"),
+                };
+                if slot == reservoir.len() {
+                    reservoir.push(record);
+                } else {
+                    reservoir[slot] = record;
+                }
+            }
+        }
+        reservoir.sort_by_key(|record| record.line);
+        Ok(Sample {
+            records: reservoir,
+            stats,
+        })
+    }
+}
+
+// SplitMix64: small, fast, and good enough for choosing samples.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A number in `0..n`, by multiplying instead of dividing. The bias is
+    // below `n / 2^64`.
+    fn below(&mut self, n: u64) -> u64 {
+        ((u128::from(self.next()) * u128::from(n)) >> 64) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::JsonErrorKind;
+    use crate::json;
+
+    #[test]
+    fn test_small_input_is_kept_whole() {
+        let input = b"{\"a\": 1}\n\n  \n[1, 2]\r\n{\"bad\": }\n\"\xff\"\n7";
+        let sample = Sampler::new(5).sample(&input[..]).unwrap();
+        let lines: Vec<u64> = sample.records.iter().map(|r| r.line).collect();
+        assert_eq!(lines, [1, 4, 7]);
+        assert_eq!(sample.records[1].value, json!([1, 2]));
+        let stats = sample.stats;
+        assert_eq!(
+            (
+                stats.lines,
+                stats.records,
+                stats.blank_lines,
+                stats.invalid_lines
+            ),
+            (7, 3, 2, 2)
+        );
+        assert_eq!(stats.bytes, input.len() as u64);
+        assert_eq!(stats.record_len, Some((1, 8)));
+        let (line, error) = stats.first_error.unwrap();
+        assert_eq!((line, error.kind()), (5, JsonErrorKind::UnexpectedToken));
+    }
+
+    #[test]
+    fn test_sample_is_roughly_uniform() {
+        let input: String = (0..100).map(|i| format!("{}\n", i)).collect();
+        let mut hits = [0u32; 100];
+        for seed in 0..2000 {
+            let sample = Sampler::new(10)
+                .seed(seed)
+                .sample(input.as_bytes())
+                .unwrap();
+            assert_eq!(sample.records.len(), 10);
+            for record in sample.records {
+                hits[record.value.as_f64().unwrap() as usize] += 1;
+            }
+        }
+        // Each record is expected 200 times.
+        assert!(hits.iter().all(|&h| (140..=260).contains(&h)), "{:?}", hits);
+        assert_eq!(
+            Sampler::new(0).sample(input.as_bytes()).unwrap().records,
+            []
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Making programmatically built values safe to serialize.
+//!
+//! A [`JsonValue`](crate::value::JsonValue) parsed from text is always
+//! valid JSON, but one built in code need not be:
+//! `JsonValue::from(f64::NAN)` prints as `NaN`, and nothing stops a runaway
+//! loop from nesting arrays thousands deep or filling a string with
+//! megabytes of log output.
+//! [`JsonValue::sanitize`](crate::value::JsonValue::sanitize) rewrites such
+//! a value in place according to
+//! [`SanitizeOptions`](crate::sanitize::SanitizeOptions) so that it
+//! serializes to JSON the default parser accepts.
+
+use crate::parser::DEFAULT_MAX_DEPTH;
+use crate::value::JsonValue;
+
+/// What to do with a NaN or infinite number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonFinite {
+    /// Replace it with `null`.
+    #[default]
+    Null,
+    /// Replace infinities with the largest finite `f64` of the same sign,
+    /// and NaN with `0`.
+    Clamp,
+    /// Remove it from its array or object. A non-finite root becomes
+    /// `null`.
+    Remove,
+}
+
+/// Settings for [`JsonValue::sanitize`].
+///
+/// The default replaces non-finite numbers with `null` and prunes
+/// containers nested deeper than [`DEFAULT_MAX_DEPTH`], so the output
+/// always parses again with a default [`JsonParser`]. Strings and
+/// containers are not truncated unless a limit is set.
+///
+/// [`JsonParser`]: crate::parser::JsonParser
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::json;
+/// use rust_json_parser::sanitize::{NonFinite, SanitizeOptions};
+///
+/// let mut value = json!({"ratio": (f64::NAN), "log": "aaaaaaaa", "rows": [1, 2, 3, 4, 5], "deep": [[[1]]]});
+/// let options = SanitizeOptions::new()
+///     .non_finite(NonFinite::Remove)
+///     .max_string_len(3)
+///     .max_items(4)
+///     .max_depth(2);
+/// assert_eq!(value.sanitize(&options), 4);
+/// assert_eq!(value.to_string(), r#"{"log":"aaa","rows":[1,2,3,4],"deep":[null]}"#);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanitizeOptions {
+    non_finite: NonFinite,
+    max_depth: usize,
+    max_items: Option<usize>,
+    max_string_len: Option<usize>,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            non_finite: NonFinite::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_items: None,
+            max_string_len: None,
+        }
+    }
+}
+
+impl SanitizeOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how NaN and infinite numbers are handled.
+    pub fn non_finite(mut self, non_finite: NonFinite) -> Self {
+        self.non_finite = non_finite;
+        self
+    }
+
+    /// Sets the deepest allowed nesting, counted like
+    /// [`JsonParser::max_depth`](crate::parser::JsonParser::max_depth):
+    /// `[[1]]` needs a depth of 2. Containers nested deeper are replaced
+    /// with `null`.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Keeps at most `max` elements of each array and members of each
+    /// object, dropping the rest.
+    pub fn max_items(mut self, max: usize) -> Self {
+        self.max_items = Some(max);
+        self
+    }
+
+    /// Truncates strings longer than `max` bytes, at the last character
+    /// boundary that fits. Object keys are left alone.
+    pub fn max_string_len(mut self, max: usize) -> Self {
+        self.max_string_len = Some(max);
+        self
+    }
+
+    // Sanitizes `value` at `depth` containers deep, returning the number
+    // of changes and whether the value should be removed from its parent.
+    pub(crate) fn apply(&self, value: &mut JsonValue, depth: usize) -> (usize, bool) {
+        match value {
+            JsonValue::Number(n) if !n.as_f64().is_finite() => match self.non_finite {
+                NonFinite::Null => {
+                    *value = JsonValue::Null;
+                    (1, false)
+                }
+                NonFinite::Clamp => {
+                    let f = n.as_f64();
+                    let clamped = if f.is_nan() {
+                        0.0
+                    } else {
+                        f64::MAX.copysign(f)
+                    };
+                    *value = JsonValue::from(clamped);
+                    (1, false)
+                }
+                NonFinite::Remove => {
+                    *value = JsonValue::Null;
+                    (1, true)
+                }
+            },
+            JsonValue::String(s) => match self.max_string_len {
+                Some(max) if s.len() > max => {
+                    let end = (0..=max)
+                        .rev()
+                        .find(|&i| s.is_char_boundary(i))
+                        .unwrap_or(0);
+                    s.truncate(end);
+                    (1, false)
+                }
+                _ => (0, false),
+            },
+            JsonValue::Array(_) | JsonValue::Object(_) if depth >= self.max_depth => {
+                *value = JsonValue::Null;
+                (1, false)
+            }
+            JsonValue::Array(elements) => {
+                let mut changes = 0;
+                if let Some(max) = self.max_items
+                    && elements.len() > max
+                {
+                    elements.truncate(max);
+                    changes += 1;
+                }
+                elements.retain_mut(|element| {
+                    let (n, remove) = self.apply(element, depth + 1);
+                    changes += n;
+                    !remove
+                });
+                (changes, false)
+            }
+            JsonValue::Object(map) => {
+                let mut changes = 0;
+                if let Some(max) = self.max_items
+                    && map.len() > max
+                {
+                    map.truncate(max);
+                    changes += 1;
+                }
+                map.retain(|_, member| {
+                    let (n, remove) = self.apply(member, depth + 1);
+                    changes += n;
+                    !remove
+                });
+                (changes, false)
+            }
+            _ => (0, false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_non_finite_policies() {
+        let junk = || json!([(f64::NAN), (f64::INFINITY), {"x": (f64::NEG_INFINITY)}, 1.5]);
+        let sanitized = |non_finite| {
+            let mut value = junk();
+            let changes = value.sanitize(&SanitizeOptions::new().non_finite(non_finite));
+            (changes, value.to_string())
+        };
+        assert_eq!(
+            sanitized(NonFinite::Null),
+            (3, r#"[null,null,{"x":null},1.5]"#.to_string())
+        );
+        assert_eq!(sanitized(NonFinite::Remove), (3, r#"[{},1.5]"#.to_string()));
+        let (changes, clamped) = sanitized(NonFinite::Clamp);
+        assert_eq!(changes, 3);
+        let reparsed = parse_json(&clamped).unwrap();
+        assert_eq!(reparsed[1].as_f64(), Some(f64::MAX));
+        assert_eq!(reparsed[2]["x"].as_f64(), Some(f64::MIN));
+        assert_eq!(reparsed[0].as_f64(), Some(0.0));
+
+        let mut root = JsonValue::from(f64::NAN);
+        root.sanitize(&SanitizeOptions::new().non_finite(NonFinite::Remove));
+        assert!(root.is_null());
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut value = json!({"s": "héllo", "a": [1, 2, 3], "o": {"k": 1, "l": 2, "m": 3}});
+        let changes = value.sanitize(&SanitizeOptions::new().max_string_len(2).max_items(2));
+        assert_eq!(changes, 3);
+        assert_eq!(value, json!({"s": "h", "a": [1, 2]}));
+        let mut value = json!([{"k": 1, "l": 2, "m": 3}]);
+        value.sanitize(&SanitizeOptions::new().max_items(2));
+        assert_eq!(value.to_string(), r#"[{"k":1,"l":2}]"#);
+
+        let mut deep = JsonValue::Null;
+        for _ in 0..DEFAULT_MAX_DEPTH + 10 {
+            deep = JsonValue::Array(vec![deep]);
+        }
+        assert_eq!(deep.sanitize(&SanitizeOptions::new()), 1);
+        assert!(parse_json(&deep.to_string()).is_ok());
+
+        let mut clean = json!({"a": [1, "b", null]});
+        assert_eq!(clean.sanitize(&SanitizeOptions::new()), 0);
+        assert_eq!(clean, json!({"a": [1, "b", null]}));
+    }
+}
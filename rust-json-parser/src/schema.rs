@@ -0,0 +1,1123 @@
+//! JSON Schema validation for a subset of draft 2020-12.
+//!
+//! A [`Schema`](crate::schema::Schema) is compiled once from a schema
+//! document and then checks any number of instances, reporting every
+//! [`Violation`](crate::schema::Violation) by the JSON Pointer of the
+//! offending value rather than stopping at the first.
+//!
+//! Supported keywords:
+//!
+//! - `type` (a name or an array of names, including `integer`), `enum`,
+//!   and `const`; values compare as in the spec, so `1` equals `1.0` and
+//!   key order does not matter
+//! - `minimum`, `maximum`, `exclusiveMinimum`, and `exclusiveMaximum`
+//! - `minLength`, `maxLength`, and `pattern`, with lengths in characters
+//! - `items`, `prefixItems`, `minItems`, and `maxItems`
+//! - `properties`, `required`, and `additionalProperties`
+//! - `true` and `false` as schemas
+//!
+//! `pattern` accepts the common core of ECMA-262 regular expressions:
+//! literals, `.`, classes such as `[a-z]` and `\d`, anchors, groups,
+//! alternation, and greedy or lazy quantifiers. Lookaround, backreferences,
+//! and `\b` are rejected when compiling.
+//!
+//! Annotations such as `title` and `$id`, and other unknown keywords, are
+//! ignored as the spec requires. Applicators this module does not
+//! implement, such as `$ref` and `anyOf`, make
+//! [`Schema::compile`](crate::schema::Schema::compile) fail rather than
+//! silently accept everything.
+
+use std::error;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::map::JsonMap;
+use crate::parser::DEFAULT_MAX_DEPTH;
+use crate::path::JsonPath;
+use crate::value::JsonValue;
+
+// Keywords that apply subschemas or references, which would make
+// validation wrong if they were skipped.
+const UNSUPPORTED: [&str; 16] = [
+    "$ref",
+    "$dynamicRef",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+    "if",
+    "then",
+    "else",
+    "dependentSchemas",
+    "dependentRequired",
+    "patternProperties",
+    "propertyNames",
+    "contains",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+
+/// A value in an instance that does not satisfy its schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Location of the value in the instance.
+    pub path: JsonPath,
+    /// The schema keyword that failed, e.g. `"minimum"`.
+    pub keyword: &'static str,
+    /// What is wrong with the value.
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.path.to_pointer(), self.message)
+    }
+}
+
+/// A schema document that cannot be compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// Location of the problem in the schema document.
+    pub path: JsonPath,
+    /// What is wrong there.
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid schema at {:?}: {}",
+            self.path.to_pointer(),
+            self.message
+        )
+    }
+}
+
+impl error::Error for SchemaError {}
+
+/// A compiled schema.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::json;
+/// use rust_json_parser::schema::Schema;
+///
+/// let schema = Schema::compile(&json!({
+///     "type": "object",
+///     "properties": {
+///         "name": {"type": "string", "pattern": "^[a-z]+$"},
+///         "age": {"type": "integer", "minimum": 0},
+///         "tags": {"type": "array", "items": {"enum": ["admin", "user"]}}
+///     },
+///     "required": ["name", "age"]
+/// }))?;
+///
+/// assert!(schema.is_valid(&json!({"name": "ann", "age": 31, "tags": ["user"]})));
+///
+/// let violations = schema
+///     .validate(&json!({"name": "Ann", "tags": ["root"]}))
+///     .unwrap_err();
+/// let messages: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+/// assert_eq!(
+///     messages,
+///     [
+///         r#""/name": string does not match pattern "^[a-z]+$""#,
+///         r#""/tags/0": value is not one of the allowed values"#,
+///         r#""": missing required property "age""#,
+///     ]
+/// );
+/// # Ok::<(), rust_json_parser::schema::SchemaError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Schema {
+    root: Node,
+}
+
+impl Schema {
+    /// Compiles `schema`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SchemaError`] if `schema` is neither an object nor a
+    /// boolean, if a keyword has a value of the wrong kind, if a `pattern`
+    /// is not a supported regular expression, or if it uses a keyword this
+    /// module does not implement.
+    pub fn compile(schema: &JsonValue) -> Result<Self, SchemaError> {
+        let root = Node::compile(schema, &mut JsonPath::root())?;
+        Ok(Self { root })
+    }
+
+    /// Checks `instance`, returning every violation in document order.
+    ///
+    /// # Errors
+    ///
+    /// Returns the violations if there are any; the list is never empty.
+    pub fn validate(&self, instance: &JsonValue) -> Result<(), Vec<Violation>> {
+        let mut violations = Vec::new();
+        self.root
+            .check(instance, &mut JsonPath::root(), &mut violations);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Returns `true` if `instance` satisfies the schema.
+    pub fn is_valid(&self, instance: &JsonValue) -> bool {
+        self.validate(instance).is_ok()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Type {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl Type {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "null" => Type::Null,
+            "boolean" => Type::Boolean,
+            "integer" => Type::Integer,
+            "number" => Type::Number,
+            "string" => Type::String,
+            "array" => Type::Array,
+            "object" => Type::Object,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Type::Null => "null",
+            Type::Boolean => "boolean",
+            Type::Integer => "integer",
+            Type::Number => "number",
+            Type::String => "string",
+            Type::Array => "array",
+            Type::Object => "object",
+        }
+    }
+
+    fn matches(self, value: &JsonValue) -> bool {
+        match (self, value) {
+            (Type::Null, JsonValue::Null)
+            | (Type::Boolean, JsonValue::Boolean(_))
+            | (Type::Number, JsonValue::Number(_))
+            | (Type::String, JsonValue::String(_))
+            | (Type::Array, JsonValue::Array(_))
+            | (Type::Object, JsonValue::Object(_)) => true,
+            (Type::Integer, JsonValue::Number(n)) => {
+                let n = n.as_f64();
+                n.is_finite() && n.fract() == 0.0
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Bool(bool),
+    Keywords(Box<Keywords>),
+}
+
+#[derive(Debug, Clone, Default)]
+struct Keywords {
+    types: Option<Vec<Type>>,
+    allowed: Option<Vec<JsonValue>>,
+    constant: Option<JsonValue>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    exclusive_minimum: Option<f64>,
+    exclusive_maximum: Option<f64>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    pattern: Option<(String, Regex)>,
+    prefix_items: Vec<Node>,
+    items: Option<Node>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+    properties: Vec<(String, Node)>,
+    required: Vec<String>,
+    additional_properties: Option<Node>,
+}
+
+// Reads keyword values out of one schema object, reporting errors at the
+// keyword's path.
+struct Reader<'a> {
+    object: &'a JsonMap,
+    path: &'a mut JsonPath,
+}
+
+impl Reader<'_> {
+    fn error(&self, keyword: &str, message: impl Into<String>) -> SchemaError {
+        SchemaError {
+            path: self.path.join(keyword),
+            message: message.into(),
+        }
+    }
+
+    fn number(&self, keyword: &str) -> Result<Option<f64>, SchemaError> {
+        match self.object.get(keyword) {
+            None => Ok(None),
+            Some(value) => value
+                .as_f64()
+                .map(Some)
+                .ok_or_else(|| self.error(keyword, "expected a number")),
+        }
+    }
+
+    fn count(&self, keyword: &str) -> Result<Option<usize>, SchemaError> {
+        match self.object.get(keyword) {
+            None => Ok(None),
+            Some(value) => match value.as_f64() {
+                Some(n) if n >= 0.0 && n.fract() == 0.0 => {
+                    Ok(Some(n.min(usize::MAX as f64) as usize))
+                }
+                _ => Err(self.error(keyword, "expected a non-negative integer")),
+            },
+        }
+    }
+
+    fn schema(&mut self, keyword: &str) -> Result<Option<Node>, SchemaError> {
+        let Some(value) = self.object.get(keyword) else {
+            return Ok(None);
+        };
+        self.path.push(keyword);
+        let node = Node::compile(value, self.path);
+        self.path.pop();
+        node.map(Some)
+    }
+}
+
+impl Node {
+    fn compile(schema: &JsonValue, path: &mut JsonPath) -> Result<Node, SchemaError> {
+        let object = match schema {
+            JsonValue::Boolean(b) => return Ok(Node::Bool(*b)),
+            JsonValue::Object(object) => object,
+            other => {
+                return Err(SchemaError {
+                    path: path.clone(),
+                    message: format!("expected an object or boolean, found {}", other.type_name()),
+                });
+            }
+        };
+        let mut reader = Reader { object, path };
+        if let Some(keyword) = UNSUPPORTED.iter().find(|k| object.contains_key(k)) {
+            return Err(reader.error(keyword, "unsupported keyword"));
+        }
+
+        let mut keywords = Keywords::default();
+        if let Some(value) = object.get("type") {
+            let names = match value {
+                JsonValue::Array(names) => names.iter().collect(),
+                name => vec![name],
+            };
+            let mut types = Vec::with_capacity(names.len());
+            for name in names {
+                match name.as_str().and_then(Type::from_name) {
+                    Some(t) => types.push(t),
+                    None => return Err(reader.error("type", format!("unknown type {}", name))),
+                }
+            }
+            keywords.types = Some(types);
+        }
+        if let Some(value) = object.get("enum") {
+            match value {
+                JsonValue::Array(values) => keywords.allowed = Some(values.clone()),
+                _ => return Err(reader.error("enum", "expected an array")),
+            }
+        }
+        keywords.constant = object.get("const").cloned();
+        keywords.minimum = reader.number("minimum")?;
+        keywords.maximum = reader.number("maximum")?;
+        keywords.exclusive_minimum = reader.number("exclusiveMinimum")?;
+        keywords.exclusive_maximum = reader.number("exclusiveMaximum")?;
+        keywords.min_length = reader.count("minLength")?;
+        keywords.max_length = reader.count("maxLength")?;
+        if let Some(value) = object.get("pattern") {
+            let Some(pattern) = value.as_str() else {
+                return Err(reader.error("pattern", "expected a string"));
+            };
+            let regex = Regex::parse(pattern).map_err(|message| {
+                reader.error("pattern", format!("{} in {:?}", message, pattern))
+            })?;
+            keywords.pattern = Some((pattern.to_string(), regex));
+        }
+        keywords.min_items = reader.count("minItems")?;
+        keywords.max_items = reader.count("maxItems")?;
+        if let Some(value) = object.get("prefixItems") {
+            let JsonValue::Array(schemas) = value else {
+                return Err(reader.error("prefixItems", "expected an array"));
+            };
+            reader.path.push("prefixItems");
+            for (i, schema) in schemas.iter().enumerate() {
+                reader.path.push(i);
+                keywords
+                    .prefix_items
+                    .push(Node::compile(schema, reader.path)?);
+                reader.path.pop();
+            }
+            reader.path.pop();
+        }
+        keywords.items = reader.schema("items")?;
+        if let Some(value) = object.get("properties") {
+            let JsonValue::Object(schemas) = value else {
+                return Err(reader.error("properties", "expected an object"));
+            };
+            reader.path.push("properties");
+            for (key, schema) in schemas {
+                reader.path.push(key.as_str());
+                let node = Node::compile(schema, reader.path)?;
+                keywords.properties.push((key.clone(), node));
+                reader.path.pop();
+            }
+            reader.path.pop();
+        }
+        if let Some(value) = object.get("required") {
+            let names = value.as_array().and_then(|names| {
+                names
+                    .iter()
+                    .map(|name| name.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+            });
+            match names {
+                Some(names) => keywords.required = names,
+                None => return Err(reader.error("required", "expected an array of strings")),
+            }
+        }
+        keywords.additional_properties = reader.schema("additionalProperties")?;
+        Ok(Node::Keywords(Box::new(keywords)))
+    }
+
+    fn check(&self, value: &JsonValue, path: &mut JsonPath, out: &mut Vec<Violation>) {
+        let keywords = match self {
+            Node::Bool(true) => return,
+            Node::Bool(false) => {
+                out.push(Violation {
+                    path: path.clone(),
+                    keyword: "false",
+                    message: "no value is allowed here".to_string(),
+                });
+                return;
+            }
+            Node::Keywords(keywords) => keywords,
+        };
+        let mut fail = |keyword: &'static str, message: String| {
+            out.push(Violation {
+                path: path.clone(),
+                keyword,
+                message,
+            })
+        };
+
+        if let Some(types) = &keywords.types
+            && !types.iter().any(|t| t.matches(value))
+        {
+            let names: Vec<&str> = types.iter().map(|t| t.name()).collect();
+            fail(
+                "type",
+                format!(
+                    "expected {}, found {}",
+                    names.join(" or "),
+                    value.type_name()
+                ),
+            );
+        }
+        if let Some(allowed) = &keywords.allowed
+            && !allowed.contains(value)
+        {
+            fail("enum", "value is not one of the allowed values".to_string());
+        }
+        if let Some(constant) = &keywords.constant
+            && constant != value
+        {
+            fail("const", format!("expected {}", constant));
+        }
+
+        match value {
+            JsonValue::Number(n) => {
+                let n = n.as_f64();
+                if let Some(min) = keywords.minimum
+                    && n < min
+                {
+                    fail(
+                        "minimum",
+                        format!("{} is less than the minimum of {}", n, min),
+                    );
+                }
+                if let Some(max) = keywords.maximum
+                    && n > max
+                {
+                    fail(
+                        "maximum",
+                        format!("{} is greater than the maximum of {}", n, max),
+                    );
+                }
+                if let Some(min) = keywords.exclusive_minimum
+                    && n <= min
+                {
+                    fail(
+                        "exclusiveMinimum",
+                        format!("{} is not greater than {}", n, min),
+                    );
+                }
+                if let Some(max) = keywords.exclusive_maximum
+                    && n >= max
+                {
+                    fail(
+                        "exclusiveMaximum",
+                        format!("{} is not less than {}", n, max),
+                    );
+                }
+            }
+            JsonValue::String(s) => {
+                let len = s.chars().count();
+                if let Some(min) = keywords.min_length
+                    && len < min
+                {
+                    fail(
+                        "minLength",
+                        format!("string is shorter than {} characters", min),
+                    );
+                }
+                if let Some(max) = keywords.max_length
+                    && len > max
+                {
+                    fail(
+                        "maxLength",
+                        format!("string is longer than {} characters", max),
+                    );
+                }
+                if let Some((pattern, regex)) = &keywords.pattern
+                    && !regex.is_match(s)
+                {
+                    fail(
+                        "pattern",
+                        format!("string does not match pattern {:?}", pattern),
+                    );
+                }
+            }
+            JsonValue::Array(elements) => {
+                if let Some(min) = keywords.min_items
+                    && elements.len() < min
+                {
+                    fail("minItems", format!("array has fewer than {} items", min));
+                }
+                if let Some(max) = keywords.max_items
+                    && elements.len() > max
+                {
+                    fail("maxItems", format!("array has more than {} items", max));
+                }
+                for (i, element) in elements.iter().enumerate() {
+                    let schema = match keywords.prefix_items.get(i) {
+                        Some(schema) => schema,
+                        None => match &keywords.items {
+                            Some(schema) => schema,
+                            None => continue,
+                        },
+                    };
+                    path.push(i);
+                    schema.check(element, path, out);
+                    path.pop();
+                }
+            }
+            JsonValue::Object(map) => {
+                for (key, member) in map {
+                    path.push(key.as_str());
+                    match keywords.properties.iter().find(|(name, _)| name == key) {
+                        Some((_, schema)) => schema.check(member, path, out),
+                        None => match &keywords.additional_properties {
+                            Some(Node::Bool(false)) => out.push(Violation {
+                                path: path.clone(),
+                                keyword: "additionalProperties",
+                                message: format!("property {:?} is not allowed", key),
+                            }),
+                            Some(schema) => schema.check(member, path, out),
+                            None => {}
+                        },
+                    }
+                    path.pop();
+                }
+                for name in &keywords.required {
+                    if !map.contains_key(name) {
+                        out.push(Violation {
+                            path: path.clone(),
+                            keyword: "required",
+                            message: format!("missing required property {:?}", name),
+                        });
+                    }
+                }
+            }
+            JsonValue::Null | JsonValue::Boolean(_) => {}
+        }
+    }
+}
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+const SPACE: &[(char, char)] = &[
+    ('\t', '\r'),
+    (' ', ' '),
+    ('\u{a0}', '\u{a0}'),
+    ('\u{1680}', '\u{1680}'),
+    ('\u{2000}', '\u{200a}'),
+    ('\u{2028}', '\u{2029}'),
+    ('\u{202f}', '\u{202f}'),
+    ('\u{205f}', '\u{205f}'),
+    ('\u{3000}', '\u{3000}'),
+    ('\u{feff}', '\u{feff}'),
+];
+
+// A parsed regular expression for `pattern`.
+#[derive(Debug, Clone)]
+enum Ast {
+    Char(char),
+    // Any character except line terminators.
+    Any,
+    // Character ranges, and whether the class is negated.
+    Class(Vec<(char, char)>, bool),
+    Start,
+    End,
+    Concat(Vec<Ast>),
+    Alt(Vec<Ast>),
+    Repeat(Box<Ast>, usize, Option<usize>),
+}
+
+// One step of a compiled pattern. Threads move through these in
+// lockstep, so matching takes time linear in the text.
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Vec<(char, char)>, bool),
+    Start,
+    End,
+    // Continue at both targets.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+// The most instructions a pattern may compile to, since bounded
+// repetitions are expanded.
+const MAX_INSTS: usize = 1 << 16;
+
+// A regular expression for `pattern`, matched by simulating every
+// thread at once (a Pike VM) rather than by backtracking, so neither a
+// long string nor nested quantifiers can make it slow or overflow the
+// stack. As in ECMA-262, a match may start anywhere unless the pattern
+// is anchored.
+#[derive(Debug, Clone)]
+struct Regex {
+    insts: Vec<Inst>,
+}
+
+type Pattern<'a> = Peekable<Chars<'a>>;
+
+impl Regex {
+    fn parse(pattern: &str) -> Result<Regex, String> {
+        let mut chars = pattern.chars().peekable();
+        let node = Self::alternation(&mut chars, 0)?;
+        if chars.next().is_some() {
+            return Err("unmatched ')'".to_string());
+        }
+        let mut insts = Vec::new();
+        compile(&node, &mut insts)?;
+        push(&mut insts, Inst::Match)?;
+        Ok(Regex { insts })
+    }
+
+    fn alternation(chars: &mut Pattern, depth: usize) -> Result<Ast, String> {
+        if depth > DEFAULT_MAX_DEPTH {
+            return Err("groups nest too deeply".to_string());
+        }
+        let mut alternatives = vec![Self::sequence(chars, depth)?];
+        while chars.next_if_eq(&'|').is_some() {
+            alternatives.push(Self::sequence(chars, depth)?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().expect("one alternative")
+        } else {
+            Ast::Alt(alternatives)
+        })
+    }
+
+    fn sequence(chars: &mut Pattern, depth: usize) -> Result<Ast, String> {
+        let mut items = Vec::new();
+        while let Some(&c) = chars.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = Self::atom(chars, depth)?;
+            items.push(Self::quantified(chars, atom)?);
+        }
+        Ok(Ast::Concat(items))
+    }
+
+    fn atom(chars: &mut Pattern, depth: usize) -> Result<Ast, String> {
+        Ok(match chars.next().expect("peeked") {
+            '.' => Ast::Any,
+            '^' => Ast::Start,
+            '$' => Ast::End,
+            '(' => {
+                if chars.next_if_eq(&'?').is_some() && chars.next_if_eq(&':').is_none() {
+                    return Err("unsupported group".to_string());
+                }
+                let inner = Self::alternation(chars, depth + 1)?;
+                if chars.next() != Some(')') {
+                    return Err("unclosed group".to_string());
+                }
+                inner
+            }
+            '[' => Self::class(chars)?,
+            '\\' => match Self::escape(chars)? {
+                Escape::Char(c) => Ast::Char(c),
+                Escape::Class(ranges, negated) => Ast::Class(ranges.to_vec(), negated),
+            },
+            '*' | '+' | '?' => return Err("nothing to repeat".to_string()),
+            c => Ast::Char(c),
+        })
+    }
+
+    fn quantified(chars: &mut Pattern, atom: Ast) -> Result<Ast, String> {
+        let (min, max) = match chars.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => match Self::braces(&mut chars.clone()) {
+                Some(bounds) => {
+                    // Re-read the braces on the real iterator.
+                    while chars.next() != Some('}') {}
+                    if bounds.1.is_some_and(|max| max < bounds.0) {
+                        return Err("quantifier range out of order".to_string());
+                    }
+                    let atom = Ast::Repeat(Box::new(atom), bounds.0, bounds.1);
+                    chars.next_if_eq(&'?');
+                    return Ok(atom);
+                }
+                // Not a quantifier, so `{` is a literal.
+                None => return Ok(atom),
+            },
+            _ => return Ok(atom),
+        };
+        chars.next();
+        // Laziness does not change whether a pattern matches.
+        chars.next_if_eq(&'?');
+        Ok(Ast::Repeat(Box::new(atom), min, max))
+    }
+
+    // Parses `{n}`, `{n,}`, or `{n,m}` after the opening brace.
+    fn braces(chars: &mut Pattern) -> Option<(usize, Option<usize>)> {
+        chars.next();
+        let number = |chars: &mut Pattern| {
+            let mut digits = String::new();
+            while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                digits.push(d);
+            }
+            digits.parse::<usize>().ok()
+        };
+        let min = number(chars)?;
+        let max = if chars.next_if_eq(&',').is_some() {
+            number(chars)
+        } else {
+            Some(min)
+        };
+        chars.next_if_eq(&'}').map(|_| (min, max))
+    }
+
+    fn class(chars: &mut Pattern) -> Result<Ast, String> {
+        let negated = chars.next_if_eq(&'^').is_some();
+        let mut ranges = Vec::new();
+        loop {
+            let low = match chars.next() {
+                None => return Err("unclosed character class".to_string()),
+                Some(']') => return Ok(Ast::Class(ranges, negated)),
+                Some('\\') => match Self::escape(chars)? {
+                    Escape::Char(c) => c,
+                    Escape::Class(class, false) => {
+                        ranges.extend_from_slice(class);
+                        continue;
+                    }
+                    Escape::Class(_, true) => {
+                        return Err("negated class escape inside a class".to_string());
+                    }
+                },
+                Some(c) => c,
+            };
+            let mut lookahead = chars.clone();
+            if lookahead.next() == Some('-') && lookahead.peek().is_some_and(|&c| c != ']') {
+                chars.next();
+                let high = match chars.next().expect("peeked") {
+                    '\\' => match Self::escape(chars)? {
+                        Escape::Char(c) => c,
+                        Escape::Class(..) => return Err("class escape in a range".to_string()),
+                    },
+                    c => c,
+                };
+                if high < low {
+                    return Err("character class range out of order".to_string());
+                }
+                ranges.push((low, high));
+            } else {
+                ranges.push((low, low));
+            }
+        }
+    }
+
+    fn escape(chars: &mut Pattern) -> Result<Escape, String> {
+        let Some(c) = chars.next() else {
+            return Err("trailing backslash".to_string());
+        };
+        Ok(match c {
+            'd' => Escape::Class(DIGIT, false),
+            'D' => Escape::Class(DIGIT, true),
+            'w' => Escape::Class(WORD, false),
+            'W' => Escape::Class(WORD, true),
+            's' => Escape::Class(SPACE, false),
+            'S' => Escape::Class(SPACE, true),
+            'n' => Escape::Char('\n'),
+            'r' => Escape::Char('\r'),
+            't' => Escape::Char('\t'),
+            'f' => Escape::Char('\u{c}'),
+            'v' => Escape::Char('\u{b}'),
+            '0' => Escape::Char('\0'),
+            'x' | 'u' => {
+                let digits = if c == 'x' { 2 } else { 4 };
+                let hex: String = chars.by_ref().take(digits).collect();
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == digits)
+                    .and_then(char::from_u32)
+                    .map(Escape::Char)
+                    .ok_or_else(|| format!("invalid \\{} escape", c))?
+            }
+            c if c.is_ascii_alphanumeric() => return Err(format!("unsupported escape \\{}", c)),
+            c => Escape::Char(c),
+        })
+    }
+
+    fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        let mut current = Threads::new(self.insts.len());
+        let mut next = Threads::new(self.insts.len());
+        let mut stack = Vec::new();
+        for at in 0..=text.len() {
+            // A new thread at every position makes the match unanchored.
+            if self.add(&mut current, &mut stack, 0, at, text.len()) {
+                return true;
+            }
+            let Some(&c) = text.get(at) else {
+                break;
+            };
+            for &pc in &current.list {
+                let step = match &self.insts[pc] {
+                    Inst::Char(expected) => *expected == c,
+                    Inst::Any => !matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}'),
+                    Inst::Class(ranges, negated) => {
+                        ranges.iter().any(|&(low, high)| low <= c && c <= high) != *negated
+                    }
+                    _ => false,
+                };
+                if step && self.add(&mut next, &mut stack, pc + 1, at + 1, text.len()) {
+                    return true;
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+        false
+    }
+
+    // Adds the thread at `pc` to `threads`, following jumps, splits, and
+    // assertions that hold at `at`. Returns `true` if one reaches `Match`.
+    fn add(
+        &self,
+        threads: &mut Threads,
+        stack: &mut Vec<usize>,
+        pc: usize,
+        at: usize,
+        len: usize,
+    ) -> bool {
+        stack.clear();
+        stack.push(pc);
+        while let Some(pc) = stack.pop() {
+            if !threads.insert(pc) {
+                continue;
+            }
+            match self.insts[pc] {
+                Inst::Match => return true,
+                Inst::Jump(to) => stack.push(to),
+                Inst::Split(first, second) => stack.extend([second, first]),
+                Inst::Start if at == 0 => stack.push(pc + 1),
+                Inst::End if at == len => stack.push(pc + 1),
+                _ => {}
+            }
+        }
+        false
+    }
+}
+
+// The set of instructions threads are at, in the order they were added.
+struct Threads {
+    list: Vec<usize>,
+    present: Vec<bool>,
+}
+
+impl Threads {
+    fn new(len: usize) -> Self {
+        Threads {
+            list: Vec::new(),
+            present: vec![false; len],
+        }
+    }
+
+    fn insert(&mut self, pc: usize) -> bool {
+        if self.present[pc] {
+            return false;
+        }
+        self.present[pc] = true;
+        self.list.push(pc);
+        true
+    }
+
+    fn clear(&mut self) {
+        for &pc in &self.list {
+            self.present[pc] = false;
+        }
+        self.list.clear();
+    }
+}
+
+enum Escape {
+    Char(char),
+    Class(&'static [(char, char)], bool),
+}
+
+fn push(insts: &mut Vec<Inst>, inst: Inst) -> Result<usize, String> {
+    if insts.len() == MAX_INSTS {
+        return Err("pattern is too large".to_string());
+    }
+    insts.push(inst);
+    Ok(insts.len() - 1)
+}
+
+fn compile(node: &Ast, insts: &mut Vec<Inst>) -> Result<(), String> {
+    match node {
+        Ast::Char(c) => push(insts, Inst::Char(*c)).map(drop),
+        Ast::Any => push(insts, Inst::Any).map(drop),
+        Ast::Class(ranges, negated) => push(insts, Inst::Class(ranges.clone(), *negated)).map(drop),
+        Ast::Start => push(insts, Inst::Start).map(drop),
+        Ast::End => push(insts, Inst::End).map(drop),
+        Ast::Concat(items) => items.iter().try_for_each(|item| compile(item, insts)),
+        Ast::Alt(alternatives) => {
+            let (last, rest) = alternatives.split_last().expect("two or more alternatives");
+            let mut jumps = Vec::new();
+            for alternative in rest {
+                let split = push(insts, Inst::Split(0, 0))?;
+                compile(alternative, insts)?;
+                jumps.push(push(insts, Inst::Jump(0))?);
+                insts[split] = Inst::Split(split + 1, insts.len());
+            }
+            compile(last, insts)?;
+            let end = insts.len();
+            for jump in jumps {
+                insts[jump] = Inst::Jump(end);
+            }
+            Ok(())
+        }
+        Ast::Repeat(inner, min, max) => {
+            for _ in 0..*min {
+                compile(inner, insts)?;
+            }
+            match max {
+                None => {
+                    let split = push(insts, Inst::Split(0, 0))?;
+                    compile(inner, insts)?;
+                    push(insts, Inst::Jump(split))?;
+                    insts[split] = Inst::Split(split + 1, insts.len());
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(push(insts, Inst::Split(0, 0))?);
+                        compile(inner, insts)?;
+                    }
+                    let end = insts.len();
+                    for split in splits {
+                        insts[split] = Inst::Split(split + 1, end);
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    fn messages(schema: &JsonValue, instance: &JsonValue) -> Vec<String> {
+        let schema = Schema::compile(schema).unwrap();
+        match schema.validate(instance) {
+            Ok(()) => Vec::new(),
+            Err(violations) => violations.iter().map(Violation::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn test_keywords() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": ["integer", "string"]},
+                "score": {"minimum": 0, "exclusiveMaximum": 1},
+                "point": {"prefixItems": [{"type": "number"}, {"type": "number"}], "items": false},
+                "name": {"minLength": 2, "maxLength": 3},
+                "kind": {"const": {"a": [1]}}
+            },
+            "additionalProperties": {"type": "boolean"}
+        });
+        let valid = json!({"id": 7.0, "score": 0, "point": [1, 2], "name": "añb", "kind": {"a": [1.0]}, "x": true});
+        assert_eq!(messages(&schema, &valid), Vec::<String>::new());
+
+        let invalid =
+            json!({"id": 1.5, "score": 1, "point": [1, "2", 3], "name": "a", "kind": {}, "x": 1});
+        assert_eq!(
+            messages(&schema, &invalid),
+            [
+                r#""/id": expected integer or string, found number"#,
+                r#""/score": 1 is not less than 1"#,
+                r#""/point/1": expected number, found string"#,
+                r#""/point/2": no value is allowed here"#,
+                r#""/name": string is shorter than 2 characters"#,
+                r#""/kind": expected {"a":[1]}"#,
+                r#""/x": expected boolean, found number"#,
+            ]
+        );
+
+        let closed =
+            json!({"properties": {"a": true}, "additionalProperties": false, "minItems": 2});
+        assert_eq!(
+            messages(&closed, &json!({"a": 1, "b": 2})),
+            [r#""/b": property "b" is not allowed"#]
+        );
+        assert_eq!(
+            messages(&closed, &json!([1])),
+            [r#""": array has fewer than 2 items"#]
+        );
+        assert_eq!(
+            messages(&json!(false), &json!(null)),
+            [r#""": no value is allowed here"#]
+        );
+        assert_eq!(
+            messages(&json!({}), &json!([1, {"a": null}])),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        let error = |schema: JsonValue| Schema::compile(&schema).unwrap_err().to_string();
+        assert_eq!(
+            error(json!(1)),
+            r#"Invalid schema at "": expected an object or boolean, found number"#
+        );
+        assert_eq!(
+            error(json!({"properties": {"a": {"type": "int"}}})),
+            r#"Invalid schema at "/properties/a/type": unknown type "int""#
+        );
+        assert_eq!(
+            error(json!({"items": {"$ref": "#/$defs/x"}})),
+            r#"Invalid schema at "/items/$ref": unsupported keyword"#
+        );
+        assert_eq!(
+            error(json!({"minLength": -1})),
+            r#"Invalid schema at "/minLength": expected a non-negative integer"#
+        );
+        assert_eq!(
+            error(json!({"pattern": "(a"})),
+            r#"Invalid schema at "/pattern": unclosed group in "(a""#
+        );
+        assert_eq!(
+            error(json!({"required": [1]})),
+            r#"Invalid schema at "/required": expected an array of strings"#
+        );
+        assert!(Schema::compile(&json!({"title": "x", "$id": "y", "x-custom": 1})).is_ok());
+    }
+
+    #[test]
+    fn test_patterns() {
+        let cases = [
+            ("abc", "xabcx", true),
+            ("^abc$", "xabc", false),
+            ("^a.c$", "a\nc", false),
+            ("^\\d{3}-\\d{4}$", "555-1234", true),
+            ("^\\d{3}-\\d{4}$", "555-123", false),
+            ("^[A-Za-z_][\\w-]*$", "snake_case-1", true),
+            ("^[^aeiou]+$", "rhythm", true),
+            ("^[^aeiou]+$", "vowel", false),
+            ("^(cat|dog)s?$", "dogs", true),
+            ("^(?:ab)+$", "ababa", false),
+            ("^a{2,}b{0,1}$", "aaab", true),
+            ("^a{2,3}$", "aaaa", false),
+            ("^(a*)*b$", "aaab", true),
+            ("^(a|)+$", "", true),
+            ("^.*?x", "abcx", true),
+            ("x{y", "x{y", true),
+            ("^\\u00e9\\.$", "é.", true),
+            ("^[\\s\\d]+$", " 1\t2", true),
+        ];
+        for (pattern, text, expected) in cases {
+            let regex = Regex::parse(pattern).unwrap();
+            assert_eq!(
+                regex.is_match(text),
+                expected,
+                "{:?} on {:?}",
+                pattern,
+                text
+            );
+        }
+        for pattern in ["a)", "*a", "[a", "(?=a)", "\\b", "\\1", "[z-a]", "a{3,2}"] {
+            assert!(Regex::parse(pattern).is_err(), "{:?}", pattern);
+        }
+        assert_eq!(
+            Regex::parse("(a{300}){300}").unwrap_err(),
+            "pattern is too large"
+        );
+        let deep = "(".repeat(1000) + &")".repeat(1000);
+        assert_eq!(Regex::parse(&deep).unwrap_err(), "groups nest too deeply");
+    }
+
+    #[test]
+    fn test_patterns_on_long_and_hostile_input() {
+        let schema = Schema::compile(&json!({"pattern": "^[a-z]*$"})).unwrap();
+        let long = "a".repeat(1 << 20);
+        assert!(schema.is_valid(&JsonValue::from(long.as_str())));
+        assert!(!schema.is_valid(&JsonValue::from(format!("{}!", long))));
+
+        // Exponential for a backtracker; a handful of steps per character
+        // here.
+        for pattern in ["^(a+)+$", "^(a*)*$", "^(a|a)*$", "^(a|aa)+$"] {
+            let regex = Regex::parse(pattern).unwrap();
+            let text = "a".repeat(5000) + "b";
+            assert!(!regex.is_match(&text), "{:?}", pattern);
+            assert!(regex.is_match(&text[..5000]), "{:?}", pattern);
+        }
+        let regex = Regex::parse("(x+x+)+y").unwrap();
+        assert!(!regex.is_match(&"x".repeat(5000)));
+    }
+}
@@ -0,0 +1,188 @@
+//! JSON documents whose contents are wiped from memory on drop.
+//!
+//! [`SecretJson`](crate::secret::SecretJson) owns a
+//! [`JsonValue`](crate::value::JsonValue) and, when dropped, overwrites
+//! every string (object keys included) with zeros and resets every number
+//! before the memory is released. Use it for documents holding credentials
+//! or tokens in security-sensitive services.
+//!
+//! Only memory owned by the wrapper is wiped. The caller remains
+//! responsible for the input text, and any value cloned out through
+//! [`expose`](crate::secret::SecretJson::expose) is an ordinary
+//! `JsonValue` that is not zeroed.
+
+use std::fmt;
+
+use zeroize::Zeroize;
+
+use crate::error::JsonError;
+use crate::masked::Masked;
+use crate::parser::JsonParser;
+use crate::value::JsonValue;
+
+/// A parsed document that is zeroed when dropped.
+///
+/// `Debug` output is masked so the wrapper can't leak its contents through
+/// logging.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::secret::SecretJson;
+///
+/// let secret = SecretJson::parse(r#"{"api_key": "sk-123"}"#)?;
+/// assert_eq!(
+///     secret.expose().get("api_key").and_then(|v| v.as_str()),
+///     Some("sk-123")
+/// );
+/// assert_eq!(format!("{:?}", secret), r#"SecretJson({"api_key":***})"#);
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+pub struct SecretJson {
+    value: JsonValue,
+}
+
+impl SecretJson {
+    /// Takes ownership of an existing value.
+    pub fn new(value: JsonValue) -> Self {
+        Self { value }
+    }
+
+    /// Parses JSON text into a zeroing wrapper.
+    ///
+    /// The parser's internal copy of the input and any leftover string
+    /// tokens are zeroed before returning, whether or not parsing
+    /// succeeded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError`] if the input is not valid JSON.
+    pub fn parse(input: &str) -> Result<Self, JsonError> {
+        let mut parser = JsonParser::new();
+        let result = parser.parse(input);
+        parser.zeroize_buffers();
+        result.map(Self::new)
+    }
+
+    /// Borrows the wrapped value.
+    pub fn expose(&self) -> &JsonValue {
+        &self.value
+    }
+
+    /// Mutably borrows the wrapped value.
+    ///
+    /// Strings replaced through this reference are dropped without being
+    /// zeroed; overwrite them in place to keep the guarantee.
+    pub fn expose_mut(&mut self) -> &mut JsonValue {
+        &mut self.value
+    }
+}
+
+impl From<JsonValue> for SecretJson {
+    fn from(value: JsonValue) -> Self {
+        Self::new(value)
+    }
+}
+
+impl fmt::Debug for SecretJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretJson({})", Masked::new(&self.value))
+    }
+}
+
+impl Drop for SecretJson {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+impl Zeroize for JsonValue {
+    /// Zeroes all strings and numbers in the tree, leaving `Null`.
+    fn zeroize(&mut self) {
+        match self {
+            JsonValue::Null => {}
+            JsonValue::Boolean(b) => b.zeroize(),
+            JsonValue::Number(n) => n.zeroize(),
+            JsonValue::String(s) => s.zeroize(),
+            JsonValue::Array(arr) => {
+                for item in arr.iter_mut() {
+                    item.zeroize();
+                }
+                arr.clear();
+            }
+            JsonValue::Object(map) => {
+                for (mut key, mut item) in map.drain() {
+                    key.zeroize();
+                    item.zeroize();
+                }
+            }
+        }
+        *self = JsonValue::Null;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::JsonMap;
+
+    #[test]
+    fn test_zeroize_value_resets_to_null() {
+        let mut value =
+            crate::parser::parse_json(r#"{"k": ["secret", 42, true, {"n": null}]}"#).unwrap();
+        value.zeroize();
+        assert_eq!(value, JsonValue::Null);
+    }
+
+    #[test]
+    fn test_zeroize_indexed_object() {
+        // Enough members for the map to build its hash index, which must
+        // not keep copies of the keys.
+        let mut map = JsonMap::new();
+        for i in 0..12 {
+            map.insert(format!("secret-key-{}", i), JsonValue::Boolean(true));
+        }
+        let mut value = JsonValue::Object(map);
+        assert_eq!(value.get("secret-key-11"), Some(&JsonValue::Boolean(true)));
+        value.zeroize();
+        assert_eq!(value, JsonValue::Null);
+    }
+
+    #[test]
+    fn test_zeroize_string_clears_contents() {
+        let mut value = JsonValue::String("password".to_string());
+        value.zeroize();
+        assert!(value.is_null());
+    }
+
+    #[test]
+    fn test_parse_error_propagates() {
+        let result = SecretJson::parse(r#"{"key": "unterminated"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_expose_mut() {
+        let mut secret = SecretJson::parse(r#"["a"]"#).unwrap();
+        if let JsonValue::Array(arr) = secret.expose_mut() {
+            arr.push(JsonValue::Boolean(true));
+        }
+        assert_eq!(secret.expose().as_array().map(Vec::len), Some(2));
+    }
+
+    #[test]
+    fn test_debug_does_not_leak() {
+        let secret = SecretJson::new(JsonValue::String("hunter2".to_string()));
+        let output = format!("{:?}", secret);
+        assert_eq!(output, "SecretJson(***)");
+    }
+
+    #[test]
+    fn test_parser_buffers_zeroed() {
+        let mut parser = JsonParser::new();
+        let _ = parser.parse(r#"["leftover", "tokens""#);
+        parser.zeroize_buffers();
+        // Buffers are cleared and the parser is still usable afterwards.
+        assert_eq!(parser.parse("1").unwrap(), JsonValue::Number(1.0.into()));
+    }
+}
@@ -0,0 +1,892 @@
+//! Interoperation with serde.
+//!
+//! With the `serde` feature enabled,
+//! [`JsonValue`](crate::value::JsonValue), [`JsonMap`](crate::map::JsonMap)
+//! and [`JsonNumber`](crate::value::JsonNumber) implement `Serialize` and
+//! `Deserialize`, so they can be embedded in serde-derived types and
+//! written by any serde format. [`to_value`](crate::serde::to_value) and
+//! [`from_value`](crate::serde::from_value) convert between a parsed value
+//! and any type implementing the serde traits, without going through text.
+//!
+//! Numbers keep their integer or float kind across the conversion, but
+//! not their raw source text. Non-finite floats serialize as `null`.
+
+use std::error;
+use std::fmt;
+
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    Unexpected, VariantAccess, Visitor,
+};
+use serde::ser::{self, Serialize, SerializeMap as _, SerializeSeq as _, Serializer};
+use serde::{Deserialize, Deserializer, forward_to_deserialize_any};
+
+use crate::map::JsonMap;
+use crate::value::{JsonNumber, JsonValue};
+
+/// Converts any serializable value into a [`JsonValue`].
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use rust_json_parser::serde::to_value;
+///
+/// let ports = BTreeMap::from([("http", 80), ("https", 443)]);
+/// assert_eq!(to_value(&ports)?.to_string(), r#"{"http":80,"https":443}"#);
+/// # Ok::<(), rust_json_parser::serde::SerdeError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`SerdeError`] if `value`'s `Serialize` implementation fails,
+/// or if it produces a map whose keys are not strings, numbers or
+/// booleans.
+pub fn to_value<T: Serialize>(value: T) -> Result<JsonValue, SerdeError> {
+    value.serialize(ValueSerializer)
+}
+
+/// Converts a [`JsonValue`] into any deserializable type.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::parse_json;
+/// use rust_json_parser::serde::from_value;
+///
+/// let value = parse_json(r#"["Alice", ["admin"], null]"#).unwrap();
+/// let (name, tags, age): (String, Vec<String>, Option<u8>) = from_value(value.clone())?;
+/// assert_eq!((name.as_str(), tags.len(), age), ("Alice", 1, None));
+///
+/// let err = from_value::<Vec<String>>(value).unwrap_err();
+/// assert_eq!(err.to_string(), "invalid type: sequence, expected a string");
+/// # Ok::<(), rust_json_parser::serde::SerdeError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`SerdeError`] if `value` does not have the shape `T` expects.
+pub fn from_value<T: DeserializeOwned>(value: JsonValue) -> Result<T, SerdeError> {
+    T::deserialize(value)
+}
+
+/// An error converting between a [`JsonValue`] and a serde type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerdeError {
+    message: String,
+}
+
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for SerdeError {}
+
+impl ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError {
+            message: msg.to_string(),
+        }
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError {
+            message: msg.to_string(),
+        }
+    }
+}
+
+// --- Serialize ---
+
+impl Serialize for JsonValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonValue::Null => serializer.serialize_unit(),
+            JsonValue::Boolean(b) => serializer.serialize_bool(*b),
+            JsonValue::Number(n) => n.serialize(serializer),
+            JsonValue::String(s) => serializer.serialize_str(s),
+            JsonValue::Array(elements) => {
+                let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                for element in elements {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            JsonValue::Object(map) => map.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for JsonMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut out = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self {
+            out.serialize_entry(key, value)?;
+        }
+        out.end()
+    }
+}
+
+impl Serialize for JsonNumber {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Some(u) = self.as_u64() {
+            serializer.serialize_u64(u)
+        } else if let Some(i) = self.as_i64() {
+            serializer.serialize_i64(i)
+        } else {
+            serializer.serialize_f64(self.as_f64())
+        }
+    }
+}
+
+// --- Deserialize ---
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<JsonValue, E> {
+        Ok(JsonValue::Boolean(b))
+    }
+
+    fn visit_i64<E>(self, i: i64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(i.into()))
+    }
+
+    fn visit_u64<E>(self, u: u64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(u.into()))
+    }
+
+    fn visit_f64<E>(self, f: f64) -> Result<JsonValue, E> {
+        Ok(float(f))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(s.to_string()))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(s))
+    }
+
+    fn visit_none<E>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<JsonValue, D::Error> {
+        JsonValue::deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(JsonValue::Array(elements))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<JsonValue, A::Error> {
+        MapVisitor.visit_map(map).map(JsonValue::Object)
+    }
+}
+
+struct MapVisitor;
+
+impl<'de> Visitor<'de> for MapVisitor {
+    type Value = JsonMap;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<JsonMap, A::Error> {
+        let mut map = JsonMap::with_capacity(access.size_hint().unwrap_or(0));
+        while let Some((key, value)) = access.next_entry()? {
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(MapVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonNumber {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match JsonValue::deserialize(deserializer)? {
+            JsonValue::Number(n) => Ok(n),
+            other => Err(de::Error::invalid_type(unexpected(&other), &"a number")),
+        }
+    }
+}
+
+// Non-finite floats have no JSON representation.
+fn float(f: f64) -> JsonValue {
+    if f.is_finite() {
+        JsonValue::Number(f.into())
+    } else {
+        JsonValue::Null
+    }
+}
+
+fn unexpected(value: &JsonValue) -> Unexpected<'_> {
+    match value {
+        JsonValue::Null => Unexpected::Unit,
+        JsonValue::Boolean(b) => Unexpected::Bool(*b),
+        JsonValue::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(u), _) => Unexpected::Unsigned(u),
+            (None, Some(i)) => Unexpected::Signed(i),
+            (None, None) => Unexpected::Float(n.as_f64()),
+        },
+        JsonValue::String(s) => Unexpected::Str(s),
+        JsonValue::Array(_) => Unexpected::Seq,
+        JsonValue::Object(_) => Unexpected::Map,
+    }
+}
+
+// --- JsonValue as a Deserializer (from_value) ---
+
+impl<'de> Deserializer<'de> for JsonValue {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            JsonValue::Null => visitor.visit_unit(),
+            JsonValue::Boolean(b) => visitor.visit_bool(b),
+            JsonValue::Number(n) => {
+                if let Some(u) = n.as_u64() {
+                    visitor.visit_u64(u)
+                } else if let Some(i) = n.as_i64() {
+                    visitor.visit_i64(i)
+                } else {
+                    visitor.visit_f64(n.as_f64())
+                }
+            }
+            JsonValue::String(s) => visitor.visit_string(s),
+            JsonValue::Array(elements) => visitor.visit_seq(SeqDeserializer {
+                iter: elements.into_iter(),
+            }),
+            JsonValue::Object(map) => visitor.visit_map(MapDeserializer {
+                iter: map.into_iter(),
+                value: None,
+            }),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            JsonValue::Null => visitor.visit_none(),
+            other => visitor.visit_some(other),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        // Unit variants are strings; others are single-member objects.
+        match self {
+            JsonValue::String(variant) => visitor.visit_enum(EnumDeserializer {
+                variant,
+                value: None,
+            }),
+            JsonValue::Object(map) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().expect("one member");
+                visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    value: Some(value),
+                })
+            }
+            other => Err(de::Error::invalid_type(
+                unexpected(&other),
+                &"a string or a single-member object",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, SerdeError> for JsonValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct SeqDeserializer {
+    iter: std::vec::IntoIter<JsonValue>,
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        self.iter
+            .next()
+            .map(|element| seed.deserialize(element))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct MapDeserializer {
+    iter: std::vec::IntoIter<(String, JsonValue)>,
+    // Value of the member whose key was just returned.
+    value: Option<JsonValue>,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before key"))?;
+        seed.deserialize(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    // `None` for a variant written as a bare string.
+    value: Option<JsonValue>,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = SerdeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        mut self,
+        seed: V,
+    ) -> Result<(V::Value, Self), SerdeError> {
+        let variant = std::mem::take(&mut self.variant);
+        Ok((seed.deserialize(variant.into_deserializer())?, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for EnumDeserializer {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.value {
+            None | Some(JsonValue::Null) => Ok(()),
+            Some(other) => Err(de::Error::invalid_type(unexpected(&other), &"unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        match self.value {
+            Some(value) => seed.deserialize(value),
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.value {
+            Some(value @ JsonValue::Array(_)) => value.deserialize_any(visitor),
+            Some(other) => Err(de::Error::invalid_type(
+                unexpected(&other),
+                &"tuple variant",
+            )),
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.value {
+            Some(value @ JsonValue::Object(_)) => value.deserialize_any(visitor),
+            Some(other) => Err(de::Error::invalid_type(
+                unexpected(&other),
+                &"struct variant",
+            )),
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
+
+// --- Serializer producing a JsonValue (to_value) ---
+
+struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = SerializeVariant<SerializeVec>;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeVariant<SerializeMap>;
+
+    fn serialize_bool(self, b: bool) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Boolean(b))
+    }
+
+    fn serialize_i8(self, i: i8) -> Result<JsonValue, SerdeError> {
+        self.serialize_i64(i.into())
+    }
+
+    fn serialize_i16(self, i: i16) -> Result<JsonValue, SerdeError> {
+        self.serialize_i64(i.into())
+    }
+
+    fn serialize_i32(self, i: i32) -> Result<JsonValue, SerdeError> {
+        self.serialize_i64(i.into())
+    }
+
+    fn serialize_i64(self, i: i64) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Number(i.into()))
+    }
+
+    fn serialize_u8(self, u: u8) -> Result<JsonValue, SerdeError> {
+        self.serialize_u64(u.into())
+    }
+
+    fn serialize_u16(self, u: u16) -> Result<JsonValue, SerdeError> {
+        self.serialize_u64(u.into())
+    }
+
+    fn serialize_u32(self, u: u32) -> Result<JsonValue, SerdeError> {
+        self.serialize_u64(u.into())
+    }
+
+    fn serialize_u64(self, u: u64) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Number(u.into()))
+    }
+
+    fn serialize_f32(self, f: f32) -> Result<JsonValue, SerdeError> {
+        self.serialize_f64(f.into())
+    }
+
+    fn serialize_f64(self, f: f64) -> Result<JsonValue, SerdeError> {
+        Ok(float(f))
+    }
+
+    fn serialize_char(self, c: char) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::String(c.to_string()))
+    }
+
+    fn serialize_str(self, s: &str) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::String(s.to_string()))
+    }
+
+    fn serialize_bytes(self, bytes: &[u8]) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Array(
+            bytes.iter().map(|&b| JsonValue::from(b)).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<JsonValue, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<JsonValue, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<JsonValue, SerdeError> {
+        Ok(self::variant(variant, to_value(value)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeVec, SerdeError> {
+        Ok(SerializeVec {
+            elements: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeVec, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeVec>, SerdeError> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, SerdeError> {
+        Ok(SerializeMap {
+            map: JsonMap::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeMap>, SerdeError> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+struct SerializeVec {
+    elements: Vec<JsonValue>,
+}
+
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.elements.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Array(self.elements))
+    }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeMap {
+    map: JsonMap,
+    // Key of the entry whose value comes next.
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.key = Some(match to_value(key)? {
+            JsonValue::String(s) => s,
+            JsonValue::Number(n) => n.to_string(),
+            JsonValue::Boolean(b) => b.to_string(),
+            other => {
+                return Err(ser::Error::custom(format!(
+                    "map key must be a string, number or boolean, not {:?}",
+                    other
+                )));
+            }
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("value serialized before key"))?;
+        self.map.insert(key, to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Object(self.map))
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.map.insert(key.to_string(), to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        Ok(JsonValue::Object(self.map))
+    }
+}
+
+// A tuple or struct variant: the fields wrapped in `{"variant": ...}`.
+struct SerializeVariant<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+// Wraps a variant's content as `{"variant": value}`.
+fn variant(name: &str, value: JsonValue) -> JsonValue {
+    let mut map = JsonMap::with_capacity(1);
+    map.insert(name.to_string(), value);
+    JsonValue::Object(map)
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeVec> {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        Ok(variant(self.variant, JsonValue::Array(self.inner.elements)))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeMap> {
+    type Ok = JsonValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<JsonValue, SerdeError> {
+        Ok(variant(self.variant, JsonValue::Object(self.inner.map)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+    use crate::parser::parse_json;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        id: u64,
+        name: String,
+        score: Option<f64>,
+        tags: Vec<String>,
+        kind: Kind,
+        extra: JsonValue,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Kind {
+        Plain,
+        Tagged(String),
+        Pair(i32, i32),
+        Point { x: i8, y: i8 },
+    }
+
+    #[test]
+    fn test_round_trip_struct() {
+        let value = parse_json(
+            r#"{"id": 7, "name": "a", "score": null, "tags": ["x"],
+                "kind": {"Point": {"x": 1, "y": -2}}, "extra": {"k": [true, 1.5]}}"#,
+        )
+        .unwrap();
+        let record: Record = from_value(value.clone()).unwrap();
+        assert_eq!(record.kind, Kind::Point { x: 1, y: -2 });
+        assert_eq!(record.extra, json!({"k": [true, 1.5]}));
+        assert_eq!(to_value(&record).unwrap(), value);
+    }
+
+    #[test]
+    fn test_enum_representations() {
+        for (kind, json) in [
+            (Kind::Plain, r#""Plain""#),
+            (Kind::Tagged("t".into()), r#"{"Tagged":"t"}"#),
+            (Kind::Pair(1, -1), r#"{"Pair":[1,-1]}"#),
+        ] {
+            let value = to_value(&kind).unwrap();
+            assert_eq!(value.to_string(), json);
+            assert_eq!(from_value::<Kind>(value).unwrap(), kind);
+        }
+        assert!(from_value::<Kind>(json!({"Plain": 1})).is_err());
+        assert!(from_value::<Kind>(json!({"Tagged": "t", "Plain": null})).is_err());
+    }
+
+    #[test]
+    fn test_numbers_and_keys() {
+        let value = to_value((u64::MAX, -1i8, 0.5f32, f64::NAN)).unwrap();
+        assert_eq!(value.to_string(), "[18446744073709551615,-1,0.5,null]");
+        let (big, small): (u64, i64) = from_value(json!([u64::MAX, -3])).unwrap();
+        assert_eq!((big, small), (u64::MAX, -3));
+        assert!(from_value::<u8>(json!(256)).is_err());
+
+        let keyed = std::collections::BTreeMap::from([(1, "a"), (2, "b")]);
+        assert_eq!(
+            to_value(&keyed).unwrap().to_string(),
+            r#"{"1":"a","2":"b"}"#
+        );
+        let err = to_value(std::collections::HashMap::from([((1, 2), 3)])).unwrap_err();
+        assert!(err.to_string().starts_with("map key must be"));
+    }
+
+    #[test]
+    fn test_value_through_its_own_deserializer() {
+        let value = json!({"a": [1, -2, 2.5, "s", null, {"b": false}]});
+        assert_eq!(from_value::<JsonValue>(value.clone()).unwrap(), value);
+        let map: JsonMap = from_value(value.clone()).unwrap();
+        assert_eq!(map.len(), 1);
+        let err = from_value::<JsonMap>(json!([1])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid type: sequence, expected a JSON object"
+        );
+    }
+}
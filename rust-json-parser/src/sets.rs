@@ -0,0 +1,184 @@
+//! Set operations over arrays of values.
+//!
+//! Reconciling two tag lists or two arrays of IDs means treating arrays as
+//! sets. [`union`](crate::sets::union),
+//! [`intersection`](crate::sets::intersection), and
+//! [`difference`](crate::sets::difference) compare elements with
+//! [`JsonValue`](crate::value::JsonValue)'s equality, so `1` matches `1.0`
+//! and objects match whatever their member order, and find matches by
+//! hashing each element's content rather than comparing every pair.
+//! [`SetOptions`](crate::sets::SetOptions) chooses the order of the result.
+
+use std::collections::HashMap;
+
+use crate::expr::compare;
+use crate::identity::{ContentHash, content_hash};
+use crate::value::JsonValue;
+
+/// The order of the elements a set operation returns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SetOrder {
+    /// The order in which elements first appear, in the left array and
+    /// then the right one.
+    #[default]
+    Preserve,
+    /// Ascending, in the order used by [`expr`](crate::expr): null, false,
+    /// true, numbers, strings, arrays, objects.
+    Sorted,
+}
+
+/// Settings for set operations. Every result is free of duplicates.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::json;
+/// use rust_json_parser::sets::{SetOptions, SetOrder};
+///
+/// let old = json!(["rust", "json", {"id": 1, "v": 2}, "cli"]);
+/// let new = json!(["parser", "rust", {"v": 2, "id": 1.0}, "json", "rust"]);
+/// let (old, new) = (old.as_array().unwrap(), new.as_array().unwrap());
+///
+/// let options = SetOptions::new();
+/// assert_eq!(
+///     options.union(old, new),
+///     json!(["rust", "json", {"id": 1, "v": 2}, "cli", "parser"]).as_array().unwrap()[..]
+/// );
+/// assert_eq!(options.difference(new, old), [json!("parser")]);
+///
+/// let sorted = SetOptions::new().order(SetOrder::Sorted);
+/// assert_eq!(
+///     sorted.intersection(old, new),
+///     [json!("json"), json!("rust"), json!({"id": 1, "v": 2})]
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SetOptions {
+    order: SetOrder,
+}
+
+impl SetOptions {
+    /// Creates the default options, which preserve order.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the order of results.
+    pub fn order(mut self, order: SetOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Returns the elements in either array.
+    pub fn union(&self, left: &[JsonValue], right: &[JsonValue]) -> Vec<JsonValue> {
+        let mut seen = ValueSet::default();
+        self.collect(left.iter().chain(right).filter(|v| seen.insert(v)))
+    }
+
+    /// Returns the elements of `left` that are also in `right`.
+    pub fn intersection(&self, left: &[JsonValue], right: &[JsonValue]) -> Vec<JsonValue> {
+        let right = ValueSet::from_slice(right);
+        let mut seen = ValueSet::default();
+        self.collect(left.iter().filter(|v| right.contains(v) && seen.insert(v)))
+    }
+
+    /// Returns the elements of `left` that are not in `right`.
+    pub fn difference(&self, left: &[JsonValue], right: &[JsonValue]) -> Vec<JsonValue> {
+        let right = ValueSet::from_slice(right);
+        let mut seen = ValueSet::default();
+        self.collect(left.iter().filter(|v| !right.contains(v) && seen.insert(v)))
+    }
+
+    fn collect<'a>(&self, values: impl Iterator<Item = &'a JsonValue>) -> Vec<JsonValue> {
+        let mut values: Vec<JsonValue> = values.cloned().collect();
+        if self.order == SetOrder::Sorted {
+            values.sort_by(compare);
+        }
+        values
+    }
+}
+
+/// Returns the elements in either array, in order of first appearance.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::json;
+/// use rust_json_parser::sets::union;
+///
+/// let ids = union(&[json!(3), json!(1)], &[json!(1.0), json!(2), json!(3)]);
+/// assert_eq!(ids, [json!(3), json!(1), json!(2)]);
+/// ```
+pub fn union(left: &[JsonValue], right: &[JsonValue]) -> Vec<JsonValue> {
+    SetOptions::default().union(left, right)
+}
+
+/// Returns the elements of `left` that are also in `right`, in `left`'s
+/// order.
+pub fn intersection(left: &[JsonValue], right: &[JsonValue]) -> Vec<JsonValue> {
+    SetOptions::default().intersection(left, right)
+}
+
+/// Returns the elements of `left` that are not in `right`, in `left`'s
+/// order.
+pub fn difference(left: &[JsonValue], right: &[JsonValue]) -> Vec<JsonValue> {
+    SetOptions::default().difference(left, right)
+}
+
+// Values bucketed by content hash; equal values always share a bucket.
+#[derive(Default)]
+struct ValueSet<'a> {
+    buckets: HashMap<ContentHash, Vec<&'a JsonValue>>,
+}
+
+impl<'a> ValueSet<'a> {
+    fn from_slice(values: &'a [JsonValue]) -> Self {
+        let mut set = Self::default();
+        for value in values {
+            set.insert(value);
+        }
+        set
+    }
+
+    fn contains(&self, value: &JsonValue) -> bool {
+        self.buckets
+            .get(&content_hash(value))
+            .is_some_and(|bucket| bucket.contains(&value))
+    }
+
+    // Returns `true` if the value was not already present.
+    fn insert(&mut self, value: &'a JsonValue) -> bool {
+        let bucket = self.buckets.entry(content_hash(value)).or_default();
+        let new = !bucket.contains(&value);
+        if new {
+            bucket.push(value);
+        }
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    #[test]
+    fn test_duplicates_and_order() {
+        let left = [json!("b"), json!("a"), json!("b"), json!(null), json!([1])];
+        let right = [json!([1.0]), json!("c"), json!("a")];
+        assert_eq!(
+            union(&left, &right),
+            [json!("b"), json!("a"), json!(null), json!([1]), json!("c")]
+        );
+        assert_eq!(intersection(&left, &right), [json!("a"), json!([1])]);
+        assert_eq!(difference(&left, &right), [json!("b"), json!(null)]);
+
+        let sorted = SetOptions::new().order(SetOrder::Sorted);
+        assert_eq!(
+            sorted.union(&left, &right),
+            [json!(null), json!("a"), json!("b"), json!("c"), json!([1])]
+        );
+        assert_eq!(sorted.difference(&right, &left), [json!("c")]);
+        assert!(intersection(&left, &[]).is_empty());
+    }
+}
@@ -0,0 +1,408 @@
+//! Golden-file snapshot testing for JSON values.
+//!
+//! Comparing serialized JSON strings is fragile: object key order, number
+//! formatting, and volatile fields such as timestamps all cause spurious
+//! failures. [`Snapshot`](crate::snapshot::Snapshot) normalizes a value
+//! first -- sorted keys, canonical numbers, ignored paths replaced by a
+//! placeholder -- and compares it against a pretty-printed file on disk.
+//!
+//! When the stored file is missing it is created and the check passes.
+//! When it differs, the new rendering is written next to it with a `.new`
+//! suffix (or over it, if `UPDATE_SNAPSHOTS` is set in the environment)
+//! and the failure lists a structural diff by JSON Pointer.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::map::JsonMap;
+use crate::parser::parse_json;
+use crate::pointer::{escape_segment, split_pointer};
+use crate::value::{JsonValue, pretty_print};
+
+/// The string that replaces values at ignored paths.
+pub const IGNORED: &str = "[ignored]";
+
+/// Normalization and comparison settings for golden files.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rust_json_parser::parser::parse_json;
+/// use rust_json_parser::snapshot::Snapshot;
+///
+/// let response = parse_json(r#"{"id": 7, "created_at": "2024-05-01T10:00:00Z"}"#)?;
+/// Snapshot::new()
+///     .ignore("/created_at")
+///     .assert_matches("tests/snapshots/user.json", &response);
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    ignored: Vec<Vec<String>>,
+}
+
+impl Snapshot {
+    /// Creates settings with no ignored paths.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ignores the value at a JSON Pointer.
+    ///
+    /// A `*` segment matches any object key or array index, so
+    /// `/items/*/id` ignores the `id` of every element of `items`.
+    pub fn ignore(mut self, pointer: &str) -> Self {
+        self.ignored.push(split_pointer(pointer));
+        self
+    }
+
+    /// Returns a normalized copy of `value` with ignored paths replaced by
+    /// [`IGNORED`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    /// use rust_json_parser::snapshot::Snapshot;
+    ///
+    /// let value = parse_json(r#"{"id": 1, "ts": 123}"#)?;
+    /// let normalized = Snapshot::new().ignore("/ts").normalize(&value);
+    /// assert_eq!(normalized.get("ts").and_then(|v| v.as_str()), Some("[ignored]"));
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn normalize(&self, value: &JsonValue) -> JsonValue {
+        let mut path = Vec::new();
+        self.normalize_at(value, &mut path)
+    }
+
+    /// Renders a value the way it is stored on disk: normalized,
+    /// pretty-printed with two-space indentation and sorted keys, and
+    /// terminated by a newline.
+    pub fn render(&self, value: &JsonValue) -> String {
+        let mut rendered = pretty_print(&self.normalize(value), 2, 0);
+        rendered.push('\n');
+        rendered
+    }
+
+    /// Compares a value against the snapshot stored at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SnapshotError::Mismatch`] if the stored snapshot differs
+    /// (after writing the new rendering), or [`SnapshotError::Io`] if the
+    /// file could not be read or written.
+    pub fn check(&self, path: impl AsRef<Path>, value: &JsonValue) -> Result<(), SnapshotError> {
+        let path = path.as_ref();
+        let rendered = self.render(value);
+        let stored = match fs::read_to_string(path) {
+            Ok(stored) => stored,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                write_file(path, &rendered)?;
+                return Ok(());
+            }
+            Err(e) => return Err(SnapshotError::Io(e)),
+        };
+        if stored == rendered {
+            return Ok(());
+        }
+
+        let expected = match parse_json(&stored) {
+            Ok(v) => self.normalize(&v),
+            Err(_) => JsonValue::String(stored),
+        };
+        let actual = self.normalize(value);
+        let mut diff = Vec::new();
+        structural_diff(&mut String::new(), &expected, &actual, &mut diff);
+
+        let written = if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            path.to_path_buf()
+        } else {
+            let mut new_path = path.as_os_str().to_owned();
+            new_path.push(".new");
+            PathBuf::from(new_path)
+        };
+        write_file(&written, &rendered)?;
+        Err(SnapshotError::Mismatch {
+            path: path.to_path_buf(),
+            written,
+            diff,
+        })
+    }
+
+    /// Like [`check`](Self::check), but panics with the diff on failure.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshot does not match or cannot be accessed.
+    pub fn assert_matches(&self, path: impl AsRef<Path>, value: &JsonValue) {
+        if let Err(e) = self.check(path, value) {
+            panic!("{}", e);
+        }
+    }
+
+    fn is_ignored(&self, path: &[String]) -> bool {
+        self.ignored.iter().any(|pattern| {
+            pattern.len() == path.len()
+                && pattern
+                    .iter()
+                    .zip(path)
+                    .all(|(p, segment)| p == "*" || p == segment)
+        })
+    }
+
+    fn normalize_at(&self, value: &JsonValue, path: &mut Vec<String>) -> JsonValue {
+        if self.is_ignored(path) {
+            return JsonValue::String(IGNORED.to_string());
+        }
+        match value {
+            JsonValue::Array(arr) => {
+                let mut items = Vec::with_capacity(arr.len());
+                for (i, item) in arr.iter().enumerate() {
+                    path.push(i.to_string());
+                    items.push(self.normalize_at(item, path));
+                    path.pop();
+                }
+                JsonValue::Array(items)
+            }
+            JsonValue::Object(map) => {
+                let mut normalized = JsonMap::with_capacity(map.len());
+                for (key, item) in value.iter_sorted() {
+                    path.push(key.clone());
+                    normalized.insert(key.clone(), self.normalize_at(item, path));
+                    path.pop();
+                }
+                JsonValue::Object(normalized)
+            }
+            // Negative zero serializes as "-0"; fold it into 0.
+            JsonValue::Number(n) if n.is_f64() && *n == 0.0 => JsonValue::Number(0.0.into()),
+            // Drop any preserved raw lexeme so equal numbers render alike,
+            // but keep integers exact: going through f64 would round those
+            // past 2^53 and hide a changed ID.
+            JsonValue::Number(n) => JsonValue::Number(n.without_raw()),
+            other => other.clone(),
+        }
+    }
+}
+
+/// Errors reported by [`Snapshot::check`].
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The value differs from the stored snapshot.
+    Mismatch {
+        /// The stored snapshot file.
+        path: PathBuf,
+        /// Where the new rendering was written.
+        written: PathBuf,
+        /// One line per difference, e.g. `~ /user/name: "a" -> "b"`.
+        diff: Vec<String>,
+    },
+    /// Reading or writing a snapshot file failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Mismatch {
+                path,
+                written,
+                diff,
+            } => {
+                writeln!(f, "Snapshot mismatch for {}", path.display())?;
+                for line in diff {
+                    writeln!(f, "  {}", line)?;
+                }
+                write!(f, "New snapshot written to {}", written.display())
+            }
+            SnapshotError::Io(e) => write!(f, "Snapshot I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::Io(e) => Some(e),
+            SnapshotError::Mismatch { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+fn write_file(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
+}
+
+/// Appends one line per difference between `expected` and `actual`.
+fn structural_diff(
+    path: &mut String,
+    expected: &JsonValue,
+    actual: &JsonValue,
+    out: &mut Vec<String>,
+) {
+    let display = |p: &str| {
+        if p.is_empty() {
+            "/".to_string()
+        } else {
+            p.to_string()
+        }
+    };
+    match (expected, actual) {
+        (JsonValue::Object(old), JsonValue::Object(new)) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_segment(key));
+                match (old.get(key), new.get(key)) {
+                    (Some(o), Some(n)) => structural_diff(path, o, n, out),
+                    (Some(o), None) => out.push(format!("- {}: {}", path, o)),
+                    (None, Some(n)) => out.push(format!("+ {}: {}", path, n)),
+                    (None, None) => {}
+                }
+                path.truncate(len);
+            }
+        }
+        (JsonValue::Array(old), JsonValue::Array(new)) => {
+            for i in 0..old.len().max(new.len()) {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&i.to_string());
+                match (old.get(i), new.get(i)) {
+                    (Some(o), Some(n)) => structural_diff(path, o, n, out),
+                    (Some(o), None) => out.push(format!("- {}: {}", path, o)),
+                    (None, Some(n)) => out.push(format!("+ {}: {}", path, n)),
+                    (None, None) => {}
+                }
+                path.truncate(len);
+            }
+        }
+        (o, n) if o != n => out.push(format!("~ {}: {} -> {}", display(path), o, n)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("rjp-snapshot-{}", std::process::id()));
+        dir.join(name)
+    }
+
+    #[test]
+    fn test_render_sorts_keys() {
+        let value = parse_json(r#"{"b": 1, "a": [true, null]}"#).unwrap();
+        let rendered = Snapshot::new().render(&value);
+        assert_eq!(
+            rendered,
+            "{\n  \"a\": [\n    true,\n    null\n  ],\n  \"b\": 1\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_ignore_wildcard() {
+        let value = parse_json(r#"{"items": [{"id": 1, "v": 2}, {"id": 3, "v": 4}]}"#).unwrap();
+        let normalized = Snapshot::new().ignore("/items/*/id").normalize(&value);
+        let items = normalized.get("items").and_then(|v| v.as_array()).unwrap();
+        for item in items {
+            assert_eq!(item.get("id").and_then(|v| v.as_str()), Some(IGNORED));
+            assert!(item.get("v").and_then(|v| v.as_f64()).is_some());
+        }
+    }
+
+    #[test]
+    fn test_ignore_escaped_pointer() {
+        let value = parse_json(r#"{"a/b": 1, "c~d": 2}"#).unwrap();
+        let normalized = Snapshot::new()
+            .ignore("/a~1b")
+            .ignore("/c~0d")
+            .normalize(&value);
+        assert_eq!(
+            normalized.get("a/b").and_then(|v| v.as_str()),
+            Some(IGNORED)
+        );
+        assert_eq!(
+            normalized.get("c~d").and_then(|v| v.as_str()),
+            Some(IGNORED)
+        );
+    }
+
+    #[test]
+    fn test_negative_zero_normalized() {
+        let normalized = Snapshot::new().normalize(&JsonValue::Number((-0.0).into()));
+        assert_eq!(normalized.to_string(), "0");
+    }
+
+    #[test]
+    fn test_large_integers_kept_exact() {
+        let snapshot = Snapshot::new();
+        let a = parse_json("[9007199254740993, 18446744073709551615]").unwrap();
+        let b = parse_json("[9007199254740992, 18446744073709551614]").unwrap();
+        assert_ne!(snapshot.normalize(&a), snapshot.normalize(&b));
+        assert_eq!(
+            snapshot.render(&a),
+            "[\n  9007199254740993,\n  18446744073709551615\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_structural_diff_lines() {
+        let old = parse_json(r#"{"a": 1, "b": [1, 2], "gone": true}"#).unwrap();
+        let new = parse_json(r#"{"a": 2, "b": [1], "added": null}"#).unwrap();
+        let mut diff = Vec::new();
+        structural_diff(&mut String::new(), &old, &new, &mut diff);
+        assert_eq!(
+            diff,
+            vec![
+                "~ /a: 1 -> 2".to_string(),
+                "+ /added: null".to_string(),
+                "- /b/1: 2".to_string(),
+                "- /gone: true".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_creates_then_matches_then_mismatches() {
+        let path = temp_path("roundtrip.json");
+        let _ = fs::remove_file(&path);
+        let snapshot = Snapshot::new().ignore("/ts");
+
+        let first = parse_json(r#"{"name": "a", "ts": 1}"#).unwrap();
+        snapshot.check(&path, &first).unwrap();
+        assert!(path.exists());
+
+        let same = parse_json(r#"{"ts": 2, "name": "a"}"#).unwrap();
+        snapshot.check(&path, &same).unwrap();
+
+        let changed = parse_json(r#"{"name": "b", "ts": 3}"#).unwrap();
+        match snapshot.check(&path, &changed) {
+            Err(SnapshotError::Mismatch { written, diff, .. }) => {
+                assert_eq!(diff, vec![r#"~ /name: "a" -> "b""#.to_string()]);
+                assert!(written.to_string_lossy().ends_with(".json.new"));
+                let new_contents = fs::read_to_string(&written).unwrap();
+                assert!(new_contents.contains("\"b\""));
+                let _ = fs::remove_file(written);
+            }
+            other => panic!("Expected mismatch, got {:?}", other),
+        }
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,757 @@
+//! Event-based parsing for documents too large to hold in memory.
+//!
+//! [`JsonParser`](crate::parser::JsonParser) builds the whole
+//! [`JsonValue`](crate::value::JsonValue) tree before returning. A
+//! [`JsonStreamParser`](crate::stream::JsonStreamParser) instead reports
+//! the document as a sequence of [`Event`](crate::stream::Event)s (start
+//! and end of containers, object keys, and scalar values) while reading
+//! it through a [`ReadTokenizer`](crate::reader::ReadTokenizer). Memory
+//! use is bounded by the nesting depth and the largest single string or
+//! number, not by document size.
+//!
+//! An [`EventWriter`](crate::stream::EventWriter) turns events back into
+//! compact JSON text, so a document can be rewritten in one pass, e.g.
+//! through the adapters in [`filter`](crate::filter).
+//!
+//! A [`StreamValidator`](crate::stream::StreamValidator) attached with
+//! [`JsonStreamParser::validator`](crate::stream::JsonStreamParser::validator)
+//! checks each value as it is parsed, so a huge document that breaks its
+//! contract early fails early.
+
+use std::error;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::ops::Range;
+
+use crate::error::JsonError;
+use crate::path::JsonPath;
+use crate::reader::{ReadTokenizer, located};
+use crate::tokenizer::Token;
+use crate::value::{JsonFormat, JsonValue};
+
+/// A single step through a JSON document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The `{` opening an object.
+    StartObject,
+    /// The `}` closing the innermost open object.
+    EndObject,
+    /// The `[` opening an array.
+    StartArray,
+    /// The `]` closing the innermost open array.
+    EndArray,
+    /// An object key. The key's value follows as the next event.
+    Key(String),
+    /// A scalar value: a string, number, boolean, or null. Arrays and
+    /// objects are reported through their start and end events instead.
+    Value(JsonValue),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Container {
+    Array,
+    Object,
+}
+
+// What the grammar allows next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    // Any value: the document root, after a colon, or after a comma in
+    // an array.
+    Value,
+    // A value or `]`, right after `[`.
+    FirstElement,
+    // A key or `}`, right after `{`.
+    FirstKey,
+    // A key, after a comma in an object.
+    Key,
+    Colon,
+    // A comma or the closer of the innermost container.
+    AfterValue,
+    // Only the end of input.
+    Done,
+}
+
+/// Pull parser that produces [`Event`]s from any [`Read`] source.
+///
+/// Events are read with [`next_event`](Self::next_event) or by
+/// iterating. The input must be exactly one JSON value; structural
+/// errors are reported the same way as by [`JsonParser`], wrapped in an
+/// [`io::Error`] like the errors of [`ReadTokenizer`]. After the first
+/// error, iteration ends.
+///
+/// [`JsonParser`]: crate::parser::JsonParser
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::stream::{Event, JsonStreamParser};
+///
+/// let input = br#"{"ids": [1, 2], "ok": true}"#;
+/// let mut total = 0.0;
+/// for event in JsonStreamParser::new(&input[..]) {
+///     if let Event::Value(value) = event? {
+///         total += value.as_f64().unwrap_or(0.0);
+///     }
+/// }
+/// assert_eq!(total, 3.0);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct JsonStreamParser<R> {
+    tokenizer: ReadTokenizer<R>,
+    stack: Vec<Container>,
+    state: State,
+    finished: bool,
+    max_depth: usize,
+    validation: Option<Validation>,
+}
+
+impl<R: Read> JsonStreamParser<R> {
+    /// Creates a stream parser that reads from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self::from_tokenizer(ReadTokenizer::new(reader))
+    }
+
+    /// Creates a stream parser over an already configured tokenizer,
+    /// e.g. one that preserves raw numbers.
+    pub fn from_tokenizer(tokenizer: ReadTokenizer<R>) -> Self {
+        Self {
+            tokenizer,
+            stack: Vec::new(),
+            state: State::Value,
+            finished: false,
+            max_depth: usize::MAX,
+            validation: None,
+        }
+    }
+
+    /// Sets how many arrays and objects may be open at once. Deeper input
+    /// fails with [`JsonError::DepthLimitExceeded`]. Unlimited by default,
+    /// since the open containers are tracked on the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::stream::JsonStreamParser;
+    ///
+    /// let mut parser = JsonStreamParser::new(&b"[[[1]]]"[..]).max_depth(2);
+    /// let err = parser.find_map(Result::err).unwrap();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "Nesting deeper than 2 levels at line 1, column 3 (position 2)"
+    /// );
+    /// ```
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Checks every value with `validator` as soon as it is parsed.
+    ///
+    /// The first rejected event ends parsing with an
+    /// [`io::ErrorKind::InvalidData`] error wrapping a
+    /// [`StreamViolation`], which records the path and byte span of the
+    /// offending value. Paths are tracked only while a validator is
+    /// attached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::stream::{Event, JsonStreamParser, StreamViolation};
+    ///
+    /// let input = br#"{"ids": [1, 2, "three", 4]}"#;
+    /// let parser = JsonStreamParser::new(&input[..]).validator(|_: &_, event: &Event| match event {
+    ///     Event::Value(v) if v.as_f64().is_none() => Err(format!("expected a number, found {}", v)),
+    ///     _ => Ok(()),
+    /// });
+    /// let err = parser.collect::<Result<Vec<_>, _>>().unwrap_err();
+    /// let violation = err.get_ref().unwrap().downcast_ref::<StreamViolation>().unwrap();
+    /// assert_eq!(violation.path.to_pointer(), "/ids/2");
+    /// assert_eq!(violation.span, 15..22);
+    /// assert_eq!(
+    ///     violation.to_string(),
+    ///     r#"Invalid value at "/ids/2" (bytes 15..22): expected a number, found "three""#
+    /// );
+    /// ```
+    pub fn validator(mut self, validator: impl StreamValidator + Send + 'static) -> Self {
+        self.validation = Some(Validation {
+            validator: Box::new(validator),
+            path: JsonPath::root(),
+            open: Vec::new(),
+        });
+        self
+    }
+
+    /// Returns the number of arrays and objects currently open.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::stream::JsonStreamParser;
+    ///
+    /// let mut parser = JsonStreamParser::new(&b"[[1]]"[..]);
+    /// parser.next_event()?;
+    /// parser.next_event()?;
+    /// assert_eq!(parser.depth(), 2);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Reads the next event, or `None` once the document has been fully
+    /// read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::stream::{Event, JsonStreamParser};
+    /// use rust_json_parser::value::JsonValue;
+    ///
+    /// let mut parser = JsonStreamParser::new(&br#"{"a": null}"#[..]);
+    /// assert_eq!(parser.next_event()?, Some(Event::StartObject));
+    /// assert_eq!(parser.next_event()?, Some(Event::Key("a".to_string())));
+    /// assert_eq!(parser.next_event()?, Some(Event::Value(JsonValue::Null)));
+    /// assert_eq!(parser.next_event()?, Some(Event::EndObject));
+    /// assert_eq!(parser.next_event()?, None);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails or the input is not a single
+    /// valid JSON value.
+    pub fn next_event(&mut self) -> io::Result<Option<Event>> {
+        loop {
+            let token = self.tokenizer.next_token()?;
+            let event = match (self.state, token) {
+                (State::Done, None) => return Ok(None),
+                (State::Value | State::FirstElement, Some(Token::LeftBracket)) => {
+                    self.open(Container::Array)?
+                }
+                (State::Value | State::FirstElement, Some(Token::LeftBrace)) => {
+                    self.open(Container::Object)?
+                }
+                (State::FirstElement, Some(Token::RightBracket)) => self.close(),
+                (State::FirstKey, Some(Token::RightBrace)) => self.close(),
+                (State::Value | State::FirstElement, Some(token)) => {
+                    let value = match token {
+                        Token::String(s) => JsonValue::String(s),
+                        Token::Number(n) => JsonValue::Number(n),
+                        Token::Boolean(b) => JsonValue::Boolean(b),
+                        Token::Null => JsonValue::Null,
+                        other => return Err(self.unexpected(Some(other))),
+                    };
+                    self.state = self.after_value();
+                    Event::Value(value)
+                }
+                (State::FirstKey | State::Key, Some(Token::String(key))) => {
+                    self.state = State::Colon;
+                    Event::Key(key)
+                }
+                (State::Colon, Some(Token::Colon)) => {
+                    self.state = State::Value;
+                    continue;
+                }
+                (State::AfterValue, Some(Token::Comma)) => {
+                    self.state = match self.stack.last() {
+                        Some(Container::Object) => State::Key,
+                        _ => State::Value,
+                    };
+                    continue;
+                }
+                (State::AfterValue, Some(Token::RightBracket))
+                    if self.stack.last() == Some(&Container::Array) =>
+                {
+                    self.close()
+                }
+                (State::AfterValue, Some(Token::RightBrace))
+                    if self.stack.last() == Some(&Container::Object) =>
+                {
+                    self.close()
+                }
+                (_, token) => return Err(self.unexpected(token)),
+            };
+            if let Some(validation) = &mut self.validation {
+                let span = self.tokenizer.token_start().0..self.tokenizer.position();
+                validation.check(&event, span)?;
+            }
+            return Ok(Some(event));
+        }
+    }
+
+    fn open(&mut self, container: Container) -> io::Result<Event> {
+        if self.stack.len() >= self.max_depth {
+            let mark = self.tokenizer.token_start();
+            let error = JsonError::DepthLimitExceeded {
+                max_depth: self.max_depth,
+                position: mark.0,
+                line: 0,
+                column: 0,
+            };
+            return Err(located(error, mark));
+        }
+        self.stack.push(container);
+        Ok(match container {
+            Container::Array => {
+                self.state = State::FirstElement;
+                Event::StartArray
+            }
+            Container::Object => {
+                self.state = State::FirstKey;
+                Event::StartObject
+            }
+        })
+    }
+
+    fn close(&mut self) -> Event {
+        let container = self.stack.pop();
+        self.state = self.after_value();
+        match container {
+            Some(Container::Object) => Event::EndObject,
+            _ => Event::EndArray,
+        }
+    }
+
+    fn after_value(&self) -> State {
+        if self.stack.is_empty() {
+            State::Done
+        } else {
+            State::AfterValue
+        }
+    }
+
+    // Builds the error for a token (or end of input) the current state
+    // does not allow, using the parser's wording.
+    fn unexpected(&self, token: Option<Token>) -> io::Error {
+        let expected = match self.state {
+            State::Value | State::FirstElement => "JSON value",
+            State::FirstKey | State::Key => "string key",
+            State::Colon => "colon",
+            State::AfterValue if self.stack.last() == Some(&Container::Object) => {
+                "comma or closing brace"
+            }
+            State::AfterValue => "comma or closing bracket",
+            State::Done => "end of input",
+        }
+        .to_string();
+        let mark = self.tokenizer.token_start();
+        let error = match token {
+            Some(token) => JsonError::UnexpectedToken {
+                expected,
+                found: format!("{:?}", token),
+                position: mark.0,
+                line: 0,
+                column: 0,
+            },
+            None => JsonError::UnexpectedEndOfInput {
+                expected,
+                position: mark.0,
+                line: 0,
+                column: 0,
+            },
+        };
+        located(error, mark)
+    }
+}
+
+impl<R: Read> Iterator for JsonStreamParser<R> {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = self.next_event().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.finished = true;
+        }
+        result
+    }
+}
+
+/// Checks a document event by event while
+/// [`JsonStreamParser`] reads it.
+///
+/// [`check`](Self::check) sees every event except [`Event::Key`], with
+/// the path of the value it belongs to: start and end events carry the
+/// container's path, and a scalar [`Event::Value`] its own. Member keys
+/// appear as the last segment of their value's path. Closures of the
+/// right signature implement the trait.
+pub trait StreamValidator {
+    /// Accepts the event, or rejects it with a message saying why.
+    fn check(&mut self, path: &JsonPath, event: &Event) -> Result<(), String>;
+}
+
+impl<F: FnMut(&JsonPath, &Event) -> Result<(), String>> StreamValidator for F {
+    fn check(&mut self, path: &JsonPath, event: &Event) -> Result<(), String> {
+        self(path, event)
+    }
+}
+
+/// A value rejected by the [`StreamValidator`] attached to a
+/// [`JsonStreamParser`].
+///
+/// It reaches the caller wrapped in an [`io::Error`] of kind
+/// [`InvalidData`](io::ErrorKind::InvalidData), and can be recovered with
+/// `get_ref` or `into_inner` and `downcast`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamViolation {
+    /// Where the value is.
+    pub path: JsonPath,
+    /// Byte range of the value in the input: the whole container for an
+    /// end event, otherwise the token just read.
+    pub span: Range<usize>,
+    /// The validator's explanation.
+    pub message: String,
+}
+
+impl fmt::Display for StreamViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid value at {:?} (bytes {}..{}): {}",
+            self.path.to_pointer(),
+            self.span.start,
+            self.span.end,
+            self.message
+        )
+    }
+}
+
+impl error::Error for StreamViolation {}
+
+impl From<StreamViolation> for io::Error {
+    fn from(violation: StreamViolation) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, violation)
+    }
+}
+
+// The validator with the path bookkeeping it needs.
+struct Validation {
+    validator: Box<dyn StreamValidator + Send>,
+    path: JsonPath,
+    // Start offset of each open container, and for arrays the index of
+    // the next element.
+    open: Vec<(usize, Option<usize>)>,
+}
+
+impl Validation {
+    // `span` is the byte range of the token that produced `event`.
+    fn check(&mut self, event: &Event, span: Range<usize>) -> Result<(), StreamViolation> {
+        match event {
+            Event::Key(key) => {
+                self.path.push(key.as_str());
+                return Ok(());
+            }
+            Event::EndArray | Event::EndObject => {
+                let (start, _) = self.open.pop().expect("an open container");
+                self.report(event, start..span.end)?;
+                self.finish_value();
+                return Ok(());
+            }
+            _ => {}
+        }
+        if let Some(&(_, Some(index))) = self.open.last() {
+            self.path.push(index);
+        }
+        self.report(event, span.clone())?;
+        match event {
+            Event::StartArray => self.open.push((span.start, Some(0))),
+            Event::StartObject => self.open.push((span.start, None)),
+            _ => self.finish_value(),
+        }
+        Ok(())
+    }
+
+    fn report(&mut self, event: &Event, span: Range<usize>) -> Result<(), StreamViolation> {
+        self.validator
+            .check(&self.path, event)
+            .map_err(|message| StreamViolation {
+                path: self.path.clone(),
+                span,
+                message,
+            })
+    }
+
+    // Leaves the path of a completed element or member.
+    fn finish_value(&mut self) {
+        if let Some((_, index)) = self.open.last_mut() {
+            self.path.pop();
+            if let Some(index) = index {
+                *index += 1;
+            }
+        }
+    }
+}
+
+/// Writes compact JSON from a sequence of [`Event`]s.
+///
+/// Commas and colons are inserted as needed. The writer does not check
+/// that the events form a valid document beyond rejecting unbalanced
+/// closers.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::stream::{EventWriter, JsonStreamParser};
+///
+/// let input = br#"{ "a" : [1, 2],
+///                   "b" : null }"#;
+/// let mut writer = EventWriter::new(Vec::new());
+/// for event in JsonStreamParser::new(&input[..]) {
+///     writer.write_event(&event?)?;
+/// }
+/// assert_eq!(writer.finish()?, br#"{"a":[1,2],"b":null}"#);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct EventWriter<W: Write> {
+    out: W,
+    // One entry per open container: whether anything was written in it.
+    stack: Vec<bool>,
+    // A key was written and its value comes next.
+    after_key: bool,
+}
+
+impl<W: Write> EventWriter<W> {
+    /// Creates a writer that writes to `out`.
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            stack: Vec::new(),
+            after_key: false,
+        }
+    }
+
+    /// Writes one event.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails, or an
+    /// [`io::ErrorKind::InvalidData`] error for an end event with no
+    /// matching start.
+    pub fn write_event(&mut self, event: &Event) -> io::Result<()> {
+        match event {
+            Event::EndObject | Event::EndArray => {
+                self.stack.pop().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "unbalanced end event")
+                })?;
+                self.after_key = false;
+                let close: &[u8] = if *event == Event::EndObject {
+                    b"}"
+                } else {
+                    b"]"
+                };
+                return self.out.write_all(close);
+            }
+            Event::Key(key) => {
+                self.separator()?;
+                self.after_key = true;
+                self.out.write_all(key.to_json_string().as_bytes())?;
+                return self.out.write_all(b":");
+            }
+            _ => {}
+        }
+        if !std::mem::take(&mut self.after_key) {
+            self.separator()?;
+        }
+        match event {
+            Event::StartObject => {
+                self.stack.push(false);
+                self.out.write_all(b"{")
+            }
+            Event::StartArray => {
+                self.stack.push(false);
+                self.out.write_all(b"[")
+            }
+            Event::Value(value) => write!(self.out, "{}", value),
+            _ => unreachable!("handled above"),
+        }
+    }
+
+    /// Flushes the writer and returns it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if flushing fails.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    // Writes a comma before every entry of a container but the first.
+    fn separator(&mut self) -> io::Result<()> {
+        match self
+            .stack
+            .last_mut()
+            .map(|written| std::mem::replace(written, true))
+        {
+            Some(true) => self.out.write_all(b","),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+    use crate::path::Segment;
+
+    fn events(input: &str) -> io::Result<Vec<Event>> {
+        JsonStreamParser::new(input.as_bytes()).collect()
+    }
+
+    fn json_error(error: io::Error) -> JsonError {
+        *error.into_inner().unwrap().downcast::<JsonError>().unwrap()
+    }
+
+    #[test]
+    fn test_event_sequence() {
+        let events = events(r#"{"a": [1, {}], "b": "x", "c": []}"#).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                Event::StartObject,
+                Event::Key("a".to_string()),
+                Event::StartArray,
+                Event::Value(JsonValue::Number(1.0.into())),
+                Event::StartObject,
+                Event::EndObject,
+                Event::EndArray,
+                Event::Key("b".to_string()),
+                Event::Value(JsonValue::String("x".to_string())),
+                Event::Key("c".to_string()),
+                Event::StartArray,
+                Event::EndArray,
+                Event::EndObject,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scalar_root() {
+        assert_eq!(
+            events(" true ").unwrap(),
+            vec![Event::Value(JsonValue::Boolean(true))]
+        );
+    }
+
+    #[test]
+    fn test_errors_match_tree_parser() {
+        let cases = [
+            "",
+            "[1,]",
+            "[1 2]",
+            "{\"a\" 1}",
+            "{1: 2}",
+            "{\"a\": 1,}",
+            "[1}",
+            "{\"a\": 1]",
+            "[1] 2",
+            "[",
+            "{\"a\":",
+            "\n  [true, :]",
+        ];
+        for input in cases {
+            let error = json_error(events(input).unwrap_err());
+            let expected = parse_json(input).unwrap_err();
+            assert_eq!(error.position(), expected.position(), "{:?}", input);
+            assert_eq!(error.line(), expected.line(), "{:?}", input);
+            assert_eq!(error.category(), expected.category(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_writer_round_trip() {
+        let input = r#"{"a": [1, {}, [], {"b\"": "\u00e9"}], "c": {"d": [true, null]}, "e": -0.5}"#;
+        let mut writer = EventWriter::new(Vec::new());
+        for event in events(input).unwrap() {
+            writer.write_event(&event).unwrap();
+        }
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(output, parse_json(input).unwrap().to_string());
+
+        let mut writer = EventWriter::new(Vec::new());
+        let error = writer.write_event(&Event::EndArray).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_stops_after_error() {
+        let mut parser = JsonStreamParser::new(&b"[1, @]"[..]);
+        assert_eq!(parser.next().unwrap().unwrap(), Event::StartArray);
+        assert_eq!(
+            parser.next().unwrap().unwrap(),
+            Event::Value(JsonValue::Number(1.0.into()))
+        );
+        assert!(parser.next().unwrap().is_err());
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn test_validator_sees_paths_and_spans() {
+        use std::sync::{Arc, Mutex};
+
+        let input = r#"{"a": [10, {"b": null}], "c": []}"#;
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        let parser = JsonStreamParser::new(input.as_bytes()).validator(
+            move |path: &JsonPath, event: &Event| {
+                log.lock()
+                    .unwrap()
+                    .push(format!("{} {:?}", path.to_pointer(), event));
+                Ok(())
+            },
+        );
+        assert_eq!(parser.count(), 13);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                " StartObject",
+                "/a StartArray",
+                "/a/0 Value(Number(10))",
+                "/a/1 StartObject",
+                "/a/1/b Value(Null)",
+                "/a/1 EndObject",
+                "/a EndArray",
+                "/c StartArray",
+                "/c EndArray",
+                " EndObject",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validator_fails_fast() {
+        // Rejects arrays with more than two elements, at the third.
+        let input = "[[1, 2], [3, 4, 5, 6], @";
+        let parser =
+            JsonStreamParser::new(input.as_bytes()).validator(|path: &JsonPath, _: &Event| {
+                match path.segments().get(1) {
+                    Some(Segment::Index(i)) if *i >= 2 => Err("too many elements".to_string()),
+                    _ => Ok(()),
+                }
+            });
+        let error = parser.collect::<io::Result<Vec<_>>>().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let violation = *error
+            .into_inner()
+            .unwrap()
+            .downcast::<StreamViolation>()
+            .unwrap();
+        assert_eq!(violation.path.to_pointer(), "/1/2");
+        assert_eq!(violation.span, 16..17);
+
+        // End events span the whole container.
+        let parser = JsonStreamParser::new(&b" {\"x\": [1]} "[..]).validator(
+            |_: &JsonPath, event: &Event| match event {
+                Event::EndObject => Err("no objects".to_string()),
+                _ => Ok(()),
+            },
+        );
+        let error = parser.last().unwrap().unwrap_err();
+        let violation = error.get_ref().unwrap().downcast_ref::<StreamViolation>();
+        assert_eq!(violation.map(|v| v.span.clone()), Some(1..11));
+    }
+}
@@ -0,0 +1,706 @@
+//! Two-stage tape parsing with on-demand materialization.
+//!
+//! The default pipeline builds a full [`JsonValue`](crate::value::JsonValue)
+//! tree even when the caller only reads a handful of fields. This module
+//! offers an alternative backend in the style of simdjson:
+//!
+//! 1. [`Tape::build`](crate::tape::Tape::build) makes a single pass over
+//!    the input, checks its structure (brackets, commas, colons, string
+//!    boundaries), and records one entry per value on a flat *tape*. Each
+//!    container entry knows where its subtree ends, so skipping it is O(1).
+//! 2. [`LazyValue`](crate::tape::LazyValue) navigates the tape on demand.
+//!    Strings, numbers, and literals are decoded only when accessed, and
+//!    [`LazyValue::to_value`](crate::tape::LazyValue::to_value) converts
+//!    just the touched subtree into an owned `JsonValue`.
+//!
+//! Because scalars are decoded lazily, a malformed number or escape
+//! sequence inside a subtree that is never read does not cause an error.
+//!
+//! Every entry also records the byte span of its value, so the exact
+//! source text of any node is available through
+//! [`Tape::source_slice`](crate::tape::Tape::source_slice): to quote the
+//! offending snippet verbatim in an error message, or to forward an
+//! untouched subtree without re-serializing it.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::error::JsonError;
+use crate::parser::{ParserOptions, parse_json};
+use crate::path::{JsonPath, Segment};
+use crate::tokenizer::{Token, Tokenizer};
+use crate::value::JsonValue;
+
+/// The JSON type of a tape entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// `null`.
+    Null,
+    /// `true` or `false`.
+    Boolean,
+    /// A number.
+    Number,
+    /// A string.
+    String,
+    /// An array.
+    Array,
+    /// An object.
+    Object,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    kind: Kind,
+    start: usize,
+    end: usize,
+    // Index of the first entry after this value's subtree.
+    next: usize,
+    // Number of elements (arrays) or members (objects).
+    len: usize,
+}
+
+/// A structural index over a JSON document.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::tape::Tape;
+///
+/// let input = r#"{"users": [{"name": "Alice"}, {"name": "Bob"}], "big": [1, 2, 3]}"#;
+/// let tape = Tape::build(input)?;
+/// let name = tape.root().get("users").and_then(|u| u.get_index(1)).and_then(|u| u.get("name"));
+/// assert_eq!(name.and_then(|n| n.as_str()).as_deref(), Some("Bob"));
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tape<'a> {
+    input: &'a str,
+    entries: Vec<Entry>,
+}
+
+impl<'a> Tape<'a> {
+    /// Scans `input` and builds its tape.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError`] if the document is structurally invalid:
+    /// unbalanced brackets, missing commas or colons, non-string keys,
+    /// unterminated strings, trailing content, or empty input, and
+    /// [`JsonError::DepthLimitExceeded`] if arrays and objects nest deeper
+    /// than the current [`ParserOptions`] allow.
+    pub fn build(input: &'a str) -> Result<Tape<'a>, JsonError> {
+        let mut builder = Builder {
+            bytes: input.as_bytes(),
+            position: 0,
+            // Rough estimate: about one value per six bytes of typical JSON.
+            entries: Vec::with_capacity(input.len() / 6),
+            depth: 0,
+            max_depth: ParserOptions::current().max_depth,
+        };
+        builder.skip_whitespace();
+        builder.value().map_err(|e| e.locate(input))?;
+        builder.skip_whitespace();
+        if builder.position < input.len() {
+            return Err(JsonError::UnexpectedToken {
+                expected: "end of input".to_string(),
+                found: (input.as_bytes()[builder.position] as char).to_string(),
+                position: builder.position,
+                line: 0,
+                column: 0,
+            }
+            .locate(input));
+        }
+        Ok(Tape {
+            input,
+            entries: builder.entries,
+        })
+    }
+
+    /// Returns the root value.
+    pub fn root(&self) -> LazyValue<'_, 'a> {
+        LazyValue {
+            tape: self,
+            index: 0,
+        }
+    }
+
+    /// Returns the exact source text of the value at `path`, with its
+    /// original whitespace, escapes, and number formatting, or `None` if
+    /// there is no value there.
+    ///
+    /// Paths are the ones errors and reports carry, so a problem found in
+    /// a parsed value can be quoted from the input as written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::path::JsonPath;
+    /// use rust_json_parser::tape::Tape;
+    ///
+    /// let input = r#"{"limits": {"cpu": 1.50, "name": "caf\u00e9" }}"#;
+    /// let tape = Tape::build(input)?;
+    /// let limits = JsonPath::parse_pointer("/limits").unwrap();
+    /// assert_eq!(tape.source_slice(&limits), Some(r#"{"cpu": 1.50, "name": "caf\u00e9" }"#));
+    /// assert_eq!(tape.source_slice(&limits.join("cpu")), Some("1.50"));
+    /// assert_eq!(tape.source_slice(&limits.join("name")), Some(r#""caf\u00e9""#));
+    /// assert_eq!(tape.source_slice(&limits.join("memory")), None);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn source_slice(&self, path: &JsonPath) -> Option<&'a str> {
+        self.root().at(path).map(|node| node.raw())
+    }
+
+    /// Returns the number of values recorded on the tape.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the tape has no entries. A successfully built
+    /// tape always has at least the root.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A cursor onto one value of a [`Tape`].
+///
+/// Navigation never allocates. Decoding a string allocates only when it
+/// contains escape sequences.
+#[derive(Debug, Clone, Copy)]
+pub struct LazyValue<'t, 'a> {
+    tape: &'t Tape<'a>,
+    index: usize,
+}
+
+impl<'t, 'a> LazyValue<'t, 'a> {
+    fn entry(&self) -> &Entry {
+        &self.tape.entries[self.index]
+    }
+
+    /// Returns the JSON type of this value.
+    pub fn kind(&self) -> Kind {
+        self.entry().kind
+    }
+
+    /// Returns the exact source text of this value.
+    pub fn raw(&self) -> &'a str {
+        let entry = self.entry();
+        &self.tape.input[entry.start..entry.end]
+    }
+
+    /// Returns the byte offset where this value starts in the input.
+    pub fn offset(&self) -> usize {
+        self.entry().start
+    }
+
+    /// Returns the byte range of this value in the input.
+    pub fn span(&self) -> Range<usize> {
+        let entry = self.entry();
+        entry.start..entry.end
+    }
+
+    /// Returns the number of elements or members, or `0` for scalars.
+    pub fn len(&self) -> usize {
+        self.entry().len
+    }
+
+    /// Returns `true` if this is an empty container or a scalar.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if this value is `null`.
+    pub fn is_null(&self) -> bool {
+        self.kind() == Kind::Null
+    }
+
+    /// Decodes the value as a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.kind() {
+            Kind::Boolean => Some(self.raw() == "true"),
+            _ => None,
+        }
+    }
+
+    /// Decodes the value as a number. Returns `None` for non-numbers and
+    /// for malformed numeric literals.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.kind() {
+            Kind::Number => match self.scalar() {
+                Ok(JsonValue::Number(n)) => Some(n.as_f64()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Decodes the value as a string, borrowing from the input when no
+    /// escape sequences are present. Returns `None` for non-strings and
+    /// for strings with invalid escapes.
+    pub fn as_str(&self) -> Option<Cow<'a, str>> {
+        if self.kind() != Kind::String {
+            return None;
+        }
+        decode_string(self.raw())
+    }
+
+    /// Looks up an object member by key.
+    pub fn get(&self, key: &str) -> Option<LazyValue<'t, 'a>> {
+        self.members().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    /// Looks up an array element by index.
+    pub fn get_index(&self, index: usize) -> Option<LazyValue<'t, 'a>> {
+        self.elements().nth(index)
+    }
+
+    /// Looks up the value at `path` below this one. An index segment
+    /// applied to an object looks up the member with that name.
+    pub fn at(&self, path: &JsonPath) -> Option<LazyValue<'t, 'a>> {
+        path.segments()
+            .iter()
+            .try_fold(*self, |node, segment| match (node.kind(), segment) {
+                (Kind::Array, Segment::Index(i)) => node.get_index(*i),
+                (Kind::Object, segment) => node.get(&segment.to_string()),
+                _ => None,
+            })
+    }
+
+    /// Iterates over the elements of an array. Yields nothing for other
+    /// kinds.
+    pub fn elements(&self) -> impl Iterator<Item = LazyValue<'t, 'a>> + 't {
+        let tape = self.tape;
+        let (mut i, end) = match self.kind() {
+            Kind::Array => (self.index + 1, self.entry().next),
+            _ => (0, 0),
+        };
+        std::iter::from_fn(move || {
+            if i >= end {
+                return None;
+            }
+            let value = LazyValue { tape, index: i };
+            i = tape.entries[i].next;
+            Some(value)
+        })
+    }
+
+    /// Iterates over the members of an object as `(key, value)` pairs.
+    /// Yields nothing for other kinds.
+    pub fn members(&self) -> impl Iterator<Item = (Cow<'a, str>, LazyValue<'t, 'a>)> + 't {
+        let tape = self.tape;
+        let (mut i, end) = match self.kind() {
+            Kind::Object => (self.index + 1, self.entry().next),
+            _ => (0, 0),
+        };
+        std::iter::from_fn(move || {
+            if i >= end {
+                return None;
+            }
+            let key_entry = &tape.entries[i];
+            let key = decode_string(&tape.input[key_entry.start..key_entry.end])?;
+            let value = LazyValue { tape, index: i + 1 };
+            i = tape.entries[i + 1].next;
+            Some((key, value))
+        })
+    }
+
+    /// Materializes this value and its subtree into a [`JsonValue`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError`] if a scalar inside the subtree is malformed.
+    /// Positions in the error are byte offsets into the original input.
+    pub fn to_value(&self) -> Result<JsonValue, JsonError> {
+        parse_json(self.raw()).map_err(|e| {
+            let position = e.position() + self.offset();
+            e.reposition(position).locate(self.tape.input)
+        })
+    }
+
+    fn scalar(&self) -> Result<JsonValue, JsonError> {
+        self.to_value()
+    }
+}
+
+fn decode_string(raw: &str) -> Option<Cow<'_, str>> {
+    let inner = &raw[1..raw.len() - 1];
+    if !inner.contains('\\') {
+        return Some(Cow::Borrowed(inner));
+    }
+    match Tokenizer::new(raw).tokenize().ok()?.pop()? {
+        Token::String(s) => Some(Cow::Owned(s)),
+        _ => None,
+    }
+}
+
+struct Builder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    entries: Vec<Entry>,
+    // Arrays and objects currently open, and how many may be.
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Builder<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\n' | b'\t' | b'\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn push(&mut self, kind: Kind, start: usize) -> usize {
+        let index = self.entries.len();
+        self.entries.push(Entry {
+            kind,
+            start,
+            end: start,
+            next: index + 1,
+            len: 0,
+        });
+        index
+    }
+
+    fn finish(&mut self, index: usize, len: usize) {
+        let next = self.entries.len();
+        let entry = &mut self.entries[index];
+        entry.end = self.position;
+        entry.next = next;
+        entry.len = len;
+    }
+
+    fn unexpected(&self, expected: &str) -> JsonError {
+        match self.peek() {
+            Some(b) => JsonError::UnexpectedToken {
+                expected: expected.to_string(),
+                found: (b as char).to_string(),
+                position: self.position,
+                line: 0,
+                column: 0,
+            },
+            None => JsonError::UnexpectedEndOfInput {
+                expected: expected.to_string(),
+                position: self.position,
+                line: 0,
+                column: 0,
+            },
+        }
+    }
+
+    fn value(&mut self) -> Result<(), JsonError> {
+        let start = self.position;
+        match self.peek() {
+            Some(b'{') => self.nested(Self::object),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'"') => {
+                let index = self.push(Kind::String, start);
+                self.string()?;
+                self.finish(index, 0);
+                Ok(())
+            }
+            Some(b'0'..=b'9' | b'-') => {
+                let index = self.push(Kind::Number, start);
+                self.scan_while(|b| {
+                    b.is_ascii_digit() || matches!(b, b'.' | b'-' | b'+' | b'e' | b'E')
+                });
+                self.finish(index, 0);
+                Ok(())
+            }
+            Some(b't' | b'f' | b'n') => {
+                self.scan_while(|b| b.is_ascii_lowercase());
+                let kind = match &self.bytes[start..self.position] {
+                    b"true" | b"false" => Kind::Boolean,
+                    b"null" => Kind::Null,
+                    word => {
+                        return Err(JsonError::UnexpectedToken {
+                            expected: "valid JSON token".to_string(),
+                            found: String::from_utf8_lossy(word).into_owned(),
+                            position: start,
+                            line: 0,
+                            column: 0,
+                        });
+                    }
+                };
+                let index = self.push(kind, start);
+                self.finish(index, 0);
+                Ok(())
+            }
+            _ => Err(self.unexpected("JSON value")),
+        }
+    }
+
+    fn nested(&mut self, build: fn(&mut Self) -> Result<(), JsonError>) -> Result<(), JsonError> {
+        if self.depth >= self.max_depth {
+            return Err(JsonError::DepthLimitExceeded {
+                max_depth: self.max_depth,
+                position: self.position,
+                line: 0,
+                column: 0,
+            });
+        }
+        self.depth += 1;
+        let result = build(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn scan_while(&mut self, accept: impl Fn(u8) -> bool) {
+        while let Some(b) = self.peek() {
+            if !accept(b) {
+                break;
+            }
+            self.position += 1;
+        }
+    }
+
+    fn string(&mut self) -> Result<(), JsonError> {
+        let start = self.position;
+        self.position += 1; // opening quote
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                Some(b'\\') => self.position += 2,
+                Some(_) => self.position += 1,
+                None => {
+                    return Err(JsonError::UnexpectedEndOfInput {
+                        expected: "closing quote".to_string(),
+                        position: start,
+                        line: 0,
+                        column: 0,
+                    });
+                }
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<(), JsonError> {
+        let index = self.push(Kind::Array, self.position);
+        self.position += 1; // '['
+        self.skip_whitespace();
+        let mut len = 0;
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            self.finish(index, 0);
+            return Ok(());
+        }
+        loop {
+            self.value()?;
+            len += 1;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => {
+                    self.position += 1;
+                    self.skip_whitespace();
+                }
+                Some(b']') => {
+                    self.position += 1;
+                    break;
+                }
+                _ => return Err(self.unexpected("comma or closing bracket")),
+            }
+        }
+        self.finish(index, len);
+        Ok(())
+    }
+
+    fn object(&mut self) -> Result<(), JsonError> {
+        let index = self.push(Kind::Object, self.position);
+        self.position += 1; // '{'
+        self.skip_whitespace();
+        let mut len = 0;
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            self.finish(index, 0);
+            return Ok(());
+        }
+        loop {
+            if self.peek() != Some(b'"') {
+                return Err(self.unexpected("string key"));
+            }
+            self.value()?;
+            self.skip_whitespace();
+            if self.peek() != Some(b':') {
+                return Err(self.unexpected("colon"));
+            }
+            self.position += 1;
+            self.skip_whitespace();
+            self.value()?;
+            len += 1;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => {
+                    self.position += 1;
+                    self.skip_whitespace();
+                }
+                Some(b'}') => {
+                    self.position += 1;
+                    break;
+                }
+                _ => return Err(self.unexpected("comma or closing brace")),
+            }
+        }
+        self.finish(index, len);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_navigate_nested() {
+        let input = r#"{"a": {"b": [10, 20, {"c": "deep"}]}, "z": null}"#;
+        let tape = Tape::build(input).unwrap();
+        let root = tape.root();
+        assert_eq!(root.kind(), Kind::Object);
+        assert_eq!(root.len(), 2);
+
+        let b = root.get("a").and_then(|a| a.get("b")).unwrap();
+        assert_eq!(b.kind(), Kind::Array);
+        assert_eq!(b.len(), 3);
+        assert_eq!(b.get_index(1).and_then(|v| v.as_f64()), Some(20.0));
+        let c = b.get_index(2).and_then(|v| v.get("c")).unwrap();
+        assert_eq!(c.as_str().as_deref(), Some("deep"));
+        assert!(root.get("z").unwrap().is_null());
+        assert!(root.get("missing").is_none());
+        assert!(b.get_index(3).is_none());
+    }
+
+    #[test]
+    fn test_source_slice_and_spans() {
+        let input = "[ {\"1\" : [ -0.0 , \"\\n\" ] } ,\n  true ]";
+        let tape = Tape::build(input).unwrap();
+        let path = |pointer: &str| JsonPath::parse_pointer(pointer).unwrap();
+        assert_eq!(tape.source_slice(&JsonPath::root()), Some(input));
+        assert_eq!(tape.source_slice(&path("/0/1")), Some("[ -0.0 , \"\\n\" ]"));
+        assert_eq!(tape.source_slice(&path("/0/1/1")), Some("\"\\n\""));
+        assert_eq!(tape.source_slice(&path("/1")), Some("true"));
+        assert_eq!(tape.source_slice(&path("/2")), None);
+        assert_eq!(tape.source_slice(&path("/1/0")), None);
+        assert_eq!(tape.source_slice(&path("/0/x")), None);
+
+        let node = tape.root().at(&path("/0/1/0")).unwrap();
+        assert_eq!(&input[node.span()], "-0.0");
+        assert_eq!(node.span().start, node.offset());
+    }
+
+    #[test]
+    fn test_raw_and_to_value() {
+        let input = r#"[1, {"k": [true, false]}, "s"]"#;
+        let tape = Tape::build(input).unwrap();
+        let obj = tape.root().get_index(1).unwrap();
+        assert_eq!(obj.raw(), r#"{"k": [true, false]}"#);
+        assert_eq!(obj.to_value().unwrap(), parse_json(obj.raw()).unwrap());
+        assert_eq!(tape.root().to_value().unwrap(), parse_json(input).unwrap());
+    }
+
+    #[test]
+    fn test_string_borrowed_unless_escaped() {
+        let tape = Tape::build(r#"["plain", "esc\naped"]"#).unwrap();
+        let plain = tape.root().get_index(0).unwrap().as_str().unwrap();
+        assert!(matches!(plain, Cow::Borrowed("plain")));
+        let escaped = tape.root().get_index(1).unwrap().as_str().unwrap();
+        assert_eq!(escaped, "esc\naped");
+        assert!(matches!(escaped, Cow::Owned(_)));
+    }
+
+    #[test]
+    fn test_escaped_key_lookup() {
+        let tape = Tape::build(r#"{"a\"b": 1}"#).unwrap();
+        assert_eq!(tape.root().get("a\"b").and_then(|v| v.as_f64()), Some(1.0));
+    }
+
+    #[test]
+    fn test_members_and_elements() {
+        let tape = Tape::build(r#"{"x": [1, 2], "y": {}}"#).unwrap();
+        let keys: Vec<String> = tape.root().members().map(|(k, _)| k.into_owned()).collect();
+        assert_eq!(keys, vec!["x", "y"]);
+        let sum: f64 = tape
+            .root()
+            .get("x")
+            .unwrap()
+            .elements()
+            .filter_map(|v| v.as_f64())
+            .sum();
+        assert_eq!(sum, 3.0);
+        assert_eq!(tape.root().elements().count(), 0);
+    }
+
+    #[test]
+    fn test_scalars() {
+        let tape = Tape::build(r#"[true, false, null, -1.5]"#).unwrap();
+        let root = tape.root();
+        assert_eq!(root.get_index(0).and_then(|v| v.as_bool()), Some(true));
+        assert_eq!(root.get_index(1).and_then(|v| v.as_bool()), Some(false));
+        assert_eq!(root.get_index(3).and_then(|v| v.as_f64()), Some(-1.5));
+        assert_eq!(root.get_index(3).and_then(|v| v.as_bool()), None);
+        assert_eq!(tape.len(), 5);
+    }
+
+    #[test]
+    fn test_structural_errors() {
+        assert!(matches!(
+            Tape::build("[1, 2"),
+            Err(JsonError::UnexpectedEndOfInput { .. })
+        ));
+        assert!(matches!(
+            Tape::build(r#"{"a" 1}"#),
+            Err(JsonError::UnexpectedToken { .. })
+        ));
+        assert!(matches!(
+            Tape::build(r#"{1: 2}"#),
+            Err(JsonError::UnexpectedToken { .. })
+        ));
+        assert!(matches!(
+            Tape::build("[1,]"),
+            Err(JsonError::UnexpectedToken { .. })
+        ));
+        assert!(matches!(
+            Tape::build("true false"),
+            Err(JsonError::UnexpectedToken { position: 5, .. })
+        ));
+        assert!(matches!(
+            Tape::build(""),
+            Err(JsonError::UnexpectedEndOfInput { .. })
+        ));
+        assert!(matches!(
+            Tape::build("nul"),
+            Err(JsonError::UnexpectedToken { .. })
+        ));
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let limit = crate::parser::DEFAULT_MAX_DEPTH;
+        let nested = format!("{}{}", "[".repeat(limit), "]".repeat(limit));
+        assert!(Tape::build(&nested).is_ok());
+        assert!(matches!(
+            Tape::build(&"[".repeat(1_000_000)),
+            Err(JsonError::DepthLimitExceeded { position, .. }) if position == limit
+        ));
+        assert!(matches!(
+            Tape::build(&r#"{"a": "#.repeat(limit + 1)),
+            Err(JsonError::DepthLimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lazy_scalar_errors_only_when_touched() {
+        let input = r#"{"ok": 1, "bad": 1.2.3}"#;
+        let tape = Tape::build(input).unwrap();
+        assert_eq!(tape.root().get("ok").and_then(|v| v.as_f64()), Some(1.0));
+        assert_eq!(tape.root().get("bad").and_then(|v| v.as_f64()), None);
+        match tape.root().get("bad").unwrap().to_value() {
+            Err(JsonError::InvalidNumber { position, .. }) => assert_eq!(position, 17),
+            other => panic!("Expected InvalidNumber, got {:?}", other),
+        }
+    }
+}
@@ -7,7 +7,10 @@
 //! Handles all JSON lexical elements: structural characters, string escape
 //! sequences (8 basic + `\uXXXX`), numbers, booleans, and null.
 
+use std::ops::Range;
+
 use crate::error::JsonError;
+use crate::value::JsonNumber;
 
 /// Represents a single semantic token produced by the JSON tokenizer.
 ///
@@ -44,15 +47,441 @@ pub enum Token {
     String(String),
     /// A JSON number value parsed as a 64-bit floating point.
     ///
-    /// Integers, decimals, and negative numbers are all represented as `f64`.
-    /// For example, `42` becomes `Token::Number(42.0)`.
-    Number(f64),
+    /// Integers, decimals, negative numbers, and exponent forms (`6.02e23`)
+    /// are all represented as `f64`.
+    /// For example, `42` becomes `Token::Number(42.0.into())`. The raw
+    /// lexeme is kept only when raw number preservation is enabled.
+    Number(JsonNumber),
     /// A JSON boolean value (`true` or `false`).
     Boolean(bool),
     /// The JSON `null` literal.
     Null,
 }
 
+/// Finds the token that closes the array or object opened at `open`.
+///
+/// Returns the index of the matching `RightBracket` or `RightBrace`, or
+/// `None` if `tokens[open]` is not an opening token or the container is
+/// never closed. Mismatched closers (`[ }`) also return `None`.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::tokenizer::{Tokenizer, matching_close};
+///
+/// let tokens = Tokenizer::new(r#"[{"a": [1]}, 2]"#).tokenize()?;
+/// assert_eq!(matching_close(&tokens, 0), Some(tokens.len() - 1));
+/// assert_eq!(matching_close(&tokens, 1), Some(7));
+/// assert_eq!(matching_close(&tokens, 2), None);
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+pub fn matching_close(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut stack = Vec::new();
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        match token {
+            Token::LeftBracket | Token::LeftBrace => stack.push(token),
+            Token::RightBracket | Token::RightBrace => {
+                let opener = stack.pop()?;
+                let matched = matches!(
+                    (opener, token),
+                    (Token::LeftBracket, Token::RightBracket)
+                        | (Token::LeftBrace, Token::RightBrace)
+                );
+                if !matched {
+                    return None;
+                }
+                if stack.is_empty() {
+                    return Some(i);
+                }
+            }
+            _ if stack.is_empty() => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Finds the byte offset of the bracket or brace that closes the one at
+/// byte offset `open` in `input`.
+///
+/// Brackets inside string literals (including escaped quotes) are
+/// ignored. The input is not otherwise validated, so this works on
+/// partial or invalid documents such as text being edited. Returns `None`
+/// if `open` is not at `[` or `{`, or no matching closer exists.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::tokenizer::matching_bracket;
+///
+/// let input = r#"{"s": "}]\"", "a": [1, [2]]} tail"#;
+/// assert_eq!(matching_bracket(input, 0), Some(27));
+/// assert_eq!(matching_bracket(input, 19), Some(26));
+/// assert_eq!(matching_bracket(input, 1), None);
+/// ```
+pub fn matching_bracket(input: &str, open: usize) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let mut stack = Vec::new();
+    let mut in_string = false;
+    let mut i = open;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if in_string {
+            match b {
+                b'\\' => i += 1,
+                b'"' => in_string = false,
+                _ => {}
+            }
+        } else {
+            match b {
+                b'"' if !stack.is_empty() => in_string = true,
+                b'[' | b'{' => stack.push(b),
+                b']' | b'}' => {
+                    let expected = if b == b']' { b'[' } else { b'{' };
+                    if stack.pop()? != expected {
+                        return None;
+                    }
+                    if stack.is_empty() {
+                        return Some(i);
+                    }
+                }
+                _ if stack.is_empty() => return None,
+                _ => {}
+            }
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Returns `true` if `s` is exactly one number as defined by RFC 8259.
+///
+/// This is the scanner the tokenizer uses to accept number tokens, so a
+/// string passes here if and only if it tokenizes to a single number.
+/// Forms that `f64::from_str` accepts but JSON does not, such as `inf`,
+/// `NaN`, `+1`, `01`, `1.`, and `.5`, are rejected.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::tokenizer::is_valid_json_number;
+///
+/// assert!(is_valid_json_number("-0.5e+10"));
+/// assert!(is_valid_json_number("0"));
+/// assert!(!is_valid_json_number("01"));
+/// assert!(!is_valid_json_number("1."));
+/// assert!(!is_valid_json_number("inf"));
+/// ```
+pub fn is_valid_json_number(s: &str) -> bool {
+    s.bytes()
+        .try_fold(NumberState::Start, NumberState::next)
+        .is_some_and(NumberState::is_accepting)
+}
+
+/// States of the RFC 8259 number grammar:
+/// `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`
+#[derive(Debug, Clone, Copy)]
+enum NumberState {
+    Start,
+    Minus,
+    Zero,
+    Integer,
+    Point,
+    Fraction,
+    Exponent,
+    ExponentSign,
+    ExponentDigits,
+}
+
+impl NumberState {
+    fn next(self, b: u8) -> Option<Self> {
+        use NumberState::*;
+        let next = match (self, b) {
+            (Start, b'-') => Minus,
+            (Start | Minus, b'0') => Zero,
+            (Start | Minus, b'1'..=b'9') | (Integer, b'0'..=b'9') => Integer,
+            (Zero | Integer, b'.') => Point,
+            (Point | Fraction, b'0'..=b'9') => Fraction,
+            (Zero | Integer | Fraction, b'e' | b'E') => Exponent,
+            (Exponent, b'+' | b'-') => ExponentSign,
+            (Exponent | ExponentSign | ExponentDigits, b'0'..=b'9') => ExponentDigits,
+            _ => return None,
+        };
+        Some(next)
+    }
+
+    fn is_accepting(self) -> bool {
+        matches!(
+            self,
+            NumberState::Zero
+                | NumberState::Integer
+                | NumberState::Fraction
+                | NumberState::ExponentDigits
+        )
+    }
+}
+
+/// Decodes the character after a backslash, other than `u`.
+pub(crate) fn escape_char(b: u8, position: usize) -> Result<char, JsonError> {
+    match b {
+        b'"' => Ok('"'),
+        b'\\' => Ok('\\'),
+        b'/' => Ok('/'),
+        b'b' => Ok('\u{0008}'),
+        b'f' => Ok('\u{000C}'),
+        b'n' => Ok('\n'),
+        b'r' => Ok('\r'),
+        b't' => Ok('\t'),
+        _ => Err(JsonError::InvalidEscape {
+            char: b as char,
+            position,
+            line: 0,
+            column: 0,
+        }),
+    }
+}
+
+/// Decodes the `\uXXXX` escape whose hex digits start at byte `start` of
+/// `input`. A high surrogate must be followed by a `\uXXXX` low surrogate,
+/// and the two decode to one character. Returns the character and the
+/// offset just past the escape.
+pub(crate) fn decode_unicode_escape(input: &str, start: usize) -> Result<(char, usize), JsonError> {
+    let unit = hex_code_unit(input, start)?;
+    let ch = match unit {
+        0xD800..=0xDBFF => {
+            let low_start = start + 6;
+            if input.as_bytes().get(start + 4..low_start) != Some(b"\\u") {
+                return Err(unpaired_surrogate(unit, start));
+            }
+            let low = hex_code_unit(input, low_start)?;
+            if !(0xDC00..=0xDFFF).contains(&low) {
+                return Err(unpaired_surrogate(unit, start));
+            }
+            let scalar = 0x10000 + ((u32::from(unit) - 0xD800) << 10) + (u32::from(low) - 0xDC00);
+            return Ok((
+                char::from_u32(scalar).expect("surrogate pairs are scalar values"),
+                low_start + 4,
+            ));
+        }
+        0xDC00..=0xDFFF => return Err(unpaired_surrogate(unit, start)),
+        _ => char::from_u32(u32::from(unit)).expect("non-surrogates are scalar values"),
+    };
+    Ok((ch, start + 4))
+}
+
+// Reads the four hex digits at `start` as a UTF-16 code unit.
+fn hex_code_unit(input: &str, start: usize) -> Result<u16, JsonError> {
+    match input.as_bytes().get(start..start + 4) {
+        Some(digits) if digits.iter().all(u8::is_ascii_hexdigit) => {
+            Ok(u16::from_str_radix(&input[start..start + 4], 16).expect("four hex digits"))
+        }
+        _ => Err(JsonError::InvalidUnicode {
+            sequence: input
+                .get(start..)
+                .unwrap_or_default()
+                .chars()
+                .take(4)
+                .collect(),
+            position: start,
+            line: 0,
+            column: 0,
+        }),
+    }
+}
+
+fn unpaired_surrogate(code_unit: u16, position: usize) -> JsonError {
+    JsonError::UnpairedSurrogate {
+        code_unit,
+        position,
+        line: 0,
+        column: 0,
+    }
+}
+
+/// Converts a run of lowercase letters into a keyword token.
+pub(crate) fn keyword_token(word: &str, start: usize) -> Result<Token, JsonError> {
+    match word {
+        "true" => Ok(Token::Boolean(true)),
+        "false" => Ok(Token::Boolean(false)),
+        "null" => Ok(Token::Null),
+        _ => Err(JsonError::UnexpectedToken {
+            expected: "valid JSON token".to_string(),
+            found: word.to_string(),
+            position: start,
+            line: 0,
+            column: 0,
+        }),
+    }
+}
+
+/// Returns `true` if `b` may continue a number lexeme after `prev`.
+pub(crate) fn is_number_byte(b: u8, prev: u8) -> bool {
+    match b {
+        b'0'..=b'9' | b'.' | b'-' | b'e' | b'E' => true,
+        // A plus sign is only valid as an exponent sign
+        b'+' => matches!(prev, b'e' | b'E'),
+        _ => false,
+    }
+}
+
+/// Returns `true` if the `_` or `,` at `i`, inside a number starting at
+/// `start`, separates digits under `mode`.
+fn is_digit_separator(bytes: &[u8], start: usize, i: usize, mode: DigitSeparators) -> bool {
+    let digit = |j: usize| bytes.get(j).is_some_and(u8::is_ascii_digit);
+    if mode == DigitSeparators::None || i == start || !digit(i - 1) {
+        return false;
+    }
+    match bytes[i] {
+        b'_' => digit(i + 1),
+        b',' if mode == DigitSeparators::Commas => {
+            let before = &bytes[start..i];
+            if before.iter().any(|b| matches!(b, b'.' | b'e' | b'E')) {
+                return false;
+            }
+            let group_start = before.iter().rposition(|&b| b == b',').map_or(0, |p| p + 1);
+            let group: Vec<u8> = before[group_start..]
+                .iter()
+                .copied()
+                .filter(u8::is_ascii_digit)
+                .collect();
+            let group_ok = if group_start == 0 {
+                (1..=3).contains(&group.len()) && group[0] != b'0'
+            } else {
+                group.len() == 3
+            };
+            group_ok
+                && (1..=3).all(|k| digit(i + k))
+                && !digit(i + 4)
+                && bytes.get(i + 4) != Some(&b'_')
+        }
+        _ => false,
+    }
+}
+
+/// Whether `c` is whitespace that JSON does not allow between tokens. See
+/// [`UnicodeWhitespace`].
+pub(crate) fn is_unicode_whitespace(c: char) -> bool {
+    (c.is_whitespace() && !matches!(c, ' ' | '\n' | '\t' | '\r'))
+        || matches!(c, '\u{200b}' | '\u{feff}')
+}
+
+/// Names a character [`is_unicode_whitespace`] accepts, with its code
+/// point, since most of them look like a space or nothing at all.
+pub(crate) fn describe_whitespace(c: char) -> String {
+    let name = match c {
+        '\u{b}' => "vertical tab",
+        '\u{c}' => "form feed",
+        '\u{85}' => "next line",
+        '\u{a0}' => "no-break space",
+        '\u{200b}' => "zero-width space",
+        '\u{2028}' => "line separator",
+        '\u{2029}' => "paragraph separator",
+        '\u{202f}' => "narrow no-break space",
+        '\u{3000}' => "ideographic space",
+        '\u{feff}' => "byte order mark",
+        _ => "Unicode whitespace",
+    };
+    format!("{} (U+{:04X})", name, c as u32)
+}
+
+/// The error for a string starting at `start` that exceeds `max_len`.
+pub(crate) fn string_too_long(max_len: usize, start: usize) -> JsonError {
+    JsonError::StringTooLong {
+        max_len,
+        position: start,
+        line: 0,
+        column: 0,
+    }
+}
+
+/// The error for a number starting at `start` that exceeds `max_len`.
+pub(crate) fn number_too_long(max_len: usize, start: usize) -> JsonError {
+    JsonError::NumberTooLong {
+        max_len,
+        position: start,
+        line: 0,
+        column: 0,
+    }
+}
+
+/// Validates and converts a scanned number lexeme starting at `start`.
+pub(crate) fn number_token(
+    num_str: &str,
+    start: usize,
+    preserve_raw: bool,
+) -> Result<JsonNumber, JsonError> {
+    if num_str.starts_with('.') || num_str.starts_with("-.") {
+        return Err(JsonError::UnexpectedToken {
+            expected: "valid JSON token".to_string(),
+            found: num_str.to_string(),
+            position: start,
+            line: 0,
+            column: 0,
+        });
+    }
+    let invalid = || JsonError::InvalidNumber {
+        value: num_str.to_string(),
+        position: start,
+        line: 0,
+        column: 0,
+    };
+    if !is_valid_json_number(num_str) {
+        return Err(invalid());
+    }
+    // Every lexeme the scanner accepts is also valid `f64` syntax, so
+    // conversion only fails on magnitudes beyond `f64::MAX`.
+    let n = num_str
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or_else(invalid)?;
+    let number = JsonNumber::from_lexeme(num_str, n);
+    if preserve_raw {
+        Ok(number.with_raw(num_str))
+    } else {
+        Ok(number)
+    }
+}
+
+/// Separators a [`Tokenizer`] accepts between the digits of a number,
+/// for feeds that write `1_000_000` or `1,000`. A number containing them
+/// is read as if they were not there.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DigitSeparators {
+    /// Only RFC 8259 numbers.
+    #[default]
+    None,
+    /// `_` between two digits anywhere in a number, as in `1_000.000_1`.
+    Underscores,
+    /// Underscores, and `,` between groups of three digits in the integer
+    /// part, as in `-12,345.5`. A comma only groups digits when the group
+    /// before it has one to three digits and does not start with `0`, or
+    /// is a later group of exactly three, and exactly three digits follow
+    /// it. Otherwise it separates values, so `[1,23]`, `[0,123]`, and
+    /// `[1234,567]` are unchanged, but `[1,234]` is the single number 1234.
+    Commas,
+}
+
+/// What a [`Tokenizer`] does with whitespace JSON does not allow between
+/// tokens, such as the no-break spaces text pasted from a word processor
+/// is full of.
+///
+/// JSON separates tokens only with spaces, tabs, and line breaks. This
+/// covers every other character Unicode classes as whitespace, form feed
+/// and vertical tab included, plus the zero-width space and the byte
+/// order mark. Strings keep these characters whatever the setting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnicodeWhitespace {
+    /// Fail with [`JsonError::UnexpectedToken`] naming the character.
+    #[default]
+    Error,
+    /// Skip it, and have a [`JsonParser`](crate::parser::JsonParser)
+    /// report each run of it as a [`Warning`](crate::parser::Warning).
+    Warn,
+    /// Skip it silently.
+    Allow,
+}
+
 /// Scans JSON text byte by byte and produces a `Vec<Token>`.
 ///
 /// Owns the input as a `String` and uses `.as_bytes()` for scanning.
@@ -69,6 +498,21 @@ pub enum Token {
 pub struct Tokenizer {
     input: String,
     position: usize,
+    // Reused for decoding strings that contain escape sequences.
+    scratch: String,
+    // Byte range of each token produced by the last tokenize call.
+    spans: Vec<Range<usize>>,
+    pub(crate) preserve_raw_numbers: bool,
+    pub(crate) allow_comments: bool,
+    pub(crate) allow_single_quotes: bool,
+    pub(crate) max_string_len: usize,
+    pub(crate) max_number_len: usize,
+    pub(crate) digit_separators: DigitSeparators,
+    pub(crate) unicode_whitespace: UnicodeWhitespace,
+    // Byte range of each number the last scan read with digit separators.
+    pub(crate) normalized: Vec<Range<usize>>,
+    // Byte range of each run of Unicode whitespace the last scan skipped.
+    pub(crate) skipped_whitespace: Vec<Range<usize>>,
 }
 
 impl Tokenizer {
@@ -80,9 +524,167 @@ impl Tokenizer {
         Self {
             input: input.to_string(),
             position: 0,
+            scratch: String::new(),
+            spans: Vec::new(),
+            preserve_raw_numbers: false,
+            allow_comments: false,
+            allow_single_quotes: false,
+            max_string_len: usize::MAX,
+            max_number_len: usize::MAX,
+            digit_separators: DigitSeparators::None,
+            unicode_whitespace: UnicodeWhitespace::Error,
+            normalized: Vec::new(),
+            skipped_whitespace: Vec::new(),
         }
     }
 
+    /// Keeps the exact source text of every number token, available via
+    /// [`JsonNumber::as_raw_str`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::tokenizer::{Token, Tokenizer};
+    ///
+    /// let tokens = Tokenizer::new("1.0").preserve_raw_numbers(true).tokenize()?;
+    /// match &tokens[0] {
+    ///     Token::Number(n) => assert_eq!(n.as_raw_str(), Some("1.0")),
+    ///     other => panic!("unexpected token {:?}", other),
+    /// }
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn preserve_raw_numbers(mut self, preserve: bool) -> Self {
+        self.preserve_raw_numbers = preserve;
+        self
+    }
+
+    /// Skips `//` line comments and `/* */` block comments as if they were
+    /// whitespace, as in JSONC files. Off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::tokenizer::{Token, Tokenizer};
+    ///
+    /// let input = "[1, // one\n /* two */ 2]";
+    /// assert!(Tokenizer::new(input).tokenize().is_err());
+    ///
+    /// let tokens = Tokenizer::new(input).allow_comments(true).tokenize()?;
+    /// assert_eq!(tokens.len(), 5);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.allow_comments = allow;
+        self
+    }
+
+    /// Accepts strings delimited by single quotes. Inside them `\'` is an
+    /// escaped quote and `"` needs no escape. Off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::tokenizer::{Token, Tokenizer};
+    ///
+    /// let tokens = Tokenizer::new(r#"'say "hi"'"#).allow_single_quotes(true).tokenize()?;
+    /// assert_eq!(tokens[0], Token::String(r#"say "hi""#.to_string()));
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn allow_single_quotes(mut self, allow: bool) -> Self {
+        self.allow_single_quotes = allow;
+        self
+    }
+
+    /// Rejects strings whose source text between the quotes is longer
+    /// than `max` bytes with [`JsonError::StringTooLong`]. The check runs
+    /// while the string is scanned, before anything is allocated for it.
+    /// Unlimited by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::error::JsonError;
+    /// use rust_json_parser::tokenizer::Tokenizer;
+    ///
+    /// assert!(Tokenizer::new(r#"["abc"]"#).max_string_len(3).tokenize().is_ok());
+    /// assert!(matches!(
+    ///     Tokenizer::new(r#"["abcd"]"#).max_string_len(3).tokenize(),
+    ///     Err(JsonError::StringTooLong { max_len: 3, position: 1, .. })
+    /// ));
+    /// ```
+    pub fn max_string_len(mut self, max: usize) -> Self {
+        self.max_string_len = max;
+        self
+    }
+
+    /// Rejects numbers whose source text is longer than `max` bytes with
+    /// [`JsonError::NumberTooLong`]. Unlimited by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::error::JsonError;
+    /// use rust_json_parser::tokenizer::Tokenizer;
+    ///
+    /// let long = "1".repeat(1000);
+    /// assert!(matches!(
+    ///     Tokenizer::new(&long).max_number_len(100).tokenize(),
+    ///     Err(JsonError::NumberTooLong { max_len: 100, position: 0, .. })
+    /// ));
+    /// ```
+    pub fn max_number_len(mut self, max: usize) -> Self {
+        self.max_number_len = max;
+        self
+    }
+
+    /// Accepts digit separators in numbers. Off by default; see
+    /// [`DigitSeparators`] for what each setting allows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::tokenizer::{DigitSeparators, Token, Tokenizer};
+    ///
+    /// let input = "[1_000, 2,500.5, 1,23]";
+    /// let tokens = Tokenizer::new(input).digit_separators(DigitSeparators::Commas).tokenize()?;
+    /// let numbers: Vec<f64> = tokens
+    ///     .iter()
+    ///     .filter_map(|t| match t {
+    ///         Token::Number(n) => Some(n.as_f64()),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// assert_eq!(numbers, [1000.0, 2500.5, 1.0, 23.0]);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn digit_separators(mut self, separators: DigitSeparators) -> Self {
+        self.digit_separators = separators;
+        self
+    }
+
+    /// Chooses what happens to Unicode whitespace between tokens. Rejected
+    /// by default; see [`UnicodeWhitespace`] for what is covered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::tokenizer::{Tokenizer, UnicodeWhitespace};
+    ///
+    /// let pasted = "{\"a\":\u{a0}1}";
+    /// let err = Tokenizer::new(pasted).tokenize().unwrap_err();
+    /// assert!(err.to_string().ends_with("found no-break space (U+00A0)"));
+    ///
+    /// let tokens = Tokenizer::new(pasted)
+    ///     .unicode_whitespace(UnicodeWhitespace::Allow)
+    ///     .tokenize()?;
+    /// assert_eq!(tokens.len(), 5);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn unicode_whitespace(mut self, policy: UnicodeWhitespace) -> Self {
+        self.unicode_whitespace = policy;
+        self
+    }
+
     /// Scans the input and produces a vector of [`Token`] values.
     ///
     /// This method consumes the entire input string, skipping whitespace and
@@ -145,9 +747,7 @@ impl Tokenizer {
     /// invalid characters, malformed strings, invalid escape sequences,
     /// invalid numbers, or unrecognized keywords.
     pub fn retokenize(&mut self, input: &str, tokens: &mut Vec<Token>) -> Result<(), JsonError> {
-        self.input.clear();
-        self.input.push_str(input);
-        self.position = 0;
+        self.reset(input);
         self.tokenize_into(tokens)
     }
 
@@ -179,55 +779,101 @@ impl Tokenizer {
     /// invalid characters, malformed strings, invalid escape sequences,
     /// invalid numbers, or unrecognized keywords.
     pub fn tokenize_into(&mut self, tokens: &mut Vec<Token>) -> Result<(), JsonError> {
+        self.spans.clear();
+        self.normalized.clear();
+        self.skipped_whitespace.clear();
+        self.scan(tokens).map_err(|e| e.locate(&self.input))
+    }
+
+    /// Returns the byte range in the input of each token produced by the
+    /// most recent tokenize call, in the same order as the tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::tokenizer::Tokenizer;
+    ///
+    /// let mut tokenizer = Tokenizer::new(r#"{"id": 42}"#);
+    /// let tokens = tokenizer.tokenize()?;
+    /// assert_eq!(tokens.len(), tokenizer.spans().len());
+    /// assert_eq!(tokenizer.spans()[1], 1..5);
+    /// assert_eq!(tokenizer.spans()[3], 7..9);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn spans(&self) -> &[Range<usize>] {
+        &self.spans
+    }
+
+    fn scan(&mut self, tokens: &mut Vec<Token>) -> Result<(), JsonError> {
+        while let Some((token, start)) = self.next_token()? {
+            tokens.push(token);
+            self.spans.push(start..self.position);
+        }
+        Ok(())
+    }
+
+    /// Scans the next token and returns it with its starting byte offset,
+    /// or `None` at the end of the input. Errors are not yet located.
+    pub(crate) fn next_token(&mut self) -> Result<Option<(Token, usize)>, JsonError> {
         while let Some(b) = self.peek() {
-            match b {
+            let start = self.position;
+            let token = match b {
                 // Structural tokens
                 b'{' => {
-                    tokens.push(Token::LeftBrace);
                     self.advance();
+                    Token::LeftBrace
                 }
                 b'}' => {
-                    tokens.push(Token::RightBrace);
                     self.advance();
+                    Token::RightBrace
                 }
                 b'[' => {
-                    tokens.push(Token::LeftBracket);
                     self.advance();
+                    Token::LeftBracket
                 }
                 b']' => {
-                    tokens.push(Token::RightBracket);
                     self.advance();
+                    Token::RightBracket
                 }
                 b':' => {
-                    tokens.push(Token::Colon);
                     self.advance();
+                    Token::Colon
                 }
                 b',' => {
-                    tokens.push(Token::Comma);
                     self.advance();
+                    Token::Comma
                 }
 
                 // Whitespace: skip
                 b' ' | b'\n' | b'\t' | b'\r' => {
                     self.advance();
+                    continue;
                 }
 
-                // String: parse
-                b'"' => {
-                    let s = self.parse_string()?;
-                    tokens.push(Token::String(s));
+                // Comments: skip, when enabled
+                b'/' if self.allow_comments => {
+                    self.skip_comment()?;
+                    continue;
                 }
 
+                // String: parse
+                b'"' => Token::String(self.parse_string(b'"')?),
+                b'\'' if self.allow_single_quotes => Token::String(self.parse_string(b'\'')?),
+
                 // Keywords: parse true, false, null
-                b't' | b'f' | b'n' => {
-                    let token = self.parse_keyword()?;
-                    tokens.push(token);
-                }
+                b't' | b'f' | b'n' => self.parse_keyword()?,
 
                 // Number: parse (starts with digit, minus sign, or decimal point)
-                b'0'..=b'9' | b'-' | b'.' => {
-                    let n = self.parse_number()?;
-                    tokens.push(Token::Number(n));
+                b'0'..=b'9' | b'-' | b'.' => Token::Number(self.parse_number()?),
+
+                // Unicode whitespace: skip or reject, by policy
+                _ if let Some(c) = self.input[self.position..]
+                    .chars()
+                    .next()
+                    .filter(|&c| is_unicode_whitespace(c)) =>
+                {
+                    self.skip_unicode_whitespace(c)?;
+                    continue;
                 }
 
                 // Unknown: return error
@@ -236,38 +882,149 @@ impl Tokenizer {
                         expected: "valid JSON token".to_string(),
                         found: (other as char).to_string(),
                         position: self.position,
+                        line: 0,
+                        column: 0,
                     });
                 }
-            }
+            };
+            return Ok(Some((token, start)));
         }
+        Ok(None)
+    }
+
+    /// Replaces the input without tokenizing it, for pulling tokens one
+    /// at a time with [`next_token`](Self::next_token).
+    pub(crate) fn reset(&mut self, input: &str) {
+        self.input.clear();
+        self.input.push_str(input);
+        self.position = 0;
+        self.spans.clear();
+        self.normalized.clear();
+        self.skipped_whitespace.clear();
+    }
 
+    fn skip_unicode_whitespace(&mut self, c: char) -> Result<(), JsonError> {
+        let start = self.position;
+        match self.unicode_whitespace {
+            UnicodeWhitespace::Error => {
+                return Err(JsonError::UnexpectedToken {
+                    expected: "valid JSON token".to_string(),
+                    found: describe_whitespace(c),
+                    position: start,
+                    line: 0,
+                    column: 0,
+                });
+            }
+            UnicodeWhitespace::Warn => match self.skipped_whitespace.last_mut() {
+                Some(run) if run.end == start => run.end += c.len_utf8(),
+                _ => self.skipped_whitespace.push(start..start + c.len_utf8()),
+            },
+            UnicodeWhitespace::Allow => {}
+        }
+        self.position += c.len_utf8();
         Ok(())
     }
 
-    fn parse_string(&mut self) -> Result<String, JsonError> {
+    /// Returns the byte offset of the next unscanned input byte.
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Moves scanning to byte offset `position`, which must be a token
+    /// boundary.
+    pub(crate) fn seek(&mut self, position: usize) {
+        self.position = position;
+    }
+
+    /// Returns the text most recently tokenized.
+    pub(crate) fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Overwrites the retained input buffer with zeros.
+    #[cfg(feature = "zeroize")]
+    pub(crate) fn zeroize_input(&mut self) {
+        use zeroize::Zeroize;
+        self.input.zeroize();
+        self.scratch.zeroize();
+        self.position = 0;
+    }
+
+    fn skip_comment(&mut self) -> Result<(), JsonError> {
+        let comment_start = self.position;
+        self.advance(); // consume '/'
+        match self.advance() {
+            Some(b'/') => {
+                while let Some(b) = self.advance() {
+                    if b == b'\n' {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+            Some(b'*') => {
+                let mut prev = 0u8;
+                while let Some(b) = self.advance() {
+                    if prev == b'*' && b == b'/' {
+                        return Ok(());
+                    }
+                    prev = b;
+                }
+                Err(JsonError::UnexpectedEndOfInput {
+                    expected: "end of comment".to_string(),
+                    position: comment_start,
+                    line: 0,
+                    column: 0,
+                })
+            }
+            Some(other) => Err(JsonError::UnexpectedToken {
+                expected: "comment".to_string(),
+                found: format!("/{}", other as char),
+                position: comment_start,
+                line: 0,
+                column: 0,
+            }),
+            None => Err(JsonError::UnexpectedEndOfInput {
+                expected: "comment".to_string(),
+                position: comment_start,
+                line: 0,
+                column: 0,
+            }),
+        }
+    }
+
+    // `quote` is the delimiter: `"`, or `'` when single quotes are allowed.
+    fn parse_string(&mut self, quote: u8) -> Result<String, JsonError> {
         let string_start = self.position;
         self.advance(); // consume opening quote
-        // TODO: estimate, most strings are short keys/values so 32 should be enough
-        let mut s = String::with_capacity(32);
+        // Escaped strings are decoded into the reused scratch buffer and
+        // copied out at their exact size; plain strings skip it entirely.
+        self.scratch.clear();
+        let mut escaped = false;
         let mut seg_start = self.position;
         loop {
+            if self.position - string_start - 1 > self.max_string_len {
+                return Err(string_too_long(self.max_string_len, string_start));
+            }
             match self.peek() {
-                Some(b'"') => {
-                    // Flush the current unescaped segment
-                    if seg_start < self.position {
-                        s.push_str(&self.input[seg_start..self.position]);
-                    }
+                Some(b) if b == quote => {
+                    let segment = &self.input[seg_start..self.position];
+                    let s = if escaped {
+                        self.scratch.push_str(segment);
+                        self.scratch.as_str().to_owned()
+                    } else {
+                        segment.to_owned()
+                    };
                     self.advance();
                     return Ok(s);
                 }
                 Some(b'\\') => {
                     // Flush the segment before the backslash
-                    if seg_start < self.position {
-                        s.push_str(&self.input[seg_start..self.position]);
-                    }
+                    self.scratch.push_str(&self.input[seg_start..self.position]);
+                    escaped = true;
                     self.advance(); // consume backslash
-                    let ch = self.parse_escape_sequence()?;
-                    s.push(ch);
+                    let ch = self.parse_escape_sequence(quote)?;
+                    self.scratch.push(ch);
                     seg_start = self.position;
                 }
                 Some(_) => {
@@ -277,86 +1034,42 @@ impl Tokenizer {
                     return Err(JsonError::UnexpectedEndOfInput {
                         expected: "closing quote".to_string(),
                         position: string_start,
+                        line: 0,
+                        column: 0,
                     });
                 }
             }
         }
     }
 
-    fn parse_escape_sequence(&mut self) -> Result<char, JsonError> {
+    fn parse_escape_sequence(&mut self, quote: u8) -> Result<char, JsonError> {
         match self.peek() {
-            Some(b'"') => {
-                self.advance();
-                Ok('"')
-            }
-            Some(b'\\') => {
-                self.advance();
-                Ok('\\')
-            }
-            Some(b'/') => {
-                self.advance();
-                Ok('/')
-            }
-            Some(b'b') => {
-                self.advance();
-                Ok('\u{0008}')
-            }
-            Some(b'f') => {
-                self.advance();
-                Ok('\u{000C}')
-            }
-            Some(b'n') => {
-                self.advance();
-                Ok('\n')
-            }
-            Some(b'r') => {
+            Some(b'u') => {
                 self.advance();
-                Ok('\r')
+                self.parse_unicode_escape()
             }
-            Some(b't') => {
+            Some(b'\'') if quote == b'\'' => {
                 self.advance();
-                Ok('\t')
+                Ok('\'')
             }
-            Some(b'u') => {
+            Some(b) => {
+                let ch = escape_char(b, self.position)?;
                 self.advance();
-                self.parse_unicode_escape()
+                Ok(ch)
             }
-            Some(b) => Err(JsonError::InvalidEscape {
-                char: b as char,
-                position: self.position,
-            }),
             None => Err(JsonError::UnexpectedEndOfInput {
                 expected: "escape character".to_string(),
                 position: self.position,
+                line: 0,
+                column: 0,
             }),
         }
     }
 
     fn parse_unicode_escape(&mut self) -> Result<char, JsonError> {
-        let hex_start = self.position;
-        // \uXXXX = 4 hex digits, check we have enough bytes
-        if self.position + 4 > self.input.len() {
-            let available = &self.input[self.position..];
-            return Err(JsonError::InvalidUnicode {
-                sequence: available.to_string(),
-                position: hex_start,
-            });
-        }
-        let hex_str = &self.input[self.position..self.position + 4];
-        self.position += 4;
-        match u32::from_str_radix(hex_str, 16) {
-            Ok(code_point) => match char::from_u32(code_point) {
-                Some(unicode_char) => Ok(unicode_char),
-                None => Err(JsonError::InvalidUnicode {
-                    sequence: hex_str.to_string(),
-                    position: hex_start,
-                }),
-            },
-            Err(_) => Err(JsonError::InvalidUnicode {
-                sequence: hex_str.to_string(),
-                position: hex_start,
-            }),
-        }
+        let (ch, end) = decode_unicode_escape(&self.input, self.position)?;
+        self.position = end;
+        Ok(ch)
     }
 
     fn parse_keyword(&mut self) -> Result<Token, JsonError> {
@@ -369,44 +1082,39 @@ impl Tokenizer {
                 _ => break,
             }
         }
-        let word = &self.input[start..self.position];
-        match word {
-            "true" => Ok(Token::Boolean(true)),
-            "false" => Ok(Token::Boolean(false)),
-            "null" => Ok(Token::Null),
-            _ => Err(JsonError::UnexpectedToken {
-                expected: "valid JSON token".to_string(),
-                found: word.to_string(),
-                position: start,
-            }),
-        }
+        keyword_token(&self.input[start..self.position], start)
     }
 
-    fn parse_number(&mut self) -> Result<f64, JsonError> {
+    fn parse_number(&mut self) -> Result<JsonNumber, JsonError> {
         let start = self.position;
+        let mut prev = 0u8;
+        let mut separated = false;
         while let Some(b) = self.peek() {
-            match b {
-                b'0'..=b'9' | b'.' | b'-' => {
-                    self.advance();
-                }
-                _ => break,
+            let separator = matches!(b, b'_' | b',')
+                && is_digit_separator(
+                    self.input.as_bytes(),
+                    start,
+                    self.position,
+                    self.digit_separators,
+                );
+            if !separator && !is_number_byte(b, prev) {
+                break;
             }
+            if self.position - start == self.max_number_len {
+                return Err(number_too_long(self.max_number_len, start));
+            }
+            self.advance();
+            separated |= separator;
+            prev = b;
         }
-        let num_str = &self.input[start..self.position];
-        if num_str.starts_with('.') || num_str.starts_with("-.") {
-            return Err(JsonError::UnexpectedToken {
-                expected: "valid JSON token".to_string(),
-                found: num_str.to_string(),
-                position: start,
-            });
-        }
-        match num_str.parse::<f64>() {
-            Ok(n) => Ok(n),
-            Err(_) => Err(JsonError::InvalidNumber {
-                value: num_str.to_string(),
-                position: start,
-            }),
+        let lexeme = &self.input[start..self.position];
+        if !separated {
+            return number_token(lexeme, start, self.preserve_raw_numbers);
         }
+        let digits: String = lexeme.chars().filter(|c| !matches!(c, '_' | ',')).collect();
+        let number = number_token(&digits, start, self.preserve_raw_numbers)?;
+        self.normalized.push(start..self.position);
+        Ok(number)
     }
 
     fn advance(&mut self) -> Option<u8> {
@@ -451,7 +1159,7 @@ mod tests {
     fn test_number() -> Result<()> {
         let tokens = Tokenizer::new("42").tokenize()?;
         assert_eq!(tokens.len(), 1);
-        assert_eq!(tokens[0], Token::Number(42.0));
+        assert_eq!(tokens[0], Token::Number(42.0.into()));
         Ok(())
     }
 
@@ -459,15 +1167,16 @@ mod tests {
     fn test_number_negative() -> Result<()> {
         let tokens = Tokenizer::new("-42").tokenize()?;
         assert_eq!(tokens.len(), 1);
-        assert_eq!(tokens[0], Token::Number(-42.0));
+        assert_eq!(tokens[0], Token::Number((-42.0).into()));
         Ok(())
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_number_simple_decimal() -> Result<()> {
         let tokens = Tokenizer::new("3.14").tokenize()?;
         assert_eq!(tokens.len(), 1);
-        assert_eq!(tokens[0], Token::Number(3.14));
+        assert_eq!(tokens[0], Token::Number(3.14.into()));
         Ok(())
     }
 
@@ -475,10 +1184,103 @@ mod tests {
     fn test_number_negative_decimal() -> Result<()> {
         let tokens = Tokenizer::new("-0.99").tokenize()?;
         assert_eq!(tokens.len(), 1);
-        assert_eq!(tokens[0], Token::Number(-0.99));
+        assert_eq!(tokens[0], Token::Number((-0.99).into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_matching_close_tokens() -> Result<()> {
+        let tokens = Tokenizer::new(r#"{"a": [[], {}], "b": 1}"#).tokenize()?;
+        assert_eq!(matching_close(&tokens, 0), Some(tokens.len() - 1));
+        assert_eq!(matching_close(&tokens, 3), Some(9));
+        assert_eq!(matching_close(&tokens, 4), Some(5));
+        assert_eq!(matching_close(&tokens, 7), Some(8));
+        assert_eq!(matching_close(&tokens, 1), None);
+        assert_eq!(matching_close(&tokens, 100), None);
+
+        let mismatched = vec![Token::LeftBracket, Token::RightBrace];
+        assert_eq!(matching_close(&mismatched, 0), None);
+        let unclosed = vec![Token::LeftBrace, Token::LeftBracket, Token::RightBracket];
+        assert_eq!(matching_close(&unclosed, 0), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_matching_bracket_bytes() {
+        let input = r#"[1, "]", {"k": "\"}"}, [[]]]"#;
+        assert_eq!(matching_bracket(input, 0), Some(input.len() - 1));
+        assert_eq!(matching_bracket(input, 9), Some(20));
+        assert_eq!(matching_bracket(input, 23), Some(26));
+        assert_eq!(matching_bracket("[1, 2", 0), None);
+        assert_eq!(matching_bracket("[}", 0), None);
+        assert_eq!(matching_bracket("abc", 0), None);
+        assert_eq!(matching_bracket("[]", 5), None);
+    }
+
+    #[test]
+    fn test_number_exponent() -> Result<()> {
+        let cases = [
+            ("1e10", 1e10),
+            ("2.5E-3", 2.5e-3),
+            ("6.02e+23", 6.02e23),
+            ("-1E2", -100.0),
+            ("0e0", 0.0),
+        ];
+        for (input, expected) in cases {
+            let tokens = Tokenizer::new(input).tokenize()?;
+            assert_eq!(tokens, vec![Token::Number(expected.into())], "{}", input);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_number_exponent_in_array() -> Result<()> {
+        let tokens = Tokenizer::new("[1e3,2E+1]").tokenize()?;
+        assert_eq!(tokens[1], Token::Number(1000.0.into()));
+        assert_eq!(tokens[3], Token::Number(20.0.into()));
         Ok(())
     }
 
+    #[test]
+    fn test_number_malformed_exponent() {
+        for input in ["1e", "1e+", "1E-", "1e5e", "1e1.5"] {
+            match Tokenizer::new(input).tokenize() {
+                Err(JsonError::InvalidNumber {
+                    value, position, ..
+                }) => {
+                    assert_eq!(value, input);
+                    assert_eq!(position, 0);
+                }
+                other => panic!("Expected InvalidNumber for {}, got {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_number_rejects_non_json_float_forms() {
+        for input in ["01", "-01", "1.", "1.e5", "-", "1-2", "1e400", "-1e400"] {
+            match Tokenizer::new(input).tokenize() {
+                Err(JsonError::InvalidNumber { position, .. }) => assert_eq!(position, 0),
+                other => panic!("Expected InvalidNumber for {}, got {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_valid_json_number() {
+        for valid in [
+            "0", "-0", "7", "10", "0.5", "-12.25", "1e3", "1E-3", "0e+0", "2.5e10",
+        ] {
+            assert!(is_valid_json_number(valid), "{}", valid);
+            assert!(Tokenizer::new(valid).tokenize().is_ok(), "{}", valid);
+        }
+        for invalid in [
+            "", "-", "+1", "01", "00", "1.", ".5", "1e", "1e+", "1.5.2", "inf", "NaN", " 1", "1 ",
+        ] {
+            assert!(!is_valid_json_number(invalid), "{:?}", invalid);
+        }
+    }
+
     #[test]
     fn test_tokenize_string() -> Result<()> {
         let tokens = Tokenizer::new(r#""hello world""#).tokenize()?;
@@ -516,7 +1318,7 @@ mod tests {
 
         // Verify we have the right tokens
         assert!(tokens.contains(&Token::String("age".to_string())));
-        assert!(tokens.contains(&Token::Number(30.0)));
+        assert!(tokens.contains(&Token::Number(30.0.into())));
         assert!(tokens.contains(&Token::Comma));
         assert!(tokens.contains(&Token::String("active".to_string())));
         assert!(tokens.contains(&Token::Boolean(true)));
@@ -565,7 +1367,7 @@ mod tests {
     fn test_negative_number() -> Result<()> {
         let tokens = Tokenizer::new("-42").tokenize()?;
         assert_eq!(tokens.len(), 1);
-        assert_eq!(tokens[0], Token::Number(-42.0));
+        assert_eq!(tokens[0], Token::Number((-42.0).into()));
         Ok(())
     }
 
@@ -573,7 +1375,7 @@ mod tests {
     fn test_decimal_number() -> Result<()> {
         let tokens = Tokenizer::new("0.5").tokenize()?;
         assert_eq!(tokens.len(), 1);
-        assert_eq!(tokens[0], Token::Number(0.5));
+        assert_eq!(tokens[0], Token::Number(0.5.into()));
         Ok(())
     }
 
@@ -635,7 +1437,7 @@ mod tests {
         assert_eq!(tokens[0], Token::LeftBrace);
         assert_eq!(tokens[1], Token::String("key".to_string()));
         assert_eq!(tokens[2], Token::Colon);
-        assert_eq!(tokens[3], Token::Number(42.0));
+        assert_eq!(tokens[3], Token::Number(42.0.into()));
         assert_eq!(tokens[4], Token::RightBrace);
     }
 
@@ -727,6 +1529,39 @@ mod tests {
         assert!(matches!(result, Err(JsonError::InvalidUnicode { .. })));
     }
 
+    #[test]
+    fn test_surrogate_pairs() -> Result<()> {
+        let tokens = Tokenizer::new(r#"["\ud83d\ude00", "\uD834\uDD1E!"]"#).tokenize()?;
+        assert_eq!(tokens[1], Token::String("😀".to_string()));
+        assert_eq!(tokens[3], Token::String("\u{1D11E}!".to_string()));
+
+        let unpaired = |input: &str| Tokenizer::new(input).tokenize().unwrap_err().locate(input);
+        assert_eq!(
+            unpaired(r#"["\ud83d"]"#).to_string(),
+            r"Unpaired surrogate '\uD83D' at line 1, column 5 (position 4)"
+        );
+        assert!(matches!(
+            unpaired(r#""a\ude00""#),
+            JsonError::UnpairedSurrogate {
+                code_unit: 0xDE00,
+                position: 4,
+                ..
+            }
+        ));
+        // A high surrogate followed by anything but a low one.
+        for input in [r#""\ud83dx""#, r#""\ud83d\n""#, r#""\ud83d\ud83d""#] {
+            assert!(matches!(
+                unpaired(input),
+                JsonError::UnpairedSurrogate { position: 3, .. }
+            ));
+        }
+        assert!(matches!(
+            unpaired(r#""\ud83d\uzzzz""#),
+            JsonError::InvalidUnicode { position: 9, .. }
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_invalid_unicode_bad_hex() {
         let result = Tokenizer::new(r#""\uGGGG""#).tokenize();
@@ -744,12 +1579,61 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_comments_rejected_by_default() {
+        let result = Tokenizer::new("// note\n1").tokenize();
+        assert!(matches!(result, Err(JsonError::UnexpectedToken { .. })));
+    }
+
+    #[test]
+    fn test_comments_skipped() -> Result<()> {
+        let input = "/* head */ {\"a\": 1, // trailing\n \"b\": /**/ 2 /* a * b */} // end";
+        let mut tokenizer = Tokenizer::new(input).allow_comments(true);
+        let tokens = tokenizer.tokenize()?;
+        assert_eq!(tokens.len(), 9);
+        assert_eq!(tokens[7], Token::Number(2.0.into()));
+        assert_eq!(&input[tokenizer.spans()[1].clone()], "\"a\"");
+        Ok(())
+    }
+
+    #[test]
+    fn test_comment_errors() {
+        let mut t = Tokenizer::new("[1] /* open").allow_comments(true);
+        let err = t.tokenize().unwrap_err();
+        assert!(matches!(err, JsonError::UnexpectedEndOfInput { .. }));
+        assert_eq!(err.position(), 4);
+
+        let mut t = Tokenizer::new("[1] /x").allow_comments(true);
+        assert!(matches!(
+            t.tokenize(),
+            Err(JsonError::UnexpectedToken { position: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn test_single_quoted_strings() -> Result<()> {
+        assert!(Tokenizer::new("'a'").tokenize().is_err());
+
+        let tokens = Tokenizer::new(r#"{'it\'s': "don't"}"#)
+            .allow_single_quotes(true)
+            .tokenize()?;
+        assert_eq!(tokens[1], Token::String("it's".to_string()));
+        assert_eq!(tokens[3], Token::String("don't".to_string()));
+
+        // `\'` stays invalid inside double quotes.
+        let result = Tokenizer::new(r#""\'""#)
+            .allow_single_quotes(true)
+            .tokenize();
+        assert!(matches!(result, Err(JsonError::InvalidEscape { .. })));
+        Ok(())
+    }
+
     // Direct tests for extracted helper methods
 
     #[test]
     fn test_parse_string_simple() -> Result<()> {
         let mut t = Tokenizer::new(r#""hello""#);
-        let s = t.parse_string()?;
+        let s = t.parse_string(b'"')?;
         assert_eq!(s, "hello");
         Ok(())
     }
@@ -757,7 +1641,7 @@ mod tests {
     #[test]
     fn test_parse_string_empty() -> Result<()> {
         let mut t = Tokenizer::new(r#""""#);
-        let s = t.parse_string()?;
+        let s = t.parse_string(b'"')?;
         assert_eq!(s, "");
         Ok(())
     }
@@ -765,7 +1649,7 @@ mod tests {
     #[test]
     fn test_parse_string_with_escapes() -> Result<()> {
         let mut t = Tokenizer::new(r#""line1\nline2""#);
-        let s = t.parse_string()?;
+        let s = t.parse_string(b'"')?;
         assert_eq!(s, "line1\nline2");
         Ok(())
     }
@@ -773,7 +1657,7 @@ mod tests {
     #[test]
     fn test_parse_string_unterminated() {
         let mut t = Tokenizer::new(r#""no end"#);
-        let result = t.parse_string();
+        let result = t.parse_string(b'"');
         assert!(matches!(
             result,
             Err(JsonError::UnexpectedEndOfInput { .. })
@@ -783,42 +1667,42 @@ mod tests {
     #[test]
     fn test_parse_escape_sequence_basic() -> Result<()> {
         let mut t = Tokenizer::new("n");
-        assert_eq!(t.parse_escape_sequence()?, '\n');
+        assert_eq!(t.parse_escape_sequence(b'"')?, '\n');
         Ok(())
     }
 
     #[test]
     fn test_parse_escape_sequence_tab() -> Result<()> {
         let mut t = Tokenizer::new("t");
-        assert_eq!(t.parse_escape_sequence()?, '\t');
+        assert_eq!(t.parse_escape_sequence(b'"')?, '\t');
         Ok(())
     }
 
     #[test]
     fn test_parse_escape_sequence_quote() -> Result<()> {
         let mut t = Tokenizer::new("\"");
-        assert_eq!(t.parse_escape_sequence()?, '"');
+        assert_eq!(t.parse_escape_sequence(b'"')?, '"');
         Ok(())
     }
 
     #[test]
     fn test_parse_escape_sequence_backslash() -> Result<()> {
         let mut t = Tokenizer::new("\\");
-        assert_eq!(t.parse_escape_sequence()?, '\\');
+        assert_eq!(t.parse_escape_sequence(b'"')?, '\\');
         Ok(())
     }
 
     #[test]
     fn test_parse_escape_sequence_invalid() {
         let mut t = Tokenizer::new("x");
-        let result = t.parse_escape_sequence();
+        let result = t.parse_escape_sequence(b'"');
         assert!(matches!(result, Err(JsonError::InvalidEscape { .. })));
     }
 
     #[test]
     fn test_parse_escape_sequence_eof() {
         let mut t = Tokenizer::new("");
-        let result = t.parse_escape_sequence();
+        let result = t.parse_escape_sequence(b'"');
         assert!(matches!(
             result,
             Err(JsonError::UnexpectedEndOfInput { .. })
@@ -896,6 +1780,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_parse_number_decimal() -> Result<()> {
         let mut t = Tokenizer::new("3.14");
         assert_eq!(t.parse_number()?, 3.14);
@@ -915,4 +1800,29 @@ mod tests {
         let result = t.parse_number();
         assert!(matches!(result, Err(JsonError::UnexpectedToken { .. })));
     }
+
+    #[test]
+    fn test_token_length_limits() {
+        let strings = |input: &str, max| Tokenizer::new(input).max_string_len(max).tokenize();
+        assert!(strings(r#"["", "abc"]"#, 3).is_ok());
+        assert!(strings(r#"["", "abc"]"#, 0).is_err());
+        // Escapes count by their source text, not their decoded length.
+        assert!(matches!(
+            strings(r#"[1, "a\n"]"#, 2),
+            Err(JsonError::StringTooLong { position: 4, .. })
+        ));
+        // An unterminated string still hits the limit first.
+        assert!(matches!(
+            strings(r#""abcdefgh"#, 4),
+            Err(JsonError::StringTooLong { max_len: 4, .. })
+        ));
+
+        let numbers = |input: &str, max| Tokenizer::new(input).max_number_len(max).tokenize();
+        assert!(numbers("[-1.5e10, 7]", 7).is_ok());
+        let err = numbers("[7, -1.5e10]", 6).unwrap_err();
+        assert_eq!(
+            err.locate("[7, -1.5e10]").to_string(),
+            "Number longer than 6 bytes at line 1, column 5 (position 4)"
+        );
+    }
 }
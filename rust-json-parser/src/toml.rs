@@ -0,0 +1,1019 @@
+//! Reading TOML configuration into values.
+//!
+//! [`from_toml`](crate::toml::from_toml) reads a TOML document into a
+//! [`JsonValue`](crate::value::JsonValue) object, so a tool can accept
+//! either configuration format and work with one data model. Tables and
+//! inline tables become objects, arrays of tables become arrays of objects,
+//! and strings, integers, floats, and booleans become their JSON
+//! counterparts.
+//!
+//! JSON has no date or time type, so offset and local date-times, dates,
+//! and times are read as strings exactly as written, and `inf` and `nan`
+//! are read as null. The
+//! [`ConversionReport`](crate::interop::ConversionReport) returned with the
+//! value lists each of them. Keys and tables defined twice are errors, as
+//! is extending a table or array given with `=`; beyond that the reader is
+//! not a validator, and accepts some documents the specification rejects.
+
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+use crate::interop::ConversionReport;
+use crate::map::JsonMap;
+use crate::parser::DEFAULT_MAX_DEPTH;
+use crate::path::{JsonPath, Segment};
+use crate::value::JsonValue;
+
+/// TOML that could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TomlError {
+    /// The 1-based line on which the problem was found.
+    pub line: usize,
+    /// What was wrong.
+    pub message: String,
+}
+
+impl fmt::Display for TomlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid TOML at line {}: {}", self.line, self.message)
+    }
+}
+
+impl error::Error for TomlError {}
+
+/// Reads a TOML document into an object, with a report of the values
+/// JSON cannot represent exactly.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::json;
+/// use rust_json_parser::toml::from_toml;
+///
+/// let config = r#"
+/// title = "Example"   # a comment
+/// released = 1979-05-27T07:32:00Z
+///
+/// [server]
+/// ports = [ 8000, 8001 ]
+/// limits = { connections = 5_000, ratio = 0.75 }
+///
+/// [[users]]
+/// name = "ada"
+///
+/// [[users]]
+/// name = 'lin'
+/// admin = true
+/// "#;
+/// let (value, report) = from_toml(config)?;
+/// assert_eq!(
+///     value,
+///     json!({
+///         "title": "Example",
+///         "released": "1979-05-27T07:32:00Z",
+///         "server": {"ports": [8000, 8001], "limits": {"connections": 5000, "ratio": 0.75}},
+///         "users": [{"name": "ada"}, {"name": "lin", "admin": true}]
+///     })
+/// );
+/// assert_eq!(report.to_string(), "\"/released\": approximated (date-time read as a string)");
+/// # Ok::<(), rust_json_parser::toml::TomlError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`TomlError`] if the text is not TOML, defines a key or table
+/// twice, or nests tables and arrays more than [`DEFAULT_MAX_DEPTH`] levels
+/// deep, counting the document itself.
+pub fn from_toml(text: &str) -> Result<(JsonValue, ConversionReport), TomlError> {
+    let hasher = RandomState::new();
+    let mut reader = Reader {
+        text: text.strip_prefix('\u{feff}').unwrap_or(text),
+        pos: 0,
+        root: JsonValue::Object(JsonMap::new()),
+        table: JsonPath::root(),
+        table_hash: hasher.build_hasher(),
+        hasher,
+        headers: HashSet::new(),
+        sealed: HashMap::new(),
+        report: ConversionReport::new(),
+    };
+    loop {
+        reader.skip_blank();
+        match reader.peek() {
+            None => break,
+            Some(b'[') => reader.header()?,
+            Some(_) => reader.key_value()?,
+        }
+        reader.end_line()?;
+    }
+    Ok((reader.root, reader.report))
+}
+
+struct Reader<'a> {
+    text: &'a str,
+    pos: usize,
+    root: JsonValue,
+    // The table that key/value pairs go into, and the hash of its path.
+    table: JsonPath,
+    table_hash: DefaultHasher,
+    // Paths are hashed a segment at a time while descending, so checking
+    // each prefix of a dotted key does not rehash the whole prefix.
+    hasher: RandomState,
+    // Tables defined by a `[header]`.
+    headers: HashSet<JsonPath>,
+    // Tables and arrays given with `=`, which nothing may extend, by the
+    // hash of their path.
+    sealed: HashMap<u64, Vec<JsonPath>>,
+    report: ConversionReport,
+}
+
+impl<'a> Reader<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn error(&self, message: impl Into<String>) -> TomlError {
+        error_at(self.text, self.pos, message)
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some(b'#') {
+            self.pos += self.rest().find('\n').unwrap_or(self.rest().len());
+        }
+    }
+
+    fn eat_newline(&mut self) -> bool {
+        let len = if self.rest().starts_with('\n') {
+            1
+        } else if self.rest().starts_with("\r\n") {
+            2
+        } else {
+            return false;
+        };
+        self.pos += len;
+        true
+    }
+
+    // Skips whitespace, comments, and line breaks.
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            if !self.eat_newline() {
+                return;
+            }
+        }
+    }
+
+    fn end_line(&mut self) -> Result<(), TomlError> {
+        self.skip_spaces();
+        self.skip_comment();
+        if self.peek().is_none() || self.eat_newline() {
+            Ok(())
+        } else {
+            Err(self.error("expected a new line"))
+        }
+    }
+
+    // Reads `[table]` or `[[array.of.tables]]` and makes it the current
+    // table.
+    fn header(&mut self) -> Result<(), TomlError> {
+        let array = self.rest().starts_with("[[");
+        self.pos += if array { 2 } else { 1 };
+        let keys = self.key()?;
+        let close = if array { "]]" } else { "]" };
+        if !self.rest().starts_with(close) {
+            return Err(self.error(format!("expected `{}` after the table name", close)));
+        }
+        self.pos += close.len();
+
+        let (last, parents) = keys.split_last().expect("keys are never empty");
+        let mut path = JsonPath::root();
+        let mut hash = self.hasher.build_hasher();
+        self.descend(&mut path, &mut hash, parents, true)?;
+        if array {
+            push(&mut path, &mut hash, Segment::Key(last.clone()));
+            let sealed = is_sealed(&self.sealed, &hash, &path);
+            let parent = table_mut(&mut self.root, &path.segments()[..path.len() - 1]);
+            if !parent.contains_key(last) {
+                parent.insert(last.clone(), JsonValue::Array(Vec::new()));
+            }
+            let index = match parent.get_mut(last).expect("inserted above") {
+                JsonValue::Array(tables) if !sealed => {
+                    tables.push(JsonValue::Object(JsonMap::new()));
+                    Ok(tables.len() - 1)
+                }
+                other => Err(kind(other)),
+            };
+            let index = index
+                .map_err(|kind| self.error(format!("`{}` is already {}", dotted(&path), kind)))?;
+            push(&mut path, &mut hash, Segment::Index(index));
+            if too_deep(&path) {
+                return Err(self.error(too_deep_message()));
+            }
+        } else {
+            self.descend(&mut path, &mut hash, std::slice::from_ref(last), false)?;
+            if !self.headers.insert(path.clone()) {
+                return Err(self.error(format!("table `[{}]` is defined twice", dotted(&path))));
+            }
+        }
+        self.table = path;
+        self.table_hash = hash;
+        Ok(())
+    }
+
+    // Moves `path` from a table through its members `keys` in turn,
+    // creating a table wherever there is nothing. Table headers may also
+    // pass through an array of tables, into its last table.
+    fn descend(
+        &mut self,
+        path: &mut JsonPath,
+        hash: &mut DefaultHasher,
+        keys: &[String],
+        headers: bool,
+    ) -> Result<(), TomlError> {
+        let mut table = table_mut(&mut self.root, path.segments());
+        for key in keys {
+            push(path, hash, Segment::Key(key.clone()));
+            if too_deep(path) {
+                return Err(error_at(self.text, self.pos, too_deep_message()));
+            }
+            if is_sealed(&self.sealed, hash, path) {
+                let message = format!(
+                    "`{}` is given with `=` and cannot be extended",
+                    dotted(path)
+                );
+                return Err(error_at(self.text, self.pos, message));
+            }
+            if !table.contains_key(key) {
+                table.insert(key.clone(), JsonValue::Object(JsonMap::new()));
+            }
+            let last = match table.get(key).expect("inserted above") {
+                JsonValue::Object(_) => None,
+                JsonValue::Array(tables) if headers => Some(tables.len() - 1),
+                other => {
+                    let message = format!("`{}` is already {}", dotted(path), kind(other));
+                    return Err(error_at(self.text, self.pos, message));
+                }
+            };
+            let child = match (last, table.get_mut(key).expect("inserted above")) {
+                (Some(last), JsonValue::Array(tables)) => {
+                    push(path, hash, Segment::Index(last));
+                    if too_deep(path) {
+                        return Err(error_at(self.text, self.pos, too_deep_message()));
+                    }
+                    &mut tables[last]
+                }
+                (_, child) => child,
+            };
+            table = child.as_object_mut().expect("tables hold tables");
+        }
+        Ok(())
+    }
+
+    fn key_value(&mut self) -> Result<(), TomlError> {
+        let keys = self.key()?;
+        self.equals()?;
+        let (last, parents) = keys.split_last().expect("keys are never empty");
+        let mut path = self.table.clone();
+        let mut hash = self.table_hash.clone();
+        self.descend(&mut path, &mut hash, parents, false)?;
+        let defined = table_mut(&mut self.root, path.segments()).contains_key(last);
+        push(&mut path, &mut hash, Segment::Key(last.clone()));
+        if defined {
+            return Err(self.error(format!("key `{}` is defined twice", dotted(&path))));
+        }
+        let value = self.value(&mut path)?;
+        if matches!(value, JsonValue::Array(_) | JsonValue::Object(_)) {
+            let paths = self.sealed.entry(hash.finish()).or_default();
+            paths.push(path.clone());
+        }
+        path.pop();
+        table_mut(&mut self.root, path.segments()).insert(last.clone(), value);
+        Ok(())
+    }
+
+    // Reads a dotted key such as `server."host name".port`.
+    fn key(&mut self) -> Result<Vec<String>, TomlError> {
+        let mut keys = Vec::new();
+        loop {
+            self.skip_spaces();
+            let key = match self.peek() {
+                Some(b'"') => self.basic_string()?,
+                Some(b'\'') => self.literal_string()?,
+                _ => {
+                    let len = self
+                        .rest()
+                        .bytes()
+                        .take_while(|&b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-'))
+                        .count();
+                    if len == 0 {
+                        return Err(self.error("expected a key"));
+                    }
+                    let key = self.rest()[..len].to_string();
+                    self.pos += len;
+                    key
+                }
+            };
+            keys.push(key);
+            if keys.len() > DEFAULT_MAX_DEPTH {
+                return Err(self.error(too_deep_message()));
+            }
+            self.skip_spaces();
+            if self.peek() != Some(b'.') {
+                return Ok(keys);
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn equals(&mut self) -> Result<(), TomlError> {
+        if self.peek() != Some(b'=') {
+            return Err(self.error("expected `=` after a key"));
+        }
+        self.pos += 1;
+        self.skip_spaces();
+        Ok(())
+    }
+
+    // Reads the value at `path`, which is used in the report.
+    fn value(&mut self, path: &mut JsonPath) -> Result<JsonValue, TomlError> {
+        let string = match self.peek() {
+            Some(b'"') if self.rest().starts_with("\"\"\"") => self.multiline_basic_string()?,
+            Some(b'"') => self.basic_string()?,
+            Some(b'\'') if self.rest().starts_with("'''") => self.multiline_literal_string()?,
+            Some(b'\'') => self.literal_string()?,
+            Some(b'[') => return self.nested(path, Self::array),
+            Some(b'{') => return self.nested(path, Self::inline_table),
+            _ => return self.scalar(path),
+        };
+        Ok(JsonValue::String(string))
+    }
+
+    fn nested(
+        &mut self,
+        path: &mut JsonPath,
+        read: fn(&mut Self, &mut JsonPath) -> Result<JsonValue, TomlError>,
+    ) -> Result<JsonValue, TomlError> {
+        if too_deep(path) {
+            return Err(self.error(too_deep_message()));
+        }
+        read(self, path)
+    }
+
+    fn array(&mut self, path: &mut JsonPath) -> Result<JsonValue, TomlError> {
+        self.pos += 1; // consume '['
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.peek() == Some(b']') {
+                self.pos += 1;
+                return Ok(JsonValue::Array(items));
+            }
+            path.push(Segment::Index(items.len()));
+            items.push(self.value(path)?);
+            path.pop();
+            self.skip_blank();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {}
+                _ => return Err(self.error("expected `,` or `]` in an array")),
+            }
+        }
+    }
+
+    fn inline_table(&mut self, path: &mut JsonPath) -> Result<JsonValue, TomlError> {
+        self.pos += 1; // consume '{'
+        let mut table = JsonMap::new();
+        self.skip_spaces();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(JsonValue::Object(table));
+        }
+        loop {
+            let keys = self.key()?;
+            self.equals()?;
+            let (last, parents) = keys.split_last().expect("keys are never empty");
+            let depth = path.len();
+            let mut current = &mut table;
+            for key in parents {
+                path.push(Segment::Key(key.clone()));
+                if too_deep(path) {
+                    return Err(self.error(too_deep_message()));
+                }
+                if !current.contains_key(key) {
+                    current.insert(key.clone(), JsonValue::Object(JsonMap::new()));
+                }
+                current = match current.get_mut(key).expect("inserted above") {
+                    JsonValue::Object(map) => map,
+                    other => {
+                        let kind = kind(other);
+                        return Err(self.error(format!("`{}` is already {}", dotted(path), kind)));
+                    }
+                };
+            }
+            path.push(Segment::Key(last.clone()));
+            if current.contains_key(last) {
+                return Err(self.error(format!("key `{}` is defined twice", dotted(path))));
+            }
+            let value = self.value(path)?;
+            current.insert(last.clone(), value);
+            while path.len() > depth {
+                path.pop();
+            }
+
+            self.skip_spaces();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(table));
+                }
+                _ => return Err(self.error("expected `,` or `}` in an inline table")),
+            }
+        }
+    }
+
+    // Reads a boolean, number, or date-time.
+    fn scalar(&mut self, path: &JsonPath) -> Result<JsonValue, TomlError> {
+        let rest = self.rest();
+        let word_len = |text: &str| {
+            text.bytes()
+                .take_while(|&b| {
+                    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'+' | b'-' | b'.' | b':')
+                })
+                .count()
+        };
+        let mut len = word_len(rest);
+        // A date and a time may be separated by a space.
+        if shape(rest.as_bytes().get(..len), b"dddd-dd-dd")
+            && shape(rest.as_bytes().get(len..len + 4), b" dd:")
+        {
+            len += 1 + word_len(&rest[len + 1..]);
+        }
+        let word = &rest[..len];
+        let value = match word {
+            "" => return Err(self.error("expected a value")),
+            "true" => JsonValue::Boolean(true),
+            "false" => JsonValue::Boolean(false),
+            "inf" | "+inf" | "-inf" | "nan" | "+nan" | "-nan" => {
+                self.report
+                    .approximated(path.clone(), format!("{} read as null", word));
+                JsonValue::Null
+            }
+            _ if is_date_time(word) => {
+                self.report
+                    .approximated(path.clone(), "date-time read as a string");
+                JsonValue::String(word.to_string())
+            }
+            _ => number(word).ok_or_else(|| self.error(format!("invalid value `{}`", word)))?,
+        };
+        self.pos += len;
+        Ok(value)
+    }
+
+    fn basic_string(&mut self) -> Result<String, TomlError> {
+        self.pos += 1; // consume '"'
+        let mut out = String::new();
+        loop {
+            match self.rest().chars().next() {
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some('\\') => self.escape(&mut out)?,
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some(c) => self.push_char(&mut out, c)?,
+            }
+        }
+    }
+
+    fn multiline_basic_string(&mut self) -> Result<String, TomlError> {
+        self.pos += 3; // consume '"""'
+        self.eat_newline();
+        let mut out = String::new();
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("\"\"\"") {
+                // Up to two quotes may come right before the closing ones.
+                let quotes = rest.bytes().take_while(|&b| b == b'"').count().min(5);
+                out.push_str(&rest[..quotes - 3]);
+                self.pos += quotes;
+                return Ok(out);
+            }
+            match rest.chars().next() {
+                None => return Err(self.error("unterminated string")),
+                Some('\\') if line_ending_backslash(&rest[1..]) => {
+                    // Trim the line break and the whitespace after it.
+                    self.pos += 1;
+                    loop {
+                        self.skip_spaces();
+                        if !self.eat_newline() {
+                            break;
+                        }
+                    }
+                }
+                Some('\\') => self.escape(&mut out)?,
+                Some(_) if self.eat_newline() => out.push('\n'),
+                Some(c) => self.push_char(&mut out, c)?,
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, TomlError> {
+        self.pos += 1; // consume '\''
+        let rest = self.rest();
+        let end = rest
+            .find(['\'', '\n'])
+            .filter(|&end| rest.as_bytes()[end] == b'\'')
+            .ok_or_else(|| self.error("unterminated string"))?;
+        self.literal(&rest[..end], end + 1)
+    }
+
+    fn multiline_literal_string(&mut self) -> Result<String, TomlError> {
+        self.pos += 3; // consume "'''"
+        self.eat_newline();
+        let rest = self.rest();
+        let end = rest
+            .find("'''")
+            .ok_or_else(|| self.error("unterminated string"))?;
+        let quotes = rest[end..]
+            .bytes()
+            .take_while(|&b| b == b'\'')
+            .count()
+            .min(5);
+        let body = &rest[..end + quotes - 3];
+        Ok(self.literal(body, end + quotes)?.replace("\r\n", "\n"))
+    }
+
+    // Takes literal string text spanning `len` bytes of input.
+    fn literal(&mut self, body: &str, len: usize) -> Result<String, TomlError> {
+        if let Some(i) = body.find(|c: char| c.is_control() && !matches!(c, '\t' | '\n' | '\r')) {
+            self.pos += i;
+            return Err(self.error("control character in a string"));
+        }
+        self.pos += len;
+        Ok(body.to_string())
+    }
+
+    fn push_char(&mut self, out: &mut String, c: char) -> Result<(), TomlError> {
+        if c.is_control() && c != '\t' {
+            return Err(self.error("control character in a string"));
+        }
+        out.push(c);
+        self.pos += c.len_utf8();
+        Ok(())
+    }
+
+    fn escape(&mut self, out: &mut String) -> Result<(), TomlError> {
+        let c = match self.text.as_bytes().get(self.pos + 1) {
+            Some(b'b') => '\u{8}',
+            Some(b't') => '\t',
+            Some(b'n') => '\n',
+            Some(b'f') => '\u{c}',
+            Some(b'r') => '\r',
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(&u @ (b'u' | b'U')) => {
+                let len = if u == b'u' { 4 } else { 8 };
+                let start = self.pos + 2;
+                let c = self
+                    .text
+                    .get(start..start + len)
+                    .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("invalid unicode escape"))?;
+                out.push(c);
+                self.pos = start + len;
+                return Ok(());
+            }
+            _ => return Err(self.error("invalid escape sequence")),
+        };
+        out.push(c);
+        self.pos += 2;
+        Ok(())
+    }
+}
+
+fn error_at(text: &str, pos: usize, message: impl Into<String>) -> TomlError {
+    TomlError {
+        line: text[..pos].matches('\n').count() + 1,
+        message: message.into(),
+    }
+}
+
+// Whether a table or array at `path` would nest deeper than a default
+// parser accepts, counting the root table as the first level.
+fn too_deep(path: &JsonPath) -> bool {
+    path.len() >= DEFAULT_MAX_DEPTH
+}
+
+fn too_deep_message() -> String {
+    format!("values nest more than {} levels deep", DEFAULT_MAX_DEPTH)
+}
+
+// Extends `path` and its running hash by `segment`.
+fn push(path: &mut JsonPath, hash: &mut DefaultHasher, segment: Segment) {
+    segment.hash(hash);
+    path.push(segment);
+}
+
+fn is_sealed(sealed: &HashMap<u64, Vec<JsonPath>>, hash: &DefaultHasher, path: &JsonPath) -> bool {
+    sealed
+        .get(&hash.finish())
+        .is_some_and(|paths| paths.contains(path))
+}
+
+fn table_mut<'v>(root: &'v mut JsonValue, path: &[Segment]) -> &'v mut JsonMap {
+    path.iter()
+        .try_fold(root, |value, segment| match (value, segment) {
+            (JsonValue::Object(map), Segment::Key(key)) => map.get_mut(key),
+            (JsonValue::Array(items), Segment::Index(i)) => items.get_mut(*i),
+            _ => None,
+        })
+        .and_then(JsonValue::as_object_mut)
+        .expect("table paths lead to tables")
+}
+
+// Names a path the way a TOML file would, as dotted keys.
+fn dotted(path: &JsonPath) -> String {
+    let keys: Vec<&str> = path
+        .segments()
+        .iter()
+        .filter_map(|segment| match segment {
+            Segment::Key(key) => Some(key.as_str()),
+            Segment::Index(_) => None,
+        })
+        .collect();
+    keys.join(".")
+}
+
+fn kind(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Boolean(_) => "a boolean",
+        JsonValue::Number(_) => "a number",
+        JsonValue::String(_) => "a string",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "a table",
+    }
+}
+
+// Whether a backslash followed by `rest` ends its line, with only
+// whitespace after it.
+fn line_ending_backslash(rest: &str) -> bool {
+    let rest = rest.trim_start_matches([' ', '\t']);
+    rest.starts_with('\n') || rest.starts_with("\r\n")
+}
+
+// Reads an integer or float, with underscores between digits and, for
+// integers, `0x`, `0o`, and `0b` prefixes.
+fn number(word: &str) -> Option<JsonValue> {
+    let radix = match word.get(..2) {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        let digits = without_underscores(&word[2..], radix)?;
+        return i64::from_str_radix(&digits, radix)
+            .ok()
+            .map(JsonValue::from);
+    }
+    let (sign, unsigned) = match word.as_bytes().first() {
+        Some(b'+' | b'-') => word.split_at(1),
+        _ => ("", word),
+    };
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (unsigned, None),
+    };
+    let (int, fraction) = match mantissa.split_once('.') {
+        Some((int, fraction)) => (int, Some(fraction)),
+        None => (mantissa, None),
+    };
+    let int = without_underscores(int, 10)?;
+    if int.len() > 1 && int.starts_with('0') {
+        return None;
+    }
+    let mut text = format!("{}{}", sign, int);
+    if fraction.is_none() && exponent.is_none() {
+        return text.parse::<i64>().ok().map(JsonValue::from);
+    }
+    if let Some(fraction) = fraction {
+        text.push('.');
+        text.push_str(&without_underscores(fraction, 10)?);
+    }
+    if let Some(exponent) = exponent {
+        let (sign, digits) = match exponent.as_bytes().first() {
+            Some(b'+' | b'-') => exponent.split_at(1),
+            _ => ("", exponent),
+        };
+        text.push('e');
+        text.push_str(sign);
+        text.push_str(&without_underscores(digits, 10)?);
+    }
+    let n: f64 = text.parse().ok()?;
+    n.is_finite().then(|| JsonValue::from(n))
+}
+
+// Removes single underscores between digits, or returns `None` unless
+// `digits` is digits of `radix` separated that way.
+fn without_underscores(digits: &str, radix: u32) -> Option<String> {
+    let mut out = String::with_capacity(digits.len());
+    let mut after_digit = false;
+    for c in digits.chars() {
+        if c == '_' && after_digit {
+            after_digit = false;
+        } else if c.is_digit(radix) {
+            out.push(c);
+            after_digit = true;
+        } else {
+            return None;
+        }
+    }
+    after_digit.then_some(out)
+}
+
+// Whether `word` is an offset or local date-time, a local date, or a
+// local time, judged by its shape.
+fn is_date_time(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    let dated = shape(bytes.get(..10), b"dddd-dd-dd");
+    let time = if dated {
+        match bytes.get(10) {
+            None => return true,
+            Some(b'T' | b't' | b' ') => &bytes[11..],
+            Some(_) => return false,
+        }
+    } else {
+        bytes
+    };
+    if !shape(time.get(..8), b"dd:dd:dd") {
+        return false;
+    }
+    let mut rest = &time[8..];
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        let digits = fraction.iter().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 {
+            return false;
+        }
+        rest = &fraction[digits..];
+    }
+    match rest {
+        [] => true,
+        [b'Z' | b'z'] => dated,
+        [b'+' | b'-', offset @ ..] => dated && shape(Some(offset), b"dd:dd"),
+        _ => false,
+    }
+}
+
+// Whether `bytes` matches `pattern`, in which `d` stands for any digit.
+fn shape(bytes: Option<&[u8]>, pattern: &[u8]) -> bool {
+    bytes.is_some_and(|bytes| {
+        bytes.len() == pattern.len()
+            && bytes.iter().zip(pattern).all(|(&b, &p)| match p {
+                b'd' => b.is_ascii_digit(),
+                _ => b == p,
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interop::LossKind;
+    use crate::json;
+
+    fn read(text: &str) -> JsonValue {
+        from_toml(text).unwrap().0
+    }
+
+    fn error(text: &str) -> String {
+        from_toml(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_tables_and_arrays_of_tables() {
+        let text = "\
+[[fruit]]
+name = \"apple\"
+[fruit.physical]
+color = \"red\"
+[[fruit.variety]]
+name = \"red delicious\"
+
+[[fruit]]
+name = \"banana\"
+[[fruit.variety]]
+name = \"plantain\"
+
+[a.b.c]
+d = 1
+[a]
+e.f = 2
+";
+        assert_eq!(
+            read(text),
+            json!({
+                "fruit": [
+                    {"name": "apple", "physical": {"color": "red"}, "variety": [{"name": "red delicious"}]},
+                    {"name": "banana", "variety": [{"name": "plantain"}]}
+                ],
+                "a": {"b": {"c": {"d": 1}}, "e": {"f": 2}}
+            })
+        );
+    }
+
+    #[test]
+    fn test_strings() {
+        let text = r#"
+bare-key_1 = "tab\tquote\" \u00e9 \U0001F600"
+"quoted key" = 'C:\path'
+'' = ""
+multi = """
+first \
+    second
+""""
+raw = '''
+a\b ''quoted'''''
+crlf = """a
+b"""
+"#;
+        let text = text.replace("crlf = \"\"\"a\n", "crlf = \"\"\"a\r\n");
+        assert_eq!(
+            read(&text),
+            json!({
+                "bare-key_1": "tab\tquote\" é 😀",
+                "quoted key": "C:\\path",
+                "": "",
+                "multi": "first second\n\"",
+                "raw": "a\\b ''quoted''",
+                "crlf": "a\nb"
+            })
+        );
+    }
+
+    #[test]
+    fn test_numbers_and_dates() {
+        let text = "\
+ints = [+99, -17, 0, 1_000, 0xDEAD_beef, 0o755, 0b1101]
+floats = [1.0, -2.5, 5e+22, 6.626e-34, 9_224_617.445_991]
+special = [inf, -nan]
+dates = [1979-05-27T00:32:00.999-07:00, 1979-05-27 07:32:00, 1979-05-27, 07:32:00.5]
+";
+        let (value, report) = from_toml(text).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "ints": [99, -17, 0, 1000, 3735928559_i64, 493, 13],
+                "floats": [1.0, -2.5, 5e22, 6.626e-34, 9224617.445991],
+                "special": [null, null],
+                "dates": ["1979-05-27T00:32:00.999-07:00", "1979-05-27 07:32:00", "1979-05-27", "07:32:00.5"]
+            })
+        );
+        assert_eq!(report.of_kind(LossKind::Approximated).count(), 6);
+        assert_eq!(report.losses[1].path.to_pointer(), "/special/1");
+
+        for bad in [
+            "01",
+            "1__0",
+            "_1",
+            "1_",
+            "1.",
+            ".5",
+            "1e",
+            "0x",
+            "+0x1",
+            "1e400",
+            "9223372036854775808",
+            "07:32",
+            "1979-05-27T07:32:00+7",
+        ] {
+            assert!(from_toml(&format!("a = {}", bad)).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_inline_tables_and_arrays() {
+        let text = "\
+point = { x = 1, y.z = 2, 'w' = [ ] }
+nested = [ [ 1, 2 ], # comment
+  { a = true },
+]
+";
+        assert_eq!(
+            read(text),
+            json!({"point": {"x": 1, "y": {"z": 2}, "w": []}, "nested": [[1, 2], {"a": true}]})
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            error("a = 1\na = 2"),
+            "Invalid TOML at line 2: key `a` is defined twice"
+        );
+        assert_eq!(
+            error("[a]\n[a]"),
+            "Invalid TOML at line 2: table `[a]` is defined twice"
+        );
+        assert_eq!(
+            error("a = 1\n[a.b]"),
+            "Invalid TOML at line 2: `a` is already a number"
+        );
+        assert_eq!(
+            error("a = {b = 1}\na.c = 2"),
+            "Invalid TOML at line 2: `a` is given with `=` and cannot be extended"
+        );
+        assert_eq!(
+            error("[[a]]\n[a]"),
+            "Invalid TOML at line 2: `a` is already an array"
+        );
+        assert_eq!(
+            error("a = [1]\n[[a]]"),
+            "Invalid TOML at line 2: `a` is already an array"
+        );
+        assert_eq!(
+            error("t = {x = 1, x = 2}"),
+            "Invalid TOML at line 1: key `t.x` is defined twice"
+        );
+        assert_eq!(
+            error("a = 1 b = 2"),
+            "Invalid TOML at line 1: expected a new line"
+        );
+        assert_eq!(
+            error("a = \"open\nb = 1"),
+            "Invalid TOML at line 1: unterminated string"
+        );
+        assert_eq!(error("a =\n1"), "Invalid TOML at line 1: expected a value");
+        assert_eq!(
+            error("a = [1 2]"),
+            "Invalid TOML at line 1: expected `,` or `]` in an array"
+        );
+        assert_eq!(
+            error("a = {b = 1,}"),
+            "Invalid TOML at line 1: expected a key"
+        );
+        assert_eq!(
+            error("a = \"\\x\""),
+            "Invalid TOML at line 1: invalid escape sequence"
+        );
+        assert_eq!(
+            error("a = yes"),
+            "Invalid TOML at line 1: invalid value `yes`"
+        );
+        assert!(error(&format!("a = {}", "[".repeat(200))).contains("more than 128 levels"));
+        assert_eq!(read("\u{feff}# only a comment\r\n"), json!({}));
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        // The deepest documents that still parse again with a default
+        // parser, counting the root table, and one level more.
+        let keys = |n: usize| vec!["k"; n].join(".");
+        let cases = |extra: usize| {
+            [
+                format!("{} = 1", keys(128 + extra)),
+                format!("[{}]", keys(127 + extra)),
+                format!("[{}]\n{} = 1", keys(100), keys(28 + extra)),
+                format!("[[{}]]", keys(126 + extra)),
+                format!(
+                    "a = {}1{}",
+                    "[".repeat(127 + extra),
+                    "]".repeat(127 + extra)
+                ),
+                format!("[{}]\nv = {{{} = 1}}", keys(100), keys(27 + extra)),
+            ]
+        };
+        for text in cases(0) {
+            let value = read(&text);
+            assert!(crate::parser::parse_json(&value.to_string()).is_ok());
+        }
+        for text in cases(1)
+            .into_iter()
+            .chain([format!("{} = 1", keys(100_000))])
+        {
+            assert!(
+                error(&text).ends_with(": values nest more than 128 levels deep"),
+                "{:.40}",
+                text
+            );
+        }
+    }
+}
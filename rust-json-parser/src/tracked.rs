@@ -0,0 +1,271 @@
+//! Recording which parts of a document are actually read.
+//!
+//! Configuration files and API payloads accumulate keys that no code
+//! reads any more. Reading a value through a
+//! [`Tracked`](crate::tracked::Tracked) handle records the path of every
+//! lookup in an [`AccessLog`](crate::tracked::AccessLog); afterwards the
+//! log lists what was accessed and, given the document, what was never
+//! touched.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use crate::convert::FromJson;
+use crate::error::AccessError;
+use crate::path::{JsonPath, Segment};
+use crate::value::JsonValue;
+
+/// The set of paths reached through [`Tracked`] handles.
+///
+/// Paths are kept in the order they were first accessed. Only lookups
+/// that find a value are recorded.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::parse_json;
+/// use rust_json_parser::tracked::AccessLog;
+///
+/// let config = parse_json(r#"{"db": {"host": "x", "port": 5432}, "legacy": true}"#)?;
+/// let log = AccessLog::new();
+/// let root = log.track(&config);
+/// assert_eq!(root.get_path_as::<String>("db.host"), Ok("x".to_string()));
+///
+/// let accessed: Vec<String> = log.accessed().iter().map(|p| p.to_dotted()).collect();
+/// assert_eq!(accessed, ["db", "db.host"]);
+/// let unused: Vec<String> = log.unused(&config).iter().map(|p| p.to_dotted()).collect();
+/// assert_eq!(unused, ["db.port", "legacy"]);
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct AccessLog {
+    paths: RefCell<Vec<JsonPath>>,
+    seen: RefCell<HashSet<JsonPath>>,
+}
+
+impl AccessLog {
+    /// Creates an empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a handle on the root of `value` that records into this log.
+    pub fn track<'a>(&'a self, value: &'a JsonValue) -> Tracked<'a> {
+        Tracked {
+            value,
+            path: JsonPath::root(),
+            log: self,
+        }
+    }
+
+    /// Returns the distinct paths accessed so far, in first-access order.
+    pub fn accessed(&self) -> Vec<JsonPath> {
+        self.paths.borrow().clone()
+    }
+
+    /// Returns `true` if a lookup has reached `path`.
+    pub fn contains(&self, path: &JsonPath) -> bool {
+        self.seen.borrow().contains(path)
+    }
+
+    /// Returns the shallowest paths in `root` that no lookup reached.
+    ///
+    /// Object members and array elements count as used only if a lookup
+    /// reached them directly; reading a container with
+    /// [`Tracked::value`] and inspecting it by hand does not mark its
+    /// children. Children of an unused path are not listed separately.
+    pub fn unused(&self, root: &JsonValue) -> Vec<JsonPath> {
+        let mut unused = Vec::new();
+        self.collect_unused(root, &mut JsonPath::root(), &mut unused);
+        unused
+    }
+
+    /// Forgets every recorded path.
+    pub fn clear(&self) {
+        self.paths.borrow_mut().clear();
+        self.seen.borrow_mut().clear();
+    }
+
+    fn record(&self, path: &JsonPath) {
+        if self.seen.borrow_mut().insert(path.clone()) {
+            self.paths.borrow_mut().push(path.clone());
+        }
+    }
+
+    fn collect_unused(&self, value: &JsonValue, path: &mut JsonPath, unused: &mut Vec<JsonPath>) {
+        let mut visit = |child: &JsonValue, segment: Segment, path: &mut JsonPath| {
+            path.push(segment);
+            if self.contains(path) {
+                self.collect_unused(child, path, unused);
+            } else {
+                unused.push(path.clone());
+            }
+            path.pop();
+        };
+        match value {
+            JsonValue::Object(map) => {
+                for (key, child) in map {
+                    visit(child, Segment::Key(key.clone()), path);
+                }
+            }
+            JsonValue::Array(arr) => {
+                for (index, child) in arr.iter().enumerate() {
+                    visit(child, Segment::Index(index), path);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A borrowed value that records every lookup made through it.
+///
+/// Lookups return further `Tracked` handles, so chains such as
+/// `root.get("db")?.get("host")` record both `db` and `db.host`.
+#[derive(Debug, Clone)]
+pub struct Tracked<'a> {
+    value: &'a JsonValue,
+    path: JsonPath,
+    log: &'a AccessLog,
+}
+
+impl<'a> Tracked<'a> {
+    /// Returns the underlying value.
+    pub fn value(&self) -> &'a JsonValue {
+        self.value
+    }
+
+    /// Returns the path of this value from the tracked root.
+    pub fn path(&self) -> &JsonPath {
+        &self.path
+    }
+
+    /// Looks up an object member, recording its path.
+    pub fn get(&self, key: &str) -> Option<Tracked<'a>> {
+        self.child(&Segment::Key(key.to_string()))
+    }
+
+    /// Looks up an array element, recording its path.
+    pub fn get_index(&self, index: usize) -> Option<Tracked<'a>> {
+        self.child(&Segment::Index(index))
+    }
+
+    /// Follows `path` from this value, recording each step that resolves.
+    pub fn at(&self, path: &JsonPath) -> Option<Tracked<'a>> {
+        path.segments()
+            .iter()
+            .try_fold(self.clone(), |current, segment| current.child(segment))
+    }
+
+    /// Follows a JSON Pointer from this value, recording each step that
+    /// resolves. Returns `None` for a malformed pointer.
+    pub fn pointer(&self, pointer: &str) -> Option<Tracked<'a>> {
+        self.at(&JsonPath::parse_pointer(pointer)?)
+    }
+
+    /// Iterates over array elements, recording each one as it is yielded.
+    /// Yields nothing for non-arrays.
+    pub fn iter(&self) -> impl Iterator<Item = Tracked<'a>> + '_ {
+        let len = self.value.as_array().map_or(0, Vec::len);
+        (0..len).filter_map(|index| self.get_index(index))
+    }
+
+    /// Tracked form of [`JsonValue::get_as`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessError`] as [`JsonValue::get_as`] does.
+    pub fn get_as<T: FromJson>(&self, key: &str) -> Result<T, AccessError> {
+        self.get(key);
+        self.value.get_as(key)
+    }
+
+    /// Tracked form of [`JsonValue::get_path_as`]. Error paths are
+    /// relative to this value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessError`] as [`JsonValue::get_path_as`] does.
+    pub fn get_path_as<T: FromJson>(&self, path: &str) -> Result<T, AccessError> {
+        self.at(&JsonPath::parse_dotted(path));
+        self.value.get_path_as(path)
+    }
+
+    fn child(&self, segment: &Segment) -> Option<Tracked<'a>> {
+        let value = self.value.child(segment)?;
+        // Record object members by key, even when looked up with an
+        // index-like segment, so paths match those from `unused`.
+        let segment = match (self.value, segment) {
+            (JsonValue::Object(_), Segment::Index(index)) => Segment::Key(index.to_string()),
+            _ => segment.clone(),
+        };
+        let path = self.path.join(segment);
+        self.log.record(&path);
+        Some(Tracked {
+            value,
+            path,
+            log: self.log,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    fn dotted(paths: Vec<JsonPath>) -> Vec<String> {
+        paths.iter().map(JsonPath::to_dotted).collect()
+    }
+
+    #[test]
+    fn test_records_chained_and_pointer_lookups() {
+        let doc =
+            parse_json(r#"{"users": [{"name": "a", "age": 1}, {"name": "b"}], "v": 2}"#).unwrap();
+        let log = AccessLog::new();
+        let root = log.track(&doc);
+        let names: Vec<&str> = root
+            .get("users")
+            .unwrap()
+            .iter()
+            .filter_map(|user| user.get("name")?.value().as_str())
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+        assert!(root.pointer("/missing/x").is_none());
+        assert_eq!(
+            dotted(log.accessed()),
+            [
+                "users",
+                "users.0",
+                "users.0.name",
+                "users.1",
+                "users.1.name"
+            ]
+        );
+        assert_eq!(dotted(log.unused(&doc)), ["users.0.age", "v"]);
+    }
+
+    #[test]
+    fn test_repeated_access_is_recorded_once() {
+        let doc = parse_json(r#"{"a": 1}"#).unwrap();
+        let log = AccessLog::new();
+        let root = log.track(&doc);
+        assert_eq!(root.get_as::<i64>("a"), Ok(1));
+        assert!(root.get_as::<String>("a").is_err());
+        assert_eq!(dotted(log.accessed()), ["a"]);
+        assert!(log.unused(&doc).is_empty());
+
+        log.clear();
+        assert!(log.accessed().is_empty());
+        assert_eq!(dotted(log.unused(&doc)), ["a"]);
+    }
+
+    #[test]
+    fn test_numeric_keys_recorded_as_keys() {
+        let doc = parse_json(r#"{"0": true}"#).unwrap();
+        let log = AccessLog::new();
+        assert!(log.track(&doc).pointer("/0").is_some());
+        assert_eq!(log.accessed(), [JsonPath::root().join("0")]);
+        assert!(log.unused(&doc).is_empty());
+    }
+}
@@ -0,0 +1,444 @@
+//! Documents that stay valid against a shape as they are edited.
+//!
+//! A long-lived configuration document is usually validated once, when it
+//! is loaded, and then edited in memory for hours. A
+//! [`TypedDocument`](crate::typed::TypedDocument) pairs the value with a
+//! [`Shape`](crate::typed::Shape) and checks every edit against it: only
+//! the paths an edit can affect are re-checked, and an edit that would
+//! break the shape is undone and reported, so the document is valid at
+//! every point between full validations.
+
+use std::error;
+use std::fmt;
+
+use crate::path::{JsonPath, Segment};
+use crate::value::JsonValue;
+
+/// Rules a document must follow, checked one value at a time.
+///
+/// [`check`](Shape::check) sees one value and the path it is at, and
+/// should look only at that value and its direct members: a container
+/// may require keys or limit its length, but whether each member is well
+/// formed is decided by the check at that member's own path. That is what
+/// lets a [`TypedDocument`] re-check a single edit without walking the
+/// whole document.
+///
+/// Closures taking `(&JsonPath, &JsonValue)` are shapes.
+pub trait Shape {
+    /// Checks the value at `path`, returning a description of the problem
+    /// if it is not allowed there.
+    fn check(&self, path: &JsonPath, value: &JsonValue) -> Result<(), String>;
+
+    /// Checks every value in `document`, stopping at the first problem.
+    fn validate(&self, document: &JsonValue) -> Result<(), ShapeError> {
+        check_tree(self, &mut JsonPath::root(), document)
+    }
+}
+
+impl<F> Shape for F
+where
+    F: Fn(&JsonPath, &JsonValue) -> Result<(), String>,
+{
+    fn check(&self, path: &JsonPath, value: &JsonValue) -> Result<(), String> {
+        self(path, value)
+    }
+}
+
+/// A value that does not satisfy a [`Shape`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeError {
+    /// Where the value is.
+    pub path: JsonPath,
+    /// What is wrong with it, as returned by [`Shape::check`].
+    pub message: String,
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid value at {:?}: {}",
+            self.path.to_pointer(),
+            self.message
+        )
+    }
+}
+
+impl error::Error for ShapeError {}
+
+/// Errors from editing a [`TypedDocument`]. Either way the document is
+/// left unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    /// The path does not exist, or for [`TypedDocument::set`], its parent
+    /// does not.
+    Missing {
+        /// The path that could not be resolved.
+        path: JsonPath,
+    },
+    /// The edit would have broken the shape.
+    Invalid(ShapeError),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::Missing { path } => write!(f, "No value at {:?}", path.to_pointer()),
+            EditError::Invalid(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl error::Error for EditError {}
+
+impl From<ShapeError> for EditError {
+    fn from(error: ShapeError) -> Self {
+        EditError::Invalid(error)
+    }
+}
+
+/// A document that always satisfies its shape.
+///
+/// Every edit re-checks the values it replaced or inserted, all the
+/// values inside them, and each of their ancestors. An edit that fails is
+/// rolled back before the error is returned.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::json;
+/// use rust_json_parser::path::JsonPath;
+/// use rust_json_parser::typed::{EditError, TypedDocument};
+/// use rust_json_parser::value::JsonValue;
+///
+/// let shape = |path: &JsonPath, value: &JsonValue| match path.to_pointer().as_str() {
+///     "" if value.get("port").is_none() => Err("missing port".to_string()),
+///     "/port" if value.as_i64().is_none_or(|p| !(1..=65535).contains(&p)) => {
+///         Err("expected a port number".to_string())
+///     }
+///     _ => Ok(()),
+/// };
+/// let mut config = TypedDocument::new(json!({"host": "a", "port": 80}), shape).unwrap();
+///
+/// config.set(&JsonPath::root().join("port"), json!(8080))?;
+/// let err = config.set(&JsonPath::root().join("port"), json!("http")).unwrap_err();
+/// assert_eq!(err.to_string(), r#"Invalid value at "/port": expected a port number"#);
+/// let err = config.remove(&JsonPath::root().join("port")).unwrap_err();
+/// assert_eq!(err.to_string(), r#"Invalid value at "": missing port"#);
+/// assert_eq!(config.value(), &json!({"host": "a", "port": 8080}));
+/// # Ok::<(), EditError>(())
+/// ```
+#[derive(Clone)]
+pub struct TypedDocument<S: Shape> {
+    value: JsonValue,
+    shape: S,
+}
+
+// Shapes are usually closures, which have no `Debug`.
+impl<S: Shape> fmt::Debug for TypedDocument<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedDocument")
+            .field("value", &self.value)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: Shape> TypedDocument<S> {
+    /// Validates `value` against `shape` in full and wraps it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first value that does not satisfy the shape.
+    pub fn new(value: JsonValue, shape: S) -> Result<Self, ShapeError> {
+        shape.validate(&value)?;
+        Ok(Self { value, shape })
+    }
+
+    /// Returns the document.
+    pub fn value(&self) -> &JsonValue {
+        &self.value
+    }
+
+    /// Returns the shape.
+    pub fn shape(&self) -> &S {
+        &self.shape
+    }
+
+    /// Unwraps the document.
+    pub fn into_inner(self) -> JsonValue {
+        self.value
+    }
+
+    /// Puts `value` at `path`, returning the value it replaced.
+    ///
+    /// An object member is replaced in place or appended; an array
+    /// element is replaced, or appended if the index is the array's length
+    /// or the last segment is the key `-`. An empty path replaces the
+    /// whole document.
+    pub fn set(
+        &mut self,
+        path: &JsonPath,
+        value: JsonValue,
+    ) -> Result<Option<JsonValue>, EditError> {
+        let Some((last, parents)) = path.segments().split_last() else {
+            self.shape.validate(&value)?;
+            return Ok(Some(std::mem::replace(&mut self.value, value)));
+        };
+        let parent = resolve_mut(&mut self.value, parents).ok_or_else(|| missing(path))?;
+        let (at, old) = match (parent, last) {
+            (JsonValue::Object(map), segment) => {
+                let key = segment.to_string();
+                let old = map.insert(key.clone(), value);
+                (Segment::Key(key), old)
+            }
+            (JsonValue::Array(elements), Segment::Index(i)) if *i < elements.len() => (
+                Segment::Index(*i),
+                Some(std::mem::replace(&mut elements[*i], value)),
+            ),
+            (JsonValue::Array(elements), segment)
+                if *segment == Segment::Index(elements.len())
+                    || *segment == Segment::Key("-".to_string()) =>
+            {
+                elements.push(value);
+                (Segment::Index(elements.len() - 1), None)
+            }
+            _ => return Err(missing(path)),
+        };
+        let mut at_path: JsonPath = parents.iter().cloned().collect();
+        at_path.push(at.clone());
+        if let Err(error) = self.recheck(&at_path, true) {
+            let parent = resolve_mut(&mut self.value, parents).expect("parent still exists");
+            match (parent, &at, old) {
+                (JsonValue::Object(map), Segment::Key(key), Some(old)) => {
+                    map.insert(key.clone(), old);
+                }
+                (JsonValue::Object(map), Segment::Key(key), None) => {
+                    map.remove(key);
+                }
+                (JsonValue::Array(elements), Segment::Index(i), Some(old)) => elements[*i] = old,
+                (JsonValue::Array(elements), _, None) => {
+                    elements.pop();
+                }
+                _ => unreachable!("parent kept its type"),
+            }
+            return Err(error.into());
+        }
+        Ok(old)
+    }
+
+    /// Removes and returns the value at `path`. Removing from an array
+    /// shifts later elements down.
+    pub fn remove(&mut self, path: &JsonPath) -> Result<JsonValue, EditError> {
+        let (last, parents) = path.segments().split_last().ok_or_else(|| missing(path))?;
+        let parent = resolve_mut(&mut self.value, parents).ok_or_else(|| missing(path))?;
+        // The member's position or the element's index, to undo the removal.
+        let (removed, position) = match (parent, last) {
+            (JsonValue::Object(map), segment) => {
+                let (position, removed) = map
+                    .remove_full(&segment.to_string())
+                    .ok_or_else(|| missing(path))?;
+                (removed, position)
+            }
+            (JsonValue::Array(elements), Segment::Index(i)) if *i < elements.len() => {
+                (elements.remove(*i), *i)
+            }
+            _ => return Err(missing(path)),
+        };
+        let parent_path: JsonPath = parents.iter().cloned().collect();
+        if let Err(error) = self.recheck(&parent_path, false) {
+            match resolve_mut(&mut self.value, parents).expect("parent still exists") {
+                JsonValue::Object(map) => map.insert_at(position, last.to_string(), removed),
+                JsonValue::Array(elements) => elements.insert(position, removed),
+                _ => unreachable!("parent kept its type"),
+            }
+            return Err(error.into());
+        }
+        Ok(removed)
+    }
+
+    /// Edits the value at `path` in place with `f`, then re-checks it,
+    /// everything inside it, and its ancestors.
+    ///
+    /// The value is cloned first so a failed edit can be undone; for
+    /// large subtrees, editing a narrower path is cheaper.
+    pub fn update(
+        &mut self,
+        path: &JsonPath,
+        f: impl FnOnce(&mut JsonValue),
+    ) -> Result<(), EditError> {
+        let target = resolve_mut(&mut self.value, path.segments()).ok_or_else(|| missing(path))?;
+        let before = target.clone();
+        f(target);
+        let result = self.recheck(path, true);
+        if result.is_err() {
+            *resolve_mut(&mut self.value, path.segments()).expect("path still exists") = before;
+        }
+        result.map_err(EditError::from)
+    }
+
+    // Checks the value at `path` and its ancestors, and with `subtree`
+    // everything inside it too.
+    fn recheck(&self, path: &JsonPath, subtree: bool) -> Result<(), ShapeError> {
+        let mut ancestors = Vec::with_capacity(path.len() + 1);
+        let mut current = &self.value;
+        ancestors.push(current);
+        for segment in path {
+            current = child(current, segment).expect("edited path exists");
+            ancestors.push(current);
+        }
+        if subtree {
+            check_tree(
+                &self.shape,
+                &mut path.clone(),
+                ancestors.pop().expect("root"),
+            )?;
+        }
+        for (depth, value) in ancestors.into_iter().enumerate().rev() {
+            let ancestor: JsonPath = path.segments()[..depth].iter().cloned().collect();
+            check_one(&self.shape, &ancestor, value)?;
+        }
+        Ok(())
+    }
+}
+
+fn missing(path: &JsonPath) -> EditError {
+    EditError::Missing { path: path.clone() }
+}
+
+fn check_one<S: Shape + ?Sized>(
+    shape: &S,
+    path: &JsonPath,
+    value: &JsonValue,
+) -> Result<(), ShapeError> {
+    shape.check(path, value).map_err(|message| ShapeError {
+        path: path.clone(),
+        message,
+    })
+}
+
+fn check_tree<S: Shape + ?Sized>(
+    shape: &S,
+    path: &mut JsonPath,
+    value: &JsonValue,
+) -> Result<(), ShapeError> {
+    check_one(shape, path, value)?;
+    match value {
+        JsonValue::Array(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                path.push(i);
+                check_tree(shape, path, element)?;
+                path.pop();
+            }
+        }
+        JsonValue::Object(map) => {
+            for (key, member) in map {
+                path.push(key.as_str());
+                check_tree(shape, path, member)?;
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn child<'a>(value: &'a JsonValue, segment: &Segment) -> Option<&'a JsonValue> {
+    match (value, segment) {
+        (JsonValue::Object(map), segment) => map.get(&segment.to_string()),
+        (JsonValue::Array(elements), Segment::Index(i)) => elements.get(*i),
+        _ => None,
+    }
+}
+
+fn resolve_mut<'a>(value: &'a mut JsonValue, segments: &[Segment]) -> Option<&'a mut JsonValue> {
+    segments
+        .iter()
+        .try_fold(value, |current, segment| match (current, segment) {
+            (JsonValue::Object(map), segment) => map.get_mut(&segment.to_string()),
+            (JsonValue::Array(elements), Segment::Index(i)) => elements.get_mut(*i),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    // Ports are integers, tags are unique strings, and the root needs a
+    // name and at most three tags.
+    fn shape(path: &JsonPath, value: &JsonValue) -> Result<(), String> {
+        let pointer = path.to_pointer();
+        if pointer.is_empty() {
+            if value.get("name").is_none() {
+                return Err("missing name".to_string());
+            }
+        } else if pointer == "/tags" {
+            let tags = value.as_array().ok_or("expected an array")?;
+            if tags.len() > 3 {
+                return Err("too many tags".to_string());
+            }
+        } else if pointer.starts_with("/tags/") && value.as_str().is_none() {
+            return Err("expected a string".to_string());
+        } else if pointer.ends_with("/port") && value.as_i64().is_none() {
+            return Err("expected an integer".to_string());
+        }
+        Ok(())
+    }
+
+    fn path(pointer: &str) -> JsonPath {
+        JsonPath::parse_pointer(pointer).unwrap()
+    }
+
+    #[test]
+    fn test_edits_are_checked_and_rolled_back() {
+        let original = json!({"name": "svc", "tags": ["a", "b"], "db": {"port": 5432}});
+        let mut doc = TypedDocument::new(original.clone(), shape).unwrap();
+
+        let failures = [
+            doc.set(&path("/tags/-"), json!(1)).unwrap_err(),
+            doc.set(&path("/tags/0"), json!(null)).unwrap_err(),
+            doc.set(&path("/db"), json!({"port": "x"})).unwrap_err(),
+            doc.remove(&path("/name")).unwrap_err(),
+            doc.update(&path("/tags"), |tags| {
+                *tags = json!(["a", "b", "c", "d"]);
+            })
+            .unwrap_err(),
+            doc.set(&path("/db/host/name"), json!("x")).unwrap_err(),
+            doc.set(&JsonPath::root(), json!({})).unwrap_err(),
+        ];
+        let messages: Vec<String> = failures.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                r#"Invalid value at "/tags/2": expected a string"#,
+                r#"Invalid value at "/tags/0": expected a string"#,
+                r#"Invalid value at "/db/port": expected an integer"#,
+                r#"Invalid value at "": missing name"#,
+                r#"Invalid value at "/tags": too many tags"#,
+                r#"No value at "/db/host/name""#,
+                r#"Invalid value at "": missing name"#,
+            ]
+        );
+        assert_eq!(doc.value().to_string(), original.to_string());
+
+        assert_eq!(doc.set(&path("/tags/2"), json!("c")), Ok(None));
+        assert_eq!(doc.remove(&path("/tags/0")), Ok(json!("a")));
+        assert_eq!(
+            doc.set(&path("/db/port"), json!(6432)),
+            Ok(Some(json!(5432)))
+        );
+        assert!(doc.update(&path("/db"), |db| db["port"] = json!(7)).is_ok());
+        assert_eq!(
+            doc.into_inner().to_string(),
+            r#"{"name":"svc","tags":["b","c"],"db":{"port":7}}"#
+        );
+    }
+
+    #[test]
+    fn test_full_validation() {
+        let err = TypedDocument::new(json!({"name": 1, "tags": [true]}), shape).unwrap_err();
+        assert_eq!(err.path, path("/tags/0"));
+        assert_eq!(shape.validate(&json!({"name": "x"})), Ok(()));
+    }
+}
@@ -0,0 +1,204 @@
+//! Checking that text is strict JSON without building a value.
+//!
+//! [`validate`](crate::validate::validate) answers "is this RFC 8259
+//! JSON?" for payloads that are only passed along, stored, or parsed
+//! elsewhere. It runs the zero-copy
+//! [`BorrowedTokenizer`](crate::borrowed::BorrowedTokenizer) through a
+//! small grammar state machine, so valid input is checked in one pass
+//! with no tree and almost no allocation. Only when the input turns out
+//! to be invalid is it parsed again with
+//! [`parse_with_recovery`](crate::recovery::parse_with_recovery) to
+//! report every error.
+
+use crate::borrowed::{BorrowedToken, BorrowedTokenizer};
+use crate::error::JsonError;
+use crate::parser::{DEFAULT_MAX_DEPTH, ParserOptions};
+use crate::recovery;
+
+// The open containers fit in the bits of a `u128`.
+const _: () = assert!(DEFAULT_MAX_DEPTH <= 128);
+
+/// Checks that `input` is a single JSON value, as the default
+/// [`JsonParser`](crate::parser::JsonParser) would accept it: no comments,
+/// trailing commas, or single quotes, whatever the
+/// [process or thread defaults](crate::parser::ParserOptions::current)
+/// are, and nesting no deeper than [`DEFAULT_MAX_DEPTH`].
+///
+/// # Errors
+///
+/// Returns every error in the input, in input order, exactly as
+/// [`parse_with_recovery`](crate::recovery::parse_with_recovery) reports
+/// them. The list is never empty.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::validate::validate;
+///
+/// assert!(validate(r#"{"ids": [1, 2, 3], "next": null}"#).is_ok());
+///
+/// let errors = validate("[1 2, tru]").unwrap_err();
+/// let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+/// assert_eq!(
+///     messages,
+///     [
+///         "Unexpected token at line 1, column 4 (position 3): expected comma or closing bracket, found Number(2)",
+///         "Unexpected token at line 1, column 7 (position 6): expected valid JSON token, found tru",
+///     ]
+/// );
+/// ```
+pub fn validate(input: &str) -> Result<(), Vec<JsonError>> {
+    if is_valid(input) {
+        return Ok(());
+    }
+    let errors = recovery::recover(input, ParserOptions::default()).errors;
+    debug_assert!(!errors.is_empty(), "recovery accepted invalid input");
+    Err(errors)
+}
+
+// What the next token may be.
+#[derive(Clone, Copy)]
+enum Expect {
+    // A value.
+    Value,
+    // A value or `]`, right after `[`.
+    ValueOrClose,
+    // A key.
+    Key,
+    // A key or `}`, right after `{`.
+    KeyOrClose,
+    // The `:` after a key.
+    Colon,
+    // A comma or the closer of the innermost container, or the end of
+    // the input after the root value.
+    Next,
+}
+
+pub(crate) fn is_valid(input: &str) -> bool {
+    let mut tokens = BorrowedTokenizer::new(input);
+    // One bit per open container, lowest for the innermost, set for
+    // objects.
+    let mut open: u128 = 0;
+    let mut depth = 0;
+    let mut expect = Expect::Value;
+    loop {
+        let Ok(token) = tokens.next_token() else {
+            return false;
+        };
+        let in_object = open & 1 == 1;
+        expect = match (expect, token) {
+            (Expect::Value | Expect::ValueOrClose, Some(BorrowedToken::LeftBracket)) => {
+                if depth == DEFAULT_MAX_DEPTH {
+                    return false;
+                }
+                open <<= 1;
+                depth += 1;
+                Expect::ValueOrClose
+            }
+            (Expect::Value | Expect::ValueOrClose, Some(BorrowedToken::LeftBrace)) => {
+                if depth == DEFAULT_MAX_DEPTH {
+                    return false;
+                }
+                open = open << 1 | 1;
+                depth += 1;
+                Expect::KeyOrClose
+            }
+            (
+                Expect::Value | Expect::ValueOrClose,
+                Some(
+                    BorrowedToken::String(_)
+                    | BorrowedToken::Number(_)
+                    | BorrowedToken::Boolean(_)
+                    | BorrowedToken::Null,
+                ),
+            ) => Expect::Next,
+            (Expect::Key | Expect::KeyOrClose, Some(BorrowedToken::String(_))) => Expect::Colon,
+            (Expect::Colon, Some(BorrowedToken::Colon)) => Expect::Value,
+            (Expect::Next, Some(BorrowedToken::Comma)) if depth > 0 => {
+                if in_object {
+                    Expect::Key
+                } else {
+                    Expect::Value
+                }
+            }
+            (Expect::ValueOrClose, Some(BorrowedToken::RightBracket))
+            | (Expect::KeyOrClose, Some(BorrowedToken::RightBrace)) => {
+                open >>= 1;
+                depth -= 1;
+                Expect::Next
+            }
+            (Expect::Next, Some(BorrowedToken::RightBracket)) if depth > 0 && !in_object => {
+                open >>= 1;
+                depth -= 1;
+                Expect::Next
+            }
+            (Expect::Next, Some(BorrowedToken::RightBrace)) if depth > 0 && in_object => {
+                open >>= 1;
+                depth -= 1;
+                Expect::Next
+            }
+            (Expect::Next, None) => return depth == 0,
+            _ => return false,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_json;
+    use crate::recovery::parse_with_recovery;
+
+    #[test]
+    fn test_agrees_with_parser() {
+        let deep = |n: usize| format!("{}{}", "[".repeat(n), "]".repeat(n));
+        let inputs = [
+            "null",
+            " 1.5e3 ",
+            r#""\ud83d\ude00""#,
+            "[]",
+            "{}",
+            r#"{"a": [1, {"b": null}], "a": {}}"#,
+            r#"[[], [[]], {"x": [true, false]}]"#,
+            "",
+            "   ",
+            "[",
+            "]",
+            "[1,]",
+            "[,1]",
+            "{,}",
+            r#"{"a"}"#,
+            r#"{"a": 1,}"#,
+            r#"{"a" 1}"#,
+            r#"{1: 2}"#,
+            "[1 2]",
+            "[1]]",
+            "[}",
+            r#"{"a": 1]"#,
+            "1 2",
+            "// c\n1",
+            "['a']",
+            "[01]",
+            "[\"\\q\"]",
+            &deep(DEFAULT_MAX_DEPTH),
+            &deep(DEFAULT_MAX_DEPTH + 1),
+        ];
+        for input in inputs {
+            let parsed = parse_json(input);
+            assert_eq!(validate(input).is_ok(), parsed.is_ok(), "{:?}", input);
+            if let Err(errors) = validate(input) {
+                assert_eq!(errors, parse_with_recovery(input).errors, "{:?}", input);
+                assert_eq!(errors[0], parsed.unwrap_err(), "{:?}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ignores_lenient_defaults() {
+        let input = "[1, 2,]";
+        ParserOptions::lenient().scoped(|| {
+            assert!(parse_json(input).is_ok());
+            assert_eq!(validate(input).unwrap_err().len(), 1);
+        });
+    }
+}
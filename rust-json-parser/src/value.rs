@@ -9,8 +9,26 @@
 //! [`JsonValue::get`](crate::value::JsonValue::get), and
 //! [`JsonValue::get_index`](crate::value::JsonValue::get_index).
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::io::{self, Write};
+use std::ops::{Index, IndexMut};
+
+use crate::canonical::{CanonicalError, write_canonical};
+use crate::convert::FromJson;
+use crate::error::{AccessError, IntConversionError};
+use crate::frozen::FrozenJson;
+#[cfg(feature = "msgpack")]
+use crate::interop::ConversionReport;
+use crate::iter::Iter;
+use crate::map::JsonMap;
+use crate::merge::MergeStrategy;
+#[cfg(feature = "msgpack")]
+use crate::msgpack::{self, MsgpackError};
+use crate::namespace::{self, NamespaceError};
+use crate::path::{JsonPath, Segment};
+use crate::pointer::{parse_index, parse_pointer};
+use crate::sanitize::SanitizeOptions;
 
 /// Represents a parsed JSON value.
 ///
@@ -32,7 +50,7 @@ use std::fmt;
 /// }
 /// # Ok::<(), rust_json_parser::error::JsonError>(())
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub enum JsonValue {
     /// Represents JSON `null`.
     ///
@@ -44,12 +62,13 @@ pub enum JsonValue {
     /// The inner `bool` holds the boolean value. Use [`JsonValue::as_bool`]
     /// to extract it as an `Option<bool>`.
     Boolean(bool),
-    /// Represents a JSON number, stored as `f64`.
+    /// Represents a JSON number.
     ///
-    /// All JSON numbers (integers and decimals) are stored as 64-bit
-    /// floating point values. Use [`JsonValue::as_f64`] to extract the
-    /// numeric value as an `Option<f64>`.
-    Number(f64),
+    /// The inner [`JsonNumber`] holds the value as an exact integer or an
+    /// `f64` and, when the parser preserves raw numbers, the exact source
+    /// text. Use [`JsonValue::as_f64`], [`JsonValue::as_i64`], or
+    /// [`JsonValue::as_u64`] to extract it.
+    Number(JsonNumber),
     /// Represents a JSON string.
     ///
     /// The inner `String` holds the decoded string content with all
@@ -64,10 +83,10 @@ pub enum JsonValue {
     Array(Vec<JsonValue>),
     /// Represents a JSON object mapping string keys to values.
     ///
-    /// The inner `HashMap<String, JsonValue>` holds the key-value pairs.
-    /// Use [`JsonValue::as_object`] to borrow the map, or
+    /// The inner [`JsonMap`] holds the key-value pairs in the order they
+    /// were inserted (for parsed documents, the order in the input). Use [`JsonValue::as_object`] to borrow the map, or
     /// [`JsonValue::get`] to look up a value by key.
-    Object(HashMap<String, JsonValue>),
+    Object(JsonMap),
 }
 
 impl JsonValue {
@@ -132,11 +151,161 @@ impl JsonValue {
     /// ```
     pub fn as_f64(&self) -> Option<f64> {
         match self {
-            JsonValue::Number(n) => Some(*n),
+            JsonValue::Number(n) => Some(n.as_f64()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a `JsonValue::Number` holding an
+    /// integer that fits in `i64`.
+    ///
+    /// Numbers written with a fraction or exponent (`1.0`, `1e3`) are
+    /// floating point and return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// assert_eq!(parse_json("-42")?.as_i64(), Some(-42));
+    /// assert_eq!(parse_json("42.5")?.as_i64(), None);
+    /// assert_eq!(parse_json("18446744073709551615")?.as_i64(), None);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValue::Number(n) => n.as_i64(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a `JsonValue::Number` holding a
+    /// non-negative integer that fits in `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// assert_eq!(parse_json("18446744073709551615")?.as_u64(), Some(u64::MAX));
+    /// assert_eq!(parse_json("-1")?.as_u64(), None);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            JsonValue::Number(n) => n.as_u64(),
             _ => None,
         }
     }
 
+    /// Returns the value if this is an integer that fits in `i32`.
+    ///
+    /// Like [`as_i64`](Self::as_i64), numbers written with a fraction or
+    /// exponent return `None`, as do values out of range. Use
+    /// [`try_into_int`](Self::try_into_int) to learn why a conversion
+    /// failed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// assert_eq!(parse_json("-7")?.as_i32(), Some(-7));
+    /// assert_eq!(parse_json("3000000000")?.as_i32(), None);
+    /// assert_eq!(parse_json("7.0")?.as_i32(), None);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn as_i32(&self) -> Option<i32> {
+        self.try_into_int().ok()
+    }
+
+    /// Returns the value if this is an integer that fits in `u32`.
+    ///
+    /// See [`as_i32`](Self::as_i32) for the exactness rules.
+    pub fn as_u32(&self) -> Option<u32> {
+        self.try_into_int().ok()
+    }
+
+    /// Returns the value if this is an integer that fits in `u16`.
+    ///
+    /// See [`as_i32`](Self::as_i32) for the exactness rules.
+    pub fn as_u16(&self) -> Option<u16> {
+        self.try_into_int().ok()
+    }
+
+    /// Returns the value if this is an integer that fits in `u8`.
+    ///
+    /// See [`as_i32`](Self::as_i32) for the exactness rules.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// assert_eq!(parse_json("255")?.as_u8(), Some(255));
+    /// assert_eq!(parse_json("256")?.as_u8(), None);
+    /// assert_eq!(parse_json("-1")?.as_u8(), None);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn as_u8(&self) -> Option<u8> {
+        self.try_into_int().ok()
+    }
+
+    /// Converts an integer value to any integer type, reporting why the
+    /// conversion failed.
+    ///
+    /// Only numbers stored as integers convert; as with
+    /// [`as_i64`](Self::as_i64), `1.0` and `1e3` are not integers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// assert_eq!(parse_json("200")?.try_into_int::<u8>(), Ok(200));
+    ///
+    /// let err = parse_json("300")?.try_into_int::<u8>().unwrap_err();
+    /// assert_eq!(err.to_string(), "Integer 300 is out of range for u8");
+    /// let err = parse_json("2.5")?.try_into_int::<u8>().unwrap_err();
+    /// assert_eq!(err.to_string(), "Expected integer, found 2.5");
+    /// let err = parse_json("\"7\"")?.try_into_int::<u8>().unwrap_err();
+    /// assert_eq!(err.to_string(), "Expected integer, found string");
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IntConversionError::NotANumber`] for non-numbers,
+    /// [`IntConversionError::NotAnInteger`] for numbers with a fraction
+    /// or exponent, and [`IntConversionError::OutOfRange`] for integers
+    /// that do not fit in `T`.
+    pub fn try_into_int<T>(&self) -> Result<T, IntConversionError>
+    where
+        T: TryFrom<i64> + TryFrom<u64>,
+    {
+        let JsonValue::Number(n) = self else {
+            return Err(IntConversionError::NotANumber {
+                found: self.type_name(),
+            });
+        };
+        let converted = match n.repr {
+            NumberRepr::PosInt(u) => T::try_from(u).ok(),
+            NumberRepr::NegInt(i) => T::try_from(i).ok(),
+            // Integers beyond 64 bits are stored as floats; report such
+            // magnitudes as out of range rather than as non-integers.
+            NumberRepr::Float(f) if f.fract() == 0.0 && f.abs() >= 2f64.powi(63) => None,
+            NumberRepr::Float(_) => {
+                return Err(IntConversionError::NotAnInteger {
+                    value: n.to_string(),
+                });
+            }
+        };
+        converted.ok_or_else(|| IntConversionError::OutOfRange {
+            value: n.to_string(),
+            target: std::any::type_name::<T>(),
+        })
+    }
+
     /// Returns the boolean value if this is a `JsonValue::Boolean`.
     ///
     /// Returns `Some(bool)` for boolean values and `None` for all other
@@ -189,7 +358,7 @@ impl JsonValue {
 
     /// Returns a reference to the inner map if this is a `JsonValue::Object`.
     ///
-    /// Returns `Some(&HashMap<String, JsonValue>)` for object values and
+    /// Returns `Some(&JsonMap)` for object values and
     /// `None` for all other variants.
     ///
     /// # Examples
@@ -207,7 +376,51 @@ impl JsonValue {
     /// assert!(number.as_object().is_none());
     /// # Ok::<(), rust_json_parser::error::JsonError>(())
     /// ```
-    pub fn as_object(&self) -> Option<&HashMap<String, JsonValue>> {
+    pub fn as_object(&self) -> Option<&JsonMap> {
+        match self {
+            JsonValue::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the inner vector if this is a
+    /// `JsonValue::Array`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let mut value = parse_json("[3, 1, 2]")?;
+    /// if let Some(arr) = value.as_array_mut() {
+    ///     arr.retain(|v| v.as_i64() != Some(1));
+    /// }
+    /// assert_eq!(value.to_string(), "[3,2]");
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the inner map if this is a
+    /// `JsonValue::Object`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let mut value = parse_json(r#"{"a": 1, "b": 2}"#)?;
+    /// if let Some(obj) = value.as_object_mut() {
+    ///     obj.clear();
+    /// }
+    /// assert_eq!(value.to_string(), "{}");
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn as_object_mut(&mut self) -> Option<&mut JsonMap> {
         match self {
             JsonValue::Object(obj) => Some(obj),
             _ => None,
@@ -264,103 +477,1760 @@ impl JsonValue {
             _ => None,
         }
     }
-}
 
-/// Trait for converting a value into its JSON string representation.
-pub(crate) trait JsonFormat {
-    /// Returns the value serialized as a JSON string.
-    fn to_json_string(&self) -> String;
-}
+    /// Looks up a value by key for modification if this is a
+    /// `JsonValue::Object`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    /// use rust_json_parser::value::JsonValue;
+    ///
+    /// let mut value = parse_json(r#"{"name": "Alice"}"#)?;
+    /// if let Some(name) = value.get_mut("name") {
+    ///     *name = JsonValue::String("Bob".to_string());
+    /// }
+    /// assert_eq!(value.get("name").and_then(|v| v.as_str()), Some("Bob"));
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        match self {
+            JsonValue::Object(obj) => obj.get_mut(key),
+            _ => None,
+        }
+    }
 
-impl JsonFormat for f64 {
-    fn to_json_string(&self) -> String {
-        if self.fract() == 0.0 {
-            format!("{:.0}", self)
-        } else {
-            format!("{}", self)
+    /// Looks up a value by index for modification if this is a
+    /// `JsonValue::Array`.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut JsonValue> {
+        match self {
+            JsonValue::Array(arr) => arr.get_mut(index),
+            _ => None,
         }
     }
-}
 
-impl JsonFormat for String {
-    fn to_json_string(&self) -> String {
-        let mut result = String::with_capacity(self.len() + 2);
-        result.push('"');
-        // Scan bytes for escape chars, copy unescaped runs via push_str.
-        let bytes = self.as_bytes();
-        let mut seg_start = 0;
-        for i in 0..bytes.len() {
-            let escape = match bytes[i] {
-                b'"' => "\\\"",
-                b'\\' => "\\\\",
-                b'\n' => "\\n",
-                b'\r' => "\\r",
-                b'\t' => "\\t",
-                _ => continue,
-            };
-            if seg_start < i {
-                result.push_str(&self[seg_start..i]);
+    /// Inserts a member into an object, returning the previous value for
+    /// the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    /// use rust_json_parser::value::JsonValue;
+    ///
+    /// let mut value = parse_json(r#"{"a": 1}"#)?;
+    /// assert_eq!(value.insert("b", JsonValue::Boolean(true)), Ok(None));
+    /// assert_eq!(value.to_string(), r#"{"a":1,"b":true}"#);
+    ///
+    /// assert!(JsonValue::Null.insert("a", JsonValue::Null).is_err());
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessError::TypeMismatch`] if this is not an object.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: JsonValue,
+    ) -> Result<Option<JsonValue>, AccessError> {
+        match self {
+            JsonValue::Object(obj) => Ok(obj.insert(key.into(), value)),
+            other => Err(other.mismatch("object")),
+        }
+    }
+
+    /// Removes a member from an object and returns its value.
+    ///
+    /// Returns `None` if the key is missing or this is not an object.
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        match self {
+            JsonValue::Object(obj) => obj.remove(key),
+            _ => None,
+        }
+    }
+
+    /// Appends an element to an array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    /// use rust_json_parser::value::JsonValue;
+    ///
+    /// let mut value = parse_json("[1]")?;
+    /// value.push(JsonValue::Null)?;
+    /// value.insert_index(0, JsonValue::Boolean(false))?;
+    /// assert_eq!(value.to_string(), "[false,1,null]");
+    /// assert_eq!(value.pop(), Some(JsonValue::Null));
+    /// assert_eq!(value.remove_index(0), Some(JsonValue::Boolean(false)));
+    /// assert_eq!(value.to_string(), "[1]");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessError::TypeMismatch`] if this is not an array.
+    pub fn push(&mut self, value: JsonValue) -> Result<(), AccessError> {
+        match self {
+            JsonValue::Array(arr) => {
+                arr.push(value);
+                Ok(())
             }
-            result.push_str(escape);
-            seg_start = i + 1;
+            other => Err(other.mismatch("array")),
         }
-        if seg_start < self.len() {
-            result.push_str(&self[seg_start..]);
+    }
+
+    /// Removes and returns the last element of an array.
+    ///
+    /// Returns `None` if the array is empty or this is not an array.
+    pub fn pop(&mut self) -> Option<JsonValue> {
+        match self {
+            JsonValue::Array(arr) => arr.pop(),
+            _ => None,
         }
-        result.push('"');
-        result
+    }
+
+    /// Inserts an element into an array at `index`, shifting later
+    /// elements to the right.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessError::TypeMismatch`] if this is not an array, or
+    /// [`AccessError::Missing`] if `index` is greater than the length.
+    pub fn insert_index(&mut self, index: usize, value: JsonValue) -> Result<(), AccessError> {
+        match self {
+            JsonValue::Array(arr) if index <= arr.len() => {
+                arr.insert(index, value);
+                Ok(())
+            }
+            JsonValue::Array(_) => Err(AccessError::Missing {
+                path: JsonPath::root().join(index),
+            }),
+            other => Err(other.mismatch("array")),
+        }
+    }
+
+    /// Removes the element at `index` from an array, shifting later
+    /// elements to the left.
+    ///
+    /// Returns `None` if `index` is out of bounds or this is not an array.
+    pub fn remove_index(&mut self, index: usize) -> Option<JsonValue> {
+        match self {
+            JsonValue::Array(arr) if index < arr.len() => Some(arr.remove(index)),
+            _ => None,
+        }
+    }
+
+    fn mismatch(&self, expected: &'static str) -> AccessError {
+        AccessError::TypeMismatch {
+            path: JsonPath::root(),
+            expected,
+            found: self.type_name(),
+        }
+    }
+
+    /// Iterates over the keys of an object. Yields nothing if this is not
+    /// an object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json(r#"{"a": 1, "b": 2}"#)?;
+    /// assert_eq!(value.keys().count(), 2);
+    /// assert!(value.keys().any(|k| k == "a"));
+    /// assert_eq!(parse_json("[1]")?.keys().count(), 0);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.as_object().into_iter().flat_map(|map| map.keys())
+    }
+
+    /// Iterates over the values of an object. Yields nothing if this is
+    /// not an object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json(r#"{"a": 1, "b": 2}"#)?;
+    /// let sum: f64 = value.values().filter_map(|v| v.as_f64()).sum();
+    /// assert_eq!(sum, 3.0);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn values(&self) -> impl Iterator<Item = &JsonValue> {
+        self.as_object().into_iter().flat_map(|map| map.values())
+    }
+
+    /// Iterates over the members of an object as `(key, value)` pairs.
+    /// Yields nothing if this is not an object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json(r#"{"a": 1, "b": "x"}"#)?;
+    /// let numeric: Vec<&str> = value
+    ///     .entries()
+    ///     .filter(|(_, v)| v.as_f64().is_some())
+    ///     .map(|(k, _)| k.as_str())
+    ///     .collect();
+    /// assert_eq!(numeric, ["a"]);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &JsonValue)> {
+        self.as_object().into_iter().flat_map(|map| map.iter())
+    }
+
+    /// Iterates over the elements of an array. Yields nothing if this is
+    /// not an array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json("[1, 2, 3]")?;
+    /// assert_eq!(value.elements().filter_map(|v| v.as_i64()).max(), Some(3));
+    /// assert_eq!(parse_json("{}")?.elements().len(), 0);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn elements(&self) -> std::slice::Iter<'_, JsonValue> {
+        self.element_slice().iter()
+    }
+
+    /// Iterates over the direct children of an array or object as
+    /// [`Child`](crate::iter::Child)ren: elements with their index,
+    /// members with their key. Yields nothing for scalars.
+    ///
+    /// `&JsonValue` and `JsonValue` implement `IntoIterator` the same way,
+    /// so a value can be used directly in a `for` loop.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::iter::Child;
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json(r#"{"a": [true], "b": null}"#)?;
+    /// let segments: Vec<String> = value.iter().map(|c| c.segment().to_string()).collect();
+    /// assert_eq!(segments, ["a", "b"]);
+    ///
+    /// for child in value.get("a").unwrap() {
+    ///     assert!(matches!(child, Child::Element(0, v) if v.as_bool() == Some(true)));
+    /// }
+    /// let nulls = value.into_iter().filter(|c| c.value().is_null()).count();
+    /// assert_eq!(nulls, 1);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(self)
+    }
+
+    /// Iterates over an array's elements in non-overlapping chunks of `size`.
+    ///
+    /// The last chunk is shorter when the length is not a multiple of
+    /// `size`. Yields nothing if this is not an array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json("[1, 2, 3, 4, 5]")?;
+    /// let sizes: Vec<usize> = value.chunks(2).map(<[_]>::len).collect();
+    /// assert_eq!(sizes, [2, 2, 1]);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn chunks(&self, size: usize) -> std::slice::Chunks<'_, JsonValue> {
+        self.element_slice().chunks(size)
+    }
+
+    /// Iterates over all overlapping windows of `size` consecutive array
+    /// elements. Yields nothing if this is not an array or it has fewer
+    /// than `size` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json("[1, 2, 3]")?;
+    /// let sums: Vec<f64> = value
+    ///     .windows(2)
+    ///     .map(|w| w.iter().filter_map(|v| v.as_f64()).sum())
+    ///     .collect();
+    /// assert_eq!(sums, [3.0, 5.0]);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn windows(&self, size: usize) -> std::slice::Windows<'_, JsonValue> {
+        self.element_slice().windows(size)
+    }
+
+    fn element_slice(&self) -> &[JsonValue] {
+        match self {
+            JsonValue::Array(arr) => arr,
+            _ => &[],
+        }
+    }
+
+    /// Iterates over an object's members in ascending key order.
+    ///
+    /// Objects iterate in insertion order; use this where output must not
+    /// depend on how the document was built, such as hashing or comparing
+    /// documents from different sources. Yields nothing if this is not an
+    /// object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json(r#"{"b": 2, "c": 3, "a": 1}"#)?;
+    /// let keys: Vec<&str> = value.iter_sorted().map(|(k, _)| k.as_str()).collect();
+    /// assert_eq!(keys, ["a", "b", "c"]);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (&String, &JsonValue)> {
+        let mut entries: Vec<(&String, &JsonValue)> = match self {
+            JsonValue::Object(map) => map.iter().collect(),
+            _ => Vec::new(),
+        };
+        entries.sort_by_key(|(k, _)| *k);
+        entries.into_iter()
+    }
+
+    /// Reorders the members of this object, and of every object nested in
+    /// it, by ascending key.
+    ///
+    /// Serialization writes members in insertion order, which is
+    /// deterministic for one document but differs between documents that
+    /// list the same members in different orders. Sorting first gives
+    /// them identical output, so tests can compare exact strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let mut a = parse_json(r#"{"b": 1, "a": {"y": [{"d": 0, "c": 0}], "x": 2}}"#)?;
+    /// let mut b = parse_json(r#"{"a": {"x": 2, "y": [{"c": 0, "d": 0}]}, "b": 1}"#)?;
+    /// assert_ne!(a.to_string(), b.to_string());
+    /// a.sort_keys();
+    /// b.sort_keys();
+    /// assert_eq!(a.to_string(), r#"{"a":{"x":2,"y":[{"c":0,"d":0}]},"b":1}"#);
+    /// assert_eq!(a.to_string(), b.to_string());
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn sort_keys(&mut self) {
+        match self {
+            JsonValue::Array(elements) => elements.iter_mut().for_each(JsonValue::sort_keys),
+            JsonValue::Object(map) => {
+                map.sort_keys();
+                map.values_mut().for_each(JsonValue::sort_keys);
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the value at a JSON Pointer (RFC 6901).
+    ///
+    /// The empty pointer `""` refers to this value. `~1` and `~0` in a
+    /// reference token stand for `/` and `~`. Array indices must be
+    /// decimal without leading zeros; `-` never resolves.
+    ///
+    /// Returns `None` if the pointer is malformed or does not resolve.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let doc = parse_json(r#"{"address": {"city": "Oslo"}, "a/b": [10, 20], "m~n": 1}"#)?;
+    /// assert_eq!(doc.pointer("/address/city").and_then(|v| v.as_str()), Some("Oslo"));
+    /// assert_eq!(doc.pointer("/a~1b/1").and_then(|v| v.as_i64()), Some(20));
+    /// assert_eq!(doc.pointer("/m~0n").and_then(|v| v.as_i64()), Some(1));
+    /// assert_eq!(doc.pointer(""), Some(&doc));
+    /// assert!(doc.pointer("/a~1b/01").is_none());
+    /// assert!(doc.pointer("address").is_none());
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        parse_pointer(pointer)?
+            .iter()
+            .try_fold(self, |current, segment| match current {
+                JsonValue::Object(map) => map.get(segment),
+                JsonValue::Array(arr) => arr.get(parse_index(segment)?),
+                _ => None,
+            })
+    }
+
+    /// Returns a mutable reference to the value at a JSON Pointer.
+    ///
+    /// Resolves pointers exactly like [`pointer`](Self::pointer).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    /// use rust_json_parser::value::JsonValue;
+    ///
+    /// let mut doc = parse_json(r#"{"servers": [{"port": 80}]}"#)?;
+    /// if let Some(port) = doc.pointer_mut("/servers/0/port") {
+    ///     *port = JsonValue::Number(8080.0.into());
+    /// }
+    /// assert_eq!(doc.to_string(), r#"{"servers":[{"port":8080}]}"#);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        parse_pointer(pointer)?
+            .iter()
+            .try_fold(self, |current, segment| match current {
+                JsonValue::Object(map) => map.get_mut(segment),
+                JsonValue::Array(arr) => arr.get_mut(parse_index(segment)?),
+                _ => None,
+            })
+    }
+
+    /// Resolves several JSON Pointers at once, returning one result per
+    /// pointer in the same order.
+    ///
+    /// Each pointer resolves exactly like [`pointer`](Self::pointer).
+    /// Consecutive pointers that share a prefix, such as `/user/name` and
+    /// `/user/email`, walk that prefix only once, so listing related
+    /// pointers together is cheaper than resolving them one by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let req = parse_json(r#"{"user": {"id": 7, "name": "Ada"}, "tags": ["x"]}"#)?;
+    /// let found = req.get_many(&["/user/id", "/user/name", "/tags/0", "/user/email"]);
+    /// assert_eq!(found[0].and_then(|v| v.as_i64()), Some(7));
+    /// assert_eq!(found[1].and_then(|v| v.as_str()), Some("Ada"));
+    /// assert_eq!(found[2].and_then(|v| v.as_str()), Some("x"));
+    /// assert!(found[3].is_none());
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn get_many(&self, pointers: &[&str]) -> Vec<Option<&JsonValue>> {
+        // The segments resolved for the previous pointer, each with the
+        // value it led to.
+        let mut resolved: Vec<(String, &JsonValue)> = Vec::new();
+        pointers
+            .iter()
+            .map(|pointer| {
+                let segments = parse_pointer(pointer)?;
+                let shared = resolved
+                    .iter()
+                    .zip(&segments)
+                    .take_while(|((done, _), segment)| done == *segment)
+                    .count();
+                resolved.truncate(shared);
+                let mut current = resolved.last().map_or(self, |&(_, value)| value);
+                for segment in &segments[shared..] {
+                    current = match current {
+                        JsonValue::Object(map) => map.get(segment),
+                        JsonValue::Array(arr) => parse_index(segment).and_then(|i| arr.get(i)),
+                        _ => None,
+                    }?;
+                    resolved.push((segment.clone(), current));
+                }
+                Some(current)
+            })
+            .collect()
+    }
+
+    /// Removes the value at a JSON Pointer and returns it without cloning.
+    ///
+    /// An object member is removed from its parent. An array element is
+    /// replaced with `Null` so the indices of its siblings stay valid. The
+    /// empty pointer `""` takes the whole value, leaving `Null` behind.
+    ///
+    /// Returns `None` if the pointer is malformed or does not resolve.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    /// use rust_json_parser::value::JsonValue;
+    ///
+    /// let mut doc = parse_json(r#"{"meta": {"id": 1}, "rows": [10, 20]}"#)?;
+    /// let meta = doc.extract("/meta");
+    /// assert_eq!(meta.and_then(|m| m.get("id").cloned()), Some(JsonValue::Number(1.0.into())));
+    /// assert!(doc.get("meta").is_none());
+    ///
+    /// assert_eq!(doc.extract("/rows/0"), Some(JsonValue::Number(10.0.into())));
+    /// assert_eq!(doc.get("rows").and_then(|r| r.get_index(0)), Some(&JsonValue::Null));
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn extract(&mut self, pointer: &str) -> Option<JsonValue> {
+        let segments = parse_pointer(pointer)?;
+        let Some((last, parents)) = segments.split_last() else {
+            return Some(std::mem::replace(self, JsonValue::Null));
+        };
+        let mut current = self;
+        for segment in parents {
+            current = match current {
+                JsonValue::Object(map) => map.get_mut(segment)?,
+                JsonValue::Array(arr) => arr.get_mut(parse_index(segment)?)?,
+                _ => return None,
+            };
+        }
+        match current {
+            JsonValue::Object(map) => map.remove(last),
+            JsonValue::Array(arr) => arr
+                .get_mut(parse_index(last)?)
+                .map(|item| std::mem::replace(item, JsonValue::Null)),
+            _ => None,
+        }
+    }
+
+    /// Returns the value at `path`, or `None` if it does not resolve.
+    ///
+    /// An [`Index`](Segment::Index) segment applied to an object looks up
+    /// its decimal form as a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    /// use rust_json_parser::path::JsonPath;
+    ///
+    /// let doc = parse_json(r#"{"rows": [[1, 2]], "0": "zero"}"#)?;
+    /// let path = JsonPath::parse_dotted("rows.0.1");
+    /// assert_eq!(doc.at(&path).and_then(|v| v.as_i64()), Some(2));
+    /// assert_eq!(doc.at(&JsonPath::parse_dotted("0")).and_then(|v| v.as_str()), Some("zero"));
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn at(&self, path: &JsonPath) -> Option<&JsonValue> {
+        path.segments()
+            .iter()
+            .try_fold(self, |current, segment| current.child(segment))
+    }
+
+    pub(crate) fn child(&self, segment: &Segment) -> Option<&JsonValue> {
+        match (self, segment) {
+            (JsonValue::Object(map), Segment::Key(key)) => map.get(key),
+            (JsonValue::Object(map), Segment::Index(index)) => map.get(&index.to_string()),
+            (JsonValue::Array(arr), Segment::Index(index)) => arr.get(*index),
+            _ => None,
+        }
+    }
+
+    /// Serializes compactly like `to_string`, but stops once the output
+    /// would exceed `max_bytes` and ends it with `...` instead.
+    ///
+    /// Returns the text and whether anything was elided. The text never
+    /// exceeds `max_bytes`, marker included, and the cost is bounded by
+    /// the budget rather than the size of the value. Long strings are cut
+    /// mid-way; other tokens are kept whole or dropped. Truncated output
+    /// is not valid JSON: it is meant for logs and error reports.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json(r#"{"id": 12345, "body": "a very long payload"}"#)?;
+    /// assert_eq!(value.to_string_bounded(24), (r#"{"id":12345,"body":"a..."#.to_string(), true));
+    /// assert_eq!(value.to_string_bounded(14), (r#"{"id":12345..."#.to_string(), true));
+    /// assert_eq!(value.to_string_bounded(100), (value.to_string(), false));
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    pub fn to_string_bounded(&self, max_bytes: usize) -> (String, bool) {
+        let mut writer = BoundedWriter {
+            out: String::new(),
+            max_bytes,
+            safe: 0,
+        };
+        let complete = writer.value(self).is_some();
+        (writer.out, !complete)
+    }
+
+    /// Serializes the value in the canonical form of RFC 8785 (JCS), for
+    /// hashing or signing.
+    ///
+    /// Numbers are written from their `f64` value, so integers beyond
+    /// 2^53 and raw lexemes kept by
+    /// [`preserve_raw_numbers`](crate::parser::JsonParser::preserve_raw_numbers)
+    /// lose their exact digits, as the scheme requires.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json(r#"{"b": [1.50, 1e30, 0.000001], "a": "\u00e9\u000b"}"#)?;
+    /// assert_eq!(
+    ///     value.to_canonical_string().unwrap(),
+    ///     r#"{"a":"é\u000b","b":[1.5,1e+30,0.000001]}"#
+    /// );
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanonicalError`] if the value contains a NaN or infinite
+    /// number.
+    pub fn to_canonical_string(&self) -> Result<String, CanonicalError> {
+        let mut out = String::new();
+        write_canonical(&mut out, self, &mut JsonPath::root())?;
+        Ok(out)
+    }
+
+    /// Writes the value as compact JSON, exactly as `to_string` would
+    /// produce it, without building the text in memory first.
+    ///
+    /// Output is written in many small pieces, so wrap unbuffered writers
+    /// such as files and sockets in a [`BufWriter`](std::io::BufWriter).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json(r#"{"id": 7, "tags": ["a\nb"]}"#)?;
+    /// let mut out = Vec::new();
+    /// value.to_writer(&mut out)?;
+    /// assert_eq!(out, value.to_string().as_bytes());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn to_writer(&self, mut out: impl Write) -> io::Result<()> {
+        write_value(&mut out, self, None, 0)
+    }
+
+    /// Writes the value as indented JSON with two spaces per level, in the
+    /// layout of `{:#?}`, without building the text in memory first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json(r#"{"id": 7, "tags": []}"#)?;
+    /// let mut out = Vec::new();
+    /// value.to_writer_pretty(&mut out)?;
+    /// assert_eq!(out, b"{\n  \"id\": 7,\n  \"tags\": []\n}");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn to_writer_pretty(&self, mut out: impl Write) -> io::Result<()> {
+        write_value(&mut out, self, Some(2), 0)
+    }
+
+    /// Rewrites this value in place so that it serializes to valid JSON
+    /// within the limits set by `options`, returning the number of values
+    /// replaced, removed, or truncated.
+    ///
+    /// See [`SanitizeOptions`] for what is changed and an example.
+    pub fn sanitize(&mut self, options: &SanitizeOptions) -> usize {
+        options.apply(self, 0).0
+    }
+
+    /// Merges `other` into this value: objects are merged key by key at
+    /// every depth, arrays are combined according to `strategy`, and any
+    /// other value in `other` replaces the one here.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::json;
+    /// use rust_json_parser::merge::MergeStrategy;
+    ///
+    /// let mut config = json!({"port": 80, "log": {"level": "info", "sinks": ["stderr"]}});
+    /// let environment = json!({"log": {"sinks": ["file"]}});
+    /// let flags = json!({"port": 8080, "log": {"level": "debug"}});
+    ///
+    /// config.deep_merge(environment, MergeStrategy::Concat);
+    /// config.deep_merge(flags, MergeStrategy::Concat);
+    /// assert_eq!(
+    ///     config,
+    ///     json!({"port": 8080, "log": {"level": "debug", "sinks": ["stderr", "file"]}})
+    /// );
+    /// ```
+    pub fn deep_merge(&mut self, other: JsonValue, strategy: MergeStrategy) {
+        strategy.merge(self, other);
+    }
+
+    /// Prepends `prefix` to every object key, at any depth.
+    ///
+    /// See [`namespace::combine`] for gathering several prefixed documents
+    /// into one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::json;
+    ///
+    /// let mut value = json!({"db": {"host": "localhost"}, "replicas": [{"host": "r1"}]});
+    /// value.prefix_keys("app.");
+    /// assert_eq!(
+    ///     value.to_string(),
+    ///     r#"{"app.db":{"app.host":"localhost"},"app.replicas":[{"app.host":"r1"}]}"#
+    /// );
+    /// ```
+    pub fn prefix_keys(&mut self, prefix: &str) {
+        namespace::prefix_keys(self, prefix);
+    }
+
+    /// Removes `prefix` from every object key that starts with it, at any
+    /// depth. Keys without the prefix are left as they are.
+    ///
+    /// To split one namespace back out of a combined object, drop the
+    /// other namespaces' members first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::json;
+    ///
+    /// let mut config = json!({"app.port": 8080, "db.port": 5432, "app.tls": {"app.cert": "a.pem"}});
+    /// config
+    ///     .as_object_mut()
+    ///     .unwrap()
+    ///     .retain(|key, _| key.starts_with("app."));
+    /// config.strip_key_prefix("app.").unwrap();
+    /// assert_eq!(config.to_string(), r#"{"port":8080,"tls":{"cert":"a.pem"}}"#);
+    ///
+    /// let mut clash = json!({"app.port": 1, "port": 2});
+    /// assert!(clash.strip_key_prefix("app.").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NamespaceError`] if stripping would give two members of
+    /// one object the same key. The value is left unchanged.
+    pub fn strip_key_prefix(&mut self, prefix: &str) -> Result<(), NamespaceError> {
+        namespace::strip_key_prefix(self, prefix)
+    }
+
+    /// Encodes this value as MessagePack, using the smallest encoding of
+    /// each number, string, array, and map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::json;
+    ///
+    /// let bytes = json!({"id": 7, "ok": true}).to_msgpack();
+    /// assert_eq!(bytes, [0x82, 0xa2, b'i', b'd', 0x07, 0xa2, b'o', b'k', 0xc3]);
+    /// ```
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        msgpack::encode(self, &mut out);
+        out
+    }
+
+    /// Decodes one MessagePack value, reporting anything JSON cannot hold
+    /// exactly.
+    ///
+    /// See [`msgpack`] for how each type is read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::json;
+    /// use rust_json_parser::value::JsonValue;
+    ///
+    /// // {"blob": bin [1, 2, 3]}
+    /// let bytes = [0x81, 0xa4, b'b', b'l', b'o', b'b', 0xc4, 0x03, 1, 2, 3];
+    /// let (value, report) = JsonValue::from_msgpack(&bytes)?;
+    /// assert_eq!(value, json!({"blob": "AQID"}));
+    /// assert_eq!(report.to_string(), "\"/blob\": approximated (binary data as base64)");
+    /// # Ok::<(), rust_json_parser::msgpack::MsgpackError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MsgpackError`] if `bytes` is not exactly one well-formed
+    /// MessagePack value, or nests deeper than
+    /// [`DEFAULT_MAX_DEPTH`](crate::parser::DEFAULT_MAX_DEPTH).
+    #[cfg(feature = "msgpack")]
+    pub fn from_msgpack(bytes: &[u8]) -> Result<(JsonValue, ConversionReport), MsgpackError> {
+        msgpack::decode(bytes)
+    }
+
+    /// Freezes this value into a read-only, cheaply cloneable
+    /// [`FrozenJson`].
+    pub fn freeze(self) -> FrozenJson {
+        FrozenJson::new(self)
+    }
+
+    /// Returns the name of this value's JSON type, as used in error
+    /// messages.
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Boolean(_) => "boolean",
+            JsonValue::Number(_) => "number",
+            JsonValue::String(_) => "string",
+            JsonValue::Array(_) => "array",
+            JsonValue::Object(_) => "object",
+        }
+    }
+
+    /// Looks up `key` and converts the value to `T` in one step.
+    ///
+    /// `T` is any [`FromJson`] type, including your own. Errors inside the
+    /// value carry paths that start at `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json(r#"{"age": 30, "name": "Alice"}"#)?;
+    /// assert_eq!(value.get_as::<i64>("age"), Ok(30));
+    /// assert!(value.get_as::<i64>("name").is_err());
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessError::Missing`] if the key does not exist (or this
+    /// is not an object) and [`AccessError::TypeMismatch`] if the value
+    /// cannot be converted to `T`.
+    pub fn get_as<T: FromJson>(&self, key: &str) -> Result<T, AccessError> {
+        match self.get(key) {
+            Some(value) => T::from_json(value).map_err(|e| e.within(key)),
+            None => Err(AccessError::Missing {
+                path: JsonPath::root().join(key),
+            }),
+        }
+    }
+
+    /// Follows a dot-separated path and converts the value to `T`.
+    ///
+    /// Each segment is an object key, or an index when the current value
+    /// is an array: `"users.0.name"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_json_parser::parser::parse_json;
+    ///
+    /// let value = parse_json(r#"{"user": {"name": "Alice", "tags": ["admin"]}}"#)?;
+    /// assert_eq!(value.get_path_as::<String>("user.name"), Ok("Alice".to_string()));
+    /// assert_eq!(value.get_path_as::<String>("user.tags.0"), Ok("admin".to_string()));
+    ///
+    /// let err = value.get_path_as::<bool>("user.name").unwrap_err();
+    /// assert_eq!(err.to_string(), r#"Expected boolean at "user.name", found string"#);
+    /// # Ok::<(), rust_json_parser::error::JsonError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessError::Missing`] with the path up to the first
+    /// segment that could not be followed, or
+    /// [`AccessError::TypeMismatch`] if the final value cannot be converted
+    /// to `T`.
+    pub fn get_path_as<T: FromJson>(&self, path: &str) -> Result<T, AccessError> {
+        let path = JsonPath::parse_dotted(path);
+        let mut current = self;
+        for (depth, segment) in path.segments().iter().enumerate() {
+            current = current.child(segment).ok_or_else(|| AccessError::Missing {
+                path: path.segments()[..=depth].iter().cloned().collect(),
+            })?;
+        }
+        T::from_json(current).map_err(|e| {
+            path.segments()
+                .iter()
+                .rev()
+                .fold(e, |e, segment| e.within(segment.clone()))
+        })
+    }
+}
+
+/// A JSON number.
+///
+/// Integers without a fraction or exponent are stored exactly as `i64`
+/// (negative) or `u64` (non-negative), so large identifiers such as
+/// `9007199254740993` survive a round trip. Everything else, and integers
+/// too large for 64 bits, is stored as `f64`.
+///
+/// When raw preservation is enabled on the parser (see
+/// [`JsonParser::preserve_raw_numbers`]), the exact source lexeme is kept
+/// as well, and `Display` reproduces it verbatim so that re-serialization
+/// emits the original digits (`1.50`, `1e3`).
+///
+/// Equality compares numeric values across representations (`42` equals
+/// `42.0`); the raw lexeme is ignored.
+///
+/// [`JsonParser::preserve_raw_numbers`]: crate::parser::JsonParser::preserve_raw_numbers
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::{JsonParser, parse_json};
+/// use rust_json_parser::value::JsonValue;
+///
+/// let value = parse_json("[9007199254740993, -7, 1.5]")?;
+/// assert_eq!(value.get_index(0).and_then(|v| v.as_u64()), Some(9007199254740993));
+/// assert_eq!(value.get_index(1).and_then(|v| v.as_i64()), Some(-7));
+/// assert_eq!(value.get_index(2).and_then(|v| v.as_i64()), None);
+///
+/// let mut parser = JsonParser::new().preserve_raw_numbers(true);
+/// let value = parser.parse("[1.50, 12345678901234567890123]")?;
+/// assert_eq!(value.to_string(), "[1.50,12345678901234567890123]");
+/// if let Some(JsonValue::Number(n)) = value.get_index(0) {
+///     assert_eq!(n.as_raw_str(), Some("1.50"));
+///     assert_eq!(n.as_f64(), 1.5);
+/// }
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+#[derive(Clone)]
+pub struct JsonNumber {
+    repr: NumberRepr,
+    raw: Option<Box<str>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NumberRepr {
+    PosInt(u64),
+    NegInt(i64),
+    Float(f64),
+}
+
+impl JsonNumber {
+    /// Creates a floating point number with no raw lexeme.
+    pub fn new(value: f64) -> Self {
+        Self {
+            repr: NumberRepr::Float(value),
+            raw: None,
+        }
+    }
+
+    /// Parses a lexeme already validated as a JSON number, picking the
+    /// exact integer representation where possible.
+    pub(crate) fn from_lexeme(lexeme: &str, value: f64) -> Self {
+        let integral = !lexeme.contains(['.', 'e', 'E']);
+        let repr = if !integral || lexeme == "-0" {
+            NumberRepr::Float(value)
+        } else if lexeme.starts_with('-') {
+            lexeme
+                .parse()
+                .map_or(NumberRepr::Float(value), NumberRepr::NegInt)
+        } else {
+            lexeme
+                .parse()
+                .map_or(NumberRepr::Float(value), NumberRepr::PosInt)
+        };
+        Self { repr, raw: None }
+    }
+
+    pub(crate) fn with_raw(mut self, raw: &str) -> Self {
+        self.raw = Some(raw.into());
+        self
+    }
+
+    /// Returns the same number with any raw lexeme dropped.
+    #[cfg(feature = "test-support")]
+    pub(crate) fn without_raw(&self) -> Self {
+        Self {
+            repr: self.repr,
+            raw: None,
+        }
+    }
+
+    /// Returns the value as `f64`, rounding integers that exceed 2^53.
+    pub fn as_f64(&self) -> f64 {
+        match self.repr {
+            NumberRepr::PosInt(u) => u as f64,
+            NumberRepr::NegInt(i) => i as f64,
+            NumberRepr::Float(f) => f,
+        }
+    }
+
+    /// Returns the value if it is an integer that fits in `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.repr {
+            NumberRepr::PosInt(u) => i64::try_from(u).ok(),
+            NumberRepr::NegInt(i) => Some(i),
+            NumberRepr::Float(_) => None,
+        }
+    }
+
+    /// Returns the value if it is a non-negative integer that fits in
+    /// `u64`.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.repr {
+            NumberRepr::PosInt(u) => Some(u),
+            NumberRepr::NegInt(_) | NumberRepr::Float(_) => None,
+        }
+    }
+
+    /// Returns `true` if the number is stored as an `i64`-compatible
+    /// integer.
+    pub fn is_i64(&self) -> bool {
+        self.as_i64().is_some()
+    }
+
+    /// Returns `true` if the number is stored as a `u64`-compatible
+    /// integer.
+    pub fn is_u64(&self) -> bool {
+        self.as_u64().is_some()
+    }
+
+    /// Returns `true` if the number is stored as a floating point value.
+    pub fn is_f64(&self) -> bool {
+        matches!(self.repr, NumberRepr::Float(_))
+    }
+
+    /// Returns the exact source text of the number, if it was parsed with
+    /// raw preservation enabled.
+    pub fn as_raw_str(&self) -> Option<&str> {
+        self.raw.as_deref()
+    }
+
+    #[cfg(feature = "zeroize")]
+    pub(crate) fn zeroize(&mut self) {
+        use zeroize::Zeroize;
+        match &mut self.repr {
+            NumberRepr::PosInt(u) => u.zeroize(),
+            NumberRepr::NegInt(i) => i.zeroize(),
+            NumberRepr::Float(f) => f.zeroize(),
+        }
+        if let Some(raw) = self.raw.as_mut() {
+            raw.zeroize();
+        }
+        self.raw = None;
+    }
+}
+
+impl From<f64> for JsonNumber {
+    fn from(value: f64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<i64> for JsonNumber {
+    fn from(value: i64) -> Self {
+        let repr = match u64::try_from(value) {
+            Ok(u) => NumberRepr::PosInt(u),
+            Err(_) => NumberRepr::NegInt(value),
+        };
+        Self { repr, raw: None }
+    }
+}
+
+impl From<u64> for JsonNumber {
+    fn from(value: u64) -> Self {
+        Self {
+            repr: NumberRepr::PosInt(value),
+            raw: None,
+        }
+    }
+}
+
+impl NumberRepr {
+    fn as_i128(self) -> Option<i128> {
+        match self {
+            NumberRepr::PosInt(u) => Some(u.into()),
+            NumberRepr::NegInt(i) => Some(i.into()),
+            NumberRepr::Float(_) => None,
+        }
+    }
+}
+
+impl PartialEq for JsonNumber {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.repr, other.repr) {
+            (NumberRepr::Float(a), NumberRepr::Float(b)) => a == b,
+            (NumberRepr::Float(f), int) | (int, NumberRepr::Float(f)) => {
+                // `as` saturates, but an integral f64 within i128 range
+                // converts exactly.
+                f.fract() == 0.0 && Some(f as i128) == int.as_i128()
+            }
+            (a, b) => a.as_i128() == b.as_i128(),
+        }
+    }
+}
+
+impl PartialEq<f64> for JsonNumber {
+    fn eq(&self, other: &f64) -> bool {
+        *self == JsonNumber::new(*other)
+    }
+}
+
+impl fmt::Debug for JsonNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.repr {
+            NumberRepr::PosInt(u) => fmt::Debug::fmt(&u, f),
+            NumberRepr::NegInt(i) => fmt::Debug::fmt(&i, f),
+            NumberRepr::Float(v) => fmt::Debug::fmt(&v, f),
+        }
+    }
+}
+
+impl fmt::Display for JsonNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_json_string())
+    }
+}
+
+/// Trait for converting a value into its JSON string representation.
+pub(crate) trait JsonFormat {
+    /// Returns the value serialized as a JSON string.
+    fn to_json_string(&self) -> String;
+}
+
+impl JsonFormat for f64 {
+    fn to_json_string(&self) -> String {
+        if self.fract() == 0.0 {
+            format!("{:.0}", self)
+        } else {
+            format!("{}", self)
+        }
+    }
+}
+
+impl JsonFormat for JsonNumber {
+    fn to_json_string(&self) -> String {
+        if let Some(raw) = &self.raw {
+            return raw.to_string();
+        }
+        match self.repr {
+            NumberRepr::PosInt(u) => u.to_string(),
+            NumberRepr::NegInt(i) => i.to_string(),
+            NumberRepr::Float(f) => f.to_json_string(),
+        }
+    }
+}
+
+impl JsonFormat for String {
+    fn to_json_string(&self) -> String {
+        let mut result = String::with_capacity(self.len() + 2);
+        let _ = write_escaped::<()>(self, |piece| {
+            result.push_str(piece);
+            Ok(())
+        });
+        result
+    }
+}
+
+// Passes the quoted, escaped form of `s` to `emit` in pieces: the quotes,
+// unescaped runs, and escape sequences.
+fn write_escaped<E>(s: &str, mut emit: impl FnMut(&str) -> Result<(), E>) -> Result<(), E> {
+    emit("\"")?;
+    // Scan bytes for escape chars, emit unescaped runs whole.
+    let bytes = s.as_bytes();
+    let mut seg_start = 0;
+    for i in 0..bytes.len() {
+        let unicode;
+        let escape = match bytes[i] {
+            b'"' => "\\\"",
+            b'\\' => "\\\\",
+            b'\n' => "\\n",
+            b'\r' => "\\r",
+            b'\t' => "\\t",
+            0x08 => "\\b",
+            0x0c => "\\f",
+            // Other control characters must be escaped as `\u00XX`.
+            b @ 0..=0x1f => {
+                unicode = format!("\\u{:04x}", b);
+                &unicode
+            }
+            _ => continue,
+        };
+        if seg_start < i {
+            emit(&s[seg_start..i])?;
+        }
+        emit(escape)?;
+        seg_start = i + 1;
+    }
+    if seg_start < s.len() {
+        emit(&s[seg_start..])?;
+    }
+    emit("\"")
+}
+
+// Streams `value` to `out`, compact when `indent` is `None` and otherwise
+// in the layout of `pretty_print`.
+fn write_value<W: Write>(
+    out: &mut W,
+    value: &JsonValue,
+    indent: Option<usize>,
+    depth: usize,
+) -> io::Result<()> {
+    let newline = |out: &mut W, depth: usize| -> io::Result<()> {
+        if let Some(indent) = indent {
+            out.write_all(b"\n")?;
+            for _ in 0..indent * depth {
+                out.write_all(b" ")?;
+            }
+        }
+        Ok(())
+    };
+    let separator = if indent.is_some() { ": " } else { ":" };
+    match value {
+        JsonValue::Null => out.write_all(b"null"),
+        JsonValue::Boolean(b) => write!(out, "{}", b),
+        JsonValue::Number(n) => out.write_all(n.to_json_string().as_bytes()),
+        JsonValue::String(s) => write_escaped(s, |piece| out.write_all(piece.as_bytes())),
+        JsonValue::Array(arr) if arr.is_empty() => out.write_all(b"[]"),
+        JsonValue::Object(map) if map.is_empty() => out.write_all(b"{}"),
+        JsonValue::Array(arr) => {
+            out.write_all(b"[")?;
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.write_all(b",")?;
+                }
+                newline(out, depth + 1)?;
+                write_value(out, item, indent, depth + 1)?;
+            }
+            newline(out, depth)?;
+            out.write_all(b"]")
+        }
+        JsonValue::Object(map) => {
+            out.write_all(b"{")?;
+            for (i, (key, val)) in map.iter().enumerate() {
+                if i > 0 {
+                    out.write_all(b",")?;
+                }
+                newline(out, depth + 1)?;
+                write_escaped(key, |piece| out.write_all(piece.as_bytes()))?;
+                out.write_all(separator.as_bytes())?;
+                write_value(out, val, indent, depth + 1)?;
+            }
+            newline(out, depth)?;
+            out.write_all(b"}")
+        }
+    }
+}
+
+impl JsonFormat for [JsonValue] {
+    fn to_json_string(&self) -> String {
+        // TODO: estimate, ~8 bytes per element (~6 value + comma + space)
+        let mut result = String::with_capacity(self.len() * 8);
+        result.push('[');
+        for (i, item) in self.iter().enumerate() {
+            if i > 0 {
+                result.push(',');
+            }
+            result.push_str(&item.to_string());
+        }
+        result.push(']');
+        result
+    }
+}
+
+impl JsonFormat for JsonMap {
+    fn to_json_string(&self) -> String {
+        // TODO: estimate, ~16 bytes per entry (~6 key + 2 quotes + colon + ~6 value + comma)
+        let mut result = String::with_capacity(self.len() * 16);
+        result.push('{');
+        let mut first = true;
+        for (key, value) in self {
+            if !first {
+                result.push(',');
+            }
+            first = false;
+            result.push_str(&key.to_json_string());
+            result.push(':');
+            result.push_str(&value.to_string());
+        }
+        result.push('}');
+        result
+    }
+}
+
+/// Marks where [`JsonValue::to_string_bounded`] elided content.
+const ELISION: &str = "...";
+
+// Compact serializer for `to_string_bounded`. Each method returns `None`
+// once the budget is exhausted and the marker has been written.
+struct BoundedWriter {
+    out: String,
+    max_bytes: usize,
+    // Longest prefix of `out`, at a token boundary, that leaves room for
+    // the marker.
+    safe: usize,
+}
+
+impl BoundedWriter {
+    fn value(&mut self, value: &JsonValue) -> Option<()> {
+        match value {
+            JsonValue::Array(arr) => {
+                self.push("[", false)?;
+                for (i, item) in arr.iter().enumerate() {
+                    if i > 0 {
+                        self.push(",", false)?;
+                    }
+                    self.value(item)?;
+                }
+                self.push("]", false)
+            }
+            JsonValue::Object(map) => {
+                self.push("{", false)?;
+                for (i, (key, val)) in map.iter().enumerate() {
+                    if i > 0 {
+                        self.push(",", false)?;
+                    }
+                    self.push(&key.to_json_string(), true)?;
+                    self.push(":", false)?;
+                    self.value(val)?;
+                }
+                self.push("}", false)
+            }
+            JsonValue::String(s) => self.push(&s.to_json_string(), true),
+            _ => self.push(&value.to_string(), false),
+        }
+    }
+
+    // Appends `piece`, or, if it does not fit, as much as fits (when
+    // `splittable`) followed by the marker.
+    fn push(&mut self, piece: &str, splittable: bool) -> Option<()> {
+        let room = self.max_bytes.saturating_sub(ELISION.len());
+        if self.out.len() + piece.len() <= self.max_bytes {
+            self.out.push_str(piece);
+            if self.out.len() <= room {
+                self.safe = self.out.len();
+            }
+            return Some(());
+        }
+        if self.out.len() > room {
+            self.out.truncate(self.safe);
+        } else if splittable {
+            let mut cut = room - self.out.len();
+            while !piece.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            let mut prefix = &piece[..cut];
+            // Don't leave half of an escape sequence.
+            if let Some(start) = open_escape(prefix) {
+                prefix = &prefix[..start];
+            }
+            self.out.push_str(prefix);
+        }
+        if ELISION.len() <= self.max_bytes {
+            self.out.push_str(ELISION);
+        }
+        None
+    }
+}
+
+// Returns where an escape sequence cut off by the end of `prefix` starts,
+// if there is one.
+fn open_escape(prefix: &str) -> Option<usize> {
+    let bytes = prefix.as_bytes();
+    // Only the last six bytes can hold an incomplete `\uXXXX`.
+    let start = (bytes.len().saturating_sub(6)..bytes.len())
+        .rev()
+        .find(|&i| bytes[i] == b'\\')?;
+    // The backslash starts an escape only if it is not itself escaped.
+    let run = bytes[..=start]
+        .iter()
+        .rev()
+        .take_while(|&&b| b == b'\\')
+        .count();
+    if run % 2 == 0 {
+        return None;
+    }
+    let len = if bytes.get(start + 1) == Some(&b'u') {
+        6
+    } else {
+        2
+    };
+    (bytes.len() - start < len).then_some(start)
+}
+
+/// Recursively format a JsonValue with indentation.
+///
+/// Primitives reuse Display. Array and Object need custom handling
+/// for indentation. Object keys keep their insertion order.
+pub(crate) fn pretty_print(value: &JsonValue, indent_size: usize, depth: usize) -> String {
+    match value {
+        JsonValue::Array(arr) => {
+            if arr.is_empty() {
+                return "[]".to_string();
+            }
+            let inner_indent = " ".repeat(indent_size * (depth + 1));
+            let outer_indent = " ".repeat(indent_size * depth);
+            // TODO: estimate, +8 per element (~6 value + comma + newline), +4 for "[\n" and "\n]"
+            let mut result = String::with_capacity(
+                arr.len() * (inner_indent.len() + 8) + outer_indent.len() + 4,
+            );
+            result.push_str("[\n");
+            for (i, item) in arr.iter().enumerate() {
+                result.push_str(&inner_indent);
+                result.push_str(&pretty_print(item, indent_size, depth + 1));
+                if i < arr.len() - 1 {
+                    result.push(',');
+                }
+                result.push('\n');
+            }
+            result.push_str(&outer_indent);
+            result.push(']');
+            result
+        }
+        JsonValue::Object(map) => {
+            if map.is_empty() {
+                return "{}".to_string();
+            }
+            let inner_indent = " ".repeat(indent_size * (depth + 1));
+            let outer_indent = " ".repeat(indent_size * depth);
+            // TODO: estimate, +20 per entry (~6 key + 2 ": " + ~8 value + comma + newline), +4 for "{\n" and "\n}"
+            let mut result = String::with_capacity(
+                map.len() * (inner_indent.len() + 20) + outer_indent.len() + 4,
+            );
+            result.push_str("{\n");
+            for (i, (key, val)) in map.iter().enumerate() {
+                result.push_str(&inner_indent);
+                result.push_str(&key.to_json_string());
+                result.push_str(": ");
+                result.push_str(&pretty_print(val, indent_size, depth + 1));
+                if i < map.len() - 1 {
+                    result.push(',');
+                }
+                result.push('\n');
+            }
+            result.push_str(&outer_indent);
+            result.push('}');
+            result
+        }
+        // Null, Boolean, Number, String: reuse Display
+        _ => value.to_string(),
+    }
+}
+
+/// The default form (`{:?}`) shows the enum structure, e.g.
+/// `Array([Number(1)])`. The alternate form (`{:#?}`) prints the value
+/// as indented JSON, which stays readable for large documents.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::parse_json;
+///
+/// let value = parse_json(r#"{"a": [1, true]}"#)?;
+/// assert_eq!(format!("{:?}", value), r#"Object({"a": Array([Number(1), Boolean(true)])})"#);
+/// assert_eq!(format!("{:#?}", value), "{\n  \"a\": [\n    1,\n    true\n  ]\n}");
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+impl fmt::Debug for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return f.write_str(&pretty_print(self, 2, 0));
+        }
+        match self {
+            JsonValue::Null => f.write_str("Null"),
+            JsonValue::Boolean(b) => f.debug_tuple("Boolean").field(b).finish(),
+            JsonValue::Number(n) => f.debug_tuple("Number").field(n).finish(),
+            JsonValue::String(s) => f.debug_tuple("String").field(s).finish(),
+            JsonValue::Array(arr) => f.debug_tuple("Array").field(arr).finish(),
+            JsonValue::Object(map) => f.debug_tuple("Object").field(map).finish(),
+        }
+    }
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Boolean(b) => write!(f, "{}", b),
+            JsonValue::Number(n) => write!(f, "{}", n.to_json_string()),
+            JsonValue::String(s) => write!(f, "{}", s.to_json_string()),
+            JsonValue::Array(arr) => write!(f, "{}", arr.to_json_string()),
+            JsonValue::Object(map) => write!(f, "{}", map.to_json_string()),
+        }
+    }
+}
+
+// Returned by `Index` for lookups that find nothing.
+static NULL: JsonValue = JsonValue::Null;
+
+/// Looks up an object member, giving `null` if this is not an object or
+/// the key is absent, so lookups can be chained without unwrapping.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::parser::parse_json;
+///
+/// let doc = parse_json(r#"{"items": [{"id": 7}]}"#)?;
+/// assert_eq!(doc["items"][0]["id"].as_i64(), Some(7));
+/// assert!(doc["items"][5]["id"].is_null());
+/// assert!(doc["missing"]["id"].is_null());
+/// # Ok::<(), rust_json_parser::error::JsonError>(())
+/// ```
+impl Index<&str> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, key: &str) -> &JsonValue {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+/// Looks up an array element, giving `null` if this is not an array or
+/// the index is out of bounds.
+impl Index<usize> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, index: usize) -> &JsonValue {
+        self.get_index(index).unwrap_or(&NULL)
+    }
+}
+
+/// Returns a mutable reference to an object member, inserting `null` if
+/// the key is absent. `null` itself is first replaced by an empty object,
+/// so nested members can be assigned in one expression.
+///
+/// # Panics
+///
+/// Panics if this is neither an object nor `null`.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::value::JsonValue;
+///
+/// let mut doc = JsonValue::Null;
+/// doc["user"]["name"] = "Alice".into();
+/// doc["user"]["tags"] = vec!["a"].into();
+/// doc["user"]["tags"][0] = "b".into();
+/// assert_eq!(doc.to_string(), r#"{"user":{"name":"Alice","tags":["b"]}}"#);
+/// ```
+impl IndexMut<&str> for JsonValue {
+    fn index_mut(&mut self, key: &str) -> &mut JsonValue {
+        if self.is_null() {
+            *self = JsonValue::Object(JsonMap::new());
+        }
+        match self {
+            JsonValue::Object(map) => {
+                if !map.contains_key(key) {
+                    map.insert(key.to_string(), JsonValue::Null);
+                }
+                map.get_mut(key).expect("member was just inserted")
+            }
+            other => panic!("cannot index {} with {:?}", other.type_name(), key),
+        }
+    }
+}
+
+/// Returns a mutable reference to an array element.
+///
+/// # Panics
+///
+/// Panics if this is not an array or the index is out of bounds.
+impl IndexMut<usize> for JsonValue {
+    fn index_mut(&mut self, index: usize) -> &mut JsonValue {
+        match self {
+            JsonValue::Array(elements) => {
+                let len = elements.len();
+                elements.get_mut(index).unwrap_or_else(|| {
+                    panic!("index {} out of bounds for array of length {}", index, len)
+                })
+            }
+            other => panic!("cannot index {} with {}", other.type_name(), index),
+        }
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Boolean(value)
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> Self {
+        JsonValue::Number(value.into())
+    }
+}
+
+impl From<f32> for JsonValue {
+    fn from(value: f32) -> Self {
+        JsonValue::Number(f64::from(value).into())
+    }
+}
+
+impl From<JsonNumber> for JsonValue {
+    fn from(value: JsonNumber) -> Self {
+        JsonValue::Number(value)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Vec<T>> for JsonValue {
+    fn from(value: Vec<T>) -> Self {
+        JsonValue::Array(value.into_iter().map(Into::into).collect())
     }
 }
 
-impl JsonFormat for [JsonValue] {
-    fn to_json_string(&self) -> String {
-        // TODO: estimate, ~8 bytes per element (~6 value + comma + space)
-        let mut result = String::with_capacity(self.len() * 8);
-        result.push('[');
-        for (i, item) in self.iter().enumerate() {
-            if i > 0 {
-                result.push(',');
-            }
-            result.push_str(&item.to_string());
-        }
-        result.push(']');
-        result
+impl From<JsonMap> for JsonValue {
+    fn from(value: JsonMap) -> Self {
+        JsonValue::Object(value)
     }
 }
 
-impl JsonFormat for HashMap<String, JsonValue> {
-    fn to_json_string(&self) -> String {
-        // TODO: estimate, ~16 bytes per entry (~6 key + 2 quotes + colon + ~6 value + comma)
-        let mut result = String::with_capacity(self.len() * 16);
-        result.push('{');
-        let mut first = true;
-        for (key, value) in self {
-            if !first {
-                result.push(',');
-            }
-            first = false;
-            result.push_str(&key.to_json_string());
-            result.push(':');
-            result.push_str(&value.to_string());
-        }
-        result.push('}');
-        result
+/// Members are inserted in key order, so the result does not depend on
+/// the map's iteration order.
+impl<T: Into<JsonValue>, S> From<HashMap<String, T, S>> for JsonValue {
+    fn from(value: HashMap<String, T, S>) -> Self {
+        let mut members: Vec<(String, T)> = value.into_iter().collect();
+        members.sort_by(|a, b| a.0.cmp(&b.0));
+        JsonValue::Object(members.into_iter().map(|(k, v)| (k, v.into())).collect())
     }
 }
 
-impl fmt::Display for JsonValue {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            JsonValue::Null => write!(f, "null"),
-            JsonValue::Boolean(b) => write!(f, "{}", b),
-            JsonValue::Number(n) => write!(f, "{}", n.to_json_string()),
-            JsonValue::String(s) => write!(f, "{}", s.to_json_string()),
-            JsonValue::Array(arr) => write!(f, "{}", arr.to_json_string()),
-            JsonValue::Object(map) => write!(f, "{}", map.to_json_string()),
-        }
+impl<T: Into<JsonValue>> From<BTreeMap<String, T>> for JsonValue {
+    fn from(value: BTreeMap<String, T>) -> Self {
+        JsonValue::Object(value.into_iter().map(|(k, v)| (k, v.into())).collect())
+    }
+}
+
+/// `None` converts to `null`.
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(JsonValue::Null, Into::into)
     }
 }
 
+macro_rules! impl_from_int_for_json_value {
+    ($wide:ty => $($t:ty),*) => {
+        $(
+            impl From<$t> for JsonValue {
+                fn from(value: $t) -> Self {
+                    JsonValue::Number(JsonNumber::from(value as $wide))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int_for_json_value!(i64 => i8, i16, i32, i64, isize);
+impl_from_int_for_json_value!(u64 => u8, u16, u32, u64, usize);
+
+/// Builds a [`JsonValue`] from JSON-like syntax.
+///
+/// `null`, arrays, and objects are written as in JSON. Any other Rust
+/// expression is converted with `JsonValue::from`, so variables, numbers,
+/// strings, `Option`s, and previously built values can be interpolated.
+/// Object keys may be any expression convertible into `String`; keys
+/// that are not a single literal or identifier must be parenthesized.
+/// Object entries keep the order they are written in.
+///
+/// # Examples
+///
+/// ```
+/// use rust_json_parser::json;
+///
+/// let name = "Alice";
+/// let value = json!({
+///     "name": name,
+///     "age": 30,
+///     "tags": [1, 2.5, -3],
+///     "address": { "city": null },
+/// });
+/// assert_eq!(
+///     value.to_string(),
+///     r#"{"name":"Alice","age":30,"tags":[1,2.5,-3],"address":{"city":null}}"#
+/// );
+/// ```
+#[macro_export]
+macro_rules! json {
+    // Arrays: accumulate converted elements, then build the vector.
+    (@array [$($elems:expr,)*]) => {
+        vec![$($elems,)*]
+    };
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        $crate::json!(@array [$($elems,)* $crate::json!(null),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json!(@array [$($elems,)* $crate::json!([$($array)*]),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] {$($object:tt)*} $($rest:tt)*) => {
+        $crate::json!(@array [$($elems,)* $crate::json!({$($object)*}),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] , $($rest:tt)*) => {
+        $crate::json!(@array [$($elems,)*] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $next:expr $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($elems,)* $crate::json!($next),] $($($rest)*)?)
+    };
+
+    // Objects: munch key tokens up to the colon, then one value.
+    (@object $object:ident () ()) => {};
+    (@object $object:ident ($($key:tt)+) (: $($rest:tt)*)) => {
+        $crate::json!(@value $object ($($key)+) ($($rest)*))
+    };
+    (@object $object:ident ($($key:tt)*) ($tt:tt $($rest:tt)*)) => {
+        $crate::json!(@object $object ($($key)* $tt) ($($rest)*))
+    };
+    (@value $object:ident ($($key:tt)+) (null $($rest:tt)*)) => {
+        $crate::json!(@insert $object ($($key)+) ($crate::json!(null)) ($($rest)*))
+    };
+    (@value $object:ident ($($key:tt)+) ([$($array:tt)*] $($rest:tt)*)) => {
+        $crate::json!(@insert $object ($($key)+) ($crate::json!([$($array)*])) ($($rest)*))
+    };
+    (@value $object:ident ($($key:tt)+) ({$($map:tt)*} $($rest:tt)*)) => {
+        $crate::json!(@insert $object ($($key)+) ($crate::json!({$($map)*})) ($($rest)*))
+    };
+    (@value $object:ident ($($key:tt)+) ($value:expr $(, $($rest:tt)*)?)) => {
+        $crate::json!(@insert $object ($($key)+) ($crate::json!($value)) (, $($($rest)*)?))
+    };
+    (@insert $object:ident ($($key:tt)+) ($value:expr) ($(, $($rest:tt)*)?)) => {
+        $object.insert(::std::string::String::from($($key)+), $value);
+        $crate::json!(@object $object () ($($($rest)*)?));
+    };
+
+    (null) => {
+        $crate::value::JsonValue::Null
+    };
+    ([]) => {
+        $crate::value::JsonValue::Array(vec![])
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::value::JsonValue::Array($crate::json!(@array [] $($tt)+))
+    };
+    ({}) => {
+        $crate::value::JsonValue::Object($crate::map::JsonMap::new())
+    };
+    ({ $($tt:tt)+ }) => {
+        $crate::value::JsonValue::Object({
+            let mut object = $crate::map::JsonMap::new();
+            $crate::json!(@object object () ($($tt)+));
+            object
+        })
+    };
+    ($other:expr) => {
+        $crate::value::JsonValue::from($other)
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -369,12 +2239,12 @@ mod tests {
     fn test_json_value_variants() {
         let null_val = JsonValue::Null;
         let bool_val = JsonValue::Boolean(true);
-        let num_val = JsonValue::Number(42.5);
+        let num_val = JsonValue::Number(42.5.into());
         let str_val = JsonValue::String("hello".to_string());
 
         assert!(matches!(null_val, JsonValue::Null));
         assert!(matches!(bool_val, JsonValue::Boolean(true)));
-        assert!(matches!(num_val, JsonValue::Number(n) if n == 42.5));
+        assert!(matches!(num_val, JsonValue::Number(ref n) if *n == 42.5));
         assert!(matches!(str_val, JsonValue::String(ref s) if s == "hello"));
     }
 
@@ -382,21 +2252,24 @@ mod tests {
     fn test_json_value_equality() {
         assert_eq!(JsonValue::Null, JsonValue::Null);
         assert_eq!(JsonValue::Boolean(true), JsonValue::Boolean(true));
-        assert_eq!(JsonValue::Number(42.0), JsonValue::Number(42.0));
+        assert_eq!(
+            JsonValue::Number(42.0.into()),
+            JsonValue::Number(42.0.into())
+        );
         assert_eq!(
             JsonValue::String("test".to_string()),
             JsonValue::String("test".to_string())
         );
 
         assert_ne!(JsonValue::Null, JsonValue::Boolean(false));
-        assert_ne!(JsonValue::Number(1.0), JsonValue::Number(2.0));
+        assert_ne!(JsonValue::Number(1.0.into()), JsonValue::Number(2.0.into()));
     }
 
     #[test]
     fn test_is_null() {
         assert!(JsonValue::Null.is_null());
         assert!(!JsonValue::Boolean(true).is_null());
-        assert!(!JsonValue::Number(42.0).is_null());
+        assert!(!JsonValue::Number(42.0.into()).is_null());
         assert!(!JsonValue::String("test".to_string()).is_null());
     }
 
@@ -407,12 +2280,13 @@ mod tests {
 
         assert!(JsonValue::Null.as_str().is_none());
         assert!(JsonValue::Boolean(true).as_str().is_none());
-        assert!(JsonValue::Number(42.0).as_str().is_none());
+        assert!(JsonValue::Number(42.0.into()).as_str().is_none());
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_as_f64() {
-        let num_val = JsonValue::Number(3.14);
+        let num_val = JsonValue::Number(3.14.into());
         assert_eq!(num_val.as_f64(), Some(3.14));
 
         assert!(JsonValue::Null.as_f64().is_none());
@@ -426,7 +2300,7 @@ mod tests {
         assert_eq!(JsonValue::Boolean(false).as_bool(), Some(false));
 
         assert!(JsonValue::Null.as_bool().is_none());
-        assert!(JsonValue::Number(42.0).as_bool().is_none());
+        assert!(JsonValue::Number(42.0.into()).as_bool().is_none());
         assert!(JsonValue::String("test".to_string()).as_bool().is_none());
     }
 
@@ -451,30 +2325,30 @@ mod tests {
     #[test]
     fn test_as_array() {
         let array_val = JsonValue::Array(vec![
-            JsonValue::Number(1.0),
-            JsonValue::Number(2.0),
-            JsonValue::Number(3.0),
+            JsonValue::Number(1.0.into()),
+            JsonValue::Number(2.0.into()),
+            JsonValue::Number(3.0.into()),
         ]);
 
         let arr = array_val.as_array();
         assert!(arr.is_some());
         assert_eq!(arr.unwrap().len(), 3);
-        assert_eq!(arr.unwrap()[0], JsonValue::Number(1.0));
-        assert_eq!(arr.unwrap()[1], JsonValue::Number(2.0));
-        assert_eq!(arr.unwrap()[2], JsonValue::Number(3.0));
+        assert_eq!(arr.unwrap()[0], JsonValue::Number(1.0.into()));
+        assert_eq!(arr.unwrap()[1], JsonValue::Number(2.0.into()));
+        assert_eq!(arr.unwrap()[2], JsonValue::Number(3.0.into()));
 
         // Non-array variants return None
         assert!(JsonValue::Null.as_array().is_none());
         assert!(JsonValue::Boolean(true).as_array().is_none());
-        assert!(JsonValue::Number(42.0).as_array().is_none());
+        assert!(JsonValue::Number(42.0.into()).as_array().is_none());
         assert!(JsonValue::String("test".to_string()).as_array().is_none());
     }
 
     #[test]
     fn test_as_object() {
-        let mut map = HashMap::new();
+        let mut map = JsonMap::new();
         map.insert("name".to_string(), JsonValue::String("Alice".to_string()));
-        map.insert("age".to_string(), JsonValue::Number(30.0));
+        map.insert("age".to_string(), JsonValue::Number(30.0.into()));
         let object_val = JsonValue::Object(map);
 
         let obj = object_val.as_object();
@@ -484,18 +2358,21 @@ mod tests {
             obj.unwrap().get("name"),
             Some(&JsonValue::String("Alice".to_string()))
         );
-        assert_eq!(obj.unwrap().get("age"), Some(&JsonValue::Number(30.0)));
+        assert_eq!(
+            obj.unwrap().get("age"),
+            Some(&JsonValue::Number(30.0.into()))
+        );
 
         // Non-object variants return None
         assert!(JsonValue::Null.as_object().is_none());
         assert!(JsonValue::Boolean(false).as_object().is_none());
-        assert!(JsonValue::Number(1.0).as_object().is_none());
+        assert!(JsonValue::Number(1.0.into()).as_object().is_none());
         assert!(JsonValue::String("test".to_string()).as_object().is_none());
     }
 
     #[test]
     fn test_get() {
-        let mut map = HashMap::new();
+        let mut map = JsonMap::new();
         map.insert("key1".to_string(), JsonValue::String("value1".to_string()));
         map.insert("key2".to_string(), JsonValue::Boolean(true));
         let object_val = JsonValue::Object(map);
@@ -512,9 +2389,9 @@ mod tests {
 
         // Non-object variants return None
         assert_eq!(JsonValue::Null.get("key"), None);
-        assert_eq!(JsonValue::Number(42.0).get("key"), None);
+        assert_eq!(JsonValue::Number(42.0.into()).get("key"), None);
         assert_eq!(
-            JsonValue::Array(vec![JsonValue::Number(1.0)]).get("key"),
+            JsonValue::Array(vec![JsonValue::Number(1.0.into())]).get("key"),
             None
         );
     }
@@ -541,14 +2418,21 @@ mod tests {
 
         // Non-array variants return None
         assert_eq!(JsonValue::Null.get_index(0), None);
-        assert_eq!(JsonValue::Number(42.0).get_index(0), None);
+        assert_eq!(JsonValue::Number(42.0.into()).get_index(0), None);
 
-        let mut map = HashMap::new();
-        map.insert("key".to_string(), JsonValue::Number(1.0));
+        let mut map = JsonMap::new();
+        map.insert("key".to_string(), JsonValue::Number(1.0.into()));
         assert_eq!(JsonValue::Object(map).get_index(0), None);
     }
 
     #[test]
+    // Spells out each method on purpose, so the simpler forms clippy
+    // suggests would defeat the demonstration.
+    #[allow(
+        clippy::nonminimal_bool,
+        clippy::unnecessary_literal_unwrap,
+        clippy::manual_unwrap_or
+    )]
     fn test_option_methods() {
         // Demonstrate Option<T> methods from Week 2 curriculum
         let some_value: Option<i32> = Some(42);
@@ -580,18 +2464,416 @@ mod tests {
     }
 }
 
+#[cfg(test)]
+mod number_tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_large_integers_are_exact() {
+        let value =
+            parse_json("[9007199254740993, -9223372036854775808, 18446744073709551615]").unwrap();
+        assert_eq!(
+            value.get_index(0).and_then(|v| v.as_u64()),
+            Some(9_007_199_254_740_993)
+        );
+        assert_eq!(value.get_index(1).and_then(|v| v.as_i64()), Some(i64::MIN));
+        assert_eq!(value.get_index(2).and_then(|v| v.as_u64()), Some(u64::MAX));
+        assert_eq!(
+            value.to_string(),
+            "[9007199254740993,-9223372036854775808,18446744073709551615]"
+        );
+    }
+
+    #[test]
+    fn test_representation_choice() {
+        let n = |s: &str| match parse_json(s).unwrap() {
+            JsonValue::Number(n) => n,
+            other => panic!("Expected number, got {:?}", other),
+        };
+        assert!(n("42").is_u64() && n("42").is_i64());
+        assert!(n("-42").is_i64() && !n("-42").is_u64());
+        assert!(n("42.0").is_f64());
+        assert!(n("1e2").is_f64());
+        assert!(n("-0").is_f64());
+        // Too large for u64: falls back to f64
+        assert!(n("18446744073709551616").is_f64());
+    }
+
+    #[test]
+    fn test_equality_across_representations() {
+        assert_eq!(JsonNumber::from(42u64), JsonNumber::from(42.0));
+        assert_eq!(JsonNumber::from(-3i64), JsonNumber::from(-3.0));
+        assert_eq!(JsonNumber::from(7i64), JsonNumber::from(7u64));
+        assert_ne!(JsonNumber::from(1u64), JsonNumber::from(1.5));
+        assert_ne!(
+            JsonNumber::from(9_007_199_254_740_993u64),
+            JsonNumber::from(9_007_199_254_740_992.0)
+        );
+        assert_eq!(
+            parse_json("[1, 2]").unwrap(),
+            parse_json("[1.0, 2e0]").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_as_f64_on_integers() {
+        assert_eq!(parse_json("-5").unwrap().as_f64(), Some(-5.0));
+        assert_eq!(
+            JsonValue::Number(JsonNumber::from(3u64)).as_f64(),
+            Some(3.0)
+        );
+        assert_eq!(format!("{:?}", JsonNumber::from(-2i64)), "-2");
+    }
+
+    #[test]
+    fn test_get_as_large_integer() {
+        let value = parse_json(r#"{"id": 18446744073709551615}"#).unwrap();
+        assert_eq!(value.get_as::<u64>("id"), Ok(u64::MAX));
+        assert!(value.get_as::<i64>("id").is_err());
+    }
+}
+
+#[cfg(test)]
+mod debug_tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_debug_matches_enum_shape() {
+        assert_eq!(format!("{:?}", JsonValue::Null), "Null");
+        assert_eq!(
+            format!("{:?}", JsonValue::Number(1.5.into())),
+            "Number(1.5)"
+        );
+        assert_eq!(
+            format!("{:?}", JsonValue::String("a\"b".to_string())),
+            r#"String("a\"b")"#
+        );
+        assert_eq!(
+            format!("{:?}", Some(&JsonValue::Boolean(false))),
+            "Some(Boolean(false))"
+        );
+    }
+
+    #[test]
+    fn test_alternate_debug_is_valid_pretty_json() {
+        let value = parse_json(r#"{"b": {"x": null}, "a": ["s", 2.5]}"#).unwrap();
+        let pretty = format!("{:#?}", value);
+        assert_eq!(
+            pretty,
+            "{\n  \"b\": {\n    \"x\": null\n  },\n  \"a\": [\n    \"s\",\n    2.5\n  ]\n}"
+        );
+        assert_eq!(parse_json(&pretty).unwrap(), value);
+    }
+
+    #[test]
+    fn test_alternate_debug_scalars() {
+        assert_eq!(format!("{:#?}", JsonValue::Null), "null");
+        assert_eq!(
+            format!("{:#?}", JsonValue::String("x".to_string())),
+            "\"x\""
+        );
+    }
+}
+
+#[cfg(test)]
+mod access_tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_get_as_types() {
+        let value =
+            parse_json(r#"{"n": 42, "f": 1.5, "neg": -3, "s": "x", "b": true, "a": [1, 2]}"#)
+                .unwrap();
+        assert_eq!(value.get_as::<i64>("n"), Ok(42));
+        assert_eq!(value.get_as::<u32>("n"), Ok(42));
+        assert_eq!(value.get_as::<f64>("f"), Ok(1.5));
+        assert_eq!(value.get_as::<String>("s"), Ok("x".to_string()));
+        assert_eq!(value.get_as::<bool>("b"), Ok(true));
+        assert_eq!(value.get_as::<Vec<i32>>("a"), Ok(vec![1, 2]));
+        assert_eq!(value.get_as::<i64>("neg"), Ok(-3));
+    }
+
+    #[test]
+    fn test_get_as_integer_rejects_fraction_and_range() {
+        let value = parse_json(r#"{"f": 1.5, "neg": -3}"#).unwrap();
+        assert_eq!(
+            value.get_as::<i64>("f"),
+            Err(AccessError::TypeMismatch {
+                path: JsonPath::parse_dotted("f"),
+                expected: "integer",
+                found: "number",
+            })
+        );
+        assert!(value.get_as::<u64>("neg").is_err());
+    }
+
+    #[test]
+    fn test_get_as_integer_bounds() {
+        let value = parse_json(
+            r#"{"u": 18446744073709551615, "over": 18446744073709551616,
+                "min": -9223372036854775808, "under": -9223372036854775809, "whole": 7.0}"#,
+        )
+        .unwrap();
+        assert_eq!(value.get_as::<u64>("u"), Ok(u64::MAX));
+        assert!(value.get_as::<u64>("over").is_err());
+        assert_eq!(value.get_as::<i64>("min"), Ok(i64::MIN));
+        assert!(value.get_as::<i64>("under").is_err());
+        assert!(u64::from_json(&value["over"]).is_err());
+        assert!(value.get_as::<u8>("whole").is_err());
+        assert_eq!(value["whole"].as_u8(), None);
+    }
+
+    #[test]
+    fn test_get_as_missing() {
+        let value = parse_json(r#"{"a": 1}"#).unwrap();
+        assert_eq!(
+            value.get_as::<i64>("b"),
+            Err(AccessError::Missing {
+                path: JsonPath::parse_dotted("b")
+            })
+        );
+        assert!(JsonValue::Null.get_as::<i64>("a").is_err());
+    }
+
+    #[test]
+    fn test_keys_and_values() {
+        let value = parse_json(r#"{"x": true, "y": null}"#).unwrap();
+        let mut keys: Vec<&String> = value.keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["x", "y"]);
+        assert_eq!(value.values().filter(|v| v.is_null()).count(), 1);
+
+        for other in [JsonValue::Null, JsonValue::Array(vec![JsonValue::Null])] {
+            assert_eq!(other.keys().count(), 0);
+            assert_eq!(other.values().count(), 0);
+        }
+    }
+
+    #[test]
+    fn test_chunks_and_windows() {
+        let value = parse_json("[1, 2, 3, 4, 5, 6, 7]").unwrap();
+        let chunks: Vec<usize> = value.chunks(3).map(<[_]>::len).collect();
+        assert_eq!(chunks, vec![3, 3, 1]);
+        assert_eq!(value.chunks(10).count(), 1);
+        assert_eq!(value.windows(7).count(), 1);
+        assert_eq!(value.windows(8).count(), 0);
+        let first = value.windows(3).next().unwrap();
+        assert_eq!(first[2], JsonValue::Number(3.0.into()));
+
+        assert_eq!(JsonValue::Null.chunks(2).count(), 0);
+        assert_eq!(JsonValue::String("ab".to_string()).windows(1).count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_chunks_zero_panics() {
+        let _ = JsonValue::Array(vec![JsonValue::Null]).chunks(0);
+    }
+
+    #[test]
+    fn test_iter_sorted() {
+        let value = parse_json(r#"{"z": 0, "A": 1, "a": 2, "": 3}"#).unwrap();
+        let pairs: Vec<(&str, f64)> = value
+            .iter_sorted()
+            .map(|(k, v)| (k.as_str(), v.as_f64().unwrap()))
+            .collect();
+        assert_eq!(pairs, vec![("", 3.0), ("A", 1.0), ("a", 2.0), ("z", 0.0)]);
+        assert_eq!(JsonValue::Array(vec![]).iter_sorted().count(), 0);
+    }
+
+    #[test]
+    fn test_sort_keys_keeps_lookups_working() {
+        let members: Vec<String> = (0..12)
+            .rev()
+            .map(|i| format!(r#""k{:02}": {}"#, i, i))
+            .collect();
+        let mut value = parse_json(&format!("[{{{}}}]", members.join(","))).unwrap();
+        value.sort_keys();
+        let object = value.get_index(0).unwrap();
+        let keys: Vec<&String> = object.keys().collect();
+        assert_eq!(keys.first().map(|k| k.as_str()), Some("k00"));
+        assert_eq!(keys.last().map(|k| k.as_str()), Some("k11"));
+        for i in 0..12 {
+            assert_eq!(object[format!("k{:02}", i).as_str()].as_i64(), Some(i));
+        }
+    }
+
+    #[test]
+    fn test_extract() {
+        let mut doc = parse_json(r#"{"a": {"b/c": [1, {"d": true}]}, "e": 2}"#).unwrap();
+        assert_eq!(doc.extract("/a/b~1c/1/d"), Some(JsonValue::Boolean(true)));
+        assert_eq!(
+            doc.get("a")
+                .and_then(|a| a.get("b/c"))
+                .and_then(|v| v.get_index(1)),
+            Some(&JsonValue::Object(JsonMap::new()))
+        );
+        assert_eq!(doc.extract("/a/b~1c/9"), None);
+        assert_eq!(doc.extract("/a/b~1c/01"), None);
+        assert_eq!(doc.extract("/e/x"), None);
+        assert_eq!(doc.extract("e"), None);
+        assert_eq!(doc.extract("/e"), Some(JsonValue::Number(2.0.into())));
+        assert_eq!(doc.extract("/e"), None);
+
+        let whole = doc.extract("").unwrap();
+        assert!(whole.get("a").is_some());
+        assert!(doc.is_null());
+    }
+
+    #[test]
+    fn test_checked_integer_conversions() {
+        let value = parse_json("[255, 65536, -1, 1e2, 100000000000000000000, null]").unwrap();
+        let item = |i| value.get_index(i).unwrap();
+        assert_eq!(item(0).as_u8(), Some(255));
+        assert_eq!(item(0).as_u16(), Some(255));
+        assert_eq!(item(1).as_u16(), None);
+        assert_eq!(item(1).as_u32(), Some(65536));
+        assert_eq!(item(2).as_u32(), None);
+        assert_eq!(item(2).as_i32(), Some(-1));
+        assert_eq!(item(3).as_i32(), None);
+        assert_eq!(
+            item(2).try_into_int::<u8>(),
+            Err(IntConversionError::OutOfRange {
+                value: "-1".to_string(),
+                target: "u8",
+            })
+        );
+        assert_eq!(
+            item(3).try_into_int::<i64>(),
+            Err(IntConversionError::NotAnInteger {
+                value: "100".to_string(),
+            })
+        );
+        assert!(matches!(
+            item(4).try_into_int::<u64>(),
+            Err(IntConversionError::OutOfRange { target: "u64", .. })
+        ));
+        assert_eq!(
+            item(5).try_into_int::<i32>(),
+            Err(IntConversionError::NotANumber { found: "null" })
+        );
+        assert_eq!(item(1).try_into_int::<i128>(), Ok(65536));
+    }
+
+    #[test]
+    fn test_pointer() {
+        let mut doc = parse_json(r#"{"": 0, "a": {"~/": [1, {"-": true}]}, "7": 7}"#).unwrap();
+        assert_eq!(doc.pointer("/"), Some(&JsonValue::Number(0.0.into())));
+        assert_eq!(doc.pointer("/a/~0~1/1/-"), Some(&JsonValue::Boolean(true)));
+        assert_eq!(doc.pointer("/7").and_then(|v| v.as_i64()), Some(7));
+        assert_eq!(doc.pointer("/a/~0~1/-"), None);
+        assert_eq!(doc.pointer("/a/~0~1/2"), None);
+        assert_eq!(doc.pointer("/a/~0~1/0/x"), None);
+        assert_eq!(doc.pointer("a"), None);
+
+        *doc.pointer_mut("/a/~0~1/0").unwrap() = JsonValue::Null;
+        assert_eq!(doc.pointer("/a/~0~1/0"), Some(&JsonValue::Null));
+        assert!(doc.pointer_mut("/a/~0~1/01").is_none());
+        assert!(doc.pointer_mut("").is_some());
+    }
+
+    #[test]
+    fn test_get_many_matches_pointer() {
+        let doc = parse_json(r#"{"a": {"b": [1, {"c": 2}], "d": null}, "a~": 3}"#).unwrap();
+        let pointers = [
+            "/a/b/1/c", "/a/b/0", "/a/b/1/x", "/a/d", "/a/b/1/c", "", "/a~0", "a", "/a/b/01",
+            "/a/b/0/z",
+        ];
+        let expected: Vec<_> = pointers.iter().map(|p| doc.pointer(p)).collect();
+        assert_eq!(doc.get_many(&pointers), expected);
+        assert_eq!(expected.iter().filter(|v| v.is_some()).count(), 6);
+        assert!(doc.get_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_get_path_as_reports_failing_prefix() {
+        let value = parse_json(r#"{"user": {"items": [{"id": 7}]}}"#).unwrap();
+        assert_eq!(value.get_path_as::<i64>("user.items.0.id"), Ok(7));
+        assert_eq!(
+            value.get_path_as::<i64>("user.items.3.id"),
+            Err(AccessError::Missing {
+                path: JsonPath::parse_dotted("user.items.3")
+            })
+        );
+        assert_eq!(
+            value.get_path_as::<i64>("user.nope.id"),
+            Err(AccessError::Missing {
+                path: JsonPath::parse_dotted("user.nope")
+            })
+        );
+        assert_eq!(
+            value.get_path_as::<String>("user.items"),
+            Err(AccessError::TypeMismatch {
+                path: JsonPath::parse_dotted("user.items"),
+                expected: "string",
+                found: "array",
+            })
+        );
+    }
+}
+
 #[cfg(test)]
 mod display_tests {
     use super::*;
     use crate::parser::parse_json;
 
     #[test]
+    fn test_to_writer_matches_string_forms() {
+        let value = parse_json(
+            r#"{"a": [1, 2.5, {"b": "q\"\\\n"}], "e": [], "o": {}, "n": null, "t": true}"#,
+        )
+        .unwrap();
+        let mut compact = Vec::new();
+        value.to_writer(&mut compact).unwrap();
+        assert_eq!(String::from_utf8(compact).unwrap(), value.to_string());
+        let mut pretty = Vec::new();
+        value.to_writer_pretty(&mut pretty).unwrap();
+        assert_eq!(String::from_utf8(pretty).unwrap(), format!("{:#?}", value));
+
+        let mut full = [0u8; 4];
+        assert!(value.to_writer(&mut full[..]).is_err());
+    }
+
+    #[test]
+    fn test_to_string_bounded() {
+        let value = parse_json(r#"{"a": [1, 2, 3], "s": "x\"yz"}"#).unwrap();
+        let full = value.to_string();
+        for max in 0..full.len() {
+            let (text, truncated) = value.to_string_bounded(max);
+            assert!(truncated);
+            assert!(text.len() <= max, "{:?} exceeds {}", text, max);
+            let kept = text.strip_suffix("...").unwrap_or(&text);
+            assert!(full.starts_with(kept));
+        }
+        assert_eq!(value.to_string_bounded(full.len()), (full, false));
+
+        // Cuts between tokens and never splits an escape or a character.
+        assert_eq!(value.to_string_bounded(12).0, r#"{"a":[1,2..."#);
+        assert_eq!(value.to_string_bounded(23).0, r#"{"a":[1,2,3],"s":"x..."#);
+        assert_eq!(value.to_string_bounded(2).0, "");
+        let accented = parse_json(r#"["éé"]"#).unwrap();
+        assert_eq!(accented.to_string_bounded(6).0, r#"[""#.to_string() + "...");
+        let control = JsonValue::from("ab\u{1}");
+        for max in 6..=9 {
+            assert_eq!(control.to_string_bounded(max).0, r#""ab..."#);
+        }
+        assert_eq!(control.to_string_bounded(10).0, r#""ab\u0001""#);
+        let backslashes = JsonValue::from("\\\\x");
+        assert_eq!(backslashes.to_string_bounded(5).0, r#""..."#);
+        assert_eq!(backslashes.to_string_bounded(6).0, r#""\\..."#);
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
     fn test_display_primitives() {
         assert_eq!(JsonValue::Null.to_string(), "null");
         assert_eq!(JsonValue::Boolean(true).to_string(), "true");
         assert_eq!(JsonValue::Boolean(false).to_string(), "false");
-        assert_eq!(JsonValue::Number(42.0).to_string(), "42");
-        assert_eq!(JsonValue::Number(3.14).to_string(), "3.14");
+        assert_eq!(JsonValue::Number(42.0.into()).to_string(), "42");
+        assert_eq!(JsonValue::Number(3.14.into()).to_string(), "3.14");
         assert_eq!(
             JsonValue::String("hello".to_string()).to_string(),
             "\"hello\""
@@ -600,14 +2882,17 @@ mod display_tests {
 
     #[test]
     fn test_display_array() {
-        let array = JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Number(2.0)]);
+        let array = JsonValue::Array(vec![
+            JsonValue::Number(1.0.into()),
+            JsonValue::Number(2.0.into()),
+        ]);
         assert_eq!(array.to_string(), "[1,2]");
     }
 
     #[test]
     fn test_display_empty_containers() {
         assert_eq!(JsonValue::Array(vec![]).to_string(), "[]");
-        assert_eq!(JsonValue::Object(HashMap::new()).to_string(), "{}");
+        assert_eq!(JsonValue::Object(JsonMap::new()).to_string(), "{}");
     }
 
     #[test]
@@ -616,6 +2901,27 @@ mod display_tests {
         assert_eq!(value.to_string(), "\"hello\\nworld\"");
     }
 
+    #[test]
+    fn test_display_escapes_control_characters() {
+        let all: String = (0..0x20u8).map(char::from).chain(['\u{7f}', 'é']).collect();
+        let value = JsonValue::String(all.clone());
+        let text = value.to_string();
+        assert!(text.starts_with(r#""\u0000\u0001"#));
+        assert!(text.contains(r#"\u0007\b\t\n\u000b\f\r\u000e"#));
+        assert!(text.ends_with("\\u001f\u{7f}é\""));
+        assert!(!text.chars().any(|c| c < ' '));
+        assert_eq!(parse_json(&text).unwrap(), value);
+
+        let mut map = JsonMap::new();
+        map.insert(all, value);
+        let object = JsonValue::Object(map);
+        assert_eq!(parse_json(&object.to_string()).unwrap(), object);
+        assert_eq!(parse_json(&format!("{:#?}", object)).unwrap(), object);
+        let mut out = Vec::new();
+        object.to_writer(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), object.to_string());
+    }
+
     #[test]
     fn test_display_escape_quotes() {
         let value = JsonValue::String("say \"hi\"".to_string());
@@ -632,7 +2938,7 @@ mod display_tests {
 
     #[test]
     fn test_display_object_key_with_quotes() {
-        let mut map = HashMap::new();
+        let mut map = JsonMap::new();
         map.insert(
             "say \"hi\"".to_string(),
             JsonValue::String("value".to_string()),
@@ -644,8 +2950,8 @@ mod display_tests {
 
     #[test]
     fn test_display_object_key_with_newline() {
-        let mut map = HashMap::new();
-        map.insert("line1\nline2".to_string(), JsonValue::Number(1.0));
+        let mut map = JsonMap::new();
+        map.insert("line1\nline2".to_string(), JsonValue::Number(1.0.into()));
         let obj = JsonValue::Object(map);
         let output = obj.to_string();
         assert!(output.contains("\"line1\\nline2\""));
@@ -653,10 +2959,174 @@ mod display_tests {
 
     #[test]
     fn test_display_object_key_with_backslash() {
-        let mut map = HashMap::new();
+        let mut map = JsonMap::new();
         map.insert("path\\to".to_string(), JsonValue::Boolean(true));
         let obj = JsonValue::Object(map);
         let output = obj.to_string();
         assert!(output.contains("\"path\\\\to\""));
     }
 }
+
+#[cfg(test)]
+mod mutation_tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_nested_edit_through_get_mut() {
+        let mut value = parse_json(r#"{"users": [{"name": "a"}, {"name": "b"}]}"#).unwrap();
+        let user = value
+            .get_mut("users")
+            .and_then(|users| users.get_index_mut(1))
+            .unwrap();
+        user.insert("admin", JsonValue::Boolean(true)).unwrap();
+        assert_eq!(
+            user.remove("name"),
+            Some(JsonValue::String("b".to_string()))
+        );
+        assert_eq!(
+            value.to_string(),
+            r#"{"users":[{"name":"a"},{"admin":true}]}"#
+        );
+    }
+
+    #[test]
+    fn test_index_mut_inserts_members() {
+        let mut value = parse_json(r#"{"a": [1, 2]}"#).unwrap();
+        value["a"][1] = JsonValue::Boolean(false);
+        value["b"]["c"] = 3.into();
+        assert_eq!(value.to_string(), r#"{"a":[1,false],"b":{"c":3}}"#);
+        assert!(value["a"]["x"].is_null());
+        assert!(value["b"][0].is_null());
+    }
+
+    #[test]
+    #[should_panic(expected = "index 2 out of bounds for array of length 2")]
+    fn test_index_mut_out_of_bounds_panics() {
+        let mut value = parse_json("[1, 2]").unwrap();
+        value[2] = JsonValue::Null;
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot index number with \"a\"")]
+    fn test_index_mut_on_scalar_panics() {
+        let mut value = parse_json("1").unwrap();
+        value["a"] = JsonValue::Null;
+    }
+
+    #[test]
+    fn test_wrong_type_is_reported() {
+        let mut value = JsonValue::Boolean(true);
+        assert_eq!(
+            value.push(JsonValue::Null),
+            Err(AccessError::TypeMismatch {
+                path: JsonPath::root(),
+                expected: "array",
+                found: "boolean",
+            })
+        );
+        assert!(value.insert("k", JsonValue::Null).is_err());
+        assert_eq!(value.remove("k"), None);
+        assert_eq!(value.pop(), None);
+        assert!(value.get_mut("k").is_none());
+        assert!(value.as_array_mut().is_none());
+        assert!(value.as_object_mut().is_none());
+    }
+
+    #[test]
+    fn test_index_bounds() {
+        let mut value = parse_json("[1, 2]").unwrap();
+        assert_eq!(
+            value.insert_index(3, JsonValue::Null),
+            Err(AccessError::Missing {
+                path: JsonPath::root().join(3),
+            })
+        );
+        value.insert_index(2, JsonValue::Null).unwrap();
+        assert_eq!(value.remove_index(5), None);
+        assert_eq!(value.to_string(), "[1,2,null]");
+        assert!(value.get_index_mut(3).is_none());
+    }
+
+    #[test]
+    fn test_insert_replaces_existing_key() {
+        let mut value = parse_json(r#"{"a": 1, "b": 2}"#).unwrap();
+        let previous = value.insert("a".to_string(), JsonValue::Null).unwrap();
+        assert_eq!(previous, Some(JsonValue::Number(1.0.into())));
+        assert_eq!(value.to_string(), r#"{"a":null,"b":2}"#);
+    }
+}
+
+#[cfg(test)]
+mod macro_tests {
+    use super::*;
+    use crate::parser::parse_json;
+
+    #[test]
+    fn test_json_macro_matches_parsed_document() {
+        let id: u64 = 9007199254740993;
+        let value = json!({
+            "id": id,
+            "name": "Alice",
+            "tags": [1, -2, 3.5, null, [], {}],
+            "nested": { "ok": true, "missing": null },
+        });
+        let expected = parse_json(
+            r#"{"id": 9007199254740993, "name": "Alice", "tags": [1, -2, 3.5, null, [], {}],
+                "nested": {"ok": true, "missing": null}}"#,
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+        assert_eq!(value.as_object().unwrap().keys().next().unwrap(), "id");
+    }
+
+    #[test]
+    fn test_from_rust_values() {
+        let scores = HashMap::from([("bob".to_string(), 2), ("alice".to_string(), 1)]);
+        assert_eq!(
+            JsonValue::from(scores).to_string(),
+            r#"{"alice":1,"bob":2}"#
+        );
+        let nested = BTreeMap::from([("b".to_string(), vec![true]), ("a".to_string(), vec![])]);
+        assert_eq!(JsonValue::from(nested), json!({"a": [], "b": [true]}));
+        let values: Vec<JsonValue> = vec![
+            "s".into(),
+            String::from("t").into(),
+            false.into(),
+            (-3i64).into(),
+            0.5f32.into(),
+            Some(1u8).into(),
+        ];
+        assert_eq!(
+            JsonValue::from(values).to_string(),
+            r#"["s","t",false,-3,0.5,1]"#
+        );
+    }
+
+    #[test]
+    fn test_json_macro_interpolates_expressions() {
+        let key = String::from("dynamic");
+        let inner = json!([1, 2]);
+        let maybe: Option<&str> = None;
+        let value = json!({
+            (key.clone()): inner.clone(),
+            "sum": 1 + 2,
+            "maybe": maybe,
+            "text": format!("{}-{}", "a", 1),
+        });
+        assert_eq!(value.get("dynamic"), Some(&inner));
+        assert_eq!(value.get("sum").and_then(|v| v.as_i64()), Some(3));
+        assert_eq!(value.get("maybe"), Some(&JsonValue::Null));
+        assert_eq!(value.get("text").and_then(|v| v.as_str()), Some("a-1"));
+    }
+
+    #[test]
+    fn test_json_macro_scalars() {
+        assert_eq!(json!(null), JsonValue::Null);
+        assert_eq!(json!(true), JsonValue::Boolean(true));
+        assert_eq!(json!("hi"), JsonValue::String("hi".to_string()));
+        assert_eq!(json!(-7).as_i64(), Some(-7));
+        assert_eq!(json!([]), JsonValue::Array(vec![]));
+        assert_eq!(json!({}), JsonValue::Object(JsonMap::new()));
+    }
+}